@@ -0,0 +1,179 @@
+//! 結果表之基準：物化、繪製與列寬。以合成之解代替存儲，故不受查詢引擎影響。
+//! 另量查詢之解析，與緩存命中時之複製相比，以知緩存已解析之查詢是否值得；
+//! 及於長查詢之末打字之耗時，千行者須與十行者相若。
+//!
+//! ```sh
+//! cargo bench --bench results            # 全部
+//! cargo bench --bench results -- render  # 名含 render 者
+//! ```
+//!
+//! 每項至少跑三次且累計一秒，報最小與中位之耗時。
+
+use oxigraph::{
+    model::{Term, Variable},
+    sparql::{Query, QuerySolution},
+};
+use ratatui::{backend::TestBackend, Terminal};
+use rdf_tui::{fixture, Action, App, Config, ResultTable};
+use std::{
+    convert::Infallible,
+    hint::black_box,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+const SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+const QUERY: &str = "SELECT ?s ?p ?o WHERE { ?s ?p ?o }";
+/// 合成查詢之 UNION 分支數。
+const BRANCHES: [usize; 3] = [1, 100, 1_000];
+/// 打字所用查詢之行數。
+const TYPING_LINES: [usize; 2] = [10, 1_000];
+
+fn main() {
+    // cargo bench 另傳 `--bench`，略之
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let selected = |name: &str| filter.as_deref().is_none_or(|f| name.contains(f));
+
+    for branches in BRANCHES {
+        let text = union(branches);
+        let name = format!("prepare/parse/{branches}");
+        if selected(&name) {
+            bench(&name, || Query::parse(&text, None).unwrap());
+        }
+        let query = Query::parse(&text, None).unwrap();
+        let name = format!("prepare/clone/{branches}");
+        if selected(&name) {
+            bench(&name, || query.clone());
+        }
+    }
+
+    // 每鍵打一字並繪製，再刪之，故查詢不增長
+    let mut typing = vec![];
+    for lines in TYPING_LINES {
+        let name = format!("typing/{lines}");
+        if !selected(&name) {
+            continue;
+        }
+        let mut config = Config::default();
+        config.autorun = false;
+        let mut app = App::new().unwrap();
+        app.configure(config);
+        app.set_query(long_query(lines));
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        typing.push(bench(&name, || {
+            app.handle_action(Action::InsertChar('x')).unwrap();
+            app.draw(&mut terminal).unwrap();
+            app.handle_action(Action::DeleteBackward).unwrap();
+            app.draw(&mut terminal).unwrap();
+        }));
+    }
+    if let [short, long] = typing[..] {
+        assert!(
+            long < short * 3,
+            "typing into {} lines takes {long:?}, into {} lines {short:?}",
+            TYPING_LINES[1],
+            TYPING_LINES[0]
+        );
+    }
+
+    for size in SIZES {
+        let (variables, rows) = synthesize(size);
+
+        let name = format!("materialize/{size}");
+        if selected(&name) {
+            bench(&name, || table(&variables, &rows));
+        }
+
+        let mut app = App::new().unwrap();
+        app.handle_action(Action::SwitchMode).unwrap();
+        app.show_table(QUERY, table(&variables, &rows));
+
+        let name = format!("column_widths/{size}");
+        if selected(&name) {
+            bench(&name, || app.column_widths());
+        }
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let mut position = 0;
+        for offset in [0, size / 2, size - 1] {
+            let name = format!("render/{size}@{offset}");
+            app.handle_action(Action::ScrollRows(offset as isize - position as isize))
+                .unwrap();
+            position = offset;
+            if selected(&name) {
+                bench(&name, || app.draw(&mut terminal).unwrap());
+            }
+        }
+    }
+}
+
+/// 合成 `size` 行 `?s ?p ?o`：取自 [`fixture`]，主語多重複，賓語雜以 IRI、帶語言之字面量、
+/// 數與字串。
+fn synthesize(size: usize) -> (Arc<[Variable]>, Vec<Vec<Option<Term>>>) {
+    let variables: Arc<[Variable]> = ["s", "p", "o"].map(Variable::new_unchecked).into();
+    // 每實例八三元組：類型、標籤、四屬性、二鏈
+    let spec = fixture::Spec {
+        classes: 10,
+        instances: size.div_ceil(70),
+        properties: 4,
+        links: 2,
+        ..fixture::Spec::default()
+    };
+    let rows = spec
+        .quads(0)
+        .take(size)
+        .map(|quad| {
+            vec![
+                Some(Term::from(quad.subject)),
+                Some(Term::from(quad.predicate)),
+                Some(quad.object),
+            ]
+        })
+        .collect();
+    (variables, rows)
+}
+
+/// 合成 `branches` 支 UNION 之查詢，每支一過濾之模式，如監視中之大查詢。
+fn union(branches: usize) -> String {
+    let branches: Vec<String> = (0..branches)
+        .map(|n| {
+            format!("{{ ?s <http://example.org/property/{n}> ?o FILTER(?o != \"note {n}\"@en) }}")
+        })
+        .collect();
+    format!("SELECT ?s ?o WHERE {{ {} }}", branches.join(" UNION "))
+}
+
+/// 合成 `lines` 行之查詢，如生成之長查詢，末行待續。
+fn long_query(lines: usize) -> String {
+    let patterns: Vec<String> = (1..lines)
+        .map(|n| format!("  OPTIONAL {{ ?s <http://example.org/property/{n}> ?o{n} }}"))
+        .collect();
+    format!("SELECT * WHERE {{ ?s ?p ?o\n{}\n", patterns.join("\n"))
+}
+
+/// 如同查詢引擎逐個產出解，物化為表。
+fn table(variables: &Arc<[Variable]>, rows: &[Vec<Option<Term>>]) -> ResultTable {
+    let solutions = rows
+        .iter()
+        .map(|row| Ok::<_, Infallible>(QuerySolution::from((Arc::clone(variables), row.clone()))));
+    ResultTable::from_solutions(variables.to_vec(), solutions, None).unwrap()
+}
+
+/// 量 `f` 之耗時，報之並返回中位數。
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+    let mut times = vec![];
+    let start = Instant::now();
+    while times.len() < 3 || start.elapsed() < Duration::from_secs(1) {
+        let begin = Instant::now();
+        black_box(f());
+        times.push(begin.elapsed());
+    }
+    times.sort();
+    println!(
+        "{name:<28} min {:>12.3?}  median {:>12.3?}  ({} runs)",
+        times[0],
+        times[times.len() / 2],
+        times.len()
+    );
+    times[times.len() / 2]
+}
@@ -0,0 +1,68 @@
+//! 將 rdf-tui 嵌入他應用：左為宿主之側欄，右為瀏覽器。
+//!
+//! ```sh
+//! cargo run --example embedded -- data.ttl
+//! ```
+//!
+//! F10 或瀏覽器中退出則結束。
+
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout},
+    widgets::{Block, Paragraph},
+    Terminal,
+};
+use rdf_tui::{Action, App};
+use std::{io::stdout, path::PathBuf, time::Duration};
+
+fn main() -> anyhow::Result<()> {
+    let mut app = App::new()?;
+    for path in std::env::args().skip(1) {
+        app.handle_action(Action::Open(PathBuf::from(path)))?;
+    }
+
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    let result = run(&mut terminal, &mut app);
+    execute!(stdout(), LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+    result
+}
+
+fn run(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    app: &mut App,
+) -> anyhow::Result<()> {
+    while !app.should_quit() {
+        terminal.draw(|frame| {
+            let [sidebar, main] = Layout::horizontal([Constraint::Length(24), Constraint::Fill(1)])
+                .areas(frame.size());
+            let query = app.query().as_str().to_string();
+            frame.render_widget(
+                Paragraph::new(format!("Mode: {:?}\n\n{query}\n\nF10 exits", app.mode()))
+                    .wrap(ratatui::widgets::Wrap { trim: false })
+                    .block(Block::bordered().title("Workbench")),
+                sidebar,
+            );
+            app.render(frame, main);
+        })?;
+        if !event::poll(Duration::from_millis(50))? {
+            continue;
+        }
+        let event = event::read()?;
+        // 宿主先取己之按鍵，餘者轉交
+        if let Event::Key(key) = &event {
+            if key.code == KeyCode::F(10) {
+                break;
+            }
+        }
+        app.handle_event(&event)?;
+    }
+    Ok(())
+}
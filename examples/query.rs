@@ -0,0 +1,59 @@
+//! 不開界面，以庫執行查詢，逐個印其解於自定之格式：每解一段，每變量一行。
+//!
+//! ```sh
+//! cargo run --example query -- data.ttl 'SELECT ?s ?o WHERE { ?s a ?o }'
+//! ```
+//!
+//! 未給查詢則列十個三元組。至多印百解，十秒不畢則止。
+
+use oxigraph::io::RdfFormat;
+use rdf_tui::{App, QueryOptions};
+use std::{fs, path::Path, time::Duration};
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        anyhow::bail!("usage: query <data.ttl> [<query>]");
+    };
+    let sparql = args
+        .next()
+        .unwrap_or_else(|| "SELECT * WHERE { ?s ?p ?o } LIMIT 10".to_string());
+
+    let mut app = App::new()?;
+    let format = Path::new(&path)
+        .extension()
+        .and_then(|extension| RdfFormat::from_extension(&extension.to_string_lossy()))
+        .unwrap_or(RdfFormat::Turtle);
+    app.load_data(fs::read_to_string(&path)?, format);
+
+    let options = QueryOptions {
+        timeout: Some(Duration::from_secs(10)),
+        limit: Some(100),
+        ..QueryOptions::default()
+    };
+    let mut solutions = app.query_iter(&sparql, options)?;
+    let Some(variables) = solutions.variables().map(<[_]>::to_vec) else {
+        anyhow::bail!("only SELECT queries have solutions to print");
+    };
+    let width = variables
+        .iter()
+        .map(|variable| variable.as_str().len())
+        .max()
+        .unwrap_or(0);
+    let mut count = 0;
+    for solution in solutions.by_ref() {
+        count += 1;
+        println!("# {count}");
+        let solution = solution?;
+        for variable in &variables {
+            let value = solution
+                .get(variable)
+                .map_or_else(|| "unbound".to_string(), ToString::to_string);
+            println!("  ?{:<width$} {value}", variable.as_str());
+        }
+    }
+    if solutions.is_truncated() {
+        println!("(stopped after {count} solutions)");
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! 為一數據類型註冊自定之單元格顯示：化學式之數字示為下標，如 `H₂O`。
+//!
+//! ```sh
+//! cargo run --example renderer -- data.ttl
+//! ```
+//!
+//! 未給文件則載入一小例。`q` 退出。
+
+use oxigraph::model::Term;
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    style::{Modifier, Style},
+    text::Span,
+    Terminal,
+};
+use rdf_tui::{app::Exit, Action, App, CellRenderer};
+use std::{fs, io::stdout, path::PathBuf};
+
+const FORMULA: &str = "http://example.org/chem#formula";
+
+const SAMPLE: &str = r#"@prefix chem: <http://example.org/chem#> .
+<http://example.org/water> chem:formula "H2O"^^chem:formula .
+<http://example.org/glucose> chem:formula "C6H12O6"^^chem:formula .
+<http://example.org/sulfate> chem:formula "SO4 2-"^^chem:formula .
+"#;
+
+/// 化學式中元素後之數字為下標。含空格者不識，交回默認之顯示。
+struct Formula;
+
+impl CellRenderer for Formula {
+    fn render(&self, term: &Term) -> Option<Vec<Span<'static>>> {
+        let Term::Literal(literal) = term else {
+            return None;
+        };
+        if literal.value().contains(' ') {
+            return None;
+        }
+        let formula: String = literal
+            .value()
+            .chars()
+            .map(|ch| match ch.to_digit(10) {
+                Some(digit) => "₀₁₂₃₄₅₆₇₈₉".chars().nth(digit as usize).unwrap_or(ch),
+                None => ch,
+            })
+            .collect();
+        Some(vec![Span::styled(
+            formula,
+            Style::new().add_modifier(Modifier::BOLD),
+        )])
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut app = App::new()?;
+    app.register_renderer(FORMULA, Formula);
+    let mut paths: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() {
+        let sample = std::env::temp_dir().join("rdf-tui-formulas.ttl");
+        fs::write(&sample, SAMPLE)?;
+        paths.push(sample);
+    }
+    for path in paths {
+        app.handle_action(Action::Open(path))?;
+    }
+    app.set_query("SELECT ?s ?formula { ?s ?p ?formula }");
+    app.run_on_start();
+
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    // 掛起之請求不理，再入循環
+    let result = loop {
+        match app.run(&mut terminal) {
+            Ok(Exit::Suspend) => continue,
+            result => break result.map(drop),
+        }
+    };
+    execute!(stdout(), LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+    result
+}
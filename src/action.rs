@@ -0,0 +1,752 @@
+use crate::{bookmarks::View, dataset::Dataset, incoming::Incoming, sink::Sink, yank::Yank};
+use oxigraph::{io::RdfFormat, model::GraphName, sparql::results::QueryResultsFormat};
+use std::{path::PathBuf, time::Duration};
+
+/// 應用之操作。按鍵與命令行皆轉為操作，經 `App::update` 執行，以免兩者行為相異。
+/// 狀態之變更皆經此，故可不經終端而以操作序列測試之。
+///
+/// 嵌入者可經 [`App::handle_action`](crate::app::App::handle_action) 直接執行。
+/// 日後或增操作，故匹配時須留通配分支。
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Action {
+    /// 退出。查詢有未保存之修改則先確認。
+    Quit,
+    /// 不加確認而退出。
+    ForceQuit,
+    /// 前後換標籤頁若干頁，負為向前，首末相接。
+    CycleTab(isize),
+    /// 開新標籤頁，有路徑則載入之於其中。
+    NewTab(Option<PathBuf>),
+    /// 關當前之標籤頁，釋其存儲。查詢有未保存之修改則先確認。
+    CloseTab,
+    /// 不加確認而關當前之標籤頁。
+    ForceCloseTab,
+    /// 於各標籤頁並行當前之查詢，浮窗比較其行數與耗時。
+    RunInTabs,
+    /// 再示上次各頁之比較。
+    ShowTabRuns,
+    /// 換至比較中所選之頁，示其結果。
+    OpenTabRun,
+    /// 記比較中所選之頁為舊者；已記則逐行比較兩頁之結果。
+    CompareTabRun,
+    /// 複製各頁之比較為 Markdown。
+    YankTabRuns,
+    /// 寫各頁之比較為 Markdown 文件。
+    ExportTabRuns(PathBuf),
+    SwitchMode,
+    /// 所選行上下移動若干行，負為向上。至首末而止。
+    ScrollRows(isize),
+    /// 結果表中所選列左右移動若干列，負為向左。未選則自首列或末列起。
+    ScrollColumns(isize),
+    SelectFirst,
+    SelectLast,
+    /// 確認所選：展開層級節點、跳至書籤、執行命令行。
+    Activate,
+    /// 返回瀏覽模式。
+    Back,
+    /// 刪除所選書籤。
+    Delete,
+    /// 層級樹換關係。
+    CycleRelation,
+    /// 列出所選類之實例。
+    ListInstances,
+    /// 列出所選資源之詳情。
+    ShowDetails,
+    /// 補全命令行，或模式之前綴名。
+    Complete,
+    /// 列補全之候選於浮窗。
+    ListCompletions,
+    /// 三元組模式之表單中，移至後或前若干欄。
+    CycleField(isize),
+    /// 刪除光標前之字符。
+    DeleteBackward,
+    /// 查詢中換行。
+    Newline,
+    /// 於查詢或命令行中輸入字符。
+    InsertChar(char),
+    /// 終端所粘貼之文本。
+    Paste(String),
+    /// 立即執行查詢，不論是否自動查詢。
+    RunQuery,
+    /// 中止後台執行中之查詢，留已到之行。
+    CancelQuery,
+    /// 掛起至 shell。
+    Suspend,
+    /// 關閉幫助或列統計之浮窗。
+    CloseHelp,
+    /// 執行待確認之操作。
+    Confirm,
+    /// 取消待確認之操作。
+    Cancel,
+    /// 執行待確認之操作之另一選擇，如改名而不覆蓋。
+    ConfirmAlternative,
+    /// 執行此操作，所寫之文件已存在亦覆蓋之。
+    Overwrite(Box<Action>),
+    /// 以此值導出，平值所失者已知，不再問。
+    ExportAs(Values, Box<Action>),
+    /// 恢復上次之會話。
+    RestoreSession,
+    /// 打開層級樹。
+    OpenHierarchy,
+    /// 打開三元組模式之表單。
+    OpenPattern,
+    /// 以所選資源為中心打開鄰域樹。
+    OpenNeighborhood,
+    /// 展開所選節點。
+    ExpandNode,
+    /// 收起所選節點，已收起則移至其父。
+    CollapseNode,
+    /// 書籤所選行之首個 IRI，或所選層級節點。
+    BookmarkRow,
+    /// 書籤當前查詢。
+    BookmarkQuery,
+    OpenBookmarks,
+    /// 打開最近打開之文件之列表。
+    OpenRecent,
+    /// 打開本會話之查詢歷史，附各查詢結果之快照。
+    OpenHistory,
+    /// 打開命令行。
+    OpenCommandLine,
+    /// 打開命令行，已填 `export `。
+    PromptExport,
+    /// 打開命令行，已填 `open `。
+    PromptOpen,
+    /// 打開命令行，已填 `rename ` 及光標所在之變量。
+    PromptRename,
+    /// 改查詢中一變量之名，先示其出現之次數以確認。
+    RenameVariable(String, String),
+    /// 改變量之名，不加確認。
+    ForceRenameVariable(String, String),
+    /// 復查詢為末次改名或規範化之前。
+    UndoRename,
+    /// 查詢中之彎引號、非 ASCII 之空白與不可見之字符代以 ASCII。
+    NormalizeQuery,
+    /// 執行查詢，不查其可疑之字符。
+    ForceRunQuery,
+    /// 始或止錄宏。始時問所錄之寄存器。
+    ToggleMacroRecording,
+    /// 錄其後之操作於此寄存器。
+    RecordMacro(char),
+    /// 問所重放之寄存器。次數取自其前所按之數字。
+    PromptPlayMacro,
+    /// 重放寄存器中之宏若干次。
+    PlayMacro(char, usize),
+    /// 列所錄之宏。
+    ListMacros,
+    /// 打開過濾之輸入行。
+    OpenFilter,
+    /// 過濾之輸入行中，切換正則與字面子串。
+    ToggleRegex,
+    /// 以某表達式過濾結果行，`None` 則不過濾。`regex` 為未以 `/…/` 標明之子句是否為正則。
+    SetFilter {
+        pattern: Option<String>,
+        regex: bool,
+    },
+    /// 每隔此時重新執行當前之查詢，`None` 則停止。
+    Watch(Option<Duration>),
+    /// 存當前之查詢及其顯示之設置為具名之視圖。
+    SaveView(String),
+    /// 打開具名之視圖：執行其查詢，再設其顯示。
+    OpenView(String),
+    /// 自目錄導入查詢庫，併入已存之視圖。
+    ImportLibrary(PathBuf),
+    /// 寫已存之視圖與查詢書籤為查詢庫。
+    ExportLibrary(PathBuf),
+    /// 以側寫之文件蓋當前之設置，不改配置文件。
+    ImportProfile(PathBuf),
+    /// 寫當前之設置為側寫。
+    ExportProfile(PathBuf),
+    /// 導入時與已存者同名而異之視圖：代之，或皆留而更其名。
+    MergeLibrary {
+        views: Vec<View>,
+        replace: bool,
+    },
+    /// 載入文件。
+    Open(PathBuf),
+    /// 載入諸文件於內存，其大雖逾 `load_memory_share` 亦不再問。
+    LoadInMemory(Vec<PathBuf>),
+    /// 寫所附之例於狀態目錄而載入之，如載入他文件。
+    LoadSample,
+    /// 關首次啓動之引導，此後不再示。
+    DismissOnboarding,
+    /// 清空存儲，重新載入所有文件。
+    Reload,
+    /// 清空存儲，或僅清空某具名圖。須先確認。
+    Clear(Option<String>),
+    /// 不加確認而清空。
+    ForceClear(Option<String>),
+    /// 導出當前結果。查詢未完則待其完成。
+    Export(ExportFormat, PathBuf),
+    /// 導出已到之行，不待查詢完成。
+    ExportLoaded(ExportFormat, PathBuf),
+    /// 導出至文件以外之去處，如附於文件、命令或 URL。查詢未完則待其完成。
+    ExportTo(ExportFormat, Sink),
+    /// 設置 CSV、TSV 與表格所導出之值。
+    SetValues(Values),
+    /// 設置查詢之默認圖並重新查詢；`None` 則報告之。
+    SetDataset(Option<Dataset>),
+    /// 默認圖於僅默認圖與諸圖之並間切換。
+    ToggleUnion,
+    /// 設置顯示之行數上限，並詢問是否重新查詢。`None` 為不限，須先確認。
+    SetLimit(Option<usize>),
+    /// 設置顯示之行數上限並即重新查詢，不加確認。
+    ForceSetLimit(Option<usize>),
+    /// 內存將逾預算：以已到之行數為上限，止查詢而留其行。
+    CapRows(usize),
+    /// 改所選行之字面量值：打開以原值起之輸入行。
+    EditValue,
+    /// 執行已確認之更新，如改值或刪去。所涉之三元組已不在則不執行。
+    ApplyUpdate,
+    /// 標記或取消標記所選行。
+    ToggleMark,
+    /// 刪去所標記之行之三元組，未標記則為所選行。先確認。
+    DeleteMarked,
+    /// 列出本會話中已執行之更新。
+    ShowUpdates,
+    /// 撤銷最近若干修改，先確認。`None` 為本會話之全部。
+    UndoData(Option<usize>),
+    /// 列出檢查點，或恢復其第若干（自 1 起，新者居先），先確認。
+    RestoreCheckpoint(Option<usize>),
+    /// 以此檢查點替換其範圍，不加確認。
+    ForceRestoreCheckpoint(PathBuf),
+    /// 檢查點逾上限而經確認後，不寫檢查點而執行此操作。
+    WithoutCheckpoint(Box<Action>),
+    /// 顯示存儲之統計。
+    ShowStats,
+    /// 就已到之行統計所選之列：計數、相異值、未綁定，及數值、日期、字符串長之範圍。
+    ShowColumnStats,
+    /// 開合表尾，逐列示其值之類別及比例，如 `xsd:integer (94%), string (6%)`。
+    ToggleTypes,
+    /// 僅一行一列之結果以大字示之，或仍以表示之。
+    ToggleTile,
+    /// 所選之行逐變量一行示之，不以表；列多者賴之。
+    ToggleTransposed,
+    /// 依次換結果格之色調：無、依類別、依列。
+    CycleTint,
+    /// 浮窗示所選列各類別之格數。
+    ShowColumnTypes,
+    /// 列出指向所選資源之三元組，先數之，多則問其列法。
+    ShowIncoming,
+    /// 列出入邊，不數。
+    ListIncoming(Incoming),
+    /// 浮窗示所選之格之原文、長度與各部分。
+    InspectTerm,
+    /// 浮窗示所選之行之各值，所算之列附其表達式。
+    ShowRow,
+    /// 以外部程序打開所選之 IRI。
+    OpenIri,
+    /// 檢視之值與所粘貼者逐字比較。
+    CompareInspected(String),
+    /// 記所選之格為比較之第一或第二值。
+    ChooseDiffValue(usize),
+    /// 浮窗示所記之兩值之逐字差異。
+    DiffValues,
+    /// 滾動值之比較浮窗若干行。
+    ScrollPopup(isize),
+    /// 複製值之比較中之第一或第二值。
+    YankDiffValue(usize),
+    /// 所選移至表頭，狀態欄示所選列之全名、所出之表達式、相異值之數與排序。
+    SelectHeader,
+    /// 依所選之列排序，已依之則反其向。
+    ToggleSort,
+    /// 以所選之列為次一排序之鍵，已在其中則反其向。
+    AddSortKey,
+    /// 清除諸排序之鍵，還原查詢所返之序。
+    ClearSort,
+    /// 以所選行為例，逐列選固定其值或取任意，生相似之行之查詢，置於查詢編輯器。
+    QueryByExample,
+    /// 於後台數所選格之項在其位置之三元組數，示於狀態欄。
+    CountTerm,
+    /// 釘所選格之項於便箋。
+    PinCell,
+    /// 釘一段文字於便箋。
+    PinText(String),
+    /// 開關便箋面板。
+    OpenScratchpad,
+    /// 複製便箋所選項至剪貼板；結果中則於狀態欄開複製之菜單，再一鍵選其形式。
+    Yank,
+    /// 以此形式複製所選之格或其行至剪貼板。
+    YankAs(Yank),
+    /// 換窗格之佈局為此預設；無名則列出諸預設。
+    SetLayout(Option<String>),
+    /// 換至下一佈局預設，首末相接。
+    CycleLayout,
+    /// 複製可分享之鏈接，記所載入之文件、查詢、數據集及其顯示。
+    Share,
+    /// 打開 [`Action::Share`] 所複製之鏈接。
+    Goto(String),
+    /// 開關調試浮層：按鍵之延遲、繪製之耗時、隊列之深度、緩存之命中率及幀率。
+    ToggleDebug,
+    /// 寫調試之度量於日誌。
+    LogDebug,
+    /// 於後台重建層級或鄰域之樹，如存儲有變而未自動重建者。
+    RefreshPanel,
+    /// 便箋所選項上下移若干位，負為向上。
+    MovePin(isize),
+    /// 列出各圖之來源、載入之時與三元組數。
+    ShowGraphs,
+    /// 示配置、會話、書籤等存於何處。
+    ShowPaths,
+    /// 示存儲、結果、快照與諸緩存之估計內存。
+    ShowMemory,
+    /// 結果表末附或不附各行所在圖之來源。
+    ToggleOrigin,
+    /// 設無 ORDER BY 之結果依項之序排列否。`None` 則報當前之設置。
+    SetStableOrder(Option<bool>),
+    /// 開關無 ORDER BY 之結果之規範之序。
+    ToggleStableOrder,
+    /// 結果表中 IRI 示為其標籤否。
+    ToggleLabels,
+    /// 與同一查詢前次之結果相較，分列新增、未變與刪去之行。已在比較則開合刪去之行。
+    ShowDiff,
+    /// 開關所選格之預覽窗格。
+    TogglePreview,
+    /// 預覽窗格上下滾動若干行，負為向上。
+    ScrollPreview(isize),
+    /// 焦點移至上或下若干部分，負為向上。至首末而止。
+    MoveFocus(isize),
+    /// 焦點依次移至後或前若干部分，首末相接。
+    CycleFocus(isize),
+    /// 執行數據質量檢查，以報告為結果。
+    RunChecks,
+    /// 查找於函數性屬性有多個相異值之主語。未給謂語則概覽所聲明之各函數性屬性。
+    CheckFunctional(Option<String>),
+    /// 查找共用某謂語之同一字面量值之主語。謂語默認為 `rdfs:label`。
+    FindDupes {
+        predicate: Option<String>,
+        /// 忽略大小寫與空白之異。
+        normalized: bool,
+    },
+    /// 顯示幫助。
+    ShowHelp,
+    /// 瀏覽某具名圖。
+    BrowseGraph(String),
+    /// 複製、移動或刪去一圖，先確認。
+    GraphOp(GraphOp),
+    /// 於後台執行圖之操作，不加確認。
+    ForceGraphOp(GraphOp),
+}
+
+/// 一圖之整體操作，同 SPARQL 之 `COPY`、`MOVE`、`DROP`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphOp {
+    /// 以前者之四元組替換後者之四元組。
+    Copy(GraphName, GraphName),
+    /// 同複製，後刪去前者。
+    Move(GraphName, GraphName),
+    /// 刪去此圖；默認圖則清空之。
+    Drop(GraphName),
+}
+
+/// 導出之格式：SPARQL 結果格式，供筆記之表，或 RDF。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Results(QueryResultsFormat),
+    /// Markdown 管道表，如表中所見。
+    Markdown,
+    /// Org 表，如表中所見。
+    Org,
+    /// CONSTRUCT 或 DESCRIBE 之三元組。
+    Rdf(RdfFormat),
+    /// 存儲之諸圖，如 `:dump`。
+    Store(RdfFormat),
+}
+
+/// CSV、TSV 與表格所導出之值。SPARQL JSON 與 XML 有定式，不受其影響。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Values {
+    /// 原樣之項，如 `"42"^^xsd:integer`。
+    #[default]
+    Raw,
+    /// 字面量之詞法形式、IRI 之串、空白節點之標籤，供電子表格。
+    Plain,
+    /// 平值，每變量另附一列，記字面量之數據類型或語言。
+    Typed,
+}
+
+impl From<QueryResultsFormat> for ExportFormat {
+    fn from(format: QueryResultsFormat) -> Self {
+        Self::Results(format)
+    }
+}
+
+impl ExportFormat {
+    /// 以名識之：`csv`、`tsv`、`json`、`xml`、`markdown`（`md`）、`org`，
+    /// 及 RDF 之 `ttl`（`turtle`）、`trig`、`nt`（`ntriples`）、`nq`（`nquads`）。
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "ttl" | "turtle" => Self::Rdf(RdfFormat::Turtle),
+            "trig" => Self::Rdf(RdfFormat::TriG),
+            "nt" | "ntriples" => Self::Rdf(RdfFormat::NTriples),
+            "nq" | "nquads" => Self::Rdf(RdfFormat::NQuads),
+            "csv" => QueryResultsFormat::Csv.into(),
+            "tsv" => QueryResultsFormat::Tsv.into(),
+            "json" => QueryResultsFormat::Json.into(),
+            "xml" => QueryResultsFormat::Xml.into(),
+            "markdown" | "md" => Self::Markdown,
+            "org" => Self::Org,
+            _ => return None,
+        })
+    }
+
+    /// 送至命令或 URL 時之內容類型。
+    pub fn media_type(self) -> &'static str {
+        match self {
+            Self::Results(format) => format.media_type(),
+            Self::Markdown => "text/markdown",
+            Self::Org => "text/org",
+            Self::Rdf(format) | Self::Store(format) => format.media_type(),
+        }
+    }
+}
+
+/// 可綁定於按鍵之操作：配置中之名、操作、說明。
+pub const BINDABLE: &[(&str, Action, &str)] = &[
+    ("quit", Action::Quit, "quit"),
+    ("force_quit", Action::ForceQuit, "quit without confirmation"),
+    ("next_tab", Action::CycleTab(1), "switch to the next tab"),
+    (
+        "previous_tab",
+        Action::CycleTab(-1),
+        "switch to the previous tab",
+    ),
+    (
+        "close_tab",
+        Action::CloseTab,
+        "close this tab and its store",
+    ),
+    (
+        "switch_mode",
+        Action::SwitchMode,
+        "switch between Query and Browse",
+    ),
+    (
+        "select_next",
+        Action::ScrollRows(1),
+        "move the selection down",
+    ),
+    (
+        "select_previous",
+        Action::ScrollRows(-1),
+        "move the selection up",
+    ),
+    (
+        "select_next_column",
+        Action::ScrollColumns(1),
+        "select the next column",
+    ),
+    (
+        "select_previous_column",
+        Action::ScrollColumns(-1),
+        "select the previous column",
+    ),
+    ("select_first", Action::SelectFirst, "select the first row"),
+    ("select_last", Action::SelectLast, "select the last row"),
+    ("activate", Action::Activate, "expand / jump / run"),
+    ("back", Action::Back, "back to Browse"),
+    ("delete", Action::Delete, "delete the bookmark"),
+    (
+        "cycle_relation",
+        Action::CycleRelation,
+        "next hierarchy relation",
+    ),
+    ("list_instances", Action::ListInstances, "list instances"),
+    (
+        "show_details",
+        Action::ShowDetails,
+        "show details of the resource",
+    ),
+    (
+        "complete",
+        Action::Complete,
+        "complete the command or prefix",
+    ),
+    (
+        "list_completions",
+        Action::ListCompletions,
+        "list the completions",
+    ),
+    ("next_field", Action::CycleField(1), "next pattern field"),
+    (
+        "previous_field",
+        Action::CycleField(-1),
+        "previous pattern field",
+    ),
+    (
+        "delete_backward",
+        Action::DeleteBackward,
+        "delete a character",
+    ),
+    ("newline", Action::Newline, "insert a newline"),
+    ("run_query", Action::RunQuery, "run the query now"),
+    (
+        "cancel_query",
+        Action::CancelQuery,
+        "stop the running query",
+    ),
+    ("open_hierarchy", Action::OpenHierarchy, "class hierarchy"),
+    (
+        "open_pattern",
+        Action::OpenPattern,
+        "browse by triple pattern",
+    ),
+    (
+        "open_neighborhood",
+        Action::OpenNeighborhood,
+        "neighborhood of the selected resource",
+    ),
+    ("expand", Action::ExpandNode, "expand the selected node"),
+    (
+        "collapse",
+        Action::CollapseNode,
+        "collapse the selected node",
+    ),
+    ("bookmark", Action::BookmarkRow, "bookmark the resource"),
+    (
+        "bookmark_query",
+        Action::BookmarkQuery,
+        "bookmark the query",
+    ),
+    ("open_bookmarks", Action::OpenBookmarks, "bookmarks"),
+    ("open_recent", Action::OpenRecent, "recently opened files"),
+    (
+        "open_history",
+        Action::OpenHistory,
+        "queries run in this session",
+    ),
+    ("open_command_line", Action::OpenCommandLine, "command line"),
+    ("prompt_export", Action::PromptExport, "export the results"),
+    ("prompt_open", Action::PromptOpen, "open a file"),
+    (
+        "rename_variable",
+        Action::PromptRename,
+        "rename the variable at the cursor",
+    ),
+    (
+        "undo_rename",
+        Action::UndoRename,
+        "undo the last rename or normalization",
+    ),
+    (
+        "normalize_query",
+        Action::NormalizeQuery,
+        "replace curly quotes, odd spaces and invisible characters",
+    ),
+    (
+        "record_macro",
+        Action::ToggleMacroRecording,
+        "start or stop recording a macro",
+    ),
+    ("play_macro", Action::PromptPlayMacro, "replay a macro"),
+    ("list_macros", Action::ListMacros, "recorded macros"),
+    ("open_filter", Action::OpenFilter, "filter the rows"),
+    (
+        "toggle_regex",
+        Action::ToggleRegex,
+        "switch between regex and substring",
+    ),
+    ("reload", Action::Reload, "reload all files"),
+    ("edit_value", Action::EditValue, "edit the literal value"),
+    ("toggle_mark", Action::ToggleMark, "mark the row"),
+    (
+        "delete_marked",
+        Action::DeleteMarked,
+        "delete the marked triples",
+    ),
+    ("show_stats", Action::ShowStats, "store statistics"),
+    (
+        "show_column_stats",
+        Action::ShowColumnStats,
+        "statistics of the selected column",
+    ),
+    (
+        "toggle_tile",
+        Action::ToggleTile,
+        "single values as big text or a table",
+    ),
+    (
+        "toggle_transposed",
+        Action::ToggleTransposed,
+        "the selected row as one variable and value per line",
+    ),
+    (
+        "cycle_tint",
+        Action::CycleTint,
+        "tint cells by term kind or column",
+    ),
+    (
+        "toggle_types",
+        Action::ToggleTypes,
+        "column types under the table",
+    ),
+    (
+        "show_column_types",
+        Action::ShowColumnTypes,
+        "types of the selected column",
+    ),
+    (
+        "show_incoming",
+        Action::ShowIncoming,
+        "triples pointing at the selected resource",
+    ),
+    (
+        "inspect_term",
+        Action::InspectTerm,
+        "exact characters and parts of the selected cell",
+    ),
+    (
+        "show_row",
+        Action::ShowRow,
+        "every value of the selected row and how it was computed",
+    ),
+    (
+        "open_iri",
+        Action::OpenIri,
+        "open the selected IRI in the browser (open_command)",
+    ),
+    (
+        "diff_first",
+        Action::ChooseDiffValue(0),
+        "choose the selected cell as the first value to compare",
+    ),
+    (
+        "diff_second",
+        Action::ChooseDiffValue(1),
+        "choose the selected cell as the second value to compare",
+    ),
+    (
+        "diff_values",
+        Action::DiffValues,
+        "character differences between the chosen values",
+    ),
+    (
+        "select_header",
+        Action::SelectHeader,
+        "select the column headers",
+    ),
+    (
+        "toggle_sort",
+        Action::ToggleSort,
+        "sort by the selected column",
+    ),
+    (
+        "add_sort_key",
+        Action::AddSortKey,
+        "add the selected column as the next sort key",
+    ),
+    ("clear_sort", Action::ClearSort, "clear all sorting"),
+    (
+        "query_by_example",
+        Action::QueryByExample,
+        "query rows like the selected one",
+    ),
+    (
+        "count_term",
+        Action::CountTerm,
+        "count the triples using the selected term",
+    ),
+    (
+        "pin",
+        Action::PinCell,
+        "pin the selected cell to the scratchpad",
+    ),
+    ("open_scratchpad", Action::OpenScratchpad, "scratchpad"),
+    ("yank", Action::Yank, "copy to the clipboard"),
+    ("move_pin_up", Action::MovePin(-1), "move the pin up"),
+    (
+        "cycle_layout",
+        Action::CycleLayout,
+        "next layout of the panes",
+    ),
+    (
+        "toggle_debug",
+        Action::ToggleDebug,
+        "latency, queue and cache numbers for debugging redraws",
+    ),
+    (
+        "refresh_panel",
+        Action::RefreshPanel,
+        "rebuild the tree from the current data",
+    ),
+    ("move_pin_down", Action::MovePin(1), "move the pin down"),
+    (
+        "show_graphs",
+        Action::ShowGraphs,
+        "where each graph came from",
+    ),
+    (
+        "show_paths",
+        Action::ShowPaths,
+        "where settings and state live",
+    ),
+    ("show_memory", Action::ShowMemory, "estimated memory use"),
+    (
+        "toggle_preview",
+        Action::TogglePreview,
+        "preview the selected cell",
+    ),
+    (
+        "preview_down",
+        Action::ScrollPreview(1),
+        "scroll the preview down",
+    ),
+    (
+        "preview_up",
+        Action::ScrollPreview(-1),
+        "scroll the preview up",
+    ),
+    ("focus_up", Action::MoveFocus(-1), "focus the pane above"),
+    ("focus_down", Action::MoveFocus(1), "focus the pane below"),
+    ("next_pane", Action::CycleFocus(1), "focus the next pane"),
+    (
+        "previous_pane",
+        Action::CycleFocus(-1),
+        "focus the previous pane",
+    ),
+    (
+        "toggle_origin",
+        Action::ToggleOrigin,
+        "show the source file of each row's graph",
+    ),
+    (
+        "toggle_labels",
+        Action::ToggleLabels,
+        "show IRIs as their labels",
+    ),
+    (
+        "toggle_stable_order",
+        Action::ToggleStableOrder,
+        "sort results without ORDER BY canonically",
+    ),
+    (
+        "toggle_union",
+        Action::ToggleUnion,
+        "query the union of all graphs or the default graph",
+    ),
+    (
+        "show_diff",
+        Action::ShowDiff,
+        "compare with the previous run",
+    ),
+    ("run_checks", Action::RunChecks, "data-quality report"),
+    ("show_help", Action::ShowHelp, "this help"),
+];
+
+impl Action {
+    /// 依配置中之名取可綁定之操作。
+    pub fn from_name(name: &str) -> Option<Self> {
+        BINDABLE
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, action, _)| action.clone())
+    }
+
+    /// 可綁定操作之說明。
+    pub fn description(&self) -> Option<&'static str> {
+        BINDABLE
+            .iter()
+            .find(|(_, action, _)| action == self)
+            .map(|(_, _, description)| *description)
+    }
+}
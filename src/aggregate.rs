@@ -0,0 +1,252 @@
+use crate::display::{compact_iri, group_digits};
+use oxigraph::model::{
+    vocab::{rdf, xsd},
+    NamedNodeRef, Term,
+};
+use oxsdatatypes::{Date, DateTime, Decimal, Double, Float};
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+};
+
+/// 整數及其派生類型之值域。`None` 為無界。
+const INTEGERS: &[(NamedNodeRef<'static>, Option<i128>, Option<i128>)] = &[
+    (xsd::INTEGER, None, None),
+    (xsd::LONG, Some(i64::MIN as i128), Some(i64::MAX as i128)),
+    (xsd::INT, Some(i32::MIN as i128), Some(i32::MAX as i128)),
+    (xsd::SHORT, Some(i16::MIN as i128), Some(i16::MAX as i128)),
+    (xsd::BYTE, Some(i8::MIN as i128), Some(i8::MAX as i128)),
+    (xsd::NON_NEGATIVE_INTEGER, Some(0), None),
+    (xsd::POSITIVE_INTEGER, Some(1), None),
+    (xsd::NON_POSITIVE_INTEGER, None, Some(0)),
+    (xsd::NEGATIVE_INTEGER, None, Some(-1)),
+    (xsd::UNSIGNED_LONG, Some(0), Some(u64::MAX as i128)),
+    (xsd::UNSIGNED_INT, Some(0), Some(u32::MAX as i128)),
+    (xsd::UNSIGNED_SHORT, Some(0), Some(u16::MAX as i128)),
+    (xsd::UNSIGNED_BYTE, Some(0), Some(u8::MAX as i128)),
+];
+
+/// 結果表中一列之統計，皆就已到之行計之，不另查詢。
+#[derive(Debug, Default)]
+pub struct ColumnStats {
+    /// 有值之格數。
+    pub count: usize,
+    pub distinct: usize,
+    /// 未綁定之格數。
+    pub unbound: usize,
+    /// 數值字面量之個數、最小、最大與和。
+    pub numbers: Option<(usize, f64, f64, f64)>,
+    /// `xsd:date` 之最小與最大。
+    pub dates: Option<(Date, Date)>,
+    /// `xsd:dateTime` 之最小與最大。時區不明而不可比者不計。
+    pub date_times: Option<(DateTime, DateTime)>,
+    /// 字符串之最短與最長，以字符計。
+    pub lengths: Option<(usize, usize)>,
+    /// 不合其數據類型之值數，依數據類型。
+    pub invalid: BTreeMap<String, usize>,
+}
+
+impl ColumnStats {
+    pub fn new<'a>(terms: impl IntoIterator<Item = Option<&'a Term>>) -> Self {
+        let mut stats = Self::default();
+        let mut seen = HashSet::new();
+        for term in terms {
+            let Some(term) = term else {
+                stats.unbound += 1;
+                continue;
+            };
+            stats.count += 1;
+            seen.insert(term);
+            let Term::Literal(literal) = term else {
+                continue;
+            };
+            let datatype = literal.datatype();
+            let value = literal.value();
+            match parse(datatype, value) {
+                Some(Value::Number(number)) => {
+                    let (count, min, max, sum) =
+                        stats.numbers.get_or_insert((0, number, number, 0.0));
+                    *count += 1;
+                    *min = min.min(number);
+                    *max = max.max(number);
+                    *sum += number;
+                }
+                Some(Value::Date(date)) => {
+                    let (min, max) = stats.dates.get_or_insert((date, date));
+                    *min = if date < *min { date } else { *min };
+                    *max = if date > *max { date } else { *max };
+                }
+                Some(Value::DateTime(date_time)) => {
+                    let (min, max) = stats.date_times.get_or_insert((date_time, date_time));
+                    *min = if date_time < *min { date_time } else { *min };
+                    *max = if date_time > *max { date_time } else { *max };
+                }
+                Some(Value::String(length)) => {
+                    let (min, max) = stats.lengths.get_or_insert((length, length));
+                    *min = (*min).min(length);
+                    *max = (*max).max(length);
+                }
+                Some(Value::Other) => {}
+                None => {
+                    *stats
+                        .invalid
+                        .entry(datatype.as_str().to_string())
+                        .or_default() += 1
+                }
+            }
+        }
+        stats.distinct = seen.len();
+        stats
+    }
+
+    /// 浮窗中之各行：名與值。
+    pub fn lines(&self, prefixes: &[(String, String)]) -> Vec<(String, String)> {
+        let mut lines = vec![
+            ("count".to_string(), group_digits(self.count)),
+            ("distinct".to_string(), group_digits(self.distinct)),
+            ("unbound".to_string(), group_digits(self.unbound)),
+        ];
+        if let Some((count, min, max, sum)) = self.numbers {
+            lines.push(("numbers".to_string(), group_digits(count)));
+            lines.push(("min".to_string(), number(min)));
+            lines.push(("max".to_string(), number(max)));
+            lines.push(("mean".to_string(), number(sum / count as f64)));
+            lines.push(("sum".to_string(), number(sum)));
+        }
+        if let Some((min, max)) = self.dates {
+            lines.push(("dates".to_string(), format!("{min} … {max}")));
+        }
+        if let Some((min, max)) = self.date_times {
+            lines.push(("date-times".to_string(), format!("{min} … {max}")));
+        }
+        if let Some((min, max)) = self.lengths {
+            lines.push(("length".to_string(), format!("{min} … {max} characters")));
+        }
+        for (datatype, count) in &self.invalid {
+            let datatype =
+                compact_iri(datatype, prefixes).unwrap_or_else(|| format!("<{datatype}>"));
+            let values = if *count == 1 { "value" } else { "values" };
+            lines.push((
+                "invalid".to_string(),
+                format!("{} invalid {datatype} {values}", group_digits(*count)),
+            ));
+        }
+        lines
+    }
+}
+
+/// 數值字面量之值。非數或不合其類型則無。
+pub fn numeric(term: &Term) -> Option<f64> {
+    let Term::Literal(literal) = term else {
+        return None;
+    };
+    match parse(literal.datatype(), literal.value())? {
+        Value::Number(number) => Some(number),
+        _ => None,
+    }
+}
+
+/// 字面量依其數據類型所得之值。
+enum Value {
+    Number(f64),
+    Date(Date),
+    DateTime(DateTime),
+    /// 字符串之長。
+    String(usize),
+    /// 不統計之類型。
+    Other,
+}
+
+/// 依數據類型解析字面量之值。不合其類型則無。
+fn parse(datatype: NamedNodeRef<'_>, value: &str) -> Option<Value> {
+    if let Some((_, min, max)) = INTEGERS.iter().find(|(iri, _, _)| *iri == datatype) {
+        let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        // 逾 i128 者必為無界之整數
+        if let Ok(integer) = value.parse::<i128>() {
+            if min.is_some_and(|min| integer < min) || max.is_some_and(|max| integer > max) {
+                return None;
+            }
+        } else if min.is_some() && max.is_some() {
+            return None;
+        }
+        return value.parse().ok().map(Value::Number);
+    }
+    Some(match datatype {
+        xsd::DECIMAL => Value::Number(Double::from(Decimal::from_str(value).ok()?).into()),
+        xsd::DOUBLE => Value::Number(Double::from_str(value).ok()?.into()),
+        xsd::FLOAT => Value::Number(Double::from(Float::from_str(value).ok()?).into()),
+        xsd::DATE => Value::Date(Date::from_str(value).ok()?),
+        xsd::DATE_TIME => Value::DateTime(DateTime::from_str(value).ok()?),
+        xsd::STRING | rdf::LANG_STRING => Value::String(value.chars().count()),
+        _ => Value::Other,
+    })
+}
+
+/// 數之顯示：整數不帶小數，餘者至多六位有效小數。
+fn number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.0}")
+    } else if value.is_finite() {
+        let text = format!("{value:.6}");
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::Literal;
+
+    #[test]
+    fn stats_cover_numbers_dates_strings_and_invalid_values() {
+        let typed = |value: &str, datatype| Term::from(Literal::new_typed_literal(value, datatype));
+        let terms = [
+            typed("1", xsd::INTEGER),
+            typed("4", xsd::INTEGER),
+            typed("4", xsd::INTEGER),
+            typed("2.5", xsd::DECIMAL),
+            typed("x", xsd::INTEGER),
+            typed("300", xsd::BYTE),
+            typed("1e", xsd::DOUBLE),
+            typed("2024-03-01", xsd::DATE),
+            typed("2020-01-31", xsd::DATE),
+            typed("2024-02-30", xsd::DATE),
+            Term::from(Literal::new_simple_literal("abc")),
+            Term::from(Literal::new_language_tagged_literal_unchecked("é", "fr")),
+        ];
+        let stats = ColumnStats::new(terms.iter().map(Some).chain([None, None]));
+        assert_eq!((stats.count, stats.distinct, stats.unbound), (12, 11, 2));
+        assert_eq!(stats.numbers, Some((4, 1.0, 4.0, 11.5)));
+        let (min, max) = stats.dates.unwrap();
+        assert_eq!(
+            (min.to_string(), max.to_string()),
+            ("2020-01-31".into(), "2024-03-01".into())
+        );
+        assert_eq!(stats.lengths, Some((1, 3)));
+
+        let prefixes = [("xsd".to_string(), xsd::INTEGER.as_str()[..33].to_string())];
+        let lines = stats.lines(&prefixes);
+        let find = |name: &str| -> Vec<&str> {
+            lines
+                .iter()
+                .filter(|(n, _)| n == name)
+                .map(|(_, value)| value.as_str())
+                .collect()
+        };
+        assert_eq!(find("mean"), ["2.875"]);
+        assert_eq!(
+            find("invalid"),
+            [
+                "1 invalid xsd:byte value",
+                "1 invalid xsd:date value",
+                "1 invalid xsd:double value",
+                "1 invalid xsd:integer value"
+            ]
+        );
+    }
+}
@@ -0,0 +1,690 @@
+//! 查詢之編輯：[`Query`] 之字串、未保存之標記與折行，僅於末尾增刪，撤銷唯復末次整體改寫
+//! （改名或規範化）；及查詢與改值輸入行、以例查詢之按鍵。
+
+use super::{wrapped_height, App, Confirmation, Mode};
+use crate::{
+    action::Action,
+    check,
+    edit::Edit,
+    example::Example,
+    rename,
+    typography::{self, Counts},
+};
+use oxigraph::model::Term;
+use ratatui::text::Line;
+use std::cell::{Cell, Ref, RefCell};
+
+impl App {
+    /// 執行查詢之操作：運行、改名、規範化與編輯值。餘者原樣返回，交下一部分。
+    pub(super) fn update_editor(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::QueryByExample => self.query_by_example(),
+            Action::RunQuery => match Counts::of(&self.query.string) {
+                counts if counts.total() > 0 && self.config.check_typography => {
+                    self.confirmation = Some(Confirmation {
+                        message: format!(
+                            "The query has {}, highlighted. Normalize it (y), run it as is (r), or cancel (n)?",
+                            counts.describe()
+                        ),
+                        preview: None,
+                        alternative: Some(('r', Action::ForceRunQuery)),
+                        action: Action::NormalizeQuery,
+                    });
+                }
+                _ => self.run_query(),
+            },
+            Action::RenameVariable(from, to) => self.propose_rename(&from, &to),
+            Action::ForceRenameVariable(from, to) => {
+                let renamed = rename::rename(&self.query.string, &from, &to);
+                self.query.replace(renamed, "rename");
+                self.status = Some(format!("Renamed ?{from} to ?{to} (Ctrl+U undoes)"));
+            }
+            Action::UndoRename => match self.query.before_rewrite.take() {
+                Some((rewrite, query)) => {
+                    self.query.replace(query, rewrite);
+                    self.query.before_rewrite = None;
+                    self.status = Some(format!("Undid the {rewrite}"));
+                }
+                None => self.status = Some("No rename to undo".to_string()),
+            },
+            Action::NormalizeQuery => self.normalize_query(),
+            Action::ForceRunQuery => self.run_query(),
+            Action::EditValue => self.open_edit()?,
+            Action::ShowGraphs => {
+                self.query.set(self.provenance.report_query());
+                self.mode = Mode::Browse;
+            }
+            Action::RunChecks => {
+                self.query.set(check::report_query());
+                self.mode = Mode::Browse;
+            }
+            Action::BrowseGraph(iri) => {
+                self.query.set(format!(
+                    "SELECT ?s ?p ?o WHERE {{ GRAPH <{iri}> {{ ?s ?p ?o }} }}"
+                ));
+                self.mode = Mode::Browse;
+            }
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 替換查詢字串，視為已保存。
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.default_query = false;
+        self.query.set(query.into());
+    }
+
+    /// 當前查詢。
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// 查詢模式下之操作。返回未處理者。
+    pub(super) fn update_in_query_mode(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::DeleteBackward => {
+                self.query.pop();
+            }
+            Action::Newline => self.query.push('\n'),
+            Action::InsertChar(ch) => self.query.push(ch),
+            Action::Paste(text) => {
+                // 終端所粘之換行或為 `\r`
+                let text = text.replace("\r\n", "\n").replace('\r', "\n");
+                self.query.push_str(&text);
+                let counts = Counts::of(&text);
+                if counts.total() > 0 {
+                    self.status = Some(format!(
+                        "Pasted {}, highlighted (Ctrl+N normalizes)",
+                        counts.describe()
+                    ));
+                }
+            }
+            action => return Some(action),
+        }
+        None
+    }
+
+    /// 改值之輸入行中之操作。返回未處理者。
+    pub(super) fn update_in_edit_mode(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::InsertChar(ch) => self.edit_line.push(ch),
+            Action::DeleteBackward => {
+                self.edit_line.pop();
+            }
+            Action::Activate => {
+                self.mode = Mode::Browse;
+                self.propose_edit();
+            }
+            Action::Back => {
+                self.edit = None;
+                self.mode = Mode::Browse;
+            }
+            action => return Some(action),
+        }
+        None
+    }
+
+    /// 打開改值之輸入行，以所選行之字面量起。
+    pub(super) fn open_edit(&mut self) -> anyhow::Result<()> {
+        if self.read_only {
+            self.status = Some("Read-only: values cannot be edited".to_string());
+            return Ok(());
+        }
+        let object_column = self.results.table().and_then(|table| {
+            table
+                .variables
+                .iter()
+                .position(|variable| ["o", "object"].contains(&variable.as_str()))
+        });
+        let statement = match self.statement(self.results.selected_row) {
+            _ if self
+                .results
+                .selected_column
+                .is_some_and(|column| Some(column) != object_column) =>
+            {
+                Err("Only the object of a triple can be edited")
+            }
+            Ok((subject, predicate, Term::Literal(object))) => Ok((subject, predicate, object)),
+            Ok(_) => Err("Only literal values can be edited"),
+            Err(error) => Err(error),
+        };
+        let (subject, predicate, object) = match statement {
+            Ok(statement) => statement,
+            Err(error) => {
+                self.status = Some(error.to_string());
+                return Ok(());
+            }
+        };
+        match Edit::find(&self.store, subject, predicate, object)? {
+            Some(edit) => {
+                self.edit_line.input = edit.object.value().to_string();
+                self.edit = Some(edit);
+                self.mode = Mode::Edit;
+            }
+            None => self.status = Some("Triple no longer present".to_string()),
+        }
+        Ok(())
+    }
+
+    /// 以所選行開以例查詢。派生之表無可依之查詢，惟三元組形者可直接寫為模式。
+    pub(super) fn query_by_example(&mut self) {
+        let Some(results) = &self.results.current else {
+            self.status = Some("No results".to_string());
+            return;
+        };
+        let Some(row) = self
+            .results
+            .table()
+            .and_then(|table| table.row(self.results.selected_row))
+        else {
+            self.status = Some("Select a row to use as the example".to_string());
+            return;
+        };
+        let cells = row.map(|term| term.cloned()).collect();
+        let query = (!self.results.derived()).then(|| results.query.clone());
+        let variables = self.results.table().map(|table| table.variables.clone());
+        let example = Example::new(variables.unwrap_or_default(), cells, query);
+        if example.query().is_none() {
+            self.status = Some("Only SELECT results can be queried by example".to_string());
+            return;
+        }
+        self.example = Some(example);
+    }
+
+    pub(super) fn update_example(&mut self, action: Action) {
+        let Some(example) = &mut self.example else {
+            return;
+        };
+        match action {
+            Action::ScrollRows(delta) => example.select(delta),
+            Action::ScrollColumns(delta) => example.cycle(delta),
+            Action::Activate => {
+                if let Some(query) = self.example.take().and_then(|example| example.query()) {
+                    self.query.set(query);
+                    self.mode = Mode::Query;
+                    self.status = Some("Run the query with Ctrl+Enter".to_string());
+                }
+            }
+            Action::Cancel => self.example = None,
+            _ => {}
+        }
+    }
+
+    /// 代查詢中之可疑字符，可以 Ctrl+U 撤銷。
+    pub(super) fn normalize_query(&mut self) {
+        let (normalized, counts) = typography::normalize(&self.query.string);
+        if counts.total() == 0 {
+            self.status = Some("No unusual characters in the query".to_string());
+            return;
+        }
+        self.query.replace(normalized, "normalization");
+        self.status = Some(format!("Replaced {} (Ctrl+U undoes)", counts.describe()));
+    }
+}
+
+/// 查詢字串。同時記錄其形狀，以减少計算成本。
+///
+/// 嵌入者經 [`App::query`] 讀之，經 [`App::set_query`] 或操作改之。
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub(super) string: String,
+    /// 上次保存或載入時之字串，用以判斷是否有未保存之修改。
+    saved: String,
+    /// 各行之顯示，隨編輯僅重建所改之行，故長查詢每鍵之耗時不隨行數增長。
+    lines: Vec<Line<'static>>,
+    /// 各行折行後所佔之行數，依 `wrap_width` 算之。前 `wrapped` 行者有效，編輯皆在末尾，故
+    /// 唯棄其後者。
+    rows: RefCell<Vec<usize>>,
+    wrapped: Cell<usize>,
+    wrap_width: Cell<usize>,
+    /// 末次整體改寫（改名或規範化）之名及其前之字串，撤銷時復之。
+    pub(super) before_rewrite: Option<(&'static str, String)>,
+}
+
+impl Query {
+    /// 新建。
+    pub(super) fn new() -> Self {
+        let string = "SELECT ?s ?p ?o WHERE { ?s ?p ?o }".to_string();
+        Self {
+            saved: string.clone(),
+            lines: split_lines(&string),
+            string,
+            rows: RefCell::default(),
+            wrapped: Cell::new(0),
+            wrap_width: Cell::new(0),
+            before_rewrite: None,
+        }
+    }
+
+    /// 依內寬折行後之高度，含邊框。
+    pub(super) fn height(&self, width: usize) -> usize {
+        2 + self.rows(width).iter().sum::<usize>()
+    }
+
+    /// 各行依內寬折行後所佔之行數，如 `Wrap { trim: false }`。寬度變則全部重算，否則唯算
+    /// 自末次編輯之行起者。
+    pub(super) fn rows(&self, width: usize) -> Ref<'_, Vec<usize>> {
+        let width = width.max(1);
+        if self.wrap_width.replace(width) != width {
+            self.wrapped.set(0);
+        }
+        let valid = self.wrapped.get();
+        if valid < self.lines.len() {
+            let mut rows = self.rows.borrow_mut();
+            rows.truncate(valid);
+            rows.extend(
+                self.lines[valid..]
+                    .iter()
+                    .map(|line| wrapped_height(&line.to_string(), width)),
+            );
+            self.wrapped.set(self.lines.len());
+        }
+        self.rows.borrow()
+    }
+
+    /// 自第 `line` 行起者已改，其折行須重算。
+    fn changed_from(&mut self, line: usize) {
+        self.wrapped.set(self.wrapped.get().min(line));
+        self.rows.get_mut().truncate(self.wrapped.get());
+    }
+
+    /// 各行之顯示。
+    pub(super) fn lines(&self) -> &[Line<'static>] {
+        &self.lines
+    }
+
+    /// 重建末行。編輯皆在末尾，故唯末行可變。
+    fn refresh_last_line(&mut self) {
+        let start = self.string.rfind('\n').map_or(0, |i| i + 1);
+        let line = Line::raw(self.string[start..].to_string());
+        match self.lines.last_mut() {
+            Some(last) => *last = line,
+            None => self.lines.push(line),
+        }
+        self.changed_from(self.lines.len() - 1);
+    }
+
+    /// 查詢之文本。
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    /// 是否有未保存之修改，即載入或書籤以來有無編輯。
+    pub fn is_dirty(&self) -> bool {
+        self.string != self.saved
+    }
+
+    /// 記當前字串為已保存。
+    pub(super) fn mark_saved(&mut self) {
+        self.saved.clone_from(&self.string);
+    }
+
+    /// 推入字符。根據是否 `\n` 計算形狀。
+    pub(super) fn push(&mut self, ch: char) {
+        self.string.push(ch);
+        // 若換行，則增一空行。
+        if ch == '\n' {
+            self.lines.push(Line::default());
+            self.changed_from(self.lines.len() - 1);
+        } else {
+            self.refresh_last_line();
+        }
+    }
+
+    /// 整體替換字串，重算形狀。視同載入，不算修改。
+    pub(super) fn set(&mut self, string: String) {
+        self.saved.clone_from(&string);
+        self.lines = split_lines(&string);
+        self.changed_from(0);
+        self.string = string;
+        self.before_rewrite = None;
+    }
+
+    /// 整體改寫，如改名。算作修改，前者留以撤銷。
+    pub(super) fn replace(&mut self, string: String, rewrite: &'static str) {
+        self.lines = split_lines(&string);
+        self.changed_from(0);
+        self.before_rewrite = Some((rewrite, std::mem::replace(&mut self.string, string)));
+    }
+
+    /// 推入字串，如自便箋所插者。
+    pub(super) fn push_str(&mut self, text: &str) {
+        // 自原末行起重建
+        let start = self.string.rfind('\n').map_or(0, |i| i + 1);
+        self.string.push_str(text);
+        self.lines.pop();
+        self.changed_from(self.lines.len());
+        self.lines.extend(split_lines(&self.string[start..]));
+    }
+
+    pub(super) fn pop(&mut self) -> Option<char> {
+        let ch = self.string.pop();
+        match ch {
+            Some('\n') => {
+                self.lines.pop();
+                self.changed_from(self.lines.len() - 1);
+            }
+            Some(_) => self.refresh_last_line(),
+            None => {}
+        }
+        ch
+    }
+}
+
+/// 分字串為各行之顯示。
+pub(super) fn split_lines(text: &str) -> Vec<Line<'static>> {
+    text.split('\n')
+        .map(|line| Line::raw(line.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_lines, *};
+    use crate::app::tests::{app_with_rows, render, rows, text, type_text, update_all};
+    use oxigraph::io::RdfFormat;
+    use ratatui::{
+        crossterm::event::{Event, KeyCode, KeyModifiers},
+        Terminal,
+    };
+
+    use oxigraph::io::RdfParser;
+    use ratatui::{backend::TestBackend, crossterm::event::KeyEvent, layout::Rect};
+
+    #[test]
+    fn query_runs_on_leaving_the_editor_without_autorun() {
+        let mut app = app_with_rows();
+        app.config.autorun = false;
+        update_all(&mut app, [Action::SwitchMode]);
+        assert_eq!(app.mode, Mode::Query);
+        app.query.set(String::new());
+        type_text(&mut app, "SELECT ?s WHERE { ?s ?p 2 }");
+        // 仍示舊結果
+        assert_eq!(rows(&app), 3);
+
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(rows(&app), 1);
+
+        type_text(&mut app, " LIMIT 0");
+        assert_eq!(rows(&app), 1);
+        update_all(&mut app, [Action::SwitchMode]);
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(rows(&app), 0);
+    }
+
+    #[test]
+    fn an_example_row_becomes_a_query_for_rows_like_it() {
+        let mut app = app_with_rows();
+        let press = |app: &mut App, code: KeyCode, modifiers: KeyModifiers| {
+            let actions = app.key_action(KeyEvent::new(code, modifiers));
+            update_all(app, actions);
+        };
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::CONTROL);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Query by example"));
+        assert!(screen.contains("p  exact value"));
+        assert!(screen.contains("o  same datatype"));
+        press(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Right, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(app.example.is_none());
+        assert_eq!(app.mode, Mode::Query);
+        assert_eq!(
+            app.query.as_str(),
+            "SELECT * WHERE {\n  ?s ?p \"3\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n}\n"
+        );
+        app.run_query();
+        app.wait_for_query();
+        assert_eq!(app.results.table().unwrap().rows.len(), 1);
+
+        app.query
+            .set("SELECT ?s (STR(?o) AS ?text) { ?s ?p ?o }".to_string());
+        app.run_query();
+        app.wait_for_query();
+        app.mode = Mode::Browse;
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert!(app.example.is_none());
+        assert_eq!(app.mode, Mode::Browse);
+        update_all(&mut app, [Action::QueryByExample, Action::Activate]);
+        app.run_query();
+        app.wait_for_query();
+        assert_eq!(app.results.table().unwrap().variables.len(), 2);
+        assert_eq!(app.results.table().unwrap().rows.len(), 3);
+    }
+
+    #[test]
+    fn renaming_a_variable_asks_and_can_be_undone() {
+        let mut app = app_with_rows();
+        let query = "SELECT ?x ?xLabel { ?x rdfs:label ?xLabel # ?x\n} ORDER BY ?x";
+        app.query.set(query.to_string());
+        app.mode = Mode::Query;
+        let ctrl = |ch| KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL);
+        assert_eq!(app.map_key(ctrl('r')), [Action::PromptRename]);
+        update_all(&mut app, [Action::PromptRename]);
+        assert_eq!(app.command_line.input, "rename ?x ?");
+        type_text(&mut app, "person");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.mode, Mode::Query);
+        let confirmation = app.confirmation.as_ref().unwrap();
+        assert_eq!(
+            confirmation.message,
+            "Rename 3 occurrences of ?x to ?person? (y/n)"
+        );
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(
+            app.query.as_str(),
+            "SELECT ?person ?xLabel { ?person rdfs:label ?xLabel # ?x\n} ORDER BY ?person"
+        );
+        assert!(app.query.is_dirty());
+
+        assert_eq!(app.map_key(ctrl('u')), [Action::UndoRename]);
+        update_all(&mut app, [Action::UndoRename]);
+        assert_eq!(app.query.as_str(), query);
+        update_all(&mut app, [Action::RenameVariable("?y".into(), "?z".into())]);
+        assert_eq!(app.status.as_deref(), Some("?y is not in the query"));
+    }
+
+    #[test]
+    fn pasted_typography_is_highlighted_and_normalized_before_running() {
+        let mut app = app_with_rows();
+        app.mode = Mode::Query;
+        app.query.set(String::new());
+        let pasted =
+            "SELECT ?o {\r\n  ?s ex:p ?o FILTER(?o\u{00A0}!= \u{201C}x\u{201D})\u{200B}\r\n}";
+        app.handle_event(&Event::Paste(pasted.to_string())).unwrap();
+        assert_eq!(app.query.as_str().matches('\n').count(), 2);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Pasted 2 curly quotes, 1 non-ASCII space and 1 invisible character, highlighted (Ctrl+N normalizes)")
+        );
+        assert!(text(&render(&mut app)).contains("?o·!= \u{201C}x\u{201D})⟨U+200B⟩"));
+
+        update_all(&mut app, [Action::RunQuery]);
+        let confirmation = app.confirmation.as_ref().unwrap();
+        assert!(confirmation
+            .message
+            .starts_with("The query has 2 curly quotes"));
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(
+            app.query.as_str(),
+            "SELECT ?o {\n  ?s ex:p ?o FILTER(?o != \"x\")\n}"
+        );
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Replaced 2 curly quotes, 1 non-ASCII space and 1 invisible character (Ctrl+U undoes)")
+        );
+        update_all(&mut app, [Action::UndoRename]);
+        assert_eq!(app.status.as_deref(), Some("Undid the normalization"));
+        assert!(app.query.as_str().contains('\u{201C}'));
+
+        // 照原樣執行者不問
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('r'))),
+            [Action::ConfirmAlternative]
+        );
+        update_all(&mut app, [Action::ConfirmAlternative]);
+        assert!(app.confirmation.is_none());
+        assert!(app.query.as_str().contains('\u{201C}'));
+
+        // 輸入行中換行棄之，非輸入之處不理
+        update_all(&mut app, [Action::OpenCommandLine]);
+        app.handle_event(&Event::Paste("ren\name".to_string()))
+            .unwrap();
+        assert_eq!(app.command_line.input, "rename");
+        app.mode = Mode::Browse;
+        app.handle_event(&Event::Paste("q".to_string())).unwrap();
+        assert_eq!(app.mode, Mode::Browse);
+    }
+
+    #[test]
+    fn checks_report_counts_and_drill_into_offenders() {
+        let mut app = App::new().unwrap();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "@prefix ex: <http://ex/> . \
+                 ex:a a ex:T ; ex:knows ex:b, ex:c . ex:b ex:knows ex:a . \
+                 ex:knows a <http://www.w3.org/2002/07/owl#ObjectProperty> ."
+                    .as_bytes(),
+            )
+            .unwrap();
+        update_all(&mut app, [Action::RunChecks]);
+        let report: Vec<Vec<String>> = app.table_text(app.results.table().unwrap());
+        let counts: Vec<(&str, &str)> = report[1..]
+            .iter()
+            .map(|row| (row[0].as_str(), row[1].as_str()))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                (
+                    "\"subjects without rdf:type\"",
+                    "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+                ),
+                (
+                    "\"dangling references\"",
+                    "\"3\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+                ),
+                (
+                    "\"undefined predicates\"",
+                    "\"0\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+                ),
+            ]
+        );
+
+        update_all(&mut app, [Action::ScrollRows(1), Action::ShowDetails]);
+        assert_eq!(app.query.string, check::CHECKS[1].query);
+        let mut dangling = app.table_text(app.results.table().unwrap());
+        dangling.sort();
+        assert_eq!(
+            dangling,
+            [
+                ["<http://ex/T>"],
+                ["<http://ex/c>"],
+                ["<http://www.w3.org/2002/07/owl#ObjectProperty>"],
+                ["?object"]
+            ]
+        );
+    }
+
+    /// 隨機編輯中可能出現之字符，含換行與多字節者。
+    const EDIT_CHARS: &[char] = &['a', ' ', '\n', '{', 'é', '中', '𝄞', '\t'];
+
+    #[test]
+    fn query_height_follows_newlines_under_random_edits() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        for seed in 0..64 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut query = Query::new();
+            // 以字符列為模型
+            let mut model: Vec<char> = query.string.chars().collect();
+            for step in 0..256 {
+                let text = |rng: &mut StdRng| -> String {
+                    (0..rng.gen_range(0..12))
+                        .map(|_| EDIT_CHARS[rng.gen_range(0..EDIT_CHARS.len())])
+                        .collect()
+                };
+                match rng.gen_range(0..11) {
+                    0..=5 => {
+                        let ch = EDIT_CHARS[rng.gen_range(0..EDIT_CHARS.len())];
+                        query.push(ch);
+                        model.push(ch);
+                    }
+                    6..=8 => assert_eq!(query.pop(), model.pop()),
+                    9 => {
+                        let text = text(&mut rng);
+                        query.push_str(&text);
+                        model.extend(text.chars());
+                    }
+                    _ => {
+                        let text = text(&mut rng);
+                        model = text.chars().collect();
+                        query.set(text);
+                    }
+                }
+                let context = format!("seed {seed}, step {step}");
+                assert_eq!(query.string, model.iter().collect::<String>(), "{context}");
+                // 僅重建所改之行，須與全部重建者同
+                assert_eq!(query.lines(), split_lines(&query.string), "{context}");
+                let rows: Vec<usize> = split_lines(&query.string)
+                    .iter()
+                    .map(|line| wrapped_height(&line.to_string(), 9))
+                    .collect();
+                assert_eq!(*query.rows(9), rows, "{context}");
+                // 寬度變則全部重算：寬者每行一行
+                if step % 32 == 0 {
+                    assert_eq!(
+                        query.height(4096),
+                        3 + query.string.matches('\n').count(),
+                        "{context}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_query_pane_fits_wrapped_lines_up_to_half_the_screen() {
+        let mut app = app_with_rows();
+        let draw = |app: &mut App, width: u16, height: u16| {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            app.draw(&mut terminal).unwrap();
+            let screen = text(terminal.backend().buffer());
+            (app.query_height(Rect::new(0, 0, width, height)), screen)
+        };
+
+        // 一長行折為數行，皆可見
+        let variables: Vec<String> = (0..58).map(|n| format!("?v{n:02}")).collect();
+        let line = format!("SELECT {} WHERE {{}}", variables.join(" "));
+        assert!(line.len() > 300);
+        app.query.set(line.clone());
+        let (height, screen) = draw(&mut app, 80, 40);
+        assert_eq!(height as usize, 2 + wrapped_height(&line, 78));
+        assert!(height > 3);
+        assert!(screen.contains("SELECT ?v00") && screen.contains("?v57 WHERE {}"));
+
+        // 終端變窄則行數增，仍至多佔其半
+        let (narrow, screen) = draw(&mut app, 40, 40);
+        assert_eq!(narrow as usize, 2 + wrapped_height(&line, 38));
+        assert!(narrow > height && screen.contains("?v57 WHERE {}"));
+        assert_eq!(draw(&mut app, 40, 16).0, 8);
+
+        // 首行跨可見之頂：其頭捲去，尾仍見
+        let long = format!("HEAD{} TAIL", " word".repeat(40));
+        assert_eq!(wrapped_height(&long, 78), 3);
+        let short: Vec<String> = (0..6).map(|n| format!("short{n}")).collect();
+        app.query.set(format!("{long}\n{}", short.join("\n")));
+        let (height, screen) = draw(&mut app, 80, 20);
+        assert_eq!(height, 10);
+        assert!(!screen.contains("HEAD"), "{screen}");
+        assert!(
+            screen.contains("word TAIL") && screen.contains("short5"),
+            "{screen}"
+        );
+    }
+}
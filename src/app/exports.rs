@@ -0,0 +1,786 @@
+//! 導出結果與圖：寫至文件或他處，Markdown 等表格之文字，平值與覆蓋之確認。
+
+use super::{App, Confirmation, Progress, ResultTable, Source, NO_DATA};
+use crate::{
+    action::{Action, ExportFormat, Values},
+    display::{group_digits, truncate, Bidi, MAX_CELL},
+    export::{self, plain_header, plain_row, ExportJob, Flattened, Graph, Rows},
+    markup,
+    overwrite::{partial_path, unique_path, Overwrite},
+    sink::Sink,
+};
+use anyhow::Context;
+use oxigraph::{
+    model::Term,
+    sparql::{results::QueryResultsFormat, Variable},
+};
+use ratatui::text::Span;
+use std::{io::Write, path::Path, time::Instant};
+
+impl App {
+    /// 執行導出之操作。餘者原樣返回，交下一部分。
+    pub(super) fn update_exports(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::Export(..) | Action::ExportLoaded(..) | Action::ExportTo(..)
+                if self.exports.job.is_some() =>
+            {
+                let sink = &self.exports.job.as_ref().unwrap().sink;
+                self.status = Some(format!("An export to {sink} is running (Esc cancels it)"));
+            }
+            action @ (Action::Export(..) | Action::ExportLoaded(..))
+                if !self.exports.overwriting
+                    && self.exports.overwrite != Overwrite::Always
+                    && export_target(&action).is_some_and(Path::exists) =>
+            {
+                self.confirm_overwrite(action);
+            }
+            Action::Export(format, path)
+                if self.running.is_some() && !matches!(format, ExportFormat::Store(_)) =>
+            {
+                let rows = self.results.table().map_or(0, |table| table.rows.len());
+                self.status = Some(format!(
+                    "Exporting to {} when the query finishes (:export! writes the {rows} rows loaded)",
+                    path.display()
+                ));
+                self.exports.pending = Some(Action::Export(format, path));
+            }
+            Action::ExportTo(format, sink)
+                if self.running.is_some() && !matches!(format, ExportFormat::Store(_)) =>
+            {
+                self.status = Some(format!("Exporting to {sink} when the query finishes"));
+                self.exports.pending = Some(Action::ExportTo(format, sink));
+            }
+            action @ (Action::Export(..) | Action::ExportLoaded(..) | Action::ExportTo(..))
+                if self.exports.confirmed.is_none() && self.flattened(&action).is_some() =>
+            {
+                self.confirm_flattening(action);
+            }
+            Action::Export(format, path) => self.export(format, Sink::File(path), false),
+            Action::ExportLoaded(format, path) => self.export(format, Sink::File(path), true),
+            Action::ExportTo(format, sink) => self.export(format, sink, false),
+            Action::SetValues(values) => {
+                self.exports.values = values;
+                self.status = Some(
+                    match values {
+                        Values::Raw => "CSV, TSV and table exports write the terms as they are",
+                        Values::Plain => "CSV, TSV and table exports write plain values",
+                        Values::Typed => {
+                            "CSV, TSV and table exports write plain values and their types"
+                        }
+                    }
+                    .to_string(),
+                );
+            }
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 將當前結果導出至 `sink`，錯誤見於狀態欄。
+    pub(super) fn export(&mut self, format: ExportFormat, sink: Sink, loaded: bool) {
+        if let Err(error) = self.start_export(format, sink.clone(), loaded) {
+            self.report_export(Err(format!("{error:#}")), &sink, "rows", Instant::now());
+        }
+    }
+
+    /// 將當前結果導出至 `sink`。Markdown 與 Org 即寫；餘者於後台寫之，可取消。
+    /// 表因顯示上限而截斷、導出上限又更寬時，重新查詢以導出，不受顯示上限所限；
+    /// `loaded` 則僅導出已載入之行。
+    pub(super) fn start_export(
+        &mut self,
+        format: ExportFormat,
+        sink: Sink,
+        loaded: bool,
+    ) -> anyhow::Result<()> {
+        if let ExportFormat::Rdf(rdf) | ExportFormat::Store(rdf) = format {
+            let (graph, limit) = match format {
+                ExportFormat::Store(_) => (Graph::Store, None),
+                _ => (self.graph()?, self.exports.limit),
+            };
+            log::info!("export started sink={sink}");
+            self.exports.job = Some(ExportJob::spawn_graph(
+                &self.store,
+                graph,
+                rdf,
+                self.export_prefixes(),
+                sink,
+                limit,
+                self.config.keep_partial_exports,
+            ));
+            return Ok(());
+        }
+        let table = self.results.table().context("No result to export")?;
+        let ExportFormat::Results(format) = format else {
+            let started = Instant::now();
+            let exported = self.export_markup(format, &sink);
+            let (exported, note) = match exported {
+                Ok((rows, note)) => (Ok(rows), note),
+                Err(error) => (Err(format!("{error:#}")), None),
+            };
+            self.report_export(exported, &sink, "rows", started);
+            self.note_export(note);
+            return Ok(());
+        };
+        let rows = if !loaded
+            && table.truncated.is_some()
+            && self.imported.is_none()
+            && table.progress == Progress::Complete
+            && self
+                .exports
+                .limit
+                .is_none_or(|limit| limit > table.rows.len())
+        {
+            self.rerun()?
+        } else {
+            let end = self
+                .exports
+                .limit
+                .unwrap_or(usize::MAX)
+                .min(table.rows.len());
+            Rows::Loaded {
+                variables: table.variables.clone(),
+                rows: table.rows[..end].to_vec(),
+            }
+        };
+        log::info!("export started sink={sink}");
+        self.exports.job = Some(ExportJob::spawn(
+            &self.store,
+            rows,
+            format,
+            self.exports.confirmed.unwrap_or(self.exports.values),
+            sink,
+            self.exports.limit,
+            self.config.keep_partial_exports,
+        ));
+        Ok(())
+    }
+
+    /// 記導出之結果於狀態欄。
+    /// `unit` 為所數者之名，如 `rows`。
+    fn report_export(
+        &mut self,
+        exported: Result<usize, String>,
+        sink: &Sink,
+        unit: &str,
+        started: Instant,
+    ) {
+        self.status = Some(match exported {
+            Ok(count) => {
+                let duration = started.elapsed();
+                log::info!(
+                    "exported sink={sink} {unit}={count} duration_ms={}",
+                    duration.as_millis()
+                );
+                format!(
+                    "Exported {} {unit} to {sink} in {:.1}s",
+                    group_digits(count),
+                    duration.as_secs_f64()
+                )
+            }
+            Err(error) => {
+                log::warn!("export failed sink={sink} error={error}");
+                let partial = sink.path().map(partial_path);
+                if let Some(partial) = partial.filter(|partial| partial.exists()) {
+                    format!(
+                        "Fail to export: {error} (partial file {})",
+                        partial.display()
+                    )
+                } else {
+                    format!("Fail to export: {error}")
+                }
+            }
+        });
+    }
+
+    /// 收取後台導出之結果。
+    pub(super) fn poll_export(&mut self) {
+        let Some(result) = self.exports.job.as_ref().and_then(ExportJob::poll) else {
+            return;
+        };
+        let job = self.exports.job.take().unwrap();
+        self.report_export(result, &job.sink, job.unit, Instant::now() - job.elapsed());
+        self.note_export(job.note());
+    }
+
+    /// 附去處之附註於導出之狀態，如命令之輸出或 HTTP 之狀態。
+    fn note_export(&mut self, note: Option<String>) {
+        if let (Some(status), Some(note)) = (&mut self.status, note) {
+            status.push_str(&format!(" ({note})"));
+        }
+    }
+
+    /// 等待後台導出完成，返回其結果。供腳本與測試。
+    pub fn wait_for_export(&mut self) -> Option<Result<usize, String>> {
+        let job = self.exports.job.take()?;
+        let result = job.wait();
+        self.report_export(
+            result.clone(),
+            &job.sink,
+            job.unit,
+            Instant::now() - job.elapsed(),
+        );
+        self.note_export(job.note());
+        Some(result)
+    }
+
+    /// 以 Markdown 或 Org 表格導出已載入之行，至多導出上限，返回行數及去處之附註。
+    fn export_markup(
+        &self,
+        format: ExportFormat,
+        sink: &Sink,
+    ) -> anyhow::Result<(usize, Option<String>)> {
+        let (text, rows) = self.markup(format)?;
+        sink.deliver(format.media_type(), false, false, |out| {
+            out.write_all(text.as_bytes())?;
+            Ok(rows)
+        })
+    }
+
+    /// 已載入之行之 Markdown 或 Org 表格，至多導出上限，及其行數。
+    fn markup(&self, format: ExportFormat) -> anyhow::Result<(String, usize)> {
+        let table = self.results.table().context("No result to export")?;
+        let rows = &table.rows[..self
+            .exports
+            .limit
+            .unwrap_or(usize::MAX)
+            .min(table.rows.len())];
+        let mut numeric = markup::numeric_columns(table.variables.len(), rows, |row, column| {
+            row[column].as_deref()
+        });
+        let lines = if self.exports.values == Values::Raw {
+            if self.show_labels {
+                self.resolve_labels(rows)?;
+            }
+            let mut lines = self.table_text(table);
+            lines.truncate(rows.len() + 1);
+            lines
+        } else {
+            let header = plain_header(&table.variables, self.exports.values);
+            if self.exports.values == Values::Typed {
+                numeric = numeric.into_iter().flat_map(|n| [n, false]).collect();
+            }
+            let cells = rows.iter().map(|row| {
+                let row: Vec<Option<Term>> = row.iter().map(|t| t.as_deref().cloned()).collect();
+                plain_row(&row, self.exports.values)
+            });
+            std::iter::once(header).chain(cells).collect()
+        };
+        let text = if format == ExportFormat::Markdown {
+            markup::markdown(&lines, &numeric)
+        } else {
+            markup::org(&lines, &numeric)
+        };
+        Ok((text, rows.len()))
+    }
+
+    /// 以此格式即寫當前結果已載入之行於 `out`，至多導出上限，返回行數。
+    /// 供 `--emit-on-exit`；末次查詢出錯或無結果則不寫。
+    pub fn emit(&self, format: ExportFormat, mut out: impl Write) -> anyhow::Result<usize> {
+        if let Some(error) = self.results.current.as_ref().and_then(|r| r.error.as_ref()) {
+            anyhow::bail!("The last query failed: {error}");
+        }
+        if let ExportFormat::Rdf(rdf) | ExportFormat::Store(rdf) = format {
+            let (graph, limit) = match format {
+                ExportFormat::Store(_) => (Graph::Store, None),
+                _ => (self.graph()?, self.exports.limit),
+            };
+            let prefixes = self.export_prefixes();
+            return export::write_graph_to(&self.store, graph, rdf, &prefixes, out, limit);
+        }
+        let table = self.results.table().context("No result to emit")?;
+        let ExportFormat::Results(format) = format else {
+            let (text, rows) = self.markup(format)?;
+            out.write_all(text.as_bytes())?;
+            return Ok(rows);
+        };
+        let rows = Rows::Loaded {
+            variables: table.variables.clone(),
+            rows: table.rows.clone(),
+        };
+        export::write_to(
+            &self.store,
+            rows,
+            format,
+            self.exports.values,
+            out,
+            self.exports.limit,
+        )
+    }
+
+    /// 重新執行所顯示之 CONSTRUCT 或 DESCRIBE 查詢所需者。
+    fn graph(&self) -> anyhow::Result<Graph> {
+        let results = self
+            .results
+            .current
+            .as_ref()
+            .context("No result to export")?;
+        if !matches!(results.source, Source::Query) || self.imported.is_some() {
+            anyhow::bail!("Only CONSTRUCT and DESCRIBE results export as RDF");
+        }
+        let mut query = self.prepared.get(&results.query, None)?;
+        self.dataset.apply(&mut query);
+        Ok(Graph::Query {
+            query: Box::new(query),
+            federated: self.federated,
+            timeout: self.config.timeout,
+        })
+    }
+
+    /// 導出 RDF 所聲明之前綴：先所載入之文件者，後配置者。`--no-prefixes` 則無。
+    pub(super) fn export_prefixes(&self) -> Vec<(String, String)> {
+        match self.exports.prefixed {
+            true => self.declared.with(&self.config.prefixes),
+            false => vec![],
+        }
+    }
+
+    /// 此導出之 CSV 或 TSV 以平值將失者，依已載入之行而計；無失者則 `None`。
+    pub(super) fn flattened(&self, action: &Action) -> Option<Flattened> {
+        let (Action::Export(ExportFormat::Results(format), _)
+        | Action::ExportLoaded(ExportFormat::Results(format), _)
+        | Action::ExportTo(ExportFormat::Results(format), _)) = action
+        else {
+            return None;
+        };
+        if !export::flattens(*format, self.exports.values) {
+            return None;
+        }
+        let table = self.results.table()?;
+        let end = self
+            .exports
+            .limit
+            .unwrap_or(usize::MAX)
+            .min(table.rows.len());
+        Some(Flattened::of(&table.rows[..end])).filter(|flattened| !flattened.is_empty())
+    }
+
+    /// 導出將失數據類型、語言或空節點之別：問照寫、加類型列或取消。
+    pub(super) fn confirm_flattening(&mut self, action: Action) {
+        let Some(flattened) = self.flattened(&action) else {
+            return;
+        };
+        log::info!("export flattens {flattened}");
+        // 已准覆蓋者，照寫或加類型列亦覆蓋之
+        let overwriting = self.exports.overwriting;
+        let with = |values, action: &Action| {
+            let action = Action::ExportAs(values, Box::new(action.clone()));
+            match overwriting {
+                true => Action::Overwrite(Box::new(action)),
+                false => action,
+            }
+        };
+        let name = match &action {
+            Action::Export(ExportFormat::Results(QueryResultsFormat::Csv), _)
+            | Action::ExportLoaded(ExportFormat::Results(QueryResultsFormat::Csv), _)
+            | Action::ExportTo(ExportFormat::Results(QueryResultsFormat::Csv), _) => "CSV",
+            _ => "TSV",
+        };
+        self.confirmation = Some(Confirmation {
+            message: format!(
+                "The {name} export flattens {flattened}. Export anyway (y), with typed \
+                 columns (t), or cancel (n)? JSON and XML keep them"
+            ),
+            preview: None,
+            alternative: Some(('t', with(Values::Typed, &action))),
+            action: with(self.exports.values, &action),
+        });
+    }
+
+    /// 所寫之文件已存在：問覆蓋、改名或取消。無人可問則不寫而報錯。
+    pub(super) fn confirm_overwrite(&mut self, action: Action) {
+        let Some(path) = export_target(&action) else {
+            return;
+        };
+        if self.exports.overwrite == Overwrite::Never {
+            self.status = Some(format!(
+                "{} exists; not overwritten (--force overwrites)",
+                path.display()
+            ));
+            return;
+        }
+        let renamed = unique_path(path);
+        let rename = match &action {
+            Action::ExportLoaded(format, _) => Action::ExportLoaded(*format, renamed.clone()),
+            Action::Export(format, _) => Action::Export(*format, renamed.clone()),
+            _ => unreachable!(),
+        };
+        self.confirmation = Some(Confirmation {
+            message: format!(
+                "{} exists. Overwrite it (y), write {} instead (r), or cancel (n)?",
+                path.display(),
+                renamed.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            preview: None,
+            alternative: Some(('r', rename)),
+            action: Action::Overwrite(Box::new(action)),
+        });
+    }
+
+    /// 查詢及當前結果之純文本，各列對齊。內聯模式退出時輸出。
+    pub fn transcript(&self) -> String {
+        let query = match self.results.pattern() {
+            Some(pattern) => pattern.describe(),
+            None => self.query.string.trim_end().to_string(),
+        };
+        let mut text = format!("{query}\n\n");
+        let Some(table) = self.results.table() else {
+            match self.results.current.as_ref().and_then(|r| r.error.as_ref()) {
+                Some(error) => text.push_str(&format!("{error}\n")),
+                None if self.store_is_empty() => text.push_str(&format!("{NO_DATA}\n")),
+                None => text.push_str("NO RESULT\n"),
+            }
+            return text;
+        };
+        let lines = self.table_text(table);
+        let widths = column_widths(&lines);
+        for line in &lines {
+            let cells: Vec<String> = line
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    let padding = width.saturating_sub(cell_width(cell));
+                    format!("{cell}{}", " ".repeat(padding))
+                })
+                .collect();
+            text.push_str(cells.join("  ").trim_end());
+            text.push('\n');
+        }
+        text.push_str(&format!(
+            "({} rows{})\n",
+            table.rows.len(),
+            if table.truncated.is_some() {
+                ", truncated"
+            } else {
+                ""
+            }
+        ));
+        text
+    }
+
+    /// 表頭及各行之純文本。不截斷，導出故得全值。
+    pub(super) fn table_text(&self, table: &ResultTable) -> Vec<Vec<String>> {
+        let mut lines: Vec<Vec<String>> =
+            vec![table.variables.iter().map(Variable::to_string).collect()];
+        lines.extend(table.rows.iter().map(|row| {
+            row.iter()
+                .map(|term| {
+                    self.cell_within(term.as_deref(), usize::MAX, Bidi::Escape)
+                        .spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect()
+                })
+                .collect()
+        }));
+        lines
+    }
+
+    /// 當前結果各列之顯示寬度，含表頭。無表則為空。
+    #[doc(hidden)]
+    pub fn column_widths(&self) -> Vec<usize> {
+        self.results
+            .table()
+            .map(|table| column_widths(&self.table_text(table)))
+            .unwrap_or_default()
+    }
+}
+
+/// 導出之部分：後台之導出、待行者，及覆蓋、所寫之值與行數上限之設置。
+pub(super) struct Exports {
+    /// 後台執行中之導出。同時僅一。
+    pub(super) job: Option<ExportJob>,
+    /// 查詢完成後待行之導出。
+    pub(super) pending: Option<Action>,
+    /// 導出之文件已存在時如何。
+    pub(super) overwrite: Overwrite,
+    /// 執行已准覆蓋之操作中。
+    pub(super) overwriting: bool,
+    /// CSV、TSV 與表格所導出之值。
+    pub(super) values: Values,
+    /// 本次導出之值。已確認其失者，不再問。
+    pub(super) confirmed: Option<Values>,
+    /// 導出之行數上限，與顯示者無涉。
+    pub(super) limit: Option<usize>,
+    /// 導出 RDF 時是否聲明並用前綴。否則皆寫全 IRI，供規範之輸出。
+    pub(super) prefixed: bool,
+}
+
+impl Default for Exports {
+    fn default() -> Self {
+        Self {
+            job: None,
+            pending: None,
+            overwrite: Overwrite::Ask,
+            overwriting: false,
+            values: Values::Raw,
+            confirmed: None,
+            limit: None,
+            prefixed: true,
+        }
+    }
+}
+
+/// 導出之操作所寫之文件。
+pub(super) fn export_target(action: &Action) -> Option<&Path> {
+    match action {
+        Action::Export(_, path) | Action::ExportLoaded(_, path) => Some(path),
+        _ => None,
+    }
+}
+
+/// 一格之顯示寬度。逾 [`MAX_CELL`] 字節者僅度其前段，巨大之格不拖慢排版。
+pub(super) fn cell_width(cell: &str) -> usize {
+    Span::raw(truncate(cell, MAX_CELL).unwrap_or(cell)).width()
+}
+
+/// 各列之最大顯示寬度。
+pub(super) fn column_widths(lines: &[Vec<String>]) -> Vec<usize> {
+    let columns = lines.first().map_or(0, Vec::len);
+    (0..columns)
+        .map(|column| {
+            lines
+                .iter()
+                .map(|line| cell_width(&line[column]))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::{app_with_rows, render, text, type_text, update_all};
+    use crate::{command, export::ExportJob, overwrite::partial_path};
+    use oxigraph::{model::vocab::xsd, sparql::results::QueryResultsFormat};
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::fs;
+
+    #[cfg(unix)]
+    #[test]
+    fn exports_stream_to_a_command_and_report_what_it_said() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::OpenCommandLine]);
+        type_text(&mut app, "export tsv cmd:wc -l");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.wait_for_export(), Some(Ok(3)));
+        let status = app.status.clone().unwrap();
+        assert!(
+            status.starts_with("Exported 3 rows to `wc -l` in "),
+            "{status}"
+        );
+        assert!(status.ends_with("(`wc -l`: 4)"), "{status}");
+
+        let failing = Sink::parse("cmd:sh -c 'exit 2'").unwrap();
+        update_all(
+            &mut app,
+            [Action::ExportTo(QueryResultsFormat::Tsv.into(), failing)],
+        );
+        assert!(app.wait_for_export().unwrap().is_err());
+        let status = app.status.clone().unwrap();
+        assert!(status.contains("exited with status 2"), "{status}");
+        assert_eq!(
+            command::parse("export! csv cmd:cat"),
+            Err(":export! writes only files".to_string())
+        );
+    }
+
+    #[test]
+    fn constructs_and_dumps_export_as_rdf_with_the_loaded_prefixes() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/animals.ttl");
+        let mut app = App::new().unwrap();
+        app.load(&fixture).unwrap();
+        let ttl =
+            std::env::temp_dir().join(format!("rdf-tui-construct-{}.ttl", std::process::id()));
+        let export = |app: &mut App, command: &str| {
+            update_all(app, [command::parse(command).unwrap()]);
+            app.wait_for_export();
+            app.status.clone().unwrap()
+        };
+        app.refresh_results();
+        app.wait_for_query();
+        let command = format!("export ttl {}", ttl.display());
+        assert_eq!(
+            export(&mut app, &command),
+            "Fail to export: Only CONSTRUCT and DESCRIBE results export as RDF"
+        );
+        app.query.set(
+            "PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#>\n\
+             CONSTRUCT { ?s a ?o } WHERE { ?s rdfs:subClassOf ?o }"
+                .to_string(),
+        );
+        app.run_query();
+        app.wait_for_query();
+        assert!(export(&mut app, &command).starts_with("Exported 2 triples"));
+        assert_eq!(
+            fs::read_to_string(&ttl).unwrap(),
+            "@prefix ex: <http://example.org/> .\nex:Cat a ex:Animal .\nex:Dog a ex:Animal .\n"
+        );
+        fs::remove_file(&ttl).unwrap();
+
+        app.set_prefixed_exports(false);
+        let command = format!("dump {}", ttl.display());
+        assert!(export(&mut app, &command).starts_with("Exported 5 triples"));
+        let dumped = fs::read_to_string(&ttl).unwrap();
+        assert!(dumped.starts_with("<http://example.org/Animal> "));
+        assert!(!dumped.contains("@prefix"));
+        fs::remove_file(ttl).unwrap();
+        assert_eq!(
+            command::parse("dump out.csv"),
+            Err(
+                ":dump needs a path ending in .ttl, .trig, .nt, .nq or another RDF extension"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn exports_ask_before_overwriting_and_can_rename() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-clobber-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.tsv");
+        fs::write(&path, "keep me").unwrap();
+        let mut app = app_with_rows();
+        app.query.set("SELECT * WHERE { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        let export = Action::Export(QueryResultsFormat::Tsv.into(), path.clone());
+
+        update_all(&mut app, [export.clone()]);
+        let message = &app.confirmation.as_ref().unwrap().message;
+        assert!(message.ends_with("write out-1.tsv instead (r), or cancel (n)?"));
+        let actions = app.map_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE));
+        update_all(&mut app, actions);
+        assert_eq!(app.wait_for_export(), Some(Ok(3)));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+        assert!(dir.join("out-1.tsv").exists());
+
+        update_all(&mut app, [export.clone(), Action::Confirm]);
+        assert_eq!(app.wait_for_export(), Some(Ok(3)));
+        assert!(fs::read_to_string(&path).unwrap().starts_with("?o\t?p\t?s"));
+
+        app.set_overwrite(Overwrite::Never);
+        update_all(&mut app, [export]);
+        assert!(app.confirmation.is_none());
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .ends_with("(--force overwrites)"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn csv_exports_of_typed_values_ask_first_and_offer_typed_columns() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-flatten-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.csv");
+        let mut app = app_with_rows();
+        let export = Action::Export(QueryResultsFormat::Csv.into(), path.clone());
+
+        update_all(&mut app, [export.clone()]);
+        let message = &app.confirmation.as_ref().unwrap().message;
+        assert!(
+            message.starts_with("The CSV export flattens 3 typed literals."),
+            "{message}"
+        );
+        let actions = app.map_key(KeyEvent::from(KeyCode::Char('t')));
+        update_all(&mut app, actions);
+        assert_eq!(app.wait_for_export(), Some(Ok(3)));
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.contains(xsd::INTEGER.as_str()), "{written}");
+        assert_eq!(app.exports.values, Values::Raw);
+
+        // 已准覆蓋者不再問覆蓋；照寫則依所設之值
+        update_all(&mut app, [export.clone(), Action::Confirm, Action::Confirm]);
+        assert_eq!(app.wait_for_export(), Some(Ok(3)));
+        assert!(!fs::read_to_string(&path).unwrap().contains("XMLSchema"));
+
+        // 唯 IRI 與字串者、類型列與原樣之 TSV 皆不問
+        app.query.set("SELECT ?s ?p WHERE { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        app.wait_for_query();
+        update_all(&mut app, [Action::Overwrite(Box::new(export.clone()))]);
+        assert!(app.confirmation.is_none());
+        assert_eq!(app.wait_for_export(), Some(Ok(3)));
+        app.query.set("SELECT * WHERE { ?s ?p ?o }".to_string());
+        update_all(
+            &mut app,
+            [Action::RunQuery, Action::SetValues(Values::Typed)],
+        );
+        app.wait_for_query();
+        update_all(&mut app, [Action::Overwrite(Box::new(export))]);
+        assert!(app.confirmation.is_none());
+        assert_eq!(app.wait_for_export(), Some(Ok(3)));
+        update_all(&mut app, [Action::SetValues(Values::Raw)]);
+        let tsv = Action::Export(QueryResultsFormat::Tsv.into(), dir.join("out.tsv"));
+        update_all(&mut app, [tsv]);
+        assert!(app.confirmation.is_none());
+        assert_eq!(app.wait_for_export(), Some(Ok(3)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn a_running_export_refuses_another_and_quitting_asks_first() {
+        let mut app = app_with_rows();
+        let path = std::env::temp_dir().join(format!("rdf-tui-running-{}.tsv", std::process::id()));
+        // 三行之十二重自連接，逾五十萬行
+        let slow = format!(
+            "SELECT * WHERE {{ {} }}",
+            (0..12)
+                .map(|n| format!("?s{n} ?p{n} ?o{n} ."))
+                .collect::<String>()
+        );
+        let rows = Rows::Query {
+            query: Box::new(app.prepared.get(&slow, None).unwrap()),
+            federated: false,
+            timeout: app.config.timeout,
+        };
+        let job = ExportJob::spawn(
+            &app.store,
+            rows,
+            QueryResultsFormat::Tsv,
+            Values::Raw,
+            Sink::File(path.clone()),
+            None,
+            false,
+        );
+        app.exports.job = Some(job);
+        update_all(
+            &mut app,
+            [Action::Export(QueryResultsFormat::Csv.into(), path.clone())],
+        );
+        assert!(app.status.as_deref().unwrap().starts_with("An export to"));
+        assert!(text(&render(&mut app)).contains("Exporting "));
+
+        update_all(&mut app, [Action::Quit]);
+        assert!(app.confirmation.is_some());
+        assert!(!app.quitting);
+        update_all(&mut app, [Action::Confirm]);
+        assert!(app.quitting);
+        assert!(app.exports.job.is_none());
+        assert!(!path.exists());
+        assert!(!partial_path(&path).exists());
+    }
+
+    #[test]
+    fn markdown_export_writes_the_shown_cells() {
+        let path = std::env::temp_dir().join(format!("rdf-tui-{}.md", std::process::id()));
+        let mut app = app_with_rows();
+        update_all(
+            &mut app,
+            [Action::Export(ExportFormat::Markdown, path.clone())],
+        );
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Exported 3 rows"));
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("| ------------- |"));
+        assert!(lines[1].ends_with("-: |"));
+        assert!(lines[2].starts_with("| <http://ex/c> | <http://ex/p> | \"3\"^^"));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,668 @@
+//! 按鍵之分派：鍵序列經 `crate::keymap` 映射為動作，宏之錄製與重放，及底部命令行之輸入。
+//! 命令之解析見 `crate::command`。
+
+use super::{App, Mode, Popup, RegisterPrompt};
+use crate::{
+    action::Action,
+    command,
+    focus::Focus,
+    macros::{self, Macro, Replay},
+    record::Recorder,
+    rename,
+    yank::Yank,
+};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::{
+    path::absolute,
+    time::{Duration, Instant},
+};
+
+impl App {
+    /// 執行命令行與宏之操作。餘者原樣返回，交下一部分。
+    pub(super) fn update_input(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::OpenCommandLine => {
+                self.command_line.input.clear();
+                self.mode = Mode::Command;
+            }
+            Action::PromptExport => {
+                self.command_line.input = "export ".to_string();
+                self.mode = Mode::Command;
+            }
+            Action::PromptOpen => {
+                self.command_line.input = "open ".to_string();
+                self.mode = Mode::Command;
+            }
+            Action::PromptRename => {
+                self.command_line.input = match rename::at_end(&self.query.string) {
+                    Some(name) => format!("rename ?{name} ?"),
+                    None => "rename ".to_string(),
+                };
+                self.mode = Mode::Command;
+            }
+            Action::ToggleMacroRecording => self.toggle_macro_recording(),
+            Action::RecordMacro(register) => {
+                self.recording = Some(Macro {
+                    register,
+                    actions: vec![],
+                });
+                self.status = Some(format!("Recording @{register} (Ctrl+Q stops)"));
+            }
+            Action::PromptPlayMacro => {
+                let count = self.macro_count.take().unwrap_or(1);
+                self.register_prompt = Some(RegisterPrompt::Play(count));
+                self.status = Some(match count {
+                    1 => "Replay register…".to_string(),
+                    count => format!("Replay register {count} times…"),
+                });
+            }
+            Action::PlayMacro(register, count) => self.play_macro(register, count),
+            Action::ListMacros => self.popup = Some(Popup::Macros),
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 按鍵所致之操作。任意按鍵皆消去警告。
+    pub(super) fn key_action(&mut self, key: KeyEvent) -> Vec<Action> {
+        // 只处理鍵盤按下事件
+        if key.kind != KeyEventKind::Press {
+            return vec![];
+        }
+        self.warning = None;
+        let mode = self.mode;
+        let actions = self.map_key(key);
+        if let Some(recording) = &mut self.recording {
+            recording.actions.extend(
+                actions
+                    .iter()
+                    .filter(|action| !macros::is_control(action))
+                    .cloned(),
+            );
+        }
+        if let Some(recorder) = &mut self.recorder {
+            let cell = self
+                .results
+                .current
+                .as_ref()
+                .and_then(|results| results.table.as_ref())
+                .and_then(|table| {
+                    let row = table.rows.get(self.results.selected_row)?;
+                    row[self
+                        .results
+                        .selected_column
+                        .unwrap_or(row.len().checked_sub(1)?)]
+                    .as_deref()
+                });
+            recorder.key(key, mode, &actions, cell);
+        }
+        actions
+    }
+
+    /// 粘貼所致之操作：查詢中整塊插入，輸入行中逐字輸入而棄換行等控制字符，他處不理。
+    pub(super) fn paste(&mut self, text: String) -> Vec<Action> {
+        // 檢視之浮窗中粘貼者與其值比較，如剪貼板中之文本
+        if let Some(Popup::Inspector(..)) = self.popup {
+            return vec![Action::CompareInspected(text)];
+        }
+        if self.confirmation.is_some() || self.help || self.popup.is_some() {
+            return vec![];
+        }
+        self.warning = None;
+        match self.mode {
+            Mode::Query => vec![Action::Paste(text)],
+            Mode::Command | Mode::Filter | Mode::Edit => text
+                .chars()
+                .filter(|ch| !ch.is_control())
+                .map(Action::InsertChar)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// 自此記所行之操作，見 [`Recorder`]。已排之文件記為其來源。
+    pub fn record(&mut self, mut recorder: Recorder) {
+        for path in self.sources.iter().chain(&self.pending) {
+            recorder.source(&absolute(path).unwrap_or_else(|_| path.clone()));
+        }
+        self.recorder = Some(recorder);
+    }
+
+    /// 將按鍵譯為操作。浮窗與確認提示在時先取按鍵，餘者依當前模式之鍵位。
+    /// 模式已變則棄未成之序列。
+    pub(super) fn map_key(&mut self, key: KeyEvent) -> Vec<Action> {
+        // 值之比較可滾動，`1` 與 `2` 複製其值，餘鍵關之
+        if !self.help && matches!(self.popup, Some(Popup::ValueDiff(_))) {
+            return vec![match key.code {
+                KeyCode::Down | KeyCode::Char('j') => Action::ScrollPopup(1),
+                KeyCode::Up | KeyCode::Char('k') => Action::ScrollPopup(-1),
+                KeyCode::PageDown => Action::ScrollPopup(10),
+                KeyCode::PageUp => Action::ScrollPopup(-10),
+                KeyCode::Char('1') => Action::YankDiffValue(0),
+                KeyCode::Char('2') => Action::YankDiffValue(1),
+                _ => Action::CloseHelp,
+            }];
+        }
+        // 各頁之比較中選頁，`Enter` 示其結果，`c` 比較兩頁，`y` 複製之，餘鍵關之
+        if !self.help && matches!(self.popup, Some(Popup::TabRuns)) {
+            return vec![match key.code {
+                KeyCode::Down | KeyCode::Char('j') => Action::ScrollPopup(1),
+                KeyCode::Up | KeyCode::Char('k') => Action::ScrollPopup(-1),
+                KeyCode::Enter => Action::OpenTabRun,
+                KeyCode::Char('c') => Action::CompareTabRun,
+                KeyCode::Char('y') => Action::YankTabRuns,
+                _ => Action::CloseHelp,
+            }];
+        }
+        // 引導中 `s` 載入所附之例，餘鍵關之
+        if !self.help && matches!(self.popup, Some(Popup::Onboarding)) {
+            return vec![match key.code {
+                KeyCode::Char('s') => Action::LoadSample,
+                _ => Action::DismissOnboarding,
+            }];
+        }
+        // 幫助與列統計之浮窗任意鍵關閉
+        if self.help || self.popup.is_some() {
+            return vec![Action::CloseHelp];
+        }
+        // 寄存器為一字母或數字，餘鍵取消
+        if let Some(prompt) = self.register_prompt.take() {
+            self.status = None;
+            return match key.code {
+                KeyCode::Char(ch) if ch.is_ascii_alphanumeric() => vec![match prompt {
+                    RegisterPrompt::Record => Action::RecordMacro(ch),
+                    RegisterPrompt::Play(count) => Action::PlayMacro(ch, count),
+                }],
+                _ => vec![],
+            };
+        }
+        // 複製之菜單：一鍵選其形式，`y` 為默認者，餘鍵取消
+        if std::mem::take(&mut self.yank_prompt) {
+            self.status = None;
+            return match key.code {
+                KeyCode::Char('y') => vec![Action::YankAs(self.config.yank)],
+                KeyCode::Char(ch) => Yank::from_key(ch).map(Action::YankAs).into_iter().collect(),
+                _ => vec![],
+            };
+        }
+        // 確認提示：`y` 或 Ctrl+C 執行，另一選擇之鍵執行之，餘鍵取消
+        if let Some(confirmation) = &self.confirmation {
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            let alternative = confirmation
+                .alternative
+                .as_ref()
+                .is_some_and(|(ch, _)| key.code == KeyCode::Char(*ch));
+            return vec![if key.code == KeyCode::Char('y') || ctrl_c {
+                Action::Confirm
+            } else if alternative {
+                Action::ConfirmAlternative
+            } else {
+                Action::Cancel
+            }];
+        }
+        // 以例查詢：上下選列，左右或空格換取法，Enter 插入，Esc 取消
+        if self.example.is_some() {
+            return match key.code {
+                KeyCode::Up | KeyCode::Char('k') => vec![Action::ScrollRows(-1)],
+                KeyCode::Down | KeyCode::Char('j') => vec![Action::ScrollRows(1)],
+                KeyCode::Left | KeyCode::Char('h') => vec![Action::ScrollColumns(-1)],
+                KeyCode::Right | KeyCode::Char('l' | ' ') => vec![Action::ScrollColumns(1)],
+                KeyCode::Enter => vec![Action::Activate],
+                KeyCode::Esc => vec![Action::Cancel],
+                _ => vec![],
+            };
+        }
+        if self
+            .pending_since
+            .is_some_and(|(_, mode)| mode != self.mode)
+        {
+            self.pending_keys.clear();
+        }
+        let actions = self
+            .config
+            .keymap
+            .map_key(self.mode, &mut self.pending_keys, key);
+        self.pending_since = (!self.pending_keys.is_empty()).then(|| (Instant::now(), self.mode));
+        // 數字積為重放之次數，如 `3@a`。綁定之數字如 `1` 仍行其操作，唯其後之數字積之
+        let digit = match key.code {
+            KeyCode::Char(ch) if key.modifiers - KeyModifiers::SHIFT == KeyModifiers::NONE => {
+                ch.to_digit(10)
+            }
+            _ => None,
+        };
+        match digit {
+            Some(digit)
+                if self.pending_keys.is_empty()
+                    && matches!(
+                        self.mode,
+                        Mode::Browse | Mode::Hierarchy | Mode::Neighborhood
+                    )
+                    && (digit > 0 || self.macro_count.is_some()) =>
+            {
+                let bound = self.macro_count.is_none() && !actions.is_empty();
+                let count = self.macro_count.unwrap_or(0);
+                self.macro_count = Some(count.saturating_mul(10).saturating_add(digit as usize));
+                if !bound {
+                    return vec![];
+                }
+            }
+            _ if actions == [Action::PromptPlayMacro] || !self.pending_keys.is_empty() => {}
+            _ => self.macro_count = None,
+        }
+        // 鍵位依模式；預覽有焦點則上下鍵捲之，表頭上 Enter 排序
+        match self.focus() {
+            Focus::ResultsSecondary => actions
+                .into_iter()
+                .map(|action| match action {
+                    Action::ScrollRows(delta) => Action::ScrollPreview(delta),
+                    action => action,
+                })
+                .collect(),
+            Focus::ResultsPrimary if self.results.on_header => actions
+                .into_iter()
+                .map(|action| match action {
+                    Action::ShowDetails => Action::ToggleSort,
+                    action => action,
+                })
+                .collect(),
+            Focus::ResultsPrimary if self.results.on_footer => actions
+                .into_iter()
+                .map(|action| match action {
+                    Action::ShowDetails => Action::ShowColumnTypes,
+                    action => action,
+                })
+                .collect(),
+            _ => actions,
+        }
+    }
+
+    /// 未成之序列逾 `SEQUENCE_TIMEOUT` 則了結之：恰為一綁定，如 `d d` 之 `d`，則返回其操作，
+    /// 否則棄之。模式已變亦棄之。
+    pub(super) fn expire_keys(&mut self) -> Option<Action> {
+        let (since, mode) = self.pending_since?;
+        if mode == self.mode && since.elapsed() < SEQUENCE_TIMEOUT {
+            return None;
+        }
+        self.pending_since = None;
+        let pending = std::mem::take(&mut self.pending_keys);
+        (mode == self.mode)
+            .then(|| self.config.keymap.resolve(mode, &pending))
+            .flatten()
+    }
+
+    /// 命令行模式下之操作。返回未處理者。
+    pub(super) fn update_in_command_mode(
+        &mut self,
+        action: Action,
+    ) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::InsertChar(ch) => self.command_line.push(ch),
+            // 空行退格則退出命令行
+            Action::DeleteBackward => {
+                if self.command_line.pop().is_none() {
+                    self.mode = Mode::Browse;
+                }
+            }
+            Action::Complete => {
+                let candidates = self.command_line.complete();
+                self.status = (!candidates.is_empty()).then(|| candidates.join("  "));
+            }
+            Action::ListCompletions => {
+                let matches = self.command_line.matches();
+                if matches.is_empty() {
+                    self.status = Some("No matches".to_string());
+                } else {
+                    self.popup = Some(Popup::Completions(matches));
+                }
+            }
+            Action::Activate => {
+                self.mode = Mode::Browse;
+                let line = std::mem::take(&mut self.command_line.input);
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.command(&line);
+                }
+                match command::parse(&line) {
+                    Ok(action) => self.update(action)?,
+                    Err(error) if self.replay.is_some() => self.stop_replay(&error),
+                    Err(error) => self.status = Some(error),
+                }
+            }
+            Action::Back => {
+                self.command_line.input.clear();
+                self.mode = Mode::Browse;
+            }
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 始錄則問寄存器；錄製中則止而存之。無所錄則清其寄存器。
+    pub(super) fn toggle_macro_recording(&mut self) {
+        let Some(recorded) = self.recording.take() else {
+            self.register_prompt = Some(RegisterPrompt::Record);
+            self.status = Some("Record into register (a-z, 0-9)…".to_string());
+            return;
+        };
+        let (register, len) = (recorded.register, recorded.actions.len());
+        self.macros.set(recorded);
+        self.status = Some(match len {
+            0 => format!("Cleared @{register}"),
+            1 => format!("Recorded @{register}: 1 action"),
+            len => format!("Recorded @{register}: {len} actions"),
+        });
+    }
+
+    /// 重放一宏若干次。錄製中不可，以免所錄與所行相左。
+    pub(super) fn play_macro(&mut self, register: char, count: usize) {
+        if self.recording.is_some() {
+            self.status = Some("Stop recording (Ctrl+Q) before replaying a macro".to_string());
+            return;
+        }
+        match self.macros.get(register) {
+            Some(recorded) => self.replay = Some(Replay::new(recorded, count)),
+            None => self.status = Some(format!("Register @{register} is empty")),
+        }
+    }
+
+    /// 行重放中之宏，至其完、須待查詢、導出或載入為止。出錯、所起之查詢失敗，
+    /// 或確認提示出現而所錄者未答之，則中止。
+    pub(super) fn replay_macro(&mut self) -> anyhow::Result<()> {
+        while let Some(replay) = &mut self.replay {
+            let awaited = std::mem::take(&mut replay.awaiting_query);
+            if awaited
+                && self
+                    .results
+                    .current
+                    .as_ref()
+                    .is_some_and(|r| r.error.is_some())
+            {
+                self.stop_replay("the query failed");
+                break;
+            }
+            if self.confirmation.is_some() && !replay.answers_prompt() {
+                self.stop_replay("a confirmation is waiting");
+                break;
+            }
+            let Some(action) = replay.actions.pop_front() else {
+                self.status = Some(format!("Replayed @{}", replay.register));
+                self.replay = None;
+                break;
+            };
+            if let Err(error) = self.update(action) {
+                self.stop_replay(&format!("{error:#}"));
+                return Err(error);
+            }
+            if let Some(replay) = self.replay.as_mut().filter(|_| self.running.is_some()) {
+                replay.awaiting_query = true;
+            }
+            if self.quitting
+                || self.running.is_some()
+                || self.exports.job.is_some()
+                || self.loading.is_some()
+                || !self.pending.is_empty()
+            {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// 中止重放，示其故。
+    pub(super) fn stop_replay(&mut self, reason: &str) {
+        if let Some(replay) = self.replay.take() {
+            self.status = Some(format!("Macro @{} stopped: {reason}", replay.register));
+        }
+    }
+}
+
+/// 按鍵序列兩鍵間之最長等待。
+pub(super) const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[cfg(test)]
+mod tests {
+    use super::{SEQUENCE_TIMEOUT, *};
+    use crate::app::tests::{app_with_rows, render, rows, text, type_text, update_all};
+    use crate::script::Step;
+    use ratatui::crossterm::event::Event;
+
+    use ratatui::crossterm::event::KeyEvent;
+    use std::fs;
+
+    #[test]
+    fn command_line_runs_the_parsed_command() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::OpenCommandLine]);
+        assert_eq!(app.mode, Mode::Command);
+        type_text(&mut app, "limit 1");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(app.limit, Some(1));
+        // 詢問後方重新查詢
+        assert_eq!(rows(&app), 3);
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(rows(&app), 1);
+        assert!(app.results.table().unwrap().truncated.is_some());
+    }
+
+    #[test]
+    fn a_recorded_session_replays_to_the_same_state() {
+        let path = std::env::temp_dir().join(format!("rdf-tui-replay-{}.tsv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let press = |app: &mut App, key: KeyEvent| {
+            let actions = app.key_action(key);
+            update_all(app, actions);
+        };
+        let mut app = app_with_rows();
+        app.record(Recorder::create(&path, false).unwrap());
+        let keys = [
+            KeyCode::Char('j'),
+            KeyCode::Char('K'),
+            KeyCode::Right,
+            KeyCode::Right,
+            KeyCode::Enter,
+            KeyCode::Enter,
+        ];
+        for code in keys {
+            press(&mut app, KeyEvent::from(code));
+        }
+        let transcript = fs::read_to_string(&path).unwrap();
+        assert!(transcript.contains("\tkey\tK\tBrowse\tSelectHeader\t#"));
+        assert!(transcript.contains("\tkey\tenter\tBrowse\tToggleSort\t"));
+
+        let mut replayed = app_with_rows();
+        for step in crate::record::parse(&transcript).unwrap().steps {
+            let Step::Key(key) = step else { panic!() };
+            press(&mut replayed, key);
+        }
+        assert_eq!(replayed.status, app.status);
+        assert_eq!(replayed.results.selected_column, Some(2));
+        assert_eq!(
+            replayed.results.table().unwrap().rows,
+            app.results.table().unwrap().rows
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn macros_replay_recorded_actions_and_stop_at_prompts() {
+        let mut app = app_with_rows();
+        let press = |app: &mut App, code, modifiers| {
+            let key = Event::Key(KeyEvent::new(code, modifiers));
+            app.handle_event(&key).unwrap();
+        };
+        let keys = |app: &mut App, keys: &str| {
+            for ch in keys.chars() {
+                press(app, KeyCode::Char(ch), KeyModifiers::NONE);
+            }
+        };
+        press(&mut app, KeyCode::Char('q'), KeyModifiers::CONTROL);
+        keys(&mut app, "aj");
+        assert!(text(&render(&mut app)).contains("recording @a · 1"));
+        press(&mut app, KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert_eq!(app.status.as_deref(), Some("Recorded @a: 1 action"));
+        assert_eq!(
+            app.macros.get('a').unwrap().actions,
+            [Action::ScrollRows(1)]
+        );
+        assert_eq!(app.results.selected_row, 1);
+
+        press(&mut app, KeyCode::Home, KeyModifiers::NONE);
+        keys(&mut app, "2@a");
+        assert_eq!(app.results.selected_row, 2);
+        assert_eq!(app.status.as_deref(), Some("Replayed @a"));
+
+        // 確認提示非所錄者所答，則止於其前
+        app.macros.set(Macro {
+            register: 'b',
+            actions: vec![
+                Action::ToggleMark,
+                Action::DeleteMarked,
+                Action::ScrollRows(-1),
+            ],
+        });
+        keys(&mut app, "@b");
+        assert!(app.confirmation.is_some());
+        assert_eq!(app.results.selected_row, 2);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Macro @b stopped: a confirmation is waiting")
+        );
+        update_all(&mut app, [Action::Cancel]);
+
+        app.query.set("SELECT WHERE".to_string());
+        app.macros.set(Macro {
+            register: 'c',
+            actions: vec![Action::RunQuery, Action::ScrollRows(-1)],
+        });
+        keys(&mut app, "@c");
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Macro @c stopped: the query failed")
+        );
+
+        update_all(&mut app, [Action::ListMacros]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("@a select_next"), "{screen}");
+        assert!(
+            screen.contains("@b toggle_mark delete_marked select_previous"),
+            "{screen}"
+        );
+    }
+
+    #[test]
+    fn legacy_terminals_get_fallback_keys() {
+        use crate::keymap::{parse_sequence, Keymap, Lookup};
+        let chord = |key| parse_sequence(key).unwrap();
+        let mut keymap = Keymap::default();
+        keymap.bind(Mode::Query, Action::RunQuery, vec![chord("ctrl-enter")]);
+        keymap.bind(Mode::Browse, Action::ShowHelp, vec![chord("g ctrl-/")]);
+        keymap.add_fallbacks();
+        assert_eq!(
+            keymap.lookup(Mode::Query, &chord("f5")),
+            Lookup::Action(Action::RunQuery)
+        );
+        assert!(keymap
+            .hints(Mode::Query)
+            .contains(&("f5".to_string(), "run")));
+        assert_eq!(
+            keymap.lookup(Mode::Browse, &chord("g ctrl-7")),
+            Lookup::Action(Action::ShowHelp)
+        );
+        assert_eq!(
+            keymap.lookup(Mode::Browse, &chord("g ctrl-/")),
+            Lookup::Action(Action::ShowHelp)
+        );
+    }
+
+    #[test]
+    fn ambiguous_sequences_wait_for_the_next_key_or_the_timeout() {
+        let key = |ch| KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE);
+        let mut app = app_with_rows();
+        assert_eq!(app.map_key(key('d')), []);
+        assert!(text(&render(&mut app)).contains("d "));
+        assert_eq!(app.map_key(key('d')), [Action::DeleteMarked]);
+        assert_eq!(app.map_key(key('d')), []);
+        assert_eq!(
+            app.map_key(key('j')),
+            [Action::ShowDetails, Action::ScrollRows(1)]
+        );
+        assert_eq!(app.map_key(key(' ')), []);
+        assert_eq!(app.map_key(key('e')), [Action::PromptExport]);
+
+        // Esc 棄之
+        app.map_key(key('d'));
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.map_key(esc), []);
+        assert!(app.pending_keys.is_empty());
+
+        // 逾時則執行其前段；模式已變則棄之
+        app.map_key(key('d'));
+        assert_eq!(app.expire_keys(), None);
+        app.pending_since = Some((Instant::now() - SEQUENCE_TIMEOUT, Mode::Browse));
+        assert_eq!(app.expire_keys(), Some(Action::ShowDetails));
+        app.map_key(key('d'));
+        app.mode = Mode::Query;
+        assert_eq!(app.expire_keys(), None);
+        assert!(app.pending_keys.is_empty());
+    }
+
+    #[test]
+    fn keys_map_to_actions() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = app_with_rows();
+        assert_eq!(
+            app.map_key(key(KeyCode::Char('j'))),
+            [Action::ScrollRows(1)]
+        );
+        assert_eq!(app.map_key(key(KeyCode::Char('g'))), []);
+        assert_eq!(app.map_key(key(KeyCode::Char('g'))), [Action::SelectFirst]);
+        // 未成之序列以末鍵重新查找
+        assert_eq!(app.map_key(key(KeyCode::Char('g'))), []);
+        assert_eq!(app.map_key(key(KeyCode::Char('q'))), [Action::Quit]);
+        assert_eq!(
+            app.map_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            [Action::Quit]
+        );
+
+        app.mode = Mode::Query;
+        assert_eq!(
+            app.map_key(key(KeyCode::Char('q'))),
+            [Action::InsertChar('q')]
+        );
+
+        app.help = true;
+        assert_eq!(app.map_key(key(KeyCode::Char('q'))), [Action::CloseHelp]);
+    }
+
+    #[test]
+    fn path_prompts_complete_and_warn_before_overwriting() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-prompt-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for file in ["out.csv", "out.tsv", "other.csv"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::PromptExport]);
+        type_text(&mut app, &format!("csv {}/o", dir.display()));
+        update_all(&mut app, [Action::Complete]);
+        // 僅 CSV 文件為候選
+        assert_eq!(
+            app.command_line.input,
+            format!("export csv {}/o", dir.display())
+        );
+        assert_eq!(app.status.as_deref(), Some("other.csv  out.csv"));
+        type_text(&mut app, "u");
+        update_all(&mut app, [Action::Complete]);
+        assert!(text(&render(&mut app)).contains("out.csv  [overwrites]"));
+
+        update_all(&mut app, (0..7).map(|_| Action::DeleteBackward));
+        update_all(&mut app, [Action::ListCompletions]);
+        assert!(text(&render(&mut app)).contains("Matches"));
+        update_all(&mut app, [Action::CloseHelp]);
+        assert_eq!(app.mode, Mode::Command);
+        type_text(&mut app, "new.csv");
+        assert!(text(&render(&mut app)).contains("new.csv  [new file]"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
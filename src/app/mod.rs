@@ -0,0 +1,1468 @@
+//! 應用：[`App`] 合諸部分，並行事件、動作與更新之循環。餘者按其所管分於子模塊，
+//! 皆為 `App` 之方法；新功能宜入其所屬之模塊，而非於此。
+//!
+//! 鍵位、命令、配置與路徑本身另見 `crate::keymap`、`crate::command`、`crate::config`
+//! 與 `crate::paths`。
+
+/// 查詢之字串與其輸入。
+mod editor;
+/// 導出。
+mod exports;
+/// 按鍵、宏與命令行。
+mod input;
+/// 結果表以外之面板。
+mod panels;
+/// 各窗格之繪製。
+mod render;
+/// 結果表、所選、排序與過濾。
+mod results;
+/// 會話、庫與分享連結之存取。
+mod session;
+/// 設置與配置之應用。
+mod setup;
+/// 查詢、載入與改值等與存儲之往來。
+mod store_worker;
+/// 標籤頁。
+mod tabs;
+
+use crate::{
+    action::Action,
+    aggregate::ColumnStats,
+    backend::{self, StoreWorker},
+    bookmarks::{Bookmarks, View},
+    chardiff::Op,
+    checkpoint::{Checkpoint, Checkpoints},
+    command::CommandLine,
+    config::Config,
+    count::Count,
+    dataset::Dataset,
+    diff::Partition,
+    download::Download,
+    edit::{Edit, Update},
+    example::Example,
+    export::{Declared, Rows},
+    focus::{self, Focus},
+    generation::{Cached, Generation},
+    graphs::GraphJob,
+    hierarchy::Hierarchy,
+    history::History,
+    keymap::KeyChord,
+    label::{LabelCache, Prefetch},
+    layout::{self, Pane, Preset},
+    linear,
+    link::Link,
+    locale::Language,
+    macros::{Macro, Macros, Replay},
+    memory,
+    metrics::Metrics,
+    neighborhood::Neighborhood,
+    paths::Paths,
+    pattern::PatternForm,
+    prepared::Prepared,
+    provenance::Provenance,
+    recent::Recent,
+    record::Recorder,
+    refresh::Refresh,
+    renderer::Renderers,
+    scratchpad::Scratchpad,
+    script::Playback,
+    server::Server,
+    session::{Restore, Session},
+    signal::{take_suspend_request, terminate_requested},
+    theme::Theme,
+    types::ColumnTypes,
+    util::{copy_to_clipboard, set_title},
+    watch::Watch,
+    worker::{TermRow, Worker},
+};
+use anyhow::Context;
+use oxigraph::{io::RdfFormat, model::Term, store::Store};
+use ratatui::{
+    backend::Backend,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    Terminal,
+};
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    io::Write,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+pub use editor::Query;
+pub use results::ResultTable;
+
+use exports::Exports;
+use render::wrapped_height;
+use results::{mark_matches, Progress, ResultSet, Results, Source};
+use store_worker::details_query;
+use tabs::{tab_name, Tabs};
+
+/// 應用程序之總體名理。
+///
+/// 獨立運行時以 [`App::run`] 接管終端。嵌入他應用時，由宿主各幀調用
+/// [`App::render`] 繪於一區域，並以 [`App::handle_event`] 或
+/// [`App::handle_action`] 轉交輸入，[`App::should_quit`] 為真時收起之。
+pub struct App {
+    /// 存儲之快照，供查詢與讀取。寫入皆經 `backend`。
+    store: Store,
+    /// 當前頁之存儲線程。載入、更新、清空、恢復與重新載入皆交之。
+    backend: StoreWorker,
+    /// 下一送予存儲線程之命令之號。
+    next_store_id: backend::Id,
+    mode: Mode,
+    query: Query,
+    quitting: bool,
+    /// 以 `ForceQuit` 退出，未經確認。
+    forced: bool,
+    /// 是否允許 SERVICE 聯邦查詢。
+    federated: bool,
+    /// 查詢之默認圖，隨會話保存。
+    dataset: Dataset,
+    /// 結果及其所選之行列與過濾。
+    results: Results,
+    /// 後台執行中之查詢，其行陸續併入 `results`。
+    running: Option<Worker>,
+    /// 已解析之查詢，監視與反復執行時免再解析。
+    prepared: Prepared,
+    /// 載入後追蹤 `owl:imports` 之層數。
+    follow_imports: Option<usize>,
+    /// 已嘗試導入之本體 IRI。
+    visited_imports: HashSet<String>,
+    /// 狀態欄訊息。
+    status: Option<String>,
+    /// 標籤之首選語言。
+    lang: Option<String>,
+    /// 界面之語言。
+    language: Language,
+    /// 存儲之代數，每次修改遞增。結果、列統計與層級、鄰域諸樹記其所算之代數，過時則重算。
+    generation: Generation,
+    /// 以線性之文字代表格與框線，為讀屏者。
+    linear: bool,
+    /// 繪製一部件時之 panic 是否止於該部件。否則如常結束會話，供開發。
+    contain_panics: bool,
+    /// 窗格之佈局預設。
+    layout: Preset,
+    /// 層級樹。進入層級模式時建立。
+    hierarchy: Option<Cached<Hierarchy>>,
+    /// 鄰域樹。進入鄰域模式時建立。
+    neighborhood: Option<Cached<Neighborhood>>,
+    /// 後台重算中之層級樹與鄰域樹，見 [`Self::refresh_stale`]。換頁則棄之。
+    hierarchy_refresh: Option<Refresh<Hierarchy>>,
+    neighborhood_refresh: Option<Refresh<Neighborhood>>,
+    /// 所請求載入之文件，含失敗者，以供重載。
+    sources: Vec<PathBuf>,
+    /// 命令行所給之數據及其格式，以供重載。
+    inline: Vec<(String, RdfFormat)>,
+    /// 待載入之文件。於首次繪製後方載入，以便錯誤可於界面中顯示。
+    pending: VecDeque<PathBuf>,
+    /// 所導入之結果文件。有則表不出自存儲，編輯器停用；執行查詢則棄之。
+    imported: Option<PathBuf>,
+    /// N3 之公式與規則是否使載入失敗。否則略去之並警告。
+    strict_n3: bool,
+    /// 所載入之文件所聲明之前綴，導出 RDF 時與配置之前綴同用。
+    declared: Declared,
+    /// 是否禁止改動存儲，如改值與清空。載入不在此限。
+    read_only: bool,
+    /// 破壞性操作前寫檢查點之處。無則不寫。
+    checkpoints: Option<Checkpoints>,
+    /// 配置與狀態之所在。
+    paths: Paths,
+    /// 方才所寫之檢查點，待附註於狀態欄。
+    checkpoint: Option<PathBuf>,
+    /// 已確認不寫檢查點而行。
+    unchecked: bool,
+    /// 並行載入之線程數。為 1 則逐一載入。
+    jobs: usize,
+    /// 系統可用之內存。不知則載入大文件前不問。
+    available_memory: Option<usize>,
+    /// 存儲線程載入或重新載入中之文件。
+    loading: Option<Loading>,
+    /// 建立之時，首幀繪後記其耗時而清之。
+    created: Option<Instant>,
+    /// 後台執行中之圖操作。
+    graph_job: Option<GraphJob>,
+    /// 導出之作業與設置。
+    exports: Exports,
+    /// 後台下載中之 URL。同時僅一，餘者待之，下載畢則排入載入。
+    download: Option<Download>,
+    downloads: VecDeque<String>,
+    /// 已下載之文件之緩存路徑及其 URL，以分享 URL 而非本機之緩存。
+    downloaded: HashMap<PathBuf, String>,
+    /// 每 URL 至多下載之字節數。
+    max_download: Option<u64>,
+    /// 後台執行中之計數。
+    count: Option<Count>,
+    /// 後台數入邊者及其資源。
+    incoming_count: Option<(Count, Term)>,
+    /// 按謂語所計之入邊之查詢及其資源，Enter 展開所選之謂語。
+    grouped_incoming: Option<(String, Term)>,
+    /// 依數據類型之自定顯示。
+    renderers: Renderers,
+    /// 上次載入之錯誤。
+    load_error: Option<String>,
+    /// 各圖出自何文件。重新載入時更新，存於會話。
+    provenance: Provenance,
+    /// 結果表末是否附各行所在圖之來源。
+    show_origin: bool,
+    /// 結果表中 IRI 示為其標籤。
+    show_labels: bool,
+    /// 已解之標籤，導出所示之單元格時亦用之。
+    labels: LabelCache,
+    /// 後台所解之一批標籤。
+    label_fetch: Option<Prefetch>,
+    /// 上次繪製時可見之行之範圍，其前後之行亦先解其標籤。
+    label_window: Cell<Option<(usize, usize)>>,
+    /// 是否顯示所選格之預覽窗格。
+    preview: bool,
+    /// 預覽所滾之行數，及其所屬之行與列。所選之格變則歸零；繪製時限於內容之內。
+    preview_scroll: Cell<(usize, Option<usize>, u16)>,
+    /// 瀏覽時焦點在預覽窗格。
+    secondary: bool,
+    /// 記所行之操作之處，見 `--record`。
+    recorder: Option<Recorder>,
+    macros: Macros,
+    /// 錄製中之宏。
+    recording: Option<Macro>,
+    /// 待按寄存器之鍵。
+    register_prompt: Option<RegisterPrompt>,
+    /// 複製之菜單開，待選其形式之鍵。
+    yank_prompt: bool,
+    /// `@` 之前所按之數字，為重放之次數。
+    macro_count: Option<usize>,
+    /// 重放中之宏。
+    replay: Option<Replay>,
+    bookmarks: Bookmarks,
+    /// 書籤面板中所選之項。
+    selected_bookmark: usize,
+    scratchpad: Scratchpad,
+    /// 便箋面板中所選之項。
+    selected_pin: usize,
+    /// 打開便箋面板前之模式，關閉時返回之。
+    scratchpad_from: Mode,
+    /// 最近打開之文件，未設存儲之文件則不記。
+    recent: Recent,
+    /// 最近文件之列表中所選之項。
+    selected_recent: usize,
+    /// 本會話所執行之查詢及其結果之快照。
+    history: History,
+    /// 查詢歷史中所選之項。
+    selected_history: usize,
+    /// 待經終端複製至剪貼板之文字，由主循環寫出。
+    clipboard: Option<String>,
+    /// 定時重新執行之查詢。
+    watch: Option<Watch>,
+    /// 執行中之刷新，及已到之行。完成後方換入結果，故表不閃爍。
+    watch_run: Option<(Worker, Option<ResultTable>)>,
+    /// 所打開之視圖，待其查詢開始後設其顯示。
+    pending_view: Option<View>,
+    /// 所打開之鏈接，及其不能恢復者。待其文件載入後方執行其查詢。
+    pending_link: Option<(Link, Vec<String>)>,
+    /// 調試之度量。首次開浮層或以 `--debug-overlay` 啓動後方收之，否則諸鈎不作。
+    metrics: Option<Metrics>,
+    /// 示調試浮層。
+    debug: bool,
+    /// 存儲之四元組數及其所計之代數。計之須遍歷存儲，故存儲改後方重計。
+    quads: Cell<Option<(Generation, usize)>>,
+    /// 將使內存逾預算之一批行，及迄今各項之估計字節數，待答是否就此限行數。
+    held_rows: Option<(Vec<TermRow>, usize)>,
+    /// 此查詢已允逾內存預算。
+    over_budget: bool,
+    command_line: CommandLine,
+    /// 所改之值。
+    edit: Option<Edit>,
+    /// 改值之輸入行。
+    edit_line: CommandLine,
+    /// 詳情之查詢及其資源，以知詳情中各行之主語。
+    details: Option<(String, Term)>,
+    /// 待確認之更新。
+    pending_update: Option<Update>,
+    /// 本會話中已執行之更新及其時，新者居後。
+    recent_updates: VecDeque<(u64, Update)>,
+    /// 本會話中可撤銷之修改，舊者居前。清空存儲則棄之。
+    undo: Vec<Update>,
+    /// 任意鍵即關之浮窗。
+    popup: Option<Popup>,
+    /// 是否顯示幫助浮窗。
+    help: bool,
+    /// 顯示之行數上限。
+    limit: Option<usize>,
+    /// 待確認之操作。
+    confirmation: Option<Confirmation>,
+    /// 以例查詢之各列取法，選定前截其按鍵。
+    example: Option<Example>,
+    config: Config,
+    /// 警告，如配置中之未知鍵。顯示於狀態欄，按鍵後消去。
+    warning: Option<String>,
+    /// 已按而未成綁定之按鍵序列。
+    pending_keys: Vec<KeyChord>,
+    /// 序列末鍵之時刻，及其時之模式。
+    pending_since: Option<(Instant, Mode)>,
+    theme: Theme,
+    /// 已請求掛起。
+    suspending: bool,
+    /// 終端標題。載入後更新，由主循環設置。
+    window_title: Option<String>,
+    /// 播放中之腳本。播放時其按鍵代替鍵盤。
+    script: Option<Playback>,
+    /// 首批文件載入後是否恢復會話。
+    restore: Restore,
+    /// 待確認恢復之會話。
+    saved_session: Option<Session>,
+    /// 首批文件載入後是否查找並執行數據集之啓動查詢。
+    startup_query: bool,
+    /// 首批文件載入後是否依數據選默認之查詢。`--query` 所設者不替。
+    default_query: bool,
+    /// 無 LIMIT 而或返回巨量之行之查詢，加 LIMIT 而後行之。
+    guard: bool,
+    /// 首批文件載入後是否執行查詢，即使不自動查詢。
+    run_on_start: bool,
+    /// 對外應答查詢之端點。隨應用丟棄而停。
+    server: Option<Server>,
+    /// 諸標籤頁。
+    tabs: Tabs,
+    pattern_form: PatternForm,
+}
+
+impl App {
+    /// 以空之內存存儲新建。
+    pub fn new() -> anyhow::Result<Self> {
+        let store = Store::new()?;
+        let backend = StoreWorker::spawn(store.clone(), backend::Settings::default());
+        let mode = Mode::Browse;
+        let query = Query::new();
+        let quitting = false;
+        Ok(Self {
+            store,
+            backend,
+            next_store_id: 0,
+            mode,
+            query,
+            quitting,
+            forced: false,
+            federated: false,
+            dataset: Dataset::Default,
+            results: Results::default(),
+            running: None,
+            prepared: Prepared::default(),
+            follow_imports: None,
+            visited_imports: HashSet::new(),
+            status: None,
+            lang: None,
+            language: Language::default(),
+            generation: Generation::default(),
+            linear: false,
+            contain_panics: true,
+            layout: layout::presets(&[]).swap_remove(0),
+            hierarchy: None,
+            neighborhood: None,
+            hierarchy_refresh: None,
+            neighborhood_refresh: None,
+            sources: vec![],
+            inline: vec![],
+            pending: VecDeque::new(),
+            imported: None,
+            strict_n3: false,
+            declared: Declared::default(),
+            read_only: false,
+            checkpoints: None,
+            paths: Paths::default(),
+            checkpoint: None,
+            unchecked: false,
+            jobs: thread::available_parallelism().map_or(1, usize::from),
+            available_memory: memory::available(),
+            loading: None,
+            created: Some(Instant::now()),
+            graph_job: None,
+            exports: Exports::default(),
+            download: None,
+            downloads: VecDeque::new(),
+            downloaded: HashMap::new(),
+            max_download: None,
+            count: None,
+            incoming_count: None,
+            grouped_incoming: None,
+            renderers: Renderers::builtin(),
+            load_error: None,
+            provenance: Provenance::default(),
+            show_origin: false,
+            show_labels: false,
+            labels: LabelCache::default(),
+            label_fetch: None,
+            label_window: Cell::default(),
+            preview: false,
+            preview_scroll: Cell::new((0, None, 0)),
+            secondary: false,
+            recorder: None,
+            macros: Macros::default(),
+            recording: None,
+            register_prompt: None,
+            yank_prompt: false,
+            macro_count: None,
+            replay: None,
+            bookmarks: Bookmarks::default(),
+            selected_bookmark: 0,
+            scratchpad: Scratchpad::default(),
+            selected_pin: 0,
+            scratchpad_from: Mode::Browse,
+            recent: Recent::default(),
+            selected_recent: 0,
+            history: History::default(),
+            selected_history: 0,
+            clipboard: None,
+            pending_view: None,
+            pending_link: None,
+            metrics: None,
+            debug: false,
+            quads: Cell::new(None),
+            held_rows: None,
+            over_budget: false,
+            watch: None,
+            watch_run: None,
+            command_line: CommandLine::default(),
+            edit: None,
+            edit_line: CommandLine::default(),
+            details: None,
+            pending_update: None,
+            recent_updates: VecDeque::new(),
+            undo: vec![],
+            popup: None,
+            help: false,
+            limit: None,
+            confirmation: None,
+            example: None,
+            config: Config::default(),
+            warning: None,
+            pending_keys: vec![],
+            pending_since: None,
+            theme: Theme::default(),
+            suspending: false,
+            window_title: None,
+            script: None,
+            restore: Restore::Never,
+            saved_session: None,
+            startup_query: false,
+            default_query: true,
+            guard: true,
+            run_on_start: false,
+            server: None,
+            tabs: Tabs::new()?,
+            pattern_form: PatternForm::default(),
+        })
+    }
+
+    /// 啓動循環。請求掛起時返回，由調用者掛起後再次調用。
+    pub fn run<B: Backend + Write>(&mut self, terminal: &mut Terminal<B>) -> anyhow::Result<Exit> {
+        // 掛起時標題已還原，故每次進入皆重設
+        let mut shown_title = None;
+        loop {
+            if self.window_title != shown_title {
+                shown_title.clone_from(&self.window_title);
+                if let Some(title) = &shown_title {
+                    set_title(terminal.backend_mut(), title)?;
+                }
+            }
+            if let Some(text) = self.clipboard.take() {
+                copy_to_clipboard(terminal.backend_mut(), &text)?;
+            }
+            self.refresh_results();
+            if self.loading.is_none() {
+                self.start_loading();
+            }
+            if let Some(path) = self.pending.front() {
+                self.status = Some(format!("Loading {}…", path.display()));
+            }
+            self.draw(terminal)?;
+            if let Some(created) = self.created.take() {
+                log::info!("first frame duration_ms={}", created.elapsed().as_millis());
+            }
+            if let Some(path) = self.pending.pop_front() {
+                self.open(path);
+                if self.pending.is_empty() {
+                    self.offer_session();
+                }
+                continue;
+            }
+            if self.loading.is_some() && self.poll_loading() {
+                self.offer_session();
+            }
+            if self.pending.is_empty()
+                && self.loading.is_none()
+                && std::mem::take(&mut self.run_on_start)
+                && self
+                    .results
+                    .current
+                    .as_ref()
+                    .is_none_or(|r| r.query != self.query.string)
+            {
+                self.run_query();
+            }
+            self.poll_event()?;
+            // 退出條件
+            if self.quitting || terminate_requested() {
+                return Ok(Exit::Quit);
+            }
+            if std::mem::take(&mut self.suspending) || take_suspend_request() {
+                return Ok(Exit::Suspend);
+            }
+        }
+    }
+
+    /// 等待並處理事件。所排之文件留待下次繪製後載入，以先示「Loading…」。
+    fn poll_event(&mut self) -> anyhow::Result<()> {
+        // 查詢、導出或載入未完則暫停腳本與宏，以免按鍵作用於半截之結果
+        let busy = self.running.is_some()
+            || self.exports.job.is_some()
+            || self.loading.is_some()
+            || self.download.is_some();
+        if !busy && self.replay.is_some() {
+            self.replay_macro()?;
+        }
+        let busy = busy || self.replay.is_some();
+        if let Some(script) = self.script.as_mut().filter(|_| !busy) {
+            if let Some(key) = script.next_key() {
+                for action in self.key_action(key) {
+                    self.update(action)?;
+                }
+                return Ok(());
+            }
+            if script.is_finished() {
+                let then_interactive = script.then_interactive;
+                self.script = None;
+                if !then_interactive {
+                    self.quit();
+                    return Ok(());
+                }
+            }
+        }
+        if let Some(action) = self.expire_keys() {
+            self.update(action)?;
+        }
+        if event::poll(self.config.tick_rate)? {
+            // 鍵盤事件譯為操作；尺寸變化則記下，下次繪製時重排
+            match event::read()? {
+                // 播放中唯 Ctrl+C 可中止之，餘鍵不理
+                Event::Key(key)
+                    if self.script.is_some()
+                        && key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    self.script = None;
+                    self.quit();
+                }
+                Event::Key(_) if self.script.is_some() => {}
+                // 重放中按任意鍵則中止之
+                Event::Key(key) if key.kind == KeyEventKind::Press && self.replay.is_some() => {
+                    self.stop_replay("interrupted");
+                }
+                Event::Key(key) => {
+                    if let Some(metrics) = &mut self.metrics {
+                        metrics.input(Instant::now(), event::poll(Duration::ZERO)?);
+                    }
+                    for action in self.key_action(key) {
+                        self.update(action)?;
+                    }
+                }
+                Event::Paste(text) if self.script.is_none() => {
+                    for action in self.paste(text) {
+                        self.update(action)?;
+                    }
+                }
+                Event::Resize(width, height) => self.resize(width, height),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 處理宿主轉交之事件。按鍵依鍵位譯為操作，經 [`App::handle_action`] 執行。
+    /// 未成之按鍵序列於其後之事件時方查是否逾時。所起之宏於此重放完畢，並待其查詢。
+    pub fn handle_event(&mut self, event: &Event) -> anyhow::Result<()> {
+        if let Some(action) = self.expire_keys() {
+            self.handle_action(action)?;
+        }
+        match event {
+            Event::Key(key) => {
+                for action in self.key_action(*key) {
+                    self.handle_action(action)?;
+                }
+            }
+            Event::Paste(text) => {
+                for action in self.paste(text.clone()) {
+                    self.handle_action(action)?;
+                }
+            }
+            Event::Resize(width, height) => self.resize(*width, *height),
+            _ => {}
+        }
+        while self.replay.is_some() {
+            self.wait_for_query();
+            self.wait_for_export();
+            self.replay_macro()?;
+            while let Some(path) = self.pending.pop_front() {
+                self.open(path);
+            }
+            self.wait_for_loading();
+        }
+        Ok(())
+    }
+
+    /// 執行一操作。與按鍵所致者同，惟 `Open`、`Reload` 等所排之文件即時載入。
+    pub fn handle_action(&mut self, action: Action) -> anyhow::Result<()> {
+        self.update(action)?;
+        while let Some(path) = self.pending.pop_front() {
+            self.open(path);
+        }
+        self.wait_for_loading();
+        self.apply_link();
+        Ok(())
+    }
+
+    /// 是否已請求退出。嵌入者據此收起之。
+    pub fn should_quit(&self) -> bool {
+        self.quitting
+    }
+
+    /// 是否如常退出：非強退，亦非因信號而止。
+    pub fn quit_normally(&self) -> bool {
+        self.quitting && !self.forced
+    }
+
+    /// 當前模式。
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// 終端尺寸變化。重新夾定所選行；佈局、查詢部分之高度與列寬於繪製時依新尺寸重算。
+    fn resize(&mut self, _width: u16, _height: u16) {
+        let len = self.results.table().map_or(0, |table| table.rows.len());
+        self.results.selected_row = self.results.selected_row.min(len.saturating_sub(1));
+    }
+
+    /// 執行操作，更新狀態。先交當前模式處理，餘者依 [`PARTS`] 之序交各部分。不涉終端。
+    fn update(&mut self, action: Action) -> anyhow::Result<()> {
+        log::debug!("action={action:?}");
+        // 離開瀏覽則預覽失焦點，返回時焦點在結果表
+        if self.mode != Mode::Browse {
+            self.secondary = false;
+        }
+        if self.example.is_some() {
+            self.update_example(action);
+            return Ok(());
+        }
+        let action = match self.mode {
+            Mode::Query => self.update_in_query_mode(action),
+            Mode::Browse => Some(action),
+            Mode::Hierarchy => self.update_in_hierarchy_mode(action)?,
+            Mode::Neighborhood => self.update_in_neighborhood_mode(action),
+            Mode::Bookmarks => self.update_in_bookmarks_mode(action),
+            Mode::Scratchpad => self.update_in_scratchpad_mode(action),
+            Mode::Recent => self.update_in_recent_mode(action),
+            Mode::History => self.update_in_history_mode(action),
+            Mode::Pattern => self.update_in_pattern_mode(action),
+            Mode::Command => self.update_in_command_mode(action)?,
+            Mode::Filter => self.update_in_filter_mode(action),
+            Mode::Edit => self.update_in_edit_mode(action),
+        };
+        let Some(mut action) = action else {
+            return Ok(());
+        };
+        for part in PARTS {
+            match part(self, action)? {
+                Some(rest) => action = rest,
+                None => return Ok(()),
+            }
+        }
+        log::warn!("unhandled action={action:?}");
+        Ok(())
+    }
+
+    /// 執行通用之操作：退出、確認、焦點、佈局等。餘者原樣返回，交下一部分。
+    fn update_app(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::Quit if self.exports.job.is_some() => {
+                self.confirmation = Some(Confirmation {
+                    message: self
+                        .tr("Quit? The running export will be cancelled (y/n)")
+                        .to_string(),
+                    action: Action::ForceQuit,
+                    preview: None,
+                    alternative: None,
+                });
+            }
+            Action::Quit
+                if self.query.is_dirty() || self.tabs.pages.iter().any(|t| t.query.is_dirty()) =>
+            {
+                self.confirmation = Some(Confirmation {
+                    message: self
+                        .tr("Quit? Unsaved query will be lost (y/n)")
+                        .to_string(),
+                    action: Action::ForceQuit,
+                    preview: None,
+                    alternative: None,
+                });
+            }
+            Action::Quit => self.quit(),
+            Action::ForceQuit => {
+                self.forced = true;
+                self.quit();
+            }
+            Action::SwitchMode => self.switch_mode()?,
+            Action::MoveFocus(step) | Action::CycleFocus(step) => {
+                let wrap = matches!(action, Action::CycleFocus(_));
+                let panes = self.panes();
+                self.focus_on(focus::step(&panes, self.focus(), step, wrap))?;
+            }
+            // 僅於其他模式有意義
+            Action::Activate
+            | Action::Back
+            | Action::Delete
+            | Action::CycleRelation
+            | Action::CycleField(_)
+            | Action::ExpandNode
+            | Action::CollapseNode
+            | Action::ListInstances
+            | Action::Complete
+            | Action::ListCompletions
+            | Action::DeleteBackward
+            | Action::ToggleRegex
+            | Action::RefreshPanel
+            | Action::MovePin(_)
+            | Action::Newline
+            | Action::InsertChar(_)
+            | Action::Paste(_) => {}
+            Action::Suspend => self.suspending = true,
+            Action::CloseHelp => {
+                self.help = false;
+                self.popup = None;
+            }
+            Action::Confirm => match self.confirmation.take().map(|c| c.action) {
+                // 確認而退者如常退出
+                Some(Action::ForceQuit) => self.quit(),
+                Some(action) => self.update(action)?,
+                None => {}
+            },
+            Action::Cancel => self.confirmation = None,
+            Action::ConfirmAlternative => {
+                if let Some((_, action)) = self
+                    .confirmation
+                    .take()
+                    .and_then(|confirmation| confirmation.alternative)
+                {
+                    self.update(action)?;
+                }
+            }
+            Action::Overwrite(action) => {
+                self.exports.overwriting = true;
+                let result = self.update(*action);
+                self.exports.overwriting = false;
+                result?;
+            }
+            Action::ExportAs(values, action) => {
+                self.exports.confirmed = Some(values);
+                let result = self.update(*action);
+                self.exports.confirmed = None;
+                result?;
+            }
+            // 樹無線性之讀法
+            action @ (Action::OpenHierarchy
+            | Action::OpenNeighborhood
+            | Action::TogglePreview
+            | Action::ToggleOrigin)
+                if self.linear =>
+            {
+                self.status = Some(match action {
+                    Action::OpenHierarchy => linear::unavailable("The class hierarchy"),
+                    Action::OpenNeighborhood => linear::unavailable("The neighborhood view"),
+                    Action::TogglePreview => {
+                        "Rows show whole values in linear mode; there is no preview".to_string()
+                    }
+                    _ => linear::unavailable("The source column (use :graphs)"),
+                });
+            }
+            Action::WithoutCheckpoint(action) => {
+                self.unchecked = true;
+                let result = self.update(*action);
+                self.unchecked = false;
+                result?;
+            }
+            Action::SetLayout(Some(name)) => match self.preset(&name) {
+                Some(preset) => self.use_layout(preset),
+                None => self.status = Some(format!("No layout named {name}")),
+            },
+            Action::SetLayout(None) => {
+                let names: Vec<String> = layout::presets(&self.config.layouts)
+                    .into_iter()
+                    .map(|preset| match preset.name == self.layout.name {
+                        true => format!("[{}]", preset.name),
+                        false => preset.name,
+                    })
+                    .collect();
+                self.status = Some(format!("Layouts: {}", names.join(" ")));
+            }
+            Action::CycleLayout => {
+                let presets = layout::presets(&self.config.layouts);
+                let next = presets
+                    .iter()
+                    .position(|preset| preset.name == self.layout.name)
+                    .map_or(0, |index| (index + 1) % presets.len());
+                self.use_layout(presets[next].clone());
+            }
+            Action::ToggleDebug => {
+                self.debug = !self.debug;
+                if self.debug && self.metrics.is_none() {
+                    self.metrics = Some(Metrics::new(Instant::now()));
+                    self.status = Some("Collecting debug numbers from now on".to_string());
+                }
+            }
+            Action::LogDebug => match &self.metrics {
+                Some(metrics) => {
+                    log::info!("debug {}", metrics.summary());
+                    self.status = Some(match log::log_enabled!(log::Level::Info) {
+                        true => "Wrote the debug numbers to the log".to_string(),
+                        false => "No log to write to: start with --log-file <path>".to_string(),
+                    });
+                }
+                None => {
+                    self.status = Some(
+                        "No debug numbers yet: open the overlay with F12 or :debug".to_string(),
+                    )
+                }
+            },
+            Action::ShowPaths => self.popup = Some(Popup::Paths),
+            Action::ShowMemory => self.popup = Some(Popup::Memory),
+            Action::ShowHelp => self.help = true,
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 重新執行所顯示之查詢、三元組模式或重複值之查找所需者。
+    fn rerun(&self) -> anyhow::Result<Rows> {
+        let results = self
+            .results
+            .current
+            .as_ref()
+            .context("No result to export")?;
+        Ok(match &results.source {
+            Source::Pattern(pattern) => Rows::Pattern(pattern.clone()),
+            Source::Dupes(dupes) => Rows::Dupes(dupes.clone()),
+            Source::Functional(functional) => Rows::Functional(functional.clone()),
+            Source::Diff(_) => anyhow::bail!("A comparison cannot be re-run"),
+            Source::Query => {
+                let mut query = self.prepared.get(&results.query, None)?;
+                self.dataset.apply(&mut query);
+                Rows::Query {
+                    query: Box::new(query),
+                    federated: self.federated,
+                    timeout: self.config.timeout,
+                }
+            }
+        })
+    }
+
+    /// 切換模式。
+    /// 按鍵所向之部分。
+    pub fn focus(&self) -> Focus {
+        Focus::of(self.mode, self.secondary && self.preview_shown())
+    }
+
+    /// 可見之各部分，自上而下：查詢、結果表或代之之面板、預覽。
+    fn panes(&self) -> Vec<Focus> {
+        let lower = match Focus::of(self.mode, false) {
+            panel @ Focus::SidePanel(_) => panel,
+            _ => Focus::ResultsPrimary,
+        };
+        let mut panes = vec![Focus::QueryEditor, lower];
+        if lower == Focus::ResultsPrimary && self.preview_shown() {
+            panes.push(Focus::ResultsSecondary);
+        }
+        panes
+    }
+
+    /// 焦點移至 `focus`。至查詢如 Tab；至結果表之上下兩部分皆處瀏覽模式。
+    fn focus_on(&mut self, focus: Focus) -> anyhow::Result<()> {
+        if focus == self.focus() {
+            return Ok(());
+        }
+        match focus {
+            Focus::QueryEditor => self.switch_mode()?,
+            Focus::ResultsPrimary | Focus::ResultsSecondary => {
+                self.mode = Mode::Browse;
+                self.secondary = focus == Focus::ResultsSecondary;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 預覽窗格可見。
+    fn preview_shown(&self) -> bool {
+        (self.preview
+            || self
+                .layout
+                .panes
+                .iter()
+                .any(|(pane, _)| *pane == Pane::Preview))
+            && !self.linear
+            && self.results.table().is_some()
+    }
+
+    /// 名為 `name` 之佈局預設，內置或配置者。
+    fn preset(&self, name: &str) -> Option<Preset> {
+        layout::presets(&self.config.layouts)
+            .into_iter()
+            .find(|preset| preset.name == name)
+    }
+
+    fn use_layout(&mut self, preset: Preset) {
+        self.status = Some(format!("Layout: {} ({})", preset.name, preset.spec()));
+        self.layout = preset;
+    }
+
+    fn switch_mode(&mut self) -> anyhow::Result<()> {
+        match self.mode {
+            Mode::Query => {
+                self.mode = Mode::Browse;
+            }
+            Mode::Browse if self.imported.is_some() => {
+                self.status = Some("Imported results have no query (Esc closes them)".to_string());
+            }
+            Mode::Browse
+            | Mode::Hierarchy
+            | Mode::Neighborhood
+            | Mode::Bookmarks
+            | Mode::Scratchpad
+            | Mode::Recent
+            | Mode::History
+            | Mode::Pattern
+            | Mode::Command
+            | Mode::Filter
+            | Mode::Edit => {
+                // 回到查詢則棄非出自查詢之結果，以便重新查詢；比較則復其所比之新結果
+                if self.results.derived() {
+                    self.results.current = match self.results.current.take() {
+                        Some(ResultSet {
+                            query,
+                            source: Source::Diff(view),
+                            generation,
+                            ..
+                        }) => Some(ResultSet {
+                            query,
+                            table: Some(view.table),
+                            error: None,
+                            remote_requests: 0,
+                            guarded: None,
+                            source: Source::Query,
+                            generation,
+                        }),
+                        _ => None,
+                    };
+                }
+                self.mode = Mode::Query
+            }
+        }
+        Ok(())
+    }
+
+    /// 設置退出狀態。
+    fn quit(&mut self) {
+        // 待導出之線程收拾所寫之部分
+        if let Some(job) = self.exports.job.take() {
+            job.cancel();
+            let _ = job.wait();
+        }
+        self.quitting = true;
+    }
+
+    /// 界面文字之譯文。
+    fn tr(&self, text: &'static str) -> &'static str {
+        self.language.tr(text)
+    }
+}
+
+/// 應用之一部分處理操作：所司者執行之，餘者原樣返回。
+type Part = fn(&mut App, Action) -> anyhow::Result<Option<Action>>;
+
+/// 當前模式未取之操作依此序交各部分；前者未取者方至後者，故同一操作之守衛以先後為序。
+const PARTS: [Part; 9] = [
+    App::update_app,
+    App::update_tabs,
+    App::update_results,
+    App::update_editor,
+    App::update_panels,
+    App::update_input,
+    App::update_exports,
+    App::update_session,
+    App::update_store,
+];
+
+/// [`App::run`] 返回之因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Exit {
+    /// 用戶退出或收到終止信號。
+    Quit,
+    /// 掛起至 shell，繼續後再入主循環。
+    Suspend,
+}
+
+/// 存儲為空時結果部分之提示，以別於無結果之查詢與錯誤。
+/// 監視到期而結果未變。
+const WATCH_SKIPPED: &str = "unchanged, skipped refresh";
+
+const NO_DATA: &str =
+    "note: the store is empty — load data with :open or pass a file on the command line";
+
+/// 應用有「瀏覽」、「輸入查詢」、「層級」、「鄰域」、「書籤」、「三元組模式」和「命令行」諸狀態。
+/// 應用根據狀態決定將事件傳到哪裏。
+///
+/// 日後或增模式，故嵌入者匹配時須留通配分支。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Mode {
+    /// 編輯查詢。
+    Query,
+    /// 瀏覽結果表。
+    Browse,
+    /// 瀏覽類或概念之層級樹。
+    Hierarchy,
+    /// 書籤面板。
+    Bookmarks,
+    /// 便箋面板。
+    Scratchpad,
+    /// 瀏覽一資源之鄰域。
+    Neighborhood,
+    /// 最近打開之文件之列表。
+    Recent,
+    /// 本會話之查詢歷史。
+    History,
+    /// 以三元組模式瀏覽。
+    Pattern,
+    /// 底部之命令行。
+    Command,
+    /// 底部之過濾輸入行。
+    Filter,
+    /// 底部之改值輸入行。
+    Edit,
+}
+
+/// 交存儲線程載入之文件，或重新載入。
+struct Loading {
+    /// 所載之文件，依所給之序。
+    paths: Vec<PathBuf>,
+    /// 未畢之載入命令及其文件。
+    waiting: Vec<(backend::Id, PathBuf)>,
+    /// 失敗者之說明。
+    failures: Vec<String>,
+    /// 重新載入則為其命令之號。諸文件載於新存儲，全部成功方換入。
+    reload: Option<backend::Id>,
+    /// 各文件之總字節數。
+    total: u64,
+    /// 始時存儲線程已讀之字節數。
+    read: u64,
+}
+
+impl Loading {
+    fn new(reload: Option<backend::Id>, read: u64) -> Self {
+        Self {
+            paths: vec![],
+            waiting: vec![],
+            failures: vec![],
+            reload,
+            total: 0,
+            read,
+        }
+    }
+
+    /// 記一文件及載之之命令。重新載入者以其命令之號為畢，不逐一待之。
+    fn add(&mut self, id: backend::Id, path: PathBuf) {
+        self.total += fs::metadata(&path).map_or(0, |metadata| metadata.len());
+        if self.reload.is_none() {
+            self.waiting.push((id, path.clone()));
+        }
+        self.paths.push(path);
+    }
+
+    /// 已完成者之數，含失敗者。
+    fn done(&self) -> usize {
+        match self.reload {
+            Some(_) => 0,
+            None => self.paths.len() - self.waiting.len(),
+        }
+    }
+
+    /// 已讀字節之比例，`read` 為存儲線程迄今所讀。
+    fn ratio(&self, read: u64) -> f64 {
+        if self.total == 0 {
+            return self.done() as f64 / self.paths.len().max(1) as f64;
+        }
+        (read.saturating_sub(self.read) as f64 / self.total as f64).min(1.0)
+    }
+}
+
+/// 與同一查詢前次結果之比較。表中依序為新增、未變與刪去之行。
+struct DiffView {
+    partition: Partition,
+    /// 是否列出刪去之行。
+    removed: bool,
+    /// 所比之新結果。離開比較時記之以供下次。
+    table: ResultTable,
+}
+
+/// 待用戶確認之操作。
+struct Confirmation {
+    message: String,
+    action: Action,
+    /// 浮窗中所示之詳情，如更新之文本。
+    preview: Option<String>,
+    /// 另一選擇之鍵及其操作。
+    alternative: Option<(char, Action)>,
+}
+
+/// 任意鍵即關之浮窗。
+enum Popup {
+    /// 列統計：所據之結果之代數、列、標題與統計。
+    ColumnStats(Generation, usize, String, Box<ColumnStats>),
+    /// 本會話中已執行之更新。
+    Updates,
+    /// 檢查點目錄中之檢查點，新者居先。
+    Checkpoints(Vec<Checkpoint>),
+    /// 命令行補全之候選。
+    Completions(Vec<String>),
+    /// 一列各類別之格數：標題與計數。
+    ColumnTypes(String, ColumnTypes),
+    /// 所錄之宏。
+    Macros,
+    /// 配置與狀態之所在。
+    Paths,
+    /// 各大結構之估計內存。
+    Memory,
+    /// 一格之檢視：標題、其項及所粘貼以比較之文本。
+    Inspector(String, Box<Term>, Option<String>),
+    /// 兩格之值之逐字比較。
+    ValueDiff(Box<ValueDiff>),
+    /// 一行之各值。
+    Row(usize),
+    /// `:tabdo run` 於各頁所得之行數。
+    TabRuns,
+    /// 首次啓動之引導。
+    Onboarding,
+}
+
+/// 值之比較之浮窗。
+struct ValueDiff {
+    /// 兩格之所在，如 `row 2 ?label`。
+    labels: [String; 2],
+    values: [String; 2],
+    edits: Vec<(Op, String)>,
+    /// 所滾之行數，限於內容之內。
+    scroll: usize,
+}
+
+/// 待按之寄存器所為。
+#[derive(Debug, Clone, Copy)]
+enum RegisterPrompt {
+    Record,
+    /// 重放若干次。
+    Play(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{action::ExportFormat, command};
+    use oxigraph::{io::RdfParser, sparql::results::QueryResultsFormat};
+    use ratatui::{backend::TestBackend, buffer::Buffer, crossterm::event::KeyEvent};
+
+    /// 以無色配置建應用，並載入一三元組。
+    pub(super) fn monochrome_app() -> App {
+        let mut app = App::new().unwrap();
+        app.configure(Config {
+            no_color: true,
+            ..Config::default()
+        });
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                r#"<http://ex/a> <http://ex/b> "c"@en ."#.as_bytes(),
+            )
+            .unwrap();
+        app
+    }
+
+    pub(super) fn render(app: &mut App) -> Buffer {
+        app.refresh_results();
+        app.wait_for_query();
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        app.draw(&mut terminal).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    pub(super) fn text(buffer: &Buffer) -> String {
+        buffer.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    /// 載入三資源之應用。
+    pub(super) fn app_with_rows() -> App {
+        let mut app = App::new().unwrap();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "<http://ex/a> <http://ex/p> 1 . <http://ex/b> <http://ex/p> 2 . \
+                 <http://ex/c> <http://ex/p> 3 ."
+                    .as_bytes(),
+            )
+            .unwrap();
+        app.refresh_results();
+        app.wait_for_query();
+        app
+    }
+
+    pub(super) fn update_all(app: &mut App, actions: impl IntoIterator<Item = Action>) {
+        for action in actions {
+            app.update(action).unwrap();
+            app.refresh_results();
+            app.wait_for_query();
+        }
+    }
+
+    pub(super) fn type_text(app: &mut App, text: &str) {
+        update_all(app, text.chars().map(Action::InsertChar));
+    }
+
+    pub(super) fn rows(app: &App) -> usize {
+        app.results.table().map_or(0, |table| table.rows.len())
+    }
+
+    #[test]
+    fn quitting_with_an_unsaved_query_asks_first() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::Quit]);
+        assert!(app.quitting);
+
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::SwitchMode]);
+        type_text(&mut app, " ");
+        update_all(&mut app, [Action::SwitchMode, Action::Quit]);
+        assert!(app.confirmation.is_some());
+        update_all(&mut app, [Action::Cancel]);
+        assert!(app.confirmation.is_none());
+        assert!(!app.quitting);
+        update_all(&mut app, [Action::Quit, Action::Confirm]);
+        assert!(app.quitting);
+    }
+
+    #[test]
+    fn focus_moves_between_panes_and_keys_follow_it() {
+        let mut app = app_with_rows();
+        app.configure(Config {
+            no_color: true,
+            ..Config::default()
+        });
+        let press = |app: &mut App, code, modifiers| {
+            let actions = app.key_action(KeyEvent::new(code, modifiers));
+            update_all(app, actions);
+        };
+        update_all(&mut app, [Action::TogglePreview]);
+        assert_eq!(app.focus(), Focus::ResultsPrimary);
+        press(&mut app, KeyCode::Down, KeyModifiers::CONTROL);
+        assert_eq!(app.focus(), Focus::ResultsSecondary);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Preview [FOCUS]"), "{screen}");
+        assert!(!screen.contains("Explore [FOCUS]"));
+        // 上下鍵捲預覽而不移所選之行
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(app.results.selected_row, 0);
+        press(&mut app, KeyCode::Down, KeyModifiers::CONTROL);
+        assert_eq!(app.focus(), Focus::ResultsSecondary);
+
+        press(&mut app, KeyCode::F(6), KeyModifiers::NONE);
+        assert_eq!(app.focus(), Focus::QueryEditor);
+        assert_eq!(app.mode, Mode::Query);
+        press(&mut app, KeyCode::Up, KeyModifiers::CONTROL);
+        assert_eq!(app.focus(), Focus::QueryEditor);
+        press(&mut app, KeyCode::F(6), KeyModifiers::SHIFT);
+        assert_eq!(app.focus(), Focus::ResultsSecondary);
+        // Tab 仍切換查詢與結果表
+        update_all(&mut app, [Action::SwitchMode, Action::SwitchMode]);
+        assert_eq!(app.focus(), Focus::ResultsPrimary);
+
+        update_all(&mut app, [Action::OpenBookmarks]);
+        assert_eq!(app.focus(), Focus::SidePanel(focus::Panel::Bookmarks));
+        assert!(text(&render(&mut app)).contains("Bookmarks [FOCUS]"));
+        press(&mut app, KeyCode::Up, KeyModifiers::CONTROL);
+        assert_eq!(app.focus(), Focus::QueryEditor);
+        assert!(text(&render(&mut app)).contains("Query [FOCUS]"));
+    }
+
+    #[test]
+    fn the_debug_overlay_collects_numbers_only_once_opened() {
+        let mut app = app_with_rows();
+        render(&mut app);
+        assert!(app.metrics.is_none());
+        update_all(&mut app, [Action::LogDebug]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("No debug numbers yet"));
+
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::F(12))),
+            [Action::ToggleDebug]
+        );
+        update_all(&mut app, [Action::ToggleDebug]);
+        render(&mut app);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Debug (F12)"), "{screen}");
+        assert!(screen.contains("cells cache"), "{screen}");
+        let metrics = app.metrics.as_ref().unwrap();
+        assert_eq!(metrics.draw.0.len(), 2);
+        // 三行三列之格，開浮層前已繪而入緩存，故二幀皆中
+        let cells = metrics.caches()[0];
+        assert_eq!((cells.0, cells.1.hits, cells.1.misses), ("cells", 18, 0));
+
+        // 閉之不止所收，再開則見其歷史
+        update_all(&mut app, [Action::ToggleDebug]);
+        assert!(!text(&render(&mut app)).contains("Debug (F12)"));
+        assert_eq!(app.metrics.as_ref().unwrap().draw.0.len(), 3);
+        assert_eq!(command::parse("debug log"), Ok(Action::LogDebug));
+    }
+
+    #[test]
+    fn layout_presets_cycle_with_w_and_arrange_the_side_panes() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::SetLayout(Some("explore".to_string()))]);
+        assert_eq!(app.layout.name, "explore");
+        assert!(app.preview_shown());
+        // 預覽在結果之右，二者同行
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("┐┌Preview"), "{screen}");
+
+        update_all(&mut app, [Action::SetLayout(Some("nowhere".to_string()))]);
+        assert_eq!(app.layout.name, "explore");
+        assert_eq!(app.status.as_deref(), Some("No layout named nowhere"));
+
+        let action = app.map_key(KeyEvent::from(KeyCode::Char('w')));
+        update_all(&mut app, action);
+        assert_eq!(app.layout.name, "monitor");
+        assert!(!app.preview_shown());
+        let screen = text(&render(&mut app));
+        assert!(
+            screen.contains("Statistics") && screen.contains("quads"),
+            "{screen}"
+        );
+
+        // 隱查詢之預設仍於編輯時示之
+        update_all(&mut app, [Action::SetLayout(Some("triage".to_string()))]);
+        assert!(!text(&render(&mut app)).contains("Query"));
+        update_all(&mut app, [Action::SetLayout(None)]);
+        assert!(app.status.as_deref().unwrap().contains("[triage]"));
+        update_all(&mut app, (0..3).map(|_| Action::CycleLayout));
+        assert_eq!(app.layout.name, "default");
+    }
+
+    #[test]
+    fn paths_lists_where_state_is_kept() {
+        let mut app = app_with_rows();
+        app.set_paths(Paths::new(
+            Some(PathBuf::from("/cfg")),
+            Some(PathBuf::from("/state")),
+            None,
+        ));
+        update_all(&mut app, [Action::ShowPaths]);
+        let screen = text(&render(&mut app));
+        assert!(
+            screen.contains("config       /cfg/config.toml (not yet created)"),
+            "{screen}"
+        );
+        assert!(screen.contains("/state/recent.tsv"), "{screen}");
+        assert!(screen.contains("none: no files loaded"), "{screen}");
+        assert!(screen.contains("off: give --checkpoint-dir"), "{screen}");
+    }
+
+    /// 任意字節解作按鍵，經鍵位與操作，並繪製之，不應 panic。
+    #[test]
+    fn arbitrary_input_never_panics() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let special = [
+            KeyCode::Enter,
+            KeyCode::Esc,
+            KeyCode::Tab,
+            KeyCode::BackTab,
+            KeyCode::Backspace,
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Home,
+            KeyCode::End,
+        ];
+        let modifiers = [
+            KeyModifiers::NONE,
+            KeyModifiers::SHIFT,
+            KeyModifiers::ALT,
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        ];
+        for seed in 0..32 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut app = app_with_rows();
+            let bytes: Vec<u8> = (0..512).map(|_| rng.gen()).collect();
+            for ch in String::from_utf8_lossy(&bytes).chars() {
+                let code = if rng.gen_bool(0.2) {
+                    special[rng.gen_range(0..special.len())]
+                } else {
+                    KeyCode::Char(ch)
+                };
+                let key = KeyEvent::new(code, modifiers[rng.gen_range(0..modifiers.len())]);
+                for action in app.key_action(key) {
+                    // 查詢、命令之錯誤皆示於界面，不應返回錯誤
+                    app.update(action).unwrap();
+                }
+                app.quitting = false;
+                app.suspending = false;
+                if rng.gen_bool(0.05) {
+                    render(&mut app);
+                }
+            }
+            render(&mut app);
+        }
+    }
+
+    #[test]
+    fn a_normal_quit_emits_the_last_result_and_a_force_quit_does_not() {
+        let mut app = app_with_rows();
+        app.set_query("SELECT ?s WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        app.run_query();
+        app.wait_for_query();
+        // 確認而退者亦如常
+        app.query.string.push(' ');
+        update_all(&mut app, [Action::Quit]);
+        assert!(app.confirmation.is_some());
+        update_all(&mut app, [Action::Confirm]);
+        assert!(app.quit_normally());
+        let mut out = vec![];
+        let tsv = QueryResultsFormat::Tsv.into();
+        assert_eq!(app.emit(tsv, &mut out).unwrap(), 3);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "?s\n<http://ex/a>\n<http://ex/b>\n<http://ex/c>\n"
+        );
+        let mut out = vec![];
+        app.emit(ExportFormat::Markdown, &mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("| ?s "));
+
+        let mut forced = app_with_rows();
+        update_all(&mut forced, [Action::ForceQuit]);
+        assert!(forced.should_quit() && !forced.quit_normally());
+
+        let mut failed = app_with_rows();
+        failed.set_query("SELECT * WHERE { SERVICE <http://ex/no> { ?s ?p ?o } }".to_string());
+        failed.run_query();
+        failed.wait_for_query();
+        let error = failed.emit(tsv, &mut vec![]).unwrap_err();
+        assert!(error.to_string().starts_with("The last query failed"));
+    }
+}
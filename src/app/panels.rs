@@ -0,0 +1,875 @@
+//! 結果表以外之面板：書籤、便箋、最近文件、歷史、層級、鄰域、三元組模式與新手引導。
+
+use super::{details_query, App, Mode, Popup};
+use crate::{
+    action::Action,
+    bookmarks::{Bookmark, View},
+    filter::Filter,
+    generation::Cached,
+    hierarchy::{Hierarchy, Relation, SKOS_BROADER, SKOS_NARROWER},
+    label::resolve_label,
+    neighborhood::Neighborhood,
+    onboarding, opener,
+    paths::create_parent,
+    scratchpad::Pin,
+    util::offset_index,
+};
+use oxigraph::model::Term;
+use std::{
+    fs,
+    path::Path,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+impl App {
+    /// 執行各面板之操作：層級、鄰域、書籤、草稿、歷史與檢視器。餘者原樣返回，交下一部分。
+    pub(super) fn update_panels(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::OpenHierarchy => self.open_hierarchy(Relation::SubClassOf),
+            Action::OpenPattern => self.mode = Mode::Pattern,
+            Action::BookmarkRow => {
+                let iri = self
+                    .results
+                    .table()
+                    .and_then(|table| table.row(self.results.selected_row))
+                    .and_then(|mut row| {
+                        row.find_map(|term| match term {
+                            Some(Term::NamedNode(iri)) => Some(iri.clone()),
+                            _ => None,
+                        })
+                    });
+                match iri {
+                    Some(iri) => self.bookmark_resource(iri.into_string()),
+                    None => self.status = Some("No IRI in the selected row".to_string()),
+                }
+            }
+            Action::OpenNeighborhood => match self.results.selected_resource() {
+                Some(term) => self.open_neighborhood(term),
+                None => {
+                    self.status = Some("No IRI or quoted triple in the selected row".to_string())
+                }
+            },
+            // 書籤即保存
+            Action::BookmarkQuery => {
+                self.add_bookmark(Bookmark::Query {
+                    query: self.query.string.clone(),
+                });
+                self.query.mark_saved();
+            }
+            Action::OpenBookmarks => self.open_bookmarks(),
+            Action::PinCell => self.pin_cell(),
+            Action::PinText(text) => self.pin(Pin::Text(text)),
+            Action::OpenScratchpad => self.open_scratchpad(),
+            Action::OpenRecent => self.open_recent(),
+            Action::OpenHistory => self.open_history(),
+            Action::LoadSample => self.load_sample()?,
+            Action::DismissOnboarding => self.dismiss_onboarding(),
+            Action::InspectTerm => self.inspect_term(),
+            Action::OpenIri => self.open_iri(),
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 三元組模式之表單下之操作。返回未處理者。
+    pub(super) fn update_in_pattern_mode(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::InsertChar(ch) => self.pattern_form.push(ch),
+            Action::DeleteBackward => {
+                self.pattern_form.pop();
+            }
+            Action::CycleField(delta) => self.pattern_form.cycle(delta),
+            Action::Complete => {
+                let candidates = self.pattern_form.complete(&self.config.prefixes);
+                self.status = (!candidates.is_empty()).then(|| candidates.join("  "));
+            }
+            Action::Activate => match self.pattern_form.parse(&self.config.prefixes) {
+                Ok(pattern) => {
+                    self.run_pattern(pattern);
+                }
+                Err(error) => self.status = Some(error),
+            },
+            Action::Back => self.mode = Mode::Browse,
+            action => return Some(action),
+        }
+        None
+    }
+
+    /// 書籤面板下之操作。返回未處理者。
+    pub(super) fn update_in_bookmarks_mode(&mut self, action: Action) -> Option<Action> {
+        let len = self.bookmarks.entries().len();
+        match action {
+            Action::ScrollRows(delta) => {
+                self.selected_bookmark = offset_index(self.selected_bookmark, delta, len);
+            }
+            // 跳至書籤
+            Action::Activate => {
+                if let Some(bookmark) = self.bookmarks.entries().get(self.selected_bookmark) {
+                    let query = match bookmark {
+                        Bookmark::Resource { iri, .. } => {
+                            format!("SELECT ?p ?o WHERE {{ <{iri}> ?p ?o }}")
+                        }
+                        Bookmark::Query { query } => query.clone(),
+                        Bookmark::View(view) => {
+                            self.open_view(view.clone());
+                            return None;
+                        }
+                    };
+                    self.query.set(query);
+                    self.mode = Mode::Browse;
+                }
+            }
+            // 刪除書籤
+            Action::Delete => {
+                if let Err(error) = self.bookmarks.remove(self.selected_bookmark) {
+                    self.status = Some(format!("Fail to save bookmarks: {error}"));
+                }
+                self.selected_bookmark = self.selected_bookmark.min(len.saturating_sub(2));
+            }
+            Action::Back => self.mode = Mode::Browse,
+            Action::SelectFirst | Action::SelectLast => {}
+            action => return Some(action),
+        }
+        None
+    }
+
+    /// 便箋面板下之操作。返回未處理者。
+    pub(super) fn update_in_scratchpad_mode(&mut self, action: Action) -> Option<Action> {
+        let len = self.scratchpad.pins().len();
+        let pin = self.scratchpad.pins().get(self.selected_pin);
+        match action {
+            Action::ScrollRows(delta) => {
+                self.selected_pin = offset_index(self.selected_pin, delta, len);
+            }
+            Action::MovePin(delta) => {
+                self.selected_pin = self.scratchpad.shift(self.selected_pin, delta);
+            }
+            // 插至查詢之末，即光標所在，前留空白
+            Action::Activate => {
+                if let Some(pin) = pin {
+                    let text = pin.text();
+                    if !self.query.string.ends_with(char::is_whitespace) {
+                        self.query.push(' ');
+                    }
+                    self.query.push_str(&text);
+                    self.mode = Mode::Query;
+                }
+            }
+            Action::Yank => {
+                if let Some(pin) = pin {
+                    self.status = Some(self.language.fill("Copied {}", &[&pin.title()]));
+                    self.clipboard = Some(pin.text());
+                }
+            }
+            Action::Delete => {
+                self.scratchpad.remove(self.selected_pin);
+                self.selected_pin = self.selected_pin.min(len.saturating_sub(2));
+            }
+            Action::Back => self.mode = self.scratchpad_from,
+            Action::SelectFirst | Action::SelectLast => {}
+            action => return Some(action),
+        }
+        None
+    }
+
+    /// 最近文件之列表下之操作。返回未處理者。
+    pub(super) fn update_in_recent_mode(&mut self, action: Action) -> Option<Action> {
+        let len = self.recent.entries().len();
+        match action {
+            Action::ScrollRows(delta) => {
+                self.selected_recent = offset_index(self.selected_recent, delta, len);
+            }
+            Action::Activate => {
+                let entry = self.recent.entries().get(self.selected_recent)?;
+                if !entry.exists() {
+                    self.status = Some(format!(
+                        "{} no longer exists (d removes it)",
+                        entry.path.display()
+                    ));
+                    return None;
+                }
+                self.mode = Mode::Browse;
+                return Some(Action::Open(entry.path.clone()));
+            }
+            Action::Delete => {
+                if let Err(error) = self.recent.remove(self.selected_recent) {
+                    self.status = Some(format!("Fail to save the recent files: {error}"));
+                }
+                self.selected_recent = self.selected_recent.min(len.saturating_sub(2));
+            }
+            Action::Back => self.mode = Mode::Browse,
+            Action::SelectFirst | Action::SelectLast => {}
+            action => return Some(action),
+        }
+        None
+    }
+
+    /// 查詢歷史中之操作：Enter 重新執行所選之查詢。
+    pub(super) fn update_in_history_mode(&mut self, action: Action) -> Option<Action> {
+        let len = self.history.entries().len();
+        match action {
+            Action::ScrollRows(delta) => {
+                self.selected_history = offset_index(self.selected_history, delta, len);
+            }
+            Action::Activate => {
+                let entry = self.history.entries().get(self.selected_history)?;
+                self.query.set(entry.query.clone());
+                self.mode = Mode::Browse;
+                self.run_query();
+            }
+            Action::Back => self.mode = Mode::Browse,
+            Action::SelectFirst | Action::SelectLast => {}
+            action => return Some(action),
+        }
+        None
+    }
+
+    pub(super) fn open_history(&mut self) {
+        if self.history.entries().is_empty() {
+            self.status = Some("No query has finished in this session yet".to_string());
+            return;
+        }
+        self.selected_history = 0;
+        self.mode = Mode::History;
+    }
+
+    /// 打開最近文件之列表。
+    pub(super) fn open_recent(&mut self) {
+        self.selected_recent = 0;
+        self.mode = Mode::Recent;
+    }
+
+    /// 於狀態目錄存取最近打開之文件，此後所打開者皆記之。
+    pub fn attach_recent(&mut self) {
+        let Some(file) = self.paths.recent_file() else {
+            return;
+        };
+        if let Err(error) = self.recent.attach(file) {
+            self.status = Some(format!("Fail to load the recent files: {error}"));
+        }
+    }
+
+    /// 啓動時未給文件，則示最近文件之列表以選之。無記錄則不示。
+    pub fn offer_recent(&mut self) {
+        if !self.recent.entries().is_empty() {
+            self.open_recent();
+            self.status = Some(
+                self.tr("Pick a recent file, or o to open another")
+                    .to_string(),
+            );
+        }
+    }
+
+    /// 啓動時未給文件而無最近之文件，則示引導。僅一次：關之則記於狀態目錄。
+    pub fn offer_onboarding(&mut self) {
+        let seen = self
+            .paths
+            .onboarding_marker()
+            .is_some_and(|marker| marker.exists());
+        if !seen
+            && self.recent.entries().is_empty()
+            && self.sources.is_empty()
+            && self.pending.is_empty()
+        {
+            self.popup = Some(Popup::Onboarding);
+        }
+    }
+
+    /// 關引導，記之以免再示。
+    pub(super) fn dismiss_onboarding(&mut self) {
+        if matches!(self.popup, Some(Popup::Onboarding)) {
+            self.popup = None;
+        }
+        let Some(marker) = self.paths.onboarding_marker() else {
+            return;
+        };
+        if let Err(error) = create_parent(&marker).and_then(|()| fs::write(&marker, "")) {
+            log::warn!("onboarding marker save failed error={error}");
+        }
+    }
+
+    /// 寫所附之例於狀態目錄，無之則於臨時目錄，而照常載入之。
+    pub(super) fn load_sample(&mut self) -> anyhow::Result<()> {
+        self.dismiss_onboarding();
+        let dir = self.paths.state_dir().unwrap_or_else(std::env::temp_dir);
+        match onboarding::write_sample(&dir) {
+            Ok(path) => self.update(Action::Open(path))?,
+            Err(error) => self.status = Some(format!("Fail to write the sample: {error}")),
+        }
+        Ok(())
+    }
+
+    /// 記打開一文件。
+    pub(super) fn remember(&mut self, path: &Path, triples: usize) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if let Err(error) = self.recent.record(path, triples, now) {
+            log::warn!("recent files save failed error={error}");
+        }
+    }
+
+    /// 打開便箋面板，關閉時返回當前之模式。
+    pub(super) fn open_scratchpad(&mut self) {
+        self.scratchpad_from = match self.mode {
+            Mode::Query => Mode::Query,
+            _ => Mode::Browse,
+        };
+        self.mode = Mode::Scratchpad;
+    }
+
+    /// 打開書籤面板。
+    pub(super) fn open_bookmarks(&mut self) {
+        self.selected_bookmark = 0;
+        self.mode = Mode::Bookmarks;
+    }
+
+    /// 書籤一資源，附其標籤。
+    pub(super) fn bookmark_resource(&mut self, iri: String) {
+        let label = oxigraph::model::NamedNode::new(iri.as_str())
+            .ok()
+            .and_then(|node| resolve_label(&self.store, &node, self.lang.as_deref()));
+        self.add_bookmark(Bookmark::Resource { iri, label });
+    }
+
+    /// 存當前之查詢及其顯示為視圖，與書籤同存。
+    pub(super) fn save_view(&mut self, name: String) {
+        // 說明與標籤出自查詢庫，更新時留之
+        let (description, tags) = self
+            .bookmarks
+            .view(&name)
+            .map(|old| (old.description.clone(), old.tags.clone()))
+            .unwrap_or_default();
+        let view = View {
+            name: name.clone(),
+            query: self.query.string.clone(),
+            filter: self
+                .results
+                .filter
+                .as_ref()
+                .map(|filter| (filter.text.clone(), filter.regex)),
+            column: self.results.selected_column,
+            origin: self.show_origin,
+            preview: self.preview,
+            limit: self.limit,
+            lang: self.lang.clone(),
+            description,
+            tags,
+            sort: self
+                .results
+                .table()
+                .map(|table| table.sort.clone())
+                .unwrap_or_default(),
+        };
+        self.status = Some(match self.bookmarks.put_view(view) {
+            Ok(false) => format!("Saved view {name}"),
+            Ok(true) => format!("Updated view {name}"),
+            Err(error) => format!("Fail to save bookmarks: {error}"),
+        });
+        self.query.mark_saved();
+    }
+
+    /// 打開視圖。必重新查詢，以見數據之更新；顯示之設置待查詢開始後方設。
+    pub(super) fn open_view(&mut self, view: View) {
+        self.limit = view.limit;
+        self.lang.clone_from(&view.lang);
+        self.query.set(view.query.clone());
+        self.invalidate_results();
+        self.mode = Mode::Browse;
+        self.status = Some(format!("Opened view {}", view.name));
+        self.pending_view = Some(view);
+    }
+
+    /// 設視圖之顯示。所選之列不在表中則不選。
+    pub(super) fn apply_view(&mut self, view: View) {
+        self.results.filter = view
+            .filter
+            .and_then(|(text, regex)| Filter::new(&text, regex).ok());
+        let columns = self
+            .results
+            .table()
+            .map_or(0, |table| table.variables.len());
+        self.results.selected_column = view.column.filter(|&column| column < columns);
+        self.show_origin = view.origin;
+        self.preview = view.preview;
+        self.restore_sort(view.sort);
+    }
+
+    pub(super) fn add_bookmark(&mut self, bookmark: Bookmark) {
+        let title = bookmark.title();
+        self.status = Some(match self.bookmarks.add(bookmark) {
+            Ok(true) => format!("Bookmarked {title}"),
+            Ok(false) => format!("Already bookmarked {title}"),
+            Err(error) => format!("Fail to save bookmarks: {error}"),
+        });
+    }
+
+    /// 層級模式下之操作。返回未處理者。
+    pub(super) fn update_in_hierarchy_mode(
+        &mut self,
+        action: Action,
+    ) -> anyhow::Result<Option<Action>> {
+        let Some(hierarchy) = &mut self.hierarchy else {
+            self.mode = Mode::Browse;
+            return Ok(None);
+        };
+        match action {
+            Action::ScrollRows(delta) => hierarchy.select_by(delta),
+            // 展開或收起
+            Action::Activate => {
+                if let Err(error) = hierarchy.toggle(&self.store) {
+                    self.status = Some(error.to_string());
+                }
+            }
+            // 換關係
+            Action::CycleRelation => {
+                let relation = hierarchy.relation().next();
+                self.open_hierarchy(relation);
+            }
+            Action::RefreshPanel => self.refresh_hierarchy(),
+            // 列出實例
+            Action::ListInstances => {
+                if let Some(iri) = hierarchy.selected_iri() {
+                    let query = match hierarchy.relation() {
+                        Relation::SubClassOf => {
+                            format!("SELECT ?instance WHERE {{ ?instance a {iri} }}")
+                        }
+                        // 概念無實例，列出引用之者
+                        Relation::Broader | Relation::Narrower => format!(
+                            "SELECT ?resource ?property WHERE {{ ?resource ?property {iri} \
+                               FILTER(?property NOT IN (<{SKOS_BROADER}>, <{SKOS_NARROWER}>)) }}"
+                        ),
+                    };
+                    self.query.set(query);
+                    self.mode = Mode::Browse;
+                }
+            }
+            // 列出詳情
+            Action::ShowDetails => {
+                if let Some(iri) = hierarchy.selected_iri() {
+                    let query = format!("SELECT ?p ?o WHERE {{ {iri} ?p ?o }}");
+                    self.details = Some((query.clone(), iri.clone().into()));
+                    self.query.set(query);
+                    self.mode = Mode::Browse;
+                }
+            }
+            Action::BookmarkRow => {
+                if let Some(iri) = hierarchy.selected_iri() {
+                    let iri = iri.as_str().to_string();
+                    self.bookmark_resource(iri);
+                }
+            }
+            Action::Back => self.mode = Mode::Browse,
+            Action::SelectFirst | Action::SelectLast => {}
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 鄰域模式下之操作。返回未處理者。
+    pub(super) fn update_in_neighborhood_mode(&mut self, action: Action) -> Option<Action> {
+        let Some(neighborhood) = &mut self.neighborhood else {
+            self.mode = Mode::Browse;
+            return None;
+        };
+        match action {
+            Action::ScrollRows(delta) => neighborhood.select_by(delta),
+            Action::ExpandNode => {
+                if let Err(error) = neighborhood.expand(&self.store) {
+                    self.status = Some(error.to_string());
+                }
+            }
+            Action::CollapseNode => neighborhood.collapse(),
+            Action::RefreshPanel => self.refresh_neighborhood(),
+            // 以所選者為中心
+            Action::Activate => match neighborhood.selected_term() {
+                Some(Term::Literal(_)) => self.status = Some("A literal has no edges".to_string()),
+                Some(term) => {
+                    let term = term.clone();
+                    self.open_neighborhood(term);
+                }
+                None => {}
+            },
+            Action::ShowDetails => match neighborhood.selected_term() {
+                Some(Term::Literal(_)) | None => {}
+                Some(term) => {
+                    self.query.set(details_query(term));
+                    self.mode = Mode::Browse;
+                }
+            },
+            Action::BookmarkRow => {
+                if let Some(Term::NamedNode(iri)) = neighborhood.selected_term() {
+                    let iri = iri.as_str().to_string();
+                    self.bookmark_resource(iri);
+                }
+            }
+            Action::Back => self.mode = Mode::Browse,
+            Action::SelectFirst | Action::SelectLast => {}
+            action => return Some(action),
+        }
+        None
+    }
+
+    /// 以某資源為中心建鄰域樹，並進入鄰域模式。
+    pub(super) fn open_neighborhood(&mut self, center: Term) {
+        let started = Instant::now();
+        match Neighborhood::new(&self.store, center, self.lang.clone()) {
+            Ok(neighborhood) => {
+                self.neighborhood_refresh = None;
+                self.neighborhood = Some(Cached::new(
+                    self.generation,
+                    neighborhood,
+                    started.elapsed(),
+                ));
+                self.mode = Mode::Neighborhood;
+            }
+            Err(error) => self.status = Some(error.to_string()),
+        }
+    }
+
+    /// 以某關係建層級樹，並進入層級模式。
+    pub(super) fn open_hierarchy(&mut self, relation: Relation) {
+        let started = Instant::now();
+        match Hierarchy::new(&self.store, relation, self.lang.clone()) {
+            Ok(hierarchy) => {
+                self.hierarchy_refresh = None;
+                self.hierarchy = Some(Cached::new(self.generation, hierarchy, started.elapsed()));
+                self.mode = Mode::Hierarchy;
+            }
+            Err(error) => self.status = Some(error.to_string()),
+        }
+    }
+
+    /// 檢視所選之格。
+    pub(super) fn inspect_term(&mut self) {
+        let (variable, term) = match self.selected_term() {
+            Ok(selected) => selected,
+            Err(message) => {
+                self.status = Some(message.to_string());
+                return;
+            }
+        };
+        let title = format!(" {} · ?{} ", self.tr("Inspect"), variable.as_str());
+        self.popup = Some(Popup::Inspector(title, Box::new(term.clone()), None));
+    }
+
+    /// 以 `open_command` 打開所選之 IRI。命令不經 shell，IRI 為一整參數。
+    pub(super) fn open_iri(&mut self) {
+        let iri = match self.selected_term() {
+            Ok((_, Term::NamedNode(iri))) => iri.as_str().to_string(),
+            Ok(_) => {
+                self.status = Some("Only IRIs can be opened".to_string());
+                return;
+            }
+            Err(message) => {
+                self.status = Some(message.to_string());
+                return;
+            }
+        };
+        let template = self
+            .config
+            .open_command
+            .clone()
+            .unwrap_or_else(opener::default_command);
+        let opened = opener::prepare(&iri, &self.config.open_schemes)
+            .and_then(|iri| opener::open(&template, &iri).map(|()| iri));
+        self.status = Some(match opened {
+            Ok(iri) => format!("Opened {iri}"),
+            Err(message) => message,
+        });
+    }
+
+    /// 按所載入之數據集存取書籤。
+    pub(super) fn attach_bookmarks(&mut self) {
+        let Some(file) = self.paths.bookmarks_file(&self.sources) else {
+            return;
+        };
+        if let Err(error) = self.bookmarks.attach(file) {
+            self.status = Some(format!("Fail to load bookmarks: {error}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::{app_with_rows, render, rows, text, type_text, update_all};
+    use crate::paths::Paths;
+    use oxigraph::io::RdfFormat;
+    use ratatui::crossterm::event::{Event, KeyCode};
+
+    use oxigraph::io::RdfParser;
+    use ratatui::crossterm::event::KeyEvent;
+    use std::fs;
+
+    #[test]
+    fn the_inspector_shows_exact_characters_and_compares_pastes() {
+        let mut app = app_with_rows();
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('I'))),
+            [Action::InspectTerm]
+        );
+        update_all(&mut app, [Action::InspectTerm]);
+        assert_eq!(app.status.as_deref(), Some("Select a cell with ←/→ first"));
+        update_all(&mut app, [Action::ScrollColumns(-1), Action::InspectTerm]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Inspect · ?o"), "{screen}");
+        assert!(screen.contains("1 character, 1 byte UTF-8"));
+        assert!(screen.contains("<http://www.w3.org/2001/XMLSchema#integer>"));
+        assert!(screen.contains("Paste text to compare it with the value"));
+
+        app.handle_event(&Event::Paste("3 ".to_string())).unwrap();
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("differs from character 2"), "{screen}");
+        assert!(screen.contains("pasted has ⟨U+0020⟩"));
+        update_all(&mut app, [Action::CloseHelp]);
+        assert!(app.popup.is_none());
+    }
+
+    #[test]
+    fn triple_patterns_browse_and_drill_down() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::OpenPattern, Action::CycleField(1)]);
+        type_text(&mut app, "<http://ex/p>");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(rows(&app), 3);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("subject   any"));
+        assert!(screen.contains("predicate <http://ex/p>"));
+
+        // 以所選行之主語細化
+        update_all(&mut app, [Action::ScrollColumns(-1), Action::ShowDetails]);
+        assert_eq!(app.mode, Mode::Pattern);
+        assert_eq!(app.pattern_form.focus, 0);
+        assert!(app.pattern_form.fields[0].starts_with("<http://ex/"));
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(rows(&app), 1);
+
+        update_all(&mut app, [Action::OpenPattern, Action::CycleField(-1)]);
+        type_text(&mut app, "1 2");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.mode, Mode::Pattern);
+        assert!(app.status.as_ref().unwrap().starts_with("Invalid object: "));
+
+        // 回到查詢則重新查詢
+        update_all(&mut app, [Action::Back, Action::SwitchMode]);
+        assert_eq!(app.mode, Mode::Query);
+        assert_eq!(rows(&app), 3);
+    }
+
+    #[test]
+    fn neighborhood_recenters_on_the_selected_resource() {
+        let mut app = App::new().unwrap();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "<http://ex/a> <http://ex/p> <http://ex/b> . <http://ex/b> <http://ex/q> 1 ."
+                    .as_bytes(),
+            )
+            .unwrap();
+        app.query
+            .set("SELECT ?s WHERE { ?s <http://ex/p> ?o }".to_string());
+        app.refresh_results();
+        app.wait_for_query();
+        update_all(&mut app, [Action::OpenNeighborhood]);
+        assert_eq!(app.mode, Mode::Neighborhood);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Neighborhood of <http://ex/a>"));
+        assert!(screen.contains("▸ <http://ex/b>"));
+
+        update_all(&mut app, [Action::ScrollRows(2), Action::Activate]);
+        let neighborhood = app.neighborhood.as_ref().unwrap();
+        assert_eq!(neighborhood.center().to_string(), "<http://ex/b>");
+        update_all(&mut app, [Action::ScrollRows(2), Action::Activate]);
+        assert_eq!(app.status.as_deref(), Some("A literal has no edges"));
+    }
+
+    #[test]
+    fn the_history_shows_snapshots_and_reruns_a_query() {
+        let mut app = app_with_rows();
+        app.query.set("SELECT ?s WHERE { ?s ?p 2 }".to_string());
+        app.run_query();
+        app.wait_for_query();
+        assert_eq!(app.history.entries().len(), 2);
+        let press = |app: &mut App, code: KeyCode| {
+            let actions = app.key_action(KeyEvent::from(code));
+            update_all(app, actions);
+        };
+        press(&mut app, KeyCode::Char('H'));
+        assert_eq!(app.mode, Mode::History);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("SELECT ?s WHERE { ?s ?p 2 }  1 rows"));
+        assert!(screen.contains("Snapshot · store unchanged"));
+        assert!(screen.contains("<http://ex/b>"));
+        press(&mut app, KeyCode::Down);
+        assert!(text(&render(&mut app)).contains("<http://ex/c>"));
+
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "<http://ex/d> <http://ex/p> 4 .".as_bytes(),
+            )
+            .unwrap();
+        app.store_changed();
+        assert!(text(&render(&mut app)).contains("the store has changed since"));
+        press(&mut app, KeyCode::Enter);
+        app.wait_for_query();
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(app.results.table().unwrap().rows.len(), 4);
+        assert_eq!(app.history.entries().len(), 2);
+        assert_eq!(app.history.entries()[0].rows, 4);
+
+        app.config.snapshot_rows = 0;
+        app.query.set("SELECT * { ?s ?p 4 }".to_string());
+        app.run_query();
+        app.wait_for_query();
+        update_all(&mut app, [Action::OpenHistory]);
+        assert!(text(&render(&mut app)).contains("No snapshot kept"));
+    }
+
+    #[test]
+    fn pins_are_reordered_yanked_and_inserted_into_the_query() {
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(
+            &mut app,
+            [
+                Action::RunQuery,
+                Action::ScrollColumns(1),
+                Action::PinCell,
+                Action::PinText("FILTER(?o > 1)".to_string()),
+                Action::PinCell,
+            ],
+        );
+        assert_eq!(app.status.as_deref(), Some("Already pinned: <http://ex/a>"));
+
+        update_all(&mut app, [Action::OpenScratchpad, Action::MovePin(1)]);
+        assert_eq!(app.mode, Mode::Scratchpad);
+        let screen = text(&render(&mut app));
+        assert!(screen.find("FILTER(?o > 1)").unwrap() < screen.find("<http://ex/a>").unwrap());
+        update_all(&mut app, [Action::Yank]);
+        assert_eq!(app.clipboard.as_deref(), Some("<http://ex/a>"));
+        app.query.set("SELECT * WHERE { ?s ?p ?o".to_string());
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.mode, Mode::Query);
+        assert_eq!(app.query.string, "SELECT * WHERE { ?s ?p ?o <http://ex/a>");
+
+        // 自查詢打開者返回查詢
+        update_all(
+            &mut app,
+            [Action::OpenScratchpad, Action::Delete, Action::Back],
+        );
+        assert_eq!(app.mode, Mode::Query);
+        assert_eq!(
+            app.scratchpad.pins(),
+            [Pin::Text("FILTER(?o > 1)".to_string())]
+        );
+    }
+
+    #[test]
+    fn recent_files_are_remembered_and_picked() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-recent-app-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (kept, gone) = (dir.join("kept.ttl"), dir.join("gone.ttl"));
+        for path in [&kept, &gone] {
+            fs::write(path, "<http://ex/a> <http://ex/p> 1, 2 .\n").unwrap();
+        }
+        let mut app = App::new().unwrap();
+        app.recent.attach(dir.join("recent.tsv")).unwrap();
+        app.open(kept.clone());
+        app.open(gone.clone());
+        fs::remove_file(&gone).unwrap();
+
+        let mut app = App::new().unwrap();
+        app.recent.attach(dir.join("recent.tsv")).unwrap();
+        app.offer_recent();
+        assert_eq!(app.mode, Mode::Recent);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("gone.ttl  (missing)"), "{screen}");
+        assert!(screen.contains("kept.ttl  2 triples"), "{screen}");
+
+        // 已不存在者不可打開，可刪去
+        update_all(&mut app, [Action::Activate]);
+        assert!(app.status.as_deref().unwrap().ends_with("(d removes it)"));
+        update_all(&mut app, [Action::Delete]);
+        assert_eq!(app.recent.entries().len(), 1);
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.mode, Mode::Browse);
+        while let Some(path) = app.pending.pop_front() {
+            app.open(path);
+        }
+        assert_eq!(app.store.len().unwrap(), 2);
+
+        update_all(&mut app, [Action::OpenRecent, Action::PromptOpen]);
+        assert_eq!(app.command_line.input, "open ");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn the_first_run_offers_the_sample_once() {
+        let state = std::env::temp_dir().join(format!("rdf-tui-onboarding-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&state);
+        let start = || {
+            let mut app = App::new().unwrap();
+            app.set_paths(Paths::new(None, Some(state.clone()), None));
+            app.offer_onboarding();
+            app
+        };
+        let mut app = start();
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("results; tab switches."), "{screen}");
+        assert!(screen.contains("? lists every key"), "{screen}");
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('s'))),
+            [Action::LoadSample]
+        );
+
+        // 例照常載入，其旁之啓動查詢列各元素
+        app.set_startup_query(true);
+        update_all(&mut app, [Action::LoadSample]);
+        assert!(app.popup.is_none());
+        while let Some(path) = app.pending.pop_front() {
+            app.open(path);
+        }
+        app.offer_session();
+        app.wait_for_query();
+        assert_eq!(rows(&app), 54);
+        assert_eq!(app.sources, [state.join("sample/elements.ttl")]);
+
+        // 示過者不再示，他鍵關之亦記之
+        assert!(start().popup.is_none());
+        fs::remove_file(state.join("onboarded")).unwrap();
+        let mut app = start();
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Esc)),
+            [Action::DismissOnboarding]
+        );
+        update_all(&mut app, [Action::DismissOnboarding]);
+        assert!(app.popup.is_none() && start().popup.is_none());
+    }
+
+    #[test]
+    fn selected_iris_open_with_the_configured_command() {
+        let mut app = app_with_rows();
+        app.config.open_command = Some(vec!["true".to_string(), "--".to_string()]);
+        app.query
+            .set("SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(&mut app, [Action::RunQuery, Action::OpenIri]);
+        assert_eq!(app.status.as_deref(), Some("Select a cell with ←/→ first"));
+        update_all(&mut app, [Action::ScrollColumns(1), Action::OpenIri]);
+        assert_eq!(app.status.as_deref(), Some("Opened http://ex/a"));
+        update_all(&mut app, [Action::ScrollColumns(1), Action::OpenIri]);
+        assert_eq!(app.status.as_deref(), Some("Only IRIs can be opened"));
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('g'))),
+            Vec::<Action>::new()
+        );
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('x'))),
+            [Action::OpenIri]
+        );
+    }
+}
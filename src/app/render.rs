@@ -0,0 +1,3165 @@
+//! 繪製：各窗格、彈窗、狀態欄與提示之繪製函數，及單元格之顯示。只讀應用之狀態，不改之
+//! （緩存除外）。
+
+use super::{
+    mark_matches, tab_name, App, Loading, Mode, Popup, Progress, ResultTable, Source, ValueDiff,
+    NO_DATA, WATCH_SKIPPED,
+};
+use crate::{
+    action::Action,
+    aggregate::ColumnStats,
+    bookmarks::Bookmark,
+    chardiff::{self, Op},
+    checkpoint::Checkpoint,
+    command::COMMANDS,
+    contain,
+    dataset::Dataset,
+    display::{
+        abbreviate_count, cell_text, compact_iri, format_bytes, group_digits, sanitize, truncate,
+        Bidi, BIDI_MARK, MAX_CELL, MAX_PREVIEW,
+    },
+    example::Example,
+    focus::Focus,
+    graphs::{describe_graph, graph_len},
+    header,
+    hierarchy::Hierarchy,
+    history, inspect,
+    keymap::KeyChord,
+    layout::{self, Chrome, Pane},
+    linear,
+    locale::pad,
+    memory,
+    metrics::{self, Metrics},
+    neighborhood::{Kind, Neighborhood},
+    pattern::FIELDS,
+    provenance::timestamp,
+    scratchpad::Pin,
+    theme::{Tint, COLUMN_TINTS},
+    tile,
+    typography::{self, Counts},
+    watch::describe_interval,
+    widths::{allocate, place, window, MIN_COLUMN},
+    worker::Truncation,
+};
+use oxigraph::{
+    model::{vocab::xsd, GraphName, Literal, LiteralRef, NamedNode, Term, TermRef, Triple},
+    sparql::Variable,
+};
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, Borders, Cell as TableCell, Clear, LineGauge, List, ListState, Padding, Paragraph,
+        Row, Table, TableState, Wrap,
+    },
+    Frame, Terminal,
+};
+use std::{cell::Cell, io, time::Instant};
+
+impl App {
+    /// 引導之各行：兩模式、執行與幫助之鍵，依當前之鍵位；其下為所附之例。
+    fn onboarding_lines(&self) -> Vec<Line<'static>> {
+        let keymap = &self.config.keymap;
+        let key = |mode: Mode, action: Action| {
+            keymap
+                .key(mode, &action)
+                .unwrap_or_else(|| self.tr("(unbound)").to_string())
+        };
+        let sentences = [
+            self.language.fill(
+                "Query mode edits SPARQL and Browse mode explores the results; {} switches.",
+                &[&key(Mode::Browse, Action::SwitchMode)],
+            ),
+            self.language.fill(
+                "In Query mode {} runs the query.",
+                &[&key(Mode::Query, Action::RunQuery)],
+            ),
+            self.language.fill(
+                "{} lists every key, and : opens the command line.",
+                &[&key(Mode::Browse, Action::ShowHelp)],
+            ),
+        ];
+        let mut lines: Vec<Line> = sentences.into_iter().map(Line::raw).collect();
+        lines.push(Line::raw(""));
+        lines.push(Line::from(vec![
+            Span::styled("s ", self.theme.key),
+            Span::raw(self.tr("loads a sample: the chemical elements of periods 1 to 5")),
+        ]));
+        lines.push(Line::styled(
+            self.tr("Any other key starts empty; this shows once (--no-onboarding skips it)"),
+            self.theme.dim,
+        ));
+        lines
+    }
+
+    /// 各頁之比較之浮窗：所選之頁高亮，所記之舊者標以 `*`，末為按鍵。
+    fn tab_run_lines(&self) -> Vec<Line<'static>> {
+        let Some(runs) = &self.tabs.runs else {
+            return vec![];
+        };
+        let mut lines = vec![Line::styled(
+            sanitize(runs.query.trim()).replace('\n', " "),
+            self.theme.dim,
+        )];
+        let entries = runs.lines();
+        let width = entries
+            .iter()
+            .map(|(name, _)| name.chars().count() + 3)
+            .fold(11, usize::max);
+        for (index, (name, summary)) in entries.into_iter().enumerate() {
+            let marker = match runs.base == Some(index) {
+                true => "* ",
+                false => "  ",
+            };
+            let line = Line::from(vec![
+                Span::styled(
+                    format!("{marker}{name:<width$}", width = width - 2),
+                    self.theme.key,
+                ),
+                Span::raw(sanitize(&summary).into_owned()),
+            ]);
+            lines.push(match index == runs.selected {
+                true => line.style(self.theme.selected),
+                false => line,
+            });
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            self.tr("Enter shows a tab's rows · c compares two tabs · y copies Markdown"),
+            self.theme.dim,
+        ));
+        lines
+    }
+
+    /// `:memory` 所列：當前頁之各項；開數頁者另列各頁之總數。
+    fn memory_lines(&self) -> Vec<(String, String)> {
+        let mut lines = self.memory_usage().lines(self.config.memory_budget);
+        if self.tabs.pages.len() > 1 {
+            let memory = self.tab_memory();
+            for (index, (name, bytes)) in self.tab_names().into_iter().zip(&memory).enumerate() {
+                let current = match index == self.tabs.current {
+                    true => " (this tab)",
+                    false => "",
+                };
+                lines.push((
+                    format!("tab {} {name}", index + 1),
+                    format!("~{}{current}", memory::abbreviate(*bytes)),
+                ));
+            }
+            lines.push((
+                "all tabs".to_string(),
+                format!("~{}", memory::abbreviate(memory.iter().sum())),
+            ));
+        }
+        lines
+    }
+
+    /// 一行之各值，所算之列附其表達式，如 `?n  3  = COUNT(?o)`。
+    fn row_lines(&self, row: usize) -> Vec<Line<'static>> {
+        let Some((table, cells)) = self
+            .results
+            .table()
+            .and_then(|table| Some((table, table.row(row)?)))
+        else {
+            return vec![Line::styled("No results", self.theme.dim)];
+        };
+        let values = cells.map(|term| self.plain_cell(term)).collect::<Vec<_>>();
+        let names = table.variables.iter().map(Variable::to_string);
+        let mut lines = self.stat_lines(names.zip(values).collect());
+        for (line, variable) in lines.iter_mut().zip(&table.variables) {
+            if let Some(expression) = self.column_expression(variable) {
+                line.push_span(Span::styled(
+                    format!("  = {}", sanitize(&expression)),
+                    self.theme.dim,
+                ));
+            }
+        }
+        lines
+    }
+
+    /// 繪製整個終端。
+    pub fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        let started = Instant::now();
+        terminal.draw(|frame| self.render(frame, frame.size()))?;
+        let workers = self.workers();
+        if let Some(metrics) = &mut self.metrics {
+            metrics.frame(started, Instant::now(), workers);
+        }
+        Ok(())
+    }
+
+    /// 繪於 `area`，供嵌入者於己之佈局中調用。查詢有變則先重新查詢。
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.poll_graph_job();
+        self.poll_download();
+        self.poll_export();
+        self.poll_count();
+        self.poll_incoming();
+        self.poll_refreshes();
+        self.refresh_results();
+        self.poll_query();
+        self.poll_watch();
+        self.poll_labels();
+        self.poll_tab_runs(false);
+        self.snap_to_filter();
+        if self.linear {
+            self.render_pane(frame, area, "screen", |frame| {
+                self.render_linear(frame, area)
+            });
+        } else {
+            self.render_app(frame, area);
+        }
+    }
+
+    /// 為讀屏者之線性輸出：無框線，各部分為整行之文字。首行宣所在之模式，其下為查詢、
+    /// 所選之一行或一項、浮窗之內容，末為狀態與按鍵。輸入行在末行，光標隨之。
+    fn render_linear(&self, frame: &mut Frame, area: Rect) {
+        use Constraint::{Fill, Length};
+        let input = matches!(self.mode, Mode::Command | Mode::Filter | Mode::Edit);
+        let layout = Layout::vertical([Fill(1), Length(input as u16)]).split(area);
+        let mut heading = format!("{} mode.", linear::mode_name(self.mode));
+        if self.tabs.pages.len() > 1 {
+            heading.push_str(&format!(
+                " Tab {} of {}: {}.",
+                self.tabs.current + 1,
+                self.tabs.pages.len(),
+                tab_name(&self.sources)
+            ));
+        }
+        let mut lines = vec![Line::styled(heading, self.theme.title)];
+        if self.help {
+            lines.push(Line::styled(self.tr("Help"), self.theme.title));
+            lines.extend(self.help_lines());
+        } else if let Some((title, content)) = self.popup_content() {
+            lines.push(Line::styled(title.trim().to_string(), self.theme.title));
+            lines.extend(content);
+        } else {
+            lines.extend(self.linear_body().into_iter().map(Line::from));
+            if let Some((x, y)) = self.linear_cursor(layout[0]) {
+                frame.set_cursor(x, y);
+            }
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::raw(self.linear_status()));
+        if self.config.key_hints {
+            lines.push(Line::styled(
+                linear::describe_hints(&self.hints()),
+                self.theme.dim,
+            ));
+        }
+        // 查詢以原樣之行示之，光標方可對上
+        let paragraph = Paragraph::new(lines);
+        let paragraph = if self.mode == Mode::Query {
+            paragraph
+        } else {
+            paragraph.wrap(Wrap { trim: false })
+        };
+        frame.render_widget(paragraph, layout[0]);
+        match self.mode {
+            Mode::Command => self.render_command_line(frame, layout[1]),
+            Mode::Filter => self.render_filter_line(frame, layout[1]),
+            Mode::Edit => self.render_edit_line(frame, layout[1]),
+            _ => {}
+        }
+    }
+
+    /// 線性輸出中模式之主體，每項一行。
+    fn linear_body(&self) -> Vec<String> {
+        let item = |kind: &str, position: usize, total: usize, text: String| match total {
+            0 => format!("No {kind}s."),
+            _ => linear::describe_item(kind, position.min(total - 1), total, &text),
+        };
+        match self.mode {
+            Mode::Query => {
+                let mut lines = vec!["Query:".to_string()];
+                lines.extend(
+                    self.query
+                        .string
+                        .lines()
+                        .map(|line| sanitize(line).into_owned()),
+                );
+                lines
+            }
+            Mode::Pattern => FIELDS
+                .iter()
+                .zip(&self.pattern_form.fields)
+                .map(|(name, text)| {
+                    let text = if text.is_empty() { "any" } else { text };
+                    format!("{name}: {}", sanitize(text))
+                })
+                .collect(),
+            Mode::Bookmarks => {
+                let bookmarks = self.bookmarks.entries();
+                let title = bookmarks
+                    .get(self.selected_bookmark)
+                    .map(Bookmark::title)
+                    .unwrap_or_default();
+                vec![item(
+                    "bookmark",
+                    self.selected_bookmark,
+                    bookmarks.len(),
+                    sanitize(&title).into_owned(),
+                )]
+            }
+            Mode::Scratchpad => {
+                let pins = self.scratchpad.pins();
+                let title = pins
+                    .get(self.selected_pin)
+                    .map(Pin::title)
+                    .unwrap_or_default();
+                vec![item(
+                    "pin",
+                    self.selected_pin,
+                    pins.len(),
+                    sanitize(&title).into_owned(),
+                )]
+            }
+            Mode::Recent => {
+                let entries = self.recent.entries();
+                let text = entries
+                    .get(self.selected_recent)
+                    .map(|entry| {
+                        let path = sanitize(&entry.path.to_string_lossy()).into_owned();
+                        match entry.exists() {
+                            true => format!(
+                                "{path}, {} triples, {}",
+                                group_digits(entry.triples),
+                                timestamp(entry.opened)
+                            ),
+                            false => format!("{path} (missing)"),
+                        }
+                    })
+                    .unwrap_or_default();
+                vec![item("file", self.selected_recent, entries.len(), text)]
+            }
+            Mode::History => {
+                let entries = self.history.entries();
+                let text = entries
+                    .get(self.selected_history)
+                    .map(|entry| format!("{}, {}", entry.title(), self.history_details(entry)))
+                    .unwrap_or_default();
+                vec![item("query", self.selected_history, entries.len(), text)]
+            }
+            _ => self.linear_result(),
+        }
+    }
+
+    /// 線性輸出中之結果：所選之一行，或錯誤。
+    fn linear_result(&self) -> Vec<String> {
+        if let Some(error) = &self.load_error {
+            let mut lines: Vec<String> = error.lines().map(|line| self.alert(line)).collect();
+            lines.push("Fix the file and press r to reload.".to_string());
+            return lines;
+        }
+        let Some(table) = self.results.table() else {
+            if self.running.is_some() {
+                return vec!["Running…".to_string()];
+            }
+            let mut lines = vec!["No result.".to_string()];
+            if let Some(error) = self.results.current.as_ref().and_then(|r| r.error.as_ref()) {
+                lines.extend(error.lines().map(|line| self.alert(line)));
+            }
+            return lines;
+        };
+        let matching = self.matching_rows(table);
+        let (position, total) = match &matching {
+            Some(rows) => (
+                rows.partition_point(|&row| row < self.results.selected_row),
+                rows.len(),
+            ),
+            None => (self.results.selected_row, table.rows.len()),
+        };
+        drop(matching);
+        let mut lines = vec![];
+        if let Some(filter) = &self.results.filter {
+            lines.push(format!(
+                "{} of {} rows match {}.",
+                group_digits(total),
+                group_digits(table.rows.len()),
+                filter.describe()
+            ));
+        }
+        if table.progress == Progress::Running {
+            lines.push("Still running; more rows may follow.".to_string());
+        }
+        let Some(row) = table.row(self.results.selected_row).filter(|_| total > 0) else {
+            lines.push("No rows.".to_string());
+            return lines;
+        };
+        let names: Vec<String> = table
+            .variables
+            .iter()
+            .enumerate()
+            .map(
+                |(column, variable)| match self.results.selected_column == Some(column) {
+                    true => format!("{} (selected)", variable.as_str()),
+                    false => variable.as_str().to_string(),
+                },
+            )
+            .collect();
+        let cells = linear::describe_cells(
+            names
+                .iter()
+                .map(String::as_str)
+                .zip(row.map(|term| term.map(|term| self.plain_cell(Some(term))))),
+        );
+        let mut text = linear::describe_item("row", position, total, &cells);
+        if table.marked.contains(&self.results.selected_row) {
+            text.push_str(" (marked)");
+        }
+        if let Some(view) = self.results.diff_view() {
+            let kept = view.partition.added.len() + view.partition.unchanged.len();
+            if self.results.selected_row < view.partition.added.len() {
+                text.push_str(" (added)");
+            } else if self.results.selected_row >= kept {
+                text.push_str(" (removed)");
+            }
+        }
+        lines.push(text);
+        lines
+    }
+
+    /// 線性輸出中光標之位置：查詢之末，或三元組模式所在之欄。
+    fn linear_cursor(&self, rect: Rect) -> Option<(u16, u16)> {
+        let (line, column) = match self.mode {
+            // 首行宣模式，次行為 `Query:`
+            Mode::Query => {
+                let lines: Vec<&str> = self.query.string.split('\n').collect();
+                let last = lines.last().copied().unwrap_or_default();
+                (
+                    2 + lines.len() - 1,
+                    Line::raw(sanitize(last).into_owned()).width(),
+                )
+            }
+            Mode::Pattern => {
+                let focus = self.pattern_form.focus;
+                let text = &self.pattern_form.fields[focus];
+                let prefix = format!("{}: {}", FIELDS[focus], sanitize(text));
+                (1 + focus, Line::raw(prefix).width())
+            }
+            _ => return None,
+        };
+        Some((
+            (rect.x + column as u16).min(rect.right().saturating_sub(1)),
+            (rect.y + line as u16).min(rect.bottom().saturating_sub(1)),
+        ))
+    }
+
+    /// 線性輸出之狀態：進度、待確認之問題，或狀態欄之常文。
+    fn linear_status(&self) -> String {
+        let percent = |ratio: f64| format!("{:.0}%", ratio * 100.0);
+        if let Some(job) = &self.graph_job {
+            return format!("{}, {}", job.label(), percent(job.ratio()));
+        }
+        if let Some(job) = &self.exports.job {
+            return format!("{}, {} (Esc cancels)", job.label(), percent(job.ratio()));
+        }
+        if let Some(download) = &self.download {
+            return format!(
+                "{}, {} (Esc cancels)",
+                download.label(),
+                percent(download.ratio())
+            );
+        }
+        if let Some(loading) = &self.loading {
+            return format!(
+                "{}, {}",
+                self.loading_label(loading),
+                percent(loading.ratio(self.backend.read()))
+            );
+        }
+        if let Some(confirmation) = &self.confirmation {
+            return self.alert(&confirmation.message);
+        }
+        let status: String = self
+            .status_spans()
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        match status.trim() {
+            "" => "Ready.".to_string(),
+            status => status.to_string(),
+        }
+    }
+
+    fn render_app(&self, frame: &mut Frame, area: Rect) {
+        // 過小則不排版，以免佈局計算出錯
+        if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "terminal too small (need ≥ {MIN_WIDTH}×{MIN_HEIGHT})"
+                ))
+                .wrap(Wrap { trim: true }),
+                area,
+            );
+            return;
+        }
+        let panel = matches!(
+            (&self.mode, &self.hierarchy, &self.neighborhood),
+            (Mode::Hierarchy, Some(_), _)
+                | (Mode::Neighborhood, _, Some(_))
+                | (
+                    Mode::Bookmarks | Mode::Scratchpad | Mode::Recent | Mode::History,
+                    _,
+                    _
+                )
+        );
+        let chrome = Chrome {
+            tabs: self.tabs.pages.len() > 1,
+            query_height: self.query_height(area),
+            editing: matches!(self.mode, Mode::Query | Mode::Pattern),
+            table: !panel && !self.linear && self.results.table().is_some(),
+            preview: self.preview,
+            hints: self.config.key_hints,
+        };
+        for (pane, rect) in layout::arrange(&self.layout, area, chrome) {
+            match pane {
+                Pane::Tabs => {
+                    self.render_pane(frame, rect, "tabs", |frame| self.render_tabs(frame, rect))
+                }
+                Pane::Query => {
+                    self.render_pane(frame, rect, "query", |frame| self.render_query(frame, rect))
+                }
+                Pane::Main => self.render_main(frame, rect),
+                Pane::Preview => self.render_pane(frame, rect, "preview", |frame| {
+                    self.render_preview(frame, rect)
+                }),
+                Pane::Stats => self.render_pane(frame, rect, "statistics", |frame| {
+                    self.render_stats(frame, rect)
+                }),
+                Pane::Status => {
+                    self.render_pane(frame, rect, "status line", |frame| match self.mode {
+                        Mode::Command => self.render_command_line(frame, rect),
+                        Mode::Filter => self.render_filter_line(frame, rect),
+                        Mode::Edit => self.render_edit_line(frame, rect),
+                        _ => self.render_status(frame, rect),
+                    })
+                }
+                Pane::Hints => self.render_pane(frame, rect, "key hints", |frame| {
+                    self.render_hints(frame, rect)
+                }),
+            }
+        }
+        let popup = self.catch_render(frame, |frame| {
+            if let Some((title, lines)) = self.popup_content() {
+                self.render_popup(frame, area, &title, lines);
+            }
+        });
+        let help = self.catch_render(frame, |frame| {
+            if self.help {
+                self.render_help(frame, area);
+            }
+        });
+        let debug = self.catch_render(frame, |frame| {
+            if let Some(metrics) = self.metrics.as_ref().filter(|_| self.debug) {
+                self.render_debug(frame, area, metrics);
+            }
+        });
+        for (name, message) in [("popup", popup), ("help", help), ("debug overlay", debug)] {
+            if let Some(message) = message {
+                let lines = failure_lines(&message);
+                self.render_popup(frame, area, &format!(" Could not draw the {name} "), lines);
+            }
+        }
+    }
+
+    /// 繪主部分：結果表，或代之之面板。
+    fn render_main(&self, frame: &mut Frame, main: Rect) {
+        match (&self.mode, &self.hierarchy) {
+            (Mode::Hierarchy, Some(hierarchy)) => {
+                self.render_pane(frame, main, "hierarchy", |frame| {
+                    self.render_hierarchy(frame, main, hierarchy)
+                })
+            }
+            (Mode::Bookmarks, _) => self.render_pane(frame, main, "bookmarks", |frame| {
+                self.render_bookmarks(frame, main)
+            }),
+            (Mode::Scratchpad, _) => self.render_pane(frame, main, "scratchpad", |frame| {
+                self.render_scratchpad(frame, main)
+            }),
+            (Mode::Recent, _) => self.render_pane(frame, main, "recent files", |frame| {
+                self.render_recent(frame, main)
+            }),
+            (Mode::History, _) => self.render_pane(frame, main, "history", |frame| {
+                self.render_history(frame, main)
+            }),
+            (Mode::Neighborhood, _) if self.neighborhood.is_some() => {
+                let neighborhood = self.neighborhood.as_ref().expect("checked");
+                self.render_pane(frame, main, "neighborhood", |frame| {
+                    self.render_neighborhood(frame, main, neighborhood)
+                })
+            }
+            _ => self.render_pane(frame, main, "results", |frame| {
+                self.render_browser(frame, main)
+            }),
+        }
+    }
+
+    /// 渲染統計窗格：結果之行列數、上次查詢之耗時、監視之間隔、存儲之大小及內存。
+    fn render_stats(&self, frame: &mut Frame, rect: Rect) {
+        let mut lines = vec![];
+        if let Some(table) = self.results.table() {
+            let truncated = match table.truncated {
+                Some(_) => " (truncated)",
+                None => "",
+            };
+            lines.push((
+                "rows".to_string(),
+                format!("{}{truncated}", group_digits(table.rows.len())),
+            ));
+            lines.push(("columns".to_string(), table.variables.len().to_string()));
+        }
+        if let Some(entry) = self.history.entries().front() {
+            lines.push((
+                "last run".to_string(),
+                format!("{} ms", entry.duration.as_millis()),
+            ));
+        }
+        if let Some(watch) = &self.watch {
+            lines.push((
+                "watch".to_string(),
+                format!("every {}", describe_interval(watch.interval)),
+            ));
+        }
+        lines.push(("quads".to_string(), group_digits(self.quads())));
+        lines.push((
+            "memory".to_string(),
+            format!("~{}", memory::abbreviate(self.memory_usage().total())),
+        ));
+        let block = Block::bordered()
+            .title(self.title(self.tr("Statistics"), false))
+            .border_style(self.border_style(false))
+            .padding(Padding::horizontal(1));
+        frame.render_widget(Paragraph::new(self.stat_lines(lines)).block(block), rect);
+    }
+
+    /// 繪一窗格。其 panic 則止於此，以錯誤之框代之，餘者照常。
+    pub(super) fn render_pane(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        name: &str,
+        render: impl FnOnce(&mut Frame),
+    ) {
+        let Some(message) = self.catch_render(frame, render) else {
+            return;
+        };
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(failure_lines(&message))
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::bordered()
+                        .title(Span::styled(
+                            format!(" Could not draw the {name} "),
+                            self.theme.error,
+                        ))
+                        .border_style(self.theme.error),
+                ),
+            area,
+        );
+    }
+
+    /// 繪之，其 panic 則捕之，返回其消息；回溯由 panic 鈎子記於日誌。`--debug-render` 則不捕。
+    fn catch_render(&self, frame: &mut Frame, render: impl FnOnce(&mut Frame)) -> Option<String> {
+        if !self.contain_panics {
+            render(frame);
+            return None;
+        }
+        contain::catch(|| render(frame)).err()
+    }
+
+    /// 頂之標籤頁條：各頁之序號與首個文件之名，當前者高亮。
+    fn render_tabs(&self, frame: &mut Frame, area: Rect) {
+        let spans: Vec<Span> = self
+            .tab_names()
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let style = match index == self.tabs.current {
+                    true => self.theme.selected,
+                    false => self.theme.dim,
+                };
+                Span::styled(format!(" {} {name} ", index + 1), style)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+
+    /// `:paths` 之各行：配置、狀態、會話、書籤與檢查點之所在。
+    fn path_lines(&self) -> Vec<(String, String)> {
+        let mut lines = self.paths.lines(&self.sources);
+        lines.push((
+            "checkpoints".to_string(),
+            self.checkpoints.as_ref().map_or_else(
+                || "off: give --checkpoint-dir".to_string(),
+                |checkpoints| checkpoints.dir.display().to_string(),
+            ),
+        ));
+        lines
+    }
+
+    /// 宏面板之各行：寄存器及其操作。
+    fn macro_lines(&self) -> Vec<Line<'static>> {
+        if self.macros.all().is_empty() {
+            return vec![Line::styled(
+                "No macros: Ctrl+Q and a register records one",
+                self.theme.dim,
+            )];
+        }
+        self.macros
+            .all()
+            .iter()
+            .map(|recorded| {
+                Line::from(vec![
+                    Span::styled(format!("@{} ", recorded.register), self.theme.key),
+                    Span::raw(sanitize(&recorded.describe()).into_owned()),
+                ])
+            })
+            .collect()
+    }
+
+    /// 浮窗之標題與內容：所開之浮窗，或待確認之更新之預覽。
+    fn popup_content(&self) -> Option<(String, Vec<Line<'static>>)> {
+        let plain = |text: &str| -> Vec<Line<'static>> {
+            text.lines()
+                .map(|line| Line::raw(sanitize(line).into_owned()))
+                .collect()
+        };
+        let popup = match &self.popup {
+            Some(Popup::ColumnStats(_, _, title, stats)) => {
+                Some((title.clone(), self.column_stats_lines(stats)))
+            }
+            Some(Popup::Updates) => {
+                Some((format!(" {} ", self.tr("Updates")), self.update_lines()))
+            }
+            Some(Popup::Checkpoints(checkpoints)) => Some((
+                format!(" {} ", self.tr("Checkpoints")),
+                self.checkpoint_lines(checkpoints),
+            )),
+            Some(Popup::Completions(matches)) => Some((
+                format!(" {} ", self.tr("Matches")),
+                matches.iter().flat_map(|name| plain(name)).collect(),
+            )),
+            Some(Popup::ColumnTypes(title, types)) => Some((
+                title.clone(),
+                self.stat_lines(types.lines(&self.config.prefixes)),
+            )),
+            Some(Popup::Paths) => Some((
+                format!(" {} ", self.tr("Paths")),
+                self.stat_lines(self.path_lines()),
+            )),
+            Some(Popup::Macros) => Some((format!(" {} ", self.tr("Macros")), self.macro_lines())),
+            Some(Popup::TabRuns) => Some((format!(" {} ", self.tr("Tabs")), self.tab_run_lines())),
+            Some(Popup::Onboarding) => Some((
+                format!(" {} ", self.tr("Welcome to rdf-tui")),
+                self.onboarding_lines(),
+            )),
+            Some(Popup::Memory) => Some((
+                format!(" {} ", self.tr("Memory")),
+                self.stat_lines(self.memory_lines()),
+            )),
+            Some(Popup::ValueDiff(diff)) => Some((
+                format!(" {} ", self.tr("Compare")),
+                self.value_diff_lines(diff).split_off(diff.scroll),
+            )),
+            Some(Popup::Row(row)) => Some((
+                format!(" {} {} ", self.tr("Row"), group_digits(row + 1)),
+                self.row_lines(*row),
+            )),
+            Some(Popup::Inspector(title, term, pasted)) => {
+                let mut lines = self.stat_lines(inspect::lines(term));
+                lines.push(Line::raw(""));
+                match pasted {
+                    Some(pasted) => lines
+                        .extend(self.stat_lines(inspect::compare(&inspect::value(term), pasted))),
+                    None => lines.push(Line::styled(
+                        self.tr("Paste text to compare it with the value"),
+                        self.theme.dim,
+                    )),
+                }
+                Some((title.clone(), lines))
+            }
+            None => self.example.as_ref().map(|example| {
+                (
+                    format!(" {} ", self.tr("Query by example")),
+                    self.example_lines(example),
+                )
+            }),
+        };
+        popup.or_else(|| {
+            let preview = self.confirmation.as_ref()?.preview.as_ref()?;
+            Some((" Update ".to_string(), plain(preview)))
+        })
+    }
+
+    /// 值之比較之各行：兩格之所在與結果，其下兩值共有者暗示，異處以增刪之色標之。
+    pub(super) fn value_diff_lines(&self, diff: &ValueDiff) -> Vec<Line<'static>> {
+        let mut lines = self.stat_lines(vec![
+            ("1".to_string(), diff.labels[0].clone()),
+            ("2".to_string(), diff.labels[1].clone()),
+            (
+                "result".to_string(),
+                chardiff::summary(&diff.values[0], &diff.values[1], &diff.edits),
+            ),
+        ]);
+        for (key, which, style) in [
+            ("1", Op::Removed, self.theme.removed),
+            ("2", Op::Added, self.theme.added),
+        ] {
+            lines.push(Line::raw(""));
+            for (index, segments) in chardiff::side(&diff.edits, which).into_iter().enumerate() {
+                let key = if index == 0 { key } else { "" };
+                let mut spans = vec![Span::styled(format!("{key:<3}"), self.theme.key)];
+                if segments.is_empty() {
+                    spans.push(Span::styled("empty", self.theme.dim));
+                }
+                spans.extend(segments.into_iter().map(|(op, text)| match op {
+                    Op::Same => Span::styled(text, self.theme.dim),
+                    _ => Span::styled(text, style),
+                }));
+                lines.push(Line::from(spans));
+            }
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            self.tr("1/2 copy a value · j/k scroll"),
+            self.theme.dim,
+        ));
+        lines
+    }
+
+    /// 以例查詢之各列與其取法，下附所生之查詢。
+    fn example_lines(&self, example: &Example) -> Vec<Line<'static>> {
+        let mut lines = vec![];
+        for (index, ((variable, cell), choice)) in example
+            .variables
+            .iter()
+            .zip(&example.cells)
+            .zip(&example.choices)
+            .enumerate()
+        {
+            let value = self.plain_cell(cell.as_ref());
+            let style = if index == example.selected {
+                self.theme.selected
+            } else {
+                Style::default()
+            };
+            lines.push(Line::styled(
+                format!("{variable}  {}  {value}", self.tr(choice.label())),
+                style,
+            ));
+        }
+        lines.push(Line::raw(""));
+        let query = example.query().unwrap_or_default();
+        lines.extend(
+            query
+                .lines()
+                .map(|line| Line::raw(sanitize(line).into_owned())),
+        );
+        lines
+    }
+
+    /// 渲染瀏覽部分
+    pub(super) fn render_browser(&self, frame: &mut Frame, rect: Rect) {
+        // 載入錯誤優先顯示
+        if let Some(error) = &self.load_error {
+            let block = Block::bordered()
+                .title(self.title(self.tr("Explore"), self.browser_focused()))
+                .title_bottom(self.tr(" fix the file and press r to reload "))
+                .border_style(self.theme.error)
+                .padding(Padding::uniform(1));
+            let lines: Vec<Line> = error.lines().map(|l| self.error_line(l)).collect();
+            frame.render_widget(
+                Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .block(block),
+                rect,
+            );
+            return;
+        }
+
+        let title = match self
+            .results
+            .current
+            .as_ref()
+            .and_then(|results| results.guarded)
+        {
+            Some(limit) => self
+                .language
+                .fill("Explore (LIMIT {} added)", &[&group_digits(limit)]),
+            None => self.tr("Explore").to_string(),
+        };
+        let block = Block::bordered()
+            .title(self.title(&title, self.browser_focused()))
+            .border_style(self.get_browser_style())
+            .padding(Padding::horizontal(1));
+
+        // 僅在查詢結果时
+        if let Some(table) = self.results.table() {
+            let count = group_digits(table.rows.len());
+            let no_rows = table.rows.is_empty() && table.progress == Progress::Complete;
+            let progress = match table.progress {
+                Progress::Running => Some(format!(" {count} rows… (running) ")),
+                Progress::Cancelled => Some(format!(" {count} rows (cancelled) ")),
+                Progress::Complete => match table.truncated {
+                    Some(Truncation::Rows(_)) => Some(format!(" first {count} rows ")),
+                    Some(cap) => Some(format!(" first {count} rows, stopped {cap} ")),
+                    None => None,
+                },
+            };
+            let block = match progress {
+                Some(progress) => block.title(Span::styled(progress, self.theme.dim)),
+                None => block,
+            };
+            // 列排序者已示其序於表頭
+            let block = match table.stable && table.sort.is_empty() {
+                true => block.title(Span::styled(
+                    " stable order, not the engine's (O) ",
+                    self.theme.warning,
+                )),
+                false => block,
+            };
+            let block = match self.results.diff_view() {
+                Some(view) => block.title(Span::styled(
+                    format!(
+                        " +{} added · {} unchanged · {} removed ({}) ",
+                        group_digits(view.partition.added.len()),
+                        group_digits(view.partition.unchanged.len()),
+                        group_digits(view.partition.removed.len()),
+                        if view.removed {
+                            "D hides"
+                        } else {
+                            "hidden, D shows"
+                        }
+                    ),
+                    self.theme.dim,
+                )),
+                None => block,
+            };
+            let block = match table.marked.len() {
+                0 => block,
+                marked => block.title(Span::styled(
+                    format!(" {} marked · Ctrl+D deletes ", group_digits(marked)),
+                    self.theme.dim,
+                )),
+            };
+            let block = match self.watch_badge() {
+                Some(badge) => block.title(Span::styled(badge, self.theme.dim)),
+                None => block,
+            };
+            if let Some(term) = self.tile_value(table) {
+                self.render_tile(frame, rect, block, table, term);
+                return;
+            }
+            if self.results.transposed && !no_rows {
+                self.render_transposed(frame, rect, block, table);
+                return;
+            }
+            // 過濾時僅列相符之行，偏移亦以其中之位置計
+            let matching = self.matching_rows(table);
+            let block = match (&matching, &self.results.filter) {
+                (Some(rows), Some(filter)) => block.title(Span::styled(
+                    format!(
+                        " {} of {count} rows match {} ",
+                        group_digits(rows.len()),
+                        filter.describe()
+                    ),
+                    self.theme.dim,
+                )),
+                _ => block,
+            };
+            let position = match &matching {
+                Some(rows) => rows.partition_point(|&row| row < self.results.selected_row),
+                None => self.results.selected_row,
+            };
+            // 僅構造可見之行：邊框二行，表頭一行，表尾亦一行
+            let footer_height = usize::from(self.results.types_footer);
+            let height = (block.inner(rect).height as usize)
+                .saturating_sub(1 + footer_height)
+                .max(1);
+            let offset = table.scroll_to(position, height);
+            let visible: Vec<usize> = match &matching {
+                Some(rows) => rows.iter().skip(offset).take(height).copied().collect(),
+                None => (offset..table.rows.len()).take(height).collect(),
+            };
+            self.label_window.set(
+                visible
+                    .first()
+                    .zip(visible.last())
+                    .map(|(first, last)| (*first, last + 1)),
+            );
+            drop(matching);
+            // 各可見行中首個所記之圖之來源。皆不在具名圖中則不附此列
+            let origins: Vec<Option<String>> = if self.show_origin {
+                visible
+                    .iter()
+                    .map(|&index| {
+                        table.rows[index]
+                            .iter()
+                            .flatten()
+                            .filter(|term| {
+                                matches!(***term, Term::NamedNode(_) | Term::BlankNode(_))
+                            })
+                            .find_map(|term| self.provenance.origin(&term.to_string()))
+                    })
+                    .collect()
+            } else {
+                vec![]
+            };
+            let show_origin = origins.iter().any(Option::is_some);
+            let on_header = self.results.on_header && self.mode == Mode::Browse;
+            let mut header: Vec<Span> = table
+                .variables
+                .iter()
+                .enumerate()
+                .map(|(column, variable)| {
+                    // 多鍵者標其先後，如 `▲1`、`▼2`
+                    let key = table.sort.iter().position(|&(sorted, _)| sorted == column);
+                    let text = match key.map(|key| (key, table.sort[key].1)) {
+                        Some((key, order)) if table.sort.len() > 1 => {
+                            format!("{variable} {}{}", order.arrow(), key + 1)
+                        }
+                        Some((_, order)) => format!("{variable} {}", order.arrow()),
+                        None => variable.to_string(),
+                    };
+                    match on_header && self.results.selected_column == Some(column) {
+                        true => Span::styled(text, self.theme.selected),
+                        false => Span::raw(text),
+                    }
+                })
+                .collect();
+            if show_origin {
+                header.push(Span::styled("origin", self.theme.dim));
+            }
+            let mut display = table.display.borrow_mut();
+            if display.len() > DISPLAY_CACHE {
+                display.clear();
+            }
+            // 列寬：表頭、前若干行之取樣與可見之行所量者，只增不減；每次僅重新分配
+            let mut measured = table.widths.borrow_mut();
+            if measured.widths().is_empty() {
+                measured.widen(
+                    table
+                        .variables
+                        .iter()
+                        .map(|variable| Span::raw(variable.to_string()).width()),
+                );
+            }
+            let sample = measured.unsampled(table.rows.len(), self.config.width_sample);
+            for row in &table.rows[sample.clone()] {
+                measured.widen(row.iter().map(|term| self.cell(term.as_deref()).width()));
+            }
+            measured.sampled_to(sample.end);
+            let cached = display.len();
+            for &row_index in &visible {
+                let widths: Vec<usize> = (0..table.variables.len())
+                    .map(|column| {
+                        display
+                            .entry((row_index, column))
+                            .or_insert_with(|| self.cell(table.rows[row_index][column].as_deref()))
+                            .width()
+                    })
+                    .collect();
+                measured.widen(widths);
+            }
+            if let Some(metrics) = &self.metrics {
+                let misses = (display.len() - cached) as u64;
+                let lookups = (visible.len() * table.variables.len()) as u64;
+                metrics.cache("cells", lookups - misses, misses);
+            }
+            let mut widths = measured.widths().to_vec();
+            drop(measured);
+            let origin_width = show_origin.then(|| {
+                origins
+                    .iter()
+                    .flatten()
+                    .map(|origin| Span::raw(origin.as_str()).width())
+                    .chain(["origin".len()])
+                    .max()
+                    .unwrap_or_default()
+            });
+            // 寬終端中表不逾上限而居中，餘處示所選格之預覽
+            let inner = block.inner(rect);
+            let symbol = Span::raw(self.highlight_symbol()).width();
+            let content = widths.iter().chain(&origin_width).sum::<usize>()
+                + widths
+                    .len()
+                    .saturating_sub(usize::from(origin_width.is_none()))
+                + symbol;
+            let placement = place(
+                content.min(u16::MAX as usize) as u16,
+                inner.width,
+                self.config.max_table_width,
+                !self.preview_shown(),
+            );
+            let available = placement.table.saturating_sub(symbol as u16);
+            // 列多而不能各得可讀之寬者，僅示所選之列所在之一段
+            let reserved = origin_width.map_or(0, |width| width.clamp(1, MIN_COLUMN) + 1);
+            let columns = window(
+                &widths,
+                available.saturating_sub(reserved as u16),
+                1,
+                self.results.column_offset.get(),
+                self.results.selected_column,
+            );
+            self.results.column_offset.set(columns.start);
+            widths = widths[columns.clone()].to_vec();
+            widths.extend(origin_width);
+            let origin_header = show_origin.then(|| header.pop()).flatten();
+            let header = Row::new(
+                header
+                    .into_iter()
+                    .skip(columns.start)
+                    .take(columns.len())
+                    .chain(origin_header),
+            )
+            .style(self.theme.header);
+            let block = match columns.len() < table.variables.len() {
+                true => block.title(Span::styled(
+                    format!(
+                        " {}/{} columns (t transposes) ",
+                        columns.len(),
+                        table.variables.len()
+                    ),
+                    self.theme.dim,
+                )),
+                false => block,
+            };
+            let widths: Vec<Constraint> = allocate(&widths, available, 1)
+                .into_iter()
+                .map(Constraint::Length)
+                .collect();
+            // 另標所選之單元格，詳情取之
+            let on_footer = self.results.on_footer && self.mode == Mode::Browse;
+            let selected_cell = self
+                .results
+                .selected_column
+                .filter(|_| !on_header && !on_footer)
+                .map(|column| (self.results.selected_row, column));
+            let clustered = matches!(
+                self.results.current.as_ref().map(|r| &r.source),
+                Some(Source::Dupes(_))
+            );
+            // 比較時新增與刪去之行另以樣式標之
+            let change_style = |row_index: usize| {
+                let view = self.results.diff_view()?;
+                let kept = view.partition.added.len() + view.partition.unchanged.len();
+                if row_index < view.partition.added.len() {
+                    Some(self.theme.added)
+                } else if row_index >= kept {
+                    Some(self.theme.removed)
+                } else {
+                    None
+                }
+            };
+            // 色調不加於所選、所標記及比較之行，以免減其樣式之對比
+            let tint = |column: usize, term: Option<&Term>| match self.config.tint {
+                Tint::Off => Style::default(),
+                Tint::Kind => match term {
+                    Some(Term::NamedNode(_) | Term::Triple(_)) => self.theme.iri,
+                    Some(Term::Literal(_)) => self.theme.literal,
+                    Some(Term::BlankNode(_)) => self.theme.blank,
+                    None => Style::default(),
+                },
+                Tint::Column => self.theme.column_tints[column % COLUMN_TINTS],
+            };
+            let highlighted = (!on_header && !on_footer).then(|| position.saturating_sub(offset));
+            let rows: Vec<Row> = visible
+                .iter()
+                .enumerate()
+                .map(|(index, &row_index)| {
+                    let row = &table.rows[row_index];
+                    let tinted = highlighted != Some(index)
+                        && !table.marked.contains(&row_index)
+                        && change_style(row_index).is_none();
+                    let cells = row
+                        .iter()
+                        .enumerate()
+                        .skip(columns.start)
+                        .take(columns.len());
+                    let cells = cells.map(|(column, term)| {
+                        let line = display
+                            .entry((row_index, column))
+                            .or_insert_with(|| self.cell(term.as_deref()))
+                            .clone();
+                        let line = match &self.results.filter {
+                            Some(filter) => mark_matches(line, self.theme.matched, |text| {
+                                filter.spans(&table.variables, column, text)
+                            }),
+                            None => line,
+                        };
+                        let line = if selected_cell == Some((row_index, column)) {
+                            line.patch_style(
+                                Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                            )
+                        } else {
+                            line
+                        };
+                        match tinted {
+                            true => TableCell::from(line).style(tint(column, term.as_deref())),
+                            false => TableCell::from(line),
+                        }
+                    });
+                    let origin = show_origin.then(|| {
+                        TableCell::from(Line::styled(
+                            origins[index].clone().unwrap_or_default(),
+                            self.theme.dim,
+                        ))
+                    });
+                    let cells = Row::new(cells.chain(origin));
+                    let style = if table.marked.contains(&row_index) {
+                        Some(self.theme.marked)
+                    } else {
+                        change_style(row_index)
+                    };
+                    let cells = match style {
+                        Some(style) => cells.style(style),
+                        None => cells,
+                    };
+                    // 重複值之各簇間空一行
+                    if clustered && index > 0 && table.rows[visible[index - 1]][0] != row[0] {
+                        cells.top_margin(1)
+                    } else {
+                        cells
+                    }
+                })
+                .collect();
+
+            // 各列之類型，兼有資源與字面量者示警
+            let footer = self.results.types_footer.then(|| {
+                let types = table.column_types();
+                let cells = types
+                    .iter()
+                    .enumerate()
+                    .skip(columns.start)
+                    .take(columns.len());
+                let cells = cells.map(|(column, types)| {
+                    let style = if on_footer && self.results.selected_column == Some(column) {
+                        self.theme.selected
+                    } else if types.mixes_resources_and_literals() {
+                        self.theme.warning
+                    } else {
+                        self.theme.dim
+                    };
+                    Line::styled(types.summary(&self.config.prefixes), style)
+                });
+                Row::new(cells.collect::<Vec<_>>())
+            });
+            let table = Table::new(rows, widths)
+                .column_spacing(1)
+                .header(header)
+                .highlight_style(self.theme.selected)
+                .highlight_symbol(self.highlight_symbol());
+            let table = match footer {
+                Some(footer) => table.footer(footer),
+                None => table,
+            };
+
+            let selected = (!visible.is_empty() && !on_header && !on_footer)
+                .then(|| position.saturating_sub(offset));
+            let mut state = TableState::default().with_selected(selected);
+            frame.render_widget(block, rect);
+            let area = Rect {
+                x: inner.x + placement.indent,
+                width: placement.table,
+                ..inner
+            };
+            frame.render_stateful_widget(table, area, &mut state);
+            // 無行者仍示表頭，以見查詢已執行
+            if no_rows && area.height > 2 {
+                let from_query = self
+                    .results
+                    .current
+                    .as_ref()
+                    .is_some_and(|results| matches!(results.source, Source::Query));
+                let message = match self.empty_data_note() {
+                    Some(note) => note,
+                    None if from_query
+                        && self.dataset == Dataset::Default
+                        && self.has_named_graphs() =>
+                    {
+                        self.tr("The query returned no rows from the default graph; named graphs are left out (U or :dataset union)").to_string()
+                    }
+                    None => self.tr("The query returned no rows").to_string(),
+                };
+                let area = Rect {
+                    y: area.y + 2,
+                    height: area.height - 2,
+                    ..area
+                };
+                frame.render_widget(
+                    Paragraph::new(Span::styled(message, self.theme.dim))
+                        .centered()
+                        .wrap(Wrap { trim: false }),
+                    area,
+                );
+            }
+            if placement.preview > 0 {
+                let area = Rect {
+                    x: inner.x + placement.preview_offset(),
+                    width: placement.preview,
+                    ..inner
+                };
+                self.render_preview(frame, area);
+            }
+        } else if self.running.is_some() {
+            frame.render_widget(Paragraph::new("Running…").centered().block(block), rect);
+        } else if let Some(error) = self.results.current.as_ref().and_then(|r| r.error.as_ref()) {
+            let lines: Vec<Line> = error.lines().map(|l| self.error_line(l)).collect();
+            frame.render_widget(
+                Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .block(block.border_style(self.theme.error)),
+                rect,
+            );
+        } else {
+            let message = match self.store_is_empty() {
+                true => self.tr(NO_DATA),
+                false => "NO RESULT",
+            };
+            frame.render_widget(
+                Paragraph::new(message)
+                    .centered()
+                    .wrap(Wrap { trim: false })
+                    .block(block),
+                rect,
+            );
+        }
+    }
+
+    /// 存儲為空且無文件在載入。
+    pub(super) fn store_is_empty(&self) -> bool {
+        self.loading.is_none() && self.store.is_empty().unwrap_or(false)
+    }
+
+    /// 無行之結果因無數據可查者之注：存儲為空，或查詢所查之圖皆空。查詢自有 FROM 或
+    /// `GRAPH` 者，或所查之圖有數據者，為 `None`，無行乃查詢濾去之故。
+    fn empty_data_note(&self) -> Option<String> {
+        if self.store_is_empty() {
+            return Some(self.tr(NO_DATA).to_string());
+        }
+        let results = self.results.current.as_ref()?;
+        if !matches!(results.source, Source::Query) || self.loading.is_some() {
+            return None;
+        }
+        let own_dataset = oxigraph::sparql::Query::parse(&results.query, None)
+            .is_ok_and(|query| !query.dataset().is_default_dataset());
+        // `GRAPH` 所查之命名圖不在所設之圖中
+        let names_graphs = header::mask(&results.query)
+            .split(|ch: char| !ch.is_alphanumeric())
+            .any(|word| word.eq_ignore_ascii_case("GRAPH"));
+        if own_dataset || names_graphs {
+            return None;
+        }
+        let empty = |graph: &GraphName| graph_len(&self.store, graph) == 0;
+        match &self.dataset {
+            Dataset::Default if empty(&GraphName::DefaultGraph) => Some(
+                self.tr("note: the default graph is empty; the data is in named graphs (U or :dataset union)")
+                    .to_string(),
+            ),
+            Dataset::Graphs(graphs) if graphs.iter().all(empty) => Some(match &graphs[..] {
+                [graph] => format!(
+                    "note: {} is empty — choose another graph with :dataset",
+                    describe_graph(graph)
+                ),
+                graphs => format!(
+                    "note: the {} selected graphs are empty — choose others with :dataset",
+                    graphs.len()
+                ),
+            }),
+            _ => None,
+        }
+    }
+
+    /// 監視之狀態，如 ` every 30s · 12:03:04Z · +12 rows, −3 rows `。
+    fn watch_badge(&self) -> Option<String> {
+        let watch = self.watch.as_ref()?;
+        if watch.paused {
+            return Some(" watch paused ".to_string());
+        }
+        let mut parts = vec![format!("every {}", describe_interval(watch.interval))];
+        if self.watch_run.is_some() && watch.probing {
+            parts.push("checking…".to_string());
+        } else if self.watch_run.is_some() {
+            parts.push("refreshing…".to_string());
+        } else if let Some(at) = watch.refreshed_at {
+            parts.push(format!("refreshed {}", &timestamp(at)[11..]));
+        }
+        parts.extend(watch.diff.map(|diff| diff.badge()));
+        if watch.skipped {
+            parts.push(WATCH_SKIPPED.to_string());
+        }
+        Some(format!(" {} ", parts.join(" · ")))
+    }
+
+    /// 標籤緩存中 `iri` 之標籤，並計其命中否。
+    fn label(&self, iri: &NamedNode) -> Option<Option<String>> {
+        let label = self.labels.get(iri);
+        if let Some(metrics) = &self.metrics {
+            metrics.cache("labels", label.is_some().into(), label.is_none().into());
+        }
+        label
+    }
+
+    /// 單元格之顯示，至多 [`MAX_CELL`] 字節。僅於顯示時轉義，原值仍留於表中以供導出。
+    /// 字面量之語言標籤與數據類型另以樣式區分。
+    fn cell(&self, term: Option<&Term>) -> Line<'static> {
+        let bidi = self.config.bidi_cells;
+        if let Some(spans) = term.and_then(|term| self.renderers.render(term)) {
+            let stripped = Cell::new(false);
+            let mut spans: Vec<_> = spans
+                .into_iter()
+                .map(|span| {
+                    let (shown, had) = cell_text(&span.content, bidi);
+                    stripped.set(stripped.get() || had);
+                    Span::styled(shown.into_owned(), span.style)
+                })
+                .collect();
+            if stripped.get() {
+                spans.push(Span::styled(BIDI_MARK, self.theme.dim));
+            }
+            return Line::from(spans);
+        }
+        self.cell_within(term, MAX_CELL, bidi)
+    }
+
+    /// 單元格之顯示。逾 `max` 字節者截斷，末註其全長，以免巨大之字面量每幀複製與度量。
+    /// 雙向文字按 `bidi` 示之；去了雙向控制符者末註 [`BIDI_MARK`]。
+    pub(super) fn cell_within(&self, term: Option<&Term>, max: usize, bidi: Bidi) -> Line<'static> {
+        let stripped = Cell::new(false);
+        let show = |text: &str| {
+            let (shown, had) = cell_text(text, bidi);
+            stripped.set(stripped.get() || had);
+            shown.into_owned()
+        };
+        let mut line = self.cell_line(term, max, &show);
+        if stripped.get() {
+            line.spans.push(Span::styled(BIDI_MARK, self.theme.dim));
+        }
+        line
+    }
+
+    fn cell_line(
+        &self,
+        term: Option<&Term>,
+        max: usize,
+        show: &dyn Fn(&str) -> String,
+    ) -> Line<'static> {
+        let compact = |iri: &NamedNode| match compact_iri(iri.as_str(), &self.config.prefixes) {
+            // 無色而依類別著色，則縮寫之 IRI 亦加尖括號，如字面量之引號
+            Some(compact) if self.theme.monochrome && self.config.tint == Tint::Kind => {
+                format!("<{compact}>")
+            }
+            Some(compact) => compact,
+            None => iri.to_string(),
+        };
+        let full_length =
+            |bytes: usize| Span::styled(format!(" ({})", format_bytes(bytes)), self.theme.dim);
+        let text = match term {
+            Some(Term::NamedNode(iri)) => match self.show_labels.then(|| self.label(iri)) {
+                Some(Some(Some(label))) => label,
+                // 標籤未到者先示縮寫之 IRI，附「…」
+                Some(None) => {
+                    return Line::from(vec![
+                        Span::raw(show(&compact(iri))),
+                        Span::styled("…", self.theme.dim),
+                    ]);
+                }
+                _ => compact(iri),
+            },
+            Some(Term::Literal(literal)) => {
+                let (value, suffix) = self.literal_parts(literal.as_ref(), max);
+                let mut spans = vec![
+                    Span::raw(show(&value)),
+                    Span::styled(suffix, self.theme.literal_suffix),
+                ];
+                if literal.value().len() > max {
+                    spans.push(full_length(literal.value().len()));
+                }
+                return Line::from(spans);
+            }
+            Some(Term::Triple(triple)) => self.quoted(triple, 1, max),
+            Some(term) => term.to_string(),
+            None => return Line::styled(self.config.null.clone(), self.theme.dim),
+        };
+        match truncate(&text, max) {
+            Some(prefix) => Line::from(vec![
+                Span::raw(format!("{}…", show(prefix))),
+                full_length(text.len()),
+            ]),
+            None => Line::from(show(&text)),
+        }
+    }
+
+    /// 字面量之值與其語言標籤或數據類型，後者縮寫。值逾 `max` 字節則截斷，以「…」標之。
+    fn literal_parts(&self, literal: LiteralRef<'_>, max: usize) -> (String, String) {
+        let value = match truncate(literal.value(), max) {
+            Some(prefix) => {
+                let quoted = Literal::new_simple_literal(prefix).to_string();
+                format!("{}…\"", &quoted[..quoted.len() - 1])
+            }
+            None => Literal::new_simple_literal(literal.value()).to_string(),
+        };
+        let suffix = if let Some(language) = literal.language() {
+            format!("@{language}")
+        } else if literal.datatype() != xsd::STRING {
+            let datatype = literal.datatype().as_str();
+            format!(
+                "^^{}",
+                compact_iri(datatype, &self.config.prefixes)
+                    .unwrap_or_else(|| format!("<{datatype}>"))
+            )
+        } else {
+            String::new()
+        };
+        (value, suffix)
+    }
+
+    /// 引用三元組之顯示，如 `<< ex:a ex:b "c" >>`。嵌套逾 [`QUOTED_DEPTH`] 層者略作 `<< … >>`。
+    fn quoted(&self, triple: &Triple, depth: usize, max: usize) -> String {
+        if depth > QUOTED_DEPTH {
+            return "<< … >>".to_string();
+        }
+        let compact = |iri: &str| {
+            compact_iri(iri, &self.config.prefixes).unwrap_or_else(|| format!("<{iri}>"))
+        };
+        let term = |term: TermRef<'_>| match term {
+            TermRef::NamedNode(iri) => compact(iri.as_str()),
+            TermRef::Literal(literal) => {
+                let (value, suffix) = self.literal_parts(literal, max);
+                value + &suffix
+            }
+            TermRef::Triple(inner) => self.quoted(inner, depth + 1, max),
+            term => term.to_string(),
+        };
+        format!(
+            "<< {} {} {} >>",
+            term(triple.subject.as_ref().into()),
+            compact(triple.predicate.as_str()),
+            term(triple.object.as_ref())
+        )
+    }
+
+    /// 渲染書籤面板
+    fn render_bookmarks(&self, frame: &mut Frame, rect: Rect) {
+        let block = Block::bordered()
+            .title(self.title(self.tr("Bookmarks"), true))
+            .border_style(self.get_browser_style())
+            .padding(Padding::horizontal(1));
+        let items = self.bookmarks.entries().iter().map(|bookmark| {
+            let kind = match bookmark {
+                Bookmark::Resource { .. } => "resource ",
+                Bookmark::Query { .. } => "query    ",
+                Bookmark::View(_) => "view     ",
+            };
+            Line::from(vec![
+                Span::styled(kind, self.theme.dim),
+                sanitize(&bookmark.title()).into_owned().into(),
+            ])
+        });
+        let list = List::new(items)
+            .highlight_style(self.theme.selected)
+            .highlight_symbol(self.highlight_symbol())
+            .block(block);
+        let mut state = ListState::default().with_selected(Some(self.selected_bookmark));
+        frame.render_stateful_widget(list, rect, &mut state);
+    }
+
+    /// 渲染最近文件之列表，已不存在者暗示
+    fn render_recent(&self, frame: &mut Frame, rect: Rect) {
+        let block = Block::bordered()
+            .title(self.title(self.tr("Recent files"), true))
+            .border_style(self.get_browser_style())
+            .padding(Padding::horizontal(1));
+        let items = self.recent.entries().iter().map(|entry| {
+            let path = sanitize(&entry.path.to_string_lossy()).into_owned();
+            let details = format!(
+                "  {}",
+                self.language.fill(
+                    "{} triples, {}",
+                    &[&group_digits(entry.triples), &timestamp(entry.opened)]
+                )
+            );
+            if entry.exists() {
+                Line::from(vec![path.into(), Span::styled(details, self.theme.dim)])
+            } else {
+                Line::styled(format!("{path}  {}", self.tr("(missing)")), self.theme.dim)
+            }
+        });
+        let list = List::new(items)
+            .highlight_style(self.theme.selected)
+            .highlight_symbol(self.highlight_symbol())
+            .block(block);
+        let mut state = ListState::default().with_selected(Some(self.selected_recent));
+        frame.render_stateful_widget(list, rect, &mut state);
+    }
+
+    /// 渲染查詢歷史，所選查詢之快照示於其下。快照取自當時，存儲其後已改者標明之。
+    fn render_history(&self, frame: &mut Frame, rect: Rect) {
+        use Constraint::{Fill, Length};
+        let entry = self.history.entries().get(self.selected_history);
+        let snapshot = entry.and_then(|entry| entry.snapshot.as_ref());
+        let rows = snapshot.map_or(1, |snapshot| snapshot.rows.len().max(1) + 1);
+        let height = (rows as u16 + 2).min(rect.height / 2);
+        let [list, below] = Layout::vertical([Fill(1), Length(height)]).areas(rect);
+
+        let block = Block::bordered()
+            .title(self.title(self.tr("Query history"), true))
+            .border_style(self.get_browser_style())
+            .padding(Padding::horizontal(1));
+        let items = self.history.entries().iter().map(|entry| {
+            let details = format!("  {}", self.history_details(entry));
+            Line::from(vec![
+                sanitize(&entry.title()).into_owned().into(),
+                Span::styled(details, self.theme.dim),
+            ])
+        });
+        let items = List::new(items)
+            .highlight_style(self.theme.selected)
+            .highlight_symbol(self.highlight_symbol())
+            .block(block);
+        let mut state = ListState::default().with_selected(Some(self.selected_history));
+        frame.render_stateful_widget(items, list, &mut state);
+
+        let changed = entry.is_some_and(|entry| entry.generation != self.generation);
+        let (title, style) = if changed {
+            (
+                " Snapshot · the store has changed since; Enter runs it afresh ",
+                self.theme.warning,
+            )
+        } else {
+            (" Snapshot · store unchanged ", self.theme.dim)
+        };
+        let block = Block::bordered()
+            .title(Span::styled(title, style))
+            .border_style(style)
+            .padding(Padding::horizontal(1));
+        let Some(snapshot) = snapshot else {
+            let text = match entry {
+                Some(_) => "No snapshot kept (see snapshot_rows and snapshot_memory)",
+                None => "",
+            };
+            frame.render_widget(
+                Paragraph::new(Line::styled(text, self.theme.dim)).block(block),
+                below,
+            );
+            return;
+        };
+        let header = Row::new(
+            snapshot
+                .variables
+                .iter()
+                .map(|variable| variable.as_str().to_string()),
+        )
+        .style(self.theme.header);
+        let rows = snapshot
+            .rows
+            .iter()
+            .map(|row| Row::new(row.iter().map(|term| self.cell(term.as_deref()))));
+        let widths = vec![Fill(1); snapshot.variables.len()];
+        frame.render_widget(Table::new(rows, widths).header(header).block(block), below);
+    }
+
+    /// 歷史中一查詢之概要：行數、耗時及其時刻。
+    fn history_details(&self, entry: &history::Entry) -> String {
+        format!(
+            "{} rows, {} ms, {}",
+            group_digits(entry.rows),
+            entry.duration.as_millis(),
+            timestamp(entry.ran)
+        )
+    }
+
+    /// 僅一行一列之已完結果，如 `COUNT` 之所得，以大字示之。
+    fn tile_value<'a>(&self, table: &'a ResultTable) -> Option<&'a Term> {
+        match &table.rows[..] {
+            [row]
+                if row.len() == 1
+                    && table.progress == Progress::Complete
+                    && self.results.filter.is_none()
+                    && !self.results.tile_as_table =>
+            {
+                row[0].as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// 大字居中，其下為變量名與耗時。大字所無之字或過寬者，以常字示之。
+    fn render_tile(
+        &self,
+        frame: &mut Frame,
+        rect: Rect,
+        block: Block,
+        table: &ResultTable,
+        term: &Term,
+    ) {
+        let inner = block.inner(rect);
+        frame.render_widget(block, rect);
+        let text = match term {
+            Term::Literal(literal) => {
+                let value = literal.value();
+                match (
+                    value.parse::<usize>(),
+                    value.strip_prefix('-').map(str::parse::<usize>),
+                ) {
+                    (Ok(count), _) => group_digits(count),
+                    (_, Some(Ok(count))) => format!("-{}", group_digits(count)),
+                    _ => value.to_string(),
+                }
+            }
+            _ => String::new(),
+        };
+        let mut lines: Vec<Line> = match tile::big(&text, inner.width as usize) {
+            Some(rows) if inner.height as usize > tile::HEIGHT + 2 => rows
+                .into_iter()
+                .map(|row| Line::styled(row, self.theme.header))
+                .collect(),
+            _ => vec![self.cell(Some(term)).patch_style(self.theme.header)],
+        };
+        lines.push(Line::default());
+        let mut caption = table.variables[0].to_string();
+        if let Some(elapsed) = table.elapsed {
+            caption.push_str(&format!(" · {} ms", elapsed.as_millis()));
+        }
+        lines.push(Line::styled(caption, self.theme.dim));
+        let top = inner.height.saturating_sub(lines.len() as u16) / 2;
+        let area = Rect {
+            y: inner.y + top,
+            height: inner.height - top,
+            ..inner
+        };
+        frame.render_widget(Paragraph::new(lines).centered(), area);
+    }
+
+    /// 所選之行轉置示之：每變量一行，左為其名，右為其值。`j`/`k` 換行，`←`/`→` 選變量。
+    fn render_transposed(&self, frame: &mut Frame, rect: Rect, block: Block, table: &ResultTable) {
+        let row = self.results.selected_row.min(table.rows.len() - 1);
+        self.label_window.set(Some((row, row + 1)));
+        let block = block.title(Span::styled(
+            format!(
+                " row {} of {} · t shows the table ",
+                group_digits(row + 1),
+                group_digits(table.rows.len())
+            ),
+            self.theme.dim,
+        ));
+        let names = table
+            .variables
+            .iter()
+            .map(|variable| Span::raw(variable.to_string()).width())
+            .max()
+            .unwrap_or_default();
+        let rows = table
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(column, variable)| {
+                let value = self.cell(table.rows[row][column].as_deref());
+                Row::new([
+                    TableCell::from(Span::styled(variable.to_string(), self.theme.header)),
+                    TableCell::from(value),
+                ])
+            });
+        let widths = [
+            Constraint::Length(names.min(u16::MAX as usize) as u16),
+            Constraint::Fill(1),
+        ];
+        let list = Table::new(rows, widths)
+            .column_spacing(2)
+            .block(block)
+            .highlight_style(self.theme.selected)
+            .highlight_symbol(self.highlight_symbol());
+        let mut state = TableState::default().with_selected(self.results.selected_column);
+        frame.render_stateful_widget(list, rect, &mut state);
+    }
+
+    /// 渲染便箋面板，所選項之全文示於其下
+    fn render_scratchpad(&self, frame: &mut Frame, rect: Rect) {
+        let block = Block::bordered()
+            .title(self.title(self.tr("Scratchpad"), true))
+            .border_style(self.get_browser_style())
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(rect);
+        frame.render_widget(block, rect);
+        let pins = self.scratchpad.pins();
+        if pins.is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::styled(
+                    "Nothing pinned: P pins the selected cell, :pin <text> a snippet",
+                    self.theme.dim,
+                ))
+                .wrap(Wrap { trim: true }),
+                inner,
+            );
+            return;
+        }
+        let full = pins
+            .get(self.selected_pin)
+            .map(Pin::text)
+            .unwrap_or_default();
+        let lines: Vec<Line> = full
+            .lines()
+            .map(|line| Line::raw(sanitize(line).into_owned()))
+            .collect();
+        let width = inner.width.max(1) as usize;
+        let wrapped: usize = lines
+            .iter()
+            .map(|line| wrapped_height(&line.to_string(), width))
+            .sum();
+        // 全文至多佔一半
+        let detail = (wrapped as u16 + 1).min(inner.height / 2);
+        let parts =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(detail)]).split(inner);
+        let items = pins.iter().map(|pin| {
+            let kind = match pin {
+                Pin::Term(Term::Literal(_)) => "literal ",
+                Pin::Term(_) => "term    ",
+                Pin::Text(_) => "text    ",
+            };
+            Line::from(vec![
+                Span::styled(kind, self.theme.dim),
+                sanitize(&pin.title()).into_owned().into(),
+            ])
+        });
+        let list = List::new(items)
+            .highlight_style(self.theme.selected)
+            .highlight_symbol(self.highlight_symbol());
+        let mut state = ListState::default().with_selected(Some(self.selected_pin));
+        frame.render_stateful_widget(list, parts[0], &mut state);
+        frame.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+                Block::new()
+                    .borders(Borders::TOP)
+                    .border_style(self.theme.border),
+            ),
+            parts[1],
+        );
+    }
+
+    /// 渲染層級樹
+    fn render_hierarchy(&self, frame: &mut Frame, rect: Rect, hierarchy: &Hierarchy) {
+        let block = Block::bordered()
+            .title(
+                self.title(
+                    &self
+                        .language
+                        .fill("Hierarchy ({})", &[hierarchy.relation().name()]),
+                    true,
+                ),
+            )
+            .title_bottom(
+                self.staleness(
+                    self.hierarchy
+                        .as_ref()
+                        .is_some_and(|h| h.is_stale(self.generation)),
+                    self.hierarchy_refresh.is_some(),
+                ),
+            )
+            .border_style(self.get_browser_style())
+            .padding(Padding::horizontal(1));
+
+        let items = hierarchy.visible().into_iter().map(|node| {
+            let marker = if node.cycle {
+                "↻ "
+            } else if node.leaf {
+                "  "
+            } else if node.expanded {
+                "▾ "
+            } else {
+                "▸ "
+            };
+            let mut spans = vec!["  ".repeat(node.depth).into(), marker.into()];
+            match node.label {
+                Some(label) => {
+                    spans.push(sanitize(label).into_owned().into());
+                    spans.push(Span::styled(format!(" {}", node.iri), self.theme.dim));
+                }
+                None => spans.push(node.iri.to_string().into()),
+            }
+            if node.cycle {
+                spans.push(Span::styled(" (cycle)", self.theme.error));
+            }
+            Line::from(spans)
+        });
+        let list = List::new(items)
+            .highlight_style(self.theme.selected)
+            .highlight_symbol(self.highlight_symbol())
+            .block(block);
+        let mut state = ListState::default().with_selected(Some(hierarchy.selected()));
+        frame.render_stateful_widget(list, rect, &mut state);
+    }
+
+    /// 渲染鄰域樹。謂語下列其賓語，資源有標籤則並示之。
+    fn render_neighborhood(&self, frame: &mut Frame, rect: Rect, neighborhood: &Neighborhood) {
+        let center = match neighborhood.center_label() {
+            Some(label) => sanitize(label).into_owned(),
+            None => self.plain_cell(Some(neighborhood.center())),
+        };
+        let block = Block::bordered()
+            .title(self.title(&self.language.fill("Neighborhood of {}", &[&center]), true))
+            .title_bottom(
+                self.staleness(
+                    self.neighborhood
+                        .as_ref()
+                        .is_some_and(|n| n.is_stale(self.generation)),
+                    self.neighborhood_refresh.is_some(),
+                ),
+            )
+            .border_style(self.get_browser_style())
+            .padding(Padding::horizontal(1));
+
+        let items = neighborhood.visible().into_iter().map(|node| {
+            let marker = if node.cycle {
+                "↻ "
+            } else if node.leaf {
+                "  "
+            } else if node.expanded {
+                "▾ "
+            } else {
+                "▸ "
+            };
+            let mut spans = vec!["  ".repeat(node.depth).into(), marker.into()];
+            match node.kind {
+                Kind::Term(term) => match node.label {
+                    Some(label) => {
+                        spans.push(sanitize(label).into_owned().into());
+                        spans.push(Span::styled(
+                            format!(" {}", self.plain_cell(Some(term))),
+                            self.theme.dim,
+                        ));
+                    }
+                    None => spans.extend(self.cell(Some(term)).spans),
+                },
+                Kind::Predicate(predicate) => {
+                    let term = Term::from(predicate.clone());
+                    spans.push(Span::styled(
+                        self.plain_cell(Some(&term)),
+                        self.theme.header,
+                    ));
+                    if let Some(label) = node.label {
+                        spans.push(Span::styled(
+                            format!(" ({})", sanitize(label)),
+                            self.theme.dim,
+                        ));
+                    }
+                }
+                Kind::More(count) => spans.push(Span::styled(
+                    format!("… {} more", group_digits(*count)),
+                    self.theme.dim,
+                )),
+            }
+            if node.cycle {
+                spans.push(Span::styled(" (cycle)", self.theme.error));
+            }
+            Line::from(spans)
+        });
+        let list = List::new(items)
+            .highlight_style(self.theme.selected)
+            .highlight_symbol(self.highlight_symbol())
+            .block(block);
+        let mut state = ListState::default().with_selected(Some(neighborhood.selected()));
+        frame.render_stateful_widget(list, rect, &mut state);
+    }
+
+    /// 所選之格之項：已選列者取之，否則取末列。
+    fn preview_term(&self) -> Option<&Term> {
+        let table = self.results.table()?;
+        let column = self
+            .results
+            .selected_column
+            .unwrap_or(table.variables.len().saturating_sub(1));
+        table.row(self.results.selected_row)?.nth(column).flatten()
+    }
+
+    /// 預覽所滾之行數。所選之格已變則為零。
+    pub(super) fn preview_offset(&self) -> u16 {
+        let (row, column, scroll) = self.preview_scroll.get();
+        if (row, column) == (self.results.selected_row, self.results.selected_column) {
+            scroll
+        } else {
+            0
+        }
+    }
+
+    /// 渲染預覽窗格：所選格之全值折行顯示，標題列其數據類型或語言及字數。
+    fn render_preview(&self, frame: &mut Frame, rect: Rect) {
+        let (value, kind) = match self.preview_term() {
+            Some(Term::Literal(literal)) => (
+                literal.value().to_string(),
+                match literal.language() {
+                    Some(language) => format!("@{language}"),
+                    None => compact_iri(literal.datatype().as_str(), &self.config.prefixes)
+                        .unwrap_or_else(|| literal.datatype().to_string()),
+                },
+            ),
+            Some(Term::NamedNode(iri)) => (iri.as_str().to_string(), "IRI".to_string()),
+            Some(Term::BlankNode(node)) => (node.to_string(), "blank node".to_string()),
+            Some(term @ Term::Triple(_)) => (term.to_string(), "quoted triple".to_string()),
+            None => (String::new(), "unbound".to_string()),
+        };
+        let detail = format!(" {kind} · {} chars ", group_digits(value.chars().count()));
+        let focused = self.focus() == Focus::ResultsSecondary;
+        let block = Block::bordered()
+            .title(self.title(self.tr("Preview"), focused))
+            .title(Span::styled(detail, self.theme.dim))
+            .border_style(self.border_style(focused))
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(rect);
+        let shown = truncate(&value, MAX_PREVIEW).unwrap_or(&value);
+        let mut lines: Vec<Line> = shown
+            .lines()
+            .map(|line| Line::raw(sanitize(line).into_owned()))
+            .collect();
+        if shown.len() < value.len() {
+            lines.push(Line::styled(
+                format!(
+                    "… {} more (:export writes the full value)",
+                    format_bytes(value.len() - shown.len())
+                ),
+                self.theme.dim,
+            ));
+        }
+        // 折行後之行數，以限滾動
+        let width = inner.width.max(1) as usize;
+        let wrapped: usize = lines
+            .iter()
+            .map(|line| wrapped_height(&line.to_string(), width))
+            .sum();
+        let limit = wrapped.saturating_sub(inner.height as usize) as u16;
+        let scroll = self.preview_offset().min(limit);
+        self.preview_scroll.set((
+            self.results.selected_row,
+            self.results.selected_column,
+            scroll,
+        ));
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .scroll((scroll, 0))
+                .block(block),
+            rect,
+        );
+    }
+
+    /// 單元格之純文本。
+    pub(super) fn plain_cell(&self, term: Option<&Term>) -> String {
+        self.cell(term)
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    /// 渲染狀態欄
+    fn render_status(&self, frame: &mut Frame, rect: Rect) {
+        if let Some(job) = &self.graph_job {
+            frame.render_widget(
+                LineGauge::default()
+                    .ratio(job.ratio())
+                    .label(format!("{} ", job.label()))
+                    .style(self.theme.status)
+                    .filled_style(self.theme.focused_border),
+                rect,
+            );
+            return;
+        }
+        if let Some(job) = &self.exports.job {
+            frame.render_widget(
+                LineGauge::default()
+                    .ratio(job.ratio())
+                    .label(format!("{} (Esc cancels) ", job.label()))
+                    .style(self.theme.status)
+                    .filled_style(self.theme.focused_border),
+                rect,
+            );
+            return;
+        }
+        if let Some(download) = &self.download {
+            frame.render_widget(
+                LineGauge::default()
+                    .ratio(download.ratio())
+                    .label(format!("{} (Esc cancels) ", download.label()))
+                    .style(self.theme.status)
+                    .filled_style(self.theme.focused_border),
+                rect,
+            );
+            return;
+        }
+        if let Some(loading) = &self.loading {
+            frame.render_widget(
+                LineGauge::default()
+                    .ratio(loading.ratio(self.backend.read()))
+                    .label(format!("{} ", self.loading_label(loading)))
+                    .style(self.theme.status)
+                    .filled_style(self.theme.focused_border),
+                rect,
+            );
+            return;
+        }
+        if let Some(confirmation) = &self.confirmation {
+            frame.render_widget(
+                Line::styled(self.alert(&confirmation.message), self.theme.warning),
+                rect,
+            );
+            return;
+        }
+        frame.render_widget(
+            Line::from(self.status_spans()).style(self.theme.status),
+            rect,
+        );
+        // 如 vim 之 showcmd，示未成之按鍵序列於右
+        let keys: Vec<String> = (self.macro_count.iter().map(usize::to_string))
+            .chain(self.pending_keys.iter().map(KeyChord::to_string))
+            .collect();
+        if !keys.is_empty() {
+            frame.render_widget(
+                Line::styled(format!("{} ", keys.join(" ")), self.theme.key)
+                    .alignment(Alignment::Right),
+                rect,
+            );
+        }
+    }
+
+    /// 載入進度之說明。
+    fn loading_label(&self, loading: &Loading) -> String {
+        let verb = match loading.reload {
+            Some(_) => "Reloading",
+            None => "Loading",
+        };
+        if let [path] = &loading.paths[..] {
+            return format!("{verb} {}", path.display());
+        }
+        let failed = loading.failures.len();
+        format!(
+            "{verb} {} files: {} done, {failed} failed",
+            loading.paths.len(),
+            loading.done() - failed
+        )
+    }
+
+    /// 狀態欄之常文：服務、聯邦查詢、警告、結果之大小及狀態訊息。
+    fn status_spans(&self) -> Vec<Span<'static>> {
+        let mut spans = vec![];
+        if let Some(server) = &self.server {
+            spans.push(Span::styled(
+                format!(
+                    "serving {} · {} requests  ",
+                    server.url(),
+                    server.requests()
+                ),
+                self.theme.dim,
+            ));
+        }
+        if self.federated {
+            let requests = self
+                .results
+                .current
+                .as_ref()
+                .map_or(0, |r| r.remote_requests);
+            spans.push(Span::styled(
+                format!("federated · {requests} remote requests  "),
+                self.theme.warning,
+            ));
+        }
+        if let Some(recording) = &self.recording {
+            spans.push(Span::styled(
+                format!(
+                    "recording @{} · {}  ",
+                    recording.register,
+                    recording.actions.len()
+                ),
+                self.theme.warning,
+            ));
+        }
+        if let Some(warning) = &self.warning {
+            spans.push(Span::styled(
+                format!("{}  ", self.alert(warning)),
+                self.theme.warning,
+            ));
+        }
+        if let Some(table) = self.results.table() {
+            let rows = format!(
+                "{} rows · ~{}",
+                abbreviate_count(table.rows.len()),
+                format_bytes(table.memory())
+            );
+            // 無命名圖則默認圖即全部，不示；非默認者醒目，以免忘其所設
+            match self.dataset {
+                Dataset::Default if !self.has_named_graphs() => {
+                    spans.push(Span::styled(format!("{rows}  "), self.theme.dim));
+                }
+                Dataset::Default => spans.push(Span::styled(
+                    format!("{rows} · {}  ", self.dataset.label()),
+                    self.theme.dim,
+                )),
+                _ => spans.extend([
+                    Span::styled(format!("{rows} · "), self.theme.dim),
+                    Span::styled(format!("{}  ", self.dataset.label()), self.theme.warning),
+                ]),
+            }
+        }
+        let memory: usize = self.tab_memory().iter().sum();
+        if memory > 0 {
+            // 預算唯限當前之頁
+            let over = self
+                .config
+                .memory_budget
+                .is_some_and(|budget| self.memory_usage().total() > budget);
+            spans.push(Span::styled(
+                format!("mem ~{}  ", memory::abbreviate(memory)),
+                match over {
+                    true => self.theme.warning,
+                    false => self.theme.dim,
+                },
+            ));
+        }
+        if let Some(status) = &self.status {
+            spans.push(status.clone().into());
+        }
+        spans
+    }
+
+    /// 提示欄之按鍵與譯後之簡稱。
+    fn hints(&self) -> Vec<(String, &'static str)> {
+        let hints = if self.help || self.popup.is_some() {
+            vec![(self.tr("any key").to_string(), "close")]
+        } else if self.confirmation.is_some() {
+            vec![
+                ("y".to_string(), "confirm"),
+                (self.tr("any key").to_string(), "cancel"),
+            ]
+        } else if self.example.is_some() {
+            vec![
+                ("↑/↓".to_string(), "column"),
+                ("←/→".to_string(), "choose"),
+                ("enter".to_string(), "insert"),
+                ("esc".to_string(), "cancel"),
+            ]
+        } else {
+            self.config.keymap.hints(self.mode)
+        };
+        hints
+            .into_iter()
+            .map(|(key, label)| (key, self.tr(label)))
+            .collect()
+    }
+
+    /// 渲染按鍵提示。取自鍵位，故反映重映射；浮窗與確認提示在時列其按鍵。
+    /// 寬度不足則整條捨去末尾之提示，不截斷半條。
+    fn render_hints(&self, frame: &mut Frame, rect: Rect) {
+        let mut spans = vec![];
+        let mut width = 0;
+        for (key, label) in self.hints() {
+            let separator = if spans.is_empty() { "" } else { "  " };
+            let hint_width =
+                separator.len() + Span::raw(&key).width() + 1 + Span::raw(label).width();
+            if width + hint_width > rect.width as usize {
+                break;
+            }
+            width += hint_width;
+            spans.push(Span::raw(separator));
+            spans.push(Span::styled(key, self.theme.key));
+            spans.push(Span::styled(format!(" {label}"), self.theme.dim));
+        }
+        frame.render_widget(Line::from(spans), rect);
+    }
+
+    /// 渲染命令行，路徑參數之後示其存否、是否將覆蓋
+    fn render_command_line(&self, frame: &mut Frame, rect: Rect) {
+        let line = format!(":{}", self.command_line.input);
+        frame.set_cursor(rect.x + line.chars().count() as u16, rect.y);
+        let mut spans = vec![Span::raw(line)];
+        if let Some(state) = self.command_line.path_arg().and_then(|arg| arg.state()) {
+            let style = if state.is_alarming() {
+                self.theme.warning
+            } else {
+                self.theme.dim
+            };
+            spans.push(Span::styled(
+                format!("  [{}]", self.tr(state.label())),
+                style,
+            ));
+        }
+        frame.render_widget(Line::from(spans), rect);
+    }
+
+    /// 渲染改值之輸入行：謂語、值，及其語言標籤或數據類型。
+    fn render_edit_line(&self, frame: &mut Frame, rect: Rect) {
+        let Some(edit) = &self.edit else {
+            return;
+        };
+        let predicate = compact_iri(edit.predicate.as_str(), &self.config.prefixes)
+            .unwrap_or_else(|| edit.predicate.to_string());
+        let prompt = format!("{}: ", sanitize(&predicate));
+        let value = sanitize(&self.edit_line.input).into_owned();
+        let (_, suffix) = self.literal_parts(edit.object.as_ref(), MAX_CELL);
+        frame.set_cursor(
+            rect.x
+                + (Line::raw(prompt.as_str()).width() + Line::raw(value.as_str()).width()) as u16,
+            rect.y,
+        );
+        frame.render_widget(
+            Line::from(vec![
+                Span::styled(prompt, self.theme.key),
+                Span::raw(value),
+                Span::styled(format!(" {suffix}"), self.theme.literal_suffix),
+            ]),
+            rect,
+        );
+    }
+
+    /// 渲染過濾之輸入行：模式、是否正則，及無效之因。
+    fn render_filter_line(&self, frame: &mut Frame, rect: Rect) {
+        let line = format!("/{}", self.results.filter_line.input);
+        frame.set_cursor(rect.x + Line::raw(line.as_str()).width() as u16, rect.y);
+        let mut spans = vec![Span::raw(line)];
+        spans.push(Span::styled(
+            if self.results.filter_regex {
+                "  [regex]"
+            } else {
+                "  [text]"
+            },
+            self.theme.dim,
+        ));
+        if let Some(error) = &self.results.filter_error {
+            spans.push(Span::styled(
+                format!("  {}", self.alert(error)),
+                self.theme.error,
+            ));
+        }
+        frame.render_widget(Line::from(spans), rect);
+    }
+
+    /// 列統計之浮窗中之各行。
+    fn column_stats_lines(&self, stats: &ColumnStats) -> Vec<Line<'static>> {
+        self.stat_lines(stats.lines(&self.config.prefixes))
+    }
+
+    /// 名與值之各行，名對齊；長名如 `xsd:dateTime` 亦留一空。
+    fn stat_lines(&self, lines: Vec<(String, String)>) -> Vec<Line<'static>> {
+        let width = lines
+            .iter()
+            .map(|(name, _)| name.chars().count() + 1)
+            .fold(11, usize::max);
+        lines
+            .into_iter()
+            .map(|(name, value)| {
+                Line::from(vec![
+                    Span::styled(format!("{name:<width$}"), self.theme.key),
+                    Span::raw(sanitize(&value).into_owned()),
+                ])
+            })
+            .collect()
+    }
+
+    /// 已執行之更新，新者居先：時、概述與其文本。
+    /// 檢查點之浮窗：序號、文件名、範圍與大小，新者居先。
+    fn checkpoint_lines(&self, checkpoints: &[Checkpoint]) -> Vec<Line<'static>> {
+        let mut lines: Vec<Line> = checkpoints
+            .iter()
+            .enumerate()
+            .map(|(i, checkpoint)| {
+                Line::from(vec![
+                    Span::styled(format!("{:>2} ", i + 1), self.theme.key),
+                    Span::raw(sanitize(&checkpoint.name()).into_owned()),
+                    Span::styled(
+                        format!(
+                            "  {} · {}",
+                            checkpoint.scope.describe(),
+                            format_bytes(checkpoint.bytes as usize)
+                        ),
+                        self.theme.dim,
+                    ),
+                ])
+            })
+            .collect();
+        lines.push(Line::styled(
+            ":restore-checkpoint <n> restores one",
+            self.theme.dim,
+        ));
+        lines
+    }
+
+    fn update_lines(&self) -> Vec<Line<'static>> {
+        if self.recent_updates.is_empty() {
+            return vec![Line::styled("No updates yet", self.theme.dim)];
+        }
+        let mut lines = vec![];
+        for (at, update) in self.recent_updates.iter().rev() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{} ", &timestamp(*at)[11..]), self.theme.key),
+                Span::raw(sanitize(&update.summary).into_owned()),
+            ]));
+            lines.extend(
+                update
+                    .text
+                    .lines()
+                    .map(|line| Line::styled(format!("  {}", sanitize(line)), self.theme.dim)),
+            );
+        }
+        lines
+    }
+
+    /// 渲染任意鍵即關之浮窗，居中，寬隨內容。
+    fn render_popup(&self, frame: &mut Frame, rect: Rect, title: &str, lines: Vec<Line>) {
+        let content = lines.iter().map(Line::width).max().unwrap_or(0);
+        let width = (content.max(Line::raw(title).width()) as u16 + 4).min(rect.width);
+        let height = (lines.len() as u16 + 2).min(rect.height);
+        let area = Rect::new(
+            rect.x + (rect.width - width) / 2,
+            rect.y + (rect.height - height) / 2,
+            width,
+            height,
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .title(Span::styled(title.to_string(), self.theme.title))
+                    .border_style(self.theme.focused_border)
+                    .padding(Padding::horizontal(1)),
+            ),
+            area,
+        );
+    }
+
+    /// 渲染調試浮層於右上角，不取焦點，其下之操作照常。
+    fn render_debug(&self, frame: &mut Frame, rect: Rect, metrics: &Metrics) {
+        let rows = metrics.lines(metrics::SAMPLES / 2);
+        let name_width = rows
+            .iter()
+            .map(|(name, ..)| Span::raw(name.as_str()).width())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = rows
+            .into_iter()
+            .map(|(name, sparkline, value)| {
+                Line::from(vec![
+                    Span::styled(pad(&name, name_width), self.theme.dim),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{sparkline:<width$}", width = metrics::SAMPLES / 2),
+                        self.theme.title,
+                    ),
+                    Span::raw(" "),
+                    Span::raw(value),
+                ])
+            })
+            .collect();
+        let content = lines.iter().map(Line::width).max().unwrap_or(0);
+        let width = (content as u16 + 4).min(rect.width);
+        let height = (lines.len() as u16 + 2).min(rect.height);
+        let area = Rect::new(rect.right() - width, rect.y, width, height);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .title(Span::styled(" Debug (F12) ", self.theme.title))
+                    .border_style(self.theme.border)
+                    .padding(Padding::horizontal(1)),
+            ),
+            area,
+        );
+    }
+
+    /// 渲染幫助浮窗
+    fn render_help(&self, frame: &mut Frame, rect: Rect) {
+        let lines = self.help_lines();
+        let width = 64.min(rect.width);
+        let height = (lines.len() as u16 + 2).min(rect.height);
+        let area = Rect::new(
+            rect.x + (rect.width - width) / 2,
+            rect.y + (rect.height - height) / 2,
+            width,
+            height,
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .title(Span::styled(self.tr("Help"), self.theme.title))
+                    .border_style(self.theme.focused_border),
+            ),
+            area,
+        );
+    }
+
+    /// 幫助之內容：當前模式之有效鍵位及諸命令。
+    fn help_lines(&self) -> Vec<Line<'static>> {
+        let keys: Vec<_> = self
+            .config
+            .keymap
+            .bindings(self.mode)
+            .into_iter()
+            .filter_map(|(action, keys)| Some((keys.join(" / "), self.tr(action.description()?))))
+            .collect();
+        let mut lines: Vec<Line> = vec![Line::styled(self.tr("Keys"), self.theme.title)];
+        lines.extend(keys.iter().map(|(key, help)| {
+            Line::from(vec![
+                Span::styled(format!("  {key:<14}"), self.theme.key),
+                (*help).into(),
+            ])
+        }));
+        lines.push("".into());
+        lines.push(Line::styled(self.tr("Commands"), self.theme.title));
+        lines.extend(COMMANDS.iter().map(|(name, args, help)| {
+            Line::from(vec![
+                Span::styled(format!("  :{name} {args:<24}"), self.theme.key),
+                self.tr(help).into(),
+            ])
+        }));
+        lines
+    }
+
+    /// 查詢部分之高度，依其內寬折行而計，至多佔終端之半。三元組模式之表單則每欄一行。
+    pub(super) fn query_height(&self, size: Rect) -> u16 {
+        let height = if self.shows_pattern() {
+            FIELDS.len() + 2
+        } else {
+            self.query.height(size.width.saturating_sub(2) as usize)
+        };
+        (height.min(usize::from(size.height / 2)) as u16).max(3)
+    }
+
+    /// 頂部是否為三元組模式之表單，而非查詢。
+    fn shows_pattern(&self) -> bool {
+        self.mode == Mode::Pattern || self.results.pattern().is_some()
+    }
+
+    /// 渲染三元組模式之表單。空欄為通配。
+    fn render_pattern(&self, frame: &mut Frame, rect: Rect) {
+        let editing = self.mode == Mode::Pattern;
+        let form = &self.pattern_form;
+        let lines: Vec<Line> = FIELDS
+            .iter()
+            .zip(&form.fields)
+            .enumerate()
+            .map(|(index, (name, text))| {
+                let label = if editing && index == form.focus {
+                    self.theme.key
+                } else {
+                    self.theme.dim
+                };
+                let value = if text.is_empty() {
+                    Span::styled(self.tr("any"), self.theme.dim)
+                } else {
+                    Span::raw(sanitize(text).into_owned())
+                };
+                Line::from(vec![Span::styled(pad(self.tr(name), 10), label), value])
+            })
+            .collect();
+        let block = Block::bordered()
+            .title(self.title(self.tr("Pattern"), editing))
+            .border_style(self.get_query_style());
+        if editing {
+            let inner = block.inner(rect);
+            let column = 10 + form.fields[form.focus].chars().count() as u16;
+            frame.set_cursor(
+                (inner.x + column).min(inner.right().saturating_sub(1)),
+                inner.y + form.focus as u16,
+            );
+        }
+        frame.render_widget(Paragraph::new(lines).block(block), rect);
+    }
+
+    /// 渲染查詢部分。內容超出時捲至末尾，以見輸入處。
+    fn render_query(&self, frame: &mut Frame, rect: Rect) {
+        if self.shows_pattern() {
+            return self.render_pattern(frame, rect);
+        }
+        // 僅取折行後可見之末若干行，不構造整個查詢；首行或僅見其尾，捲去其頭
+        let visible = rect.height.saturating_sub(2) as usize;
+        let lines = self.query.lines();
+        let (mut first, mut shown) = (lines.len(), 0);
+        for rows in self
+            .query
+            .rows(rect.width.saturating_sub(2) as usize)
+            .iter()
+            .rev()
+        {
+            if shown >= visible {
+                break;
+            }
+            first -= 1;
+            shown += rows;
+        }
+        let scroll = shown.saturating_sub(visible) as u16;
+        let lines: Vec<Line> = lines[first..]
+            .iter()
+            .map(|line| {
+                let text: String = line
+                    .spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect();
+                match Counts::of(&text).total() {
+                    0 => line.clone(),
+                    _ => typography::mark(&text, self.theme.suspicious),
+                }
+            })
+            .collect();
+        let (text, scroll) = match &self.imported {
+            Some(path) => (
+                Line::styled(
+                    sanitize(
+                        &self
+                            .language
+                            .fill("Imported from {}", &[&path.display().to_string()]),
+                    )
+                    .into_owned(),
+                    self.theme.dim,
+                )
+                .into(),
+                0,
+            ),
+            None => (Text::from(lines), scroll),
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .scroll((scroll, 0))
+                .block(
+                    Block::bordered()
+                        .title(self.title(
+                            self.tr(if self.query.is_dirty() {
+                                "Query *"
+                            } else {
+                                "Query"
+                            }),
+                            self.focus() == Focus::QueryEditor,
+                        ))
+                        .border_style(self.get_query_style()),
+                ),
+            rect,
+        );
+    }
+
+    /// 部分之標題。無色時以 `[FOCUS]` 標明焦點。
+    fn title(&self, text: &str, focused: bool) -> Line<'static> {
+        let mut spans = vec![Span::styled(text.to_string(), self.theme.title)];
+        if focused && self.theme.monochrome {
+            spans.push(Span::styled(" [FOCUS]", self.theme.title));
+        }
+        Line::from(spans)
+    }
+
+    /// 面板底邊之標記：存儲已改而後台重算中，或待按 `R` 重算。未過時則空。
+    fn staleness(&self, stale: bool, refreshing: bool) -> Line<'static> {
+        match (stale, refreshing) {
+            (_, true) => Line::styled(self.tr(" refreshing… "), self.theme.dim),
+            (true, false) => Line::styled(
+                self.alert(self.tr(" stale — press R to refresh ")),
+                self.theme.warning,
+            ),
+            (false, false) => Line::default(),
+        }
+    }
+
+    /// 錯誤之一行。
+    fn error_line(&self, text: &str) -> Line<'static> {
+        Line::styled(self.alert(text), self.theme.error)
+    }
+
+    /// 錯誤或警告之文字。無色時冠以 `!`，以別於常文。
+    fn alert(&self, text: &str) -> String {
+        if self.theme.monochrome {
+            format!("! {text}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// 所選行之標記。僅無色時需之。
+    fn highlight_symbol(&self) -> &'static str {
+        if self.theme.monochrome {
+            "> "
+        } else {
+            ""
+        }
+    }
+
+    /// 結果表或代之之面板有焦點。輸入行作用於其上，亦算之。
+    fn browser_focused(&self) -> bool {
+        !matches!(self.focus(), Focus::QueryEditor | Focus::ResultsSecondary)
+    }
+
+    /// 有無焦點之框線樣式。
+    fn border_style(&self, focused: bool) -> Style {
+        match focused {
+            true => self.theme.focused_border,
+            false => self.theme.border,
+        }
+    }
+
+    /// 瀏覽部分樣式。
+    fn get_browser_style(&self) -> Style {
+        self.border_style(self.browser_focused())
+    }
+
+    /// 查詢部分樣式。
+    fn get_query_style(&self) -> Style {
+        self.border_style(self.focus() == Focus::QueryEditor)
+    }
+}
+
+/// 部件繪製失敗之說明：panic 之消息，及餘者仍可用。
+pub(super) fn failure_lines(message: &str) -> Vec<Line<'static>> {
+    vec![
+        Line::raw(format!("panic: {message}")),
+        Line::raw("The rest still works: export your results, then restart."),
+    ]
+}
+
+/// 按詞折行後所佔之行數，如 `Wrap { trim: false }`。過長之詞斷開。
+pub(super) fn wrapped_height(text: &str, width: usize) -> usize {
+    let (mut lines, mut used) = (1, 0);
+    for word in text.split(' ') {
+        let word = Span::raw(word).width();
+        let needed = if used == 0 { word } else { used + 1 + word };
+        if needed <= width {
+            used = needed;
+            continue;
+        }
+        if used > 0 {
+            lines += 1;
+        }
+        lines += word.saturating_sub(1) / width;
+        used = if word == 0 { 0 } else { (word - 1) % width + 1 };
+    }
+    lines
+}
+
+/// 緩存之單元格數上限。滾過大表後清空重來，以免緩存與表同大。
+pub(super) const DISPLAY_CACHE: usize = 64 * 1024;
+
+/// 單元格中引用三元組顯示之嵌套層數。
+pub(super) const QUOTED_DEPTH: usize = 2;
+
+/// 可用之最小終端尺寸。
+pub(super) const MIN_WIDTH: u16 = 40;
+
+pub(super) const MIN_HEIGHT: u16 = 10;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::{app_with_rows, monochrome_app, render, rows, text, update_all};
+    use crate::{
+        display::{group_digits, MAX_CELL},
+        locale::Language,
+        renderer::CellRenderer,
+        sink::Sink,
+        theme::Theme,
+    };
+    use oxigraph::{
+        io::{RdfFormat, RdfParser},
+        model::{GraphNameRef, NamedNodeRef, QuadRef},
+        sparql::results::QueryResultsFormat,
+    };
+    use ratatui::{
+        backend::TestBackend,
+        buffer::Buffer,
+        crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+        layout::{Constraint, Layout, Rect},
+        style::Color,
+        text::Span,
+        widgets::Paragraph,
+        Frame,
+    };
+    use std::fs;
+
+    #[test]
+    fn a_panicking_pane_becomes_an_error_box_and_the_rest_still_draws() {
+        let mut app = app_with_rows();
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let [top, bottom] = Layout::vertical([Constraint::Length(6), Constraint::Fill(1)])
+            .areas(Rect::new(0, 0, 80, 20));
+        let fake = |frame: &mut Frame| {
+            frame.render_widget(Paragraph::new("half drawn"), top);
+            panic!("exotic literal");
+        };
+        terminal
+            .draw(|frame| {
+                app.render_pane(frame, top, "facets", fake);
+                app.render_pane(frame, bottom, "results", |frame| {
+                    app.render_browser(frame, bottom)
+                });
+            })
+            .unwrap();
+        let screen = text(terminal.backend().buffer());
+        assert!(screen.contains("Could not draw the facets"));
+        assert!(screen.contains("panic: exotic literal"));
+        assert!(!screen.contains("half drawn"));
+        assert!(screen.contains("http://ex/c"));
+
+        // 開發時不捕之
+        app.set_contain_panics(false);
+        let drawn = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            terminal
+                .draw(|frame| app.render_pane(frame, top, "facets", fake))
+                .map(|_| ())
+        }));
+        assert!(drawn.is_err());
+    }
+
+    #[test]
+    fn no_color_emits_no_color_attributes() {
+        let mut app = monochrome_app();
+        for mode in [Mode::Browse, Mode::Query] {
+            app.mode = mode;
+            let buffer = render(&mut app);
+            for cell in &buffer.content {
+                assert_eq!(cell.fg, Color::Reset, "foreground of {:?}", cell.symbol());
+                assert_eq!(cell.bg, Color::Reset, "background of {:?}", cell.symbol());
+            }
+        }
+    }
+
+    #[test]
+    fn no_color_marks_focus_and_selection() {
+        let mut app = monochrome_app();
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Explore [FOCUS]"));
+        assert!(!screen.contains("Query [FOCUS]"));
+        assert!(screen.contains("> <http://ex/a>"));
+
+        app.mode = Mode::Query;
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Query [FOCUS]"));
+        assert!(!screen.contains("Explore [FOCUS]"));
+    }
+
+    #[test]
+    fn cells_are_tinted_by_kind_or_column_but_not_when_selected() {
+        let mut app = app_with_rows();
+        app.theme = Theme::default();
+        let at = |buffer: &Buffer, needle: &str| {
+            let width = buffer.area.width as usize;
+            let text = text(buffer);
+            let index = text.find(needle).unwrap();
+            let index = text[..index].chars().count();
+            buffer
+                .get((index % width) as u16, (index / width) as u16)
+                .clone()
+        };
+        update_all(&mut app, [Action::CycleTint]);
+        assert_eq!(app.config.tint, Tint::Kind);
+        let buffer = render(&mut app);
+        assert_eq!(at(&buffer, "<http://ex/b>").fg, Color::Cyan);
+        assert_eq!(at(&buffer, "\"2\"").fg, Color::Green);
+        // 所選之行不著色
+        assert_eq!(at(&buffer, "<http://ex/c>").fg, Color::Reset);
+
+        update_all(&mut app, [Action::CycleTint]);
+        let buffer = render(&mut app);
+        let tints = app.theme.column_tints;
+        assert_eq!(Some(at(&buffer, "<http://ex/b>").bg), tints[0].bg);
+        assert_eq!(Some(at(&buffer, "\"2\"").bg), tints[2].bg);
+        assert_ne!(tints[0].bg, tints[1].bg);
+
+        let mut app = monochrome_app();
+        app.config.prefixes = vec![("ex".to_string(), "http://ex/".to_string())];
+        assert!(text(&render(&mut app)).contains(" ex:a "));
+        update_all(&mut app, [Action::CycleTint]);
+        let shown = text(&render(&mut app));
+        assert!(shown.contains("<ex:a>"), "{shown}");
+        assert!(shown.contains("\"c\"@en"), "{shown}");
+    }
+
+    #[test]
+    fn no_color_marks_errors() {
+        let mut app = monochrome_app();
+        app.query.set("SELECT WHERE".to_string());
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("! error"));
+    }
+
+    #[test]
+    fn linear_mode_reads_one_row_at_a_time_without_box_drawing() {
+        let mut app = app_with_rows();
+        app.set_linear(true);
+        app.mode = Mode::Browse;
+        let no_box = |shown: &str| {
+            !shown
+                .chars()
+                .any(|c| ('\u{2500}'..='\u{257f}').contains(&c))
+        };
+        let shown = text(&render(&mut app));
+        assert!(shown.contains("Browse mode."), "{shown}");
+        assert!(
+            shown.contains("row 1 of 3: s = <http://ex/c>, p = <http://ex/p>"),
+            "{shown}"
+        );
+        assert!(shown.contains("Keys: "), "{shown}");
+        assert!(no_box(&shown), "{shown}");
+
+        update_all(&mut app, [Action::ScrollRows(1)]);
+        let shown = text(&render(&mut app));
+        assert!(shown.contains("row 2 of 3: s = <http://ex/b>"), "{shown}");
+        // 標記後移至下一行
+        update_all(&mut app, [Action::ToggleMark, Action::ScrollRows(-1)]);
+        let shown = text(&render(&mut app));
+        assert!(shown.contains("row 2 of 3: s = <http://ex/b>"), "{shown}");
+        assert!(shown.contains("(marked)"), "{shown}");
+
+        update_all(&mut app, [Action::OpenHierarchy]);
+        assert_eq!(app.mode, Mode::Browse);
+        let shown = text(&render(&mut app));
+        assert!(
+            shown.contains("The class hierarchy is not available in linear mode"),
+            "{shown}"
+        );
+
+        app.help = true;
+        let shown = text(&render(&mut app));
+        assert!(shown.contains("Keys"), "{shown}");
+        assert!(no_box(&shown), "{shown}");
+    }
+
+    #[test]
+    fn registered_renderers_show_their_datatype() {
+        struct Shout;
+        impl CellRenderer for Shout {
+            fn render(&self, term: &Term) -> Option<Vec<Span<'static>>> {
+                let Term::Literal(literal) = term else {
+                    return None;
+                };
+                Some(vec![Span::raw(format!(
+                    "{}!",
+                    literal.value().to_uppercase()
+                ))])
+            }
+        }
+        let mut app = App::new().unwrap();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "<http://ex/a> <http://ex/p> \"hello\"^^<http://ex/shout> , \"plain\" .".as_bytes(),
+            )
+            .unwrap();
+        app.query.set("SELECT ?o { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("\"hello\"^^<http://ex/shout>"), "{screen}");
+
+        app.register_renderer("http://ex/shout", Shout);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("HELLO!"), "{screen}");
+        assert!(screen.contains("\"plain\""));
+        // 導出仍用原值
+        let table = app.table_text(app.results.table().unwrap());
+        assert!(table.iter().flatten().any(|cell| cell.contains("hello")));
+    }
+
+    #[test]
+    fn preview_shows_the_whole_selected_value() {
+        let mut app = App::new().unwrap();
+        let words: Vec<String> = (1..=150).map(|n| format!("word{n}")).collect();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                format!(
+                    "<http://ex/a> <http://ex/comment> \"{}\"@en .",
+                    words.join(" ")
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        update_all(&mut app, [Action::TogglePreview]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Preview"));
+        assert!(screen.contains(&format!(
+            "@en · {} chars",
+            group_digits(words.join(" ").len())
+        )));
+        assert!(screen.contains("word10 "));
+        assert!(!screen.contains("word150"));
+
+        // 滾至末行即止
+        update_all(&mut app, (0..40).map(|_| Action::ScrollPreview(1)));
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("word150"));
+        assert!(!screen.contains("word10 "));
+        let end = app.preview_offset();
+        update_all(&mut app, [Action::ScrollPreview(-1)]);
+        assert_eq!(app.preview_offset(), end - 1);
+        assert_eq!(wrapped_height("aaaa bb cccccccccc", 4), 5);
+    }
+
+    #[test]
+    fn empty_stores_empty_results_and_errors_read_differently() {
+        let mut app = App::new().unwrap();
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("note: the store is empty"), "{screen}");
+        assert!(!screen.contains("NO RESULT"));
+        // 查詢無行亦注之，三元組模式則不執行
+        app.query.set("SELECT ?s { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(rows(&app), 0);
+        assert!(text(&render(&mut app)).contains("load data with :open"));
+        update_all(&mut app, [Action::OpenPattern, Action::Activate]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("note: the store is empty"));
+        assert!(matches!(
+            app.results.current.as_ref().unwrap().source,
+            Source::Query
+        ));
+
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?o WHERE { ?s ?p ?o FILTER(?o = 42) }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("?s") && screen.contains("?o"), "{screen}");
+        assert!(screen.contains("The query returned no rows"));
+        assert!(!screen.contains("note:"));
+        // 無行之結果亦可導出，僅有表頭
+        let csv = std::env::temp_dir().join(format!("rdf-tui-no-rows-{}.csv", std::process::id()));
+        app.start_export(
+            QueryResultsFormat::Csv.into(),
+            Sink::File(csv.clone()),
+            false,
+        )
+        .unwrap();
+        assert_eq!(app.wait_for_export(), Some(Ok(0)));
+        assert_eq!(fs::read_to_string(&csv).unwrap(), "s,o\r\n");
+        fs::remove_file(csv).unwrap();
+
+        app.query.set("SELECT WHERE".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        let screen = text(&render(&mut app));
+        assert!(!screen.contains("NO RESULT") && !screen.contains("returned no rows"));
+        assert!(screen.contains("error"), "{screen}");
+    }
+
+    #[test]
+    fn single_values_show_as_big_text_until_toggled() {
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT (COUNT(*) AS ?n) WHERE { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("███"));
+        assert!(screen.contains("?n · "));
+        assert!(!screen.contains("?n  "));
+
+        let key = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE);
+        assert_eq!(app.map_key(key), [Action::ToggleTile]);
+        update_all(&mut app, [Action::ToggleTile]);
+        let screen = text(&render(&mut app));
+        assert!(!screen.contains("███"));
+        assert!(screen.contains("?n"));
+
+        update_all(&mut app, [Action::ToggleTile]);
+        app.query
+            .set("SELECT ?s WHERE { ?s ?p ?o } LIMIT 2".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        assert!(!text(&render(&mut app)).contains("███"));
+    }
+
+    #[test]
+    fn chinese_interface_translates_titles_hints_and_help() {
+        let mut app = monochrome_app();
+        app.set_language(Language::Zh);
+        let buffer = render(&mut app);
+        let bottom: String = (0..buffer.area.width)
+            .map(|x| buffer.get(x, buffer.area.height - 1).symbol())
+            .collect::<String>()
+            .split_whitespace()
+            .collect();
+        let shown: String = text(&buffer).split_whitespace().collect();
+        assert!(shown.contains("瀏覽"), "{shown}");
+        assert!(shown.contains("查詢"), "{shown}");
+        // 寬字以兩格計，末尾之提示整條捨去
+        let hints: Vec<String> = app
+            .config
+            .keymap
+            .hints(Mode::Browse)
+            .into_iter()
+            .map(|(key, label)| format!("{key}{}", app.tr(label)))
+            .collect();
+        assert!(bottom.contains("退出"), "{bottom}");
+        assert!(
+            (1..hints.len()).any(|shown| hints[..shown].concat() == bottom),
+            "{bottom}"
+        );
+
+        app.help = true;
+        let shown: String = text(&render(&mut app)).split_whitespace().collect();
+        assert!(shown.contains("按鍵"), "{shown}");
+        assert!(shown.contains("不經確認即退出"), "{shown}");
+    }
+
+    #[test]
+    fn columns_are_sized_by_their_content() {
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?o ?p WHERE { ?s ?p ?o }".to_string());
+        let screen = render(&mut app);
+        let header = (0..screen.area.height)
+            .map(|y| {
+                (0..screen.area.width)
+                    .map(|x| screen.get(x, y).symbol())
+                    .collect::<String>()
+            })
+            .rfind(|line| line.contains("?s"))
+            .unwrap();
+        // 整數字面量最寬，IRI 各得其寬而不均分
+        let column = |name: &str| header.find(name).unwrap();
+        assert!(column("?p") - column("?o") > column("?o") - column("?s"));
+        assert_eq!(app.results.table().unwrap().widths.borrow().widths()[0], 13);
+
+        // 不取樣則僅量可見之行
+        app.config.width_sample = 0;
+        app.query.set("SELECT ?s WHERE { ?s ?p ?o }".to_string());
+        render(&mut app);
+        assert_eq!(app.results.table().unwrap().widths.borrow().widths(), [13]);
+    }
+
+    #[test]
+    fn enormous_literals_are_capped_for_display_only() {
+        let mut app = App::new().unwrap();
+        let blob = "QUJD".repeat(1_500_000);
+        app.store
+            .insert(QuadRef::new(
+                NamedNodeRef::new_unchecked("http://ex/a"),
+                NamedNodeRef::new_unchecked("http://ex/p"),
+                oxigraph::model::LiteralRef::new_simple_literal(&blob),
+                GraphNameRef::DefaultGraph,
+            ))
+            .unwrap();
+        render(&mut app);
+        // 每幀僅複製與度量截斷後之格；預覽亦僅折行其前段
+        let start = Instant::now();
+        for _ in 0..20 {
+            render(&mut app);
+        }
+        app.preview = true;
+        app.results.selected_column = Some(2);
+        render(&mut app);
+        update_all(&mut app, [Action::ScrollPreview(i16::MAX as isize)]);
+        assert!(text(&render(&mut app)).contains("(:export writes the full value)"));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+
+        let table = app.results.table().unwrap();
+        let cached: usize = table
+            .display
+            .borrow()
+            .values()
+            .flat_map(|line| &line.spans)
+            .map(|span| span.content.len())
+            .sum();
+        assert!(cached < 2 * MAX_CELL, "{cached} bytes cached");
+        let cell = app.plain_cell(table.rows[0][2].as_deref());
+        assert!(cell.ends_with("…\" (5.7 MB)"));
+        assert_eq!(app.table_text(table)[1][2].len(), blob.len() + 2);
+        assert!(app.column_widths()[2] <= MAX_CELL);
+    }
+}
@@ -0,0 +1,2155 @@
+//! 結果：緩存之 [`ResultTable`]、所選之行列、排序、過濾、列之統計與結果之比較。
+
+use super::{details_query, App, DiffView, Mode, Popup, ValueDiff};
+use crate::{
+    action::Action,
+    aggregate::ColumnStats,
+    chardiff, check,
+    command::CommandLine,
+    definition,
+    diff::Partition,
+    display::{group_digits, sanitize},
+    dupes::Dupes,
+    filter::{Filter, Matches},
+    functional::{self, Functional},
+    generation::Generation,
+    header::{self, Order},
+    history::{self, Snapshot},
+    inspect,
+    intern::Interner,
+    label::resolve_label,
+    memory,
+    pattern::Pattern,
+    scratchpad::Pin,
+    selection::{snap_row, step_column, Anchor, Restored},
+    theme::Tint,
+    types::ColumnTypes,
+    util::offset_index,
+    widths::ColumnWidths,
+    worker::{TermRow, Truncation},
+    yank::{self, Yank},
+};
+use oxigraph::{
+    model::Term,
+    sparql::{QuerySolution, Variable},
+};
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+    ops::Range,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+impl App {
+    /// 執行結果表之操作：選擇、排序、過濾、標記、複製與顯示。餘者原樣返回，交下一部分。
+    pub(super) fn update_results(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::SelectHeader => self.select_header(),
+            Action::ToggleSort => self.toggle_sort(),
+            Action::AddSortKey => self.add_sort_key(),
+            Action::ClearSort => self.change_sort(false, |_, _| vec![]),
+            // 表頭之下即首行；其上無物
+            Action::ScrollRows(delta) if self.results.on_header => {
+                if delta > 0 {
+                    self.results.on_header = false;
+                    self.results.selected_row = 0;
+                }
+            }
+            Action::CancelQuery if self.results.on_header && self.running.is_none() => {
+                self.results.on_header = false;
+                self.status = None;
+            }
+            // 表尾之上即末行；自末行下移則至表尾
+            Action::ScrollRows(delta) if self.results.on_footer => {
+                if delta < 0 {
+                    self.results.on_footer = false;
+                }
+            }
+            Action::ScrollRows(delta)
+                if delta > 0
+                    && self.results.types_footer
+                    && self.results.filter.is_none()
+                    && self.results.selected_row + 1
+                        >= self.results.table().map_or(0, |t| t.rows.len()) =>
+            {
+                self.results.on_footer = true;
+                self.results.selected_column.get_or_insert(0);
+            }
+            Action::CancelQuery if self.results.on_footer && self.running.is_none() => {
+                self.results.on_footer = false;
+            }
+            Action::ScrollRows(delta) if self.results.filter.is_some() => {
+                if let Some(table) = self.results.table() {
+                    let rows = self.matching_rows(table).unwrap_or_else(|| unreachable!());
+                    if !rows.is_empty() {
+                        let position = rows.partition_point(|&row| row < self.results.selected_row);
+                        let row = rows[offset_index(position, delta, rows.len())];
+                        drop(rows);
+                        self.results.selected_row = row;
+                    }
+                }
+            }
+            Action::ScrollRows(delta) => {
+                let len = self.results.table().map_or(0, |table| table.rows.len());
+                self.results.selected_row = offset_index(self.results.selected_row, delta, len);
+            }
+            Action::ScrollColumns(delta) => {
+                let len = self
+                    .results
+                    .table()
+                    .map_or(0, |table| table.variables.len());
+                if len > 0 {
+                    self.results.selected_column = step_column(
+                        self.results.selected_column,
+                        delta,
+                        len,
+                        self.config.wrap_columns,
+                    );
+                }
+                if self.results.on_header {
+                    self.describe_header();
+                }
+            }
+            Action::SelectFirst | Action::SelectLast if self.results.filter.is_some() => {
+                if let Some(table) = self.results.table() {
+                    let rows = self.matching_rows(table).unwrap_or_else(|| unreachable!());
+                    let row = match action {
+                        Action::SelectFirst => rows.first(),
+                        _ => rows.last(),
+                    }
+                    .copied();
+                    drop(rows);
+                    self.results.selected_row = row.unwrap_or(self.results.selected_row);
+                }
+            }
+            Action::SelectFirst => self.results.selected_row = 0,
+            Action::SelectLast => {
+                let len = self.results.table().map_or(0, |table| table.rows.len());
+                self.results.selected_row = len.saturating_sub(1);
+            }
+            // 三元組模式中，以所選之項填入其欄，再細化
+            Action::ShowDetails if self.results.pattern().is_some() => {
+                let column = self.results.selected_column.unwrap_or(0);
+                let term = self
+                    .results
+                    .table()
+                    .and_then(|table| table.row(self.results.selected_row))
+                    .and_then(|mut row| row.nth(column).flatten().cloned());
+                if let Some(term) = term {
+                    self.pattern_form.fill(column, &term, &self.config.prefixes);
+                    self.mode = Mode::Pattern;
+                }
+            }
+            // 檢查報告中，列出所選檢查之違例
+            Action::ShowDetails if self.query.string == check::report_query() => {
+                let check = self
+                    .results
+                    .table()
+                    .and_then(|table| table.row(self.results.selected_row))
+                    .and_then(|mut row| match row.next().flatten() {
+                        Some(Term::Literal(name)) => check::find(name.value()),
+                        _ => None,
+                    });
+                if let Some(check) = check {
+                    self.query.set(check.query.to_string());
+                }
+            }
+            // 函數性屬性之概覽中，列出所選屬性之違例
+            Action::ShowDetails
+                if self.query.string == functional::summary_query() && !self.results.derived() =>
+            {
+                let predicate = self
+                    .results
+                    .table()
+                    .and_then(|table| table.row(self.results.selected_row))
+                    .and_then(|mut row| match row.next().flatten() {
+                        Some(Term::NamedNode(predicate)) => Some(predicate.clone()),
+                        _ => None,
+                    });
+                if let Some(predicate) = predicate {
+                    self.check_functional(Functional { predicate });
+                }
+            }
+            // 來源報告中，瀏覽所選之圖
+            Action::ShowDetails if self.query.string == self.provenance.report_query() => {
+                let graph = self
+                    .results
+                    .table()
+                    .and_then(|table| table.row(self.results.selected_row))
+                    .map(|mut row| row.next().flatten().cloned());
+                match graph {
+                    Some(Some(Term::NamedNode(graph))) => {
+                        self.query.set(format!(
+                            "SELECT ?s ?p ?o WHERE {{ GRAPH {graph} {{ ?s ?p ?o }} }}"
+                        ));
+                    }
+                    Some(None) => self
+                        .query
+                        .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o }".to_string()),
+                    Some(Some(_)) => {
+                        self.status =
+                            Some("A blank-node graph cannot be named in a query".to_string())
+                    }
+                    None => {}
+                }
+            }
+            // 屬性或類則列其定義，否則列其用法
+            Action::ShowDetails if self.expand_incoming() => {}
+            Action::ShowDetails => match self.results.selected_resource() {
+                Some(term) => {
+                    let query = match &term {
+                        Term::NamedNode(iri) if definition::is_defined(&self.store, iri)? => {
+                            definition::query(iri)
+                        }
+                        _ => details_query(&term),
+                    };
+                    self.details = Some((query.clone(), term));
+                    self.query.set(query);
+                    self.mode = Mode::Browse;
+                }
+                None => {
+                    self.status = Some("No IRI or quoted triple in the selected row".to_string())
+                }
+            },
+            Action::Yank => match self.results.selected_terms() {
+                Some(_) => {
+                    self.yank_prompt = true;
+                    self.status = Some(Yank::menu(self.config.yank));
+                }
+                None => self.status = Some("No results".to_string()),
+            },
+            Action::YankAs(form) => self.yank(form),
+            Action::OpenFilter => {
+                // 以現有之過濾起，以便修改
+                self.results.filter_line.input = self
+                    .results
+                    .filter
+                    .as_ref()
+                    .map_or_else(String::new, |filter| filter.text.clone());
+                self.results.filter_regex = self
+                    .results
+                    .filter
+                    .as_ref()
+                    .is_some_and(|filter| filter.regex);
+                self.results.filter_error = None;
+                self.mode = Mode::Filter;
+            }
+            Action::SetFilter { pattern, regex } => {
+                if !self.set_filter(pattern, regex) {
+                    let error = self.results.filter_error.take().unwrap_or_default();
+                    self.status = Some(format!("Invalid filter: {error}"));
+                }
+            }
+            Action::ToggleLabels => {
+                self.show_labels = !self.show_labels;
+                if let Some(table) = self.results.table() {
+                    table.display.borrow_mut().clear();
+                }
+                self.status = Some(
+                    match self.show_labels {
+                        true => "IRIs show their rdfs:label or skos:prefLabel",
+                        false => "IRIs show as written",
+                    }
+                    .to_string(),
+                );
+            }
+            Action::ShowColumnStats => self.show_column_stats(),
+            Action::ToggleTile => self.results.tile_as_table = !self.results.tile_as_table,
+            Action::ToggleTransposed => {
+                self.results.transposed = !self.results.transposed;
+                // 轉置時左右移所示之變量，須有所選之列
+                if self.results.transposed && self.results.selected_column.is_none() {
+                    self.results.selected_column = self
+                        .results
+                        .table()
+                        .filter(|table| !table.variables.is_empty())
+                        .map(|_| 0);
+                }
+            }
+            Action::CycleTint => {
+                self.config.tint = self.config.tint.next();
+                // 無色時縮寫之 IRI 依之加減尖括號
+                if let Some(table) = self.results.table() {
+                    table.display.borrow_mut().clear();
+                }
+                self.status = Some(
+                    match self.config.tint {
+                        Tint::Off => "Cells untinted",
+                        Tint::Kind => "Cells tinted by term kind",
+                        Tint::Column => "Cells tinted by column",
+                    }
+                    .to_string(),
+                );
+            }
+            Action::ToggleTypes => {
+                self.results.types_footer = !self.results.types_footer;
+                self.results.on_footer = false;
+            }
+            Action::ShowColumnTypes => self.show_column_types(),
+            Action::ShowRow => {
+                match self
+                    .results
+                    .table()
+                    .is_some_and(|t| t.row(self.results.selected_row).is_some())
+                {
+                    true => self.popup = Some(Popup::Row(self.results.selected_row)),
+                    false => self.status = Some("No row selected".to_string()),
+                }
+            }
+            Action::CompareInspected(text) => {
+                if let Some(Popup::Inspector(_, _, pasted)) = &mut self.popup {
+                    *pasted = Some(text);
+                }
+            }
+            Action::ChooseDiffValue(index) => self.choose_diff_value(index),
+            Action::DiffValues => self.diff_values(),
+            Action::ScrollPopup(delta) => {
+                if let Some(Popup::ValueDiff(diff)) = &self.popup {
+                    let limit = self.value_diff_lines(diff).len() - 1;
+                    let scroll = diff.scroll.saturating_add_signed(delta).min(limit);
+                    if let Some(Popup::ValueDiff(diff)) = &mut self.popup {
+                        diff.scroll = scroll;
+                    }
+                }
+            }
+            Action::YankDiffValue(index) => {
+                if let Some(Popup::ValueDiff(diff)) = &self.popup {
+                    self.status = Some(format!("Copied value {}", index + 1));
+                    self.clipboard = Some(diff.values[index].clone());
+                }
+            }
+            Action::ToggleMark => {
+                let row = self.results.selected_row;
+                let results = self.results.current.as_mut().and_then(|r| r.table.as_mut());
+                match results {
+                    Some(table) if row < table.rows.len() => {
+                        if !table.marked.remove(&row) {
+                            table.marked.insert(row);
+                        }
+                        self.results.selected_row = (row + 1).min(table.rows.len() - 1);
+                    }
+                    _ => self.status = Some("No row to mark".to_string()),
+                }
+            }
+            Action::TogglePreview => self.preview = !self.preview,
+            Action::ScrollPreview(delta) => {
+                let scroll = self.preview_offset().saturating_add_signed(delta as i16);
+                self.preview_scroll.set((
+                    self.results.selected_row,
+                    self.results.selected_column,
+                    scroll,
+                ));
+            }
+            Action::ToggleOrigin => {
+                self.show_origin = !self.show_origin;
+                self.status = Some(
+                    if !self.show_origin {
+                        "Origin column off"
+                    } else if self.provenance.entries().iter().any(|e| e.graph.is_some()) {
+                        "Origin column on"
+                    } else {
+                        "Origin column on; no named graphs were loaded"
+                    }
+                    .to_string(),
+                );
+            }
+            Action::ShowDiff => self.show_diff(),
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 過濾之輸入行中之操作。模式無效則留於輸入行，報告其因。返回未處理者。
+    pub(super) fn update_in_filter_mode(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::InsertChar(ch) => {
+                self.results.filter_line.push(ch);
+                self.results.filter_error = None;
+            }
+            Action::DeleteBackward => {
+                if self.results.filter_line.pop().is_none() {
+                    self.mode = Mode::Browse;
+                }
+                self.results.filter_error = None;
+            }
+            Action::ToggleRegex => {
+                self.results.filter_regex = !self.results.filter_regex;
+                self.results.filter_error = None;
+            }
+            Action::Activate => {
+                let pattern = (!self.results.filter_line.input.is_empty())
+                    .then(|| self.results.filter_line.input.clone());
+                if self.set_filter(pattern, self.results.filter_regex) {
+                    self.mode = Mode::Browse;
+                }
+            }
+            Action::Back => {
+                self.results.filter_error = None;
+                self.mode = Mode::Browse;
+            }
+            action => return Some(action),
+        }
+        None
+    }
+
+    /// 設置過濾，所選移至首個相符之行。無效則記其因而返回假，原過濾不變。
+    pub(super) fn set_filter(&mut self, pattern: Option<String>, regex: bool) -> bool {
+        let Some(pattern) = pattern else {
+            self.results.filter = None;
+            self.results.filter_error = None;
+            self.status = Some("Filter cleared".to_string());
+            return true;
+        };
+        let filter = Filter::new(&pattern, regex).and_then(|filter| {
+            // 所指之列須在當前之表中；換查詢後不在者不相符而已
+            match self.results.table() {
+                Some(table) => filter.check(&table.variables).map(|()| filter),
+                None => Ok(filter),
+            }
+        });
+        match filter {
+            Ok(filter) => {
+                log::debug!("filter {}", filter.describe());
+                self.results.filter = Some(filter);
+                self.results.filter_error = None;
+                self.snap_to_filter();
+                true
+            }
+            Err(error) => {
+                self.results.filter_error = Some(error);
+                false
+            }
+        }
+    }
+
+    /// 過濾時，所選行不相符則移至其後首個相符者，無則移至末個。
+    pub(super) fn snap_to_filter(&mut self) {
+        let Some(table) = self.results.table() else {
+            return;
+        };
+        let Some(rows) = self.matching_rows(table) else {
+            return;
+        };
+        let snapped = snap_row(self.results.selected_row, &rows);
+        drop(rows);
+        self.results.selected_row = snapped;
+    }
+
+    /// 過濾後相符之行，依表之序；無過濾則無。新到之行於此時方查，已顯示之格取其緩存之文本。
+    pub(super) fn matching_rows<'a>(&self, table: &'a ResultTable) -> Option<Ref<'a, Vec<usize>>> {
+        let filter = self.results.filter.as_ref()?;
+        {
+            let mut matches = table.matches.borrow_mut();
+            let key = filter.describe();
+            if matches.key != key {
+                *matches = Matches {
+                    key,
+                    ..Matches::default()
+                };
+            }
+            let display = table.display.borrow();
+            for index in matches.checked..table.rows.len() {
+                let cells: Vec<String> = table.rows[index]
+                    .iter()
+                    .enumerate()
+                    .map(|(column, term)| match display.get(&(index, column)) {
+                        Some(line) => line
+                            .spans
+                            .iter()
+                            .map(|span| span.content.as_ref())
+                            .collect(),
+                        None => self.plain_cell(term.as_deref()),
+                    })
+                    .collect();
+                if filter.keeps(&table.variables, &cells) {
+                    matches.rows.push(index);
+                }
+            }
+            matches.checked = table.rows.len();
+        }
+        Some(Ref::map(table.matches.borrow(), |matches| &matches.rows))
+    }
+
+    /// 釘所選格之項。
+    pub(super) fn pin_cell(&mut self) {
+        let Some(table) = self.results.table() else {
+            self.status = Some("No results".to_string());
+            return;
+        };
+        let Some(column) = self
+            .results
+            .selected_column
+            .filter(|&column| column < table.variables.len())
+        else {
+            self.status = Some("Select a cell with ←/→ first".to_string());
+            return;
+        };
+        match table
+            .rows
+            .get(self.results.selected_row)
+            .and_then(|row| row[column].as_deref())
+        {
+            Some(term) => self.pin(Pin::Term(term.clone())),
+            None => self.status = Some("Nothing to pin in an unbound cell".to_string()),
+        }
+    }
+
+    /// 以此形式複製所選之格；`Row` 則複製所選之行，不必選格。
+    pub(super) fn yank(&mut self, form: Yank) {
+        let text = if form == Yank::Row {
+            match self.results.selected_terms() {
+                Some(row) => Ok(yank::row(&row)),
+                None => Err("No results"),
+            }
+        } else {
+            self.selected_term().and_then(|(_, term)| {
+                let label = match (form, term) {
+                    (Yank::Label, Term::NamedNode(iri)) => self
+                        .labels
+                        .get(iri)
+                        .unwrap_or_else(|| resolve_label(&self.store, iri, self.lang.as_deref())),
+                    _ => None,
+                };
+                yank::term(form, term, &self.export_prefixes(), label)
+            })
+        };
+        match text {
+            Ok(text) => {
+                self.status = Some(format!("Copied the {}: {}", form.name(), sanitize(&text)));
+                self.clipboard = Some(text);
+            }
+            Err(message) => self.status = Some(message.to_string()),
+        }
+    }
+
+    pub(super) fn pin(&mut self, pin: Pin) {
+        let title = pin.title();
+        self.status = Some(if self.scratchpad.add(pin) {
+            format!("Pinned {title}")
+        } else {
+            format!("Already pinned: {title}")
+        });
+    }
+
+    /// 查詢字串有變或存儲已改時重新查詢。
+    pub(super) fn refresh_results(&mut self) {
+        self.refresh_stale();
+        if self.imported.is_some()
+            || self.results.derived()
+            || self
+                .results
+                .current
+                .as_ref()
+                .is_some_and(|r| r.query == self.query.string)
+        {
+            return;
+        }
+        // 不自動查詢時，待離開編輯器再查
+        if !self.config.autorun && matches!(self.mode, Mode::Query) {
+            return;
+        }
+        self.run_query();
+    }
+
+    /// 棄當前結果，待重新查詢。導入之結果不出自存儲，留之。
+    pub(super) fn invalidate_results(&mut self) {
+        if self.imported.is_none() {
+            self.remember_run();
+            self.results.current = None;
+        }
+    }
+
+    /// 記下當前已完成之查詢結果，供下次比較。比較中則記其所比之新結果。
+    pub(super) fn remember_run(&mut self) {
+        if self.imported.is_some() {
+            return;
+        }
+        let Some(results) = self.results.current.take() else {
+            return;
+        };
+        let table = match results.source {
+            Source::Query => results.table,
+            Source::Diff(view) => Some(view.table),
+            _ => None,
+        };
+        if let Some(table) = table.filter(|table| table.progress == Progress::Complete) {
+            self.results.previous = Some((results.query, table));
+        }
+    }
+
+    /// 就已到之行統計所選之列，以浮窗顯示。
+    pub(super) fn show_column_stats(&mut self) {
+        let Some(table) = self.results.table() else {
+            self.status = Some("No results to summarize".to_string());
+            return;
+        };
+        let Some(column) = self
+            .results
+            .selected_column
+            .filter(|&column| column < table.variables.len())
+        else {
+            self.status = Some("Select a column with ←/→ first".to_string());
+            return;
+        };
+        self.popup = self.column_stats(column);
+    }
+
+    /// 所選列各類別之浮窗。
+    pub(super) fn show_column_types(&mut self) {
+        let Some(table) = self.results.table() else {
+            self.status = Some("No results to summarize".to_string());
+            return;
+        };
+        let Some(column) = self
+            .results
+            .selected_column
+            .filter(|&column| column < table.variables.len())
+        else {
+            self.status = Some("Select a column with ←/→ first".to_string());
+            return;
+        };
+        let types = table.column_types()[column].clone();
+        let title = format!(" {} · types ", table.variables[column]);
+        self.popup = Some(Popup::ColumnTypes(title, types));
+    }
+
+    /// 所選之格之變量及其項。無則返回所報者。
+    pub(super) fn selected_term(&self) -> Result<(&Variable, &Term), &'static str> {
+        let table = self.results.table().ok_or("No results")?;
+        let column = self
+            .results
+            .selected_column
+            .filter(|&column| column < table.variables.len())
+            .ok_or("Select a cell with ←/→ first")?;
+        let term = table
+            .rows
+            .get(self.results.selected_row)
+            .and_then(|row| row[column].as_deref())
+            .ok_or("Nothing to inspect in an unbound cell")?;
+        Ok((&table.variables[column], term))
+    }
+
+    /// 記所選之格為所比較之第 `index + 1` 值，兩值皆備則示之。
+    pub(super) fn choose_diff_value(&mut self, index: usize) {
+        let (variable, term) = match self.selected_term() {
+            Ok(selected) => selected,
+            Err(message) => {
+                self.status = Some(message.to_string());
+                return;
+            }
+        };
+        let label = format!(
+            "row {} ?{}",
+            self.results.selected_row + 1,
+            variable.as_str()
+        );
+        let term = term.clone();
+        let other = match index {
+            0 => "2",
+            _ => "1",
+        };
+        self.status = Some(match &self.results.diff_values[1 - index] {
+            Some(_) => format!("Value {}: {label} (= compares)", index + 1),
+            None => format!(
+                "Value {}: {label} (choose the other with {other})",
+                index + 1
+            ),
+        });
+        self.results.diff_values[index] = Some((label, term));
+    }
+
+    /// 浮窗示所記之兩值之逐字差異。
+    pub(super) fn diff_values(&mut self) {
+        let [Some((first, a)), Some((second, b))] = &self.results.diff_values else {
+            self.status = Some("Choose two cells with 1 and 2 first".to_string());
+            return;
+        };
+        let values = [inspect::value(a), inspect::value(b)];
+        let edits = chardiff::diff(&values[0], &values[1]);
+        self.popup = Some(Popup::ValueDiff(Box::new(ValueDiff {
+            labels: [first.clone(), second.clone()],
+            values,
+            edits,
+            scroll: 0,
+        })));
+    }
+
+    /// 當前結果某列之統計浮窗。
+    pub(super) fn column_stats(&self, column: usize) -> Option<Popup> {
+        let table = self
+            .results
+            .table()
+            .filter(|table| column < table.variables.len())?;
+        let stats = ColumnStats::new(table.rows.iter().map(|row| row[column].as_deref()));
+        let rows = group_digits(table.rows.len());
+        let rows = if table.progress == Progress::Complete && table.truncated.is_none() {
+            format!("{rows} rows")
+        } else {
+            format!("first {rows} rows")
+        };
+        let title = format!(" {} · {rows} ", table.variables[column]);
+        let generation = self.results.current.as_ref()?.generation;
+        Some(Popup::ColumnStats(
+            generation,
+            column,
+            title,
+            Box::new(stats),
+        ))
+    }
+
+    /// 查詢中算出此列之表達式，如 `COUNT(?o)`。三元組模式之列無之。
+    pub(super) fn column_expression(&self, variable: &Variable) -> Option<String> {
+        let results = self
+            .results
+            .current
+            .as_ref()
+            .filter(|results| !matches!(results.source, Source::Pattern(_)))?;
+        header::projection(&results.query, variable.as_str())
+    }
+
+    /// 所選移至表頭。未選列則選首列。
+    pub(super) fn select_header(&mut self) {
+        let Some(len) = self.results.table().map(|table| table.variables.len()) else {
+            self.status = Some("No results".to_string());
+            return;
+        };
+        if len == 0 {
+            return;
+        }
+        if self
+            .results
+            .selected_column
+            .is_none_or(|column| column >= len)
+        {
+            self.results.selected_column = Some(0);
+        }
+        self.results.on_header = true;
+        self.results.on_footer = false;
+        self.describe_header();
+    }
+
+    /// 狀態欄示所選列之詳情，如 `?n = COUNT(?o) · 3 distinct values · sorted ascending`。
+    /// 三元組模式中已定之位置示其全項。
+    pub(super) fn describe_header(&mut self) {
+        let (Some(results), Some(column)) =
+            (self.results.current.as_ref(), self.results.selected_column)
+        else {
+            return;
+        };
+        let Some(table) = results
+            .table
+            .as_ref()
+            .filter(|table| column < table.variables.len())
+        else {
+            return;
+        };
+        let variable = &table.variables[column];
+        let mut parts = vec![match &results.source {
+            Source::Pattern(pattern) => {
+                let fixed = match column {
+                    0 => pattern.subject.as_ref().map(ToString::to_string),
+                    1 => pattern.predicate.as_ref().map(ToString::to_string),
+                    _ => pattern.object.as_ref().map(ToString::to_string),
+                };
+                match fixed {
+                    Some(term) => format!("{variable} = {term}"),
+                    None => variable.to_string(),
+                }
+            }
+            _ => match self.column_expression(variable) {
+                Some(expression) => format!("{variable} = {expression}"),
+                None => variable.to_string(),
+            },
+        }];
+        let distinct = table
+            .rows
+            .iter()
+            .filter_map(|row| row[column].as_deref())
+            .collect::<HashSet<_>>()
+            .len();
+        parts.push(format!(
+            "{} distinct {}",
+            group_digits(distinct),
+            if distinct == 1 { "value" } else { "values" }
+        ));
+        let key = table.sort.iter().position(|&(sorted, _)| sorted == column);
+        parts.push(match key.map(|key| (key, table.sort[key].1)) {
+            Some((key, order)) => {
+                let order = match order {
+                    Order::Ascending => "sorted ascending",
+                    Order::Descending => "sorted descending",
+                };
+                match table.sort.len() {
+                    1 => order.to_string(),
+                    keys => format!("{order}, key {} of {keys}", key + 1),
+                }
+            }
+            None => "unsorted (Enter sorts)".to_string(),
+        });
+        self.status = Some(sanitize(&parts.join(" · ")).into_owned());
+    }
+
+    /// 唯依所選之列排序，已唯依之則反其向。
+    pub(super) fn toggle_sort(&mut self) {
+        self.change_sort(true, |keys, column| match keys {
+            [(sorted, order)] if *sorted == column => vec![(column, order.toggled())],
+            _ => vec![(column, Order::Ascending)],
+        });
+    }
+
+    /// 以所選之列為次一排序之鍵，已在其中則反其向。
+    pub(super) fn add_sort_key(&mut self) {
+        self.change_sort(true, |keys, column| {
+            let mut keys = keys.to_vec();
+            match keys.iter_mut().find(|(sorted, _)| *sorted == column) {
+                Some((_, order)) => *order = order.toggled(),
+                None => keys.push((column, Order::Ascending)),
+            }
+            keys
+        });
+    }
+
+    /// 以 `keys` 所返之諸鍵重排當前之表，其得今之諸鍵與所選之列。
+    /// `select` 則須已選一列；否則未選者作首列。
+    pub(super) fn change_sort(
+        &mut self,
+        select: bool,
+        keys: impl FnOnce(&[(usize, Order)], usize) -> Vec<(usize, Order)>,
+    ) {
+        let column = match (self.results.selected_column, select) {
+            (Some(column), _) => column,
+            (None, false) => 0,
+            (None, true) => {
+                self.status = Some("Select a column with ←/→ first".to_string());
+                return;
+            }
+        };
+        let Some(results) = self.results.current.as_mut() else {
+            self.status = Some("No results".to_string());
+            return;
+        };
+        if matches!(results.source, Source::Diff(_)) {
+            self.status = Some("The comparison keeps its own order".to_string());
+            return;
+        }
+        let Some(table) = results
+            .table
+            .as_mut()
+            .filter(|table| !select || column < table.variables.len())
+        else {
+            self.status = Some("No results".to_string());
+            return;
+        };
+        if table.progress == Progress::Running {
+            self.status = Some("Wait for the query to finish before sorting".to_string());
+            return;
+        }
+        let keys = keys(&table.sort, column);
+        let cleared = keys.is_empty();
+        table.sort(keys);
+        self.results.selected_row = 0;
+        match cleared {
+            true => self.status = Some("Rows are back in the order the query returned".to_string()),
+            false => self.describe_header(),
+        }
+    }
+
+    /// 恢復會話或視圖所存之排序。不在表中之列不依；比較與未完之表不排。
+    pub(super) fn restore_sort(&mut self, mut keys: Vec<(usize, Order)>) {
+        let Some(results) = self
+            .results
+            .current
+            .as_mut()
+            .filter(|results| !matches!(results.source, Source::Diff(_)))
+        else {
+            return;
+        };
+        if let Some(table) = results
+            .table
+            .as_mut()
+            .filter(|table| table.progress != Progress::Running)
+        {
+            keys.retain(|&(column, _)| column < table.variables.len());
+            if !keys.is_empty() {
+                table.sort(keys);
+            }
+        }
+    }
+
+    /// 比較當前結果與同一查詢前次之結果。已在比較則開合刪去之行。
+    pub(super) fn show_diff(&mut self) {
+        let Some(results) = self.results.current.as_mut() else {
+            self.status = Some("No results to compare".to_string());
+            return;
+        };
+        if let Source::Diff(view) = &mut results.source {
+            view.removed = !view.removed;
+            let rows = view.partition.rows(view.removed);
+            let table = results
+                .table
+                .get_or_insert_with(|| ResultTable::new(vec![]));
+            table.rows = rows;
+            table.display.borrow_mut().clear();
+            table.types.take();
+            *table.matches.borrow_mut() = Matches::default();
+            self.results.selected_row = self
+                .results
+                .selected_row
+                .min(table.rows.len().saturating_sub(1));
+            return;
+        }
+        let current = match (&results.source, &results.table) {
+            (Source::Query, Some(table)) if self.imported.is_none() => table,
+            _ => {
+                self.status = Some("Only query results can be compared".to_string());
+                return;
+            }
+        };
+        if current.progress != Progress::Complete {
+            self.status = Some("Wait for the query to finish before comparing".to_string());
+            return;
+        }
+        let Some((_, old)) =
+            self.results.previous.as_ref().filter(|(query, old)| {
+                *query == results.query && old.variables == current.variables
+            })
+        else {
+            self.status = Some("No earlier run of this query to compare with".to_string());
+            return;
+        };
+        let partition = Partition::between(&old.rows, &current.rows);
+        log::debug!(
+            "diff added={} unchanged={} removed={}",
+            partition.added.len(),
+            partition.unchanged.len(),
+            partition.removed.len()
+        );
+        let Some(ResultSet {
+            table: Some(current),
+            generation,
+            ..
+        }) = self.results.current.take()
+        else {
+            unreachable!();
+        };
+        self.open_diff(partition, current, generation);
+    }
+
+    /// 示兩結果之比較之表：新增、未變之行，`D` 再示刪去者。`current` 為所比之新結果。
+    pub(super) fn open_diff(
+        &mut self,
+        partition: Partition,
+        current: ResultTable,
+        generation: Generation,
+    ) {
+        let mut table = ResultTable::new(Partition::variables(&current.variables));
+        table.append(partition.rows(false), current.term_bytes);
+        table.progress = Progress::Complete;
+        self.results.selected_row = 0;
+        self.results.selected_column = None;
+        self.results.on_header = false;
+        self.results.on_footer = false;
+        self.results.current = Some(ResultSet {
+            query: self.query.string.clone(),
+            table: Some(table),
+            error: None,
+            remote_requests: 0,
+            guarded: None,
+            generation,
+            source: Source::Diff(Box::new(DiffView {
+                partition,
+                removed: false,
+                table: current,
+            })),
+        });
+    }
+
+    /// 改名前先示變量於查詢中出現之次數，及改後之查詢。
+    /// 改查詢之默認圖。所示為查詢之結果則即重新執行之，以見其異。
+    /// 開則即排當前之完整結果；關則重查以復引擎之序。
+    pub(super) fn set_stable_order(&mut self, stable: bool) {
+        self.config.stable_order = stable;
+        let query = self
+            .results
+            .current
+            .as_mut()
+            .filter(|results| matches!(results.source, Source::Query))
+            .filter(|_| self.imported.is_none());
+        if let Some(results) = query {
+            let ordered = header::ordered(&results.query);
+            match results.table.as_mut() {
+                Some(table) if stable && !ordered && table.progress == Progress::Complete => {
+                    table.stabilize()
+                }
+                Some(table) if !stable && table.stable => self.run_query(),
+                _ => {}
+            }
+        }
+        self.status = Some(self.describe_stable_order().to_string());
+    }
+
+    pub(super) fn describe_stable_order(&self) -> &'static str {
+        match self.config.stable_order {
+            true => "Results without ORDER BY are sorted canonically",
+            false => "Results keep the engine's order",
+        }
+    }
+
+    /// 依重新執行前所選之行選新結果之行，前 `searched` 行已尋之。其間用戶已移所選者不復。
+    pub(super) fn restore_selection(&mut self, searched: usize, finished: bool) {
+        let (Some(anchor), Some(table)) = (&self.results.anchor, self.results.table()) else {
+            return;
+        };
+        if self.results.selected_row != 0 {
+            self.results.anchor = None;
+            return;
+        }
+        let Some(restored) = anchor.restore(&table.rows, searched, finished) else {
+            return;
+        };
+        let (Restored::Same(row) | Restored::Index(row)) = restored;
+        table.offset.set(row.saturating_sub(anchor.above));
+        if restored == Restored::Index(row) && anchor.row > 0 && !table.rows.is_empty() {
+            self.status = Some(format!(
+                "The selected row is gone; kept row {}",
+                group_digits(row + 1)
+            ));
+        }
+        log::debug!("selection restored {restored:?}");
+        self.results.selected_row = row;
+        self.results.anchor = None;
+    }
+}
+
+/// 結果之部分：所緩存之查詢結果，所選之行列，過濾，及表之示法。排序存於其表，隨之而換。
+#[derive(Default)]
+pub(super) struct Results {
+    /// 上次查詢之結果。查詢字串未變則不重算。
+    pub(super) current: Option<ResultSet>,
+    /// 表中所選之行。
+    pub(super) selected_row: usize,
+    /// 結果表中所選之列。三元組模式之結果恆有之；他表以左右鍵選之，詳情遂取此格。
+    pub(super) selected_column: Option<usize>,
+    /// 所選在表頭，而非某行。
+    pub(super) on_header: bool,
+    /// 所選在表尾之類型上。
+    pub(super) on_footer: bool,
+    /// 列多而不能盡示時，所示之首列。繪製時隨所選之列移之。
+    pub(super) column_offset: Cell<usize>,
+    /// 過濾結果行之模式。換查詢、換頁亦留之。
+    pub(super) filter: Option<Filter>,
+    /// 過濾之輸入行。
+    pub(super) filter_line: CommandLine,
+    /// 輸入行中之模式是否為正則。
+    pub(super) filter_regex: bool,
+    /// 輸入行中之模式無效之因。顯示於其後，不棄已有之過濾。
+    pub(super) filter_error: Option<String>,
+    /// 重新執行前所選之行，新結果之行到則復之。
+    pub(super) anchor: Option<Anchor>,
+    /// 重新查詢前所選之列之變量，待新表之列到時隨之。
+    pub(super) carried_column: Option<Variable>,
+    /// 前次完成之查詢及其結果，供比較。
+    pub(super) previous: Option<(String, ResultTable)>,
+    /// 所選之行逐變量一行示之。
+    pub(super) transposed: bool,
+    /// 表下示各列之類型。
+    pub(super) types_footer: bool,
+    /// 僅一行一列之結果以表示之，不以大字。
+    pub(super) tile_as_table: bool,
+    /// `1` 與 `2` 所記以逐字比較之兩格：其所在及其項。
+    pub(super) diff_values: [Option<(String, Term)>; 2],
+}
+
+impl Results {
+    /// 當前結果之表。
+    pub(super) fn table(&self) -> Option<&ResultTable> {
+        self.current.as_ref().and_then(|r| r.table.as_ref())
+    }
+
+    /// 所選之行之諸項。無結果則為 `None`。
+    pub(super) fn selected_terms(&self) -> Option<Vec<Option<Term>>> {
+        let row = self.table()?.rows.get(self.selected_row)?;
+        Some(row.iter().map(|term| term.as_deref().cloned()).collect())
+    }
+
+    /// 所選行之資源：末列之資源，如賓語；末列非資源則首個資源，如主語。
+    pub(super) fn selected_resource(&self) -> Option<Term> {
+        let resource = |term: &&Term| matches!(term, Term::NamedNode(_) | Term::Triple(_));
+        let row: Vec<_> = self.table()?.row(self.selected_row)?.collect();
+        if let Some(column) = self.selected_column {
+            return row.get(column).copied().flatten().filter(resource).cloned();
+        }
+        row.last()
+            .copied()
+            .flatten()
+            .filter(resource)
+            .or_else(|| row.iter().copied().flatten().find(resource))
+            .cloned()
+    }
+
+    /// 當前結果所出之三元組模式。
+    pub(super) fn pattern(&self) -> Option<&Pattern> {
+        match &self.current.as_ref()?.source {
+            Source::Pattern(pattern) => Some(pattern),
+            _ => None,
+        }
+    }
+
+    /// 當前結果所出之比較。
+    pub(super) fn diff_view(&self) -> Option<&DiffView> {
+        match &self.current.as_ref()?.source {
+            Source::Diff(view) => Some(view),
+            _ => None,
+        }
+    }
+
+    /// 當前結果是否非出自查詢，如三元組模式。編輯查詢不致重算。
+    pub(super) fn derived(&self) -> bool {
+        self.current
+            .as_ref()
+            .is_some_and(|r| !matches!(r.source, Source::Query))
+    }
+
+    /// 與他頁之結果及所選互換。過濾不隨頁，故不換；所復之行列換頁則棄之。
+    pub(super) fn swap_tab(&mut self, other: &mut Self) {
+        use std::mem::swap;
+        swap(&mut self.current, &mut other.current);
+        swap(&mut self.selected_row, &mut other.selected_row);
+        swap(&mut self.selected_column, &mut other.selected_column);
+        swap(&mut self.on_header, &mut other.on_header);
+        swap(&mut self.on_footer, &mut other.on_footer);
+        swap(&mut self.column_offset, &mut other.column_offset);
+        swap(&mut self.previous, &mut other.previous);
+    }
+}
+
+/// 查詢結果之緩存。
+pub(super) struct ResultSet {
+    /// 產生此結果之查詢字串。
+    pub(super) query: String,
+    /// 表頭與各行。非 SELECT 查詢則無。
+    pub(super) table: Option<ResultTable>,
+    pub(super) error: Option<String>,
+    /// 此次查詢發出之遠端請求數。
+    pub(super) remote_requests: usize,
+    /// 防護所加之 LIMIT。
+    pub(super) guarded: Option<usize>,
+    pub(super) source: Source,
+    /// 查詢時存儲之代數。
+    pub(super) generation: Generation,
+}
+
+impl ResultSet {
+    /// 記入查詢歷史之一項，附前 `snapshot_rows` 行之快照。
+    pub(super) fn history_entry(
+        &self,
+        duration: u128,
+        rows: usize,
+        snapshot_rows: usize,
+    ) -> history::Entry {
+        let snapshot = self
+            .table
+            .as_ref()
+            .filter(|_| snapshot_rows > 0)
+            .map(|table| Snapshot {
+                variables: table.variables.clone(),
+                rows: table.rows.iter().take(snapshot_rows).cloned().collect(),
+            });
+        history::Entry {
+            query: self.query.clone(),
+            ran: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            duration: Duration::from_millis(duration as u64),
+            rows,
+            generation: self.generation,
+            snapshot,
+        }
+    }
+}
+
+/// 結果之所出。
+pub(super) enum Source {
+    /// 所記之查詢。
+    Query,
+    /// 三元組模式。
+    Pattern(Pattern),
+    /// 重複值之查找。
+    Dupes(Dupes),
+    /// 函數性屬性之檢查。
+    Functional(Functional),
+    /// 與前次結果之比較。
+    Diff(Box<DiffView>),
+}
+
+/// SELECT 查詢結果之表。存原項以供排序、導出等；顯示之文本僅於可見時生成並緩存。
+pub struct ResultTable {
+    pub(super) variables: Vec<Variable>,
+    /// 各行之項。未綁定者為 `None`。相同之項共用一份。
+    pub(super) rows: Vec<TermRow>,
+    /// 因何上限而截斷。
+    pub(super) truncated: Option<Truncation>,
+    pub(super) progress: Progress,
+    /// 已顯示之單元格，以（行，列）為鍵。
+    pub(super) display: RefCell<HashMap<(usize, usize), Line<'static>>>,
+    /// 各列依內容之寬度，隨行送回而增。
+    pub(super) widths: RefCell<ColumnWidths>,
+    /// 首個可見行。如 [`TableState`] 之偏移，隨所選行滾動。過濾時為相符之行中之位置。
+    pub(super) offset: Cell<usize>,
+    /// 當前過濾下相符之行。
+    matches: RefCell<Matches>,
+    /// 所標記之行，待刪去。
+    pub(super) marked: BTreeSet<usize>,
+    /// 各項之估計字節數。
+    pub(super) term_bytes: usize,
+    /// 所依排序之諸列及其向，前者為主。未排序則依查詢所返之序。
+    pub(super) sort: Vec<(usize, Order)>,
+    /// 各行未排序時之位置，清除排序時還原之。空則即今之序。
+    unsorted: Vec<usize>,
+    /// 查詢無 ORDER BY 而依項之規範之序排列，非引擎所返之序。
+    pub(super) stable: bool,
+    /// 查詢之耗時，完成時記之。
+    pub(super) elapsed: Option<Duration>,
+    /// 已計之行數與各列之類型，顯示時補計新到之行。
+    types: RefCell<(usize, Vec<ColumnTypes>)>,
+}
+
+/// 結果之進度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Progress {
+    /// 後台仍在送回。
+    Running,
+    Complete,
+    /// 用戶中止，留已到之行。
+    Cancelled,
+}
+
+impl ResultTable {
+    /// 取解至多 `limit` 行。不涉存儲，故亦可以合成之解構造。
+    pub fn from_solutions<E: fmt::Display>(
+        variables: Vec<Variable>,
+        solutions: impl IntoIterator<Item = Result<QuerySolution, E>>,
+        limit: Option<usize>,
+    ) -> Result<Self, String> {
+        let mut rows = vec![];
+        let mut truncated = None;
+        let mut interner = Interner::default();
+        for s in solutions {
+            if let Some(limit) = limit.filter(|&limit| rows.len() >= limit) {
+                truncated = Some(Truncation::Rows(limit));
+                break;
+            }
+            let s = s.map_err(|e| e.to_string())?;
+            rows.push(
+                variables
+                    .iter()
+                    .map(|v| s.get(v).map(|term| interner.intern(term)))
+                    .collect(),
+            );
+        }
+        let mut table = Self::new(variables);
+        table.append(rows, interner.bytes());
+        table.truncated = truncated;
+        table.progress = Progress::Complete;
+        Ok(table)
+    }
+
+    /// 空表，待後台查詢送回之行。
+    pub(super) fn new(variables: Vec<Variable>) -> Self {
+        Self {
+            variables,
+            rows: vec![],
+            truncated: None,
+            progress: Progress::Running,
+            display: RefCell::default(),
+            widths: RefCell::default(),
+            offset: Cell::default(),
+            matches: RefCell::default(),
+            marked: BTreeSet::new(),
+            term_bytes: 0,
+            sort: vec![],
+            unsorted: vec![],
+            stable: false,
+            elapsed: None,
+            types: RefCell::default(),
+        }
+    }
+
+    /// 依諸列排序，前者為主，後者分其相等者；皆等者保持原序。空則還原未排序之序。
+    fn sort(&mut self, keys: Vec<(usize, Order)>) {
+        if keys.is_empty() {
+            if !self.unsorted.is_empty() {
+                let mut positions = vec![0; self.unsorted.len()];
+                for (row, &original) in self.unsorted.iter().enumerate() {
+                    positions[original] = row;
+                }
+                self.permute(positions);
+                self.unsorted.clear();
+            }
+        } else {
+            self.reorder(|a, b| {
+                keys.iter()
+                    .fold(std::cmp::Ordering::Equal, |ordering, &(column, order)| {
+                        ordering.then_with(|| {
+                            let ordering =
+                                header::compare(a[column].as_deref(), b[column].as_deref());
+                            match order {
+                                Order::Ascending => ordering,
+                                Order::Descending => ordering.reverse(),
+                            }
+                        })
+                    })
+            });
+        }
+        self.sort = keys;
+    }
+
+    /// 依各列之項逐列排序，與讀者或引擎無涉，同數據重查則同序。此即其未排序之序。
+    pub(super) fn stabilize(&mut self) {
+        self.reorder(|a, b| header::compare_rows(a, b));
+        self.unsorted.clear();
+        self.sort.clear();
+        self.stable = true;
+    }
+
+    /// 依 `compare` 重排各行，相等者保持原序。
+    fn reorder(&mut self, compare: impl Fn(&TermRow, &TermRow) -> std::cmp::Ordering) {
+        let mut positions: Vec<usize> = (0..self.rows.len()).collect();
+        positions.sort_by(|&a, &b| compare(&self.rows[a], &self.rows[b]));
+        self.permute(positions);
+    }
+
+    /// 第 `row` 行移自第 `positions[row]` 行。所標記之行隨之移動。
+    fn permute(&mut self, positions: Vec<usize>) {
+        self.unsorted = match self.unsorted.is_empty() {
+            true => positions.clone(),
+            false => positions
+                .iter()
+                .map(|&position| self.unsorted[position])
+                .collect(),
+        };
+        let mut rows: Vec<Option<TermRow>> = std::mem::take(&mut self.rows)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.rows = positions
+            .iter()
+            .map(|&position| rows[position].take().unwrap())
+            .collect();
+        self.marked = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, position)| self.marked.contains(position))
+            .map(|(row, _)| row)
+            .collect();
+        self.display.borrow_mut().clear();
+        *self.matches.borrow_mut() = Matches::default();
+    }
+
+    /// 各列之類型，補計其後到之行。行已換去者重計。
+    pub(super) fn column_types(&self) -> Ref<'_, Vec<ColumnTypes>> {
+        {
+            let mut types = self.types.borrow_mut();
+            let (counted, columns) = &mut *types;
+            if *counted > self.rows.len() {
+                *counted = 0;
+                columns.clear();
+            }
+            columns.resize_with(self.variables.len(), ColumnTypes::default);
+            for row in &self.rows[*counted..] {
+                for (types, term) in columns.iter_mut().zip(row) {
+                    types.add(term.as_deref());
+                }
+            }
+            *counted = self.rows.len();
+        }
+        Ref::map(self.types.borrow(), |(_, columns)| columns)
+    }
+
+    /// 追加行。`term_bytes` 為迄今各項之估計字節數。
+    pub(super) fn append(&mut self, rows: Vec<TermRow>, term_bytes: usize) {
+        if !self.unsorted.is_empty() {
+            self.unsorted
+                .extend(self.rows.len()..self.rows.len() + rows.len());
+        }
+        self.rows.extend(rows);
+        self.term_bytes = term_bytes;
+    }
+
+    /// 估計所佔內存之字節數，不含顯示緩存。
+    pub(super) fn memory(&self) -> usize {
+        self.term_bytes
+            + memory::rows(self.rows.capacity(), self.rows.len(), self.variables.len())
+            + self.unsorted.capacity() * std::mem::size_of::<usize>()
+    }
+
+    /// 顯示緩存之估計字節數。
+    pub(super) fn cell_bytes(&self) -> usize {
+        self.display.borrow().values().map(memory::line).sum()
+    }
+
+    /// 第 `row` 行之項。
+    pub(super) fn row(&self, row: usize) -> Option<impl Iterator<Item = Option<&Term>>> {
+        Some(self.rows.get(row)?.iter().map(Option::as_deref))
+    }
+
+    /// 使所選行可見，返回首個可見行。
+    pub(super) fn scroll_to(&self, selected: usize, height: usize) -> usize {
+        let mut offset = self.offset.get().min(selected);
+        if selected >= offset + height {
+            offset = selected + 1 - height;
+        }
+        self.offset.set(offset);
+        offset
+    }
+}
+
+/// 標出格中與過濾相符之處。`find` 對全格之文本求相符之區間，或跨數段。
+pub(super) fn mark_matches(
+    line: Line<'static>,
+    style: Style,
+    find: impl FnOnce(&str) -> Vec<Range<usize>>,
+) -> Line<'static> {
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    let ranges = find(&text);
+    if ranges.is_empty() {
+        return line;
+    }
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for span in line.spans {
+        let end = start + span.content.len();
+        // 本段中各相符與不相符之界
+        let mut cuts = vec![start, end];
+        for range in &ranges {
+            cuts.extend(
+                [range.start, range.end]
+                    .into_iter()
+                    .filter(|&cut| start < cut && cut < end),
+            );
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+        for pair in cuts.windows(2) {
+            let piece = &span.content[pair[0] - start..pair[1] - start];
+            let matched = ranges
+                .iter()
+                .any(|range| range.start <= pair[0] && pair[1] <= range.end);
+            let piece_style = if matched {
+                span.style.patch(style)
+            } else {
+                span.style
+            };
+            spans.push(Span::styled(piece.to_string(), piece_style));
+        }
+        start = end;
+    }
+    Line::from(spans).style(line.style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::{app_with_rows, render, rows, text, type_text, update_all};
+    use crate::{command, header::Order, overwrite::partial_path, sink::Sink};
+    use oxigraph::{
+        io::{RdfFormat, RdfParser},
+        model::{Literal, NamedNode},
+        sparql::{results::QueryResultsFormat, QuerySolution, Variable},
+    };
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::{fs, mem::size_of, sync::Arc};
+
+    #[test]
+    fn re_runs_keep_the_selected_row_by_its_terms_then_its_index() {
+        use oxigraph::model::{GraphName, NamedNode, Quad};
+        let quad = |subject: &str, value: i32| {
+            Quad::new(
+                NamedNode::new_unchecked(format!("http://ex/{subject}")),
+                NamedNode::new_unchecked("http://ex/p"),
+                Literal::from(value),
+                GraphName::DefaultGraph,
+            )
+        };
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?o".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        app.wait_for_query();
+        update_all(&mut app, [Action::SelectLast]);
+        assert_eq!(app.results.selected_row, 2);
+
+        // 前插一行，所選之行隨之下移
+        app.store.insert(&quad("d", 0)).unwrap();
+        update_all(&mut app, [Action::RunQuery]);
+        app.wait_for_query();
+        assert_eq!(app.results.selected_row, 3);
+        let row: Vec<_> = app.results.table().unwrap().row(3).unwrap().collect();
+        assert_eq!(row[0].unwrap().to_string(), "<http://ex/c>");
+
+        // 所選之行已無，則留其位，止於末行
+        app.store.remove(&quad("c", 3)).unwrap();
+        update_all(&mut app, [Action::RunQuery]);
+        app.wait_for_query();
+        assert_eq!(app.results.selected_row, 2);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("The selected row is gone; kept row 3")
+        );
+
+        // 他查詢不復
+        app.query
+            .set("SELECT ?o ?s WHERE { ?s ?p ?o } ORDER BY ?o".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        app.wait_for_query();
+        assert_eq!(app.results.selected_row, 0);
+    }
+
+    #[test]
+    fn scrolling_stops_at_the_ends() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::ScrollRows(10)]);
+        assert_eq!(app.results.selected_row, 2);
+        update_all(&mut app, [Action::ScrollRows(-1)]);
+        assert_eq!(app.results.selected_row, 1);
+        update_all(&mut app, [Action::ScrollRows(-10)]);
+        assert_eq!(app.results.selected_row, 0);
+        update_all(&mut app, [Action::SelectLast]);
+        assert_eq!(app.results.selected_row, 2);
+    }
+
+    #[test]
+    fn two_chosen_values_are_diffed_by_character() {
+        let mut app = App::new().unwrap();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "<http://ex/a> <http://ex/p> \"Paris, France\" . \
+                 <http://ex/b> <http://ex/p> \"Paris France \" ."
+                    .as_bytes(),
+            )
+            .unwrap();
+        app.query
+            .set("SELECT ?o { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(&mut app, [Action::RunQuery, Action::DiffValues]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Choose two cells with 1 and 2 first")
+        );
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('1'))),
+            [Action::ChooseDiffValue(0)]
+        );
+        update_all(
+            &mut app,
+            [Action::ScrollColumns(1), Action::ChooseDiffValue(0)],
+        );
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Value 1: row 1 ?o (choose the other with 2)")
+        );
+        update_all(
+            &mut app,
+            [
+                Action::ScrollRows(1),
+                Action::ChooseDiffValue(1),
+                Action::DiffValues,
+            ],
+        );
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("row 2 ?o"), "{screen}");
+        assert!(screen.contains("differs in 2 places"));
+        // 異處之空格示為 `·`
+        assert!(screen.contains("2  Paris France·"), "{screen}");
+
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('2'))),
+            [Action::YankDiffValue(1)]
+        );
+        update_all(&mut app, [Action::YankDiffValue(1)]);
+        assert_eq!(app.clipboard.as_deref(), Some("Paris France "));
+        assert!(app.popup.is_some());
+        update_all(&mut app, [Action::ScrollPopup(100)]);
+        let screen = text(&render(&mut app));
+        assert!(!screen.contains("row 2 ?o"), "{screen}");
+        assert!(screen.contains("1/2 copy a value"));
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('x'))),
+            [Action::CloseHelp]
+        );
+    }
+
+    #[test]
+    fn details_of_a_defined_property_show_its_definition() {
+        let mut app = App::new().unwrap();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "@prefix ex: <http://ex/> . \
+                 ex:hasPart a <http://www.w3.org/2002/07/owl#ObjectProperty> ; \
+                   <http://www.w3.org/2000/01/rdf-schema#range> ex:Part . \
+                 ex:car ex:hasPart ex:wheel ."
+                    .as_bytes(),
+            )
+            .unwrap();
+        app.query
+            .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o FILTER(?s = <http://ex/car>) }".to_string());
+        app.refresh_results();
+        app.wait_for_query();
+        // 未選格則取末列，即用法
+        update_all(&mut app, [Action::ShowDetails]);
+        assert_eq!(
+            app.query.string,
+            details_query(&NamedNode::new_unchecked("http://ex/wheel").into())
+        );
+
+        update_all(&mut app, [Action::Back]);
+        app.query
+            .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o FILTER(?s = <http://ex/car>) }".to_string());
+        app.refresh_results();
+        app.wait_for_query();
+        update_all(
+            &mut app,
+            [
+                Action::ScrollColumns(-1),
+                Action::ScrollColumns(-1),
+                Action::ShowDetails,
+            ],
+        );
+        let has_part = NamedNode::new_unchecked("http://ex/hasPart");
+        assert_eq!(app.query.string, definition::query(&has_part));
+        let text = app.table_text(app.results.table().unwrap());
+        assert_eq!(text[2], ["\"range\"", "<http://ex/Part>"]);
+    }
+
+    #[test]
+    fn the_header_row_describes_columns_and_sorts_them() {
+        let mut app = app_with_rows();
+        let enter = |app: &mut App| {
+            let actions = app.key_action(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+            update_all(app, actions);
+        };
+        let first = |app: &App| {
+            app.results.table().unwrap().rows[0][0]
+                .as_ref()
+                .unwrap()
+                .to_string()
+        };
+        update_all(&mut app, [Action::SelectHeader]);
+        assert_eq!(app.results.selected_column, Some(0));
+        assert_eq!(
+            app.status.as_deref(),
+            Some("?s · 3 distinct values · unsorted (Enter sorts)")
+        );
+        update_all(&mut app, [Action::ScrollColumns(2)]);
+        assert!(app.status.as_deref().unwrap().starts_with("?o · "));
+        enter(&mut app);
+        assert_eq!(first(&app), "<http://ex/a>");
+        assert!(app.status.as_deref().unwrap().ends_with("sorted ascending"));
+        assert!(text(&render(&mut app)).contains("o ▲"));
+        enter(&mut app);
+        assert_eq!(first(&app), "<http://ex/c>");
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .ends_with("sorted descending"));
+        update_all(&mut app, [Action::ScrollRows(1)]);
+        assert!(!app.results.on_header);
+        assert_eq!(app.results.selected_row, 0);
+
+        app.query
+            .set("SELECT ?s (STR(?o) AS ?text) { ?s ?p ?o }".to_string());
+        app.run_query();
+        app.wait_for_query();
+        update_all(&mut app, [Action::SelectHeader, Action::ScrollColumns(1)]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("?text = STR(?o) · 3 distinct values"));
+    }
+
+    #[test]
+    fn further_sort_keys_break_ties_and_clear_back_to_the_query_order() {
+        let mut app = app_with_rows();
+        app.query.set(
+            r#"SELECT ?g ?n { VALUES (?g ?n) { ("2" "2") ("1" "3") ("2" "1") ("1" "2") } }"#
+                .to_string(),
+        );
+        app.run_query();
+        app.wait_for_query();
+        let rows = |app: &App| {
+            app.results
+                .table()
+                .unwrap()
+                .rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|term| term.as_ref().unwrap().to_string().replace('"', ""))
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rows(&app), ["22", "13", "21", "12"]);
+        update_all(&mut app, [Action::SelectHeader, Action::ToggleSort]);
+        assert_eq!(rows(&app), ["13", "12", "22", "21"]);
+        update_all(&mut app, [Action::ScrollColumns(1), Action::AddSortKey]);
+        assert_eq!(rows(&app), ["12", "13", "21", "22"]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .ends_with("sorted ascending, key 2 of 2"));
+        update_all(&mut app, [Action::AddSortKey]);
+        assert_eq!(rows(&app), ["13", "12", "22", "21"]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("g ▲1"));
+        assert!(screen.contains("n ▼2"));
+        // 唯依一列排序則棄次鍵
+        update_all(&mut app, [Action::ToggleSort]);
+        assert_eq!(app.results.table().unwrap().sort, [(1, Order::Ascending)]);
+        update_all(&mut app, [Action::ClearSort]);
+        assert_eq!(rows(&app), ["22", "13", "21", "12"]);
+        assert!(app.results.table().unwrap().sort.is_empty());
+    }
+
+    #[test]
+    fn filter_hides_rows_and_keeps_invalid_patterns_in_the_prompt() {
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        let selected = |app: &App| {
+            let table = app.results.table().unwrap();
+            app.plain_cell(table.rows[app.results.selected_row][0].as_deref())
+        };
+        update_all(&mut app, [Action::OpenFilter]);
+        type_text(&mut app, "/B");
+        update_all(&mut app, [Action::DeleteBackward, Action::DeleteBackward]);
+        type_text(&mut app, "B>");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(selected(&app), "<http://ex/b>");
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("1 of 3 rows match \"B>\""));
+        assert!(!screen.contains("ex/a>"));
+        update_all(&mut app, [Action::SelectFirst, Action::ScrollRows(1)]);
+        assert_eq!(selected(&app), "<http://ex/b>");
+
+        // 反轉之，所選移至其後相符者
+        update_all(&mut app, [Action::OpenFilter]);
+        assert_eq!(app.results.filter_line.input, "B>");
+        update_all(
+            &mut app,
+            [Action::SetFilter {
+                pattern: Some("!b>".into()),
+                regex: false,
+            }],
+        );
+        assert_eq!(selected(&app), "<http://ex/c>");
+        update_all(&mut app, [Action::ScrollRows(-1)]);
+        assert_eq!(selected(&app), "<http://ex/a>");
+
+        // 無效之正則留於輸入行，原過濾不變
+        update_all(&mut app, [Action::OpenFilter, Action::ToggleRegex]);
+        type_text(&mut app, "(");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.mode, Mode::Filter);
+        assert!(text(&render(&mut app)).contains("unclosed group"));
+        update_all(&mut app, [Action::Back]);
+        assert_eq!(app.results.filter.as_ref().unwrap().describe(), "!\"b>\"");
+
+        // 子句限於一列，皆須相符
+        update_all(
+            &mut app,
+            [Action::SetFilter {
+                pattern: Some("?o: /^\"3/ !1:/a>$/".into()),
+                regex: false,
+            }],
+        );
+        assert_eq!(selected(&app), "<http://ex/c>");
+        assert!(text(&render(&mut app)).contains("1 of 3 rows match ?o:/^\"3/ !1:/a>$/"));
+        update_all(&mut app, [Action::OpenFilter]);
+        type_text(&mut app, " ?p:x");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.results.filter_error.as_deref(), Some("no variable ?p"));
+        update_all(&mut app, [Action::Back]);
+
+        update_all(
+            &mut app,
+            [Action::SetFilter {
+                pattern: None,
+                regex: false,
+            }],
+        );
+        assert!(app.results.filter.is_none());
+        assert!(text(&render(&mut app)).contains("ex/b>"));
+    }
+
+    #[test]
+    fn the_selected_cell_wraps_if_configured_and_follows_its_variable_across_runs() {
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(
+            &mut app,
+            [
+                Action::RunQuery,
+                Action::ScrollColumns(1),
+                Action::ScrollColumns(1),
+            ],
+        );
+        assert_eq!(app.results.selected_column, Some(1));
+        app.config.wrap_columns = true;
+        update_all(&mut app, [Action::ScrollColumns(1)]);
+        assert_eq!(app.results.selected_column, Some(0));
+        update_all(&mut app, [Action::ScrollColumns(-1), Action::ScrollRows(1)]);
+        assert_eq!(
+            (app.results.selected_row, app.results.selected_column),
+            (1, Some(1))
+        );
+
+        // 投影之序變，所選仍為 ?o
+        app.query
+            .set("SELECT ?o ?s WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(
+            (app.results.selected_row, app.results.selected_column),
+            (0, Some(0))
+        );
+        app.query.set("SELECT ?s WHERE { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(app.results.selected_column, None);
+    }
+
+    #[test]
+    fn y_opens_a_menu_whose_next_key_picks_the_form_to_copy() {
+        let mut app = app_with_rows();
+        app.config
+            .prefixes
+            .push(("ex".to_string(), "http://ex/".to_string()));
+        app.query
+            .set("SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        app.wait_for_query();
+        let press = |app: &mut App, keys: &str| {
+            for ch in keys.chars() {
+                let actions = app.map_key(KeyEvent::from(KeyCode::Char(ch)));
+                update_all(app, actions);
+            }
+        };
+
+        press(&mut app, "y");
+        assert!(app.status.as_deref().unwrap().starts_with("Yank: i iri"));
+        press(&mut app, "r");
+        assert_eq!(app.clipboard.take().as_deref(), Some("http://ex/a	1"));
+
+        // 格之形式須先選格；`y y` 取所配置者，他鍵取消
+        update_all(&mut app, [Action::ScrollColumns(1)]);
+        press(&mut app, "yc");
+        assert_eq!(app.clipboard.take().as_deref(), Some("ex:a"));
+        press(&mut app, "yi");
+        assert_eq!(app.clipboard.take().as_deref(), Some("http://ex/a"));
+        press(&mut app, "yy");
+        assert_eq!(app.clipboard.take().as_deref(), Some("<http://ex/a>"));
+        app.config.yank = Yank::Curie;
+        update_all(&mut app, [Action::ScrollColumns(1)]);
+        press(&mut app, "yy");
+        assert_eq!(
+            app.clipboard.take().as_deref(),
+            Some("\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>")
+        );
+        press(&mut app, "yv");
+        assert_eq!(app.clipboard.take().as_deref(), Some("1"));
+        press(&mut app, "yi");
+        assert_eq!(app.clipboard, None);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("A literal has no IRI; v copies its value")
+        );
+        press(&mut app, "yq");
+        assert!(app.clipboard.is_none() && app.status.is_none());
+        assert_eq!(
+            command::parse("yank nt"),
+            Ok(Action::YankAs(Yank::NTriples))
+        );
+    }
+
+    #[test]
+    fn diffs_partition_rows_against_the_previous_run() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::ShowDiff]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("No earlier run of this query to compare with")
+        );
+        app.store
+            .update(
+                "DELETE DATA { <http://ex/a> <http://ex/p> 1 } ; \
+                 INSERT DATA { <http://ex/d> <http://ex/p> 4 . <http://ex/e> <http://ex/p> 5 }",
+            )
+            .unwrap();
+        app.invalidate_results();
+        app.refresh_results();
+        app.wait_for_query();
+        update_all(&mut app, [Action::ShowDiff]);
+        assert!(app.results.derived());
+        let changes = |app: &App| -> Vec<String> {
+            let table = app.results.table().unwrap();
+            table
+                .rows
+                .iter()
+                .map(|row| row[0].as_deref().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            changes(&app),
+            ["\"added\"", "\"added\"", "\"unchanged\"", "\"unchanged\""]
+        );
+        assert_eq!(app.results.table().unwrap().variables.len(), 4);
+        assert!(text(&render(&mut app))
+            .contains("+2 added · 2 unchanged · 1 removed (hidden, D shows)"));
+
+        // 再按則列出刪去之行
+        update_all(&mut app, [Action::ShowDiff]);
+        assert_eq!(changes(&app).last().unwrap(), "\"removed\"");
+        assert!(text(&render(&mut app)).contains("(D hides)"));
+
+        // 回到查詢則復其結果，不重新查詢
+        update_all(&mut app, [Action::SwitchMode]);
+        assert!(!app.results.derived());
+        assert!(app.running.is_none());
+        assert_eq!(rows(&app), 4);
+    }
+
+    #[test]
+    fn column_stats_summarize_the_selected_column() {
+        let mut app = app_with_rows();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        update_all(&mut app, [Action::ShowColumnStats]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Select a column with ←/→ first")
+        );
+        assert_eq!(
+            app.map_key(key(KeyCode::Char('Σ'))),
+            [Action::ShowColumnStats]
+        );
+        update_all(
+            &mut app,
+            [Action::ScrollColumns(-1), Action::ShowColumnStats],
+        );
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("?o · 3 rows"));
+        assert!(screen.contains("distinct   3"));
+        assert!(screen.contains("mean       2"));
+        assert!(screen.contains("sum        6"));
+
+        // 任意鍵關閉
+        assert_eq!(app.map_key(key(KeyCode::Char('j'))), [Action::CloseHelp]);
+        update_all(&mut app, [Action::CloseHelp]);
+        assert!(app.popup.is_none());
+    }
+
+    #[test]
+    fn the_footer_summarizes_column_types() {
+        let mut app = app_with_rows();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(app.map_key(key(KeyCode::Char('T'))), [Action::ToggleTypes]);
+        update_all(&mut app, [Action::ToggleTypes]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("IRI (100%)"));
+        assert!(screen.contains("xsd:integer (100%)"));
+
+        update_all(&mut app, [Action::ScrollRows(1), Action::ScrollRows(1)]);
+        assert!(!app.results.on_footer);
+        update_all(&mut app, [Action::ScrollRows(1)]);
+        assert!(app.results.on_footer);
+        update_all(
+            &mut app,
+            [Action::ScrollColumns(1), Action::ScrollColumns(1)],
+        );
+        assert_eq!(app.map_key(key(KeyCode::Enter)), [Action::ShowColumnTypes]);
+        update_all(&mut app, [Action::ShowColumnTypes]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("?o · types"));
+        assert!(screen.contains("xsd:integer 3 (100%)"));
+        assert!(screen.contains("unbound     0"));
+
+        update_all(&mut app, [Action::CloseHelp, Action::ScrollRows(-1)]);
+        assert!(!app.results.on_footer);
+        assert_eq!(app.results.selected_row, 2);
+        update_all(&mut app, [Action::ToggleTypes]);
+        assert!(!text(&render(&mut app)).contains("IRI (100%)"));
+    }
+
+    /// 百萬行、五十謂語之結果，共用項後遠小於每格一 `String`；表釋放則項亦釋放。
+    #[test]
+    fn interned_results_are_much_smaller_than_strings() {
+        let variables: Arc<[Variable]> = ["s", "p", "o"].map(Variable::new_unchecked).into();
+        let iri = |kind: &str, n: usize| {
+            Term::from(NamedNode::new_unchecked(format!(
+                "http://example.org/{kind}/{n}"
+            )))
+        };
+        let mut naive = 0;
+        let solutions = (0..1_000_000).map(|n| {
+            let row = vec![
+                Some(iri("resource", n / 100)),
+                Some(iri("property", n % 50)),
+                Some(iri("class", n % 7)),
+            ];
+            naive += row
+                .iter()
+                .flatten()
+                .map(|term| size_of::<String>() + term.to_string().len())
+                .sum::<usize>();
+            Ok::<_, std::convert::Infallible>(QuerySolution::from((Arc::clone(&variables), row)))
+        });
+        let table = ResultTable::from_solutions(variables.to_vec(), solutions, None).unwrap();
+        assert_eq!(table.rows.len(), 1_000_000);
+        assert!(
+            table.memory() * 3 < naive,
+            "interned {} vs naive {naive}",
+            table.memory()
+        );
+
+        let predicate = table.rows[0][1].clone().unwrap();
+        assert!(Arc::ptr_eq(&predicate, table.rows[50][1].as_ref().unwrap()));
+        // 僅表中之行持有之，構造時之合併表已不在
+        assert_eq!(Arc::strong_count(&predicate), 1_000_000 / 50 + 1);
+        drop(table);
+        assert_eq!(Arc::strong_count(&predicate), 1);
+    }
+
+    #[test]
+    fn quoted_triples_render_follow_and_refuse_csv() {
+        let mut app = App::new().unwrap();
+        app.config.prefixes = vec![("ex".to_string(), "http://ex/".to_string())];
+        app.load_data(
+            "@prefix ex: <http://ex/> .\n\
+             << ex:a ex:b << ex:c ex:d \"e\"@en >> >> ex:by \
+             << << << ex:f ex:g ex:h >> ex:k ex:l >> ex:i ex:j >> .",
+            RdfFormat::Turtle,
+        );
+        app.refresh_results();
+        app.wait_for_query();
+        let table = app.results.table().unwrap();
+        let text = app.table_text(table);
+        assert_eq!(text[1][0], "<< ex:a ex:b << ex:c ex:d \"e\"@en >> >>");
+        assert_eq!(text[1][2], "<< << << … >> ex:k ex:l >> ex:i ex:j >>");
+
+        let dir = std::env::temp_dir();
+        let csv = dir.join(format!("rdf-tui-star-{}.csv", std::process::id()));
+        let tsv = csv.with_extension("tsv");
+        app.start_export(
+            QueryResultsFormat::Csv.into(),
+            Sink::File(csv.clone()),
+            false,
+        )
+        .unwrap();
+        assert!(app.wait_for_export().unwrap().is_err());
+        assert!(!csv.exists());
+        assert!(!partial_path(&csv).exists());
+        app.start_export(
+            QueryResultsFormat::Tsv.into(),
+            Sink::File(tsv.clone()),
+            false,
+        )
+        .unwrap();
+        assert_eq!(app.wait_for_export(), Some(Ok(1)));
+        assert!(fs::read_to_string(&tsv)
+            .unwrap()
+            .contains("<< <http://ex/a> <http://ex/b> << <http://ex/c>"));
+        fs::remove_file(tsv).unwrap();
+
+        update_all(&mut app, [Action::ShowDetails]);
+        let table = app.results.table().unwrap();
+        let text = app.table_text(table);
+        assert_eq!(text.len(), 4);
+        assert!(text.iter().any(|row| row[1] == "ex:j"));
+        assert!(text
+            .iter()
+            .any(|row| row[1] == "<< << ex:f ex:g ex:h >> ex:k ex:l >>"));
+    }
+
+    #[test]
+    fn the_row_popup_shows_how_each_column_was_computed() {
+        let mut app = app_with_rows();
+        app.query.set(
+            "SELECT ?s (STR(?o) AS ?text) # (1 AS ?s)\nWHERE { ?s ?p ?o } ORDER BY ?s".to_string(),
+        );
+        update_all(&mut app, [Action::RunQuery]);
+        app.mode = Mode::Browse;
+        let actions = app.map_key(KeyEvent::from(KeyCode::Char('R')));
+        update_all(&mut app, actions);
+        assert!(matches!(app.popup, Some(Popup::Row(0))));
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Row 1"));
+        assert!(screen.contains("?text      \"1\"  = STR(?o)"));
+        assert!(!screen.contains("= 1"));
+
+        update_all(
+            &mut app,
+            [
+                Action::CloseHelp,
+                Action::SelectHeader,
+                Action::ScrollColumns(1),
+            ],
+        );
+        assert_eq!(
+            app.status
+                .as_deref()
+                .map(|status| status.split(" · ").next()),
+            Some(Some("?text = STR(?o)"))
+        );
+    }
+
+    #[test]
+    fn stable_order_sorts_results_without_order_by() {
+        let mut app = app_with_rows();
+        let first = |app: &App| {
+            app.results.table().unwrap().rows[0][0]
+                .as_ref()
+                .unwrap()
+                .to_string()
+        };
+        update_all(&mut app, [Action::ToggleStableOrder]);
+        assert_eq!(first(&app), "<http://ex/a>");
+        assert!(text(&render(&mut app)).contains("stable order, not the engine's"));
+
+        // 自有 ORDER BY 者依之
+        let ordered = "SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY DESC(?s)";
+        app.query.set(ordered.to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(first(&app), "<http://ex/c>");
+        assert!(!text(&render(&mut app)).contains("stable order"));
+
+        let unordered = "SELECT ?s ?o WHERE { ?s ?p ?o }";
+        app.query.set(unordered.to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(first(&app), "<http://ex/a>");
+        update_all(&mut app, [Action::SetStableOrder(Some(false))]);
+        assert!(!app.results.table().unwrap().stable);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Results keep the engine's order")
+        );
+        assert_eq!(
+            command::parse("order stable"),
+            Ok(Action::SetStableOrder(Some(true)))
+        );
+    }
+}
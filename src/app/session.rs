@@ -0,0 +1,790 @@
+//! 存於磁盤之狀態：會話之保存與恢復、啓動查詢、庫與配置檔之導入導出、分享連結。
+//! 其所在見 `crate::paths`。
+
+use super::{App, Confirmation, Mode, Query};
+use crate::{
+    action::Action,
+    bookmarks::{Bookmark, View},
+    display::abbreviate_count,
+    download,
+    filter::Filter,
+    library,
+    link::Link,
+    profile,
+    session::{Restore, Session},
+    startup,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+impl App {
+    /// 執行會話、視圖、文庫與設定之操作。餘者原樣返回，交下一部分。
+    pub(super) fn update_session(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::RestoreSession => self.restore_session(),
+            Action::SaveView(name) => self.save_view(name),
+            Action::ImportLibrary(dir) => self.import_library(&dir),
+            Action::ExportLibrary(dir) => self.export_library(&dir),
+            Action::ImportProfile(path) => self.import_profile(&path),
+            Action::ExportProfile(path) => {
+                self.status = Some(match fs::write(&path, profile::export(&self.config)) {
+                    Ok(()) => format!("Exported the settings to {}", path.display()),
+                    Err(error) => format!("Fail to export the settings: {error}"),
+                });
+            }
+            Action::MergeLibrary { views, replace } => self.merge_library(views, replace),
+            Action::OpenView(name) => match self.bookmarks.view(&name).cloned() {
+                Some(view) => self.open_view(view),
+                None => self.status = Some(format!("No view named {name}")),
+            },
+            Action::Share => self.share(),
+            Action::Goto(text) => {
+                if let Err(error) = self.goto(&text) {
+                    self.status = Some(error);
+                }
+            }
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 導入查詢庫：新者存為視圖，與已存者全同者略之，同名而查詢、說明或標籤異者問之。
+    pub(super) fn import_library(&mut self, dir: &Path) {
+        let entries = match library::read(dir) {
+            Ok(entries) => entries,
+            Err(error) => {
+                self.status = Some(format!("Fail to read library: {error:#}"));
+                return;
+            }
+        };
+        let (mut added, mut saved, mut conflicts) = (vec![], 0, vec![]);
+        for entry in entries {
+            let old = self
+                .bookmarks
+                .view(&entry.name)
+                .map(library::Entry::from_view);
+            let queued = added.iter().any(|view: &View| view.name == entry.name);
+            match old {
+                Some(old) if old == entry => saved += 1,
+                Some(_) => conflicts.push(entry.into_view()),
+                None if queued => conflicts.push(entry.into_view()),
+                None => added.push(entry.into_view()),
+            }
+        }
+        let count = added.len();
+        if let Err(error) = self.bookmarks.put_views(added) {
+            self.status = Some(format!("Fail to save bookmarks: {error}"));
+            return;
+        }
+        self.status = Some(format!(
+            "Imported {count} views from {} ({saved} already saved)",
+            dir.display()
+        ));
+        if conflicts.is_empty() {
+            return;
+        }
+        let names: Vec<&str> = conflicts.iter().map(|view| view.name.as_str()).collect();
+        self.confirmation = Some(Confirmation {
+            message: format!(
+                "{} differ from the saved views of the same name. Replace them (y), keep both (k), or skip them (n)?",
+                names.join(", ")
+            ),
+            preview: None,
+            alternative: Some((
+                'k',
+                Action::MergeLibrary {
+                    views: conflicts.clone(),
+                    replace: false,
+                },
+            )),
+            action: Action::MergeLibrary {
+                views: conflicts,
+                replace: true,
+            },
+        });
+    }
+
+    /// 併入同名之視圖：代之，或以 `名 (2)` 之類新名存之。
+    pub(super) fn merge_library(&mut self, mut views: Vec<View>, replace: bool) {
+        if !replace {
+            let mut taken: Vec<String> = vec![];
+            for view in &mut views {
+                let name = (2..)
+                    .map(|n| format!("{} ({n})", view.name))
+                    .find(|name| self.bookmarks.view(name).is_none() && !taken.contains(name))
+                    .unwrap_or_else(|| unreachable!());
+                taken.push(name.clone());
+                view.name = name;
+            }
+        }
+        let names: Vec<String> = views.iter().map(|view| view.name.clone()).collect();
+        self.status = Some(match self.bookmarks.put_views(views) {
+            Ok(()) if replace => format!("Replaced views {}", names.join(", ")),
+            Ok(()) => format!("Added views {}", names.join(", ")),
+            Err(error) => format!("Fail to save bookmarks: {error}"),
+        });
+    }
+
+    /// 寫視圖與查詢書籤為查詢庫。目錄中同名之文件代之：庫多在版本庫中，舊者可復。
+    pub(super) fn export_library(&mut self, dir: &Path) {
+        let entries: Vec<library::Entry> = self
+            .bookmarks
+            .entries()
+            .iter()
+            .filter_map(|bookmark| match bookmark {
+                Bookmark::View(view) => Some(library::Entry::from_view(view)),
+                Bookmark::Query { query } => Some(library::Entry::from_query(query)),
+                Bookmark::Resource { .. } => None,
+            })
+            .collect();
+        if entries.is_empty() {
+            self.status = Some("No saved views or queries to export".to_string());
+            return;
+        }
+        self.status = Some(match library::write(dir, &entries) {
+            Ok(()) => format!("Exported {} queries to {}", entries.len(), dir.display()),
+            Err(error) => format!("Fail to export library: {error:#}"),
+        });
+    }
+
+    /// 以側寫蓋當前之設置。所改者報於狀態欄，結果不重新查詢。
+    pub(super) fn import_profile(&mut self, path: &Path) {
+        let mut config = self.config.clone();
+        let applied = fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| profile::import(&mut config, &text));
+        let applied = match applied {
+            Ok(applied) => applied,
+            Err(error) => {
+                self.status = Some(format!("Fail to import profile: {error:#}"));
+                return;
+            }
+        };
+        log::info!("profile {} {}", path.display(), applied.describe());
+        if let Some(language) = config.ui_language {
+            self.language = language;
+        }
+        self.limit = config.max_rows;
+        self.lang.clone_from(&config.lang);
+        self.theme = config.theme();
+        self.config = config;
+        if let Some(preset) = self.preset(&self.config.layout) {
+            self.layout = preset;
+        }
+        // 前綴、空值之文字與色調或已改
+        if let Some(table) = self.results.table() {
+            table.display.borrow_mut().clear();
+        }
+        let mut status = format!("Profile {}: {}", path.display(), applied.describe());
+        if !applied.warnings.is_empty() {
+            status.push_str(&format!(" ({})", applied.warnings.join("; ")));
+        }
+        self.status = Some(status);
+    }
+
+    /// 此數據集之會話文件。
+    fn session_path(&self) -> Option<PathBuf> {
+        self.paths.session_file(&self.sources)
+    }
+
+    /// 首批文件載入後執行啓動查詢，再按設置恢復會話或詢問之。會話文件損壞則警告而忽略。
+    pub(super) fn offer_session(&mut self) {
+        self.apply_link();
+        self.run_startup_query();
+        self.choose_default_query();
+        let restore = std::mem::replace(&mut self.restore, Restore::Never);
+        if restore == Restore::Never {
+            return;
+        }
+        let Some(path) = self.session_path() else {
+            return;
+        };
+        match Session::load(&path) {
+            Ok(None) => {}
+            Ok(Some(mut session)) => {
+                // 便箋與宏不待確認即恢復，不恢復會話亦不失之
+                self.scratchpad.replace(std::mem::take(&mut session.pins));
+                self.macros.replace(std::mem::take(&mut session.macros));
+                self.saved_session = Some(session);
+                if restore == Restore::Always {
+                    self.restore_session();
+                } else {
+                    self.confirmation = Some(Confirmation {
+                        message: self.tr("Restore the previous session? (y/n)").to_string(),
+                        action: Action::RestoreSession,
+                        preview: None,
+                        alternative: None,
+                    });
+                }
+            }
+            Err(error) => {
+                log::warn!("session ignored path={} error={error}", path.display());
+                self.warn(format!("session ignored: {error}"));
+            }
+        }
+    }
+
+    /// 編輯器仍為初始之查詢，則代以配置之默認查詢，或依數據選之：有命名圖則兼列其圖，
+    /// 大存儲則限百行。僅一次，所選者示於狀態欄。
+    fn choose_default_query(&mut self) {
+        if !std::mem::take(&mut self.default_query) || self.query.string != Query::new().string {
+            return;
+        }
+        let (query, reason) = match &self.config.default_query {
+            Some(query) => (query.clone(), "from the config".to_string()),
+            None => {
+                let triples = self.store.len().unwrap_or(0);
+                let graphs = self.store.named_graphs().next().is_some();
+                let large = triples > self.config.large_store;
+                let mut query = if graphs {
+                    "SELECT ?s ?p ?o ?g WHERE { { ?s ?p ?o } UNION { GRAPH ?g { ?s ?p ?o } } }"
+                        .to_string()
+                } else {
+                    Query::new().string
+                };
+                if large {
+                    query.push_str(" LIMIT 100");
+                }
+                let reason = match (graphs, large) {
+                    (false, false) => return,
+                    (true, false) => "listing named graphs".to_string(),
+                    (graphs, true) => format!(
+                        "{}first 100 of {} triples",
+                        if graphs { "listing named graphs, " } else { "" },
+                        abbreviate_count(triples)
+                    ),
+                };
+                (query, reason)
+            }
+        };
+        log::info!("default query reason={reason}");
+        self.query.set(query);
+        self.status = Some(format!("Default query {reason}"));
+    }
+
+    /// 以數據集之啓動查詢代替默認之查詢，並於狀態欄示其出處。僅一次。
+    fn run_startup_query(&mut self) {
+        if !std::mem::take(&mut self.startup_query) {
+            return;
+        }
+        let Some(found) = startup::find(
+            &self.sources,
+            &self.config.startup_queries,
+            self.config.startup_query_files,
+        ) else {
+            return;
+        };
+        log::info!("startup query origin={}", found.origin);
+        self.query.set(found.query);
+        self.run_query();
+        self.status = Some(format!("Startup query from {}", found.origin));
+    }
+
+    /// 恢復待恢復之會話：先執行上次之查詢，再還原編輯器與所選行。
+    pub(super) fn restore_session(&mut self) {
+        let Some(session) = self.saved_session.take() else {
+            return;
+        };
+        self.limit = session.limit;
+        self.dataset = session.dataset;
+        self.mode = session.mode;
+        self.provenance.merge(session.graphs);
+        if let Some(query) = session.last_query {
+            self.query.set(query);
+            self.run_query();
+        }
+        self.query.set(session.query);
+        self.refresh_results();
+        self.wait_for_query();
+        self.restore_sort(session.sort);
+        let len = self.results.table().map_or(0, |table| table.rows.len());
+        self.results.selected_row = session.selected_row.min(len.saturating_sub(1));
+        self.status = Some("Restored the previous session".to_string());
+    }
+
+    /// 複製當前之鏈接。URL 之憑據不入其中；`--data` 所給之數據無路徑，亦不入。
+    pub(super) fn share(&mut self) {
+        let sources = self
+            .sources
+            .iter()
+            .map(|path| match self.downloaded.get(path) {
+                Some(url) => url.clone(),
+                None => path.display().to_string(),
+            })
+            .collect();
+        let link = Link {
+            sources,
+            query: self.query.string.clone(),
+            dataset: self.dataset.clone(),
+            filter: self
+                .results
+                .filter
+                .as_ref()
+                .map(|filter| (filter.text.clone(), filter.regex)),
+            sort: self
+                .results
+                .table()
+                .map(|table| table.sort.clone())
+                .unwrap_or_default(),
+            column: self.results.selected_column,
+            row: self.results.selected_row,
+            limit: self.limit,
+            lang: self.lang.clone(),
+        };
+        let text = link.encode();
+        let mut status = format!(
+            "Copied a link ({} characters); open it with :goto",
+            text.len()
+        );
+        if !self.inline.is_empty() {
+            status.push_str("; data given with --data is not in it");
+        }
+        self.clipboard = Some(text);
+        self.status = Some(status);
+    }
+
+    /// 打開鏈接：未載入之文件排入待載入，載畢方執行其查詢，代會話與啓動查詢。
+    /// 已載入者不重載；不存之文件記之，恢復後報之。
+    pub(super) fn goto(&mut self, text: &str) -> Result<(), String> {
+        let link = Link::decode(text)?;
+        let mut missing = vec![];
+        for source in &link.sources {
+            let path = PathBuf::from(source);
+            let loaded = self.sources.contains(&path)
+                || self.pending.contains(&path)
+                || self.downloaded.values().any(|url| url == source);
+            if loaded {
+                continue;
+            }
+            match download::is_url(&path) || path.exists() {
+                true => self.pending.push_back(path),
+                false => missing.push(format!("{source} (not found)")),
+            }
+        }
+        self.restore = Restore::Never;
+        self.startup_query = false;
+        self.default_query = false;
+        log::info!(
+            "open link sources={} missing={}",
+            link.sources.len(),
+            missing.len()
+        );
+        self.pending_link = Some((link, missing));
+        self.apply_link();
+        Ok(())
+    }
+
+    /// 命令行之 `--open-link`。鏈接損壞或來自新版者返回其因。
+    pub fn open_link(&mut self, text: &str) -> Result<(), String> {
+        self.goto(text)
+    }
+
+    /// 文件皆已載入則恢復待打開之鏈接：如恢復會話，先執行其查詢，再設其顯示與所選行。
+    pub(super) fn apply_link(&mut self) {
+        if !self.pending.is_empty() || self.loading.is_some() || self.download.is_some() {
+            return;
+        }
+        let Some((link, mut problems)) = self.pending_link.take() else {
+            return;
+        };
+        self.limit = link.limit;
+        self.lang = link.lang;
+        self.dataset = link.dataset;
+        self.mode = Mode::Browse;
+        self.query.set(link.query);
+        self.run_query();
+        self.wait_for_query();
+        if let Some((text, regex)) = link.filter {
+            match Filter::new(&text, regex) {
+                Ok(filter) => self.results.filter = Some(filter),
+                Err(error) => problems.push(format!("the filter {text} ({error})")),
+            }
+        }
+        let (columns, len) = self
+            .results
+            .table()
+            .map_or((0, 0), |table| (table.variables.len(), table.rows.len()));
+        self.results.selected_column = link.column.filter(|&column| column < columns);
+        if let Some(column) = link.column.filter(|&column| column >= columns) {
+            problems.push(format!(
+                "column {} (the results have {columns})",
+                column + 1
+            ));
+        }
+        self.restore_sort(link.sort);
+        if link.row >= len && link.row > 0 {
+            problems.push(format!("row {} (the results have {len})", link.row + 1));
+        }
+        self.results.selected_row = link.row.min(len.saturating_sub(1));
+        self.status = Some(match problems.is_empty() {
+            true => "Opened the link".to_string(),
+            false => format!("Opened the link; could not restore {}", problems.join(", ")),
+        });
+    }
+
+    /// 保存此數據集之會話。未載入文件則不存。
+    pub fn save_session(&self) -> io::Result<()> {
+        // 導入之結果非存儲之查詢，不足為會話
+        let Some(path) = self.session_path().filter(|_| self.imported.is_none()) else {
+            return Ok(());
+        };
+        self.session().save(&path)
+    }
+
+    /// 此刻之會話。
+    pub(super) fn session(&self) -> Session {
+        Session {
+            query: self.query.string.clone(),
+            last_query: self
+                .results
+                .current
+                .as_ref()
+                .map(|results| results.query.clone()),
+            mode: match self.mode {
+                Mode::Query => Mode::Query,
+                _ => Mode::Browse,
+            },
+            selected_row: self.results.selected_row,
+            sort: self
+                .results
+                .table()
+                .map(|table| table.sort.clone())
+                .unwrap_or_default(),
+            limit: self.limit,
+            dataset: self.dataset.clone(),
+            graphs: self.provenance.entries().to_vec(),
+            pins: self.scratchpad.pins().to_vec(),
+            macros: self.macros.all().to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::{app_with_rows, render, rows, text, type_text, update_all};
+    use crate::theme::Tint;
+    use oxigraph::io::RdfFormat;
+    use ratatui::crossterm::event::KeyCode;
+
+    use ratatui::crossterm::event::KeyEvent;
+    use std::fs;
+
+    #[test]
+    fn views_restore_the_display_after_their_query_runs() {
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(
+            &mut app,
+            [
+                Action::RunQuery,
+                Action::SetFilter {
+                    pattern: Some("?s: b".into()),
+                    regex: false,
+                },
+                Action::ScrollColumns(1),
+                Action::TogglePreview,
+                Action::SaveView("only b".to_string()),
+            ],
+        );
+        assert_eq!(app.status.as_deref(), Some("Saved view only b"));
+
+        app.query.set("SELECT * WHERE { ?s ?p ?o }".to_string());
+        update_all(
+            &mut app,
+            [
+                Action::RunQuery,
+                Action::SetFilter {
+                    pattern: None,
+                    regex: false,
+                },
+                Action::TogglePreview,
+                Action::OpenView("only b".to_string()),
+            ],
+        );
+        assert!(app.query.string.ends_with("ORDER BY ?s"));
+        assert_eq!(app.results.filter.as_ref().unwrap().describe(), "?s:\"b\"");
+        assert_eq!(app.results.selected_column, Some(0));
+        assert!(app.preview);
+        assert!(text(&render(&mut app)).contains("1 of 3 rows match"));
+
+        // 列於書籤面板，可由此打開
+        update_all(&mut app, [Action::OpenBookmarks]);
+        assert!(text(&render(&mut app)).contains("view     only b: SELECT ?s ?o"));
+        update_all(&mut app, [Action::OpenView("nothing".to_string())]);
+        assert_eq!(app.status.as_deref(), Some("No view named nothing"));
+    }
+
+    #[test]
+    fn libraries_import_with_a_prompt_on_conflicts_and_export_views_and_queries() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-app-library-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut app = app_with_rows();
+        app.query.set("SELECT * WHERE { ?s ?p ?o }".to_string());
+        update_all(
+            &mut app,
+            [
+                Action::SaveView("all".to_string()),
+                Action::SaveView("same".to_string()),
+                Action::BookmarkQuery,
+            ],
+        );
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("all.rq"),
+            "# every triple\nSELECT ?s WHERE { ?s ?p ?o }\n",
+        )
+        .unwrap();
+        fs::write(dir.join("same.rq"), "SELECT * WHERE { ?s ?p ?o }").unwrap();
+        fs::write(dir.join("new.rq"), "ASK {}").unwrap();
+        update_all(&mut app, [Action::ImportLibrary(dir.clone())]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Imported 1 views from"));
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .ends_with("(1 already saved)"));
+        assert!(app.bookmarks.view("new").is_some());
+        let message = &app.confirmation.as_ref().unwrap().message;
+        assert!(message.starts_with("all differ from the saved views"));
+
+        // 皆留：庫中者以新名存之
+        update_all(&mut app, [Action::ConfirmAlternative]);
+        assert_eq!(app.status.as_deref(), Some("Added views all (2)"));
+        assert!(app
+            .bookmarks
+            .view("all (2)")
+            .unwrap()
+            .query
+            .starts_with("# every"));
+        update_all(
+            &mut app,
+            [Action::ImportLibrary(dir.clone()), Action::Confirm],
+        );
+        assert_eq!(app.status.as_deref(), Some("Replaced views all"));
+        assert!(app
+            .bookmarks
+            .view("all")
+            .unwrap()
+            .query
+            .starts_with("# every"));
+
+        let out = dir.join("out");
+        update_all(&mut app, [Action::ExportLibrary(out.clone())]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Exported 5 queries to"));
+        assert_eq!(
+            fs::read_to_string(out.join("all.rq")).unwrap(),
+            "# every triple\nSELECT ?s WHERE { ?s ?p ?o }\n"
+        );
+        assert!(out.join("all-2.rq").exists() && out.join("query.rq").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn profiles_apply_over_the_config_and_export_the_effective_settings() {
+        let path =
+            std::env::temp_dir().join(format!("rdf-tui-profile-{}.toml", std::process::id()));
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::OpenCommandLine]);
+        type_text(&mut app, &format!("profile export {}", path.display()));
+        update_all(&mut app, [Action::Activate]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Exported the settings"));
+        let exported = fs::read_to_string(&path).unwrap();
+        assert!(exported.contains("\ntint = \"off\"\n"), "{exported}");
+
+        fs::write(
+            &path,
+            "tint = \"kind\"\nmax_rows = 2\n[keys]\nquit = \"x\"\n",
+        )
+        .unwrap();
+        update_all(&mut app, [Action::ImportProfile(path.clone())]);
+        let status = app.status.clone().unwrap();
+        assert!(
+            status.ends_with(": overrides max_rows, tint, keys.quit"),
+            "{status}"
+        );
+        assert_eq!(app.config.tint, Tint::Kind);
+        assert_eq!(app.limit, Some(2));
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('x'))),
+            [Action::Quit]
+        );
+
+        fs::write(&path, "tint = 3").unwrap();
+        update_all(&mut app, [Action::ImportProfile(path.clone())]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Fail to import profile"));
+        assert_eq!(app.config.tint, Tint::Kind);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn a_shared_link_reopens_the_files_query_and_view() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-link-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.ttl");
+        fs::write(
+            &path,
+            "<http://ex/a> <http://ex/p> 1 . <http://ex/b> <http://ex/p> 2 . \
+             <http://ex/c> <http://ex/p> 3 .\n",
+        )
+        .unwrap();
+        let mut app = App::new().unwrap();
+        app.open(path.clone());
+        app.query.set("SELECT ?s ?o WHERE { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        app.results.selected_column = Some(1);
+        update_all(
+            &mut app,
+            [
+                Action::ToggleSort,
+                Action::ToggleSort,
+                Action::SetFilter {
+                    pattern: Some("!/a>$/".to_string()),
+                    regex: false,
+                },
+                Action::SelectLast,
+                Action::Share,
+            ],
+        );
+        assert!(app.status.as_deref().unwrap().starts_with("Copied a link"));
+        let link = app.clipboard.take().unwrap();
+        assert!(link.starts_with("rdf-tui:"));
+
+        // 他處打開之：載入其文件，執行其查詢，恢復其過濾、排序與所選行
+        let mut other = App::new().unwrap();
+        other.handle_action(Action::Goto(link.clone())).unwrap();
+        assert_eq!(other.sources, [path.as_path()]);
+        assert_eq!(other.query.string, app.query.string);
+        assert_eq!(other.results.filter.as_ref().unwrap().text, "!/a>$/");
+        assert_eq!(
+            other.results.table().unwrap().sort,
+            app.results.table().unwrap().sort
+        );
+        assert_eq!(
+            (other.results.selected_row, other.results.selected_column),
+            (app.results.selected_row, Some(1))
+        );
+        assert_eq!(other.status.as_deref(), Some("Opened the link"));
+        // 已載入者不重載
+        other.handle_action(Action::Goto(link)).unwrap();
+        assert_eq!(other.sources, [path.as_path()]);
+
+        // 文件已不在、行已不足者報之
+        fs::write(&path, "<http://ex/a> <http://ex/p> 1 .\n").unwrap();
+        let link = Link {
+            sources: vec![path.display().to_string(), "/nowhere/gone.ttl".to_string()],
+            query: "SELECT * WHERE { ?s ?p ?o }".to_string(),
+            row: 4,
+            ..Link::default()
+        };
+        let mut other = App::new().unwrap();
+        other.handle_action(Action::Goto(link.encode())).unwrap();
+        assert_eq!(rows(&other), 1);
+        assert_eq!(
+            other.status.as_deref(),
+            Some(
+                "Opened the link; could not restore /nowhere/gone.ttl (not found), row 5 \
+                 (the results have 1)"
+            )
+        );
+        other
+            .handle_action(Action::Goto("rdf-tui:!!".to_string()))
+            .unwrap();
+        assert!(other.status.as_deref().unwrap().contains("damaged"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn a_query_next_to_the_data_runs_on_startup() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-front-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.ttl");
+        fs::write(&path, "<http://ex/a> <http://ex/p> 1, 2 .\n").unwrap();
+        fs::write(dir.join(".rdf-tui.rq"), "SELECT ?o WHERE { ?s ?p ?o }\n").unwrap();
+        let start = |enabled: bool| {
+            let mut app = App::new().unwrap();
+            app.set_startup_query(enabled);
+            app.open(path.clone());
+            app.offer_session();
+            app.wait_for_query();
+            app
+        };
+        let mut app = start(true);
+        assert_eq!(app.query.string, "SELECT ?o WHERE { ?s ?p ?o }");
+        assert_eq!(rows(&app), 2);
+        assert_eq!(
+            app.status,
+            Some(format!(
+                "Startup query from {}",
+                dir.join(".rdf-tui.rq").display()
+            ))
+        );
+        // 僅一次
+        app.query.set("ASK {}".to_string());
+        app.offer_session();
+        assert_eq!(app.query.string, "ASK {}");
+
+        assert_eq!(start(false).query.string, Query::new().string);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn the_default_query_suits_the_data() {
+        let start = |data: &str, configure: &dyn Fn(&mut App)| {
+            let mut app = App::new().unwrap();
+            configure(&mut app);
+            app.load_data(data, RdfFormat::TriG);
+            app.offer_session();
+            app
+        };
+        let data = "<http://ex/a> <http://ex/p> 1, 2, 3 .";
+        let app = start(data, &|_| {});
+        assert_eq!(app.query.string, Query::new().string);
+        assert!(!app.query.is_dirty());
+
+        let app = start(data, &|app| app.config.large_store = 2);
+        assert_eq!(
+            app.query.string,
+            format!("{} LIMIT 100", Query::new().string)
+        );
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Default query first 100 of 3 triples")
+        );
+
+        let app = start("<http://ex/g> { <http://ex/a> <http://ex/p> 1 }", &|_| {});
+        assert!(app.query.string.contains("GRAPH ?g"));
+        assert!(!app.query.string.contains("LIMIT"));
+
+        let app = start(data, &|app| {
+            app.config.default_query = Some("ASK {}".to_string());
+        });
+        assert_eq!(app.query.string, "ASK {}");
+
+        // `--query` 所設者不替
+        let app = start(data, &|app| {
+            app.config.large_store = 2;
+            app.set_query(Query::new().string);
+        });
+        assert_eq!(app.query.string, Query::new().string);
+    }
+}
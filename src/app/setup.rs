@@ -0,0 +1,192 @@
+//! 嵌入者與 `main` 所用之設置：語言、上限、導出、檢查點、路徑等，以及 [`Config`] 之應用。
+//! 配置文件之解析見 `crate::config`。
+
+use super::{App, Mode, ResultSet, ResultTable, Source};
+use crate::{
+    action::Values,
+    checkpoint::{Checkpoints, DEFAULT_LIMIT},
+    config::Config,
+    locale::Language,
+    metrics::Metrics,
+    overwrite::Overwrite,
+    paths::Paths,
+    renderer::CellRenderer,
+    script::{Playback, Step},
+    server::Server,
+    session::Restore,
+};
+use std::{path::PathBuf, time::Instant};
+
+impl App {
+    /// 設置標籤之首選語言。
+    pub fn set_lang(&mut self, lang: Option<String>) {
+        self.lang = lang;
+        self.labels.clear();
+    }
+
+    /// 以線性之文字輸出，為讀屏者。
+    pub fn set_linear(&mut self, linear: bool) {
+        self.linear = linear;
+    }
+
+    /// 設置繪製一部件時之 panic 是否止於該部件。
+    pub fn set_contain_panics(&mut self, contain: bool) {
+        self.contain_panics = contain;
+    }
+
+    /// 設置界面之語言。
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    /// 設置載入後追蹤 `owl:imports` 之層數。`None` 則不追蹤。
+    pub fn set_follow_imports(&mut self, depth: Option<usize>) {
+        self.follow_imports = depth;
+    }
+
+    /// 註冊某數據類型之字面量之顯示，先於內置者及已註冊者問之。僅改結果表與詳情中之顯示。
+    pub fn register_renderer(&mut self, datatype: &str, renderer: impl CellRenderer + 'static) {
+        self.renderers.register(datatype, Box::new(renderer));
+        if let Some(table) = self.results.table() {
+            table.display.borrow_mut().clear();
+        }
+    }
+
+    /// 應用配置。命令行參數宜於其後設置，以覆蓋之。
+    pub fn configure(&mut self, config: Config) {
+        self.limit = config.max_rows;
+        self.lang.clone_from(&config.lang);
+        self.theme = config.theme();
+        self.config = config;
+        self.results.current = None;
+        if let Some(preset) = self.preset(&self.config.layout) {
+            self.layout = preset;
+        }
+    }
+
+    /// 顯示警告。
+    pub fn warn(&mut self, warning: String) {
+        self.warning = Some(warning);
+    }
+
+    /// 以現成之表為當前查詢之結果，不經存儲。供基準測試等。
+    #[doc(hidden)]
+    pub fn show_table(&mut self, query: impl Into<String>, table: ResultTable) {
+        self.query.set(query.into());
+        self.results.selected_row = 0;
+        self.results.selected_column = None;
+        self.results.on_header = false;
+        self.results.on_footer = false;
+        self.results.current = Some(ResultSet {
+            query: self.query.as_str().to_string(),
+            table: Some(table),
+            error: None,
+            remote_requests: 0,
+            guarded: None,
+            source: Source::Query,
+            generation: self.generation,
+        });
+    }
+
+    /// 播放腳本。播完後 `then_interactive` 則交還鍵盤，否則退出。
+    pub fn play(&mut self, steps: Vec<Step>, then_interactive: bool) {
+        self.script = Some(Playback::new(steps, then_interactive));
+    }
+
+    /// 設置導出之行數上限。`None` 為不限。
+    pub fn set_export_limit(&mut self, limit: Option<usize>) {
+        self.exports.limit = limit;
+    }
+
+    /// 設置並行載入之線程數。為 1 則逐一載入，便於調試。
+    pub fn set_jobs(&mut self, jobs: usize) {
+        self.jobs = jobs.max(1);
+    }
+
+    /// 設置每 URL 至多下載之字節數。
+    pub fn set_max_download(&mut self, max: Option<u64>) {
+        self.max_download = max;
+    }
+
+    /// 設置 N3 中無法存入之構造是否使載入失敗。
+    pub fn set_strict_n3(&mut self, strict: bool) {
+        self.strict_n3 = strict;
+    }
+
+    /// 設置導出 RDF 時是否聲明並用前綴。
+    pub fn set_prefixed_exports(&mut self, prefixed: bool) {
+        self.exports.prefixed = prefixed;
+    }
+
+    /// 設置是否禁止改動存儲。
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// 改值、刪去、清空與恢復前，寫檢查點於 `dir`。`limit` 為所存四元組數之上限。
+    pub fn set_checkpoints(&mut self, dir: PathBuf, limit: Option<usize>) {
+        self.checkpoints = Some(Checkpoints {
+            dir,
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
+        });
+    }
+
+    /// 設置配置與狀態之所在，見 `--config-dir` 與 `--state-dir`。須先於文件與最近文件之載入。
+    pub fn set_paths(&mut self, paths: Paths) {
+        self.paths = paths;
+    }
+
+    /// 設置首批文件載入後是否執行數據集之啓動查詢：配置中為其文件所設者，或其旁之
+    /// `.rdf-tui.rq`。
+    pub fn set_startup_query(&mut self, enabled: bool) {
+        self.startup_query = enabled;
+    }
+
+    /// 設置是否為無 LIMIT 而或返回巨量之行之查詢加 LIMIT。
+    pub fn set_guard(&mut self, enabled: bool) {
+        self.guard = enabled;
+    }
+
+    /// 設置 CSV、TSV 與表格導出原樣之項或平值。
+    pub fn set_values(&mut self, values: Values) {
+        self.exports.values = values;
+    }
+
+    /// 設置導出之文件已存在時覆蓋、詢問或報錯。
+    pub fn set_overwrite(&mut self, overwrite: Overwrite) {
+        self.exports.overwrite = overwrite;
+    }
+
+    /// 設置首批文件載入後是否恢復上次之會話。
+    pub fn set_restore(&mut self, restore: Restore) {
+        self.restore = restore;
+    }
+
+    /// 設置初始之模式。
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    /// 首批文件載入後即執行查詢，不待按鍵。
+    pub fn run_on_start(&mut self) {
+        self.run_on_start = true;
+    }
+
+    /// 設置是否允許 SERVICE 聯邦查詢。
+    /// 於此地址開 SPARQL 端點，供他人查詢所載之數據。
+    pub fn serve(&mut self, address: &str) -> anyhow::Result<()> {
+        self.server = Some(Server::spawn(&self.store, address)?);
+        Ok(())
+    }
+
+    pub fn set_federated(&mut self, federated: bool) {
+        self.federated = federated;
+        self.results.current = None;
+    }
+
+    /// 啓動時即收調試之度量並示其浮層，以含首批之幀。
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.debug = enabled;
+        self.metrics = enabled.then(|| Metrics::new(Instant::now()));
+    }
+}
@@ -0,0 +1,3733 @@
+//! 與存儲之往來：於後臺線程執行查詢並收其行，載入、下載、重載文件，改值、撤銷與檢查點，
+//! 標籤、計數與入邊之解析，監視與刷新。線程本身見 `crate::worker` 與 `crate::backend`。
+
+use super::{
+    tab_name, App, Confirmation, Loading, Mode, Popup, Progress, Query, ResultSet, ResultTable,
+    Source, NO_DATA, WATCH_SKIPPED,
+};
+use crate::{
+    action::{Action, GraphOp},
+    backend::{self, Command, Event},
+    checkpoint::Outcome,
+    count::{describe, Count, Position},
+    dataset::Dataset,
+    display::{abbreviate_count, compact_iri, format_bytes, group_digits},
+    download::{self, Download},
+    dupes::Dupes,
+    edit::{graphs_of, Update},
+    functional::{self, Functional},
+    generation::Cached,
+    graphs::{describe_graph, graph_len, GraphJob},
+    guard, header,
+    hierarchy::Hierarchy,
+    imports::ImportReport,
+    incoming::{self, Incoming},
+    label::{self, Prefetch},
+    memory::{self, Usage},
+    neighborhood::Neighborhood,
+    pattern::{parse_predicate, Pattern},
+    refresh::{self, Refresh},
+    rename,
+    saved::{read_results, results_format},
+    selection::{follow_column, Anchor, Restored},
+    solutions::{self, QueryOptions, SolutionStream},
+    watch::{self, describe_interval, Diff, Watch},
+    worker::{Caps, Job, Message, TermRow, Truncation, Worker},
+};
+use anyhow::Context;
+use oxigraph::{
+    io::RdfFormat,
+    model::{
+        vocab::{rdf, rdfs},
+        GraphName, Literal, NamedNode, Quad, Subject, Term, TermRef,
+    },
+    sparql::results::QueryResultsFormat,
+};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{absolute, Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+impl App {
+    /// 執行涉及存儲之操作：查詢、載入、修改、檢查點與數據集。餘者原樣返回，交下一部分。
+    pub(super) fn update_store(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::CancelQuery => self.cancel_query(),
+            Action::Watch(Some(interval)) => {
+                if self.imported.is_some() || self.results.derived() {
+                    self.status = Some("Only query results can be watched".to_string());
+                } else {
+                    self.watch = Some(Watch::new(self.query.string.clone(), interval));
+                    self.watch_run = None;
+                    self.status = Some(format!(
+                        "Watching the query every {}",
+                        describe_interval(interval)
+                    ));
+                }
+            }
+            Action::Watch(None) => {
+                self.watch_run = None;
+                self.status = Some(match self.watch.take() {
+                    Some(_) => "Stopped watching".to_string(),
+                    None => "Not watching".to_string(),
+                });
+            }
+            Action::Open(path) if download::is_url(&path) => {
+                self.pending.push_back(path);
+            }
+            Action::Open(path) => {
+                // 重載同一文件則空白節點重複，故只提議全部重載
+                let absolute = absolute(&path).unwrap_or(path);
+                if self.sources.contains(&absolute) || self.pending.contains(&absolute) {
+                    self.confirmation = Some(Confirmation {
+                        message: format!(
+                            "{} is already loaded. Reload all files? (y/n)",
+                            absolute.display()
+                        ),
+                        action: Action::Reload,
+                        preview: None,
+                        alternative: None,
+                    });
+                } else {
+                    self.pending.push_back(absolute);
+                }
+            }
+            Action::LoadInMemory(paths) => self.open_all(paths),
+            Action::Reload if self.loading.is_some() => {
+                self.status = Some("Wait for the files to finish loading".to_string());
+            }
+            Action::Reload => self.reload()?,
+            Action::Clear(_) | Action::ForceClear(_) if self.read_only => {
+                self.status = Some("Read-only: the store cannot be cleared".to_string());
+            }
+            Action::Clear(graph) => {
+                let quads = match &graph {
+                    Some(iri) => self
+                        .store
+                        .quads_for_pattern(None, None, None, Some(graph_name(iri)?.as_ref().into()))
+                        .count(),
+                    None => self.store.len()?,
+                };
+                let target = match &graph {
+                    Some(iri) => format!("graph <{iri}>"),
+                    None => "all data".to_string(),
+                };
+                self.confirmation = Some(Confirmation {
+                    message: format!("Remove {quads} quads ({target})? (y/n)"),
+                    action: Action::ForceClear(graph),
+                    preview: None,
+                    alternative: None,
+                });
+            }
+            Action::GraphOp(_) | Action::ForceGraphOp(_) if self.read_only => {
+                self.status = Some("Read-only: graphs cannot be changed".to_string());
+            }
+            Action::GraphOp(_) | Action::ForceGraphOp(_) if self.graph_job.is_some() => {
+                self.status = Some("Wait for the graph operation to finish".to_string());
+            }
+            Action::GraphOp(op) => self.propose_graph_op(op)?,
+            Action::ForceGraphOp(op) => {
+                if self.checkpoint(Some(&op.affected()), Action::ForceGraphOp(op.clone()))? {
+                    log::info!("graph operation started op={op:?}");
+                    self.graph_job = Some(GraphJob::spawn(&self.store, op));
+                }
+            }
+            Action::ForceClear(graph) => {
+                let graphs = match &graph {
+                    Some(iri) => Some(vec![GraphName::from(graph_name(iri)?)]),
+                    None => None,
+                };
+                if self.checkpoint(graphs.as_deref(), Action::ForceClear(graph.clone()))? {
+                    self.clear(graph)?;
+                    self.note_checkpoint();
+                }
+            }
+            // 不限則結果或大至耗盡內存
+            Action::SetLimit(None) if self.limit.is_some() => {
+                self.confirmation = Some(Confirmation {
+                    message: self
+                        .tr("Show all rows? Large results can exhaust memory (y/n)")
+                        .to_string(),
+                    action: Action::ForceSetLimit(None),
+                    preview: None,
+                    alternative: None,
+                });
+            }
+            Action::SetDataset(None) => self.status = Some(self.dataset.describe()),
+            Action::SetStableOrder(None) => {
+                self.status = Some(self.describe_stable_order().to_string())
+            }
+            Action::SetStableOrder(Some(stable)) => self.set_stable_order(stable),
+            Action::ToggleStableOrder => self.set_stable_order(!self.config.stable_order),
+            Action::SetDataset(Some(dataset)) => self.set_dataset(dataset),
+            Action::ToggleUnion => self.set_dataset(match self.dataset {
+                Dataset::Default => Dataset::Union,
+                _ => Dataset::Default,
+            }),
+            Action::SetLimit(limit) => {
+                let changed = limit != self.limit;
+                self.limit = limit;
+                self.status = Some(describe_limit(limit));
+                if changed && self.results.current.is_some() {
+                    self.confirmation = Some(Confirmation {
+                        message: format!("{}. Re-run the query? (y/n)", describe_limit(limit)),
+                        action: Action::RunQuery,
+                        preview: None,
+                        alternative: None,
+                    });
+                }
+            }
+            Action::ForceSetLimit(limit) => {
+                self.limit = limit;
+                self.status = Some(describe_limit(limit));
+                self.run_query();
+            }
+            Action::ShowStats => {
+                let quads = self.store.len()?;
+                let graphs = self.store.named_graphs().count();
+                self.status = Some(format!(
+                    "{quads} quads, {graphs} named graphs, {} files",
+                    self.sources.len()
+                ));
+            }
+            Action::ShowIncoming => self.count_incoming(),
+            Action::ListIncoming(incoming) => self.list_incoming(incoming),
+            Action::CountTerm => self.count_term(),
+            Action::ApplyUpdate => self.apply_update()?,
+            Action::DeleteMarked => self.propose_delete()?,
+            Action::ShowUpdates => self.popup = Some(Popup::Updates),
+            Action::UndoData(count) => self.propose_undo(count)?,
+            Action::RestoreCheckpoint(_) | Action::ForceRestoreCheckpoint(_) if self.read_only => {
+                self.status = Some("Read-only: the store cannot be changed".to_string());
+            }
+            Action::RestoreCheckpoint(n) => self.propose_restore(n)?,
+            Action::ForceRestoreCheckpoint(path) => self.restore_checkpoint(path)?,
+            Action::CapRows(rows) => {
+                self.limit = Some(rows);
+                self.held_rows = None;
+                if let Some(worker) = &self.running {
+                    worker.cancel();
+                }
+                // 已到之行留之，如因行數上限而截斷
+                if self.running.is_some() {
+                    self.receive(Message::Finished {
+                        truncated: Some(Truncation::Rows(rows)),
+                    });
+                }
+                self.status = Some(format!(
+                    "{} to stay within the memory budget",
+                    describe_limit(self.limit)
+                ));
+            }
+            Action::FindDupes {
+                predicate,
+                normalized,
+            } => {
+                let predicate = match predicate {
+                    Some(text) => parse_predicate(&text, &self.config.prefixes),
+                    None => Ok(rdfs::LABEL.into_owned()),
+                };
+                match predicate {
+                    Ok(predicate) => self.find_dupes(Dupes {
+                        predicate,
+                        normalized,
+                    }),
+                    Err(error) => self.status = Some(format!("Invalid predicate: {error}")),
+                }
+            }
+            Action::CheckFunctional(Some(text)) => {
+                match parse_predicate(&text, &self.config.prefixes) {
+                    Ok(predicate) => self.check_functional(Functional { predicate }),
+                    Err(error) => self.status = Some(format!("Invalid predicate: {error}")),
+                }
+            }
+            Action::CheckFunctional(None) => {
+                if functional::declared(&self.store)? {
+                    self.query.set(functional::summary_query());
+                    self.mode = Mode::Browse;
+                } else {
+                    self.status = Some(
+                        "No owl:FunctionalProperty is declared; name one with \
+                         :check functional <predicate>"
+                            .to_string(),
+                    );
+                }
+            }
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 等待並行載入或重新載入完成。
+    pub(super) fn wait_for_loading(&mut self) {
+        while self.loading.is_some() {
+            match self.backend.recv() {
+                Some(event) => self.store_event(event),
+                None => self.loading = None,
+            }
+        }
+    }
+
+    /// 存儲線程之設置，依當前之選項。
+    fn backend_settings(&self) -> backend::Settings {
+        backend::Settings {
+            federated: self.federated,
+            remote_timeout: self.config.timeout,
+            read_only: self.read_only,
+            strict_n3: self.strict_n3,
+            jobs: self.jobs,
+            ..backend::Settings::default()
+        }
+    }
+
+    /// 依當前之選項送命令予存儲線程，返回其號。
+    fn send_store(&mut self, command: impl FnOnce(backend::Id) -> Command) -> backend::Id {
+        let id = self.next_store_id;
+        self.next_store_id += 1;
+        self.backend
+            .send(Command::Configure(self.backend_settings()));
+        self.backend.send(command(id));
+        id
+    }
+
+    /// 送命令予存儲線程並待其末一事件。其間所到之他事件照常處理。
+    fn call_store(&mut self, command: impl FnOnce(backend::Id) -> Command) -> Event {
+        let id = self.send_store(command);
+        loop {
+            match self.backend.recv() {
+                Some(event) if event.id() == id => return event,
+                Some(event) => self.store_event(event),
+                None => {
+                    return Event::Failed {
+                        id,
+                        error: "The store thread has stopped".to_string(),
+                    }
+                }
+            }
+        }
+    }
+
+    /// 不經界面於當前頁之存儲執行查詢，逐個取其解。默認圖同於界面之 `:dataset`；行數上限、
+    /// 時限與取消依 `options`，不從配置。未取者不佔內存，丟棄即止。
+    pub fn query_iter(
+        &self,
+        sparql: &str,
+        options: QueryOptions,
+    ) -> anyhow::Result<SolutionStream> {
+        let mut query = self.prepared.get(sparql, None)?;
+        self.dataset.apply(&mut query);
+        solutions::run(&self.store, query, options, Default::default())
+    }
+
+    /// 某行所示之三元組。詳情中主語取自其查詢，餘者取自 `?s ?p ?o` 三列。
+    pub(super) fn statement(&self, row: usize) -> Result<(Subject, NamedNode, Term), &'static str> {
+        let table = self.results.table().ok_or("No results")?;
+        let row = table.rows.get(row).ok_or("No row selected")?;
+        let cell = |names: &[&str]| {
+            let column = table
+                .variables
+                .iter()
+                .position(|variable| names.contains(&variable.as_str()))?;
+            row[column].as_deref()
+        };
+        let details = self.details.as_ref().filter(|(query, _)| {
+            self.results
+                .current
+                .as_ref()
+                .is_some_and(|r| r.query == *query)
+        });
+        let subject = match details {
+            Some((_, subject)) => Some(subject),
+            None => cell(&["s", "subject"]),
+        };
+        let (Some(subject), Some(Term::NamedNode(predicate)), Some(object)) =
+            (subject, cell(&["p", "predicate"]), cell(&["o", "object"]))
+        else {
+            return Err("Select a row with a subject, a predicate and an object");
+        };
+        // 更新中不能指名空白節點
+        if has_blank_node(subject.as_ref()) || has_blank_node(object.as_ref()) {
+            return Err("Triples with blank nodes cannot be changed by an update");
+        }
+        let subject = Subject::try_from(subject.clone()).map_err(|_| "Not a subject")?;
+        Ok((subject, predicate.clone(), object.clone()))
+    }
+
+    /// 以輸入行之值生成更新，待確認。
+    pub(super) fn propose_edit(&mut self) {
+        let Some(edit) = self.edit.take() else {
+            return;
+        };
+        let replacement = edit.literal(&self.edit_line.input);
+        if replacement == edit.object {
+            self.status = Some("Value unchanged".to_string());
+            return;
+        }
+        let compact = |iri: &str| {
+            compact_iri(iri, &self.config.prefixes).unwrap_or_else(|| format!("<{iri}>"))
+        };
+        let subject = match &edit.subject {
+            Subject::NamedNode(iri) => compact(iri.as_str()),
+            subject => subject.to_string(),
+        };
+        let summary = format!(
+            "Changed {} of {subject} to {}",
+            compact(edit.predicate.as_str()),
+            Literal::new_simple_literal(replacement.value())
+        );
+        self.propose_update(edit.update(&replacement, summary));
+    }
+
+    /// 刪去所標記之行之三元組，未標記則為所選行。
+    pub(super) fn propose_delete(&mut self) -> anyhow::Result<()> {
+        if self.read_only {
+            self.status = Some("Read-only: triples cannot be deleted".to_string());
+            return Ok(());
+        }
+        let Some(table) = self.results.table() else {
+            self.status = Some("No results".to_string());
+            return Ok(());
+        };
+        let rows: Vec<usize> = if table.marked.is_empty() {
+            vec![self.results.selected_row]
+        } else {
+            table.marked.iter().copied().collect()
+        };
+        let mut quads = vec![];
+        let mut missing = 0;
+        for &row in &rows {
+            let (subject, predicate, object) = match self.statement(row) {
+                Ok(statement) => statement,
+                Err(error) => {
+                    self.status = Some(error.to_string());
+                    return Ok(());
+                }
+            };
+            let graphs = graphs_of(&self.store, &subject, &predicate, &object)?;
+            missing += usize::from(graphs.is_empty());
+            quads.extend(
+                graphs.into_iter().map(|graph| {
+                    Quad::new(subject.clone(), predicate.clone(), object.clone(), graph)
+                }),
+            );
+        }
+        if missing > 0 {
+            self.status = Some(if rows.len() == 1 {
+                "Triple no longer present".to_string()
+            } else {
+                format!("{missing} of the marked triples are no longer present")
+            });
+            return Ok(());
+        }
+        let graphs: HashSet<&GraphName> = quads.iter().map(|quad| &quad.graph_name).collect();
+        let triples = if rows.len() == 1 { "triple" } else { "triples" };
+        let summary = match graphs.len() {
+            1 => format!("Deleted {} {triples}", rows.len()),
+            graphs => format!("Deleted {} {triples} across {graphs} graphs", rows.len()),
+        };
+        self.propose_update(Update::delete(quads, summary));
+        Ok(())
+    }
+
+    /// 以浮窗示更新之文本，待確認。
+    pub(super) fn propose_update(&mut self, update: Update) {
+        log::debug!("update proposed text={:?}", update.text);
+        self.confirmation = Some(Confirmation {
+            message: format!("{}? Run this update (y/n)", update.summary),
+            action: Action::ApplyUpdate,
+            preview: Some(update.text.clone()),
+            alternative: None,
+        });
+        self.pending_update = Some(update);
+    }
+
+    /// 請確認圖之操作，示所涉之四元組數。
+    pub(super) fn propose_graph_op(&mut self, op: GraphOp) -> anyhow::Result<()> {
+        let missing = |graph: &GraphName| -> anyhow::Result<bool> {
+            Ok(match graph {
+                GraphName::NamedNode(graph) => !self.store.contains_named_graph(graph)?,
+                _ => false,
+            })
+        };
+        if missing(op.source())? {
+            self.status = Some(format!("No graph {}", describe_graph(op.source())));
+            return Ok(());
+        }
+        let quads = graph_len(&self.store, op.source());
+        let replacing = |target: &GraphName| match graph_len(&self.store, target) {
+            0 => String::new(),
+            n => format!(", replacing its {}", group_digits(n)),
+        };
+        let message = match &op {
+            GraphOp::Copy(source, target) | GraphOp::Move(source, target) if source == target => {
+                self.status = Some("The source and target are the same graph".to_string());
+                return Ok(());
+            }
+            GraphOp::Copy(source, target) => format!(
+                "Copy {} quads from {} to {}{}? (y/n)",
+                group_digits(quads),
+                describe_graph(source),
+                describe_graph(target),
+                replacing(target)
+            ),
+            GraphOp::Move(source, target) => format!(
+                "Move {} quads from {} to {}{}? (y/n)",
+                group_digits(quads),
+                describe_graph(source),
+                describe_graph(target),
+                replacing(target)
+            ),
+            GraphOp::Drop(graph) => {
+                format!(
+                    "Drop {} and its {} quads? (y/n)",
+                    describe_graph(graph),
+                    group_digits(quads)
+                )
+            }
+        };
+        self.confirmation = Some(Confirmation {
+            message,
+            action: Action::ForceGraphOp(op),
+            preview: None,
+            alternative: None,
+        });
+        Ok(())
+    }
+
+    /// 收取後台圖操作之結果：記其來源，刷新圖之列表與結果。
+    pub(super) fn poll_graph_job(&mut self) {
+        let Some(result) = self.graph_job.as_ref().and_then(GraphJob::poll) else {
+            return;
+        };
+        let job = self.graph_job.take().unwrap();
+        if let Err(error) = result {
+            log::error!("graph operation failed op={:?} error={error}", job.op);
+            self.status = Some(format!("Graph operation failed: {error}"));
+            return;
+        }
+        log::info!("graph operation finished op={:?}", job.op);
+        let name = |graph: &GraphName| match graph {
+            GraphName::DefaultGraph => None,
+            graph => Some(graph.to_string()),
+        };
+        // 列表之查詢內聯各圖，故須重生之
+        let listing = self.query.string == self.provenance.report_query();
+        let quads = group_digits(job.total);
+        self.status = Some(match &job.op {
+            GraphOp::Copy(source, target) => {
+                self.provenance
+                    .copy_graph(name(source).as_deref(), name(target).as_deref());
+                format!(
+                    "Copied {quads} quads from {} to {}",
+                    describe_graph(source),
+                    describe_graph(target)
+                )
+            }
+            GraphOp::Move(source, target) => {
+                self.provenance
+                    .copy_graph(name(source).as_deref(), name(target).as_deref());
+                self.provenance.remove_graph(name(source).as_deref());
+                format!(
+                    "Moved {quads} quads from {} to {}",
+                    describe_graph(source),
+                    describe_graph(target)
+                )
+            }
+            GraphOp::Drop(graph) => {
+                self.provenance.remove_graph(name(graph).as_deref());
+                format!("Dropped {} ({quads} quads)", describe_graph(graph))
+            }
+        });
+        if listing {
+            self.query.set(self.provenance.report_query());
+        }
+        // 撤銷所記之修改已不合各圖
+        self.undo.clear();
+        self.note_checkpoint();
+        self.store_changed();
+    }
+
+    /// 撤銷最近 `count` 次修改，`None` 為全部。
+    pub(super) fn propose_undo(&mut self, count: Option<usize>) -> anyhow::Result<()> {
+        if self.read_only {
+            self.status = Some("Read-only: the store cannot be changed".to_string());
+            return Ok(());
+        }
+        if self.undo.is_empty() {
+            self.status = Some("Nothing to undo".to_string());
+            return Ok(());
+        }
+        let count = count.unwrap_or(usize::MAX).min(self.undo.len());
+        let changes = &self.undo[self.undo.len() - count..];
+        let summary = match changes {
+            [change] => format!("Undo: {}", change.summary),
+            _ => format!("Undo the last {count} changes"),
+        };
+        match Update::undo(changes, &self.store, summary)? {
+            Some(update) => self.propose_update(update),
+            None => {
+                self.undo.truncate(self.undo.len() - count);
+                self.status = Some("The store already holds the earlier data".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// 執行已確認之更新，記之，再重新查詢。
+    pub(super) fn apply_update(&mut self) -> anyhow::Result<()> {
+        let Some(mut update) = self.pending_update.take() else {
+            self.status = Some("No update to run".to_string());
+            return Ok(());
+        };
+        if self.read_only {
+            self.status = Some("Read-only: the store cannot be changed".to_string());
+        } else if !update.present(&self.store)? {
+            self.status = Some("Triple no longer present".to_string());
+        } else {
+            let graphs: Vec<GraphName> = update
+                .deleted
+                .iter()
+                .chain(&update.inserted)
+                .map(|quad| quad.graph_name.clone())
+                .collect();
+            if !self.checkpoint(Some(&graphs), Action::ApplyUpdate)? {
+                self.pending_update = Some(update);
+                return Ok(());
+            }
+            update.capture(&self.store)?;
+            let text = update.text.clone();
+            if let Event::Failed { error, .. } = self.call_store(|id| Command::Update { id, text })
+            {
+                anyhow::bail!(error);
+            }
+            log::info!("update ran summary={:?}", update.summary);
+            match update.undoes {
+                0 => self.undo.push(update.clone()),
+                undoes => self.undo.truncate(self.undo.len().saturating_sub(undoes)),
+            }
+            self.status = Some(update.summary.clone());
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            if self.recent_updates.len() == MAX_UPDATES {
+                self.recent_updates.pop_front();
+            }
+            self.recent_updates.push_back((now, update));
+            self.note_checkpoint();
+            self.store_changed();
+        }
+        Ok(())
+    }
+
+    /// 破壞性操作前寫檢查點，`graphs` 為所涉之圖，`None` 為全部。
+    /// 逾上限則請確認後不寫而行 `action`，返回否；未設目錄或已寫則返回是。
+    pub(super) fn checkpoint(
+        &mut self,
+        graphs: Option<&[GraphName]>,
+        action: Action,
+    ) -> anyhow::Result<bool> {
+        let Some(checkpoints) = &self.checkpoints else {
+            return Ok(true);
+        };
+        if std::mem::take(&mut self.unchecked) {
+            log::warn!("checkpoint skipped action={action:?}");
+            return Ok(true);
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        match checkpoints.take(&self.store, graphs, now)? {
+            Outcome::Written(path) => {
+                log::info!("checkpoint written path={}", path.display());
+                self.checkpoint = Some(path);
+                Ok(true)
+            }
+            Outcome::TooLarge(scope) => {
+                self.confirmation = Some(Confirmation {
+                    message: format!(
+                        "No checkpoint: {} has over {} quads. Go on without one? (y/n)",
+                        scope.describe(),
+                        group_digits(checkpoints.limit)
+                    ),
+                    action: Action::WithoutCheckpoint(Box::new(action)),
+                    preview: None,
+                    alternative: None,
+                });
+                Ok(false)
+            }
+        }
+    }
+
+    /// 狀態欄附註方才所寫之檢查點。
+    pub(super) fn note_checkpoint(&mut self) {
+        if let Some(path) = self.checkpoint.take() {
+            let status = self.status.take().unwrap_or_default();
+            self.status = Some(format!("{status} · checkpoint {}", path.display()));
+        }
+    }
+
+    /// 無參數則列出檢查點，否則請確認恢復其第 `n`。
+    pub(super) fn propose_restore(&mut self, n: Option<usize>) -> anyhow::Result<()> {
+        let Some(checkpoints) = &self.checkpoints else {
+            self.status = Some("Checkpoints are off (start with --checkpoint-dir)".to_string());
+            return Ok(());
+        };
+        let list = checkpoints.list()?;
+        if list.is_empty() {
+            self.status = Some(format!("No checkpoints in {}", checkpoints.dir.display()));
+            return Ok(());
+        }
+        let Some(n) = n else {
+            self.popup = Some(Popup::Checkpoints(list));
+            return Ok(());
+        };
+        if self.read_only {
+            self.status = Some("Read-only: the store cannot be changed".to_string());
+            return Ok(());
+        }
+        let Some(checkpoint) = list.into_iter().nth(n - 1) else {
+            self.status = Some(format!(
+                "No checkpoint {n} (:restore-checkpoint lists them)"
+            ));
+            return Ok(());
+        };
+        self.confirmation = Some(Confirmation {
+            message: format!(
+                "Replace {} with checkpoint {}? (y/n)",
+                checkpoint.scope.describe(),
+                checkpoint.name()
+            ),
+            action: Action::ForceRestoreCheckpoint(checkpoint.path),
+            preview: None,
+            alternative: None,
+        });
+        Ok(())
+    }
+
+    /// 以檢查點替換其範圍。替換前亦寫檢查點。
+    pub(super) fn restore_checkpoint(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        let Some(checkpoints) = &self.checkpoints else {
+            return Ok(());
+        };
+        let Some(checkpoint) = checkpoints
+            .list()?
+            .into_iter()
+            .find(|checkpoint| checkpoint.path == path)
+        else {
+            self.status = Some(format!("Checkpoint {} is gone", path.display()));
+            return Ok(());
+        };
+        if !self.checkpoint(checkpoint.graphs(), Action::ForceRestoreCheckpoint(path))? {
+            return Ok(());
+        }
+        let (path, graphs) = (
+            checkpoint.path.clone(),
+            checkpoint.graphs().map(<[_]>::to_vec),
+        );
+        if let Event::Failed { error, .. } =
+            self.call_store(|id| Command::Replace { id, path, graphs })
+        {
+            anyhow::bail!(error);
+        }
+        log::info!("checkpoint restored path={}", checkpoint.path.display());
+        // 撤銷所記之修改已不合存儲
+        self.undo.clear();
+        self.status = Some(format!(
+            "Restored {} from {}",
+            checkpoint.scope.describe(),
+            checkpoint.name()
+        ));
+        self.note_checkpoint();
+        self.store_changed();
+        Ok(())
+    }
+
+    /// 於後台依當前之存儲重建層級樹，關係不變。前次未畢者棄之。
+    pub(super) fn refresh_hierarchy(&mut self) {
+        let Some(hierarchy) = &self.hierarchy else {
+            return;
+        };
+        let (relation, lang) = (hierarchy.relation(), self.lang.clone());
+        self.hierarchy_refresh = Some(Refresh::spawn(&self.store, self.generation, move |store| {
+            Hierarchy::new(store, relation, lang)
+        }));
+    }
+
+    /// 於後台依當前之存儲重建鄰域樹，中心不變。前次未畢者棄之。
+    pub(super) fn refresh_neighborhood(&mut self) {
+        let Some(neighborhood) = &self.neighborhood else {
+            return;
+        };
+        let (center, lang) = (neighborhood.center().clone(), self.lang.clone());
+        self.neighborhood_refresh =
+            Some(Refresh::spawn(&self.store, self.generation, move |store| {
+                Neighborhood::new(store, center, lang)
+            }));
+    }
+
+    /// 收取後台重建之樹。其間存儲又改者仍過時，依 [`Self::refresh_stale`] 再建。
+    pub(super) fn poll_refreshes(&mut self) {
+        if let Some(result) = self.hierarchy_refresh.as_ref().and_then(Refresh::poll) {
+            self.hierarchy_refresh = None;
+            self.hierarchy = self.refreshed(result);
+        }
+        if let Some(result) = self.neighborhood_refresh.as_ref().and_then(Refresh::poll) {
+            self.neighborhood_refresh = None;
+            self.neighborhood = self.refreshed(result);
+        }
+    }
+
+    /// 待後台重建之樹，供測試。
+    #[cfg(test)]
+    pub(super) fn wait_for_refreshes(&mut self) {
+        if let Some(refresh) = self.hierarchy_refresh.take() {
+            self.hierarchy = self.refreshed(refresh.wait());
+        }
+        if let Some(refresh) = self.neighborhood_refresh.take() {
+            self.neighborhood = self.refreshed(refresh.wait());
+        }
+    }
+
+    /// 重建之樹；出錯則報之，棄舊樹。
+    fn refreshed<T>(&mut self, result: anyhow::Result<Cached<T>>) -> Option<Cached<T>> {
+        result
+            .map_err(|error| self.status = Some(error.to_string()))
+            .ok()
+    }
+
+    /// 存儲之四元組數，每代只數一次。
+    pub(super) fn quads(&self) -> usize {
+        match self.quads.get() {
+            Some((generation, quads)) if generation == self.generation => quads,
+            _ => {
+                let quads = self.store.len().unwrap_or(0);
+                self.quads.set(Some((self.generation, quads)));
+                quads
+            }
+        }
+    }
+
+    /// 存儲有變。諸緩存記其所算之代數，過時者於下次取用時重算，見 [`Self::refresh_stale`]。
+    pub(super) fn store_changed(&mut self) {
+        self.generation.bump();
+        self.labels.clear();
+    }
+
+    /// 收取後台所解之標籤而重繪；再取可見之行及其前後 [`label::LOOKAHEAD`] 行中未解之
+    /// IRI，一批交後台。前批未畢則待之，故滾動再快亦僅一查詢在途。
+    pub(super) fn poll_labels(&mut self) {
+        if let Some(result) = self.label_fetch.as_ref().and_then(Prefetch::poll) {
+            self.label_fetch = None;
+            match result {
+                Ok(found) => log::debug!("labels found={found}"),
+                Err(error) => {
+                    log::warn!("labels failed error={error}");
+                    self.show_labels = false;
+                    self.status = Some(format!("Labels could not be resolved: {error}"));
+                }
+            }
+            if let Some(table) = self.results.table() {
+                table.display.borrow_mut().clear();
+            }
+        }
+        if !self.show_labels || self.label_fetch.is_some() {
+            return;
+        }
+        let (Some((first, last)), Some(table)) = (self.label_window.get(), self.results.table())
+        else {
+            return;
+        };
+        let end = (last + label::LOOKAHEAD).min(table.rows.len());
+        let start = first.saturating_sub(label::LOOKAHEAD).min(end);
+        let terms = table.rows[start..end].iter().flatten().flatten();
+        let missing = self.labels.missing(terms.map(|term| &**term), label::BATCH);
+        if !missing.is_empty() {
+            self.label_fetch = Some(Prefetch::spawn(
+                &self.store,
+                &self.labels,
+                missing,
+                self.lang.clone(),
+            ));
+        }
+    }
+
+    /// 先解諸行之所有標籤，供導出所示之單元格，不留「…」。
+    pub(super) fn resolve_labels(&self, rows: &[TermRow]) -> anyhow::Result<()> {
+        let terms = rows.iter().flatten().flatten().map(|term| &**term);
+        let missing = self.labels.missing(terms, usize::MAX);
+        for batch in missing.chunks(label::BATCH) {
+            self.labels
+                .resolve(&self.store, batch, self.lang.as_deref())
+                .map_err(anyhow::Error::msg)?;
+        }
+        Ok(())
+    }
+
+    /// 重算存儲修改以前所算者：重新查詢，或重跑三元組模式與重複值之查找；新結果完成後
+    /// 重算列統計。層級、鄰域諸樹於後台重建，唯存儲小或上次建之快者自動為之，見
+    /// [`refresh::automatic`]；餘者標為過時，舊樹照示，待按 `R`。
+    pub(super) fn refresh_stale(&mut self) {
+        let generation = self.generation;
+        if self.imported.is_none() {
+            if let Some(results) = self
+                .results
+                .current
+                .as_ref()
+                .filter(|r| r.generation != generation)
+            {
+                match &results.source {
+                    Source::Pattern(pattern) => self.run_pattern(pattern.clone()),
+                    Source::Dupes(dupes) => self.find_dupes(dupes.clone()),
+                    Source::Functional(functional) => self.check_functional(functional.clone()),
+                    Source::Query | Source::Diff(_) => self.invalidate_results(),
+                }
+            }
+        }
+        let automatic = |took| {
+            refresh::automatic(
+                self.quads(),
+                took,
+                self.config.auto_refresh_quads,
+                self.config.auto_refresh_time,
+            )
+        };
+        let hierarchy = self.hierarchy_refresh.is_none()
+            && self
+                .hierarchy
+                .as_ref()
+                .is_some_and(|h| h.is_stale(generation) && automatic(h.took()));
+        let neighborhood = self.neighborhood_refresh.is_none()
+            && self
+                .neighborhood
+                .as_ref()
+                .is_some_and(|n| n.is_stale(generation) && automatic(n.took()));
+        if hierarchy {
+            self.refresh_hierarchy();
+        }
+        if neighborhood {
+            self.refresh_neighborhood();
+        }
+        let fresh = self
+            .results
+            .table()
+            .is_some_and(|table| table.progress == Progress::Complete)
+            && self
+                .results
+                .current
+                .as_ref()
+                .is_some_and(|r| r.generation == generation);
+        if let Some(Popup::ColumnStats(at, column, ..)) = self.popup {
+            if at != generation && fresh {
+                self.popup = self.column_stats(column);
+            }
+        }
+    }
+
+    /// 數所選格之項之三元組。位置依列名，如 `?p`；不明則數其可居之各位置。
+    pub(super) fn count_term(&mut self) {
+        let Some(table) = self.results.table() else {
+            self.status = Some("No results".to_string());
+            return;
+        };
+        let Some(column) = self
+            .results
+            .selected_column
+            .filter(|&column| column < table.variables.len())
+        else {
+            self.status = Some("Select a cell with ←/→ first".to_string());
+            return;
+        };
+        let Some(term) = table
+            .rows
+            .get(self.results.selected_row)
+            .and_then(|row| row[column].as_deref())
+        else {
+            self.status = Some("Nothing to count in an unbound cell".to_string());
+            return;
+        };
+        let positions = match Position::of_column(table.variables[column].as_str()) {
+            Some(position) => vec![position],
+            None => Position::possible(term),
+        };
+        let term = term.clone();
+        let label = self.plain_cell(Some(&term));
+        self.status = Some(format!("Counting {label}…"));
+        // 前一計數丟棄即取消
+        self.count = Some(Count::spawn(
+            &self.store,
+            term,
+            positions,
+            label,
+            self.config.timeout,
+        ));
+    }
+
+    /// 數指向所選資源之三元組，畢則列之，見 [`App::poll_incoming`]。詳情中則為其資源。
+    pub(super) fn count_incoming(&mut self) {
+        let details = self
+            .details
+            .as_ref()
+            .filter(|(query, _)| {
+                self.results
+                    .current
+                    .as_ref()
+                    .is_some_and(|r| r.query == *query)
+            })
+            .map(|(_, term)| term.clone());
+        let Some(term) = details
+            .or_else(|| self.results.selected_resource())
+            .filter(|term| !has_blank_node(term.as_ref()))
+        else {
+            self.status = Some("No IRI or quoted triple in the selected row".to_string());
+            return;
+        };
+        let label = self.plain_cell(Some(&term));
+        self.status = Some(format!("Counting the triples pointing at {label}…"));
+        let count = Count::spawn(
+            &self.store,
+            term.clone(),
+            vec![Position::Object],
+            label,
+            self.config.timeout,
+        );
+        self.incoming_count = Some((count, term));
+    }
+
+    /// 入邊已數畢：少則盡列之，逾 `hub_threshold` 則問先載入若干、按謂語計數或作罷。
+    pub(super) fn poll_incoming(&mut self) {
+        let Some(result) = self
+            .incoming_count
+            .as_ref()
+            .and_then(|(count, _)| count.poll())
+        else {
+            return;
+        };
+        let (count, term) = self.incoming_count.take().unwrap();
+        let named = self.dataset != Dataset::Default;
+        let total = match result {
+            Ok(tallies) => tallies
+                .first()
+                .map_or(0, |tally| incoming::visible(tally, named)),
+            Err(error) => {
+                self.status = Some(format!("Counting {} {error}", count.label));
+                return;
+            }
+        };
+        let view = |view| Incoming {
+            term: term.clone(),
+            view,
+        };
+        if total <= self.config.hub_threshold {
+            self.list_incoming(view(incoming::View::All(None)));
+            return;
+        }
+        self.status = None;
+        self.confirmation = Some(Confirmation {
+            message: format!(
+                "{} triples point at {}: load the first {} (y), group them by predicate (g), or skip (n)?",
+                group_digits(total),
+                count.label,
+                group_digits(incoming::PREVIEW)
+            ),
+            action: Action::ListIncoming(view(incoming::View::All(Some(incoming::PREVIEW)))),
+            preview: None,
+            alternative: Some(('g', Action::ListIncoming(view(incoming::View::Grouped)))),
+        });
+    }
+
+    /// 執行入邊之查詢。按謂語計數者記之，以便展開。
+    pub(super) fn list_incoming(&mut self, incoming: Incoming) {
+        let query = incoming.query();
+        self.grouped_incoming = (incoming.view == incoming::View::Grouped)
+            .then(|| (query.clone(), incoming.term.clone()));
+        self.query.set(query);
+        self.mode = Mode::Browse;
+        self.run_query();
+    }
+
+    /// 按謂語計數之入邊中，展開所選之謂語。非此結果則返回否。
+    pub(super) fn expand_incoming(&mut self) -> bool {
+        let Some((_, term)) = self.grouped_incoming.as_ref().filter(|(query, _)| {
+            self.results
+                .current
+                .as_ref()
+                .is_some_and(|r| r.query == *query)
+        }) else {
+            return false;
+        };
+        let predicate = self
+            .results
+            .table()
+            .and_then(|table| table.rows.get(self.results.selected_row))
+            .and_then(|row| row.first()?.as_deref());
+        if let Some(Term::NamedNode(predicate)) = predicate {
+            let incoming = Incoming {
+                term: term.clone(),
+                view: incoming::View::Predicate(predicate.clone()),
+            };
+            self.list_incoming(incoming);
+        }
+        true
+    }
+
+    /// 收取後台計數之結果，示於狀態欄。
+    pub(super) fn poll_count(&mut self) {
+        let Some(result) = self.count.as_ref().and_then(Count::poll) else {
+            return;
+        };
+        let count = self.count.take().unwrap();
+        self.status = Some(match result {
+            Ok(tallies) => describe(&count.label, &tallies),
+            Err(error) => format!("Counting {} {error}", count.label),
+        });
+    }
+
+    pub(super) fn set_dataset(&mut self, dataset: Dataset) {
+        self.dataset = dataset;
+        let rerun = self
+            .results
+            .current
+            .as_ref()
+            .is_some_and(|results| matches!(results.source, Source::Query))
+            && self.imported.is_none();
+        if rerun {
+            self.run_query();
+        }
+        self.status = Some(self.dataset.describe());
+    }
+
+    /// 存儲中有命名圖否。
+    pub(super) fn has_named_graphs(&self) -> bool {
+        self.store.named_graphs().next().is_some()
+    }
+
+    pub(super) fn propose_rename(&mut self, from: &str, to: &str) {
+        self.mode = Mode::Query;
+        let (Some(from), Some(to)) = (rename::name(from), rename::name(to)) else {
+            self.status = Some(format!("Not a variable name: {from} or {to}"));
+            return;
+        };
+        let count = rename::occurrences(&self.query.string, from);
+        if count == 0 {
+            self.status = Some(format!("?{from} is not in the query"));
+            return;
+        }
+        if rename::occurrences(&self.query.string, to) > 0 {
+            self.status = Some(format!("?{to} is already in the query"));
+            return;
+        }
+        let noun = if count == 1 {
+            "occurrence"
+        } else {
+            "occurrences"
+        };
+        self.confirmation = Some(Confirmation {
+            message: format!("Rename {count} {noun} of ?{from} to ?{to}? (y/n)"),
+            action: Action::ForceRenameVariable(from.to_string(), to.to_string()),
+            preview: Some(rename::rename(&self.query.string, from, to)),
+            alternative: None,
+        });
+    }
+
+    /// 離開導入之結果，回到默認之查詢。
+    fn leave_import(&mut self) {
+        if let Some(path) = self.imported.take() {
+            self.query = Query::new();
+            self.results.current = None;
+            self.status = Some(format!("Closed the results of {}", path.display()));
+        }
+    }
+
+    /// 於後台執行查詢。行陸續送回，繪製時併入結果；前一查詢未完則棄之。
+    pub(super) fn run_query(&mut self) {
+        log::debug!("query text={:?}", self.query.string);
+        self.results.carried_column = self
+            .results
+            .selected_column
+            .and_then(|column| self.results.table()?.variables.get(column).cloned());
+        // 同一查詢重新執行，如改數據後或手動，所選行待新結果而復之
+        self.results.anchor = self
+            .results
+            .current
+            .as_ref()
+            .filter(|results| {
+                results.query == self.query.string
+                    && matches!(results.source, Source::Query)
+                    && self.imported.is_none()
+            })
+            .and_then(|results| results.table.as_ref())
+            .and_then(|table| {
+                Anchor::new(
+                    &table.variables,
+                    &table.rows,
+                    self.results.selected_row,
+                    table.offset.get(),
+                )
+            });
+        self.remember_run();
+        self.imported = None;
+        self.results.selected_row = 0;
+        self.results.selected_column = None;
+        self.results.on_header = false;
+        self.results.on_footer = false;
+        self.exports.pending = None;
+        let (query, guarded) = self.guarded(&self.query.string);
+        if let Some(limit) = guarded {
+            log::info!("guard limit={limit}");
+            self.status = Some(format!(
+                "No LIMIT: showing the first {} rows (write a LIMIT to choose)",
+                group_digits(limit)
+            ));
+        }
+        self.running = Some(Worker::spawn(
+            &self.store,
+            Job {
+                query,
+                federated: self.federated,
+                timeout: self.config.timeout,
+                limit: self.limit,
+                caps: self.caps(),
+                prepared: self.prepared.clone(),
+                dataset: self.dataset.clone(),
+            },
+        ));
+        self.held_rows = None;
+        self.over_budget = false;
+        self.results.current = Some(ResultSet {
+            query: self.query.string.clone(),
+            table: None,
+            error: None,
+            remote_requests: 0,
+            guarded,
+            source: Source::Query,
+            generation: self.generation,
+        });
+    }
+
+    /// 防護開、存儲之三元組逾顯示上限而查詢或返回巨量之行，則加以等於顯示上限之 LIMIT。返回所行之文本及所加者。
+    fn guarded(&self, text: &str) -> (String, Option<usize>) {
+        let limit = self.limit.unwrap_or(guard::DEFAULT_LIMIT);
+        // 三元組不逾上限之存儲，無從返回巨量之行
+        let large = || self.store.iter().nth(limit).is_some();
+        match (self.guard && large())
+            .then(|| guard::guard(text, limit))
+            .flatten()
+        {
+            Some(query) => (query, Some(limit)),
+            None => (text.to_string(), None),
+        }
+    }
+
+    /// 行數之外，結果之單元格數及其估計之內存之上限。
+    pub(super) fn caps(&self) -> Caps {
+        Caps {
+            cells: self.config.max_cells,
+            bytes: self.config.max_result_memory,
+        }
+    }
+
+    /// 以三元組模式瀏覽。
+    pub(super) fn run_pattern(&mut self, pattern: Pattern) {
+        // 存儲為空者不執行，免示空表而似模式有誤
+        if self.store_is_empty() {
+            self.status = Some(self.tr(NO_DATA).to_string());
+            return;
+        }
+        log::debug!("pattern {}", pattern.describe());
+        let worker = Worker::spawn_pattern(&self.store, pattern.clone(), self.limit, self.caps());
+        self.run_derived(worker, Source::Pattern(pattern));
+    }
+
+    /// 查找重複之值。
+    pub(super) fn find_dupes(&mut self, dupes: Dupes) {
+        log::debug!(
+            "dupes predicate={} normalized={}",
+            dupes.predicate,
+            dupes.normalized
+        );
+        let worker = Worker::spawn_dupes(&self.store, dupes.clone(), self.limit, self.caps());
+        self.run_derived(worker, Source::Dupes(dupes));
+    }
+
+    /// 查找於一函數性屬性有多值之主語。
+    pub(super) fn check_functional(&mut self, functional: Functional) {
+        log::debug!("functional predicate={}", functional.predicate);
+        let worker =
+            Worker::spawn_functional(&self.store, functional.clone(), self.limit, self.caps());
+        self.run_derived(worker, Source::Functional(functional));
+    }
+
+    /// 以非出自查詢之結果為當前結果。查詢不變，回到查詢則棄之。
+    fn run_derived(&mut self, worker: Worker, source: Source) {
+        self.results.anchor = None;
+        self.imported = None;
+        self.results.selected_row = 0;
+        // 三元組模式之結果恆有所選之列，以示細化之欄；函數性之檢查選主語，詳情取之
+        self.results.carried_column = None;
+        self.results.selected_column =
+            matches!(source, Source::Pattern(_) | Source::Functional(_)).then_some(0);
+        self.exports.pending = None;
+        self.running = Some(worker);
+        self.held_rows = None;
+        self.over_budget = false;
+        self.results.current = Some(ResultSet {
+            query: self.query.string.clone(),
+            table: None,
+            error: None,
+            remote_requests: 0,
+            guarded: None,
+            source,
+            generation: self.generation,
+        });
+        self.mode = Mode::Browse;
+    }
+
+    /// 收下後台查詢已送回之消息，不等待。一批行將使內存逾預算，則留之不併，問是否就此
+    /// 限行數；其間後台因消息未取而等待。答否則併之而續，此查詢不再問。
+    pub(super) fn poll_query(&mut self) {
+        if self.held_rows.is_some() {
+            if self.confirmation.is_some() {
+                return;
+            }
+            self.over_budget = true;
+            self.release_held_rows();
+        }
+        while let Some(message) = self.running.as_ref().and_then(Worker::try_recv) {
+            match message {
+                Message::Rows(rows, term_bytes) if self.passes_budget(&rows, term_bytes) => {
+                    self.hold_rows(rows, term_bytes);
+                    return;
+                }
+                message => self.receive(message),
+            }
+        }
+    }
+
+    fn release_held_rows(&mut self) {
+        if let Some((rows, term_bytes)) = self.held_rows.take() {
+            self.receive(Message::Rows(rows, term_bytes));
+        }
+    }
+
+    /// 併入此批行是否使內存逾預算；先棄可棄者以容之。首批不問，無行可留。
+    fn passes_budget(&mut self, rows: &[TermRow], term_bytes: usize) -> bool {
+        let Some(budget) = self.config.memory_budget.filter(|_| !self.over_budget) else {
+            return false;
+        };
+        let Some(table) = self.results.table().filter(|table| !table.rows.is_empty()) else {
+            return false;
+        };
+        let added = term_bytes.saturating_sub(table.term_bytes)
+            + memory::rows(rows.len(), rows.len(), table.variables.len());
+        if self.memory_usage().total() + added <= budget {
+            return false;
+        }
+        self.relieve_memory(added);
+        self.memory_usage().total() + added > budget
+    }
+
+    fn hold_rows(&mut self, rows: Vec<TermRow>, term_bytes: usize) {
+        let shown = self.results.table().map_or(0, |table| table.rows.len());
+        let budget = self.config.memory_budget.unwrap_or_default();
+        log::info!("memory budget reached rows={shown} budget={budget}");
+        self.held_rows = Some((rows, term_bytes));
+        self.confirmation = Some(Confirmation {
+            message: format!(
+                "More rows would pass the memory budget of {} after {}. Cap the rows at {} here? (y/n)",
+                memory::abbreviate(budget),
+                group_digits(shown),
+                group_digits(shown)
+            ),
+            action: Action::CapRows(shown),
+            preview: None,
+            alternative: None,
+        });
+    }
+
+    /// 各大結構之估計字節數。
+    pub(super) fn memory_usage(&self) -> Usage {
+        let quads = self.quads();
+        let shown = self.shown_tables();
+        let previous = self.results.previous.as_ref();
+        Usage {
+            store: quads * memory::QUAD_BYTES,
+            results: shown.iter().map(|table| table.memory()).sum(),
+            cells: shown
+                .iter()
+                .copied()
+                .chain(previous.map(|(_, table)| table))
+                .map(ResultTable::cell_bytes)
+                .sum(),
+            snapshots: self.history.snapshot_bytes(),
+            previous: previous.map_or(0, |(query, table)| memory::text(query) + table.memory()),
+            trees: self.hierarchy.as_ref().map_or(0, |tree| tree.bytes())
+                + self.neighborhood.as_ref().map_or(0, |tree| tree.bytes()),
+            updates: self
+                .undo
+                .iter()
+                .chain(self.recent_updates.iter().map(|(_, update)| update))
+                .map(Update::bytes)
+                .sum(),
+        }
+    }
+
+    /// 所示之結果，比較中之新結果，及監視中未換入之結果。
+    fn shown_tables(&self) -> Vec<&ResultTable> {
+        let diff = self
+            .results
+            .current
+            .as_ref()
+            .and_then(|results| match &results.source {
+                Source::Diff(view) => Some(&view.table),
+                _ => None,
+            });
+        let watching = self
+            .watch_run
+            .as_ref()
+            .and_then(|(_, table)| table.as_ref());
+        [self.results.table(), diff, watching]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// 內存逾預算，則依序棄舊之快照、格之緩存與前次之結果，另留 `reserve` 字節之餘地。
+    pub(super) fn relieve_memory(&mut self, reserve: usize) {
+        let Some(budget) = self.config.memory_budget else {
+            return;
+        };
+        let usage = self.memory_usage();
+        let relief = usage.relieve(budget.saturating_sub(reserve));
+        if relief.is_empty() {
+            return;
+        }
+        let mut dropped = vec![];
+        if let Some(keep) = relief.snapshots {
+            dropped.push(match self.history.trim(keep) {
+                1 => "1 old snapshot".to_string(),
+                count => format!("{count} old snapshots"),
+            });
+        }
+        if relief.cells {
+            for table in self.shown_tables() {
+                table.display.borrow_mut().clear();
+            }
+            if let Some((_, table)) = &self.results.previous {
+                table.display.borrow_mut().clear();
+            }
+            dropped.push("the cell cache".to_string());
+        }
+        if relief.previous {
+            self.results.previous = None;
+            dropped.push("the previous result".to_string());
+        }
+        let dropped = dropped.join(", ");
+        log::info!(
+            "memory over budget total={} budget={budget} dropped={dropped}",
+            usage.total()
+        );
+        self.status = Some(format!(
+            "Over the memory budget of {}: dropped {dropped}",
+            memory::abbreviate(budget)
+        ));
+    }
+
+    /// 等待後台查詢完成，及各頁之比較。供腳本、測試及須完整結果者。
+    pub fn wait_for_query(&mut self) {
+        self.release_held_rows();
+        while let Some(worker) = &self.running {
+            let message = worker.recv();
+            self.receive(message);
+        }
+        self.poll_tab_runs(true);
+    }
+
+    /// 併入一條消息。行只追加於表末，故所選行與滾動位置不變。
+    pub(super) fn receive(&mut self, message: Message) {
+        let Some(results) = &mut self.results.current else {
+            return;
+        };
+        if let Some(worker) = &self.running {
+            results.remote_requests = worker.requests();
+        }
+        let duration = self.running.as_ref().map_or(0, |w| w.elapsed().as_millis());
+        let settled = matches!(message, Message::Started(_) | Message::Failed(_));
+        match message {
+            Message::Note(note) => self.status = Some(note),
+            Message::Started(variables) => {
+                // 重新執行或改投影之序後，所選之列仍為同一變量
+                if let Some(variable) = self.results.carried_column.take() {
+                    self.results.selected_column = variables
+                        .as_deref()
+                        .and_then(|variables| follow_column(Some(&variable), variables));
+                }
+                // 變量已異者無從復所選之行
+                if let Some(anchor) = self.results.anchor.take() {
+                    match variables.as_deref() == Some(&anchor.variables[..]) {
+                        true => self.results.anchor = Some(anchor),
+                        false if anchor.row > 0 => {
+                            self.status =
+                                Some("The columns changed; back to the first row".to_string())
+                        }
+                        false => {}
+                    }
+                }
+                results.table = variables.map(ResultTable::new);
+            }
+            Message::Rows(rows, term_bytes) => {
+                if let Some(table) = &mut results.table {
+                    let searched = table.rows.len();
+                    table.append(rows, term_bytes);
+                    self.restore_selection(searched, false);
+                }
+            }
+            Message::Finished { truncated } => {
+                if let Some(table) = &mut results.table {
+                    // 止於所加之 LIMIT 者亦為截斷，導出時重新查詢
+                    table.truncated = truncated.or(results
+                        .guarded
+                        .filter(|&limit| table.rows.len() >= limit)
+                        .map(Truncation::Rows));
+                    if let Some(cap @ (Truncation::Cells(_) | Truncation::Bytes(_))) = truncated {
+                        self.status = Some(format!(
+                            "Stopped {cap} (max_cells, max_result_memory); exports run the \
+                             query in full"
+                        ));
+                    }
+                    table.progress = Progress::Complete;
+                    table.elapsed = Some(Duration::from_millis(duration as u64));
+                    if self.config.stable_order && !header::ordered(&results.query) {
+                        table.stabilize();
+                    }
+                }
+                let rows = results.table.as_ref().map_or(0, |t| t.rows.len());
+                log::info!(
+                    "query rows={rows} duration_ms={duration} remote_requests={}",
+                    results.remote_requests
+                );
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.query(&results.query, duration, Ok(rows));
+                }
+                if matches!(results.source, Source::Query) {
+                    let entry = results.history_entry(duration, rows, self.config.snapshot_rows);
+                    self.history.record(entry, self.config.snapshot_memory);
+                }
+                self.restore_selection(rows, true);
+                self.finish_query();
+                self.relieve_memory(0);
+            }
+            Message::Failed(error) => {
+                self.results.anchor = None;
+                log::warn!("query failed duration_ms={duration} error={error}");
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.query(&results.query, duration, Err(&error));
+                }
+                results.table = None;
+                results.error = Some(error);
+                self.finish_query();
+            }
+        }
+        if settled {
+            if let Some(view) = self.pending_view.take() {
+                self.apply_view(view);
+            }
+        }
+    }
+
+    /// 監視查詢：查詢被改則暫停，離開編輯器後問是否改監視新查詢；到期且無查詢在執行則刷新；
+    /// 刷新完成則換入結果，記其增減。
+    pub(super) fn poll_watch(&mut self) {
+        let Some(watch) = self.watch.as_mut() else {
+            return;
+        };
+        if !watch.paused && self.query.string != watch.query {
+            watch.paused = true;
+            self.watch_run = None;
+            self.status = Some("Watch paused: the query changed".to_string());
+        }
+        if watch.paused && !watch.asked && self.mode != Mode::Query && self.confirmation.is_none() {
+            watch.asked = true;
+            self.confirmation = Some(Confirmation {
+                message: format!(
+                    "Watch the edited query every {}? (y/n)",
+                    describe_interval(watch.interval)
+                ),
+                action: Action::Watch(Some(watch.interval)),
+                preview: None,
+                alternative: None,
+            });
+        }
+
+        let mut outcome = None;
+        let mut refreshed = false;
+        if let Some((worker, table)) = &mut self.watch_run {
+            while let Some(message) = worker.try_recv() {
+                match message {
+                    Message::Note(_) => {}
+                    Message::Started(variables) => *table = variables.map(ResultTable::new),
+                    Message::Rows(rows, term_bytes) => {
+                        if let Some(table) = table {
+                            table.append(rows, term_bytes);
+                        }
+                    }
+                    Message::Finished { truncated } => {
+                        outcome = Some(Ok(truncated));
+                        break;
+                    }
+                    Message::Failed(error) => {
+                        outcome = Some(Err(error));
+                        break;
+                    }
+                }
+            }
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        match outcome {
+            Some(outcome) if watch.probing => {
+                let (_, table) = self.watch_run.take().unwrap_or_else(|| unreachable!());
+                watch.probing = false;
+                let fingerprint = match (outcome, table) {
+                    (Ok(_), Some(table)) => watch::fingerprint(&table.rows),
+                    (outcome, _) => {
+                        log::warn!("watch fingerprint unavailable: {outcome:?}");
+                        watch.unprobed = true;
+                        None
+                    }
+                };
+                if fingerprint.is_some() && fingerprint == watch.fingerprint {
+                    watch.skip(now);
+                    self.status = Some(WATCH_SKIPPED.to_string());
+                } else {
+                    // 指紋已變，即重新執行原查詢
+                    log::debug!("watch fingerprint changed");
+                    watch.fingerprint = fingerprint;
+                    watch.changed = true;
+                    watch.due = Instant::now();
+                }
+            }
+            Some(outcome) => {
+                let (_, table) = self.watch_run.take().unwrap_or_else(|| unreachable!());
+                let results = self.results.current.as_mut().filter(|results| {
+                    results.query == watch.query && matches!(results.source, Source::Query)
+                });
+                match (outcome, table, results) {
+                    (Ok(truncated), Some(mut table), Some(results)) => {
+                        table.truncated = truncated;
+                        table.progress = Progress::Complete;
+                        if self.config.stable_order && !header::ordered(&watch.query) {
+                            table.stabilize();
+                        }
+                        let diff = results
+                            .table
+                            .as_ref()
+                            .map(|old| Diff::between(&old.rows, &table.rows));
+                        // 所選行隨其項，已無則留其位，表縮則止於末行；所選列隨其變量
+                        let anchor = results.table.as_ref().and_then(|old| {
+                            Anchor::new(
+                                &old.variables,
+                                &old.rows,
+                                self.results.selected_row,
+                                old.offset.get(),
+                            )
+                        });
+                        self.results.selected_row = anchor
+                            .filter(|anchor| anchor.variables == table.variables)
+                            .and_then(|anchor| {
+                                let restored = anchor.restore(&table.rows, 0, true)?;
+                                let (Restored::Same(row) | Restored::Index(row)) = restored;
+                                table.offset.set(row.saturating_sub(anchor.above));
+                                Some(row)
+                            })
+                            .unwrap_or(0);
+                        if let Some(old) = &results.table {
+                            let variable = self
+                                .results
+                                .selected_column
+                                .and_then(|c| old.variables.get(c));
+                            self.results.selected_column =
+                                follow_column(variable, &table.variables);
+                        }
+                        let old = results.table.replace(table);
+                        results.error = None;
+                        results.generation = watch.started;
+                        if let Some(old) = old.filter(|old| old.progress == Progress::Complete) {
+                            self.results.previous = Some((watch.query.clone(), old));
+                        }
+                        watch.refreshed(now, diff);
+                        refreshed = true;
+                    }
+                    (Err(error), _, _) => {
+                        self.status = Some(format!("Watch refresh failed: {error}"));
+                        watch.refreshed(now, None);
+                    }
+                    _ => watch.refreshed(now, None),
+                }
+            }
+            None => {}
+        }
+        if watch.is_due() && self.watch_run.is_none() && self.running.is_none() {
+            // 存儲未改於其後之完整結果。SERVICE 之所返不在存儲之中，唯有以指紋知其變否
+            let current = self.results.current.as_ref().is_some_and(|results| {
+                results.query == watch.query
+                    && matches!(results.source, Source::Query)
+                    && results.generation == self.generation
+                    && results.error.is_none()
+                    && results
+                        .table
+                        .as_ref()
+                        .is_none_or(|table| table.progress == Progress::Complete)
+            });
+            let remote = self.federated && watch::remote(&watch.query);
+            if current && !remote && watch::deterministic(&watch.query) {
+                log::debug!("watch skipped: the store is unchanged");
+                watch.skip(now);
+                self.status = Some(WATCH_SKIPPED.to_string());
+            } else {
+                self.refresh_watch(current && remote);
+            }
+        }
+        if refreshed {
+            self.relieve_memory(0);
+        }
+    }
+
+    /// 發監視之刷新。`probe` 則先以指紋查詢問結果變否，見 [`watch::fingerprint_query`]。
+    fn refresh_watch(&mut self, probe: bool) {
+        let Some(watch) = self.watch.as_mut() else {
+            return;
+        };
+        let probe = (probe && !watch.changed && !watch.unprobed)
+            .then(|| watch::fingerprint_query(&watch.query))
+            .flatten();
+        log::debug!(
+            "watch refresh every={:?} fingerprint={}",
+            watch.interval,
+            probe.is_some()
+        );
+        watch.probing = probe.is_some();
+        watch.changed = false;
+        watch.started = self.generation;
+        let query = watch.query.clone();
+        let query = match probe {
+            Some(probe) => probe,
+            None => self.guarded(&query).0,
+        };
+        let worker = Worker::spawn(
+            &self.store,
+            Job {
+                query,
+                federated: self.federated,
+                timeout: self.config.timeout,
+                limit: self.limit,
+                caps: self.caps(),
+                prepared: self.prepared.clone(),
+                dataset: self.dataset.clone(),
+            },
+        );
+        self.watch_run = Some((worker, None));
+    }
+
+    /// 中止後台查詢，留已到之行。
+    pub(super) fn cancel_query(&mut self) {
+        if let Some(download) = self.download.as_ref().filter(|_| self.running.is_none()) {
+            download.cancel();
+            self.downloads.clear();
+            self.status = Some("Cancelling the download…".to_string());
+            return;
+        }
+        if let Some(job) = self.exports.job.as_ref().filter(|_| self.running.is_none()) {
+            job.cancel();
+            self.status = Some("Cancelling the export…".to_string());
+            return;
+        }
+        if self.running.is_none() && self.imported.is_some() {
+            self.leave_import();
+            return;
+        }
+        if let Some(worker) = &self.running {
+            worker.cancel();
+        }
+        self.poll_query();
+        self.running = None;
+        let Some(table) = self
+            .results
+            .current
+            .as_mut()
+            .and_then(|r| r.table.as_mut())
+            .filter(|table| table.progress == Progress::Running)
+        else {
+            return;
+        };
+        table.progress = Progress::Cancelled;
+        let rows = table.rows.len();
+        log::info!("query cancelled rows={rows}");
+        self.status = Some(format!("Query cancelled after {rows} rows"));
+        self.finish_query();
+    }
+
+    /// 查詢已止。有待導出者則導出之，覆蓋與否已於請求時定。
+    fn finish_query(&mut self) {
+        self.running = None;
+        self.held_rows = None;
+        if let Some(export) = self.exports.pending.take() {
+            // 導出之錯誤見於狀態欄，不致出錯
+            let _ = self.update(Action::Overwrite(Box::new(export)));
+        }
+    }
+
+    /// 待載入、待下載及執行中之後台工作之數。
+    pub(super) fn workers(&self) -> usize {
+        let running = [
+            self.running.is_some(),
+            self.exports.job.is_some(),
+            self.loading.is_some(),
+            self.download.is_some(),
+            self.graph_job.is_some(),
+        ];
+        self.pending.len() + self.downloads.len() + running.into_iter().filter(|&r| r).count()
+    }
+
+    /// 排入待載入之文件。於後台載入，界面先繪。
+    pub fn queue_load(&mut self, path: impl Into<PathBuf>) {
+        self.pending.push_back(path.into());
+    }
+
+    /// 載入文件並記錄之。失敗時不中止，將錯誤顯示於界面。結果文件則導入為表。
+    pub fn open(&mut self, path: PathBuf) {
+        let path = absolute(&path).unwrap_or(path);
+        if let Some(format) = results_format(&path) {
+            self.import(path, format);
+            return;
+        }
+        if !self.sources.contains(&path) {
+            self.sources.push(path.clone());
+            self.attach_bookmarks();
+        }
+        let start = Instant::now();
+        match self.load(&path) {
+            Ok(skipped) => {
+                log::info!(
+                    "loaded path={} duration_ms={}",
+                    path.display(),
+                    start.elapsed().as_millis()
+                );
+                self.load_error = None;
+                if let Some(skipped) = skipped {
+                    log::warn!("load path={} {skipped}", path.display());
+                    self.warn(format!("{}: {skipped}", path.display()));
+                }
+                if self
+                    .status
+                    .as_deref()
+                    .is_none_or(|s| s.starts_with("Loading"))
+                {
+                    self.status = Some(format!("Loaded {}", path.display()));
+                }
+            }
+            Err(error) => {
+                log::error!("load failed path={} error={error:#}", path.display());
+                let message = format!("Fail to load {}: {error:#}", path.display());
+                self.fail_load(message);
+            }
+        }
+        if self.config.terminal_title {
+            self.window_title = Some(self.describe_dataset());
+        }
+    }
+
+    /// 載入命令行所給之數據並記錄之。錯誤如文件般報告，以「inline data #N」稱之。
+    pub fn load_data(&mut self, data: impl Into<String>, format: RdfFormat) {
+        self.inline.push((data.into(), format));
+        self.load_inline(self.inline.len());
+    }
+
+    /// 載入第 `number` 段數據，自 1 起。
+    fn load_inline(&mut self, number: usize) {
+        let (data, format) = self.inline[number - 1].clone();
+        let bytes = data.len();
+        let command = |id| Command::LoadData {
+            id,
+            data,
+            format,
+            number,
+        };
+        match self.call_store(command) {
+            Event::Loaded { graphs, .. } => {
+                self.provenance
+                    .record(&format!("inline data #{number}"), &graphs);
+                log::info!("loaded inline={number} bytes={bytes}");
+                self.store_changed();
+                self.status = Some(format!("Loaded inline data #{number}"));
+            }
+            event => {
+                let error = store_error(event);
+                log::error!("load failed inline={number} error={error}");
+                let message = format!("Fail to load inline data #{number}: {error}");
+                self.fail_load(message);
+            }
+        }
+    }
+
+    /// 報告載入或導入之失敗：首行示於狀態欄，全文代結果表而示。
+    fn fail_load(&mut self, message: String) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.error(&message);
+        }
+        self.status = Some(message.lines().next().unwrap_or_default().to_string());
+        self.load_error = Some(message);
+    }
+
+    /// 導入他處所存之查詢結果為表，不經存儲，並轉至瀏覽模式。
+    fn import(&mut self, path: PathBuf, format: QueryResultsFormat) {
+        let start = Instant::now();
+        match read_results(&path, format, self.limit) {
+            Ok(table) => {
+                log::info!(
+                    "imported path={} rows={} duration_ms={}",
+                    path.display(),
+                    table.rows.len(),
+                    start.elapsed().as_millis()
+                );
+                self.running = None;
+                self.load_error = None;
+                self.status = Some(format!(
+                    "Imported {} rows from {}",
+                    group_digits(table.rows.len()),
+                    path.display()
+                ));
+                self.remember(&path, table.rows.len());
+                self.query.set(String::new());
+                self.show_table(String::new(), table);
+                self.mode = Mode::Browse;
+                self.imported = Some(path);
+            }
+            Err(error) => {
+                log::error!("import failed path={} error={error:#}", path.display());
+                let message = format!("Fail to import {}: {error:#}", path.display());
+                self.fail_load(message);
+            }
+        }
+    }
+
+    /// 所排之數據文件皆交後台載入，單個亦然，故首幀不待之。結果文件留於隊列，逐一導入。
+    pub(super) fn start_loading(&mut self) {
+        let (urls, paths): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|path| download::is_url(path));
+        self.downloads
+            .extend(urls.iter().map(|url| url.to_string_lossy().into_owned()));
+        self.start_download();
+        let (results, paths): (Vec<_>, Vec<_>) = paths
+            .into_iter()
+            .partition(|path| results_format(path).is_some());
+        self.pending.extend(results);
+        if paths.is_empty() {
+            return;
+        }
+        match self.too_large(&paths) {
+            Some(message) => {
+                self.confirmation = Some(Confirmation {
+                    message,
+                    action: Action::LoadInMemory(paths),
+                    preview: None,
+                    alternative: None,
+                });
+            }
+            None => self.open_all(paths),
+        }
+    }
+
+    /// 無下載中者則始下一 URL 之下載。
+    fn start_download(&mut self) {
+        if self.download.is_some() {
+            return;
+        }
+        let Some(url) = self.downloads.pop_front() else {
+            return;
+        };
+        let options = download::Options {
+            cache: self.paths.cache_dir(),
+            max: self.max_download,
+            retries: self.config.download_retries,
+            stall: self.config.timeout,
+            ..download::Options::default()
+        };
+        log::info!("download url={url}");
+        self.download = Some(Download::spawn(url, options));
+    }
+
+    /// 收取下載之結果：成則排入其文件以載入，敗則如載入之失敗報之。
+    pub(super) fn poll_download(&mut self) {
+        let Some(result) = self.download.as_ref().and_then(Download::poll) else {
+            return;
+        };
+        self.finish_download(result);
+    }
+
+    /// 等待下載完成。供腳本與測試。
+    pub fn wait_for_download(&mut self) {
+        while let Some(download) = &self.download {
+            let result = download.wait();
+            self.finish_download(result);
+        }
+    }
+
+    fn finish_download(&mut self, result: Result<download::Fetched, String>) {
+        let Some(download) = self.download.take() else {
+            return;
+        };
+        match result {
+            Ok(fetched) => {
+                self.status = Some(match fetched.cached {
+                    true => format!("{} is unchanged; using the cached copy", download.url),
+                    false => format!(
+                        "Downloaded {} ({})",
+                        download.url,
+                        format_bytes(fetched.bytes as usize)
+                    ),
+                });
+                self.downloaded
+                    .insert(fetched.path.clone(), download.url.clone());
+                self.queue_load(fetched.path);
+            }
+            Err(error) => {
+                log::error!("download failed url={} error={error}", download.url);
+                self.fail_load(format!("Fail to download {}: {error}", download.url));
+            }
+        }
+        self.start_download();
+    }
+
+    /// 諸文件之存儲估計逾可用內存之 `load_memory_share` 則返回所問之言。
+    fn too_large(&self, paths: &[PathBuf]) -> Option<String> {
+        let share = self.config.load_memory_share;
+        let available = self.available_memory.filter(|_| share > 0)?;
+        let bytes: u64 = paths
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        let estimate = memory::load_estimate(bytes);
+        if estimate <= available / 100 * share {
+            return None;
+        }
+        let files = match paths {
+            [path] => path.display().to_string(),
+            paths => format!("{} files", paths.len()),
+        };
+        Some(format!(
+            "{files} ({}) would take ~{} in memory, over {share}% of the {} available, and this \
+             build has no on-disk store. Load anyway? (y/n)",
+            memory::abbreviate(bytes as usize),
+            memory::abbreviate(estimate),
+            memory::abbreviate(available),
+        ))
+    }
+
+    /// 交存儲線程並行載入多個文件。主循環收取其結果，載完後如 [`App::open`] 般報告。
+    pub(super) fn open_all(&mut self, paths: Vec<PathBuf>) {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .map(|path| absolute(&path).unwrap_or(path))
+            .collect();
+        for path in &paths {
+            if !self.sources.contains(path) {
+                self.sources.push(path.clone());
+            }
+        }
+        self.attach_bookmarks();
+        log::info!("parallel load files={} jobs={}", paths.len(), self.jobs);
+        let read = self.backend.read();
+        self.loading.get_or_insert_with(|| Loading::new(None, read));
+        for path in paths {
+            let command = |id| Command::Load {
+                id,
+                path: path.clone(),
+            };
+            let id = self.send_store(command);
+            if let Some(loading) = &mut self.loading {
+                loading.add(id, path);
+            }
+        }
+    }
+
+    /// 收取存儲線程已送之事件。載入或重新載入全部完成則返回真。
+    pub(super) fn poll_loading(&mut self) -> bool {
+        while let Some(event) = self.backend.try_recv() {
+            self.store_event(event);
+            if self.loading.is_none() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 處理存儲線程之事件：載入中之文件之結果，或重新載入之結果。
+    fn store_event(&mut self, event: Event) {
+        let id = event.id();
+        let Some(loading) = &mut self.loading else {
+            log::debug!("store event dropped id={id}");
+            return;
+        };
+        if loading.reload == Some(id) {
+            let loading = self.loading.take().unwrap();
+            match event {
+                Event::Reloaded { loads, .. } => {
+                    self.finish_reload(loads, loading.paths.len(), vec![])
+                }
+                event => self.finish_reload(vec![], 0, vec![store_error(event)]),
+            }
+            return;
+        }
+        let Some(index) = loading
+            .waiting
+            .iter()
+            .position(|(waiting, _)| *waiting == id)
+        else {
+            log::debug!("store event dropped id={id}");
+            return;
+        };
+        let (_, path) = loading.waiting.remove(index);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        match event {
+            Event::Loaded {
+                graphs,
+                prefixes,
+                note,
+                ..
+            } => {
+                log::info!("loaded path={}", path.display());
+                let source = path.display().to_string();
+                self.declared.add(&prefixes);
+                self.provenance.record(&source, &graphs);
+                let triples = graphs.iter().map(|(_, n)| n).sum();
+                if let Err(error) = self.recent.record(&path, triples, now) {
+                    log::warn!("recent files save failed error={error}");
+                }
+                if let Some(skipped) = &note {
+                    log::warn!("load path={} {skipped}", path.display());
+                    self.warning = Some(format!("{}: {skipped}", path.display()));
+                }
+                self.generation.bump();
+            }
+            event => {
+                let error = store_error(event);
+                log::error!("load failed path={} error={error}", path.display());
+                if let Some(loading) = &mut self.loading {
+                    loading
+                        .failures
+                        .push(format!("Fail to load {}: {error}", path.display()));
+                }
+            }
+        }
+        if self
+            .loading
+            .as_ref()
+            .is_some_and(|loading| loading.waiting.is_empty())
+        {
+            let loading = self.loading.take().unwrap();
+            self.finish_loading(loading);
+        }
+    }
+
+    /// 並行載入已畢：報告之，並按設置追蹤導入。
+    fn finish_loading(&mut self, loading: Loading) {
+        let Loading {
+            paths, failures, ..
+        } = loading;
+        let count = paths.len();
+        let loaded = count - failures.len();
+        self.status = Some(match failures.first() {
+            None if count == 1 => format!("Loaded {}", paths[0].display()),
+            Some(only) if count == 1 => only.lines().next().unwrap_or_default().to_string(),
+            None => format!("Loaded {loaded} files"),
+            Some(first) => format!(
+                "Loaded {loaded} of {count} files; {}",
+                first.lines().next().unwrap_or_default()
+            ),
+        });
+        if let Some(recorder) = &mut self.recorder {
+            for failure in &failures {
+                recorder.error(failure);
+            }
+        }
+        self.load_error = (!failures.is_empty()).then(|| failures.join("\n\n"));
+        self.follow_default_imports();
+        if self.config.terminal_title {
+            self.window_title = Some(self.describe_dataset());
+        }
+        self.relieve_memory(0);
+    }
+
+    /// 如 `rdf-tui — data.ttl +1 (1.2M triples)`，以作終端標題。
+    pub(super) fn describe_dataset(&self) -> String {
+        let mut title = "rdf-tui".to_string();
+        if !self.sources.is_empty() {
+            title.push_str(" — ");
+            title.push_str(&tab_name(&self.sources));
+        }
+        if let Ok(triples) = self.store.len() {
+            title.push_str(&format!(" ({} triples)", abbreviate_count(triples)));
+        }
+        title
+    }
+
+    /// 交存儲線程重新載入所有文件及數據至新存儲。全部成功方換入，否則舊存儲與結果不變。
+    pub(super) fn reload(&mut self) -> anyhow::Result<()> {
+        log::info!("reload files={}", self.sources.len());
+        let (data, paths) = (self.inline.clone(), self.sources.clone());
+        let read = self.backend.read();
+        let id = self.send_store(|id| Command::Reload {
+            id,
+            data,
+            paths: paths.clone(),
+        });
+        let mut loading = Loading::new(Some(id), read);
+        for path in paths {
+            loading.add(id, path);
+        }
+        self.loading = Some(loading);
+        Ok(())
+    }
+
+    /// 重新載入已畢：全部成功則換入新存儲，否則棄之而報告錯誤。
+    fn finish_reload(
+        &mut self,
+        loads: Vec<(String, Vec<(GraphName, usize)>)>,
+        files: usize,
+        failures: Vec<String>,
+    ) {
+        if let Some(first) = failures.first() {
+            log::error!("reload failed failures={}", failures.len());
+            self.status = Some(format!(
+                "Reload failed, the previous data is kept; {}",
+                first.lines().next().unwrap_or_default()
+            ));
+            self.load_error = Some(failures.join("\n\n"));
+            return;
+        }
+        log::info!("reloaded files={files}");
+        self.store = self.backend.store();
+        if let Some(server) = &self.server {
+            server.swap(&self.store);
+        }
+        for (source, graphs) in &loads {
+            self.provenance.record(source, graphs);
+        }
+        self.visited_imports.clear();
+        self.load_error = None;
+        self.store_changed();
+        self.status = Some(format!("Reloaded {files} files"));
+        self.follow_default_imports();
+        if self.config.terminal_title {
+            self.window_title = Some(self.describe_dataset());
+        }
+    }
+
+    /// 清空存儲或某具名圖。全清則亦忘所載之文件，以便重新開始。
+    pub(super) fn clear(&mut self, graph: Option<String>) -> anyhow::Result<()> {
+        self.leave_import();
+        self.running = None;
+        self.results.current = None;
+        self.hierarchy = None;
+        self.load_error = None;
+        // 清空後之撤銷無從說起
+        self.undo.clear();
+        if self.mode == Mode::Hierarchy {
+            self.mode = Mode::Browse;
+        }
+        match graph {
+            Some(iri) => {
+                let graph = Some(graph_name(&iri)?.into());
+                if let Event::Failed { error, .. } =
+                    self.call_store(|id| Command::Clear { id, graph })
+                {
+                    anyhow::bail!(error);
+                }
+                self.provenance.remove_graph(Some(&format!("<{iri}>")));
+                log::info!("cleared graph={iri}");
+                self.status = Some(format!("Cleared graph <{iri}>"));
+            }
+            None => {
+                if let Event::Failed { error, .. } =
+                    self.call_store(|id| Command::Clear { id, graph: None })
+                {
+                    anyhow::bail!(error);
+                }
+                self.sources.clear();
+                self.inline.clear();
+                self.visited_imports.clear();
+                self.provenance.clear();
+                self.declared.clear();
+                log::info!("cleared store");
+                self.status = Some("Cleared all data".to_string());
+            }
+        }
+        self.store_changed();
+        Ok(())
+    }
+
+    /// 交存儲線程讀取 RDF 文件並待之，記其來源，並按設置追蹤其導入。返回 N3 略去之說明。
+    pub(super) fn load(&mut self, path: impl AsRef<Path>) -> anyhow::Result<Option<String>> {
+        let path = path.as_ref();
+        let command = |id| Command::Load {
+            id,
+            path: path.to_path_buf(),
+        };
+        let (graphs, prefixes, note) = match self.call_store(command) {
+            Event::Loaded {
+                graphs,
+                prefixes,
+                note,
+                ..
+            } => (graphs, prefixes, note),
+            event => anyhow::bail!(store_error(event)),
+        };
+        self.provenance.record(&path.display().to_string(), &graphs);
+        self.declared.add(&prefixes);
+        self.remember(path, graphs.iter().map(|(_, n)| n).sum());
+        self.store_changed();
+        self.follow_default_imports();
+        Ok(note)
+    }
+
+    /// 按設置追蹤默認圖之導入。失敗時僅報告，不中止載入。
+    fn follow_default_imports(&mut self) {
+        if let Some(depth) = self.follow_imports {
+            let visited = self.visited_imports.clone();
+            let command = |id| Command::FollowImports { id, depth, visited };
+            let report = match self.call_store(command) {
+                Event::Imported {
+                    imported,
+                    failures,
+                    visited,
+                    ..
+                } => {
+                    self.visited_imports = visited;
+                    ImportReport { imported, failures }
+                }
+                event => ImportReport {
+                    failures: vec![("owl:imports".to_string(), store_error(event))],
+                    ..ImportReport::default()
+                },
+            };
+            for (iri, triples) in &report.imported {
+                log::info!("imported iri={iri} triples={triples}");
+                let graph = GraphName::NamedNode(NamedNode::new_unchecked(iri));
+                self.provenance.record(iri, &[(graph, *triples)]);
+            }
+            for (iri, error) in &report.failures {
+                log::warn!("import failed iri={iri} error={error}");
+            }
+            self.status = report.summary();
+        }
+    }
+}
+
+/// 存儲線程所報之錯誤。他事件不應為命令之末，至此亦以錯誤說明之。
+fn graph_name(iri: &str) -> anyhow::Result<NamedNode> {
+    NamedNode::new(iri).with_context(|| format!("Invalid graph IRI <{iri}>"))
+}
+
+fn describe_limit(limit: Option<usize>) -> String {
+    match limit {
+        Some(limit) => format!("Showing at most {limit} rows"),
+        None => "Showing all rows".to_string(),
+    }
+}
+
+fn store_error(event: Event) -> String {
+    match event {
+        Event::Failed { error, .. } => error,
+        Event::Cancelled { .. } => "Cancelled".to_string(),
+        event => format!("Unexpected {event:?}"),
+    }
+}
+
+/// 列出資源詳情之查詢。引用三元組另列其主語、謂語、賓語，以便再循之；含空白節點者不可寫入 `VALUES`，略之。
+pub(super) fn details_query(term: &Term) -> String {
+    let Term::Triple(triple) = term else {
+        return format!(
+            "SELECT ?p ?o WHERE {{ {} ?p ?o }}",
+            sparql_term(term.as_ref())
+        );
+    };
+    let parts: Vec<String> = [
+        (rdf::SUBJECT, Term::from(triple.subject.clone())),
+        (rdf::PREDICATE, triple.predicate.clone().into()),
+        (rdf::OBJECT, triple.object.clone()),
+    ]
+    .iter()
+    .filter(|(_, part)| !has_blank_node(part.as_ref()))
+    .map(|(relation, part)| format!("({relation} {})", sparql_term(part.as_ref())))
+    .collect();
+    let pattern = format!("{{ {} ?p ?o }}", sparql_term(term.as_ref()));
+    if parts.is_empty() {
+        return format!("SELECT ?p ?o WHERE {pattern}");
+    }
+    format!(
+        "SELECT ?p ?o WHERE {{ {pattern} UNION {{ VALUES (?p ?o) {{ {} }} }} }}",
+        parts.join(" ")
+    )
+}
+
+/// 項於 SPARQL 中之寫法。引用三元組寫作 `<< s p o >>`，各部以空格分隔。
+pub(super) fn sparql_term(term: TermRef<'_>) -> String {
+    match term {
+        TermRef::Triple(triple) => format!(
+            "<< {} {} {} >>",
+            sparql_term(triple.subject.as_ref().into()),
+            triple.predicate,
+            sparql_term(triple.object.as_ref())
+        ),
+        term => term.to_string(),
+    }
+}
+
+pub(super) fn has_blank_node(term: TermRef<'_>) -> bool {
+    match term {
+        TermRef::BlankNode(_) => true,
+        TermRef::Triple(triple) => {
+            has_blank_node(triple.subject.as_ref().into()) || has_blank_node(triple.object.as_ref())
+        }
+        _ => false,
+    }
+}
+
+/// 本會話中所記之更新數上限。
+pub(super) const MAX_UPDATES: usize = 20;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::{app_with_rows, render, rows, text, type_text, update_all};
+    use crate::{
+        action::{ExportFormat, GraphOp},
+        fixture,
+        graphs::graph_len,
+        hierarchy::Relation,
+        paths::Paths,
+        sink::Sink,
+        solutions::QueryOptions,
+    };
+    use oxigraph::{
+        io::RdfParser,
+        model::{GraphNameRef, Literal, NamedNodeRef, QuadRef},
+        sparql::{results::QueryResultsFormat, Variable},
+    };
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use std::{fs, io::Write, sync::Arc, thread};
+
+    #[test]
+    fn trees_over_the_refresh_budget_stay_marked_stale_until_r() {
+        use oxigraph::model::{GraphName, NamedNode, Quad};
+        let sub_class_of =
+            NamedNode::new_unchecked("http://www.w3.org/2000/01/rdf-schema#subClassOf");
+        let mut app = app_with_rows();
+        app.config.auto_refresh_quads = 0;
+        app.config.auto_refresh_time = Duration::ZERO;
+        app.open_hierarchy(Relation::SubClassOf);
+        assert!(app.hierarchy.as_ref().unwrap().visible().is_empty());
+
+        let quad = Quad::new(
+            NamedNode::new_unchecked("http://ex/B"),
+            sub_class_of,
+            NamedNode::new_unchecked("http://ex/A"),
+            GraphName::DefaultGraph,
+        );
+        app.propose_update(Update::new("Inserted".to_string(), vec![], vec![quad]));
+        update_all(&mut app, [Action::Confirm]);
+        app.mode = Mode::Hierarchy;
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("stale — press R to refresh"), "{screen}");
+        assert!(app.hierarchy_refresh.is_none());
+        assert!(app.hierarchy.as_ref().unwrap().visible().is_empty());
+
+        update_all(&mut app, [Action::RefreshPanel]);
+        assert!(app.hierarchy_refresh.is_some());
+        app.wait_for_refreshes();
+        assert_eq!(app.hierarchy.as_ref().unwrap().visible().len(), 1);
+        assert!(!text(&render(&mut app)).contains("stale"));
+
+        // 換頁則棄後台之重建
+        update_all(&mut app, [Action::RefreshPanel, Action::NewTab(None)]);
+        assert!(app.hierarchy_refresh.is_none());
+    }
+
+    #[test]
+    fn an_update_refreshes_every_cached_surface() {
+        use oxigraph::model::{GraphName, NamedNode, Quad};
+        let iri = |name: &str| NamedNode::new_unchecked(format!("http://ex/{name}"));
+        let sub_class_of =
+            NamedNode::new_unchecked("http://www.w3.org/2000/01/rdf-schema#subClassOf");
+        let label = NamedNode::new_unchecked("http://www.w3.org/2000/01/rdf-schema#label");
+        let insert = |app: &mut App, quads: Vec<(NamedNode, NamedNode, Term)>| {
+            let quads = quads
+                .into_iter()
+                .map(|(s, p, o)| Quad::new(s, p, o, GraphName::DefaultGraph))
+                .collect();
+            app.propose_update(Update::new("Inserted".to_string(), vec![], quads));
+            update_all(app, [Action::Confirm]);
+            // 列統計待新結果完成後重算
+            app.refresh_results();
+        };
+        let mut app = app_with_rows();
+        insert(
+            &mut app,
+            vec![(iri("B"), sub_class_of.clone(), iri("A").into())],
+        );
+        assert_eq!(app.results.table().unwrap().rows.len(), 4);
+        update_all(
+            &mut app,
+            [Action::ScrollColumns(1), Action::ShowColumnStats],
+        );
+        app.open_hierarchy(Relation::SubClassOf);
+        app.open_neighborhood(iri("a").into());
+        app.mode = Mode::Browse;
+        assert_eq!(app.hierarchy.as_ref().unwrap().visible()[0].label, None);
+        assert_eq!(app.neighborhood.as_ref().unwrap().center_label(), None);
+
+        insert(
+            &mut app,
+            vec![
+                (
+                    iri("A"),
+                    label.clone(),
+                    Literal::new_simple_literal("Animal").into(),
+                ),
+                (iri("a"), label, Literal::new_simple_literal("Alpha").into()),
+            ],
+        );
+        assert_eq!(app.results.table().unwrap().rows.len(), 6);
+        let Some(Popup::ColumnStats(_, _, title, _)) = &app.popup else {
+            panic!("column statistics closed");
+        };
+        assert!(title.contains("6 rows"), "{title}");
+        app.wait_for_refreshes();
+        let hierarchy = app.hierarchy.as_ref().unwrap();
+        assert_eq!(hierarchy.visible()[0].label, Some("Animal"));
+        let neighborhood = app.neighborhood.as_ref().unwrap();
+        assert_eq!(neighborhood.center_label(), Some("Alpha"));
+    }
+
+    #[test]
+    fn the_dataset_setting_chooses_the_default_graph() {
+        let mut app = App::new().unwrap();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::TriG),
+                "<http://ex/g> { <http://ex/a> <http://ex/p> 1 }".as_bytes(),
+            )
+            .unwrap();
+        app.query.set("SELECT ?s { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(rows(&app), 0);
+        let screen = text(&render(&mut app));
+        assert!(
+            screen.contains("note: the default graph is empty; the data is in named graphs"),
+            "{screen}"
+        );
+        assert!(screen.contains("0 rows · ~"));
+        assert!(screen.contains(" · default graph"));
+
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('U'))),
+            [Action::ToggleUnion]
+        );
+        update_all(&mut app, [Action::ToggleUnion]);
+        assert_eq!(rows(&app), 1);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Queries match the union of all graphs")
+        );
+        assert!(text(&render(&mut app)).contains(" · union graph"));
+
+        update_all(&mut app, [Action::OpenCommandLine]);
+        type_text(&mut app, "dataset graphs default");
+        update_all(&mut app, [Action::Activate]);
+        assert_eq!(app.dataset, Dataset::Graphs(vec![GraphName::DefaultGraph]));
+        assert_eq!(rows(&app), 0);
+        let screen = text(&render(&mut app));
+        assert!(
+            screen.contains("note: the default graph is empty — choose another graph"),
+            "{screen}"
+        );
+        assert_eq!(app.session().dataset, app.dataset);
+        update_all(&mut app, [Action::ToggleUnion]);
+        assert_eq!(app.dataset, Dataset::Default);
+    }
+
+    #[test]
+    fn cell_caps_cut_wide_results_and_say_which_cap() {
+        let mut app = app_with_rows();
+        app.config.max_cells = Some(7);
+        update_all(&mut app, [Action::ForceRunQuery]);
+        // 三列之表，七格容二行
+        let table = app.results.table().unwrap();
+        assert_eq!(table.truncated, Some(Truncation::Cells(7)));
+        assert_eq!(rows(&app), 2);
+        let screen = text(&render(&mut app));
+        assert!(
+            screen.contains("first 2 rows, stopped at 7 cells"),
+            "{screen}"
+        );
+        assert!(app.status.as_ref().unwrap().contains("max_cells"));
+    }
+
+    #[test]
+    fn hubs_ask_before_listing_their_incoming_triples() {
+        let mut app = App::new().unwrap();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "@prefix ex: <http://ex/> . ex:a ex:in ex:hub . ex:b ex:in ex:hub . \
+                 ex:c ex:in ex:hub . ex:d ex:near ex:hub ."
+                    .as_bytes(),
+            )
+            .unwrap();
+        let incoming = |app: &mut App| {
+            update_all(app, [Action::ShowIncoming]);
+            while app.incoming_count.is_some() {
+                app.poll_incoming();
+                std::thread::yield_now();
+            }
+            app.wait_for_query();
+        };
+        app.query
+            .set("SELECT ?o WHERE { <http://ex/a> ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('<'))),
+            [Action::ShowIncoming]
+        );
+        incoming(&mut app);
+        assert_eq!(rows(&app), 4);
+        assert!(app.confirmation.is_none());
+
+        app.config.hub_threshold = 3;
+        app.query
+            .set("SELECT ?o WHERE { <http://ex/a> ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery]);
+        incoming(&mut app);
+        let confirmation = app.confirmation.as_ref().unwrap();
+        assert_eq!(
+            confirmation.message,
+            "4 triples point at <http://ex/hub>: load the first 1 000 (y), group them by predicate (g), or skip (n)?"
+        );
+        update_all(&mut app, [Action::ConfirmAlternative]);
+        assert_eq!(rows(&app), 2);
+        let table = app.results.table().unwrap();
+        assert_eq!(
+            table.rows[0][1].as_deref(),
+            Some(&Term::from(Literal::from(3)))
+        );
+        // Enter 展開所選之謂語
+        update_all(&mut app, [Action::ShowDetails]);
+        assert_eq!(rows(&app), 3);
+        assert_eq!(app.results.table().unwrap().variables[0].as_str(), "s");
+    }
+
+    #[test]
+    fn watch_skips_refreshes_while_the_store_is_unchanged() {
+        let mut app = app_with_rows();
+        let due = |app: &mut App| {
+            app.watch.as_mut().unwrap().due = Instant::now();
+            app.poll_watch();
+            let ran = app.watch_run.is_some();
+            while app.watch_run.is_some() {
+                thread::sleep(std::time::Duration::from_millis(5));
+                app.poll_watch();
+            }
+            ran
+        };
+        let watch = Action::Watch(Some(std::time::Duration::from_secs(30)));
+        update_all(&mut app, [watch.clone()]);
+        assert!(!due(&mut app));
+        assert_eq!(app.status.as_deref(), Some("unchanged, skipped refresh"));
+        assert!(text(&render(&mut app)).contains("unchanged, skipped refresh"));
+
+        app.store_changed();
+        assert!(due(&mut app));
+        assert!(!app.watch.as_ref().unwrap().skipped);
+        assert!(!due(&mut app));
+
+        // 每次皆異之查詢照常重新執行
+        app.query
+            .set("SELECT ?s (RAND() AS ?r) WHERE { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery, watch.clone()]);
+        assert!(due(&mut app));
+
+        // 聯邦查詢之所返不在存儲中：先問指紋，變則重新執行，同則略過
+        app.federated = true;
+        app.query
+            .set("# SERVICE\nSELECT ?s ?o WHERE { ?s ?p ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery, watch]);
+        assert!(due(&mut app));
+        assert!(app.watch.as_ref().unwrap().fingerprint.is_some());
+        assert!(!app.watch.as_ref().unwrap().skipped);
+        assert!(due(&mut app));
+        assert!(app.watch.as_ref().unwrap().skipped);
+        assert_eq!(rows(&app), 3);
+    }
+
+    #[test]
+    fn watch_refreshes_in_place_and_pauses_on_edits() {
+        let mut app = app_with_rows();
+        let refresh = |app: &mut App| {
+            app.watch.as_mut().unwrap().due = Instant::now();
+            app.poll_watch();
+            while app.watch_run.is_some() {
+                thread::sleep(std::time::Duration::from_millis(5));
+                app.poll_watch();
+            }
+        };
+        update_all(
+            &mut app,
+            [
+                Action::Watch(Some(std::time::Duration::from_secs(30))),
+                Action::ScrollRows(1),
+            ],
+        );
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "<http://ex/d> <http://ex/p> 4 .".as_bytes(),
+            )
+            .unwrap();
+        app.store_changed();
+        refresh(&mut app);
+        assert_eq!(rows(&app), 4);
+        // 所選之行隨其項
+        let row: Vec<_> = app
+            .results
+            .table()
+            .unwrap()
+            .row(app.results.selected_row)
+            .unwrap()
+            .collect();
+        assert_eq!(row[0].unwrap().to_string(), "<http://ex/b>");
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("every 30s · refreshed "));
+        assert!(screen.contains("+1 row, −0 rows"));
+
+        // 刷新不堆積：查詢在執行時不發
+        app.run_query();
+        app.watch.as_mut().unwrap().due = Instant::now();
+        app.poll_watch();
+        assert!(app.watch_run.is_none());
+        app.wait_for_query();
+
+        // 改查詢則暫停，離開編輯器後問之
+        update_all(&mut app, [Action::SwitchMode]);
+        type_text(&mut app, " LIMIT 1");
+        app.poll_watch();
+        assert!(app.watch.as_ref().unwrap().paused);
+        assert!(app.confirmation.is_none());
+        update_all(&mut app, [Action::SwitchMode]);
+        app.poll_watch();
+        assert!(app.confirmation.is_some());
+        update_all(&mut app, [Action::Confirm]);
+        let watch = app.watch.as_ref().unwrap();
+        assert!(!watch.paused && watch.query.ends_with("LIMIT 1"));
+
+        update_all(&mut app, [Action::Watch(None)]);
+        assert!(app.watch.is_none());
+        assert_eq!(app.status.as_deref(), Some("Stopped watching"));
+    }
+
+    #[test]
+    fn counts_use_the_position_of_the_selected_column() {
+        let mut app = app_with_rows();
+        let finish = |app: &mut App| {
+            while app.count.is_some() {
+                app.poll_count();
+                std::thread::yield_now();
+            }
+            app.status.clone().unwrap()
+        };
+        app.query
+            .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(&mut app, [Action::RunQuery, Action::CountTerm]);
+        assert_eq!(app.status.as_deref(), Some("Select a cell with ←/→ first"));
+        update_all(
+            &mut app,
+            [Action::ScrollColumns(1), Action::ScrollColumns(1)],
+        );
+        update_all(&mut app, [Action::CountTerm]);
+        assert_eq!(finish(&mut app), "<http://ex/p> as predicate: 3 triples");
+        update_all(&mut app, [Action::ScrollColumns(1), Action::CountTerm]);
+        assert_eq!(
+            finish(&mut app),
+            "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer> as object: 1 triple"
+        );
+
+        app.query.set("SELECT ?x WHERE { ?x ?p 1 }".to_string());
+        update_all(
+            &mut app,
+            [
+                Action::RunQuery,
+                Action::ScrollColumns(1),
+                Action::CountTerm,
+            ],
+        );
+        assert_eq!(
+            finish(&mut app),
+            "<http://ex/a>: 1 as subject, 0 as predicate, 0 as object"
+        );
+    }
+
+    #[test]
+    fn queries_stream_from_the_library_over_the_dataset_shown() {
+        let mut app = app_with_rows();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::NQuads),
+                "<http://ex/d> <http://ex/p> \"4\" <http://ex/g> .\n".as_bytes(),
+            )
+            .unwrap();
+        let sparql = "SELECT ?s WHERE { ?s ?p ?o }";
+        let count = |app: &App, limit| {
+            let options = QueryOptions {
+                limit,
+                ..QueryOptions::default()
+            };
+            let mut solutions = app.query_iter(sparql, options).unwrap();
+            let count = solutions.by_ref().map(Result::unwrap).count();
+            (count, solutions.is_truncated())
+        };
+        assert_eq!(count(&app, None), (3, false));
+        assert_eq!(count(&app, Some(2)), (2, true));
+        app.dataset = Dataset::Union;
+        assert_eq!(count(&app, None), (4, false));
+        assert!(app.query_iter("SELECT", QueryOptions::default()).is_err());
+    }
+
+    #[test]
+    fn a_memory_budget_drops_snapshots_and_asks_before_passing_it() {
+        let mut app = App::new().unwrap();
+        let spec = fixture::Spec {
+            instances: 134,
+            ..fixture::Spec::default()
+        };
+        spec.load(0, &app.store).unwrap();
+        let triples = app.store.len().unwrap();
+        for limit in [1, 2] {
+            app.query
+                .set(format!("SELECT * WHERE {{ ?s ?p ?o }} LIMIT {limit}"));
+            update_all(&mut app, [Action::RunQuery]);
+        }
+        assert!(text(&render(&mut app)).contains("mem ~"));
+        let usage = app.memory_usage();
+        assert_eq!(usage.store, triples * memory::QUAD_BYTES);
+        assert!(usage.snapshots > 0 && usage.results > 0 && usage.cells > 0);
+
+        // 僅逾一字節：棄最舊之快照即足
+        app.config.memory_budget = Some(usage.total() - 1);
+        app.relieve_memory(0);
+        let kept: Vec<bool> = app
+            .history
+            .entries()
+            .iter()
+            .map(|entry| entry.snapshot.is_some())
+            .collect();
+        assert_eq!(kept, [true, false]);
+        let status = app.status.clone().unwrap();
+        assert!(status.ends_with("dropped 1 old snapshot"), "{status}");
+        update_all(&mut app, [Action::ShowMemory]);
+        assert!(text(&render(&mut app)).contains("snapshots  ~"));
+        app.popup = None;
+
+        // 十倍於三元組之行逾一批之上限，故必分批送回；預算僅容其一部
+        fn poll(app: &mut App) {
+            let start = Instant::now();
+            while app.running.is_some() && app.confirmation.is_none() {
+                app.poll_query();
+                thread::sleep(Duration::from_millis(1));
+                assert!(start.elapsed() < Duration::from_secs(10));
+            }
+        }
+        app.config.memory_budget = Some(app.memory_usage().total() + (100 << 10));
+        let query = "SELECT * WHERE { ?s ?p ?o VALUES ?n { 0 1 2 3 4 5 6 7 8 9 } } LIMIT 100000";
+        app.query.set(query.to_string());
+        app.update(Action::RunQuery).unwrap();
+        poll(&mut app);
+        let message = app.confirmation.as_ref().unwrap().message.clone();
+        assert!(message.contains("memory budget"), "{message}");
+        let shown = app.results.table().unwrap().rows.len();
+        assert!(shown > 0 && shown < 10 * triples, "{shown} rows");
+        app.update(Action::Confirm).unwrap();
+        assert_eq!(app.limit, Some(shown));
+        assert!(app.running.is_none());
+        let table = app.results.table().unwrap();
+        assert!(table.truncated.is_some() && table.rows.len() == shown);
+        assert!(app
+            .status
+            .as_ref()
+            .unwrap()
+            .ends_with("within the memory budget"));
+
+        // 答否則續取，不再問
+        app.limit = None;
+        app.update(Action::RunQuery).unwrap();
+        poll(&mut app);
+        assert!(app.confirmation.is_some());
+        app.update(Action::Cancel).unwrap();
+        poll(&mut app);
+        assert!(app.confirmation.is_none());
+        assert_eq!(app.results.table().unwrap().rows.len(), 10 * triples);
+    }
+
+    #[test]
+    fn edits_run_a_confirmed_update_and_refresh() {
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(&mut app, [Action::RunQuery, Action::EditValue]);
+        assert_eq!(app.mode, Mode::Edit);
+        assert_eq!(app.edit_line.input, "1");
+        type_text(&mut app, "0");
+        update_all(&mut app, [Action::Activate]);
+        let preview = app.confirmation.as_ref().unwrap().preview.clone().unwrap();
+        assert!(preview.starts_with(
+            "DELETE DATA {\n  <http://ex/a> <http://ex/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n}"
+        ));
+        assert!(preview.contains("INSERT DATA {\n  <http://ex/a> <http://ex/p> \"10\"^^"));
+        assert!(text(&render(&mut app)).contains("INSERT DATA {"));
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Changed <http://ex/p> of <http://ex/a> to \"10\"")
+        );
+        app.refresh_results();
+        app.wait_for_query();
+        let object = app.results.table().unwrap().rows[0][2].clone().unwrap();
+        assert_eq!(*object, Literal::from(10).into());
+
+        // 確認前三元組已去則不插入
+        update_all(&mut app, [Action::EditValue]);
+        type_text(&mut app, "0");
+        app.store
+            .update("DELETE WHERE { <http://ex/a> ?p ?o }")
+            .unwrap();
+        update_all(&mut app, [Action::Activate, Action::Confirm]);
+        assert_eq!(app.status.as_deref(), Some("Triple no longer present"));
+        assert_eq!(app.store.len().unwrap(), 2);
+
+        update_all(&mut app, [Action::ScrollColumns(1), Action::EditValue]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Only the object of a triple can be edited")
+        );
+        app.set_read_only(true);
+        update_all(&mut app, [Action::EditValue]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Read-only: values cannot be edited")
+        );
+    }
+
+    #[test]
+    fn marked_rows_are_deleted_and_recorded() {
+        let mut app = app_with_rows();
+        app.query
+            .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(
+            &mut app,
+            [Action::RunQuery, Action::ToggleMark, Action::ToggleMark],
+        );
+        assert_eq!(app.results.selected_row, 2);
+        assert!(text(&render(&mut app)).contains("2 marked · Ctrl+D deletes"));
+        update_all(&mut app, [Action::DeleteMarked]);
+        let confirmation = app.confirmation.as_ref().unwrap();
+        assert_eq!(
+            confirmation.message,
+            "Deleted 2 triples? Run this update (y/n)"
+        );
+        let preview = confirmation.preview.as_ref().unwrap();
+        assert!(preview.contains("<http://ex/a> <http://ex/p>"));
+        assert!(preview.contains("<http://ex/b> <http://ex/p>"));
+        assert!(!preview.contains("<http://ex/c>"));
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(app.store.len().unwrap(), 1);
+        assert_eq!(rows(&app), 1);
+
+        update_all(&mut app, [Action::ShowUpdates]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Z Deleted 2 triples"));
+        assert!(screen.contains("DELETE DATA {"));
+    }
+
+    #[test]
+    fn destructive_updates_write_a_checkpoint_first() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-checkpoint-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut app = app_with_rows();
+        app.set_checkpoints(dir.clone(), Some(1));
+        app.query
+            .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(
+            &mut app,
+            [Action::RunQuery, Action::DeleteMarked, Action::Confirm],
+        );
+        assert_eq!(
+            app.confirmation.as_ref().unwrap().message,
+            "No checkpoint: the default graph has over 1 quads. Go on without one? (y/n)"
+        );
+        update_all(&mut app, [Action::Cancel]);
+        assert_eq!(app.store.len().unwrap(), 3);
+
+        app.set_checkpoints(dir.clone(), None);
+        update_all(&mut app, [Action::DeleteMarked, Action::Confirm]);
+        assert_eq!(app.store.len().unwrap(), 2);
+        let status = app.status.clone().unwrap();
+        assert!(
+            status.starts_with("Deleted 1 triple · checkpoint "),
+            "{status}"
+        );
+        assert!(status.ends_with("-store.nq"));
+
+        update_all(&mut app, [Action::RestoreCheckpoint(None)]);
+        assert!(text(&render(&mut app)).contains("the store ·"));
+        update_all(
+            &mut app,
+            [Action::CloseHelp, Action::RestoreCheckpoint(Some(1))],
+        );
+        assert!(app
+            .confirmation
+            .as_ref()
+            .unwrap()
+            .message
+            .starts_with("Replace the store with checkpoint "));
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(app.store.len().unwrap(), 3);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        app.set_checkpoints(dir.clone(), Some(1));
+        update_all(
+            &mut app,
+            [Action::DeleteMarked, Action::Confirm, Action::Confirm],
+        );
+        assert_eq!(app.store.len().unwrap(), 2);
+        assert_eq!(app.status.as_deref(), Some("Deleted 1 triple"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn undo_restores_deleted_triples_in_reverse_order() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::UndoData(Some(1))]);
+        assert_eq!(app.status.as_deref(), Some("Nothing to undo"));
+        app.query
+            .set("SELECT ?s ?p ?o WHERE { ?s ?p ?o } ORDER BY ?s".to_string());
+        update_all(
+            &mut app,
+            [
+                Action::RunQuery,
+                Action::DeleteMarked,
+                Action::Confirm,
+                Action::DeleteMarked,
+                Action::Confirm,
+            ],
+        );
+        assert_eq!(app.store.len().unwrap(), 1);
+
+        update_all(&mut app, [Action::UndoData(Some(1))]);
+        let confirmation = app.confirmation.as_ref().unwrap();
+        assert_eq!(
+            confirmation.message,
+            "Undo: Deleted 1 triple? Run this update (y/n)"
+        );
+        assert!(confirmation
+            .preview
+            .as_ref()
+            .unwrap()
+            .starts_with("INSERT DATA {\n  <http://ex/b> <http://ex/p>"));
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(app.store.len().unwrap(), 2);
+        assert_eq!(rows(&app), 2);
+
+        update_all(&mut app, [Action::UndoData(None), Action::Confirm]);
+        assert_eq!(app.store.len().unwrap(), 3);
+        assert_eq!(app.status.as_deref(), Some("Undo: Deleted 1 triple"));
+        update_all(&mut app, [Action::UndoData(None)]);
+        assert_eq!(app.status.as_deref(), Some("Nothing to undo"));
+    }
+
+    #[test]
+    fn functional_properties_list_subjects_with_several_values() {
+        let mut app = App::new().unwrap();
+        update_all(&mut app, [Action::CheckFunctional(None)]);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("No owl:FunctionalProperty is declared"));
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "@prefix ex: <http://ex/> . \
+                 ex:born a <http://www.w3.org/2002/07/owl#FunctionalProperty> . \
+                 ex:mother a <http://www.w3.org/2002/07/owl#FunctionalProperty> . \
+                 ex:a ex:born 1815, 1816 ; ex:mother ex:m . ex:b ex:born 1906 ."
+                    .as_bytes(),
+            )
+            .unwrap();
+        update_all(&mut app, [Action::CheckFunctional(None)]);
+        let summary = app.table_text(app.results.table().unwrap());
+        assert_eq!(summary[1][0], "<http://ex/born>");
+        assert_eq!(summary[2][0], "<http://ex/mother>");
+        assert_eq!(
+            summary[2][1],
+            "\"0\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+        );
+
+        update_all(&mut app, [Action::ShowDetails]);
+        assert_eq!(
+            app.table_text(app.results.table().unwrap())[0],
+            ["?subject", "?values", "?value1", "?value2"]
+        );
+        assert_eq!(app.results.table().unwrap().rows.len(), 1);
+        assert_eq!(app.results.selected_column, Some(0));
+        update_all(&mut app, [Action::ShowDetails]);
+        assert_eq!(app.details.as_ref().unwrap().1.to_string(), "<http://ex/a>");
+
+        update_all(
+            &mut app,
+            [Action::CheckFunctional(Some("ex:nothing".to_string()))],
+        );
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Invalid predicate"));
+        update_all(
+            &mut app,
+            [Action::CheckFunctional(Some(
+                "<http://ex/born>".to_string(),
+            ))],
+        );
+        assert_eq!(app.results.table().unwrap().rows.len(), 1);
+    }
+
+    /// 模擬後台逐批送回之行。
+    fn streaming_app() -> App {
+        let mut app = App::new().unwrap();
+        app.results.current = Some(ResultSet {
+            query: app.query.string.clone(),
+            table: None,
+            error: None,
+            remote_requests: 0,
+            guarded: None,
+            source: Source::Query,
+            generation: app.generation,
+        });
+        app.receive(Message::Started(Some(vec![Variable::new_unchecked("n")])));
+        app
+    }
+
+    fn batch(start: i32) -> Vec<TermRow> {
+        (start..start + 256)
+            .map(|n| vec![Some(Arc::new(Term::from(Literal::from(n))))])
+            .collect()
+    }
+
+    #[test]
+    fn streamed_rows_append_without_moving_the_view() {
+        let mut app = streaming_app();
+        app.receive(Message::Rows(batch(0), 0));
+        update_all(&mut app, [Action::ScrollRows(100)]);
+        let before = text(&render(&mut app));
+        assert!(before.contains("256 rows… (running)"), "{before}");
+
+        app.receive(Message::Rows(batch(256), 0));
+        assert_eq!(app.results.selected_row, 100);
+        let after = text(&render(&mut app));
+        assert!(after.contains("512 rows… (running)"));
+        assert_eq!(
+            before.lines().position(|line| line.contains("\"100\"")),
+            after.lines().position(|line| line.contains("\"100\""))
+        );
+
+        app.receive(Message::Finished { truncated: None });
+        assert!(!text(&render(&mut app)).contains("running"));
+    }
+
+    #[test]
+    fn cancelling_keeps_the_loaded_rows_and_runs_the_pending_export() {
+        let path = std::env::temp_dir().join(format!("rdf-tui-stream-{}.tsv", std::process::id()));
+        let mut app = streaming_app();
+        app.receive(Message::Rows(batch(0), 0));
+        app.exports.pending = Some(Action::Export(QueryResultsFormat::Tsv.into(), path.clone()));
+
+        update_all(&mut app, [Action::CancelQuery]);
+        assert_eq!(rows(&app), 256);
+        assert_eq!(app.results.table().unwrap().progress, Progress::Cancelled);
+        assert_eq!(app.wait_for_export(), Some(Ok(256)));
+        assert!(text(&render(&mut app)).contains("256 rows (cancelled)"));
+        // 表頭一行
+        let exported = fs::read_to_string(&path).unwrap();
+        assert_eq!(exported.lines().count(), 257);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_first_frame_is_drawn_before_a_slow_file_arrives() {
+        // 命名管道於寫者開之前讀不出，猶極慢之文件
+        let path = std::env::temp_dir().join(format!("rdf-tui-slow-{}.nt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let made = std::process::Command::new("mkfifo").arg(&path).status();
+        assert!(made.unwrap().success());
+
+        let mut app = App::new().unwrap();
+        app.set_jobs(1);
+        app.queue_load(&path);
+        app.start_loading();
+        let screen = text(&render(&mut app));
+        assert!(
+            screen.contains("Loading") && screen.contains("slow"),
+            "{screen}"
+        );
+        assert!(app.store.is_empty().unwrap());
+        assert!(!app.poll_loading());
+
+        fs::write(&path, "<http://ex/a> <http://ex/p> \"1\" .\n").unwrap();
+        let start = Instant::now();
+        while !app.poll_loading() {
+            assert!(start.elapsed().as_secs() < 10, "loading never finished");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(app.store.len().unwrap(), 1);
+        assert!(app.status.as_deref().unwrap().starts_with("Loaded "));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn parallel_loading_reports_each_file() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-parallel-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<PathBuf> = ["a.nt", "b.nt", "broken.nt"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+        for (index, path) in paths.iter().enumerate().take(2) {
+            let triples: String = (0..100)
+                .map(|n| format!("<http://ex/{index}/{n}> <http://ex/p> \"{n}\" .\n"))
+                .collect();
+            fs::write(path, triples).unwrap();
+        }
+        fs::write(&paths[2], "<http://ex/a> <http://ex/p> .\n").unwrap();
+
+        let mut app = App::new().unwrap();
+        app.set_jobs(3);
+        app.open_all(paths);
+        let start = Instant::now();
+        while !app.poll_loading() {
+            assert!(start.elapsed().as_secs() < 10, "loading never finished");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(app.store.len().unwrap(), 200);
+        assert_eq!(app.sources.len(), 3);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Loaded 2 of 3 files"));
+        assert!(app.load_error.as_deref().unwrap().contains("broken.nt"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn urls_download_into_the_cache_and_then_load() {
+        use std::{io::Read, net::TcpListener};
+        let state = std::env::temp_dir().join(format!("rdf-tui-fetch-{}", std::process::id()));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let body = "<http://ex/a> <http://ex/p> \"1\" .\n<http://ex/b> <http://ex/p> \"2\" .\n";
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 1024]);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/n-triples\r\n\
+                 Content-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        let mut app = App::new().unwrap();
+        app.set_paths(Paths::new(None, Some(state.clone()), None));
+        app.config.download_retries = 0;
+        update_all(
+            &mut app,
+            [Action::Open(format!("http://{address}/data").into())],
+        );
+        app.start_loading();
+        assert!(text(&render(&mut app)).contains(&format!("Downloading http://{address}/data")));
+        app.wait_for_download();
+        assert!(app.status.as_deref().unwrap().starts_with("Downloaded "));
+        // 無擴展名者依其內容類型
+        let path = app.pending.front().unwrap().clone();
+        assert!(path.starts_with(state.join("downloads")));
+        assert_eq!(path.extension().unwrap(), "nt");
+        while let Some(path) = app.pending.pop_front() {
+            app.open(path);
+        }
+        assert_eq!(app.store.len().unwrap(), 2);
+
+        // 服務器已去，連接不上者報其所收
+        update_all(
+            &mut app,
+            [Action::Open(format!("http://{address}/gone.ttl").into())],
+        );
+        app.start_loading();
+        app.wait_for_download();
+        let error = app.load_error.clone().unwrap();
+        assert!(
+            error.starts_with(&format!("Fail to download http://{address}/gone.ttl: ")),
+            "{error}"
+        );
+        assert!(error.ends_with(", 0 B received"), "{error}");
+        fs::remove_dir_all(state).unwrap();
+    }
+
+    #[test]
+    fn files_too_large_for_memory_ask_before_loading() {
+        let path = std::env::temp_dir().join(format!("rdf-tui-large-{}.ttl", std::process::id()));
+        let spec = fixture::Spec {
+            instances: 50,
+            ..fixture::Spec::default()
+        };
+        fs::write(&path, spec.turtle(0)).unwrap();
+        let triples = spec.quads(0).count();
+        let wait = |app: &mut App| {
+            let start = Instant::now();
+            while !app.poll_loading() {
+                assert!(start.elapsed().as_secs() < 10, "loading never finished");
+                thread::sleep(Duration::from_millis(10));
+            }
+        };
+
+        // 約四萬五千字節之文件，估其存儲約 170K，逾可用之 256K 之半
+        let mut app = App::new().unwrap();
+        app.available_memory = Some(256 << 10);
+        app.queue_load(&path);
+        app.start_loading();
+        assert!(app.loading.is_none() && app.sources.is_empty());
+        let message = app.confirmation.as_ref().unwrap().message.clone();
+        assert!(
+            message.ends_with("over 50% of the 256K available, and this build has no on-disk store. Load anyway? (y/n)"),
+            "{message}"
+        );
+        update_all(&mut app, [Action::Cancel]);
+        app.start_loading();
+        assert!(app.confirmation.is_none() && app.loading.is_none());
+
+        app.queue_load(&path);
+        app.start_loading();
+        update_all(&mut app, [Action::Confirm]);
+        wait(&mut app);
+        assert_eq!(app.store.len().unwrap(), triples);
+
+        // 內存足，或不問者，徑載入
+        for (available, share) in [(Some(1 << 30), 50), (Some(1 << 10), 0), (None, 50)] {
+            let mut app = App::new().unwrap();
+            app.available_memory = available;
+            app.config.load_memory_share = share;
+            app.queue_load(&path);
+            app.start_loading();
+            assert!(app.confirmation.is_none());
+            wait(&mut app);
+            assert_eq!(app.store.len().unwrap(), triples);
+        }
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unlimited_rows_need_confirmation_and_exports_ignore_the_display_cap() {
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::ForceSetLimit(Some(2))]);
+        assert!(app.results.table().unwrap().truncated.is_some());
+
+        let path = std::env::temp_dir().join(format!("rdf-tui-limit-{}.tsv", std::process::id()));
+        let export = Action::Export(QueryResultsFormat::Tsv.into(), path.clone());
+        update_all(&mut app, [export.clone()]);
+        app.wait_for_export();
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 4);
+        app.set_export_limit(Some(1));
+        update_all(&mut app, [Action::Overwrite(Box::new(export))]);
+        app.wait_for_export();
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+        fs::remove_file(path).unwrap();
+
+        update_all(&mut app, [Action::SetLimit(None)]);
+        assert_eq!(app.limit, Some(2));
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(app.limit, None);
+        assert_eq!(rows(&app), 3);
+    }
+
+    #[test]
+    fn clearing_asks_with_the_quad_count() {
+        let mut app = app_with_rows();
+        app.store
+            .insert(QuadRef::new(
+                NamedNodeRef::new_unchecked("http://ex/a"),
+                NamedNodeRef::new_unchecked("http://ex/p"),
+                NamedNodeRef::new_unchecked("http://ex/b"),
+                NamedNodeRef::new_unchecked("http://ex/g"),
+            ))
+            .unwrap();
+        update_all(&mut app, [Action::Clear(Some("http://ex/g".to_string()))]);
+        assert_eq!(
+            app.confirmation.as_ref().unwrap().message,
+            "Remove 1 quads (graph <http://ex/g>)? (y/n)"
+        );
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(app.store.len().unwrap(), 3);
+
+        update_all(&mut app, [Action::Clear(None), Action::Cancel]);
+        assert_eq!(app.store.len().unwrap(), 3);
+        update_all(&mut app, [Action::Clear(None), Action::Confirm]);
+        assert!(app.store.is_empty().unwrap());
+        assert_eq!(rows(&app), 0);
+        // 二清空皆經存儲線程
+        assert_eq!(app.backend.generation(), 2);
+    }
+
+    #[test]
+    fn graphs_are_copied_moved_and_dropped_after_confirmation() {
+        let mut app = app_with_rows();
+        let finish = |app: &mut App| {
+            while app.graph_job.is_some() {
+                app.poll_graph_job();
+                std::thread::yield_now();
+            }
+        };
+        let g = GraphName::from(NamedNode::new_unchecked("http://ex/g"));
+        let h = GraphName::from(NamedNode::new_unchecked("http://ex/h"));
+        update_all(
+            &mut app,
+            [Action::GraphOp(GraphOp::Copy(
+                GraphName::DefaultGraph,
+                g.clone(),
+            ))],
+        );
+        assert_eq!(
+            app.confirmation.as_ref().unwrap().message,
+            "Copy 3 quads from the default graph to <http://ex/g>? (y/n)"
+        );
+        update_all(&mut app, [Action::Confirm]);
+        finish(&mut app);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Copied 3 quads from the default graph to <http://ex/g>")
+        );
+        assert_eq!(app.store.len().unwrap(), 6);
+
+        update_all(
+            &mut app,
+            [Action::GraphOp(GraphOp::Move(g.clone(), h.clone()))],
+        );
+        update_all(&mut app, [Action::Confirm]);
+        finish(&mut app);
+        assert_eq!(graph_len(&app.store, &h), 3);
+        update_all(&mut app, [Action::GraphOp(GraphOp::Drop(g))]);
+        assert_eq!(app.status.as_deref(), Some("No graph <http://ex/g>"));
+
+        update_all(&mut app, [Action::GraphOp(GraphOp::Drop(h))]);
+        assert_eq!(
+            app.confirmation.as_ref().unwrap().message,
+            "Drop <http://ex/h> and its 3 quads? (y/n)"
+        );
+        update_all(&mut app, [Action::Confirm]);
+        finish(&mut app);
+        assert_eq!(app.store.len().unwrap(), 3);
+
+        app.set_read_only(true);
+        update_all(
+            &mut app,
+            [Action::GraphOp(GraphOp::Drop(GraphName::DefaultGraph))],
+        );
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Read-only: graphs cannot be changed")
+        );
+    }
+
+    #[test]
+    fn graphs_remember_their_source_across_reloads() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-origin-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.trig"), dir.join("b.ttl"));
+        fs::write(
+            &a,
+            "<http://ex/g> { <http://ex/s> <http://ex/p> 1, 2 }
+",
+        )
+        .unwrap();
+        fs::write(
+            &b,
+            "<http://ex/t> <http://ex/p> 3 .
+",
+        )
+        .unwrap();
+        let mut app = App::new().unwrap();
+        app.open(a.clone());
+        app.open(b);
+        let graphs: Vec<(Option<&str>, &str, usize)> = app
+            .provenance
+            .entries()
+            .iter()
+            .map(|e| (e.graph.as_deref(), e.short_source(), e.triples))
+            .collect();
+        assert_eq!(
+            graphs,
+            [(Some("<http://ex/g>"), "a.trig", 2), (None, "b.ttl", 1)]
+        );
+
+        update_all(&mut app, [Action::Reload]);
+        app.wait_for_loading();
+        assert_eq!(app.status.as_deref(), Some("Reloaded 2 files"));
+        assert_eq!(app.provenance.entries().len(), 2);
+
+        app.query
+            .set("SELECT ?g ?s WHERE { GRAPH ?g { ?s ?p ?o } } LIMIT 1".to_string());
+        update_all(&mut app, [Action::ToggleOrigin]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("origin"));
+        assert!(screen.contains("a.trig"));
+        // 導出與複製不含此列
+        assert_eq!(
+            app.table_text(app.results.table().unwrap())[0],
+            ["?g", "?s"]
+        );
+
+        update_all(&mut app, [Action::ShowGraphs]);
+        assert_eq!(rows(&app), 2);
+        update_all(&mut app, [Action::ShowDetails]);
+        assert_eq!(
+            app.query.string,
+            "SELECT ?s ?p ?o WHERE { GRAPH <http://ex/g> { ?s ?p ?o } }"
+        );
+        assert_eq!(rows(&app), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn opening_a_loaded_file_offers_a_reload() {
+        let path = std::env::temp_dir().join(format!("rdf-tui-open-{}.ttl", std::process::id()));
+        fs::write(&path, "<http://ex/a> <http://ex/p> [ <http://ex/q> 1 ] .\n").unwrap();
+        let mut app = App::new().unwrap();
+        app.open(path.clone());
+        assert_eq!(app.store.len().unwrap(), 2);
+
+        update_all(&mut app, [Action::Open(path.clone())]);
+        assert!(app.pending.is_empty());
+        assert!(app
+            .confirmation
+            .as_ref()
+            .unwrap()
+            .message
+            .ends_with("is already loaded. Reload all files? (y/n)"));
+        update_all(&mut app, [Action::Confirm]);
+        while let Some(path) = app.pending.pop_front() {
+            app.open(path);
+        }
+        assert_eq!(app.store.len().unwrap(), 2);
+        assert_eq!(app.sources, std::slice::from_ref(&path));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unbounded_explosive_queries_are_limited_and_say_so() {
+        let mut app = app_with_rows();
+        app.limit = Some(2);
+        app.run_query();
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Explore (LIMIT 2 added)"), "{screen}");
+        assert!(app.status.as_deref().unwrap().starts_with("No LIMIT"));
+        assert_eq!(rows(&app), 2);
+        assert!(app.results.table().unwrap().truncated.is_some());
+        // 查詢本身不變
+        assert_eq!(
+            app.results.current.as_ref().unwrap().query,
+            Query::new().string
+        );
+
+        app.query
+            .set("SELECT * WHERE { ?s ?p ?o } LIMIT 3".to_string());
+        app.run_query();
+        app.wait_for_query();
+        assert_eq!(app.results.current.as_ref().unwrap().guarded, None);
+
+        app.set_guard(false);
+        app.query.set(Query::new().string);
+        app.limit = Some(1);
+        app.run_query();
+        app.wait_for_query();
+        assert_eq!(app.results.current.as_ref().unwrap().guarded, None);
+        assert_eq!(rows(&app), 1);
+    }
+
+    #[test]
+    fn inline_data_is_numbered_and_survives_a_reload() {
+        let mut app = App::new().unwrap();
+        app.load_data(
+            "@prefix ex: <http://ex/> . ex:a ex:b ex:c .",
+            RdfFormat::Turtle,
+        );
+        app.load_data("<a> <b> .", RdfFormat::Turtle);
+        assert_eq!(app.store.len().unwrap(), 1);
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Fail to load inline data #2: "));
+        app.load_data("<a> <b> <c> .", RdfFormat::NTriples);
+        assert!(app
+            .load_error
+            .as_deref()
+            .unwrap()
+            .contains("inline data #3"));
+        app.load_data("<a> <b> <c> .", RdfFormat::Turtle);
+        assert!(app
+            .store
+            .contains(QuadRef::new(
+                NamedNodeRef::new_unchecked("http://inline.invalid/4/a"),
+                NamedNodeRef::new_unchecked("http://inline.invalid/4/b"),
+                NamedNodeRef::new_unchecked("http://inline.invalid/4/c"),
+                GraphNameRef::DefaultGraph,
+            ))
+            .unwrap());
+
+        update_all(&mut app, [Action::Reload]);
+        app.wait_for_loading();
+        assert_eq!(app.store.len().unwrap(), 2);
+        // 唯二成功之載入經存儲線程計入世代；重新載入因 #2 而敗，仍讀舊存儲
+        assert_eq!(app.backend.generation(), 2);
+        assert_eq!(app.backend.store().len().unwrap(), 2);
+    }
+
+    #[test]
+    fn a_failed_reload_keeps_the_previous_store_and_results() {
+        let path = std::env::temp_dir().join(format!("rdf-tui-atomic-{}.ttl", std::process::id()));
+        fs::write(&path, "<http://ex/a> <http://ex/p> 1, 2 .\n").unwrap();
+        let mut app = App::new().unwrap();
+        app.open(path.clone());
+        let old = app.store.clone();
+        render(&mut app);
+        assert_eq!(rows(&app), 2);
+
+        fs::write(
+            &path,
+            "<http://ex/a> <http://ex/p> 3 .\n<http://ex/b> <http://ex/p> .\n",
+        )
+        .unwrap();
+        update_all(&mut app, [Action::Reload]);
+        app.wait_for_loading();
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Reload failed, the previous data is kept"));
+        assert!(app.load_error.is_some());
+        assert_eq!(app.store.len().unwrap(), 2);
+        assert_eq!(rows(&app), 2);
+
+        fs::write(&path, "<http://ex/a> <http://ex/p> 3 .\n").unwrap();
+        update_all(&mut app, [Action::Reload]);
+        app.wait_for_loading();
+        assert!(app.load_error.is_none());
+        render(&mut app);
+        assert_eq!(rows(&app), 1);
+        // 舊存儲不變，進行中之查詢仍見之
+        assert_eq!(old.len().unwrap(), 2);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn imported_results_skip_the_store_until_closed() {
+        let dir = std::env::temp_dir();
+        let csv = dir.join(format!("rdf-tui-import-{}.csv", std::process::id()));
+        let tsv = csv.with_extension("tsv");
+        fs::write(&csv, "s,label\nhttp://ex/x,\"Hello, world\"\n_:z,\n").unwrap();
+        let mut app = app_with_rows();
+        app.open(csv.clone());
+        assert_eq!(app.imported.as_ref(), Some(&csv));
+        assert_eq!(rows(&app), 2);
+        assert!(app.sources.is_empty());
+
+        update_all(&mut app, [Action::SwitchMode]);
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(rows(&app), 2);
+        app.start_export(
+            QueryResultsFormat::Tsv.into(),
+            Sink::File(tsv.clone()),
+            false,
+        )
+        .unwrap();
+        assert_eq!(app.wait_for_export(), Some(Ok(2)));
+        assert!(fs::read_to_string(&tsv)
+            .unwrap()
+            .contains("<http://ex/x>\t\"Hello, world\""));
+
+        update_all(&mut app, [Action::CancelQuery]);
+        assert!(app.imported.is_none());
+        assert_eq!(rows(&app), 3);
+        fs::remove_file(csv).unwrap();
+        fs::remove_file(tsv).unwrap();
+    }
+
+    #[test]
+    fn labels_are_fetched_for_the_visible_rows_and_exported() {
+        let mut app = app_with_rows();
+        app.store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "<http://ex/a> <http://www.w3.org/2000/01/rdf-schema#label> \"Alpha\" .".as_bytes(),
+            )
+            .unwrap();
+        app.query
+            .set("SELECT ?s ?o WHERE { ?s <http://ex/p> ?o }".to_string());
+        update_all(&mut app, [Action::RunQuery, Action::ToggleLabels]);
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("<http://ex/a>…"));
+        // 所繪之行一批交後台，問畢再繪則標籤已到，無標籤者照常顯示
+        app.poll_labels();
+        assert!(app.label_fetch.is_some());
+        while app.label_fetch.is_some() {
+            thread::sleep(std::time::Duration::from_millis(5));
+            app.poll_labels();
+        }
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("Alpha"));
+        assert!(screen.contains("<http://ex/b>") && !screen.contains("<http://ex/b>…"));
+        assert!(app.label_fetch.is_none());
+
+        // 導出所示之單元格者先解未到之標籤
+        app.store_changed();
+        let path = std::env::temp_dir().join(format!("rdf-tui-labels-{}.md", std::process::id()));
+        update_all(
+            &mut app,
+            [Action::Export(ExportFormat::Markdown, path.clone())],
+        );
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(
+            written.contains("| Alpha ") && !written.contains('…'),
+            "{written}"
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,710 @@
+//! 標籤頁：各頁之存儲與結果，換頁、開關頁，及於諸頁執行同一查詢。
+
+use super::{
+    App, Confirmation, Mode, Popup, Progress, Query, ResultSet, ResultTable, Results, Source,
+};
+use crate::{
+    action::Action,
+    backend::{Settings, StoreWorker},
+    dataset::Dataset,
+    diff::Partition,
+    display::{group_digits, sanitize},
+    download,
+    edit::Update,
+    generation::{Cached, Generation},
+    header,
+    hierarchy::Hierarchy,
+    history::History,
+    label::LabelCache,
+    memory,
+    neighborhood::Neighborhood,
+    overwrite::write_then_rename,
+    prepared::Prepared,
+    provenance::Provenance,
+    tabdo::{TabRun, TabRuns},
+    util::offset_index,
+    watch::Watch,
+    worker::{Job, Worker},
+};
+use oxigraph::{io::RdfFormat, model::Term, store::Store};
+use std::{
+    cell::Cell,
+    collections::{HashSet, VecDeque},
+    fs,
+    path::{absolute, PathBuf},
+};
+
+impl App {
+    /// 執行頁之操作。餘者原樣返回，交下一部分。
+    pub(super) fn update_tabs(&mut self, action: Action) -> anyhow::Result<Option<Action>> {
+        match action {
+            Action::CycleTab(_) if self.tabs.pages.len() < 2 => {
+                self.status = Some(
+                    self.tr("Only one tab is open (:tabnew opens another)")
+                        .to_string(),
+                );
+            }
+            Action::CycleTab(delta) => {
+                let count = self.tabs.pages.len() as isize;
+                let index = (self.tabs.current as isize + delta).rem_euclid(count);
+                self.switch_tab(index as usize);
+            }
+            Action::NewTab(path) => self.new_tab(path)?,
+            Action::CloseTab if self.query.is_dirty() && self.tabs.pages.len() > 1 => {
+                self.confirmation = Some(Confirmation {
+                    message: self
+                        .tr("Close the tab? Unsaved query will be lost (y/n)")
+                        .to_string(),
+                    action: Action::ForceCloseTab,
+                    preview: None,
+                    alternative: None,
+                });
+            }
+            Action::CloseTab | Action::ForceCloseTab => self.close_tab(),
+            Action::RunInTabs => self.run_in_tabs(),
+            Action::ShowTabRuns if self.tabs.runs.is_none() => {
+                self.status = Some(self.tr("Nothing compared yet (:tabdo run)").to_string());
+            }
+            Action::ShowTabRuns => self.popup = Some(Popup::TabRuns),
+            Action::OpenTabRun => self.open_tab_run(),
+            Action::CompareTabRun => self.compare_tab_run(),
+            Action::YankTabRuns => {
+                if let Some(runs) = &self.tabs.runs {
+                    self.clipboard = Some(runs.markdown());
+                    self.status = Some("Copied the comparison as Markdown".to_string());
+                }
+            }
+            Action::ExportTabRuns(path) => {
+                let Some(runs) = &self.tabs.runs else {
+                    self.status = Some(self.tr("Nothing compared yet (:tabdo run)").to_string());
+                    return Ok(None);
+                };
+                let text = runs.markdown();
+                self.status = Some(
+                    match write_then_rename(&path, false, |partial| Ok(fs::write(partial, text)?)) {
+                        Ok(()) => format!("Exported the comparison to {}", path.display()),
+                        Err(error) => format!("Fail to export the comparison: {error:#}"),
+                    },
+                );
+            }
+            Action::ScrollPopup(delta) if matches!(self.popup, Some(Popup::TabRuns)) => {
+                if let Some(runs) = &mut self.tabs.runs {
+                    runs.selected = offset_index(runs.selected, delta, runs.runs.len());
+                }
+            }
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+
+    /// 後台為當前之頁所行而未畢者，如查詢與載入。其結果將入當前頁之存儲，故未畢不換頁。
+    fn tab_busy(&self) -> Option<&'static str> {
+        if self.running.is_some() || self.held_rows.is_some() || self.watch_run.is_some() {
+            Some("the running query")
+        } else if self.loading.is_some() || !self.pending.is_empty() || self.download.is_some() {
+            Some("the files loading")
+        } else if self.exports.job.is_some() {
+            Some("the running export")
+        } else if self.graph_job.is_some() {
+            Some("the graph operation")
+        } else if self.count.is_some() || self.incoming_count.is_some() {
+            Some("the running count")
+        } else if self.tabs.runs.as_ref().is_some_and(TabRuns::is_running) {
+            Some("the queries in every tab")
+        } else {
+            None
+        }
+    }
+
+    /// 換至第 `index` 頁，返回是否已換。
+    pub(super) fn switch_tab(&mut self, index: usize) -> bool {
+        if let Some(busy) = self.tab_busy() {
+            self.status = Some(format!("Wait for {busy} before switching tabs"));
+            return false;
+        }
+        let current = self.tabs.current;
+        self.swap_tab(current);
+        self.swap_tab(index);
+        self.tabs.current = index;
+        // 他頁之存儲所生者皆棄之
+        self.mode = Mode::Browse;
+        self.popup = None;
+        self.label_fetch = None;
+        self.hierarchy_refresh = None;
+        self.neighborhood_refresh = None;
+        self.label_window.set(None);
+        self.grouped_incoming = None;
+        self.results.diff_values = [None, None];
+        self.pending_view = None;
+        self.results.carried_column = None;
+        self.results.anchor = None;
+        if self.config.terminal_title {
+            self.window_title = Some(self.describe_dataset());
+        }
+        self.status = Some(format!(
+            "Tab {} of {}: {}",
+            index + 1,
+            self.tabs.pages.len(),
+            tab_name(&self.sources)
+        ));
+        true
+    }
+
+    /// 互換應用之諸項與第 `index` 頁所存者。
+    fn swap_tab(&mut self, index: usize) {
+        use std::mem::swap;
+        let tab = &mut self.tabs.pages[index];
+        swap(&mut self.store, &mut tab.store);
+        swap(&mut self.backend, &mut tab.backend);
+        swap(&mut self.generation, &mut tab.generation);
+        swap(&mut self.dataset, &mut tab.dataset);
+        self.results.swap_tab(&mut tab.results);
+        swap(&mut self.prepared, &mut tab.prepared);
+        swap(&mut self.sources, &mut tab.sources);
+        swap(&mut self.inline, &mut tab.inline);
+        swap(&mut self.imported, &mut tab.imported);
+        swap(&mut self.visited_imports, &mut tab.visited_imports);
+        swap(&mut self.provenance, &mut tab.provenance);
+        swap(&mut self.load_error, &mut tab.load_error);
+        swap(&mut self.query, &mut tab.query);
+        swap(&mut self.history, &mut tab.history);
+        swap(&mut self.selected_history, &mut tab.selected_history);
+        swap(&mut self.hierarchy, &mut tab.hierarchy);
+        swap(&mut self.neighborhood, &mut tab.neighborhood);
+        swap(&mut self.labels, &mut tab.labels);
+        swap(&mut self.details, &mut tab.details);
+        swap(&mut self.quads, &mut tab.quads);
+        swap(&mut self.watch, &mut tab.watch);
+        swap(&mut self.undo, &mut tab.undo);
+        swap(&mut self.recent_updates, &mut tab.recent_updates);
+    }
+
+    /// 開新頁，有路徑則載入之於其中。
+    pub(super) fn new_tab(&mut self, path: Option<PathBuf>) -> anyhow::Result<()> {
+        self.tabs.pages.push(Tab::new()?);
+        if !self.switch_tab(self.tabs.pages.len() - 1) {
+            self.tabs.pages.pop();
+            return Ok(());
+        }
+        if let Some(path) = path {
+            match download::is_url(&path) {
+                true => self.pending.push_back(path),
+                false => self.pending.push_back(absolute(&path).unwrap_or(path)),
+            }
+        }
+        Ok(())
+    }
+
+    /// 關當前之頁而換至其後者，末頁則換至其前者。所關之頁連同其存儲棄之。
+    pub(super) fn close_tab(&mut self) {
+        if self.tabs.pages.len() < 2 {
+            self.status = Some(
+                self.tr("The last tab stays open (:quit to leave)")
+                    .to_string(),
+            );
+            return;
+        }
+        let closed = self.tabs.current;
+        let next = match closed + 1 < self.tabs.pages.len() {
+            true => closed + 1,
+            false => closed - 1,
+        };
+        let name = tab_name(&self.sources);
+        if !self.switch_tab(next) {
+            return;
+        }
+        self.tabs.pages.remove(closed);
+        self.tabs.runs = None;
+        if next > closed {
+            self.tabs.current -= 1;
+        }
+        log::info!("tab closed name={name} tabs={}", self.tabs.pages.len());
+        self.status = Some(format!(
+            "Closed {name}; tab {} of {}: {}",
+            self.tabs.current + 1,
+            self.tabs.pages.len(),
+            tab_name(&self.sources)
+        ));
+    }
+
+    /// 各頁之名，依其序。
+    pub(super) fn tab_names(&self) -> Vec<String> {
+        self.tabs
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| match index == self.tabs.current {
+                true => tab_name(&self.sources),
+                false => tab_name(&tab.sources),
+            })
+            .collect()
+    }
+
+    /// 各頁所估之字節數，依其序。
+    pub(super) fn tab_memory(&self) -> Vec<usize> {
+        self.tabs
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| match index == self.tabs.current {
+                true => self.memory_usage().total(),
+                false => tab.memory(),
+            })
+            .collect()
+    }
+
+    /// 於各頁並行當前之查詢，各依其頁之默認圖，畢則浮窗比較其行數。
+    pub(super) fn run_in_tabs(&mut self) {
+        if self.tabs.pages.len() < 2 {
+            self.status = Some(
+                self.tr("Only one tab is open (:tabnew opens another)")
+                    .to_string(),
+            );
+            return;
+        }
+        if let Some(busy) = self.tab_busy() {
+            self.status = Some(format!("Wait for {busy} before running in every tab"));
+            return;
+        }
+        let names = self.tab_names();
+        let mut runs = TabRuns::new(self.query.string.clone());
+        for (index, name) in names.into_iter().enumerate() {
+            let tab = &self.tabs.pages[index];
+            let (store, prepared, dataset) = match index == self.tabs.current {
+                true => (&self.store, &self.prepared, &self.dataset),
+                false => (&tab.store, &tab.prepared, &tab.dataset),
+            };
+            let job = Job {
+                query: runs.query.clone(),
+                federated: self.federated,
+                timeout: self.config.timeout,
+                limit: self.limit,
+                caps: self.caps(),
+                prepared: prepared.clone(),
+                dataset: dataset.clone(),
+            };
+            runs.push(index, name, Worker::spawn(store, job));
+        }
+        log::info!("tabdo run tabs={}", runs.runs.len());
+        runs.selected = self.tabs.current;
+        self.tabs.runs = Some(runs);
+        self.popup = Some(Popup::TabRuns);
+    }
+
+    /// 併入各頁已到之行，`wait` 則等其皆畢。皆畢時報之。
+    pub(super) fn poll_tab_runs(&mut self, wait: bool) {
+        let Some(runs) = &mut self.tabs.runs else {
+            return;
+        };
+        if !runs.is_running() {
+            return;
+        }
+        match wait {
+            true => runs.wait(),
+            false => runs.poll(),
+        }
+        if !runs.is_running() {
+            let failed = runs.runs.iter().filter(|run| run.error.is_some()).count();
+            self.status = Some(match failed {
+                0 => format!("Ran the query in {} tabs", runs.runs.len()),
+                _ => format!("Ran the query in {} tabs; {failed} failed", runs.runs.len()),
+            });
+        }
+    }
+
+    /// 換至所選之頁，示其結果，查詢有異則代之，Ctrl+U 可復。
+    pub(super) fn open_tab_run(&mut self) {
+        let Some(runs) = &self.tabs.runs else {
+            return;
+        };
+        let run = &runs.runs[runs.selected];
+        if run.is_running() {
+            self.status = Some(format!("Tab {} is still running", run.tab + 1));
+            return;
+        }
+        let (index, tab) = (runs.selected, run.tab);
+        if !self.show_tab_query(tab) {
+            return;
+        }
+        let Some(runs) = &self.tabs.runs else {
+            return;
+        };
+        let run = &runs.runs[index];
+        let table = run.variables.is_some().then(|| self.tab_run_table(run));
+        self.results.selected_row = 0;
+        self.results.selected_column = None;
+        self.results.on_header = false;
+        self.results.on_footer = false;
+        self.results.current = Some(ResultSet {
+            query: self.query.string.clone(),
+            table,
+            error: run.error.clone(),
+            remote_requests: 0,
+            guarded: None,
+            source: Source::Query,
+            generation: self.generation,
+        });
+    }
+
+    /// 首按 `c` 記所選為舊者；再按則換至所選之頁，逐行比較兩者之結果。
+    pub(super) fn compare_tab_run(&mut self) {
+        let Some(runs) = &mut self.tabs.runs else {
+            return;
+        };
+        let selected = runs.selected;
+        let base = match runs.base {
+            None => {
+                runs.base = Some(selected);
+                self.status = Some(format!(
+                    "Comparing from tab {}: choose another and press c",
+                    runs.runs[selected].tab + 1
+                ));
+                return;
+            }
+            Some(base) if base == selected => {
+                runs.base = None;
+                self.status = None;
+                return;
+            }
+            Some(base) => base,
+        };
+        if let Err(reason) = runs.comparable(base, selected) {
+            self.status = Some(reason);
+            return;
+        }
+        runs.base = None;
+        let (old, tab) = (runs.runs[base].tab, runs.runs[selected].tab);
+        if !self.show_tab_query(tab) {
+            return;
+        }
+        let Some(runs) = &self.tabs.runs else {
+            return;
+        };
+        let (old_run, new_run) = (&runs.runs[base], &runs.runs[selected]);
+        let partition = Partition::between(&old_run.rows, &new_run.rows);
+        let current = self.tab_run_table(new_run);
+        self.status = Some(format!(
+            "Tab {} against tab {}: {} added, {} removed (D lists the removed)",
+            tab + 1,
+            old + 1,
+            group_digits(partition.added.len()),
+            group_digits(partition.removed.len())
+        ));
+        self.open_diff(partition, current, self.generation);
+    }
+
+    /// 換至 `tab` 頁，關浮窗，並以所比之查詢代其編輯器中者。已改之查詢可以 Ctrl+U 復之。
+    fn show_tab_query(&mut self, tab: usize) -> bool {
+        if tab != self.tabs.current && !self.switch_tab(tab) {
+            return false;
+        }
+        self.popup = None;
+        self.mode = Mode::Browse;
+        let Some(query) = self.tabs.runs.as_ref().map(|runs| runs.query.clone()) else {
+            return false;
+        };
+        if self.query.string != query {
+            self.query.replace(query, "tab comparison");
+        }
+        true
+    }
+
+    /// 一頁所得之行為表。
+    fn tab_run_table(&self, run: &TabRun) -> ResultTable {
+        let mut table = ResultTable::new(run.variables.clone().unwrap_or_default());
+        table.append(run.rows.clone(), run.term_bytes);
+        table.truncated = run.truncated;
+        table.progress = Progress::Complete;
+        table.elapsed = Some(run.duration);
+        if self.config.stable_order && !header::ordered(&self.query.string) {
+            table.stabilize();
+        }
+        table
+    }
+}
+
+/// 標籤頁之部分：諸頁及當前者，與 `:tabdo run` 之結果。
+pub(super) struct Tabs {
+    /// 諸標籤頁之數據集。當前者之槽存一空頁，換頁時與應用之諸項互換。
+    pub(super) pages: Vec<Tab>,
+    /// 當前之標籤頁。
+    pub(super) current: usize,
+    /// `:tabdo run` 於各頁所行之查詢。關頁則棄之。
+    pub(super) runs: Option<TabRuns>,
+}
+
+impl Tabs {
+    /// 僅一頁，即當前者。
+    pub(super) fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            pages: vec![Tab::new()?],
+            current: 0,
+            runs: None,
+        })
+    }
+}
+
+/// 一標籤頁所有者：存儲及其線程、文件、查詢、結果與諸緩存。非當前之頁存於此；當前者即在應用之諸項
+/// 中。頁關則棄之，其存儲隨之釋放。
+pub(super) struct Tab {
+    store: Store,
+    backend: StoreWorker,
+    generation: Generation,
+    dataset: Dataset,
+    results: Results,
+    prepared: Prepared,
+    sources: Vec<PathBuf>,
+    inline: Vec<(String, RdfFormat)>,
+    imported: Option<PathBuf>,
+    visited_imports: HashSet<String>,
+    provenance: Provenance,
+    load_error: Option<String>,
+    pub(super) query: Query,
+    history: History,
+    selected_history: usize,
+    hierarchy: Option<Cached<Hierarchy>>,
+    neighborhood: Option<Cached<Neighborhood>>,
+    labels: LabelCache,
+    details: Option<(String, Term)>,
+    quads: Cell<Option<(Generation, usize)>>,
+    watch: Option<Watch>,
+    undo: Vec<Update>,
+    recent_updates: VecDeque<(u64, Update)>,
+}
+
+impl Tab {
+    /// 空存儲之新頁。
+    pub(super) fn new() -> anyhow::Result<Self> {
+        let store = Store::new()?;
+        Ok(Self {
+            backend: StoreWorker::spawn(store.clone(), Settings::default()),
+            store,
+            generation: Generation::default(),
+            dataset: Dataset::Default,
+            results: Results::default(),
+            prepared: Prepared::default(),
+            sources: vec![],
+            inline: vec![],
+            imported: None,
+            visited_imports: HashSet::new(),
+            provenance: Provenance::default(),
+            load_error: None,
+            query: Query::new(),
+            history: History::default(),
+            selected_history: 0,
+            hierarchy: None,
+            neighborhood: None,
+            labels: LabelCache::default(),
+            details: None,
+            quads: Cell::new(None),
+            watch: None,
+            undo: vec![],
+            recent_updates: VecDeque::new(),
+        })
+    }
+
+    /// 所估之字節數，各項同於 [`Usage`]。
+    fn memory(&self) -> usize {
+        let quads = match self.quads.get() {
+            Some((generation, quads)) if generation == self.generation => quads,
+            _ => {
+                let quads = self.store.len().unwrap_or(0);
+                self.quads.set(Some((self.generation, quads)));
+                quads
+            }
+        };
+        let table = self.results.current.as_ref().and_then(|r| r.table.as_ref());
+        quads * memory::QUAD_BYTES
+            + table.map_or(0, |table| table.memory() + table.cell_bytes())
+            + self.results.previous.as_ref().map_or(0, |(query, table)| {
+                memory::text(query) + table.memory() + table.cell_bytes()
+            })
+            + self.history.snapshot_bytes()
+            + self.hierarchy.as_ref().map_or(0, |tree| tree.bytes())
+            + self.neighborhood.as_ref().map_or(0, |tree| tree.bytes())
+            + self
+                .undo
+                .iter()
+                .chain(self.recent_updates.iter().map(|(_, update)| update))
+                .map(Update::bytes)
+                .sum::<usize>()
+    }
+}
+
+/// 標籤頁之名：首個文件之名，多者附其餘之數。未載入文件者為 `untitled`。
+pub(super) fn tab_name(sources: &[PathBuf]) -> String {
+    let Some(first) = sources.first() else {
+        return "untitled".to_string();
+    };
+    let name = first.file_name().unwrap_or(first.as_os_str());
+    let mut name = sanitize(&name.to_string_lossy()).into_owned();
+    if sources.len() > 1 {
+        name.push_str(&format!(" +{}", sources.len() - 1));
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::{app_with_rows, render, rows, text, type_text, update_all};
+    use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+    use ratatui::crossterm::event::KeyEvent;
+    use std::fs;
+
+    #[test]
+    fn tabs_keep_their_own_datasets_and_free_them_on_close() {
+        let path = std::env::temp_dir().join(format!("rdf-tui-tab-{}.ttl", std::process::id()));
+        fs::write(&path, "<http://ex/x> <http://ex/q> 9 .\n").unwrap();
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::CycleTab(1)]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Only one tab is open (:tabnew opens another)")
+        );
+        assert!(!text(&render(&mut app)).contains(" 1 untitled "));
+
+        update_all(&mut app, [Action::NewTab(Some(path.clone()))]);
+        while let Some(path) = app.pending.pop_front() {
+            app.open(path);
+        }
+        app.refresh_results();
+        app.wait_for_query();
+        assert_eq!((app.tabs.current, rows(&app)), (1, 1));
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let screen = text(&render(&mut app));
+        assert!(
+            screen.contains(&format!(" 1 untitled  2 {name} ")),
+            "{screen}"
+        );
+
+        // 各頁之查詢、所選與存儲各自獨立
+        update_all(&mut app, [Action::SwitchMode]);
+        type_text(&mut app, " ");
+        update_all(&mut app, [Action::SwitchMode]);
+        assert_eq!(
+            app.map_key(KeyEvent::new(KeyCode::PageUp, KeyModifiers::CONTROL)),
+            [Action::CycleTab(-1)]
+        );
+        update_all(&mut app, [Action::CycleTab(-1), Action::SelectLast]);
+        assert_eq!(
+            (app.tabs.current, rows(&app), app.results.selected_row),
+            (0, 3, 2)
+        );
+        assert!(!app.query.is_dirty());
+        assert_eq!(app.status.as_deref(), Some("Tab 1 of 2: untitled"));
+        update_all(&mut app, [Action::ShowMemory]);
+        let screen = text(&render(&mut app));
+        assert!(
+            screen.contains("(this tab)") && screen.contains("all tabs"),
+            "{screen}"
+        );
+        update_all(&mut app, [Action::CloseHelp, Action::CycleTab(1)]);
+        assert_eq!(
+            (app.tabs.current, rows(&app), app.results.selected_row),
+            (1, 1, 0)
+        );
+        assert!(app.query.is_dirty());
+
+        // 查詢未保存者先確認；關後換至前頁，所關之存儲已棄
+        update_all(&mut app, [Action::Quit]);
+        assert!(app.confirmation.is_some());
+        update_all(
+            &mut app,
+            [Action::Cancel, Action::CycleTab(-1), Action::Quit],
+        );
+        assert!(app.confirmation.is_some(), "another tab's unsaved query");
+        update_all(
+            &mut app,
+            [Action::Cancel, Action::CycleTab(1), Action::CloseTab],
+        );
+        assert!(app.confirmation.is_some());
+        update_all(&mut app, [Action::Confirm]);
+        assert_eq!(
+            (app.tabs.current, app.tabs.pages.len(), rows(&app)),
+            (0, 1, 3)
+        );
+        assert!(app
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with(&format!("Closed {name}")));
+        update_all(&mut app, [Action::CloseTab]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("The last tab stays open (:quit to leave)")
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn the_query_runs_in_every_tab_and_two_tabs_compare_row_by_row() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-tabdo-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (data, report) = (dir.join("new.ttl"), dir.join("report.md"));
+        fs::write(
+            &data,
+            "<http://ex/a> <http://ex/p> 1 . <http://ex/z> <http://ex/p> 9 .\n",
+        )
+        .unwrap();
+        let mut app = app_with_rows();
+        update_all(&mut app, [Action::ShowTabRuns]);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Nothing compared yet (:tabdo run)")
+        );
+        update_all(&mut app, [Action::NewTab(Some(data.clone()))]);
+        while let Some(path) = app.pending.pop_front() {
+            app.open(path);
+        }
+        update_all(&mut app, [Action::CycleTab(-1), Action::RunInTabs]);
+        app.wait_for_query();
+        assert_eq!(app.status.as_deref(), Some("Ran the query in 2 tabs"));
+        let screen = text(&render(&mut app));
+        assert!(screen.contains("1 untitled"), "{screen}");
+        assert!(screen.contains("3 rows"));
+        assert!(screen.contains("2 rows"));
+        assert!(screen.contains("-1 vs tab 1"));
+        assert_eq!(
+            app.map_key(KeyEvent::from(KeyCode::Char('c'))),
+            [Action::CompareTabRun]
+        );
+
+        // 首頁為舊者，次頁為新者
+        update_all(
+            &mut app,
+            [
+                Action::CompareTabRun,
+                Action::ScrollPopup(1),
+                Action::CompareTabRun,
+            ],
+        );
+        assert_eq!((app.tabs.current, rows(&app)), (1, 2));
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Tab 2 against tab 1: 1 added, 2 removed (D lists the removed)")
+        );
+        assert!(app.popup.is_none());
+        update_all(
+            &mut app,
+            [
+                Action::ShowTabRuns,
+                Action::ScrollPopup(-1),
+                Action::OpenTabRun,
+            ],
+        );
+        assert_eq!((app.tabs.current, rows(&app)), (0, 3));
+
+        update_all(&mut app, [Action::ExportTabRuns(report.clone())]);
+        let written = fs::read_to_string(&report).unwrap();
+        assert!(
+            written.starts_with("```sparql\nSELECT ?s ?p ?o"),
+            "{written}"
+        );
+        assert!(written.contains("| new.ttl  |"), "{written}");
+        update_all(&mut app, [Action::YankTabRuns]);
+        assert_eq!(app.clipboard, Some(written));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
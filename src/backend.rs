@@ -0,0 +1,849 @@
+//! 存儲之主人：一後台線程收類型化之命令，送回類型化之事件。界面之載入、更新、清空、
+//! 恢復與重新載入皆經此，不於界面線程寫存儲；圖之複製與移動另於 `crate::graphs` 之線程行之。
+//!
+//! 寫入依命令之序：載入至多 [`Settings::jobs`] 個並行，更新、清空、替換、重新載入與
+//! 導入之追蹤則獨行，待其前者畢方始，其後者亦待之。每成一次寫入世代加一；查詢之事件附其
+//! 開始時之世代，界面可知結果是否已舊。讀取者可以 [`StoreWorker::store`] 取存儲之快照，
+//! 如界面之查詢與標籤。
+
+use crate::{
+    checkpoint,
+    dataset::Dataset,
+    imports::follow_imports,
+    loader::{load_all, load_data, load_file, FileLoad},
+    overwrite::write_then_rename,
+    prepared::Prepared,
+    service::query_options,
+    worker::{Caps, Job, Message, TermRow, Worker},
+};
+use anyhow::Context;
+use oxigraph::{
+    io::RdfFormat,
+    model::{GraphName, GraphNameRef, Variable},
+    sparql::{
+        results::{QueryResultsFormat, QueryResultsSerializer},
+        EvaluationError, QueryResults,
+    },
+    store::Store,
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::File,
+    io::{BufWriter, Write as _},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// 有查詢執行時，每隔此時收其消息、查其時限。
+const TICK: Duration = Duration::from_millis(10);
+/// 未取之事件數上限。滿則暫不收查詢之消息，其線程隨之等待。
+const CAPACITY: usize = 64;
+
+/// 命令所屬之號，由發者定，事件以之相對。
+pub type Id = u64;
+
+/// 送予存儲線程之命令。
+#[derive(Debug, Clone)]
+pub enum Command {
+    RunQuery {
+        id: Id,
+        text: String,
+    },
+    /// 載入一文件。至多 [`Settings::jobs`] 個並行。
+    Load {
+        id: Id,
+        path: PathBuf,
+    },
+    /// 載入所給之數據，如命令行之 `--data`。`number` 為其序，自 1 起，定其基 IRI。
+    LoadData {
+        id: Id,
+        data: String,
+        format: RdfFormat,
+        number: usize,
+    },
+    /// 執行 SPARQL 更新。
+    Update {
+        id: Id,
+        text: String,
+    },
+    /// 清空一圖；`None` 則清空全部。
+    Clear {
+        id: Id,
+        graph: Option<GraphName>,
+    },
+    /// 清空諸圖（`None` 為整個存儲），再載入 N-Quads 文件，如恢復檢查點。
+    Replace {
+        id: Id,
+        path: PathBuf,
+        graphs: Option<Vec<GraphName>>,
+    },
+    /// 於新存儲重新載入諸數據與文件。全部成功方換入，否則舊存儲不變。
+    Reload {
+        id: Id,
+        data: Vec<(String, RdfFormat)>,
+        paths: Vec<PathBuf>,
+    },
+    /// 追蹤默認圖之 `owl:imports`，至多 `depth` 層。`visited` 中之 IRI 不再取。
+    FollowImports {
+        id: Id,
+        depth: usize,
+        visited: HashSet<String>,
+    },
+    /// 執行查詢，逐行寫入文件，不經界面。
+    Export {
+        id: Id,
+        text: String,
+        format: QueryResultsFormat,
+        path: PathBuf,
+    },
+    /// 取消查詢、導出或未始之寫入。已始之寫入不可中止。
+    Cancel {
+        id: Id,
+    },
+    /// 此後之命令依此設置。
+    Configure(Settings),
+}
+
+/// 存儲線程送回之事件。每命令終以 `Finished`、`Loaded`、`Changed`、`Reloaded`、
+/// `Imported`、`Exported`、`Failed` 或 `Cancelled` 之一。
+#[derive(Debug)]
+pub enum Event {
+    /// SELECT 之變量，及查詢所見存儲之世代；他種查詢則變量為 `None`，其後無行。
+    Started {
+        id: Id,
+        generation: u64,
+        variables: Option<Vec<Variable>>,
+    },
+    /// 一批行，及迄今各項之估計字節數。
+    Rows {
+        id: Id,
+        rows: Vec<TermRow>,
+        bytes: usize,
+    },
+    /// 須告知用戶者，如結果有所略去。
+    Note {
+        id: Id,
+        note: String,
+    },
+    Finished {
+        id: Id,
+        truncated: bool,
+    },
+    /// 文件或數據已載入，存儲升至 `generation`，附各圖之三元組數及所聲明之前綴。
+    Loaded {
+        id: Id,
+        generation: u64,
+        graphs: Vec<(GraphName, usize)>,
+        prefixes: Vec<(String, String)>,
+        note: Option<String>,
+    },
+    /// 更新、清空或替換已畢。
+    Changed {
+        id: Id,
+        generation: u64,
+    },
+    /// 新存儲已換入，見 [`StoreWorker::store`]。`loads` 為各數據與文件之名及其各圖之
+    /// 三元組數。
+    Reloaded {
+        id: Id,
+        generation: u64,
+        loads: Vec<(String, Vec<(GraphName, usize)>)>,
+    },
+    /// 已導入之本體 IRI 及其三元組數、失敗者及其因，及此後當略之 IRI。
+    Imported {
+        id: Id,
+        generation: u64,
+        imported: Vec<(String, usize)>,
+        failures: Vec<(String, String)>,
+        visited: HashSet<String>,
+    },
+    /// 已寫入之行數。
+    Exported {
+        id: Id,
+        rows: usize,
+    },
+    Failed {
+        id: Id,
+        error: String,
+    },
+    Cancelled {
+        id: Id,
+    },
+}
+
+impl Event {
+    /// 所屬命令之號。
+    pub fn id(&self) -> Id {
+        match self {
+            Self::Started { id, .. }
+            | Self::Rows { id, .. }
+            | Self::Note { id, .. }
+            | Self::Finished { id, .. }
+            | Self::Loaded { id, .. }
+            | Self::Changed { id, .. }
+            | Self::Reloaded { id, .. }
+            | Self::Imported { id, .. }
+            | Self::Exported { id, .. }
+            | Self::Failed { id, .. }
+            | Self::Cancelled { id } => *id,
+        }
+    }
+
+    /// 是否為其命令之末一事件。
+    pub fn is_last(&self) -> bool {
+        !matches!(
+            self,
+            Self::Started { .. } | Self::Rows { .. } | Self::Note { .. }
+        )
+    }
+}
+
+/// 存儲線程之設置。
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub federated: bool,
+    /// 遠端請求之時限，亦為追蹤導入時每請求之時限。
+    pub remote_timeout: Duration,
+    /// 查詢與導出之時限。逾時則取消，報 `Failed`。
+    pub deadline: Option<Duration>,
+    /// 唯讀則拒絕更新、清空與替換；載入與重新載入不改已有之數據，仍可。
+    pub read_only: bool,
+    pub strict_n3: bool,
+    /// 每查詢送回之行數上限。
+    pub limit: Option<usize>,
+    /// 導出之文件已存在則覆蓋之；否則報 `Failed`。
+    pub overwrite: bool,
+    /// 並行載入之文件數。零同一。
+    pub jobs: usize,
+}
+
+/// 存儲線程之端。丟棄即停，並取消其上之查詢；已始之寫入仍畢之。
+pub struct StoreWorker {
+    commands: Sender<Input>,
+    events: Receiver<Event>,
+    generation: Arc<AtomicU64>,
+    read: Arc<AtomicU64>,
+    store: Arc<Mutex<Store>>,
+}
+
+impl StoreWorker {
+    pub fn spawn(store: Store, settings: Settings) -> Self {
+        let (commands, inputs) = mpsc::channel();
+        let (events, receiver) = mpsc::sync_channel(CAPACITY);
+        let generation = Arc::new(AtomicU64::new(0));
+        let read = Arc::new(AtomicU64::new(0));
+        let shared = Arc::new(Mutex::new(store.clone()));
+        let owner = Owner {
+            store,
+            shared: Arc::clone(&shared),
+            settings,
+            inputs,
+            commands: commands.clone(),
+            events,
+            backlog: VecDeque::new(),
+            generation: Arc::clone(&generation),
+            read: Arc::clone(&read),
+            prepared: Prepared::default(),
+            queries: vec![],
+            exports: vec![],
+            writes: VecDeque::new(),
+            writing: vec![],
+        };
+        thread::spawn(move || owner.run());
+        Self {
+            commands,
+            events: receiver,
+            generation,
+            read,
+            store: shared,
+        }
+    }
+
+    pub fn send(&self, command: Command) {
+        let _ = self.commands.send(Input::Command(command));
+    }
+
+    /// 取已到之事件，不等待。
+    pub fn try_recv(&self) -> Option<Event> {
+        self.events.try_recv().ok()
+    }
+
+    /// 等待下一事件。存儲線程已止則返回 `None`。
+    pub fn recv(&self) -> Option<Event> {
+        self.events.recv().ok()
+    }
+
+    /// 等待下一事件，至多 `timeout`。
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Event> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// 已成之寫入數。
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// 諸載入迄今所讀之字節數，供進度。
+    pub fn read(&self) -> u64 {
+        self.read.load(Ordering::Relaxed)
+    }
+
+    /// 存儲之快照，供讀取。重新載入後為新存儲。
+    pub fn store(&self) -> Store {
+        self.store.lock().unwrap().clone()
+    }
+}
+
+impl Drop for StoreWorker {
+    fn drop(&mut self) {
+        let _ = self.commands.send(Input::Stop);
+    }
+}
+
+/// 存儲線程所收者：命令，或其所起之線程之結果。
+enum Input {
+    Command(Command),
+    Written(Id, Result<Written, String>),
+    Exported(Id, Result<usize, String>),
+    Stop,
+}
+
+/// 待行之寫入。
+enum Write {
+    Load(PathBuf),
+    LoadData {
+        data: String,
+        format: RdfFormat,
+        number: usize,
+    },
+    Update(String),
+    Clear(Option<GraphName>),
+    Replace {
+        path: PathBuf,
+        graphs: Option<Vec<GraphName>>,
+    },
+    Reload {
+        data: Vec<(String, RdfFormat)>,
+        paths: Vec<PathBuf>,
+    },
+    FollowImports {
+        depth: usize,
+        visited: HashSet<String>,
+    },
+}
+
+impl Write {
+    /// 載入可並行；餘者獨行。
+    fn is_exclusive(&self) -> bool {
+        !matches!(self, Self::Load(_) | Self::LoadData { .. })
+    }
+
+    /// 改已有之數據者，唯讀時拒之。
+    fn changes_data(&self) -> bool {
+        matches!(
+            self,
+            Self::Update(_) | Self::Clear(_) | Self::Replace { .. }
+        )
+    }
+
+    /// 於 `store` 行之。
+    fn run(
+        self,
+        store: &Store,
+        settings: &Settings,
+        read: &Arc<AtomicU64>,
+    ) -> anyhow::Result<Written> {
+        Ok(match self {
+            Self::Load(path) => Written::Loaded(load_file(store, &path, read, settings.strict_n3)?),
+            Self::LoadData {
+                data,
+                format,
+                number,
+            } => Written::Loaded(FileLoad {
+                graphs: load_data(store, &data, format, number)?,
+                ..FileLoad::default()
+            }),
+            Self::Update(text) => {
+                store.update(text.as_str())?;
+                Written::Changed
+            }
+            Self::Clear(Some(graph)) => {
+                store.clear_graph(&graph)?;
+                Written::Changed
+            }
+            Self::Clear(None) => {
+                store.clear()?;
+                Written::Changed
+            }
+            Self::Replace { path, graphs } => {
+                checkpoint::replace(store, &path, graphs.as_deref())?;
+                Written::Changed
+            }
+            Self::Reload { data, paths } => reload(&data, paths, settings, read)?,
+            Self::FollowImports { depth, mut visited } => {
+                let report = follow_imports(
+                    store,
+                    GraphNameRef::DefaultGraph,
+                    depth,
+                    &mut visited,
+                    settings.remote_timeout,
+                );
+                Written::Imported {
+                    imported: report.imported,
+                    failures: report.failures,
+                    visited,
+                }
+            }
+        })
+    }
+}
+
+/// 寫入之所得。
+enum Written {
+    Loaded(FileLoad),
+    Changed,
+    Reloaded {
+        store: Store,
+        loads: Vec<(String, Vec<(GraphName, usize)>)>,
+    },
+    Imported {
+        imported: Vec<(String, usize)>,
+        failures: Vec<(String, String)>,
+        visited: HashSet<String>,
+    },
+}
+
+/// 載入諸數據與文件於新存儲。有失敗者則棄之，其錯誤皆列於所返。
+fn reload(
+    data: &[(String, RdfFormat)],
+    paths: Vec<PathBuf>,
+    settings: &Settings,
+    read: &Arc<AtomicU64>,
+) -> anyhow::Result<Written> {
+    let store = Store::new()?;
+    let mut loads = vec![];
+    for (index, (data, format)) in data.iter().enumerate() {
+        let source = format!("inline data #{}", index + 1);
+        let graphs = load_data(&store, data, *format, index + 1)
+            .with_context(|| format!("Fail to load {source}"))?;
+        loads.push((source, graphs));
+    }
+    let mut failures = vec![];
+    for loaded in load_all(&store, paths, settings.jobs, settings.strict_n3, read) {
+        log::info!(
+            "reloaded path={} duration_ms={}",
+            loaded.path.display(),
+            loaded.duration.as_millis()
+        );
+        match loaded.result {
+            Ok(load) => loads.push((loaded.path.display().to_string(), load.graphs)),
+            Err(error) => {
+                failures.push(format!("Fail to load {}: {error:#}", loaded.path.display()))
+            }
+        }
+    }
+    if !failures.is_empty() {
+        anyhow::bail!(failures.join("\n\n"));
+    }
+    Ok(Written::Reloaded { store, loads })
+}
+
+struct Query {
+    id: Id,
+    generation: u64,
+    worker: Worker,
+    deadline: Option<Instant>,
+}
+
+struct Export {
+    id: Id,
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+/// 存儲線程之狀態。
+struct Owner {
+    store: Store,
+    /// 予 [`StoreWorker::store`] 之副本，換入新存儲時亦換之。
+    shared: Arc<Mutex<Store>>,
+    settings: Settings,
+    inputs: Receiver<Input>,
+    /// 所起之線程送回結果之端。
+    commands: Sender<Input>,
+    events: SyncSender<Event>,
+    /// 通道滿時暫存之事件。
+    backlog: VecDeque<Event>,
+    generation: Arc<AtomicU64>,
+    read: Arc<AtomicU64>,
+    prepared: Prepared,
+    queries: Vec<Query>,
+    exports: Vec<Export>,
+    /// 待始之寫入。
+    writes: VecDeque<(Id, Write)>,
+    /// 進行中之寫入，及其是否獨行。
+    writing: Vec<(Id, bool)>,
+}
+
+impl Owner {
+    fn run(mut self) {
+        loop {
+            // 無查詢、導出、待送之事件則不必按時醒來
+            let idle = self.queries.is_empty() && self.exports.is_empty();
+            let input = if idle && self.backlog.is_empty() {
+                self.inputs
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                self.inputs.recv_timeout(TICK)
+            };
+            match input {
+                Ok(Input::Command(command)) => self.handle(command),
+                Ok(Input::Written(id, result)) => self.written(id, result),
+                Ok(Input::Exported(id, result)) => self.exported(id, result),
+                Ok(Input::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+            self.flush();
+            self.poll_queries();
+            self.enforce_deadlines();
+        }
+    }
+
+    fn emit(&mut self, event: Event) {
+        self.backlog.push_back(event);
+        self.flush();
+    }
+
+    /// 送出暫存之事件，至通道滿而止。
+    fn flush(&mut self) {
+        while let Some(event) = self.backlog.pop_front() {
+            match self.events.try_send(event) {
+                Ok(()) => {}
+                Err(TrySendError::Full(event)) => {
+                    self.backlog.push_front(event);
+                    return;
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    self.backlog.clear();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.settings
+            .deadline
+            .map(|deadline| Instant::now() + deadline)
+    }
+
+    fn handle(&mut self, command: Command) {
+        match command {
+            Command::RunQuery { id, text } => {
+                log::debug!("backend query id={id}");
+                let worker = Worker::spawn(
+                    &self.store,
+                    Job {
+                        query: text,
+                        federated: self.settings.federated,
+                        timeout: self.settings.remote_timeout,
+                        limit: self.settings.limit,
+                        caps: Caps::default(),
+                        prepared: self.prepared.clone(),
+                        dataset: Dataset::Default,
+                    },
+                );
+                self.queries.push(Query {
+                    id,
+                    generation: self.generation.load(Ordering::Relaxed),
+                    worker,
+                    deadline: self.deadline(),
+                });
+            }
+            Command::Load { id, path } => self.queue(id, Write::Load(path)),
+            Command::LoadData {
+                id,
+                data,
+                format,
+                number,
+            } => self.queue(
+                id,
+                Write::LoadData {
+                    data,
+                    format,
+                    number,
+                },
+            ),
+            Command::Update { id, text } => self.queue(id, Write::Update(text)),
+            Command::Clear { id, graph } => self.queue(id, Write::Clear(graph)),
+            Command::Replace { id, path, graphs } => {
+                self.queue(id, Write::Replace { path, graphs })
+            }
+            Command::Reload { id, data, paths } => self.queue(id, Write::Reload { data, paths }),
+            Command::FollowImports { id, depth, visited } => {
+                self.queue(id, Write::FollowImports { depth, visited })
+            }
+            Command::Export {
+                id,
+                text,
+                format,
+                path,
+            } => {
+                let cancelled = Arc::new(AtomicBool::new(false));
+                let (store, prepared, sender) = (
+                    self.store.clone(),
+                    self.prepared.clone(),
+                    self.commands.clone(),
+                );
+                let options = query_options(
+                    self.settings.federated,
+                    self.settings.remote_timeout,
+                    Arc::default(),
+                );
+                let stop = Arc::clone(&cancelled);
+                let overwrite = self.settings.overwrite;
+                thread::spawn(move || {
+                    let result = prepared
+                        .get(&text, None)
+                        .map_err(EvaluationError::from)
+                        .and_then(|query| store.query_opt(query, options))
+                        .map_err(|error| error.to_string())
+                        .and_then(|results| export(results, format, &path, overwrite, &stop));
+                    let _ = sender.send(Input::Exported(id, result));
+                });
+                self.exports.push(Export {
+                    id,
+                    cancelled,
+                    deadline: self.deadline(),
+                });
+            }
+            Command::Cancel { id } => self.cancel(id),
+            Command::Configure(settings) => self.settings = settings,
+        }
+    }
+
+    /// 排入一寫入，可行則始之。
+    fn queue(&mut self, id: Id, write: Write) {
+        if self.settings.read_only && write.changes_data() {
+            self.emit(Event::Failed {
+                id,
+                error: "The store is read-only".to_string(),
+            });
+            return;
+        }
+        self.writes.push_back((id, write));
+        self.start_writes();
+    }
+
+    fn cancel(&mut self, id: Id) {
+        let before = self.queries.len() + self.exports.len() + self.writes.len();
+        self.queries.retain(|query| query.id != id);
+        self.exports.retain(|export| {
+            let keep = export.id != id;
+            if !keep {
+                export.cancelled.store(true, Ordering::Relaxed);
+            }
+            keep
+        });
+        self.writes.retain(|(write, _)| *write != id);
+        if self.queries.len() + self.exports.len() + self.writes.len() < before {
+            self.emit(Event::Cancelled { id });
+        }
+    }
+
+    /// 依序始待行之寫入：載入至多 `jobs` 個並行，獨行者待進行中者皆畢。
+    fn start_writes(&mut self) {
+        while let Some((_, write)) = self.writes.front() {
+            let exclusive = write.is_exclusive();
+            let ready = match exclusive {
+                true => self.writing.is_empty(),
+                false => {
+                    self.writing.iter().all(|(_, exclusive)| !exclusive)
+                        && self.writing.len() < self.settings.jobs.max(1)
+                }
+            };
+            if !ready {
+                return;
+            }
+            let (id, write) = self.writes.pop_front().unwrap();
+            log::debug!("backend write id={id} exclusive={exclusive}");
+            self.writing.push((id, exclusive));
+            let (store, settings, read, sender) = (
+                self.store.clone(),
+                self.settings.clone(),
+                Arc::clone(&self.read),
+                self.commands.clone(),
+            );
+            thread::spawn(move || {
+                let result = write
+                    .run(&store, &settings, &read)
+                    .map_err(|error| format!("{error:#}"));
+                let _ = sender.send(Input::Written(id, result));
+            });
+        }
+    }
+
+    fn written(&mut self, id: Id, result: Result<Written, String>) {
+        self.writing.retain(|(writing, _)| *writing != id);
+        let bump = || self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let event = match result {
+            Ok(Written::Loaded(load)) => Event::Loaded {
+                id,
+                generation: bump(),
+                graphs: load.graphs,
+                prefixes: load.prefixes,
+                note: load.note,
+            },
+            Ok(Written::Changed) => Event::Changed {
+                id,
+                generation: bump(),
+            },
+            Ok(Written::Reloaded { store, loads }) => {
+                let generation = bump();
+                self.store = store.clone();
+                *self.shared.lock().unwrap() = store;
+                Event::Reloaded {
+                    id,
+                    generation,
+                    loads,
+                }
+            }
+            Ok(Written::Imported {
+                imported,
+                failures,
+                visited,
+            }) => Event::Imported {
+                id,
+                generation: match imported.is_empty() {
+                    true => self.generation.load(Ordering::Relaxed),
+                    false => bump(),
+                },
+                imported,
+                failures,
+                visited,
+            },
+            Err(error) => Event::Failed { id, error },
+        };
+        self.emit(event);
+        self.start_writes();
+    }
+
+    fn exported(&mut self, id: Id, result: Result<usize, String>) {
+        // 已取消者之結果棄之
+        let Some(index) = self.exports.iter().position(|export| export.id == id) else {
+            return;
+        };
+        self.exports.remove(index);
+        self.emit(match result {
+            Ok(rows) => Event::Exported { id, rows },
+            Err(error) => Event::Failed { id, error },
+        });
+    }
+
+    /// 轉送各查詢已到之消息；畢者去之。
+    /// 界面未取盡前不收，使查詢之線程等待。
+    fn poll_queries(&mut self) {
+        if !self.backlog.is_empty() {
+            return;
+        }
+        let (mut events, mut finished) = (vec![], vec![]);
+        for query in &self.queries {
+            while let Some(message) = query.worker.try_recv() {
+                let id = query.id;
+                let (event, done) = match message {
+                    Message::Note(note) => (Event::Note { id, note }, false),
+                    Message::Started(variables) => (
+                        Event::Started {
+                            id,
+                            generation: query.generation,
+                            variables,
+                        },
+                        false,
+                    ),
+                    Message::Rows(rows, bytes) => (Event::Rows { id, rows, bytes }, false),
+                    Message::Finished { truncated } => {
+                        let truncated = truncated.is_some();
+                        (Event::Finished { id, truncated }, true)
+                    }
+                    Message::Failed(error) => (Event::Failed { id, error }, true),
+                };
+                events.push(event);
+                if done {
+                    finished.push(id);
+                    break;
+                }
+            }
+        }
+        self.queries.retain(|query| !finished.contains(&query.id));
+        for event in events {
+            self.emit(event);
+        }
+    }
+
+    /// 取消逾時之查詢與導出。
+    fn enforce_deadlines(&mut self) {
+        let now = Instant::now();
+        let expired = |deadline: Option<Instant>| deadline.is_some_and(|deadline| now > deadline);
+        let mut failed = vec![];
+        self.queries.retain(|query| {
+            let keep = !expired(query.deadline);
+            if !keep {
+                failed.push(query.id);
+            }
+            keep
+        });
+        self.exports.retain(|export| {
+            let keep = !expired(export.deadline);
+            if !keep {
+                export.cancelled.store(true, Ordering::Relaxed);
+                failed.push(export.id);
+            }
+            keep
+        });
+        let deadline = self.settings.deadline.unwrap_or_default();
+        for id in failed {
+            self.emit(Event::Failed {
+                id,
+                error: format!("timed out after {}s", deadline.as_secs_f64()),
+            });
+        }
+    }
+}
+
+/// 逐行寫入文件，至取消而止。先寫至 `<path>.partial`，成則易名。返回行數。
+fn export(
+    results: QueryResults,
+    format: QueryResultsFormat,
+    path: &Path,
+    overwrite: bool,
+    cancelled: &AtomicBool,
+) -> Result<usize, String> {
+    let QueryResults::Solutions(solutions) = results else {
+        return Err("Not a SELECT query".to_string());
+    };
+    if !overwrite && path.exists() {
+        return Err(format!("{} exists", path.display()));
+    }
+    write_then_rename(path, false, |partial| {
+        let file = BufWriter::new(File::create(partial)?);
+        let mut writer = QueryResultsSerializer::from_format(format)
+            .serialize_solutions_to_write(file, solutions.variables().to_vec())?;
+        let mut rows = 0;
+        for solution in solutions {
+            if cancelled.load(Ordering::Relaxed) {
+                anyhow::bail!("cancelled");
+            }
+            writer.write(&solution?)?;
+            rows += 1;
+        }
+        writer.finish()?.flush()?;
+        Ok(rows)
+    })
+    .map_err(|error| error.to_string())
+}
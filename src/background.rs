@@ -0,0 +1,142 @@
+use std::{fmt, time::Duration};
+
+/// 待終端答背景色之久。凡終端皆答其後之 DA1，故僅不答 DA1 者待滿此時。
+pub const TIMEOUT: Duration = Duration::from_millis(200);
+
+/// 終端之背景色。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Rgb {
+    /// 亮度過半者為淺色之背景。
+    pub fn is_light(&self) -> bool {
+        let luma = 299 * self.red as u32 + 587 * self.green as u32 + 114 * self.blue as u32;
+        luma > 1000 * 255 / 2
+    }
+
+    /// 配此背景之預設主題。
+    pub fn preset(&self) -> &'static str {
+        match self.is_light() {
+            true => "light",
+            false => "dark",
+        }
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+/// 問終端之背景色：先發 OSC 11 之查詢，再發 DA1（`ESC [ c`）而讀至其答。不識 OSC 11
+/// 者亦答 DA1，故不待逾時，所答亦盡讀去，不留於輸入中為按鍵。須於原始模式中，
+/// 他處讀輸入之前問之。不答或出錯則為 `None`。
+#[cfg(unix)]
+pub fn query(timeout: Duration) -> Option<Rgb> {
+    use std::{
+        fs::File,
+        io::{Read, Write},
+        os::fd::AsRawFd,
+        time::Instant,
+    };
+    let mut tty = File::options()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    tty.write_all(b"\x1b]11;?\x07\x1b[c").ok()?;
+    tty.flush().ok()?;
+    let deadline = Instant::now() + timeout;
+    let mut answer = vec![];
+    let mut buffer = [0; 64];
+    while !answered(&answer) {
+        let left = deadline.checked_duration_since(Instant::now())?;
+        let mut fd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `fd` 為有效之描述符，僅問其可讀與否
+        if unsafe { libc::poll(&mut fd, 1, left.as_millis() as libc::c_int) } <= 0 {
+            return None;
+        }
+        let read = tty.read(&mut buffer).ok()?;
+        if read == 0 {
+            return None;
+        }
+        answer.extend_from_slice(&buffer[..read]);
+    }
+    log::info!("background answer={:?}", String::from_utf8_lossy(&answer));
+    parse(&answer)
+}
+
+#[cfg(not(unix))]
+pub fn query(_timeout: Duration) -> Option<Rgb> {
+    None
+}
+
+/// 是否已讀至 DA1 之答，如 `ESC [ ? 62 ; 22 c`。
+fn answered(answer: &[u8]) -> bool {
+    answer.windows(3).enumerate().any(|(start, window)| {
+        window == b"\x1b[?"
+            && answer[start + 3..]
+                .iter()
+                .find(|byte| !byte.is_ascii_digit() && **byte != b';')
+                == Some(&b'c')
+    })
+}
+
+/// 解 OSC 11 之答，如 `ESC ] 11 ; rgb:ffff/ffff/dddd BEL`，每分量一至四位十六進制。
+fn parse(answer: &[u8]) -> Option<Rgb> {
+    let start = answer.windows(5).position(|window| window == b"\x1b]11;")? + 5;
+    let rest = &answer[start..];
+    let end = rest.iter().position(|&byte| byte == 0x07 || byte == 0x1b)?;
+    let color = std::str::from_utf8(&rest[..end]).ok()?;
+    let color = color
+        .strip_prefix("rgb:")
+        .or_else(|| color.strip_prefix("rgba:"))?;
+    let mut channels = color.split('/').map(|channel| {
+        let digits = (1..=4).contains(&channel.len()).then_some(channel)?;
+        let value = u32::from_str_radix(digits, 16).ok()?;
+        let max = (1u32 << (4 * digits.len())) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    Some(Rgb {
+        red: channels.next()??,
+        green: channels.next()??,
+        blue: channels.next()??,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn background_answers_are_parsed() {
+        let rgb = |red, green, blue| Rgb { red, green, blue };
+        assert_eq!(
+            parse(b"\x1b]11;rgb:ffff/ffff/dddd\x07\x1b[?62;22c"),
+            Some(rgb(255, 255, 221))
+        );
+        // 以 ST 終者，及每分量二位者
+        assert_eq!(parse(b"\x1b]11;rgb:1e/1e/2e\x1b\\"), Some(rgb(30, 30, 46)));
+        assert_eq!(parse(b"\x1b]11;rgba:0/0/0/f\x07"), Some(rgb(0, 0, 0)));
+        // 僅答 DA1 者
+        assert_eq!(parse(b"\x1b[?1;2c"), None);
+        assert_eq!(parse(b"\x1b]11;rgb:12345/0/0\x07"), None);
+
+        assert!(answered(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;22c"));
+        assert!(answered(b"\x1b[?6c"));
+        assert!(!answered(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;2"));
+
+        assert_eq!(rgb(253, 246, 227).preset(), "light");
+        assert_eq!(rgb(30, 30, 46).preset(), "dark");
+        assert_eq!(rgb(30, 30, 46).to_string(), "#1e1e2e");
+    }
+}
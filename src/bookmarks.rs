@@ -0,0 +1,308 @@
+use crate::{
+    header::{sort_from_field, sort_to_field, Order},
+    paths::create_parent,
+};
+use std::{fs, io, path::PathBuf};
+
+/// 書籤：資源、查詢，或具名之視圖。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bookmark {
+    Resource { iri: String, label: Option<String> },
+    Query { query: String },
+    View(View),
+}
+
+/// 視圖：查詢及其顯示之設置。打開時先設行數上限與標籤語言，查詢開始後再設其餘。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct View {
+    pub name: String,
+    pub query: String,
+    /// 過濾之表達式，及未標明之子句是否為正則。
+    pub filter: Option<(String, bool)>,
+    pub column: Option<usize>,
+    pub origin: bool,
+    pub preview: bool,
+    pub limit: Option<usize>,
+    pub lang: Option<String>,
+    /// 查詢庫中之說明與標籤，隨庫導入導出。
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// 所依排序之諸列及其向，前者為主。
+    pub sort: Vec<(usize, Order)>,
+}
+
+impl View {
+    fn to_line(&self) -> String {
+        let (filter, regex) = match &self.filter {
+            Some((filter, regex)) => (escape(filter), if *regex { "regex" } else { "text" }),
+            None => (String::new(), ""),
+        };
+        let number = |n: Option<usize>| n.map(|n| n.to_string()).unwrap_or_default();
+        let flag = |on: bool| if on { "1" } else { "0" };
+        [
+            "view".to_string(),
+            escape(&self.name),
+            escape(&self.query),
+            filter,
+            regex.to_string(),
+            number(self.column),
+            flag(self.origin).to_string(),
+            flag(self.preview).to_string(),
+            number(self.limit),
+            escape(self.lang.as_deref().unwrap_or_default()),
+            escape(self.description.as_deref().unwrap_or_default()),
+            escape(&self.tags.join(",")),
+            sort_to_field(&self.sort),
+        ]
+        .join("\t")
+    }
+
+    /// 解析 `view` 之後各字段。說明、標籤與排序為後加者，舊文件中無之。
+    fn from_fields<'a>(mut fields: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let name = unescape(fields.next()?);
+        let query = unescape(fields.next()?);
+        let filter = fields.next()?;
+        let filter = match fields.next()? {
+            "regex" => Some((unescape(filter), true)),
+            "text" => Some((unescape(filter), false)),
+            _ => None,
+        };
+        let number = |field: &str| match field {
+            "" => Some(None),
+            field => field.parse().ok().map(Some),
+        };
+        Some(Self {
+            name,
+            query,
+            filter,
+            column: number(fields.next()?)?,
+            origin: fields.next()? == "1",
+            preview: fields.next()? == "1",
+            limit: number(fields.next()?)?,
+            lang: fields.next().filter(|l| !l.is_empty()).map(unescape),
+            description: fields.next().filter(|d| !d.is_empty()).map(unescape),
+            tags: fields.next().map_or_else(Vec::new, |tags| {
+                unescape(tags)
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            }),
+            sort: fields.next().map_or(Some(vec![]), sort_from_field)?,
+        })
+    }
+}
+
+impl Bookmark {
+    /// 面板中顯示之文字。
+    pub fn title(&self) -> String {
+        match self {
+            Self::Resource {
+                iri,
+                label: Some(label),
+            } => format!("{label} <{iri}>"),
+            Self::Resource { iri, label: None } => format!("<{iri}>"),
+            Self::Query { query } => query.split_whitespace().collect::<Vec<_>>().join(" "),
+            Self::View(view) => format!(
+                "{}: {}",
+                view.name,
+                view.query.split_whitespace().collect::<Vec<_>>().join(" ")
+            ),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        match self {
+            Self::Resource { iri, label } => format!(
+                "resource\t{}\t{}",
+                escape(iri),
+                escape(label.as_deref().unwrap_or_default())
+            ),
+            Self::Query { query } => format!("query\t{}", escape(query)),
+            Self::View(view) => view.to_line(),
+        }
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        match fields.next()? {
+            "resource" => Some(Self::Resource {
+                iri: unescape(fields.next()?),
+                label: fields.next().filter(|l| !l.is_empty()).map(unescape),
+            }),
+            "query" => Some(Self::Query {
+                query: unescape(fields.next()?),
+            }),
+            "view" => View::from_fields(fields).map(Self::View),
+            _ => None,
+        }
+    }
+}
+
+/// 書籤列表。若有文件則每次修改皆寫回。
+#[derive(Debug, Default)]
+pub struct Bookmarks {
+    entries: Vec<Bookmark>,
+    file: Option<PathBuf>,
+}
+
+impl Bookmarks {
+    pub fn entries(&self) -> &[Bookmark] {
+        &self.entries
+    }
+
+    /// 改用 `file` 存儲：讀入其中之書籤，併入現有者，並寫回。
+    pub fn attach(&mut self, file: PathBuf) -> io::Result<()> {
+        let mut entries = match fs::read_to_string(&file) {
+            Ok(content) => content.lines().filter_map(Bookmark::from_line).collect(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error),
+        };
+        for entry in self.entries.drain(..) {
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+        self.entries = entries;
+        self.file = Some(file);
+        self.save()
+    }
+
+    /// 添加書籤。已有則不重複。
+    pub fn add(&mut self, bookmark: Bookmark) -> io::Result<bool> {
+        if self.entries.contains(&bookmark) {
+            return Ok(false);
+        }
+        self.entries.push(bookmark);
+        self.save()?;
+        Ok(true)
+    }
+
+    /// 存一視圖。同名者代之，返回是否已有。
+    pub fn put_view(&mut self, view: View) -> io::Result<bool> {
+        let replaced = self.put(view);
+        self.save()?;
+        Ok(replaced)
+    }
+
+    /// 存若干視圖，如自查詢庫導入者，僅寫一次。
+    pub fn put_views(&mut self, views: Vec<View>) -> io::Result<()> {
+        for view in views {
+            self.put(view);
+        }
+        self.save()
+    }
+
+    fn put(&mut self, view: View) -> bool {
+        let existing = self
+            .entries
+            .iter_mut()
+            .find(|entry| matches!(entry, Bookmark::View(old) if old.name == view.name));
+        let replaced = existing.is_some();
+        match existing {
+            Some(entry) => *entry = Bookmark::View(view),
+            None => self.entries.push(Bookmark::View(view)),
+        }
+        replaced
+    }
+
+    /// 按名取視圖。
+    pub fn view(&self, name: &str) -> Option<&View> {
+        self.entries.iter().find_map(|entry| match entry {
+            Bookmark::View(view) if view.name == name => Some(view),
+            _ => None,
+        })
+    }
+
+    pub fn remove(&mut self, index: usize) -> io::Result<()> {
+        if index < self.entries.len() {
+            self.entries.remove(index);
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        create_parent(file)?;
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(&entry.to_line());
+            content.push('\n');
+        }
+        fs::write(file, content)
+    }
+}
+
+/// 轉義制表符、換行、回車與反斜杠，使一字段佔一行中之一列。會話文件亦用之。
+pub fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+pub fn unescape(field: &str) -> String {
+    let mut result = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some('t') => result.push('\t'),
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn views_round_trip_through_lines() {
+        let mut view = View {
+            name: "missing labels".to_string(),
+            query: "SELECT ?s WHERE {\n  ?s a ?t\n}".to_string(),
+            filter: Some(("?s: \"ex\"\tx".to_string(), true)),
+            column: Some(1),
+            origin: true,
+            preview: false,
+            limit: Some(50),
+            lang: Some("de".to_string()),
+            description: Some("Subjects\twithout a label".to_string()),
+            tags: vec!["quality".to_string(), "labels".to_string()],
+            sort: vec![(1, Order::Ascending), (0, Order::Descending)],
+        };
+        let bookmark = Bookmark::View(view.clone());
+        assert_eq!(Bookmark::from_line(&bookmark.to_line()), Some(bookmark));
+        view.filter = None;
+        view.column = None;
+        view.limit = None;
+        view.lang = None;
+        view.description = None;
+        view.tags = vec![];
+        view.sort = vec![];
+        let bookmark = Bookmark::View(view);
+        assert_eq!(Bookmark::from_line(&bookmark.to_line()), Some(bookmark));
+        assert_eq!(
+            Bookmark::from_line("view\tx\tq\t\t\tnot a number\t0\t0\t\t"),
+            None
+        );
+        // 無說明與標籤之舊行
+        let old = Bookmark::from_line("view\tx\tq\r\\nr\t\t\t\t0\t0\t\t").unwrap();
+        assert!(
+            matches!(old, Bookmark::View(view) if view.query == "q\r\nr" && view.tags.is_empty())
+        );
+    }
+}
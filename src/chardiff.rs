@@ -0,0 +1,238 @@
+use crate::inspect;
+
+/// 浮窗中一行所示之字符數，過長之值分行示之。
+const WIDTH: usize = 60;
+
+/// 逾此字符數者不逐字比較，僅示兩者全異。Myers 之法所記之跡隨編輯距離與長度之積而增。
+const LIMIT: usize = 4000;
+
+/// 差異之一段之所屬。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// 兩值共有。
+    Same,
+    /// 僅第一值有。
+    Removed,
+    /// 僅第二值有。
+    Added,
+}
+
+/// 兩值之逐字差異，相鄰而同屬者合為一段。共同之首尾先去之，餘者以 Myers 之法求最短之編輯。
+pub fn diff(a: &str, b: &str) -> Vec<(Op, String)> {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (middle_a, middle_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let mut edits = vec![];
+    push(&mut edits, Op::Same, &a[..prefix]);
+    if middle_a.len() + middle_b.len() > LIMIT {
+        push(&mut edits, Op::Removed, middle_a);
+        push(&mut edits, Op::Added, middle_b);
+    } else {
+        for (op, ch) in myers(middle_a, middle_b) {
+            push(&mut edits, op, &[ch]);
+        }
+    }
+    push(&mut edits, Op::Same, &a[a.len() - suffix..]);
+    edits
+}
+
+/// 異處之數，即相連之非共有之段之數。
+pub fn changes(edits: &[(Op, String)]) -> usize {
+    edits
+        .iter()
+        .enumerate()
+        .filter(|(index, (op, _))| {
+            *op != Op::Same && (*index == 0 || edits[index - 1].0 == Op::Same)
+        })
+        .count()
+}
+
+/// 如 `differs in 2 places`，或 `differs only in whitespace`，同者 `identical`。
+pub fn summary(a: &str, b: &str, edits: &[(Op, String)]) -> String {
+    if a == b {
+        return "identical".to_string();
+    }
+    let places = match changes(edits) {
+        1 => "1 place".to_string(),
+        count => format!("{count} places"),
+    };
+    let words = |text: &str| text.split_whitespace().collect::<String>();
+    match words(a) == words(b) {
+        true => format!("differs only in whitespace, in {places}"),
+        false => format!("differs in {places}"),
+    }
+}
+
+/// 一值之各段，已轉為所示之形，逾 `WIDTH` 字符者分行。`which` 為 `Removed` 者取第一值，
+/// `Added` 者取第二值。
+pub fn side(edits: &[(Op, String)], which: Op) -> Vec<Vec<(Op, String)>> {
+    let mut lines: Vec<Vec<(Op, String)>> = vec![vec![]];
+    let mut width = 0;
+    for (op, text) in edits
+        .iter()
+        .filter(|(op, _)| *op == Op::Same || *op == which)
+    {
+        for ch in text.chars() {
+            let shown = shown(&ch.to_string(), *op);
+            let len = shown.chars().count();
+            if width > 0 && width + len > WIDTH {
+                lines.push(vec![]);
+                width = 0;
+            }
+            width += len;
+            let line = lines.last_mut().unwrap_or_else(|| unreachable!());
+            match line.last_mut() {
+                Some((last, text)) if last == op => text.push_str(&shown),
+                _ => line.push((*op, shown)),
+            }
+        }
+    }
+    lines
+}
+
+/// 一段於浮窗中之形：異處之空格示為 `·`，他空白與不可見之字符示其碼位如 `⟨U+00A0⟩`。
+pub fn shown(text: &str, op: Op) -> String {
+    text.chars()
+        .map(|ch| match ch {
+            ' ' if op == Op::Same => " ".to_string(),
+            ' ' => "·".to_string(),
+            ch => inspect::visible(&ch.to_string()),
+        })
+        .collect()
+}
+
+fn push(edits: &mut Vec<(Op, String)>, op: Op, chars: &[char]) {
+    if chars.is_empty() {
+        return;
+    }
+    match edits.last_mut() {
+        Some((last, text)) if *last == op => text.extend(chars),
+        _ => edits.push((op, chars.iter().collect())),
+    }
+}
+
+/// 逐字之編輯序列。`v[k]` 為第 `k` 對角線上所及最遠之 `x`，每輪之前記之以回溯。
+fn myers(a: &[char], b: &[char]) -> Vec<(Op, char)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let index = |k: isize| (k + max + 1) as usize;
+    let mut v = vec![0; 2 * max as usize + 3];
+    let mut trace = vec![];
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let (d, k) = (d as isize, x - y);
+        let previous = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = v[index(previous)];
+        let previous_y = previous_x - previous;
+        while x > previous_x && y > previous_y {
+            edits.push((Op::Same, a[x as usize - 1]));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            match x == previous_x {
+                true => edits.push((Op::Added, b[y as usize - 1])),
+                false => edits.push((Op::Removed, a[x as usize - 1])),
+            }
+        }
+        (x, y) = (previous_x, previous_y);
+    }
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 各段記為 `=`、`-` 或 `+` 及其文本。
+    fn edits(a: &str, b: &str) -> Vec<String> {
+        diff(a, b)
+            .into_iter()
+            .map(|(op, text)| match op {
+                Op::Same => format!("={text}"),
+                Op::Removed => format!("-{text}"),
+                Op::Added => format!("+{text}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diffs_find_the_differing_spans() {
+        assert_eq!(edits("", ""), [""; 0]);
+        assert_eq!(edits("abc", "abc"), ["=abc"]);
+        assert_eq!(edits("", "abc"), ["+abc"]);
+        assert_eq!(edits("abc", ""), ["-abc"]);
+        assert_eq!(
+            edits("Paris, France", "Paris France "),
+            ["=Paris", "-,", "= France", "+ "]
+        );
+        assert_eq!(
+            edits("kitten", "sitting"),
+            ["-k", "+s", "=itt", "-e", "+i", "=n", "+g"]
+        );
+        // 多字節之字符逐字而非逐字節比較
+        assert_eq!(
+            edits("café 東京", "cafe 東亰"),
+            ["=caf", "-é", "+e", "= 東", "-京", "+亰"]
+        );
+        // 重複之字符：共同之首尾不致誤配
+        assert_eq!(edits("aaa", "aa"), ["=aa", "-a"]);
+        assert_eq!(changes(&diff("kitten", "sitting")), 3);
+        assert_eq!(changes(&diff("abc", "abc")), 0);
+
+        // 過長者僅示全異
+        let (long_a, long_b) = ("x".repeat(LIMIT), "y".repeat(LIMIT));
+        assert_eq!(
+            changes(&diff(&format!("<{long_a}>"), &format!("<{long_b}>"))),
+            1
+        );
+        assert_eq!(shown("a b\u{00A0}", Op::Removed), "a·b⟨U+00A0⟩");
+        assert_eq!(shown("a b", Op::Same), "a b");
+
+        let summary = |a: &str, b: &str| super::summary(a, b, &diff(a, b));
+        assert_eq!(summary("", ""), "identical");
+        assert_eq!(summary("kitten", "sitting"), "differs in 3 places");
+        assert_eq!(
+            summary("New  York", "New York "),
+            "differs only in whitespace, in 2 places"
+        );
+        let edits = diff("ab", &format!("a{}", "c".repeat(WIDTH)));
+        assert_eq!(
+            side(&edits, Op::Removed),
+            [[(Op::Same, "a".to_string()), (Op::Removed, "b".to_string())]]
+        );
+        assert_eq!(side(&edits, Op::Added).len(), 2);
+        assert_eq!(side(&[], Op::Added), [vec![]]);
+    }
+}
@@ -0,0 +1,56 @@
+/// 一項數據質量檢查。
+pub struct Check {
+    /// 報告中之名，亦為細查時所認。
+    pub name: &'static str,
+    /// 列出違例之項之查詢。
+    pub query: &'static str,
+}
+
+/// `:check` 所行之檢查。皆僅及默認圖。
+pub const CHECKS: &[Check] = &[
+    Check {
+        name: "subjects without rdf:type",
+        query: "SELECT DISTINCT ?subject WHERE { ?subject ?p ?o \
+                  FILTER NOT EXISTS { ?subject a ?type } }",
+    },
+    Check {
+        name: "dangling references",
+        query: "SELECT DISTINCT ?object WHERE { ?s ?p ?object FILTER(isIRI(?object)) \
+                  FILTER NOT EXISTS { ?object ?q ?any } }",
+    },
+    // 有類型或上位屬性者為已定義；RDF、RDFS、OWL 之內建者不計
+    Check {
+        name: "undefined predicates",
+        query: "SELECT DISTINCT ?predicate WHERE { ?s ?predicate ?o \
+                  FILTER NOT EXISTS { ?predicate a ?kind } \
+                  FILTER NOT EXISTS { ?predicate \
+                    <http://www.w3.org/2000/01/rdf-schema#subPropertyOf> ?super } \
+                  FILTER(!STRSTARTS(STR(?predicate), \"http://www.w3.org/1999/02/22-rdf-syntax-ns#\") \
+                    && !STRSTARTS(STR(?predicate), \"http://www.w3.org/2000/01/rdf-schema#\") \
+                    && !STRSTARTS(STR(?predicate), \"http://www.w3.org/2002/07/owl#\")) }",
+    },
+];
+
+/// 報告之查詢：每項檢查一行，依 [`CHECKS`] 之序列其名與違例之數。
+pub fn report_query() -> String {
+    let parts: Vec<String> = CHECKS
+        .iter()
+        .enumerate()
+        .map(|(rank, check)| {
+            format!(
+                "{{ SELECT ({rank} AS ?rank) (\"{}\" AS ?check) (COUNT(*) AS ?count) \
+                   WHERE {{ {} }} }}",
+                check.name, check.query
+            )
+        })
+        .collect();
+    format!(
+        "SELECT ?check ?count WHERE {{\n  {}\n}} ORDER BY ?rank",
+        parts.join("\n  UNION\n  ")
+    )
+}
+
+/// 報告中某名之檢查。
+pub fn find(name: &str) -> Option<&'static Check> {
+    CHECKS.iter().find(|check| check.name == name)
+}
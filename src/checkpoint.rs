@@ -0,0 +1,288 @@
+use crate::{overwrite::write_then_rename, paths::create_dir, provenance::timestamp};
+use anyhow::Context;
+use oxigraph::{
+    io::{RdfFormat, RdfParser, RdfSerializer},
+    model::{GraphName, NamedNode},
+    store::Store,
+};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// 檢查點所存四元組數之缺省上限。
+pub const DEFAULT_LIMIT: usize = 100_000;
+
+/// 首行注釋之前綴，其後為所存之範圍。
+const HEADER: &str = "# rdf-tui checkpoint: ";
+
+/// 檢查點所存之範圍。恢復時先清空之。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    Store,
+    Graphs(Vec<GraphName>),
+}
+
+impl Scope {
+    /// 如 `the store`、`graph <http://ex/g>`、`3 graphs`。
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Store => "the store".to_string(),
+            Self::Graphs(graphs) => match graphs.as_slice() {
+                [GraphName::DefaultGraph] => "the default graph".to_string(),
+                [graph] => format!("graph {graph}"),
+                graphs => format!("{} graphs", graphs.len()),
+            },
+        }
+    }
+
+    fn header(&self) -> String {
+        match self {
+            Self::Store => format!("{HEADER}store"),
+            Self::Graphs(graphs) => {
+                let graphs: Vec<String> = graphs
+                    .iter()
+                    .map(|graph| match graph {
+                        GraphName::DefaultGraph => "DEFAULT".to_string(),
+                        graph => graph.to_string(),
+                    })
+                    .collect();
+                format!("{HEADER}{}", graphs.join(" "))
+            }
+        }
+    }
+
+    fn parse(header: &str) -> Option<Self> {
+        let scope = header.trim_end().strip_prefix(HEADER)?;
+        if scope == "store" {
+            return Some(Self::Store);
+        }
+        scope
+            .split_whitespace()
+            .map(|graph| match graph {
+                "DEFAULT" => Some(GraphName::DefaultGraph),
+                graph => {
+                    let iri = graph.strip_prefix('<')?.strip_suffix('>')?;
+                    Some(NamedNode::new(iri).ok()?.into())
+                }
+            })
+            .collect::<Option<_>>()
+            .map(Self::Graphs)
+    }
+}
+
+/// 破壞性操作前所寫之 N-Quads 快照，存於一目錄。
+#[derive(Debug, Clone)]
+pub struct Checkpoints {
+    pub dir: PathBuf,
+    /// 所存四元組數之上限。逾之則不寫，另請確認。
+    pub limit: usize,
+}
+
+/// 寫檢查點之結果。
+#[derive(Debug)]
+pub enum Outcome {
+    Written(PathBuf),
+    /// 所涉之範圍逾上限而未寫。
+    TooLarge(Scope),
+}
+
+impl Checkpoints {
+    /// 修改 `graphs` 前寫檢查點，`None` 為涉及全部。存儲不逾上限則存全部，否則僅存所涉之圖。
+    pub fn take(
+        &self,
+        store: &Store,
+        graphs: Option<&[GraphName]>,
+        now: u64,
+    ) -> anyhow::Result<Outcome> {
+        let total = store.len()?;
+        let scope = match graphs {
+            Some(graphs) if total > self.limit => {
+                let mut graphs = graphs.to_vec();
+                graphs.sort_by_key(|graph| (!graph.is_default_graph(), graph.to_string()));
+                graphs.dedup();
+                Scope::Graphs(graphs)
+            }
+            _ => Scope::Store,
+        };
+        let count = match &scope {
+            Scope::Store => total,
+            // 數至逾上限即止
+            Scope::Graphs(graphs) => graphs
+                .iter()
+                .map(|graph| {
+                    store
+                        .quads_for_pattern(None, None, None, Some(graph.as_ref()))
+                        .take(self.limit + 1)
+                        .count()
+                })
+                .sum(),
+        };
+        if count > self.limit {
+            return Ok(Outcome::TooLarge(scope));
+        }
+
+        create_dir(&self.dir)?;
+        let name = timestamp(now).replace(':', "");
+        let kind = match scope {
+            Scope::Store => "store",
+            Scope::Graphs(_) => "graphs",
+        };
+        let mut path = self.dir.join(format!("{name}-{kind}.nq"));
+        for n in 2.. {
+            if !path.exists() {
+                break;
+            }
+            path = self.dir.join(format!("{name}-{kind}-{n}.nq"));
+        }
+        write_then_rename(&path, false, |partial| {
+            let mut file = BufWriter::new(
+                File::create(partial)
+                    .with_context(|| format!("Cannot write {}", path.display()))?,
+            );
+            writeln!(file, "{}", scope.header())?;
+            match &scope {
+                Scope::Store => {
+                    store.dump_to_write(RdfFormat::NQuads, &mut file)?;
+                }
+                Scope::Graphs(graphs) => {
+                    let mut writer =
+                        RdfSerializer::from_format(RdfFormat::NQuads).serialize_to_write(&mut file);
+                    for graph in graphs {
+                        for quad in store.quads_for_pattern(None, None, None, Some(graph.as_ref()))
+                        {
+                            writer.write_quad(&quad?)?;
+                        }
+                    }
+                    writer.finish()?;
+                }
+            }
+            Ok(file.flush()?)
+        })?;
+        Ok(Outcome::Written(path))
+    }
+
+    /// 目錄中之檢查點，新者居先。目錄不存在則無。
+    pub fn list(&self) -> anyhow::Result<Vec<Checkpoint>> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Ok(vec![]);
+        };
+        let mut checkpoints = vec![];
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "nq") {
+                continue;
+            }
+            let Ok(file) = File::open(&path) else {
+                continue;
+            };
+            let mut header = String::new();
+            BufReader::new(&file).read_line(&mut header)?;
+            // 非本程序所寫者略之
+            if let Some(scope) = Scope::parse(&header) {
+                let bytes = file.metadata()?.len();
+                checkpoints.push(Checkpoint { path, scope, bytes });
+            }
+        }
+        // 文件名以時刻起，故依名即依時
+        checkpoints.sort_by(|a, b| b.path.cmp(&a.path));
+        Ok(checkpoints)
+    }
+}
+
+/// 目錄中之一檢查點。
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub path: PathBuf,
+    pub scope: Scope,
+    pub bytes: u64,
+}
+
+impl Checkpoint {
+    /// 文件名，如 `2024-05-01T120000Z-store.nq`。
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+    }
+
+    /// 所存之圖；`None` 為整個存儲。恢復時以 [`replace`] 清空之再載入。
+    pub fn graphs(&self) -> Option<&[GraphName]> {
+        match &self.scope {
+            Scope::Store => None,
+            Scope::Graphs(graphs) => Some(graphs.as_slice()),
+        }
+    }
+}
+
+/// 清空 `graphs`（`None` 為整個存儲），再載入 N-Quads 文件 `path` 之四元組。
+pub fn replace(store: &Store, path: &Path, graphs: Option<&[GraphName]>) -> anyhow::Result<()> {
+    let file = File::open(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    match graphs {
+        None => store.clear()?,
+        Some(graphs) => {
+            for graph in graphs {
+                store.clear_graph(graph)?;
+            }
+        }
+    }
+    store
+        .load_from_read(
+            RdfParser::from_format(RdfFormat::NQuads),
+            BufReader::new(file),
+        )
+        .with_context(|| format!("Cannot load {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, Quad};
+
+    #[test]
+    fn checkpoints_restore_what_they_saved() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-checkpoints-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = Store::new().unwrap();
+        let graph = GraphName::from(NamedNode::new_unchecked("http://ex/g"));
+        let quad = |n: i64, graph: &GraphName| {
+            Quad::new(
+                NamedNode::new_unchecked("http://ex/a"),
+                NamedNode::new_unchecked("http://ex/p"),
+                Literal::from(n),
+                graph.clone(),
+            )
+        };
+        for n in 0..3 {
+            store.insert(&quad(n, &GraphName::DefaultGraph)).unwrap();
+        }
+        store.insert(&quad(0, &graph)).unwrap();
+
+        let checkpoints = Checkpoints {
+            dir: dir.clone(),
+            limit: 2,
+        };
+        let graphs = [graph.clone()];
+        let Outcome::Written(path) = checkpoints.take(&store, Some(&graphs), 0).unwrap() else {
+            panic!("graph over the limit");
+        };
+        assert!(path.ends_with("1970-01-01T000000Z-graphs.nq"));
+        assert!(matches!(
+            checkpoints.take(&store, None, 0).unwrap(),
+            Outcome::TooLarge(Scope::Store)
+        ));
+
+        store.clear_graph(&graph).unwrap();
+        store.insert(&quad(9, &graph)).unwrap();
+        let list = checkpoints.list().unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].scope.describe(), "graph <http://ex/g>");
+        replace(&store, &list[0].path, list[0].graphs()).unwrap();
+        assert!(store.contains(&quad(0, &graph)).unwrap());
+        assert!(!store.contains(&quad(9, &graph)).unwrap());
+        assert_eq!(store.len().unwrap(), 4);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
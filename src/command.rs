@@ -0,0 +1,578 @@
+use crate::{
+    action::{Action, ExportFormat, GraphOp, Values},
+    dataset::Dataset,
+    path_input::{self, PathArg, OPEN_EXTENSIONS},
+    sink::Sink,
+    util::common_prefix,
+    watch::parse_interval,
+    yank::Yank,
+};
+use oxigraph::{
+    io::RdfFormat,
+    model::{GraphName, NamedNode},
+};
+
+/// 命令及其說明，供幫助與補全。
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    ("open", "<path>", "Load an RDF file, or download one from an http(s) URL"),
+    (
+        "sample",
+        "",
+        "Load the bundled sample: the chemical elements of periods 1 to 5",
+    ),
+    ("reload", "", "Reload all files"),
+    (
+        "tabnew",
+        "[<path>]",
+        "Open a file in a new tab with its own store, query and history",
+    ),
+    (
+        "tabclose",
+        "",
+        "Close this tab and free its store (:tabclose! skips the confirmation)",
+    ),
+    ("tabnext", "", "Switch to the next tab"),
+    ("tabprevious", "", "Switch to the previous tab"),
+    (
+        "tabdo",
+        "[run|export <path>]",
+        "Run the query in every tab and compare the row counts, show the last comparison, or write it as Markdown",
+    ),
+    ("recent", "", "Pick a recently opened file"),
+    ("history", "", "Browse the queries run in this session"),
+    (
+        "export",
+        "csv|tsv|json|xml|markdown|org|ttl|trig|nt|nq <path>|append:<path>|cmd:<command>|post:<url>",
+        "Write the result, or a CONSTRUCT's triples as RDF, to a file, a command's stdin or a URL (:export! skips waiting for a running query)",
+    ),
+    (
+        "dump",
+        "<path>",
+        "Write every graph in the store as RDF, in the format of the path's extension",
+    ),
+    (
+        "values",
+        "raw|plain|typed",
+        "Export CSV, TSV and tables as terms, plain values, or plain values with type columns",
+    ),
+    ("limit", "<n>", "Cap displayed rows (0 for unlimited)"),
+    (
+        "dataset",
+        "[default|union|graphs <iri>…]",
+        "Query the default graph, the union of all graphs, or the graphs listed",
+    ),
+    (
+        "order",
+        "[stable|engine]",
+        "Sort results without ORDER BY canonically, or keep the engine's order",
+    ),
+    (
+        "graph",
+        "<iri>|copy <src> <dst>|move <src> <dst>|drop <iri>",
+        "Browse a named graph, or copy, move or drop one (default names the default graph)",
+    ),
+    (
+        "filter",
+        "[[!][?var:|n:]/<regex>/|<text>]…",
+        "Show the rows matching every clause (! inverts; no argument clears)",
+    ),
+    (
+        "view",
+        "save|open <name>",
+        "Save the query with its display settings, or open a saved view",
+    ),
+    (
+        "library",
+        "import|export <dir>",
+        "Merge a directory of .rq files and index.toml into the views, or write them out",
+    ),
+    (
+        "profile",
+        "import|export <path>",
+        "Apply a profile of display, theme and key settings for this session, or write the current ones",
+    ),
+    (
+        "watch",
+        "<interval>|off",
+        "Re-run the query every interval, such as 30s or 5m, and show what changed",
+    ),
+    (
+        "pin",
+        "[<text>]",
+        "Pin a snippet, or the selected cell, to the scratchpad",
+    ),
+    (
+        "layout",
+        "[<name>]",
+        "Arrange the panes by a layout preset, or list the presets",
+    ),
+    (
+        "yank",
+        "[iri|curie|label|value|row|nt]",
+        "Copy the selected cell in that form, or pick the form from a menu",
+    ),
+    (
+        "share",
+        "",
+        "Copy a link to the loaded files, query, dataset, filter, sort and selected row",
+    ),
+    (
+        "goto",
+        "<link>",
+        "Open a link made by :share, loading its files and running its query",
+    ),
+    (
+        "debug",
+        "[log]",
+        "Show or hide the latency, queue and cache numbers, or write them to the log",
+    ),
+    ("updates", "", "List the updates run in this session"),
+    (
+        "undo-data",
+        "[<n>|all]",
+        "Undo the last edit or delete, the last n, or all of this session's (asks first)",
+    ),
+    (
+        "restore-checkpoint",
+        "[<n>]",
+        "List the checkpoints, or replace what the nth saved with it (asks first)",
+    ),
+    (
+        "stats",
+        "[col]",
+        "Show store statistics, or those of the selected column",
+    ),
+    (
+        "graphs",
+        "",
+        "List each graph's source file, load time and triples",
+    ),
+    (
+        "paths",
+        "",
+        "Show where the config, sessions, bookmarks and other state are kept",
+    ),
+    (
+        "memory",
+        "",
+        "Show the estimated memory of the store, results, snapshots and caches",
+    ),
+    (
+        "dupes",
+        "[-i] [<predicate>]",
+        "Find subjects sharing a literal (default rdfs:label; -i ignores case and spacing)",
+    ),
+    (
+        "check",
+        "[functional [<predicate>]]",
+        "Count untyped subjects, dangling references and undefined predicates; \
+         functional lists subjects with several values for a functional property",
+    ),
+    (
+        "clear",
+        "[graph <iri>]",
+        "Remove all data, or one named graph",
+    ),
+    (
+        "rename",
+        "<?var> <?new>",
+        "Rename a query variable everywhere (asks first)",
+    ),
+    (
+        "normalize",
+        "",
+        "Replace curly quotes, odd spaces and invisible characters in the query",
+    ),
+    ("macros", "", "List recorded macros"),
+    ("help", "", "Show keys and commands"),
+    ("quit", "", "Quit (:q! skips the confirmation)"),
+];
+
+/// 命令行之內容。
+#[derive(Debug, Default)]
+pub struct CommandLine {
+    pub input: String,
+}
+
+impl CommandLine {
+    pub fn push(&mut self, ch: char) {
+        self.input.push(ch);
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        self.input.pop()
+    }
+
+    /// 補全命令名或路徑，返回其餘候選。
+    pub fn complete(&mut self) -> Vec<String> {
+        let (completed, candidates) = match self.path_arg() {
+            Some(arg) => {
+                let (completed, candidates) = path_input::complete(arg.text, arg.extensions);
+                (
+                    format!("{}{completed}", &self.input[..arg.start]),
+                    candidates,
+                )
+            }
+            None => complete(&self.input),
+        };
+        self.input = completed;
+        candidates
+    }
+
+    /// 路徑參數或命令名之候選，以列於浮窗。
+    pub fn matches(&self) -> Vec<String> {
+        match self.path_arg() {
+            Some(arg) => path_input::matches(arg.text, arg.extensions),
+            None if !self.input.contains(' ') => COMMANDS
+                .iter()
+                .map(|(command, _, _)| command.to_string())
+                .filter(|command| command.starts_with(&self.input))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// 命令之路徑參數，如 `open` 之文件、`export` 所寫之文件。
+    pub fn path_arg(&self) -> Option<PathArg<'_>> {
+        let (name, rest) = self.input.split_once(' ')?;
+        let (name, _) = name.split_once('!').unwrap_or((name, ""));
+        let (extensions, writing, offset) = match resolve_name(name).ok()? {
+            "open" | "tabnew" => (OPEN_EXTENSIONS, false, 0),
+            "tabdo" => {
+                let ("export", _) = rest.split_once(' ')? else {
+                    return None;
+                };
+                (&["md", "markdown"][..], true, "export".len() + 1)
+            }
+            "export" => {
+                let (format, _) = rest.split_once(' ')?;
+                let extensions: &[&str] = match format {
+                    "csv" => &["csv"],
+                    "tsv" => &["tsv"],
+                    "json" => &["json", "srj"],
+                    "xml" => &["xml", "srx"],
+                    "markdown" | "md" => &["md", "markdown"],
+                    "org" => &["org"],
+                    "ttl" | "turtle" => &["ttl"],
+                    "trig" => &["trig"],
+                    "nt" | "ntriples" => &["nt"],
+                    "nq" | "nquads" => &["nq"],
+                    _ => &[],
+                };
+                (extensions, true, format.len() + 1)
+            }
+            "dump" => (&["ttl", "trig", "nt", "nq"][..], true, 0),
+            "library" => {
+                let (direction, _) = rest.split_once(' ')?;
+                (&[][..], direction == "export", direction.len() + 1)
+            }
+            "profile" => {
+                let (direction, _) = rest.split_once(' ')?;
+                (&["toml"][..], direction == "export", direction.len() + 1)
+            }
+            _ => return None,
+        };
+        let start = self.input.len() - rest.len() + offset;
+        Some(PathArg {
+            start,
+            text: &self.input[start..],
+            extensions,
+            writing,
+        })
+    }
+}
+
+/// 解析命令行為操作。
+pub fn parse(line: &str) -> Result<Action, String> {
+    let line = line.trim();
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    if name == "q!" || name == "quit!" {
+        return Ok(Action::ForceQuit);
+    }
+    // `:export!` 不待查詢完成，`:tabclose!` 不經確認
+    let (name, bang) = match name.strip_suffix('!') {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    let name = resolve_name(name)?;
+    if bang && name != "export" && name != "tabclose" {
+        return Err(format!(":{name} takes no !"));
+    }
+    let require = |what: &str| {
+        if rest.is_empty() {
+            Err(format!(":{name} needs {what}"))
+        } else {
+            Ok(rest)
+        }
+    };
+    match name {
+        "open" => Ok(Action::Open(path_input::expand(require("a path")?))),
+        "sample" => Ok(Action::LoadSample),
+        "export" => {
+            let (format, path) = require("a format and a path")?
+                .split_once(char::is_whitespace)
+                .ok_or(":export needs a format and a path")?;
+            let format = ExportFormat::from_name(format)
+                .ok_or_else(|| format!("Unknown export format: {format}"))?;
+            match Sink::parse(path)? {
+                Sink::File(path) => {
+                    let path = path_input::expand(&path.to_string_lossy());
+                    Ok(if bang {
+                        Action::ExportLoaded(format, path)
+                    } else {
+                        Action::Export(format, path)
+                    })
+                }
+                _ if bang => Err(":export! writes only files".to_string()),
+                Sink::Append(path) => Ok(Action::ExportTo(
+                    format,
+                    Sink::Append(path_input::expand(&path.to_string_lossy())),
+                )),
+                sink => Ok(Action::ExportTo(format, sink)),
+            }
+        }
+        "dump" => {
+            let path = path_input::expand(require("a path")?);
+            let format = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(RdfFormat::from_extension)
+                .ok_or(
+                    ":dump needs a path ending in .ttl, .trig, .nt, .nq or another RDF extension",
+                )?;
+            Ok(Action::Export(ExportFormat::Store(format), path))
+        }
+        "limit" => {
+            let limit: usize = require("a number")?
+                .parse()
+                .map_err(|_| format!("Not a number: {rest}"))?;
+            Ok(Action::SetLimit((limit > 0).then_some(limit)))
+        }
+        "dataset" => match rest.split_whitespace().collect::<Vec<_>>()[..] {
+            [] => Ok(Action::SetDataset(None)),
+            ["default"] => Ok(Action::SetDataset(Some(Dataset::Default))),
+            ["union"] => Ok(Action::SetDataset(Some(Dataset::Union))),
+            ["graphs", ref graphs @ ..] if !graphs.is_empty() => {
+                let graphs = graphs
+                    .iter()
+                    .map(|graph| parse_graph(graph))
+                    .collect::<Result<_, _>>()?;
+                Ok(Action::SetDataset(Some(Dataset::Graphs(graphs))))
+            }
+            _ => Err(":dataset takes default, union or graphs <iri>…".to_string()),
+        },
+        "order" => match rest.trim() {
+            "" => Ok(Action::SetStableOrder(None)),
+            "stable" => Ok(Action::SetStableOrder(Some(true))),
+            "engine" => Ok(Action::SetStableOrder(Some(false))),
+            _ => Err(":order takes stable or engine".to_string()),
+        },
+        "graph" => {
+            let words: Vec<&str> = require("an IRI")?.split_whitespace().collect();
+            match words.as_slice() {
+                ["copy", source, target] => Ok(Action::GraphOp(GraphOp::Copy(
+                    parse_graph(source)?,
+                    parse_graph(target)?,
+                ))),
+                ["move", source, target] => Ok(Action::GraphOp(GraphOp::Move(
+                    parse_graph(source)?,
+                    parse_graph(target)?,
+                ))),
+                ["drop", graph] => Ok(Action::GraphOp(GraphOp::Drop(parse_graph(graph)?))),
+                ["copy" | "move", ..] => Err(format!(":graph {} takes <src> <dst>", words[0])),
+                ["drop", ..] => Err(":graph drop takes <iri>".to_string()),
+                _ => Ok(Action::BrowseGraph(
+                    rest.trim_start_matches('<')
+                        .trim_end_matches('>')
+                        .to_string(),
+                )),
+            }
+        }
+        "clear" => match rest.split_once(char::is_whitespace) {
+            None if rest.is_empty() => Ok(Action::Clear(None)),
+            Some(("graph", iri)) => Ok(Action::Clear(Some(
+                iri.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string(),
+            ))),
+            _ => Err(":clear takes no argument or graph <iri>".to_string()),
+        },
+        "reload" => Ok(Action::Reload),
+        "tabnew" => Ok(Action::NewTab(
+            (!rest.is_empty()).then(|| path_input::expand(rest)),
+        )),
+        "tabclose" if bang => Ok(Action::ForceCloseTab),
+        "tabclose" => Ok(Action::CloseTab),
+        "tabnext" => Ok(Action::CycleTab(1)),
+        "tabprevious" => Ok(Action::CycleTab(-1)),
+        "tabdo" => match rest.split_once(char::is_whitespace) {
+            None if rest.is_empty() => Ok(Action::ShowTabRuns),
+            None if rest == "run" => Ok(Action::RunInTabs),
+            Some(("export", path)) => Ok(Action::ExportTabRuns(path_input::expand(path.trim()))),
+            _ => Err(":tabdo takes run or export <path>".to_string()),
+        },
+        "recent" => Ok(Action::OpenRecent),
+        "history" => Ok(Action::OpenHistory),
+        // 子句之語法見 `Filter`，正則以 `/…/` 標明
+        "filter" => Ok(Action::SetFilter {
+            pattern: (!rest.is_empty()).then(|| rest.to_string()),
+            regex: false,
+        }),
+        "view" => match require("save or open and a name")?.split_once(char::is_whitespace) {
+            Some(("save", name)) => Ok(Action::SaveView(name.trim().to_string())),
+            Some(("open", name)) => Ok(Action::OpenView(name.trim().to_string())),
+            _ => Err(":view takes save <name> or open <name>".to_string()),
+        },
+        "library" => {
+            match require("import or export and a directory")?.split_once(char::is_whitespace) {
+                Some(("import", dir)) => Ok(Action::ImportLibrary(path_input::expand(dir.trim()))),
+                Some(("export", dir)) => Ok(Action::ExportLibrary(path_input::expand(dir.trim()))),
+                _ => Err(":library takes import <dir> or export <dir>".to_string()),
+            }
+        }
+        "profile" => {
+            match require("import or export and a path")?.split_once(char::is_whitespace) {
+                Some(("import", path)) => {
+                    Ok(Action::ImportProfile(path_input::expand(path.trim())))
+                }
+                Some(("export", path)) => {
+                    Ok(Action::ExportProfile(path_input::expand(path.trim())))
+                }
+                _ => Err(":profile takes import <path> or export <path>".to_string()),
+            }
+        }
+        "values" => match require("raw, plain or typed")? {
+            "raw" => Ok(Action::SetValues(Values::Raw)),
+            "plain" => Ok(Action::SetValues(Values::Plain)),
+            "typed" => Ok(Action::SetValues(Values::Typed)),
+            _ => Err(":values takes raw, plain or typed".to_string()),
+        },
+        "watch" => match require("an interval or off")? {
+            "off" => Ok(Action::Watch(None)),
+            interval => Ok(Action::Watch(Some(parse_interval(interval)?))),
+        },
+        "stats" => match rest {
+            "" => Ok(Action::ShowStats),
+            "col" | "column" => Ok(Action::ShowColumnStats),
+            _ => Err(":stats takes no argument or col".to_string()),
+        },
+        "graphs" => Ok(Action::ShowGraphs),
+        "paths" => Ok(Action::ShowPaths),
+        "memory" => Ok(Action::ShowMemory),
+        "updates" => Ok(Action::ShowUpdates),
+        "pin" => match rest {
+            "" => Ok(Action::PinCell),
+            text => Ok(Action::PinText(text.to_string())),
+        },
+        "layout" => Ok(Action::SetLayout(
+            Some(rest.to_string()).filter(|name| !name.is_empty()),
+        )),
+        "yank" => match rest {
+            "" => Ok(Action::Yank),
+            name => Yank::parse(name)
+                .map(Action::YankAs)
+                .ok_or_else(|| format!("Unknown form: {name} (iri, curie, label, value, row, nt)")),
+        },
+        "share" => Ok(Action::Share),
+        "debug" => match rest {
+            "" => Ok(Action::ToggleDebug),
+            "log" => Ok(Action::LogDebug),
+            _ => Err(":debug takes no argument or log".to_string()),
+        },
+        "goto" => Ok(Action::Goto(require("a link")?.to_string())),
+        "restore-checkpoint" => match rest {
+            "" => Ok(Action::RestoreCheckpoint(None)),
+            n => match n.parse() {
+                Ok(n) if n > 0 => Ok(Action::RestoreCheckpoint(Some(n))),
+                _ => Err(format!("Not a checkpoint number: {n}")),
+            },
+        },
+        "undo-data" => match rest {
+            "" => Ok(Action::UndoData(Some(1))),
+            "all" => Ok(Action::UndoData(None)),
+            count => match count.parse() {
+                Ok(count) if count > 0 => Ok(Action::UndoData(Some(count))),
+                _ => Err(":undo-data takes no argument, a count or all".to_string()),
+            },
+        },
+        "check" => match rest.split_once(char::is_whitespace) {
+            _ if rest.is_empty() => Ok(Action::RunChecks),
+            Some(("functional", predicate)) => {
+                Ok(Action::CheckFunctional(Some(predicate.trim().to_string())))
+            }
+            None if rest == "functional" => Ok(Action::CheckFunctional(None)),
+            _ => Err(":check takes no argument or functional [<predicate>]".to_string()),
+        },
+        "dupes" => {
+            let (normalized, predicate) = match rest.split_once(char::is_whitespace) {
+                Some(("-i", predicate)) => (true, predicate.trim()),
+                None if rest == "-i" => (true, ""),
+                _ => (false, rest),
+            };
+            Ok(Action::FindDupes {
+                predicate: (!predicate.is_empty()).then(|| predicate.to_string()),
+                normalized,
+            })
+        }
+        "rename" => match rest.split_whitespace().collect::<Vec<_>>()[..] {
+            [from, to] => Ok(Action::RenameVariable(from.to_string(), to.to_string())),
+            _ => Err(":rename takes a variable and its new name".to_string()),
+        },
+        "normalize" => Ok(Action::NormalizeQuery),
+        "macros" => Ok(Action::ListMacros),
+        "help" => Ok(Action::ShowHelp),
+        "quit" => Ok(Action::Quit),
+        _ => unreachable!(),
+    }
+}
+
+/// 圖名：`default` 為默認圖，餘者為 IRI，尖括號可省。
+fn parse_graph(text: &str) -> Result<GraphName, String> {
+    if text.eq_ignore_ascii_case("default") {
+        return Ok(GraphName::DefaultGraph);
+    }
+    let iri = text.trim_start_matches('<').trim_end_matches('>');
+    NamedNode::new(iri)
+        .map(GraphName::from)
+        .map_err(|_| format!("Invalid graph IRI <{iri}>"))
+}
+
+/// 以唯一前綴或全名解析命令名。
+fn resolve_name(name: &str) -> Result<&'static str, String> {
+    if name == "q" {
+        return Ok("quit");
+    }
+    let matches: Vec<_> = COMMANDS
+        .iter()
+        .map(|(command, _, _)| *command)
+        .filter(|command| command.starts_with(name))
+        .collect();
+    match matches.as_slice() {
+        _ if name.is_empty() => Err("Empty command (try :help)".to_string()),
+        [command] => Ok(command),
+        _ if matches.contains(&name) => Ok(matches.into_iter().find(|c| *c == name).unwrap()),
+        [] => Err(format!("Unknown command: {name} (try :help)")),
+        _ => Err(format!(
+            "Ambiguous command: {name} ({})",
+            matches.join(", ")
+        )),
+    }
+}
+
+/// 補全首詞為命令名。路徑參數見 [`CommandLine::path_arg`]。
+fn complete(line: &str) -> (String, Vec<String>) {
+    if line.contains(' ') {
+        return (line.to_string(), vec![]);
+    }
+    let names: Vec<String> = COMMANDS
+        .iter()
+        .map(|(command, _, _)| command.to_string())
+        .filter(|command| command.starts_with(line))
+        .collect();
+    match names.as_slice() {
+        [name] => (format!("{name} "), vec![]),
+        _ => (common_prefix(line, &names), names),
+    }
+}
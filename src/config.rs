@@ -0,0 +1,914 @@
+use crate::{
+    action::{Action, BINDABLE},
+    app::Mode,
+    display::Bidi,
+    keymap::{parse_sequence, KeySequence, Keymap},
+    layout::{self, Preset},
+    locale::Language,
+    paths::Paths,
+    theme::{parse_color, ColorSupport, Theme, Tint, PRESETS},
+    widths::DEFAULT_SAMPLE,
+    yank::Yank,
+};
+use anyhow::{bail, Context};
+use ratatui::style::Color;
+use std::{fmt::Write, fs, path::PathBuf, time::Duration};
+
+/// 配置文件之模板，即諸項之默認值。`--print-default-config` 輸出之。
+pub const DEFAULT_CONFIG: &str = r##"# rdf-tui configuration
+# Read from config.toml in the config directory (or --config PATH); :paths shows it.
+# Command line flags take precedence over these settings.
+
+# Show at most this many rows of a result (0 = no cap).
+# max_rows = 0
+
+# Also stop a result at this many cells (rows times columns), or once its
+# terms and rows take about this many megabytes, whichever comes first; 0 for
+# no cap. The table title and status bar say which cap cut it. Exports run the
+# query again in full.
+# max_cells = 0
+# max_result_memory = 1024
+
+# Preferred language tag for labels.
+# lang = "en"
+
+# Language of the interface: "en" or "zh". By default it follows LC_ALL,
+# LC_MESSAGES or LANG; --lang-ui overrides it.
+# lang_ui = "zh"
+
+# Color theme: "dark", "light" or "high-contrast". If unset, the light or
+# dark theme is chosen by the background color the terminal reports, and dark
+# when it reports none. Individual colors can be overridden in the [theme] section below.
+# theme = "dark"
+
+# Use no colors at all; focus, errors and the selection are marked with text.
+# Also enabled by the NO_COLOR environment variable or --no-color.
+# no_color = false
+
+# Seconds to wait for remote SPARQL endpoints, owl:imports and `#` counts.
+# timeout = 30
+
+# Run the query while typing. When false, it runs on leaving the editor.
+# autorun = true
+
+# Milliseconds between checks for input.
+# tick_rate = 16
+
+# Text shown in cells of unbound variables.
+# null = ""
+
+# Show the most relevant keys on the bottom line.
+# key_hints = true
+
+# Show the dataset and its size in the terminal title.
+# terminal_title = true
+
+# The layout of the panes on startup: "default", "editing" (a tall query),
+# "triage" (the table alone; the query shows while editing it), "explore"
+# (the preview of the selected cell beside the table), "monitor" (statistics
+# beside the table) or one of [layouts] below. w cycles through them and
+# :layout <name> picks one.
+# layout = "default"
+
+# Keys of the query editor: "default", or "vim" (Esc leaves the editor).
+# keyscheme = "default"
+
+# Size the columns by the widest cell among this many first rows of a result
+# and the rows shown so far. Raise it for exact widths on small results.
+# width_sample = 1000
+
+# On wider terminals the table is at most this wide and centered, with a
+# preview of the selected cell beside it when there is room. 0 for no limit.
+# max_table_width = 160
+
+# Tint the cells: "off", "kind" (IRIs, literals and blank nodes each in a
+# color of the theme) or "column" (a faint background per column). c cycles
+# through them. Without colors, "kind" writes prefixed IRIs as <ex:name>.
+# tint = "off"
+
+# Right-to-left text in cells: "visual" (bidi controls removed and Hebrew or
+# Arabic reordered for terminals that show text as stored), "logical" (bidi
+# controls removed, order left to terminals that reorder themselves) or
+# "escape" (bidi controls shown as \uXXXX). ⇄ marks cells that had controls.
+# bidi_cells = "visual"
+
+# Let ←/→ (h/l) wrap around from the last column of the table to the first
+# and back, instead of stopping at either end.
+# wrap_columns = false
+
+# What y y copies from the selected cell: "iri", "curie", "label", "value",
+# "row" (the whole row as TSV) or "ntriples". y followed by i, c, l, v, r or n
+# picks one each time.
+# yank = "ntriples"
+
+# Before running a query, ask whether to normalize curly quotes, non-ASCII
+# spaces and invisible characters pasted into it. They are highlighted in the
+# editor either way; Ctrl+N replaces them.
+# check_typography = true
+
+# Sort results of queries without ORDER BY by the terms (unbound, blank nodes,
+# IRIs, literals; numbers by value, the rest by text), so reruns, watch
+# refreshes and screenshots show the same order. O toggles it.
+# stable_order = false
+
+# Run the .rdf-tui.rq file found next to the data on startup. Turn this off
+# for data you do not trust; --no-startup-query skips it for one run.
+# startup_query_files = true
+
+# Listing the triples that point at a resource (<) counts them first. Past
+# this many, it asks whether to load the first 1000, group them by predicate
+# or skip, so a hub node does not load millions of rows.
+# hub_threshold = 10000
+
+# Keep the first rows of each query run in this session, shown in the history
+# (H) before running one again; 0 keeps none. When the snapshots take more than
+# snapshot_memory megabytes, the oldest are dropped first.
+# snapshot_rows = 20
+# snapshot_memory = 16
+
+# Approximate megabytes that the store, results, snapshots and caches may take
+# together; 0 for no budget. Over it, old snapshots, the cell cache and the
+# previous result kept for comparison (D) are dropped, and a query whose rows
+# would pass it stops to ask whether to cap the rows there. The status bar
+# shows the total (mem).
+# memory_budget = 0
+
+# Before loading files whose store would take more than this percent of the
+# memory available (judged from their size), ask whether to load them; 0 never
+# asks.
+# load_memory_share = 50
+
+# Retry a URL download this many times when the connection drops or the server
+# is busy, resuming where it stopped; waits 1, 2, 4… seconds in between.
+# download_retries = 3
+
+# After an update or reload, the class hierarchy (h) and neighborhood (n) trees
+# rebuild themselves in the background when the store has at most
+# auto_refresh_quads quads or they last took under auto_refresh_ms
+# milliseconds to build. Otherwise they keep showing the old tree, marked
+# stale, until R rebuilds them.
+# auto_refresh_quads = 1000000
+# auto_refresh_ms = 500
+
+# Keep what a cancelled or failed export wrote, as <path>.partial.
+# keep_partial_exports = false
+
+# The program that g x opens the selected IRI with, and its arguments. It is
+# run directly, never through a shell: each item is one argument, and "{iri}"
+# within one is replaced by the IRI (or the IRI is added last). By default the
+# system's opener (xdg-open, open or explorer).
+# open_command = ["firefox", "--new-tab", "{iri}"]
+
+# Schemes besides http, https and file that g x may open.
+# open_schemes = ["mailto"]
+
+# The query in the editor when no startup query or --query replaces it. By
+# default the whole store is listed, with the graph of each triple when there
+# are named graphs, and only the first 100 rows of stores over large_store
+# triples.
+# default_query = "SELECT ?s ?p ?o WHERE { ?s ?p ?o }"
+# large_store = 1000000
+
+# Queries run on startup instead of the default one, by data file path.
+# They take precedence over .rdf-tui.rq files.
+[startup_queries]
+# "/data/catalog.ttl" = "SELECT ?class (COUNT(*) AS ?n) WHERE { [] a ?class } GROUP BY ?class"
+
+# Prefixes used to abbreviate IRIs in the table.
+[prefixes]
+# rdf = "http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+# rdfs = "http://www.w3.org/2000/01/rdf-schema#"
+
+# Layouts of your own, or replacing a built-in one of the same name: a
+# comma-separated list of query:<size> (or query:hidden), preview:<size>,
+# stats:<size> and beside (the panes right of the table rather than below).
+# A size is auto (query only), a number of lines (columns beside) or a
+# percentage.
+[layouts]
+# wide = "query:30%, preview:40%, stats:20%, beside"
+
+# Colors overriding the theme: a name ("red", "lightblue"), "#rrggbb" or an
+# index 0-255. Append _bg for the background. Colors are reduced to what the
+# terminal supports.
+[theme]
+# focused_border = "green"
+# selected_bg = "#264f78"
+# error = "red"
+# iri = "cyan"
+# column_1_bg = "#1c202a"
+
+# Key bindings of Browse mode: action = "key" or ["key", "key sequence"].
+# Keys are written like "q", "Q", "ctrl-enter", "shift-tab", "g g".
+# Binding an action replaces its default keys; [] unbinds it.
+# [keys.query], [keys.hierarchy], [keys.neighborhood], [keys.bookmarks],
+# [keys.scratchpad], [keys.recent], [keys.history], [keys.pattern], [keys.command],
+# [keys.filter] and [keys.edit] configure the other modes. See the help (?) for
+# the action names. :profile export writes all of these, as in effect, to a file
+# that --profile or :profile import applies over this one.
+[keys]
+# quit = "q"
+# force_quit = "Q"
+# select_next = ["down", "j"]
+# select_previous = ["up", "k"]
+# select_first = ["g g", "home"]
+"##;
+
+/// 頂層之有效鍵。
+pub(crate) const KEYS: &[&str] = &[
+    "max_rows",
+    "max_cells",
+    "max_result_memory",
+    "lang",
+    "lang_ui",
+    "theme",
+    "no_color",
+    "timeout",
+    "autorun",
+    "tick_rate",
+    "null",
+    "layout",
+    "keyscheme",
+    "terminal_title",
+    "key_hints",
+    "startup_query_files",
+    "width_sample",
+    "max_table_width",
+    "tint",
+    "bidi_cells",
+    "wrap_columns",
+    "yank",
+    "check_typography",
+    "stable_order",
+    "default_query",
+    "large_store",
+    "auto_refresh_quads",
+    "auto_refresh_ms",
+    "keep_partial_exports",
+    "open_command",
+    "open_schemes",
+    "hub_threshold",
+    "snapshot_rows",
+    "snapshot_memory",
+    "memory_budget",
+    "load_memory_share",
+    "download_retries",
+];
+
+/// 有效之節。
+pub(crate) const SECTIONS: &[&str] = &[
+    "prefixes",
+    "startup_queries",
+    "layouts",
+    "theme",
+    "keys",
+    "keys.query",
+    "keys.hierarchy",
+    "keys.neighborhood",
+    "keys.bookmarks",
+    "keys.scratchpad",
+    "keys.recent",
+    "keys.history",
+    "keys.pattern",
+    "keys.command",
+    "keys.filter",
+    "keys.edit",
+];
+
+/// 查詢編輯器之鍵位方案。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Keyscheme {
+    /// Tab 離開編輯器。
+    Default,
+    /// Esc 離開編輯器。
+    Vim,
+}
+
+/// 用戶配置。
+///
+/// 日後或增設置，故嵌入者宜取 [`Config::default`] 再改其字段。
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Config {
+    /// 顯示之行數上限。
+    pub max_rows: Option<usize>,
+    /// 顯示之格數上限。
+    pub max_cells: Option<usize>,
+    /// 顯示之結果之估計字節數上限。
+    pub max_result_memory: Option<usize>,
+    pub lang: Option<String>,
+    /// 界面之語言。未設則依環境變量。
+    pub ui_language: Option<Language>,
+    /// 預設主題之名。未設則依終端之背景選之。
+    pub theme: Option<String>,
+    /// 覆蓋主題之顏色。
+    pub colors: Vec<(String, Color)>,
+    /// 不用顏色。
+    pub no_color: bool,
+    /// 遠端請求之超時。
+    pub timeout: Duration,
+    /// 輸入時即查詢。
+    pub autorun: bool,
+    /// 輪詢輸入之間隔。
+    pub tick_rate: Duration,
+    /// 未綁定之單元格所顯示之文字。
+    pub null: String,
+    /// 啓動時窗格之佈局預設之名。
+    pub layout: String,
+    /// 配置之佈局預設，同名者代內置者。
+    pub layouts: Vec<Preset>,
+    pub keyscheme: Keyscheme,
+    /// 於底行顯示按鍵提示。
+    pub key_hints: bool,
+    /// 於終端標題顯示數據集。
+    pub terminal_title: bool,
+    /// 縮寫 IRI 所用之前綴及其命名空間。
+    pub prefixes: Vec<(String, String)>,
+    /// 默認鍵位合以 `[keys]` 諸節。
+    pub keymap: Keymap,
+    /// 量列寬所取樣之前若干行。可見之行另量之。
+    pub width_sample: usize,
+    /// 寬終端中表之寬之上限，零則不限。
+    pub max_table_width: u16,
+    /// 結果格之色調。
+    pub tint: Tint,
+    /// 單元格中雙向文字之顯示。
+    pub bidi_cells: Bidi,
+    /// 左右移所選之格越表之兩端則繞回。
+    pub wrap_columns: bool,
+    /// `y y` 所複製之形式。
+    pub yank: Yank,
+    /// 執行前問是否代查詢中之可疑字符。
+    pub check_typography: bool,
+    /// 無 ORDER BY 之結果依項之序排列。
+    pub stable_order: bool,
+    /// 啓動時執行數據文件旁之 `.rdf-tui.rq`。
+    pub startup_query_files: bool,
+    /// 各數據文件啓動時之查詢，先於其旁之文件。
+    pub startup_queries: Vec<(PathBuf, String)>,
+    /// 編輯器初始之查詢。未設則依存儲之大小與命名圖選之。
+    pub default_query: Option<String>,
+    /// 逾此三元組數則默認之查詢限百行。
+    pub large_store: usize,
+    /// 存儲至多此數四元組，則其有變後自動重建過時之樹。
+    pub auto_refresh_quads: usize,
+    /// 上次建之短於此者，存儲有變後亦自動重建。
+    pub auto_refresh_time: Duration,
+    /// 取消或失敗之導出留下所寫之部分。
+    pub keep_partial_exports: bool,
+    /// 打開 IRI 之程序及其參數。未設則用系統之。
+    pub open_command: Option<Vec<String>>,
+    /// 默認之外另許打開之方案。
+    pub open_schemes: Vec<String>,
+    /// 入邊逾此數則先問其列法。
+    pub hub_threshold: usize,
+    /// 查詢歷史中各快照所留之行數。
+    pub snapshot_rows: usize,
+    /// 快照之字節預算。
+    pub snapshot_memory: usize,
+    /// 諸結構合計之字節預算。
+    pub memory_budget: Option<usize>,
+    /// 估計之存儲逾可用內存之此百分比則先問而後載入。為零則不問。
+    pub load_memory_share: usize,
+    /// URL 之下載中斷後之重試次數。
+    pub download_retries: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_rows: None,
+            max_cells: None,
+            max_result_memory: Some(1 << 30),
+            lang: None,
+            ui_language: None,
+            theme: None,
+            colors: vec![],
+            no_color: false,
+            timeout: Duration::from_secs(30),
+            autorun: true,
+            tick_rate: Duration::from_millis(16),
+            null: String::new(),
+            layout: "default".to_string(),
+            layouts: vec![],
+            keyscheme: Keyscheme::Default,
+            terminal_title: true,
+            key_hints: true,
+            prefixes: vec![],
+            keymap: Keymap::default(),
+            width_sample: DEFAULT_SAMPLE,
+            max_table_width: 160,
+            tint: Tint::Off,
+            bidi_cells: Bidi::default(),
+            wrap_columns: false,
+            yank: Yank::default(),
+            check_typography: true,
+            stable_order: false,
+            startup_query_files: true,
+            startup_queries: vec![],
+            default_query: None,
+            large_store: 1_000_000,
+            auto_refresh_quads: 1_000_000,
+            auto_refresh_time: Duration::from_millis(500),
+            keep_partial_exports: false,
+            open_command: None,
+            open_schemes: vec![],
+            hub_threshold: 10_000,
+            snapshot_rows: 20,
+            snapshot_memory: 16 << 20,
+            memory_budget: None,
+            load_memory_share: 50,
+            download_retries: 3,
+        }
+    }
+}
+
+impl Config {
+    /// 讀取配置。`--config` 所給者必須存在；否則讀配置目錄中者，不存在則用默認值。
+    /// 返回配置及警告（如未知之鍵）。
+    pub fn load(paths: &Paths) -> anyhow::Result<(Self, Vec<String>)> {
+        let path = match paths.given_config() {
+            Some(path) => path.to_path_buf(),
+            None => match paths.config_file().filter(|path| path.exists()) {
+                Some(path) => path,
+                None => return Ok((Self::default(), vec![])),
+            },
+        };
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Fail to read config {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    /// 解析配置文本。
+    pub fn parse(text: &str) -> anyhow::Result<(Self, Vec<String>)> {
+        let mut config = Self::default();
+        let warnings = config.merge(text)?;
+        Ok((config, warnings))
+    }
+
+    /// 以配置文本之諸項蓋現有之設置，返回警告。前綴、顏色等同名者代之；鍵位方案有變則
+    /// 鍵位自其默認重建，否則所給之操作逐一改綁。出錯則已設之項不復原，宜施於副本。
+    pub fn merge(&mut self, text: &str) -> anyhow::Result<Vec<String>> {
+        let keyscheme = self.keyscheme;
+        let mut warnings = vec![];
+        // 鍵位待鍵位方案確定後方合併
+        let mut bindings = vec![];
+        for entry in parse_toml(text)? {
+            let line = entry.line;
+            let result = match entry.section.as_str() {
+                "" => self.set(&entry.key, entry.value, line, &mut warnings),
+                "prefixes" => match entry.value {
+                    Value::String(namespace) => {
+                        self.prefixes.retain(|(name, _)| *name != entry.key);
+                        self.prefixes.push((entry.key, namespace));
+                        Ok(())
+                    }
+                    _ => Err("prefix namespace must be a string".to_string()),
+                },
+                "startup_queries" => match entry.value {
+                    Value::String(query) => {
+                        let path = PathBuf::from(entry.key);
+                        self.startup_queries.retain(|(known, _)| *known != path);
+                        self.startup_queries.push((path, query));
+                        Ok(())
+                    }
+                    _ => Err("startup queries must be strings".to_string()),
+                },
+                "layouts" => match entry.value {
+                    Value::String(spec) => Preset::parse(&entry.key, &spec).map(|preset| {
+                        self.layouts.retain(|known| known.name != preset.name);
+                        self.layouts.push(preset);
+                    }),
+                    _ => Err("layouts must be strings".to_string()),
+                },
+                "theme" => match entry.value {
+                    Value::String(color) => match parse_color(&color) {
+                        // 以默認主題驗證樣式名
+                        Some(color) => Theme::default().set_color(&entry.key, color).map(|()| {
+                            self.colors.retain(|(name, _)| *name != entry.key);
+                            self.colors.push((entry.key, color));
+                        }),
+                        None => Err(format!("invalid color `{color}`")),
+                    },
+                    _ => Err("colors must be strings".to_string()),
+                },
+                section if section == "keys" || section.starts_with("keys.") => {
+                    match section_mode(section) {
+                        Some(mode) => match Action::from_name(&entry.key) {
+                            Some(action) => key_sequences(entry.value)
+                                .map(|sequences| bindings.push((mode, action, sequences))),
+                            None => {
+                                let names: Vec<_> =
+                                    BINDABLE.iter().map(|(name, _, _)| *name).collect();
+                                warnings.push(format!(
+                                    "line {line}: unknown action `{}` (valid actions: {})",
+                                    entry.key,
+                                    names.join(", ")
+                                ));
+                                Ok(())
+                            }
+                        },
+                        None => {
+                            warnings.push(format!(
+                                "line {line}: unknown section [{section}] (valid sections: {})",
+                                SECTIONS.join(", ")
+                            ));
+                            Ok(())
+                        }
+                    }
+                }
+                section => {
+                    warnings.push(format!(
+                        "line {line}: unknown section [{section}] (valid sections: {})",
+                        SECTIONS.join(", ")
+                    ));
+                    Ok(())
+                }
+            };
+            if let Err(message) = result {
+                bail!("line {line}: {message}");
+            }
+        }
+        let layouts = layout::presets(&self.layouts);
+        if !layouts.iter().any(|preset| preset.name == self.layout) {
+            let names: Vec<&str> = layouts.iter().map(|preset| preset.name.as_str()).collect();
+            warnings.push(format!(
+                "unknown layout `{}` (valid layouts: {})",
+                self.layout,
+                names.join(", ")
+            ));
+            self.layout = "default".to_string();
+        }
+        if self.keyscheme != keyscheme {
+            self.keymap = Keymap::new(self.keyscheme);
+        }
+        for (mode, action, sequences) in bindings {
+            self.keymap.bind(mode, action, sequences);
+        }
+        Ok(warnings)
+    }
+
+    /// 設置預設主題。
+    pub fn set_theme(&mut self, theme: String) -> Result<(), String> {
+        if !PRESETS.contains(&theme.as_str()) {
+            return Err(format!(
+                "unknown theme `{theme}` (valid themes: {})",
+                PRESETS.join(", ")
+            ));
+        }
+        self.theme = Some(theme);
+        Ok(())
+    }
+
+    /// 預設主題合以所覆蓋之顏色，並依終端能力降級。
+    pub fn theme(&self) -> Theme {
+        if self.no_color {
+            return Theme::monochrome();
+        }
+        let mut theme = Theme::preset(self.theme.as_deref().unwrap_or("dark")).unwrap_or_default();
+        for (name, color) in &self.colors {
+            let _ = theme.set_color(name, *color);
+        }
+        theme.degrade(ColorSupport::detect())
+    }
+
+    /// 設置頂層之一鍵。類型不符為錯誤，未知之鍵為警告。
+    fn set(
+        &mut self,
+        key: &str,
+        value: Value,
+        line: usize,
+        warnings: &mut Vec<String>,
+    ) -> Result<(), String> {
+        match key {
+            "max_rows" => self.max_rows = Some(value.non_negative(key)?).filter(|n| *n > 0),
+            "max_cells" => self.max_cells = Some(value.non_negative(key)?).filter(|n| *n > 0),
+            "max_result_memory" => {
+                self.max_result_memory = Some(value.non_negative(key)?)
+                    .filter(|megabytes| *megabytes > 0)
+                    .map(|megabytes| megabytes.saturating_mul(1 << 20))
+            }
+            "lang" => self.lang = Some(value.string(key)?).filter(|s| !s.is_empty()),
+            "lang_ui" => self.ui_language = Some(value.string(key)?.parse()?),
+            "theme" => {
+                let theme = value.string(key)?;
+                self.set_theme(theme)?;
+            }
+            "no_color" => self.no_color = value.boolean(key)?,
+            "timeout" => self.timeout = Duration::from_secs(value.non_negative(key)? as u64),
+            "autorun" => self.autorun = value.boolean(key)?,
+            "tick_rate" => {
+                self.tick_rate = Duration::from_millis(value.non_negative(key)?.max(1) as u64)
+            }
+            "null" => self.null = value.string(key)?,
+            "layout" => self.layout = value.string(key)?,
+            "keyscheme" => {
+                self.keyscheme = match value.string(key)?.as_str() {
+                    "default" => Keyscheme::Default,
+                    "vim" => Keyscheme::Vim,
+                    other => return Err(format!("unknown keyscheme `{other}` (default, vim)")),
+                }
+            }
+            "key_hints" => self.key_hints = value.boolean(key)?,
+            "terminal_title" => self.terminal_title = value.boolean(key)?,
+            "startup_query_files" => self.startup_query_files = value.boolean(key)?,
+            "width_sample" => self.width_sample = value.non_negative(key)?,
+            "max_table_width" => {
+                self.max_table_width = value.non_negative(key)?.min(u16::MAX.into()) as u16
+            }
+            "tint" => {
+                let name = value.string(key)?;
+                self.tint = Tint::parse(&name)
+                    .ok_or_else(|| format!("unknown tint `{name}` (off, kind, column)"))?;
+            }
+            "bidi_cells" => {
+                let name = value.string(key)?;
+                self.bidi_cells = Bidi::parse(&name).ok_or_else(|| {
+                    format!("unknown bidi_cells `{name}` (visual, logical, escape)")
+                })?;
+            }
+            "wrap_columns" => self.wrap_columns = value.boolean(key)?,
+            "yank" => {
+                let name = value.string(key)?;
+                self.yank = Yank::parse(&name).ok_or_else(|| {
+                    format!("unknown yank `{name}` (iri, curie, label, value, row, ntriples)")
+                })?;
+            }
+            "check_typography" => self.check_typography = value.boolean(key)?,
+            "stable_order" => self.stable_order = value.boolean(key)?,
+            "default_query" => {
+                self.default_query = Some(value.string(key)?).filter(|s| !s.trim().is_empty())
+            }
+            "large_store" => self.large_store = value.non_negative(key)?,
+            "auto_refresh_quads" => self.auto_refresh_quads = value.non_negative(key)?,
+            "auto_refresh_ms" => {
+                self.auto_refresh_time = Duration::from_millis(value.non_negative(key)? as u64)
+            }
+            "keep_partial_exports" => self.keep_partial_exports = value.boolean(key)?,
+            "open_command" => {
+                self.open_command = Some(value.strings(key)?).filter(|args| !args.is_empty())
+            }
+            "open_schemes" => self.open_schemes = value.strings(key)?,
+            "hub_threshold" => self.hub_threshold = value.non_negative(key)?,
+            "snapshot_rows" => self.snapshot_rows = value.non_negative(key)?,
+            "snapshot_memory" => {
+                self.snapshot_memory = value.non_negative(key)?.saturating_mul(1 << 20)
+            }
+            "memory_budget" => {
+                self.memory_budget = Some(value.non_negative(key)?)
+                    .filter(|megabytes| *megabytes > 0)
+                    .map(|megabytes| megabytes.saturating_mul(1 << 20))
+            }
+            "load_memory_share" => self.load_memory_share = value.non_negative(key)?,
+            "download_retries" => self.download_retries = value.non_negative(key)?,
+            _ => warnings.push(format!(
+                "line {line}: unknown key `{key}` (valid keys: {})",
+                KEYS.join(", ")
+            )),
+        }
+        Ok(())
+    }
+}
+
+/// `[keys]` 諸節所對應之模式。
+pub(crate) fn section_mode(section: &str) -> Option<Mode> {
+    match section {
+        "keys" => Some(Mode::Browse),
+        "keys.query" => Some(Mode::Query),
+        "keys.hierarchy" => Some(Mode::Hierarchy),
+        "keys.neighborhood" => Some(Mode::Neighborhood),
+        "keys.bookmarks" => Some(Mode::Bookmarks),
+        "keys.scratchpad" => Some(Mode::Scratchpad),
+        "keys.recent" => Some(Mode::Recent),
+        "keys.history" => Some(Mode::History),
+        "keys.pattern" => Some(Mode::Pattern),
+        "keys.command" => Some(Mode::Command),
+        "keys.filter" => Some(Mode::Filter),
+        "keys.edit" => Some(Mode::Edit),
+        _ => None,
+    }
+}
+
+/// 鍵位之值：一字串或字串之數組。
+fn key_sequences(value: Value) -> Result<Vec<KeySequence>, String> {
+    let values = match value {
+        Value::Array(values) => values,
+        value => vec![value],
+    };
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::String(keys) => parse_sequence(&keys),
+            _ => Err("key bindings must be strings".to_string()),
+        })
+        .collect()
+}
+
+/// 配置之值。僅支持所需之 TOML 子集：字串、整數、布爾及單行之數組。
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub(crate) fn string(self, key: &str) -> Result<String, String> {
+        match self {
+            Self::String(s) => Ok(s),
+            _ => Err(format!("`{key}` must be a string")),
+        }
+    }
+
+    fn strings(self, key: &str) -> Result<Vec<String>, String> {
+        let error = || format!("`{key}` must be an array of strings");
+        match self {
+            Self::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    Self::String(s) => Ok(s),
+                    _ => Err(error()),
+                })
+                .collect(),
+            _ => Err(error()),
+        }
+    }
+
+    fn boolean(self, key: &str) -> Result<bool, String> {
+        match self {
+            Self::Boolean(b) => Ok(b),
+            _ => Err(format!("`{key}` must be true or false")),
+        }
+    }
+
+    fn non_negative(self, key: &str) -> Result<usize, String> {
+        match self {
+            Self::Integer(n) if n >= 0 => Ok(n as usize),
+            _ => Err(format!("`{key}` must be a non-negative integer")),
+        }
+    }
+}
+
+/// 一條鍵值。
+pub(crate) struct Entry {
+    pub(crate) section: String,
+    pub(crate) key: String,
+    pub(crate) value: Value,
+    pub(crate) line: usize,
+}
+
+/// 解析 TOML 之子集：註釋、`[節]`、`鍵 = 值`。
+pub(crate) fn parse_toml(text: &str) -> anyhow::Result<Vec<Entry>> {
+    let mut entries = vec![];
+    let mut section = String::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('[') {
+            let Some((name, tail)) = rest.split_once(']') else {
+                bail!("line {line_number}: unclosed section header");
+            };
+            if !is_comment_or_empty(tail) {
+                bail!("line {line_number}: unexpected text after section header");
+            }
+            section = name.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {line_number}: expected `key = value`");
+        };
+        let key =
+            parse_key(key.trim()).with_context(|| format!("line {line_number}: invalid key"))?;
+        let value =
+            parse_value(value.trim()).map_err(|e| anyhow::anyhow!("line {line_number}: {e}"))?;
+        entries.push(Entry {
+            section: section.clone(),
+            key,
+            value,
+            line: line_number,
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_key(key: &str) -> Option<String> {
+    if let Some(quoted) = key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+        return Some(quoted.to_string());
+    }
+    let valid = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    valid.then(|| key.to_string())
+}
+
+fn parse_value(value: &str) -> Result<Value, String> {
+    let (value, tail) = parse_item(value)?;
+    if !is_comment_or_empty(tail) {
+        return Err(format!("unexpected text `{}`", tail.trim()));
+    }
+    Ok(value)
+}
+
+/// 解析一值，返回其後之餘文。
+fn parse_item(text: &str) -> Result<(Value, &str), String> {
+    let text = text.trim_start();
+    if let Some(rest) = text.strip_prefix('"') {
+        let (string, tail) = parse_quoted(rest)?;
+        return Ok((Value::String(string), tail));
+    }
+    if let Some(rest) = text.strip_prefix('\'') {
+        let (string, tail) = rest
+            .split_once('\'')
+            .ok_or_else(|| "unterminated string".to_string())?;
+        return Ok((Value::String(string.to_string()), tail));
+    }
+    if let Some(mut rest) = text.strip_prefix('[') {
+        let mut values = vec![];
+        loop {
+            rest = rest.trim_start();
+            if let Some(tail) = rest.strip_prefix(']') {
+                return Ok((Value::Array(values), tail));
+            }
+            let (value, tail) = parse_item(rest)?;
+            values.push(value);
+            let tail = tail.trim_start();
+            rest = match tail.strip_prefix(',') {
+                Some(tail) => tail,
+                None if tail.starts_with(']') => tail,
+                None => return Err("expected `,` or `]` in array".to_string()),
+            };
+        }
+    }
+    let end = text
+        .find(|c: char| c == ',' || c == ']' || c == '#' || c.is_whitespace())
+        .unwrap_or(text.len());
+    let (value, tail) = text.split_at(end);
+    let value = match value {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        _ => value
+            .replace('_', "")
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("invalid value `{value}`"))?,
+    };
+    Ok((value, tail))
+}
+
+/// 解析開引號後之雙引號字串，處理 `\n`、`\t`、`\"`、`\\`、`\uXXXX` 轉義。
+/// 返回字串及閉引號後之餘文。
+pub(crate) fn parse_quoted(text: &str) -> Result<(String, &str), String> {
+    let mut string = String::new();
+    let mut chars = text.chars();
+    loop {
+        match chars.next() {
+            None => return Err("unterminated string".to_string()),
+            Some('"') => break,
+            Some('\\') => string.push(match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('"') => '"',
+                Some('\\') => '\\',
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| format!("invalid escape \\u{hex}"))?
+                }
+                other => return Err(format!("invalid escape \\{}", other.unwrap_or(' '))),
+            }),
+            Some(c) => string.push(c),
+        }
+    }
+    Ok((string, chars.as_str()))
+}
+
+/// TOML 之基本字串。
+pub(crate) fn quote(text: &str) -> String {
+    let mut quoted = String::from('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            ch if ch.is_control() => {
+                let _ = write!(quoted, "\\u{:04X}", ch as u32);
+            }
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn is_comment_or_empty(text: &str) -> bool {
+    let text = text.trim();
+    text.is_empty() || text.starts_with('#')
+}
@@ -0,0 +1,67 @@
+//! 繪製一部件時之 panic 止於該部件，餘者照常繪製，以便用戶尚可導出結果而後重啓。
+
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    panic::{self, AssertUnwindSafe, PanicHookInfo},
+};
+
+thread_local! {
+    /// 正於 [`catch`] 中。panic 鈎子見之則不恢復終端。
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    /// 已記其回溯之消息。同一 panic 每幀重現，只記一次。
+    static LOGGED: RefCell<HashSet<String>> = RefCell::default();
+}
+
+/// 執行 `f`。其 panic 則捕之，返回其消息。
+pub fn catch<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    let outer = CATCHING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(outer);
+    result.map_err(|payload| message(&*payload))
+}
+
+/// 供 panic 鈎子：於 [`catch`] 中者，首見其消息則記其回溯於日誌，返回真，終端不必恢復。
+pub fn handle(info: &PanicHookInfo) -> bool {
+    if !CATCHING.get() {
+        return false;
+    }
+    let first = LOGGED.with_borrow_mut(|logged| logged.insert(message(info.payload())));
+    if first && log::log_enabled!(log::Level::Error) {
+        let backtrace = Backtrace::force_capture();
+        log::error!("contained panic: {info}\n{backtrace}");
+        log::logger().flush();
+    }
+    true
+}
+
+/// panic 之消息，如 `panic!` 所給之文。
+fn message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics_become_their_messages_and_catching_nests() {
+        assert_eq!(catch(|| 1), Ok(1));
+        assert_eq!(catch(|| panic!("plain")), Err::<(), _>("plain".to_string()));
+        let inner = catch(|| {
+            let inner = catch(|| panic!("at {}", 2));
+            assert!(CATCHING.get());
+            inner
+        });
+        assert_eq!(inner, Ok(Err("at 2".to_string())));
+        assert!(!CATCHING.get());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::display::group_digits;
+use oxigraph::{
+    model::{SubjectRef, Term},
+    store::Store,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// 每數若干四元組查看一次是否逾時或被取消。
+const CHECK_EVERY: usize = 4096;
+
+/// 項在三元組中之位置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    Subject,
+    Predicate,
+    Object,
+}
+
+impl Position {
+    /// 由列名推斷，如 `?s`、`?predicate`。
+    pub fn of_column(name: &str) -> Option<Self> {
+        match name {
+            "s" | "subject" => Some(Self::Subject),
+            "p" | "predicate" => Some(Self::Predicate),
+            "o" | "object" => Some(Self::Object),
+            _ => None,
+        }
+    }
+
+    /// 此項可居之位置：字面量唯為賓語，IRI 三者皆可。
+    pub fn possible(term: &Term) -> Vec<Self> {
+        match term {
+            Term::NamedNode(_) => vec![Self::Subject, Self::Predicate, Self::Object],
+            Term::Literal(_) => vec![Self::Object],
+            _ => vec![Self::Subject, Self::Object],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Subject => "subject",
+            Self::Predicate => "predicate",
+            Self::Object => "object",
+        }
+    }
+}
+
+/// 一位置之計數：默認圖中者，及具名圖中者。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tally {
+    pub position: Position,
+    pub default: usize,
+    pub named: usize,
+}
+
+/// 後台數某項於各位置之三元組，同 `SELECT (COUNT(*) AS ?n) WHERE { ?s <p> ?o }`，
+/// 而直接遍歷存儲，故空白節點亦可數。丟棄即取消。
+pub struct Count {
+    /// 狀態欄中此項之寫法。
+    pub label: String,
+    receiver: Receiver<Result<Vec<Tally>, String>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Count {
+    pub fn spawn(
+        store: &Store,
+        term: Term,
+        positions: Vec<Position>,
+        label: String,
+        timeout: Duration,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (store, stop) = (store.clone(), Arc::clone(&cancelled));
+        thread::spawn(move || {
+            let deadline = Instant::now() + timeout;
+            let tallies = positions
+                .into_iter()
+                .map(|position| tally(&store, &term, position, &stop, deadline))
+                .collect();
+            let _ = sender.send(tallies);
+        });
+        Self {
+            label,
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// 已數畢則返回結果。
+    pub fn poll(&self) -> Option<Result<Vec<Tally>, String>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Drop for Count {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+fn tally(
+    store: &Store,
+    term: &Term,
+    position: Position,
+    cancelled: &AtomicBool,
+    deadline: Instant,
+) -> Result<Tally, String> {
+    let mut tally = Tally {
+        position,
+        default: 0,
+        named: 0,
+    };
+    let quads = match (position, term) {
+        (Position::Subject, Term::NamedNode(iri)) => {
+            store.quads_for_pattern(Some(iri.into()), None, None, None)
+        }
+        (Position::Subject, Term::BlankNode(node)) => {
+            store.quads_for_pattern(Some(node.into()), None, None, None)
+        }
+        (Position::Subject, Term::Triple(triple)) => {
+            store.quads_for_pattern(Some(SubjectRef::Triple(triple)), None, None, None)
+        }
+        (Position::Predicate, Term::NamedNode(iri)) => {
+            store.quads_for_pattern(None, Some(iri.into()), None, None)
+        }
+        (Position::Subject | Position::Predicate, _) => return Ok(tally),
+        (Position::Object, term) => store.quads_for_pattern(None, None, Some(term.as_ref()), None),
+    };
+    for (i, quad) in quads.enumerate() {
+        if i % CHECK_EVERY == 0 {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err("cancelled".to_string());
+            }
+            if Instant::now() > deadline {
+                return Err(format!(
+                    "timed out after {} triples",
+                    group_digits(tally.default + tally.named)
+                ));
+            }
+        }
+        if quad
+            .map_err(|error| error.to_string())?
+            .graph_name
+            .is_default_graph()
+        {
+            tally.default += 1;
+        } else {
+            tally.named += 1;
+        }
+    }
+    Ok(tally)
+}
+
+/// 狀態欄之文字，如 `ex:p as predicate: 1,234 triples (+5 in named graphs)`；
+/// 數個位置則如 `ex:a: 12 as subject, 0 as predicate, 3 as object`。
+pub fn describe(label: &str, tallies: &[Tally]) -> String {
+    let named = |tally: &Tally| match tally.named {
+        0 => String::new(),
+        n => format!(" (+{} in named graphs)", group_digits(n)),
+    };
+    match tallies {
+        [tally] => {
+            let triples = if tally.default == 1 {
+                "triple"
+            } else {
+                "triples"
+            };
+            format!(
+                "{label} as {}: {} {triples}{}",
+                tally.position.name(),
+                group_digits(tally.default),
+                named(tally)
+            )
+        }
+        tallies => {
+            let parts: Vec<String> = tallies
+                .iter()
+                .map(|tally| {
+                    format!(
+                        "{} as {}{}",
+                        group_digits(tally.default),
+                        tally.position.name(),
+                        named(tally)
+                    )
+                })
+                .collect();
+            format!("{label}: {}", parts.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::{
+        io::{RdfFormat, RdfParser},
+        model::{Literal, NamedNode},
+    };
+
+    #[test]
+    fn counts_split_default_and_named_graphs() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::TriG),
+                "<http://ex/a> <http://ex/p> 1, 2 ; <http://ex/q> <http://ex/p> . \
+                 <http://ex/g> { <http://ex/b> <http://ex/p> 1 }"
+                    .as_bytes(),
+            )
+            .unwrap();
+        let count = |term: Term, positions| {
+            let count = Count::spawn(
+                &store,
+                term,
+                positions,
+                "x".to_string(),
+                Duration::from_secs(10),
+            );
+            loop {
+                if let Some(result) = count.poll() {
+                    return result.unwrap();
+                }
+                thread::yield_now();
+            }
+        };
+        let p = Term::from(NamedNode::new_unchecked("http://ex/p"));
+        assert_eq!(
+            describe("ex:p", &count(p.clone(), Position::possible(&p))),
+            "ex:p: 0 as subject, 2 as predicate (+1 in named graphs), 1 as object"
+        );
+        let one = Term::from(Literal::from(1));
+        assert_eq!(
+            describe("1", &count(one, vec![Position::Object])),
+            "1 as object: 1 triple (+1 in named graphs)"
+        );
+    }
+}
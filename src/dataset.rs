@@ -0,0 +1,134 @@
+use oxigraph::{model::GraphName, sparql::Query};
+
+/// 查詢之默認圖。Oxigraph 之默認圖僅為所載入之無名之圖，TriG 等之命名圖須以
+/// `GRAPH` 或此設置查之。經查詢之數據集設之，不改其文。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Dataset {
+    /// 僅默認圖。
+    #[default]
+    Default,
+    /// 諸圖之並。
+    Union,
+    /// 所列之圖之並，可含默認圖。
+    Graphs(Vec<GraphName>),
+}
+
+impl Dataset {
+    /// 設查詢之默認圖。查詢自有 FROM 者從之，不改。
+    pub fn apply(&self, query: &mut Query) {
+        if !query.dataset().is_default_dataset() {
+            return;
+        }
+        match self {
+            Self::Default => {}
+            Self::Union => query.dataset_mut().set_default_graph_as_union(),
+            Self::Graphs(graphs) => query.dataset_mut().set_default_graph(graphs.clone()),
+        }
+    }
+
+    /// 狀態欄所示者，如 `union graph`、`2 graphs`。
+    pub fn label(&self) -> String {
+        match self {
+            Self::Default => "default graph".to_string(),
+            Self::Union => "union graph".to_string(),
+            Self::Graphs(graphs) => match &graphs[..] {
+                [graph] => format!("graph {graph}"),
+                graphs => format!("{} graphs", graphs.len()),
+            },
+        }
+    }
+
+    /// `:dataset` 所報：設置及其所列之圖。
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Default => "Queries match the default graph only".to_string(),
+            Self::Union => "Queries match the union of all graphs".to_string(),
+            Self::Graphs(graphs) => {
+                let names: Vec<String> = graphs.iter().map(GraphName::to_string).collect();
+                format!("Queries match the graphs {}", names.join(", "))
+            }
+        }
+    }
+
+    /// 會話文件中之一欄：`default`、`union`，或 `graphs` 及以 Tab 分隔之圖名。
+    pub fn to_field(&self) -> String {
+        match self {
+            Self::Default => "default".to_string(),
+            Self::Union => "union".to_string(),
+            Self::Graphs(graphs) => std::iter::once("graphs".to_string())
+                .chain(graphs.iter().map(GraphName::to_string))
+                .collect::<Vec<_>>()
+                .join("\t"),
+        }
+    }
+
+    pub fn from_field(field: &str) -> Option<Self> {
+        let mut fields = field.split('\t');
+        match fields.next()? {
+            "default" => Some(Self::Default),
+            "union" => Some(Self::Union),
+            "graphs" => fields
+                .map(|graph| match graph {
+                    "DEFAULT" => Some(GraphName::DefaultGraph),
+                    graph => {
+                        oxigraph::model::NamedNode::new(graph.strip_prefix('<')?.strip_suffix('>')?)
+                            .ok()
+                            .map(GraphName::from)
+                    }
+                })
+                .collect::<Option<Vec<_>>>()
+                .filter(|graphs| !graphs.is_empty())
+                .map(Self::Graphs),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::{io::RdfFormat, model::NamedNode, sparql::QueryResults, store::Store};
+
+    #[test]
+    fn datasets_choose_the_default_graph() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfFormat::TriG,
+                "<http://ex/a> <http://ex/p> 1 . <http://ex/g> { <http://ex/b> <http://ex/p> 2 }"
+                    .as_bytes(),
+            )
+            .unwrap();
+        let count = |dataset: &Dataset, text: &str| {
+            let mut query = Query::parse(text, None).unwrap();
+            dataset.apply(&mut query);
+            let QueryResults::Solutions(solutions) = store.query(query).unwrap() else {
+                unreachable!()
+            };
+            solutions.count()
+        };
+        let all = "SELECT * { ?s ?p ?o }";
+        let g = GraphName::from(NamedNode::new_unchecked("http://ex/g"));
+        assert_eq!(count(&Dataset::Default, all), 1);
+        assert_eq!(count(&Dataset::Union, all), 2);
+        assert_eq!(count(&Dataset::Graphs(vec![g.clone()]), all), 1);
+        // 查詢自有 FROM 者不改
+        assert_eq!(
+            count(&Dataset::Union, "SELECT * FROM <http://ex/g> { ?s ?p ?o }"),
+            1
+        );
+
+        for dataset in [
+            Dataset::Default,
+            Dataset::Union,
+            Dataset::Graphs(vec![GraphName::DefaultGraph, g]),
+        ] {
+            assert_eq!(Dataset::from_field(&dataset.to_field()), Some(dataset));
+        }
+        assert_eq!(Dataset::from_field("graphs"), None);
+        assert_eq!(
+            Dataset::Graphs(vec![GraphName::DefaultGraph]).label(),
+            "graph DEFAULT"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use oxigraph::{
+    model::NamedNode,
+    sparql::{EvaluationError, QueryResults},
+    store::Store,
+};
+
+/// 有此類型者視為詞彙之定義，詳情列其定義而非其用法。
+const DEFINING_TYPES: &[&str] = &[
+    "http://www.w3.org/2002/07/owl#ObjectProperty",
+    "http://www.w3.org/2002/07/owl#DatatypeProperty",
+    "http://www.w3.org/2002/07/owl#AnnotationProperty",
+    "http://www.w3.org/2002/07/owl#Class",
+    "http://www.w3.org/2000/01/rdf-schema#Class",
+    "http://www.w3.org/1999/02/22-rdf-syntax-ns#Property",
+];
+
+/// 定義之各方面：名、所經之路徑。依此序列出。
+const ASPECTS: &[(&str, &str)] = &[
+    ("type", "<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>"),
+    ("label", "<http://www.w3.org/2000/01/rdf-schema#label>"),
+    ("comment", "<http://www.w3.org/2000/01/rdf-schema#comment>"),
+    ("domain", "<http://www.w3.org/2000/01/rdf-schema#domain>"),
+    ("range", "<http://www.w3.org/2000/01/rdf-schema#range>"),
+    (
+        "super-property",
+        "<http://www.w3.org/2000/01/rdf-schema#subPropertyOf>",
+    ),
+    (
+        "super-class",
+        "<http://www.w3.org/2000/01/rdf-schema#subClassOf>",
+    ),
+    (
+        "sub-class",
+        "^<http://www.w3.org/2000/01/rdf-schema#subClassOf>",
+    ),
+    (
+        "domain of",
+        "^<http://www.w3.org/2000/01/rdf-schema#domain>",
+    ),
+    ("range of", "^<http://www.w3.org/2000/01/rdf-schema#range>"),
+];
+
+/// 默認圖中 `iri` 是否為主語而有定義之類型，即屬性或類。
+pub fn is_defined(store: &Store, iri: &NamedNode) -> Result<bool, EvaluationError> {
+    let types: Vec<String> = DEFINING_TYPES.iter().map(|t| format!("<{t}>")).collect();
+    let query = format!(
+        "ASK {{ {iri} a ?type FILTER(?type IN ({})) }}",
+        types.join(", ")
+    );
+    match store.query(query.as_str())? {
+        QueryResults::Boolean(defined) => Ok(defined),
+        _ => unreachable!("an ASK query"),
+    }
+}
+
+/// 列出屬性或類之定義之查詢：類型、標籤、說明、定義域、值域、上位者，
+/// 及以之為定義域或值域之屬性。
+pub fn query(iri: &NamedNode) -> String {
+    let parts: Vec<String> = ASPECTS
+        .iter()
+        .enumerate()
+        .map(|(rank, (aspect, path))| {
+            format!("{{ BIND({rank} AS ?rank) BIND(\"{aspect}\" AS ?aspect) {iri} {path} ?value }}")
+        })
+        .collect();
+    format!(
+        "SELECT ?aspect ?value WHERE {{\n  {}\n}} ORDER BY ?rank ?value",
+        parts.join("\n  UNION\n  ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::{RdfFormat, RdfParser};
+
+    #[test]
+    fn properties_list_their_definition() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                r#"@prefix ex: <http://ex/> .
+                   @prefix owl: <http://www.w3.org/2002/07/owl#> .
+                   @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+                   ex:hasPart a owl:ObjectProperty ; rdfs:domain ex:Whole ;
+                     rdfs:range ex:Part ; rdfs:comment "A component." .
+                   ex:Whole a owl:Class .
+                   ex:car ex:hasPart ex:wheel ."#
+                    .as_bytes(),
+            )
+            .unwrap();
+        let iri = |name: &str| NamedNode::new_unchecked(format!("http://ex/{name}"));
+        assert!(is_defined(&store, &iri("hasPart")).unwrap());
+        assert!(is_defined(&store, &iri("Whole")).unwrap());
+        assert!(!is_defined(&store, &iri("car")).unwrap());
+
+        let QueryResults::Solutions(solutions) = store.query(&query(&iri("Whole"))).unwrap() else {
+            unreachable!()
+        };
+        let rows: Vec<(String, String)> = solutions
+            .map(|solution| {
+                let solution = solution.unwrap();
+                (
+                    solution["aspect"].to_string(),
+                    solution["value"].to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (
+                    "\"type\"".to_string(),
+                    "<http://www.w3.org/2002/07/owl#Class>".to_string()
+                ),
+                (
+                    "\"domain of\"".to_string(),
+                    "<http://ex/hasPart>".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::worker::TermRow;
+use oxigraph::model::{Literal, Term, Variable};
+use std::{collections::HashMap, sync::Arc};
+
+/// 同一查詢前後兩次結果之行，分為新增、刪去與未變者。行以其各項之全組為準；
+/// 重複之行逐一相抵。
+#[derive(Debug, Clone, Default)]
+pub struct Partition {
+    /// 新增之行，依新結果之序。
+    pub added: Vec<TermRow>,
+    /// 未變之行，依新結果之序。
+    pub unchanged: Vec<TermRow>,
+    /// 刪去之行，依舊結果之序。
+    pub removed: Vec<TermRow>,
+}
+
+impl Partition {
+    /// 以散列表相較，行數之線性時間。
+    pub fn between(old: &[TermRow], new: &[TermRow]) -> Self {
+        let mut remaining: HashMap<&TermRow, usize> = HashMap::new();
+        for row in old {
+            *remaining.entry(row).or_default() += 1;
+        }
+        let mut partition = Self::default();
+        for row in new {
+            match remaining.get_mut(row).filter(|count| **count > 0) {
+                Some(count) => {
+                    *count -= 1;
+                    partition.unchanged.push(row.clone());
+                }
+                None => partition.added.push(row.clone()),
+            }
+        }
+        for row in old {
+            if let Some(count) = remaining.get_mut(row).filter(|count| **count > 0) {
+                *count -= 1;
+                partition.removed.push(row.clone());
+            }
+        }
+        partition
+    }
+
+    /// 表頭：首列為行之變，餘為原表頭。
+    pub fn variables(variables: &[Variable]) -> Vec<Variable> {
+        let mut all = vec![Variable::new_unchecked("change")];
+        all.extend_from_slice(variables);
+        all
+    }
+
+    /// 首列標以 `added`、`unchanged`、`removed` 之行，依此序。`removed` 為是否含刪去之行。
+    pub fn rows(&self, removed: bool) -> Vec<TermRow> {
+        let sections = [
+            ("added", &self.added),
+            ("unchanged", &self.unchanged),
+            ("removed", &self.removed),
+        ];
+        let shown = if removed { 3 } else { 2 };
+        sections[..shown]
+            .iter()
+            .flat_map(|(change, rows)| {
+                let change = Arc::new(Term::from(Literal::new_simple_literal(*change)));
+                rows.iter().map(move |row| {
+                    let mut tagged = vec![Some(change.clone())];
+                    tagged.extend(row.iter().cloned());
+                    tagged
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_matched_by_their_whole_tuple() {
+        let row = |a: i64, b: &str| {
+            vec![
+                Some(Arc::new(Term::from(Literal::from(a)))),
+                (!b.is_empty()).then(|| Arc::new(Term::from(Literal::new_simple_literal(b)))),
+            ]
+        };
+        let old = [row(1, "x"), row(2, "y"), row(2, "y"), row(3, "")];
+        let new = [row(4, "x"), row(2, "y"), row(1, "x"), row(3, "z")];
+        let partition = Partition::between(&old, &new);
+        assert_eq!(partition.added, [row(4, "x"), row(3, "z")]);
+        assert_eq!(partition.unchanged, [row(2, "y"), row(1, "x")]);
+        assert_eq!(partition.removed, [row(2, "y"), row(3, "")]);
+        assert_eq!(partition.rows(false).len(), 4);
+        let rows = partition.rows(true);
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[5][0].as_deref().unwrap().to_string(), "\"removed\"");
+        assert_eq!(rows[5][1..], row(3, "")[..]);
+    }
+}
@@ -0,0 +1,365 @@
+use std::{borrow::Cow, fmt::Write};
+
+/// 將字串轉為可安全顯示之形式。控制字符、零寬字符與雙向控制符換作可見之轉義，
+/// 以免破壞表格佈局或向終端注入轉義序列。所得每字符皆有寬度，列寬計算不受影響。
+///
+/// 僅用於顯示；導出、複製等仍用原值。
+pub fn sanitize(string: &str) -> Cow<'_, str> {
+    if !string.chars().any(needs_escape) {
+        return Cow::Borrowed(string);
+    }
+    let mut sanitized = String::with_capacity(string.len() + 8);
+    for ch in string.chars() {
+        match ch {
+            '\t' => sanitized.push_str("\\t"),
+            '\n' => sanitized.push_str("\\n"),
+            '\r' => sanitized.push_str("\\r"),
+            // ESC 單獨換作「␛」，其後之序列遂成普通文字
+            '\u{1B}' => sanitized.push('␛'),
+            // 其餘 C0 控制符與 DEL 以控制圖形表示
+            '\0'..='\u{1F}' => sanitized.push(char::from_u32(0x2400 + ch as u32).unwrap_or('�')),
+            '\u{7F}' => sanitized.push('␡'),
+            ch if needs_escape(ch) => {
+                let _ = write!(sanitized, "\\u{:04X}", ch as u32);
+            }
+            ch => sanitized.push(ch),
+        }
+    }
+    Cow::Owned(sanitized)
+}
+
+/// 單元格中雙向文字之顯示。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bidi {
+    /// 去雙向控制符，右至左之文字重排為視覺順序，終端不自排者亦可讀。
+    #[default]
+    Visual,
+    /// 去雙向控制符，餘按邏輯順序，交終端自排。
+    Logical,
+    /// 雙向控制符轉義為可見之 `\uXXXX`，不重排。
+    Escape,
+}
+
+impl Bidi {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "visual" => Some(Self::Visual),
+            "logical" => Some(Self::Logical),
+            "escape" => Some(Self::Escape),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Visual => "visual",
+            Self::Logical => "logical",
+            Self::Escape => "escape",
+        }
+    }
+}
+
+/// 單元格去了雙向控制符者，末註此記。
+pub const BIDI_MARK: &str = "⇄";
+
+/// 是否為雙向控制符：方向標記、嵌入、覆蓋與隔離。
+pub fn is_bidi_control(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{61C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// 單元格之文字：按 `bidi` 去雙向控制符、重排右至左之文字，再如 [`sanitize`] 轉義。
+/// 重排僅換字符之位，不改其寬，列寬計算不受影響。另返回是否去了控制符。
+pub fn cell_text(text: &str, bidi: Bidi) -> (Cow<'_, str>, bool) {
+    if bidi == Bidi::Escape {
+        return (sanitize(text), false);
+    }
+    let stripped = text.chars().any(is_bidi_control);
+    let text: Cow<'_, str> = if stripped {
+        Cow::Owned(text.chars().filter(|ch| !is_bidi_control(*ch)).collect())
+    } else {
+        Cow::Borrowed(text)
+    };
+    let text = match bidi {
+        Bidi::Visual if text.chars().any(|ch| class(ch) == Class::Right) => {
+            Cow::Owned(visual(&text))
+        }
+        _ => text,
+    };
+    let shown = match text {
+        Cow::Borrowed(text) => sanitize(text),
+        Cow::Owned(text) => Cow::Owned(sanitize(&text).into_owned()),
+    };
+    (shown, stripped)
+}
+
+/// 字符之雙向類別，僅取重排所需者。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Left,
+    Right,
+    Number,
+    Neutral,
+    /// 附標，隨其前之字符。
+    Mark,
+}
+
+fn class(ch: char) -> Class {
+    match ch {
+        '0'..='9' | '\u{660}'..='\u{669}' | '\u{6F0}'..='\u{6F9}' => Class::Number,
+        '\u{300}'..='\u{36F}'
+        | '\u{591}'..='\u{5BD}'
+        | '\u{5BF}'
+        | '\u{5C1}'..='\u{5C2}'
+        | '\u{5C4}'..='\u{5C5}'
+        | '\u{5C7}'
+        | '\u{610}'..='\u{61A}'
+        | '\u{64B}'..='\u{65F}'
+        | '\u{670}'
+        | '\u{6D6}'..='\u{6DC}'
+        | '\u{6DF}'..='\u{6E4}'
+        | '\u{6E7}'..='\u{6E8}'
+        | '\u{6EA}'..='\u{6ED}' => Class::Mark,
+        // 希伯來、阿拉伯、敘利亞、它拿等文字及其表現形式
+        '\u{590}'..='\u{8FF}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFC}'
+        | '\u{10800}'..='\u{10FFF}'
+        | '\u{1E800}'..='\u{1EFFF}' => Class::Right,
+        ch if ch.is_alphabetic() => Class::Left,
+        _ => Class::Neutral,
+    }
+}
+
+/// 按簡化之 Unicode 雙向算法重排為視覺順序：段落方向取首個強字符，數字隨其前之
+/// 左至右文字，中性字符兩旁同向則從之、否則從段落方向，右至左之段中括號鏡像。
+/// 附標與其前之字符同進退。
+fn visual(text: &str) -> String {
+    // 字符連其後之附標為一簇
+    let mut clusters: Vec<(&str, Class)> = vec![];
+    let mut start = 0;
+    for (index, ch) in text.char_indices().skip(1) {
+        if class(ch) != Class::Mark {
+            clusters.push((
+                &text[start..index],
+                class(text[start..].chars().next().unwrap()),
+            ));
+            start = index;
+        }
+    }
+    clusters.push((
+        &text[start..],
+        class(text[start..].chars().next().unwrap_or(' ')),
+    ));
+    let mut classes: Vec<Class> = clusters
+        .iter()
+        .map(|(_, class)| match class {
+            Class::Mark => Class::Neutral,
+            class => *class,
+        })
+        .collect();
+    let right = classes
+        .iter()
+        .find(|class| matches!(class, Class::Left | Class::Right))
+        == Some(&Class::Right);
+    let base = if right { Class::Right } else { Class::Left };
+    // 數字前之強字符為左至右者，數字亦然
+    let mut strong = base;
+    for class in &mut classes {
+        match *class {
+            Class::Left | Class::Right => strong = *class,
+            Class::Number if strong == Class::Left => *class = Class::Left,
+            _ => {}
+        }
+    }
+    // 中性字符兩旁同向則從之，數字作右至左論
+    let direction = |class: Class| match class {
+        Class::Left => Some(Class::Left),
+        Class::Right | Class::Number => Some(Class::Right),
+        _ => None,
+    };
+    let mut index = 0;
+    while index < classes.len() {
+        if classes[index] != Class::Neutral {
+            index += 1;
+            continue;
+        }
+        let end = (index..classes.len())
+            .find(|&end| classes[end] != Class::Neutral)
+            .unwrap_or(classes.len());
+        let before = index
+            .checked_sub(1)
+            .map_or(Some(base), |i| direction(classes[i]));
+        let after = classes
+            .get(end)
+            .map_or(Some(base), |class| direction(*class));
+        let resolved = if before == after {
+            before.unwrap_or(base)
+        } else {
+            base
+        };
+        classes[index..end].fill(resolved);
+        index = end;
+    }
+    let levels: Vec<u8> = classes
+        .iter()
+        .map(|class| match (right, class) {
+            (false, Class::Left) => 0,
+            (_, Class::Right) => 1,
+            _ => 2,
+        })
+        .collect();
+    // 自最高層至最低之奇數層，逐層反轉連續之段
+    let mut order: Vec<usize> = (0..clusters.len()).collect();
+    let highest = levels.iter().copied().max().unwrap_or(0);
+    for level in (1..=highest).rev() {
+        let mut index = 0;
+        while index < order.len() {
+            if levels[order[index]] < level {
+                index += 1;
+                continue;
+            }
+            let end = (index..order.len())
+                .find(|&end| levels[order[end]] < level)
+                .unwrap_or(order.len());
+            order[index..end].reverse();
+            index = end;
+        }
+    }
+    let mut visual = String::with_capacity(text.len());
+    for index in order {
+        let (cluster, level) = (clusters[index].0, levels[index]);
+        match mirror(cluster).filter(|_| level % 2 == 1) {
+            Some(mirrored) => visual.push(mirrored),
+            None => visual.push_str(cluster),
+        }
+    }
+    visual
+}
+
+/// 括號之鏡像。
+fn mirror(cluster: &str) -> Option<char> {
+    Some(match cluster {
+        "(" => ')',
+        ")" => '(',
+        "[" => ']',
+        "]" => '[',
+        "{" => '}',
+        "}" => '{',
+        "<" => '>',
+        ">" => '<',
+        "«" => '»',
+        "»" => '«',
+        _ => return None,
+    })
+}
+
+/// 單元格顯示之字節數上限。逾之則截斷而註其全長；詳情、預覽與導出仍取完整之項。
+pub const MAX_CELL: usize = 4096;
+
+/// 預覽窗格所折行顯示之字節數上限。每幀皆須折行，故亦不可無限。
+pub const MAX_PREVIEW: usize = 64 * 1024;
+
+/// 截為至多 `max` 字節，不斷字符。未逾則為 `None`。
+pub fn truncate(text: &str, max: usize) -> Option<&str> {
+    if text.len() <= max {
+        return None;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(&text[..end])
+}
+
+/// 以前綴縮寫 IRI，取最長之命名空間。無相符者返回 `None`。
+pub fn compact_iri(iri: &str, prefixes: &[(String, String)]) -> Option<String> {
+    prefixes
+        .iter()
+        .filter(|(_, namespace)| !namespace.is_empty() && iri.starts_with(namespace.as_str()))
+        .max_by_key(|(_, namespace)| namespace.len())
+        .map(|(prefix, namespace)| format!("{prefix}:{}", &iri[namespace.len()..]))
+}
+
+/// 以 `k`、`M` 縮寫計數，如 `1.2M`。
+pub fn abbreviate_count(count: usize) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        // 四捨五入後不足 1000.0k 者
+        1_000..=999_949 => format!("{:.1}k", count as f64 / 1e3),
+        _ => format!("{:.1}M", count as f64 / 1e6),
+    }
+}
+
+/// 每三位以空格分隔，如 `1 024`。
+pub fn group_digits(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(' ');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// 以 KB、MB、GB 表字節數，如 `12.3 MB`。
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// 是否須轉義：控制字符、軟連字符、零寬字符、雙向控制符與 BOM。
+fn needs_escape(ch: char) -> bool {
+    ch.is_control()
+        || is_bidi_control(ch)
+        || matches!(
+            ch,
+            '\u{AD}' | '\u{200B}'..='\u{200D}' | '\u{2028}' | '\u{2029}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_strip_bidi_controls_and_show_right_to_left_text_in_visual_order() {
+        assert_eq!(
+            cell_text("plain", Bidi::Visual),
+            (Cow::Borrowed("plain"), false)
+        );
+        // 覆蓋符使其後之文字倒顯，去之則原樣
+        assert_eq!(
+            cell_text("invoice\u{202E}fdp.exe\u{202C}", Bidi::Logical),
+            (Cow::Owned("invoicefdp.exe".to_string()), true)
+        );
+        assert_eq!(cell_text("a\u{202E}b", Bidi::Escape).0, "a\\u202Eb");
+        // 段落方向取首個強字符；數字自左至右
+        assert_eq!(cell_text("שלום עולם 42", Bidi::Visual).0, "42 םלוע םולש");
+        assert_eq!(cell_text("שלום עולם 42", Bidi::Logical).0, "שלום עולם 42");
+        assert_eq!(
+            cell_text("see שלום (x) now", Bidi::Visual).0,
+            "see םולש (x) now"
+        );
+        assert_eq!(cell_text("שלום (x)", Bidi::Visual).0, "(x) םולש");
+        // 附標隨其字符，字數與寬度不變
+        let pointed = "שָׁלוֹם";
+        let shown = cell_text(pointed, Bidi::Visual).0;
+        assert_eq!(shown.chars().count(), pointed.chars().count());
+        assert!(shown.starts_with('ם') && shown.ends_with("שָׁ"), "{shown}");
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{
+    background, config::Config, locale::Language, paths::Paths, theme::ColorSupport,
+    util::Capabilities,
+};
+use ratatui::{
+    crossterm::{
+        cursor, execute,
+        style::Print,
+        terminal::{self, disable_raw_mode, enable_raw_mode},
+    },
+    text::Line,
+};
+use std::{
+    env,
+    io::{self, stdout, IsTerminal},
+};
+
+/// 量寬之樣本：二漢字，當佔四列。
+const CJK_SAMPLE: &str = "漢字";
+
+/// `--doctor` 之報告，每項一行。所用之探測即運行時所據者，故報告與界面之判斷不相左。
+/// 原始模式、鍵盤協議與字寬須問終端，輸出非終端則略之。
+pub fn report(paths: &Paths, capabilities: Capabilities) -> String {
+    let tty = stdout().is_terminal();
+    let term = env::var("TERM").unwrap_or_default();
+    let mut items: Vec<(&str, String)> = vec![];
+    items.push((
+        "terminal",
+        match (tty, terminal::size()) {
+            (false, _) => format!("TERM={term}, stdout is not a terminal"),
+            (true, Ok((width, height))) => format!("TERM={term}, {width}×{height}"),
+            (true, Err(error)) => format!("TERM={term}, size unknown: {error}"),
+        },
+    ));
+    items.push(("colors", colors(capabilities)));
+    items.push(("alternate screen", likely(capabilities.alternate_screen)));
+    items.push(("mouse, paste, title, OSC 52", likely(capabilities.escapes)));
+    let probes = if tty {
+        probe(capabilities)
+    } else {
+        Probes::skipped("stdout is not a terminal")
+    };
+    items.push(("raw mode", probes.raw_mode));
+    items.push(("kitty keyboard", probes.keyboard));
+    items.push(("background", probes.background));
+    items.push(("locale", locale()));
+    items.push(("CJK width", probes.width));
+    items.push(("config", config_state(paths)));
+    items.push((
+        "state",
+        paths.state_dir().map_or_else(
+            || "no state directory".to_string(),
+            |dir| dir.display().to_string(),
+        ),
+    ));
+    let (oxigraph, ratatui) = versions();
+    items.push((
+        "versions",
+        format!(
+            "rdf-tui {}, oxigraph {oxigraph}, ratatui {ratatui}",
+            env!("CARGO_PKG_VERSION")
+        ),
+    ));
+    let width = items.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    items
+        .into_iter()
+        .map(|(name, value)| format!("{name:<width$}  {value}\n"))
+        .collect()
+}
+
+fn likely(supported: bool) -> String {
+    match supported {
+        true => "likely supported".to_string(),
+        false => "off for this TERM".to_string(),
+    }
+}
+
+/// 色彩之深，與渲染時降色所據者同。
+fn colors(capabilities: Capabilities) -> String {
+    if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return "none (NO_COLOR is set)".to_string();
+    }
+    if !capabilities.colors {
+        return "none for this TERM".to_string();
+    }
+    let depth = match ColorSupport::detect() {
+        ColorSupport::TrueColor => "24-bit",
+        ColorSupport::Ansi256 => "256 colors",
+        ColorSupport::Ansi16 => "16 colors",
+    };
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    format!("{depth} (COLORTERM={colorterm})")
+}
+
+/// 須於原始模式中問終端者。
+struct Probes {
+    raw_mode: String,
+    keyboard: String,
+    background: String,
+    width: String,
+}
+
+impl Probes {
+    fn skipped(reason: &str) -> Self {
+        Self {
+            raw_mode: reason.to_string(),
+            keyboard: reason.to_string(),
+            background: reason.to_string(),
+            width: format!("{} columns expected; {reason}", expected_width()),
+        }
+    }
+}
+
+fn probe(capabilities: Capabilities) -> Probes {
+    if let Err(error) = enable_raw_mode() {
+        return Probes::skipped(&format!("raw mode failed: {error}"));
+    }
+    let keyboard = match capabilities.keyboard_enhancement() {
+        true => "yes".to_string(),
+        false => "no: ctrl-enter and ctrl-/ fall back to f5 and ctrl-7".to_string(),
+    };
+    let background = match capabilities.escapes {
+        true => match background::query(background::TIMEOUT) {
+            Some(rgb) => format!("{rgb}: the {} theme unless one is configured", rgb.preset()),
+            None => "no answer: the dark theme unless one is configured".to_string(),
+        },
+        false => "not asked for this TERM".to_string(),
+    };
+    let width = match capabilities.escapes {
+        true => measured_width().unwrap_or_else(|error| format!("not measured: {error}")),
+        false => format!("{} columns expected; not measured", expected_width()),
+    };
+    let _ = disable_raw_mode();
+    Probes {
+        raw_mode: "ok".to_string(),
+        keyboard,
+        background,
+        width,
+    }
+}
+
+/// 界面排版所假定之寬。
+fn expected_width() -> usize {
+    Line::raw(CJK_SAMPLE).width()
+}
+
+/// 寫樣本於行首，問光標之所至，再抹去之。
+fn measured_width() -> io::Result<String> {
+    let mut out = stdout();
+    execute!(out, Print("\r"), Print(CJK_SAMPLE))?;
+    let (column, _) = cursor::position()?;
+    execute!(out, Print("\r\x1b[2K"))?;
+    let expected = expected_width();
+    Ok(match column as usize {
+        column if column == expected => format!("{column} columns, as expected"),
+        column => format!("{column} columns, but {expected} expected: wide text will misalign"),
+    })
+}
+
+/// 區域之環境變量及所得之界面語言。
+fn locale() -> String {
+    let found = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|name| Some((name, env::var(name).ok().filter(|v| !v.is_empty())?)));
+    let language = match Language::from_env() {
+        Language::En => "en",
+        Language::Zh => "zh",
+    };
+    match found {
+        Some((name, value)) => format!("{name}={value}, interface {language}"),
+        None => format!("not set, interface {language}"),
+    }
+}
+
+/// 所讀之配置文件及其能否解析。
+fn config_state(paths: &Paths) -> String {
+    let Some(path) = paths.config_file() else {
+        return "no config directory".to_string();
+    };
+    if !path.exists() {
+        return format!("{} not found, using defaults", path.display());
+    }
+    match Config::load(paths) {
+        Ok((_, warnings)) if warnings.is_empty() => format!("{} parsed", path.display()),
+        Ok((_, warnings)) => format!(
+            "{} parsed with warnings: {}",
+            path.display(),
+            warnings.join("; ")
+        ),
+        Err(error) => format!("{}: {error:#}", path.display()),
+    }
+}
+
+/// 所依之 oxigraph 與 ratatui 之版本，取自清單。
+fn versions() -> (&'static str, &'static str) {
+    let manifest = include_str!("../Cargo.toml");
+    let version = |name: &str| {
+        manifest
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.trim_start().strip_prefix('='))
+            .and_then(|rest| rest.split('"').nth(1))
+            .unwrap_or("unknown")
+    };
+    (version("oxigraph"), version("ratatui"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_every_item_without_a_terminal() {
+        let paths = Paths::new(
+            None,
+            Some("/nonexistent/state".into()),
+            Some("/nonexistent/config.toml".into()),
+        );
+        let report = report(&paths, Capabilities::dumb());
+        assert!(report.contains("colors "));
+        assert!(report.contains("/nonexistent/config.toml not found, using defaults"));
+        assert!(report.contains("  /nonexistent/state\n"), "{report}");
+        assert!(report.contains("oxigraph 0.4.0-alpha.7, ratatui 0.27.0"));
+        assert_eq!(expected_width(), 4);
+    }
+}
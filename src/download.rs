@@ -0,0 +1,652 @@
+use crate::{
+    display::format_bytes,
+    paths::{create_dir, dataset_key},
+};
+use anyhow::{bail, Context};
+use oxigraph::io::RdfFormat;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// 下載之設置。
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// 緩存之目錄。無則下載於臨時目錄，不再驗證。
+    pub cache: Option<PathBuf>,
+    /// 至多下載之字節數。
+    pub max: Option<u64>,
+    /// 連接中斷或服務器暫不可用時之重試次數。
+    pub retries: usize,
+    /// 首次重試前之等待，其後每次加倍。服務器給 `Retry-After` 則從之。
+    pub backoff: Duration,
+    /// 連接或無數據到達逾此時則視為中斷。
+    pub stall: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            cache: None,
+            max: None,
+            retries: 3,
+            backoff: Duration::from_secs(1),
+            stall: Duration::from_secs(30),
+        }
+    }
+}
+
+/// 是否為可下載之 URL，而非本地之路徑。
+pub fn is_url(path: &Path) -> bool {
+    let path = path.to_string_lossy().to_ascii_lowercase();
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// 下載所得。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fetched {
+    /// 緩存中之文件，擴展名示其格式。
+    pub path: PathBuf,
+    /// 服務器答以未變（304），用緩存中者。
+    pub cached: bool,
+    pub bytes: u64,
+}
+
+/// 與界面共享之進度。
+#[derive(Debug, Default)]
+pub struct Progress {
+    received: AtomicU64,
+    /// 總字節數，未知則為零。
+    total: AtomicU64,
+    cancel: AtomicBool,
+}
+
+impl Progress {
+    /// 已收之字節數，如 `1.2 MB of 4.0 MB`。
+    fn describe(&self) -> String {
+        let received = format_bytes(self.received.load(Ordering::Relaxed) as usize);
+        match self.total.load(Ordering::Relaxed) {
+            0 => received,
+            total => format!("{received} of {}", format_bytes(total as usize)),
+        }
+    }
+}
+
+/// 後台之下載。
+pub struct Download {
+    pub url: String,
+    progress: Arc<Progress>,
+    receiver: Receiver<Result<Fetched, String>>,
+}
+
+impl Download {
+    pub fn spawn(url: String, options: Options) -> Self {
+        let progress = Arc::new(Progress::default());
+        let (sender, receiver) = mpsc::channel();
+        let (target, shared) = (url.clone(), Arc::clone(&progress));
+        thread::spawn(move || {
+            let result = fetch(&target, &options, &shared).map_err(|error| format!("{error:#}"));
+            let _ = sender.send(result);
+        });
+        Self {
+            url,
+            progress,
+            receiver,
+        }
+    }
+
+    /// 已完成則返回其結果。
+    pub fn poll(&self) -> Option<Result<Fetched, String>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// 等待完成。
+    pub fn wait(&self) -> Result<Fetched, String> {
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err("The download stopped".to_string()))
+    }
+
+    /// 請求取消。已下載之部分刪去。
+    pub fn cancel(&self) {
+        self.progress.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// 已收之比例。總數未知則為零。
+    pub fn ratio(&self) -> f64 {
+        match self.progress.total.load(Ordering::Relaxed) {
+            0 => 0.0,
+            total => {
+                (self.progress.received.load(Ordering::Relaxed) as f64 / total as f64).min(1.0)
+            }
+        }
+    }
+
+    /// 狀態欄之標籤，如 `Downloading https://x/a.nt: 1.2 MB of 4.0 MB`。
+    pub fn label(&self) -> String {
+        format!("Downloading {}: {}", self.url, self.progress.describe())
+    }
+}
+
+/// 下載 `url` 於緩存，返回其文件。緩存中有之且有 ETag 者先驗證，未變則不再下載。
+/// 中斷者以 Range 自斷處續之，至多重試 `options.retries` 次。失敗之錯誤中明其 HTTP 狀態
+/// 及已收之字節數。
+pub fn fetch(url: &str, options: &Options, progress: &Progress) -> anyhow::Result<Fetched> {
+    let dir = options
+        .cache
+        .clone()
+        .unwrap_or_else(|| env::temp_dir().join("rdf-tui-downloads"));
+    create_dir(&dir)?;
+    let key = dataset_key(&[PathBuf::from(url)]);
+    let part = dir.join(format!("{key}.part"));
+    let headers = dir.join(format!("{key}.headers"));
+    let meta = dir.join(format!("{key}.meta"));
+    let cached = options
+        .cache
+        .as_ref()
+        .and_then(|_| Meta::read(&meta))
+        .filter(|meta| dir.join(&meta.file).is_file());
+    let _ = fs::remove_file(&part);
+    let mut attempt = 0;
+    loop {
+        let offset = fs::metadata(&part).map_or(0, |metadata| metadata.len());
+        // 續傳者不驗證緩存，所續者即新版
+        let etag = cached
+            .as_ref()
+            .and_then(|meta| meta.etag.as_deref())
+            .filter(|_| offset == 0);
+        let outcome = transfer(url, &part, &headers, offset, etag, options, progress)?;
+        let received = progress.received.load(Ordering::Relaxed);
+        let failure = match outcome {
+            Outcome::NotModified => {
+                let _ = fs::remove_file(&part);
+                let cached = cached.context("The server answered 304 without a cached copy")?;
+                let path = dir.join(&cached.file);
+                let bytes = fs::metadata(&path)?.len();
+                log::info!("download url={url} not modified bytes={bytes}");
+                return Ok(Fetched {
+                    path,
+                    cached: true,
+                    bytes,
+                });
+            }
+            Outcome::Complete(response) => {
+                let path = dir.join(format!("{key}.{}", extension(url, &response)));
+                fs::rename(&part, &path)?;
+                if options.cache.is_some() {
+                    let file = path.file_name().unwrap_or_default().to_string_lossy();
+                    Meta {
+                        etag: response.etag,
+                        file: file.into_owned(),
+                    }
+                    .write(&meta)?;
+                }
+                log::info!(
+                    "download url={url} bytes={received} attempts={}",
+                    attempt + 1
+                );
+                return Ok(Fetched {
+                    path,
+                    cached: false,
+                    bytes: received,
+                });
+            }
+            Outcome::Failed(failure) => failure,
+        };
+        let gave_up = match failure.retry {
+            Retry::Never => String::new(),
+            _ if attempt == options.retries || progress.cancel.load(Ordering::Relaxed) => {
+                match attempt {
+                    0 => String::new(),
+                    _ => format!(" (gave up after {attempt} retries)"),
+                }
+            }
+            Retry::Resume | Retry::Restart => {
+                let wait = failure
+                    .wait
+                    .unwrap_or(options.backoff * 2u32.saturating_pow(attempt as u32));
+                log::warn!(
+                    "download url={url} attempt={} error={} received={received} retry_in_ms={}",
+                    attempt + 1,
+                    failure.reason,
+                    wait.as_millis()
+                );
+                if matches!(failure.retry, Retry::Restart) {
+                    let _ = fs::remove_file(&part);
+                }
+                thread::sleep(wait);
+                attempt += 1;
+                continue;
+            }
+        };
+        let _ = fs::remove_file(&part);
+        bail!(
+            "{}, {} received{gave_up}",
+            failure.reason,
+            progress.describe()
+        );
+    }
+}
+
+/// 一次傳輸之結局。
+enum Outcome {
+    NotModified,
+    Complete(Response),
+    Failed(Failure),
+}
+
+struct Failure {
+    reason: String,
+    retry: Retry,
+    /// 服務器所求之等待。
+    wait: Option<Duration>,
+}
+
+enum Retry {
+    Never,
+    /// 自已收者續之。
+    Resume,
+    /// 服務器不支持 Range，自頭再來。
+    Restart,
+}
+
+/// 不待服務器所求之失敗。
+fn failed(reason: impl Into<String>, retry: Retry) -> Outcome {
+    Outcome::Failed(Failure {
+        reason: reason.into(),
+        retry,
+        wait: None,
+    })
+}
+
+/// 以 curl 傳輸一次，正文續寫於 `part` 自 `offset` 起，應答頭寫於 `headers`。進行中讀二者
+/// 以更新進度，逾上限或取消則止之。
+fn transfer(
+    url: &str,
+    part: &Path,
+    headers: &Path,
+    offset: u64,
+    etag: Option<&str>,
+    options: &Options,
+    progress: &Progress,
+) -> anyhow::Result<Outcome> {
+    let stall = options.stall.as_secs().max(1).to_string();
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--location", "--fail"])
+        .args(["--max-redirs", "10"])
+        .args(["--connect-timeout", &stall])
+        // 每秒不足一字節逾 `stall` 秒則止
+        .args(["--speed-limit", "1", "--speed-time", &stall])
+        .arg("--dump-header")
+        .arg(headers)
+        .arg("--output")
+        .arg(part)
+        .args(["--write-out", "%{http_code}"]);
+    if offset > 0 {
+        command.args(["--continue-at", &offset.to_string()]);
+    }
+    if let Some(etag) = etag {
+        command.args(["--header", &format!("If-None-Match: {etag}")]);
+    }
+    if let Some(max) = options.max {
+        command.args(["--max-filesize", &max.to_string()]);
+    }
+    // `--` 之後之 URL 不會被當作選項
+    command.arg("--").arg(url);
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Fail to run curl")?;
+    progress.received.store(offset, Ordering::Relaxed);
+    let stopped = watch(&mut child, part, headers, offset, options.max, progress)?;
+    let output = child.wait_with_output()?;
+    let response = Response::parse(&fs::read_to_string(headers).unwrap_or_default());
+    let _ = fs::remove_file(headers);
+    if let Some(failure) = stopped {
+        return Ok(failure);
+    }
+    let status: u16 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .unwrap_or(0);
+    let error = String::from_utf8_lossy(&output.stderr);
+    let error = error
+        .trim()
+        .strip_prefix("curl: ")
+        .map_or(error.trim(), |error| {
+            error.split_once(") ").map_or(error, |(_, error)| error)
+        });
+    let received = fs::metadata(part).map_or(offset, |metadata| metadata.len());
+    progress.received.store(received, Ordering::Relaxed);
+    if let Some(total) = response.total(offset) {
+        progress.total.store(total, Ordering::Relaxed);
+    }
+    Ok(match output.status.code() {
+        _ if status == 304 => Outcome::NotModified,
+        Some(0) => {
+            let total = progress.total.load(Ordering::Relaxed);
+            match total > 0 && received < total {
+                true => failed("The connection closed early", Retry::Resume),
+                false => Outcome::Complete(response),
+            }
+        }
+        // --fail：狀態碼 400 以上者
+        Some(22) => {
+            let reason = format!("HTTP {status}");
+            match status {
+                429 | 500..=599 => Outcome::Failed(Failure {
+                    reason,
+                    retry: Retry::Resume,
+                    wait: response.retry_after,
+                }),
+                _ => failed(reason, Retry::Never),
+            }
+        }
+        Some(33) => failed("The server cannot resume the download", Retry::Restart),
+        Some(63) => failed(
+            format!(
+                "Larger than --max-download {}",
+                format_bytes(options.max.unwrap_or_default() as usize)
+            ),
+            Retry::Never,
+        ),
+        // 連接不上、中斷或停滯
+        _ => failed(error.to_string(), Retry::Resume),
+    })
+}
+
+/// 傳輸中每五十毫秒更新進度。逾上限或取消則止 curl，返回其因。
+fn watch(
+    child: &mut Child,
+    part: &Path,
+    headers: &Path,
+    offset: u64,
+    max: Option<u64>,
+    progress: &Progress,
+) -> anyhow::Result<Option<Outcome>> {
+    while child.try_wait()?.is_none() {
+        let received = fs::metadata(part).map_or(offset, |metadata| metadata.len());
+        progress.received.store(received, Ordering::Relaxed);
+        let response = Response::parse(&fs::read_to_string(headers).unwrap_or_default());
+        if let Some(total) = response.total(offset) {
+            progress.total.store(total, Ordering::Relaxed);
+        }
+        let stop = if progress.cancel.load(Ordering::Relaxed) {
+            Some(failed("Cancelled", Retry::Never))
+        } else {
+            // 未給長度者 curl 無從先拒之
+            max.filter(|max| received > *max).map(|max| {
+                failed(
+                    format!("Larger than --max-download {}", format_bytes(max as usize)),
+                    Retry::Never,
+                )
+            })
+        };
+        if stop.is_some() {
+            child.kill()?;
+            return Ok(stop);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(None)
+}
+
+/// 末一應答之頭，重定向之前者不計。
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Response {
+    status: u16,
+    length: Option<u64>,
+    /// `Content-Range` 之總長。
+    range_total: Option<u64>,
+    etag: Option<String>,
+    content_type: Option<String>,
+    retry_after: Option<Duration>,
+}
+
+impl Response {
+    fn parse(text: &str) -> Self {
+        let Some(block) = text
+            .split("\r\n\r\n")
+            .flat_map(|block| block.split("\n\n"))
+            .filter(|block| block.starts_with("HTTP/"))
+            .last()
+        else {
+            return Self::default();
+        };
+        let mut lines = block.lines();
+        let mut response = Self {
+            status: lines
+                .next()
+                .and_then(|line| line.split_whitespace().nth(1))
+                .and_then(|status| status.parse().ok())
+                .unwrap_or(0),
+            ..Self::default()
+        };
+        for line in lines {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => response.length = value.parse().ok(),
+                "content-range" => {
+                    response.range_total = value
+                        .rsplit_once('/')
+                        .and_then(|(_, total)| total.parse().ok())
+                }
+                "etag" => response.etag = Some(value.to_string()),
+                "content-type" => {
+                    response.content_type = value
+                        .split(';')
+                        .next()
+                        .map(|media| media.trim().to_string())
+                }
+                "retry-after" => response.retry_after = value.parse().ok().map(Duration::from_secs),
+                _ => {}
+            }
+        }
+        response
+    }
+
+    /// 全文之字節數。續傳者之長度僅其餘，故加 `offset`。
+    fn total(&self, offset: u64) -> Option<u64> {
+        match self.status {
+            206 => self
+                .range_total
+                .or(self.length.map(|length| length + offset)),
+            200 => self.length,
+            _ => None,
+        }
+    }
+}
+
+/// 緩存文件之擴展名，以便依之解析：URL 之路徑者可識則用之，否則依內容類型，皆不識則為 Turtle。
+fn extension(url: &str, response: &Response) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let from_url = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .and_then(|(_, extension)| RdfFormat::from_extension(extension));
+    from_url
+        .or_else(|| RdfFormat::from_media_type(response.content_type.as_deref()?))
+        .unwrap_or(RdfFormat::Turtle)
+        .file_extension()
+}
+
+/// 緩存中一下載之記錄：其 ETag 與文件名。
+struct Meta {
+    etag: Option<String>,
+    file: String,
+}
+
+impl Meta {
+    fn read(path: &Path) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        let (mut etag, mut file) = (None, None);
+        for line in text.lines() {
+            match line.split_once('\t') {
+                Some(("etag", value)) => etag = Some(value.to_string()),
+                Some(("file", value)) => file = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Some(Self { etag, file: file? })
+    }
+
+    fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let mut text = format!("file\t{}\n", self.file);
+        if let Some(etag) = &self.etag {
+            text.push_str(&format!("etag\t{etag}\n"));
+        }
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::Mutex,
+    };
+
+    /// 依次答以所給之應答，記所收之請求頭。應答之後即斷開。
+    fn serve(responses: Vec<Vec<u8>>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/data/animals.nt", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let seen = Arc::clone(&requests);
+        thread::spawn(move || {
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                seen.lock().unwrap().push(request);
+                let _ = reader.get_mut().write_all(&response);
+            }
+        });
+        (url, requests)
+    }
+
+    const BODY: &str = "<http://ex/a> <http://ex/p> \"1\" .\n<http://ex/b> <http://ex/p> \"2\" .\n";
+
+    fn options(cache: &Path) -> Options {
+        Options {
+            cache: Some(cache.to_path_buf()),
+            backoff: Duration::from_millis(10),
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn dropped_downloads_resume_and_revalidate_from_the_cache() {
+        let cache = env::temp_dir().join(format!("rdf-tui-download-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&cache);
+        let length = BODY.len();
+        let (url, requests) = serve(vec![
+            // 首次於一半處斷開，續傳者得其餘
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {length}\r\nETag: \"v1\"\r\n\r\n{}",
+                &BODY[..20]
+            )
+            .into_bytes(),
+            format!(
+                "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 20-{}/{length}\r\n\
+                 Content-Length: {}\r\nETag: \"v1\"\r\n\r\n{}",
+                length - 1,
+                length - 20,
+                &BODY[20..]
+            )
+            .into_bytes(),
+            b"HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\n\r\n".to_vec(),
+        ]);
+        let progress = Progress::default();
+        let fetched = fetch(&url, &options(&cache), &progress).unwrap();
+        assert!(!fetched.cached && fetched.bytes == length as u64);
+        assert_eq!(fetched.path.extension().unwrap(), "nt");
+        assert_eq!(fs::read_to_string(&fetched.path).unwrap(), BODY);
+        assert_eq!(progress.describe(), format!("{length} B of {length} B"));
+
+        let again = fetch(&url, &options(&cache), &Progress::default()).unwrap();
+        assert_eq!(again.path, fetched.path);
+        assert!(again.cached);
+        let requests = requests.lock().unwrap();
+        let header = |n: usize, name: &str| {
+            requests[n]
+                .lines()
+                .find(|line| line.to_ascii_lowercase().starts_with(name))
+                .map(str::to_string)
+        };
+        assert_eq!(header(0, "range"), None);
+        assert_eq!(header(1, "range").as_deref(), Some("Range: bytes=20-"));
+        assert_eq!(
+            header(2, "if-none-match").as_deref(),
+            Some("If-None-Match: \"v1\"")
+        );
+        fs::remove_dir_all(cache).unwrap();
+    }
+
+    #[test]
+    fn failures_tell_the_status_and_the_bytes_received() {
+        let cache = env::temp_dir().join(format!("rdf-tui-download-fail-{}", std::process::id()));
+        let unavailable =
+            b"HTTP/1.1 503 Service Unavailable\r\nRetry-After: 0\r\nContent-Length: 0\r\n\r\n";
+        let (url, requests) = serve(vec![unavailable.to_vec(); 3]);
+        let options = Options {
+            retries: 2,
+            ..options(&cache)
+        };
+        let error = fetch(&url, &options, &Progress::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "HTTP 503, 0 B received (gave up after 2 retries)"
+        );
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        let (url, _) = serve(vec![
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec()
+        ]);
+        let error = fetch(&url, &options, &Progress::default()).unwrap_err();
+        assert_eq!(error.to_string(), "HTTP 404, 0 B received");
+
+        let big = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{BODY}",
+            BODY.len()
+        );
+        let (url, _) = serve(vec![big.into_bytes()]);
+        let capped = Options {
+            max: Some(10),
+            ..options
+        };
+        let error = fetch(&url, &capped, &Progress::default()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Larger than --max-download 10 B"),
+            "{error}"
+        );
+        assert!(!is_url(Path::new("data/a.ttl")) && is_url(Path::new("HTTPS://x/a.ttl")));
+        fs::remove_dir_all(cache).unwrap();
+    }
+}
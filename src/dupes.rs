@@ -0,0 +1,220 @@
+use oxigraph::{
+    model::{Literal, NamedNode, Term},
+    sparql::{EvaluationError, QueryResults, Variable},
+    store::Store,
+};
+use std::collections::HashMap;
+
+/// 列出之簇數上限，以免重複甚多時結果過大。
+pub const MAX_CLUSTERS: usize = 500;
+
+/// 不同主語共用同一字面量值之查找。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dupes {
+    pub predicate: NamedNode,
+    /// 比較前是否轉小寫並合併空白。
+    pub normalized: bool,
+}
+
+/// 所得之簇，依值排序，每行為簇號、值、主語。
+#[derive(Debug, Default)]
+pub struct Clusters {
+    pub rows: Vec<[Term; 3]>,
+    pub count: usize,
+    /// 是否因簇數上限而略去其餘。
+    pub truncated: bool,
+}
+
+impl Clusters {
+    /// 併入一簇。已達上限則為假。
+    fn push(&mut self, members: impl IntoIterator<Item = (Term, Term)>) -> bool {
+        if self.count == MAX_CLUSTERS {
+            self.truncated = true;
+            return false;
+        }
+        self.count += 1;
+        let cluster = Term::from(Literal::from(self.count as i64));
+        self.rows.extend(
+            members
+                .into_iter()
+                .map(|(value, subject)| [cluster.clone(), value, subject]),
+        );
+        true
+    }
+}
+
+impl Dupes {
+    /// 結果表之列。
+    pub fn variables() -> Vec<Variable> {
+        ["cluster", "value", "subject"]
+            .map(Variable::new_unchecked)
+            .to_vec()
+    }
+
+    /// 精確比較之查詢：共用一值者依值、主語排序。多取一簇以知是否截斷。
+    pub fn exact_query(&self) -> String {
+        let predicate = &self.predicate;
+        format!(
+            "SELECT ?value ?subject WHERE {{ \
+               {{ SELECT ?value WHERE {{ ?s {predicate} ?value FILTER(isLiteral(?value)) }} \
+                  GROUP BY ?value HAVING (COUNT(DISTINCT ?s) > 1) \
+                  ORDER BY ?value LIMIT {} }} \
+               ?subject {predicate} ?value }} ORDER BY ?value ?subject",
+            MAX_CLUSTERS + 1
+        )
+    }
+
+    /// 查找各簇。精確者以 SPARQL 分組；規範化者於此逐行分組。
+    pub fn clusters(&self, store: &Store) -> Result<Clusters, EvaluationError> {
+        let mut clusters = Clusters::default();
+        if !self.normalized {
+            let mut current: Option<(Term, Vec<(Term, Term)>)> = None;
+            for pair in pairs(store, &self.exact_query())? {
+                let (value, subject) = pair?;
+                match &mut current {
+                    Some((key, members)) if *key == value => members.push((value, subject)),
+                    _ => {
+                        if let Some((_, members)) = current.take() {
+                            if !clusters.push(members) {
+                                return Ok(clusters);
+                            }
+                        }
+                        current = Some((value.clone(), vec![(value, subject)]));
+                    }
+                }
+            }
+            if let Some((_, members)) = current {
+                clusters.push(members);
+            }
+            return Ok(clusters);
+        }
+
+        let query = format!(
+            "SELECT ?value ?subject WHERE {{ ?subject {} ?value FILTER(isLiteral(?value)) }}",
+            self.predicate
+        );
+        let mut groups: HashMap<String, Vec<(Term, Term)>> = HashMap::new();
+        for pair in pairs(store, &query)? {
+            let (value, subject) = pair?;
+            let Term::Literal(literal) = &value else {
+                continue;
+            };
+            groups
+                .entry(normalize(literal.value()))
+                .or_default()
+                .push((value, subject));
+        }
+        let mut groups: Vec<(String, Vec<(Term, Term)>)> = groups
+            .into_iter()
+            .filter(|(_, members)| members.iter().any(|(_, subject)| *subject != members[0].1))
+            .collect();
+        groups.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (_, mut members) in groups {
+            members.sort_by_cached_key(|(value, subject)| (value.to_string(), subject.to_string()));
+            if !clusters.push(members) {
+                break;
+            }
+        }
+        Ok(clusters)
+    }
+}
+
+/// 執行返回 `?value ?subject` 之查詢。
+fn pairs(
+    store: &Store,
+    query: &str,
+) -> Result<impl Iterator<Item = Result<(Term, Term), EvaluationError>>, EvaluationError> {
+    let QueryResults::Solutions(solutions) = store.query(query)? else {
+        unreachable!("a SELECT query")
+    };
+    Ok(solutions.filter_map(|solution| {
+        let solution = match solution {
+            Ok(solution) => solution,
+            Err(error) => return Some(Err(error)),
+        };
+        let value = solution.get("value")?.clone();
+        let subject = solution.get("subject")?.clone();
+        Some(Ok((value, subject)))
+    }))
+}
+
+/// 轉小寫並將連續空白合為一空格，去首尾空白。
+pub fn normalize(value: &str) -> String {
+    value
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::{RdfFormat, RdfParser};
+
+    fn clusters(normalized: bool) -> Vec<Vec<String>> {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                r#"@prefix ex: <http://ex/> .
+                   ex:a ex:name "Ada Lovelace" . ex:b ex:name "Ada Lovelace" .
+                   ex:c ex:name "ada  lovelace" . ex:d ex:name "Grace" .
+                   ex:e ex:name "Grace", "grace" ."#
+                    .as_bytes(),
+            )
+            .unwrap();
+        let dupes = Dupes {
+            predicate: NamedNode::new_unchecked("http://ex/name"),
+            normalized,
+        };
+        let clusters = dupes.clusters(&store).unwrap();
+        assert!(!clusters.truncated);
+        clusters
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Term::to_string).collect())
+            .collect()
+    }
+
+    #[test]
+    fn exact_values_group_distinct_subjects() {
+        let integer = |n: u8| format!("\"{n}\"^^<http://www.w3.org/2001/XMLSchema#integer>");
+        assert_eq!(
+            clusters(false),
+            [
+                [
+                    integer(1),
+                    "\"Ada Lovelace\"".into(),
+                    "<http://ex/a>".into()
+                ],
+                [
+                    integer(1),
+                    "\"Ada Lovelace\"".into(),
+                    "<http://ex/b>".into()
+                ],
+                [integer(2), "\"Grace\"".into(), "<http://ex/d>".into()],
+                [integer(2), "\"Grace\"".into(), "<http://ex/e>".into()],
+            ]
+        );
+    }
+
+    #[test]
+    fn normalized_values_ignore_case_and_spacing() {
+        let rows = clusters(true);
+        let subjects: Vec<&str> = rows.iter().map(|row| row[2].as_str()).collect();
+        assert_eq!(
+            subjects,
+            [
+                "<http://ex/a>",
+                "<http://ex/b>",
+                "<http://ex/c>",
+                "<http://ex/d>",
+                "<http://ex/e>",
+                "<http://ex/e>"
+            ]
+        );
+        assert_eq!(rows[2][1], "\"ada  lovelace\"");
+        assert_eq!(normalize("  Ada\tLOVELACE "), "ada lovelace");
+    }
+}
@@ -0,0 +1,332 @@
+use crate::memory;
+use oxigraph::{
+    model::{GraphName, Literal, NamedNode, Quad, Subject, Term},
+    store::{StorageError, Store},
+};
+use std::{collections::HashMap, mem::size_of};
+
+/// 改一三元組之字面量值。各圖中有此三元組者一併改之。
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub subject: Subject,
+    pub predicate: NamedNode,
+    pub object: Literal,
+    /// 有此三元組之圖。
+    pub graphs: Vec<GraphName>,
+}
+
+impl Edit {
+    /// 查此三元組所在之圖。已不在存儲中則無。
+    pub fn find(
+        store: &Store,
+        subject: Subject,
+        predicate: NamedNode,
+        object: Literal,
+    ) -> Result<Option<Self>, StorageError> {
+        let graphs = graphs_of(store, &subject, &predicate, &object.clone().into())?;
+        if graphs.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            subject,
+            predicate,
+            object,
+            graphs,
+        }))
+    }
+
+    /// 以新文本為值之字面量，留原有之語言標籤或數據類型。
+    pub fn literal(&self, value: &str) -> Literal {
+        match self.object.language() {
+            Some(language) => Literal::new_language_tagged_literal_unchecked(value, language),
+            None => Literal::new_typed_literal(value, self.object.datatype()),
+        }
+    }
+
+    /// 改為 `replacement` 之更新：先刪後插。
+    pub fn update(&self, replacement: &Literal, summary: String) -> Update {
+        let quads = |object: &Literal| -> Vec<Quad> {
+            self.graphs
+                .iter()
+                .map(|graph| {
+                    Quad::new(
+                        self.subject.clone(),
+                        self.predicate.clone(),
+                        object.clone(),
+                        graph.clone(),
+                    )
+                })
+                .collect()
+        };
+        Update::new(summary, quads(&self.object), quads(replacement))
+    }
+}
+
+/// 待執行或已執行之更新。
+#[derive(Debug, Clone)]
+pub struct Update {
+    /// 一行之概述，如 `Deleted 3 triples`。
+    pub summary: String,
+    pub text: String,
+    /// 所刪之四元組，執行前須仍在存儲中。
+    pub deleted: Vec<Quad>,
+    /// 所插之四元組。執行後僅留原先所無者，撤銷時方不誤刪。
+    pub inserted: Vec<Quad>,
+    /// 所撤銷之修改數。非撤銷則為零。
+    pub undoes: usize,
+}
+
+impl Update {
+    /// 先刪後插之更新。二者有一為空則略其塊。
+    pub fn new(summary: String, deleted: Vec<Quad>, inserted: Vec<Quad>) -> Self {
+        let text = [("DELETE DATA", &deleted), ("INSERT DATA", &inserted)]
+            .into_iter()
+            .filter(|(_, quads)| !quads.is_empty())
+            .map(|(operation, quads)| data_block(operation, quads))
+            .collect::<Vec<_>>()
+            .join(" ;\n");
+        Self {
+            summary,
+            text,
+            deleted,
+            inserted,
+            undoes: 0,
+        }
+    }
+
+    /// 估計之字節數。四元組之文本約與更新之文本同長，故不逐一計之。
+    pub fn bytes(&self) -> usize {
+        let quads = self.deleted.len() + self.inserted.len();
+        2 * memory::text(&self.text) + memory::text(&self.summary) + quads * size_of::<Quad>()
+    }
+
+    /// 刪去此等四元組之更新。
+    pub fn delete(quads: Vec<Quad>, summary: String) -> Self {
+        Self::new(summary, quads, vec![])
+    }
+
+    /// 撤銷 `changes` 之更新，自後而前逐一反之，僅取與存儲現狀相異之淨變。
+    /// 皆已如舊則無。
+    pub fn undo(
+        changes: &[Update],
+        store: &Store,
+        summary: String,
+    ) -> Result<Option<Self>, StorageError> {
+        // 四元組撤銷後是否應在；較早之修改居後，故覆蓋較晚者
+        let mut wanted: HashMap<&Quad, bool> = HashMap::new();
+        let mut order = vec![];
+        for change in changes.iter().rev() {
+            for (quads, present) in [(&change.inserted, false), (&change.deleted, true)] {
+                for quad in quads {
+                    if wanted.insert(quad, present).is_none() {
+                        order.push(quad);
+                    }
+                }
+            }
+        }
+        let (mut deleted, mut inserted) = (vec![], vec![]);
+        for quad in order {
+            match (wanted[quad], store.contains(quad)?) {
+                (false, true) => deleted.push(quad.clone()),
+                (true, false) => inserted.push(quad.clone()),
+                _ => {}
+            }
+        }
+        if deleted.is_empty() && inserted.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            undoes: changes.len(),
+            ..Self::new(summary, deleted, inserted)
+        }))
+    }
+
+    /// 所刪之四元組是否皆仍在存儲中。
+    pub fn present(&self, store: &Store) -> Result<bool, StorageError> {
+        for quad in &self.deleted {
+            if !store.contains(quad)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 執行前所記：所插者僅留存儲原先所無者。
+    pub fn capture(&mut self, store: &Store) -> Result<(), StorageError> {
+        let mut inserted = vec![];
+        for quad in self.inserted.drain(..) {
+            if !store.contains(&quad)? {
+                inserted.push(quad);
+            }
+        }
+        self.inserted = inserted;
+        Ok(())
+    }
+}
+
+/// 有此三元組之圖。默認圖居先，餘者依名，更新之文本故而穩定。
+pub fn graphs_of(
+    store: &Store,
+    subject: &Subject,
+    predicate: &NamedNode,
+    object: &Term,
+) -> Result<Vec<GraphName>, StorageError> {
+    let mut graphs = store
+        .quads_for_pattern(
+            Some(subject.as_ref()),
+            Some(predicate.as_ref()),
+            Some(object.as_ref()),
+            None,
+        )
+        .map(|quad| Ok(quad?.graph_name))
+        .collect::<Result<Vec<_>, StorageError>>()?;
+    graphs.sort_by_key(|graph| (!graph.is_default_graph(), graph.to_string()));
+    Ok(graphs)
+}
+
+/// `DELETE DATA`、`INSERT DATA` 之塊：默認圖之三元組居先，餘者依圖包於 `GRAPH` 中，
+/// 每三元組一行。項以 N-Triples 之寫法，亦即 SPARQL 之寫法，轉義與類型皆不失。
+pub fn data_block(operation: &str, quads: &[Quad]) -> String {
+    let mut quads: Vec<&Quad> = quads.iter().collect();
+    quads.sort_by_key(|quad| {
+        (
+            !quad.graph_name.is_default_graph(),
+            quad.graph_name.to_string(),
+        )
+    });
+    let mut text = format!("{operation} {{\n");
+    let mut graph: Option<&GraphName> = None;
+    for quad in quads {
+        let triple = format!("{} {} {} .", quad.subject, quad.predicate, quad.object);
+        if quad.graph_name.is_default_graph() {
+            text.push_str(&format!("  {triple}\n"));
+            continue;
+        }
+        if graph != Some(&quad.graph_name) {
+            if graph.is_some() {
+                text.push_str("  }\n");
+            }
+            text.push_str(&format!("  GRAPH {} {{\n", quad.graph_name));
+            graph = Some(&quad.graph_name);
+        }
+        text.push_str(&format!("    {triple}\n"));
+    }
+    if graph.is_some() {
+        text.push_str("  }\n");
+    }
+    text.push('}');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::{RdfFormat, RdfParser};
+
+    fn store(data: &str) -> Store {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(RdfParser::from_format(RdfFormat::TriG), data.as_bytes())
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn edits_replace_the_triple_in_every_graph() {
+        let store = store(
+            r#"<http://ex/a> <http://ex/label> "Colour"@en .
+            <http://ex/g> { <http://ex/a> <http://ex/label> "Colour"@en }"#,
+        );
+        let subject = Subject::from(NamedNode::new_unchecked("http://ex/a"));
+        let predicate = NamedNode::new_unchecked("http://ex/label");
+        let object = Literal::new_language_tagged_literal_unchecked("Colour", "en");
+        let edit = Edit::find(&store, subject.clone(), predicate.clone(), object.clone())
+            .unwrap()
+            .unwrap();
+        let mut update = edit.update(&edit.literal("Color"), String::new());
+        assert_eq!(
+            update.text,
+            "DELETE DATA {
+  <http://ex/a> <http://ex/label> \"Colour\"@en .
+  GRAPH <http://ex/g> {
+    <http://ex/a> <http://ex/label> \"Colour\"@en .
+  }
+} ;
+INSERT DATA {
+  <http://ex/a> <http://ex/label> \"Color\"@en .
+  GRAPH <http://ex/g> {
+    <http://ex/a> <http://ex/label> \"Color\"@en .
+  }
+}"
+        );
+        assert!(update.present(&store).unwrap());
+        update.capture(&store).unwrap();
+        store.update(&update.text).unwrap();
+        assert!(!update.present(&store).unwrap());
+        assert_eq!(store.len().unwrap(), 2);
+
+        let undo = Update::undo(&[update], &store, String::new())
+            .unwrap()
+            .unwrap();
+        assert!(undo
+            .text
+            .starts_with("DELETE DATA {\n  <http://ex/a> <http://ex/label> \"Color\"@en ."));
+        store.update(&undo.text).unwrap();
+        assert_eq!(
+            graphs_of(&store, &subject, &predicate, &object.into())
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(Update::undo(&[], &store, String::new()).unwrap().is_none());
+
+        let gone = Literal::new_simple_literal("missing");
+        assert!(Edit::find(&store, subject, predicate, gone)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn undo_keeps_values_that_were_already_present() {
+        let store = store(r#"<http://ex/a> <http://ex/p> "x" , "y" ."#);
+        let object = Literal::new_simple_literal("x");
+        let edit = Edit::find(
+            &store,
+            NamedNode::new_unchecked("http://ex/a").into(),
+            NamedNode::new_unchecked("http://ex/p"),
+            object,
+        )
+        .unwrap()
+        .unwrap();
+        let mut update = edit.update(&edit.literal("y"), String::new());
+        update.capture(&store).unwrap();
+        assert!(update.inserted.is_empty());
+        store.update(&update.text).unwrap();
+        let undo = Update::undo(&[update], &store, String::new())
+            .unwrap()
+            .unwrap();
+        store.update(&undo.text).unwrap();
+        assert_eq!(store.len().unwrap(), 2);
+    }
+
+    #[test]
+    fn deletions_round_trip_awkward_terms() {
+        let store = store(
+            r#"@prefix ex: <http://ex/> .
+            @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+            ex:a ex:p "quote \" backslash \\ tab	newline\nreturn\r end" ,
+                "é 中 𝄞"@zh-Hant , "3.50"^^xsd:decimal , "x"^^<http://ex/type> , ex:b ,
+                << ex:a ex:p ex:b >> .
+            << ex:a ex:p "q" >> ex:p "annotated" .
+            ex:g { ex:a ex:p "in a graph" , ex:b . }
+            ex:h { ex:a ex:p ex:b . }"#,
+        );
+        let quads: Vec<Quad> = store.iter().map(Result::unwrap).collect();
+        assert_eq!(quads.len(), 10);
+        let update = Update::delete(quads, String::new());
+        assert!(update.text.contains("  GRAPH <http://ex/g> {\n"));
+        store.update(&update.text).unwrap();
+        assert_eq!(store.len().unwrap(), 0);
+        assert!(!update.present(&store).unwrap());
+    }
+}
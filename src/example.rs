@@ -0,0 +1,209 @@
+use oxigraph::model::{Term, Variable};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 一格於所生查詢中之取法。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// 須為此值。
+    Exact,
+    /// 須為同一數據類型之字面量。
+    Datatype,
+    /// 任意，亦可未綁定。
+    Any,
+}
+
+impl Choice {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Exact => "exact value",
+            Self::Datatype => "same datatype",
+            Self::Any => "any",
+        }
+    }
+
+    /// 此格可有之取法。空白節點於查詢中即變量，不能固定。
+    fn options(term: Option<&Term>) -> &'static [Self] {
+        match term {
+            Some(Term::NamedNode(_)) => &[Self::Exact, Self::Any],
+            Some(Term::Literal(_)) => &[Self::Exact, Self::Datatype, Self::Any],
+            _ => &[Self::Any],
+        }
+    }
+}
+
+/// 以所選之行為例，逐列定其取法，生「與此行相似」之查詢。
+#[derive(Debug)]
+pub struct Example {
+    pub variables: Vec<Variable>,
+    pub cells: Vec<Option<Term>>,
+    pub choices: Vec<Choice>,
+    /// 所選之列。
+    pub selected: usize,
+    /// 產生此行之查詢；三元組形之結果不需之。
+    query: Option<String>,
+}
+
+impl Example {
+    /// 首列任意，即所求之物；餘列 IRI 固定，字面量取同一類型。
+    pub fn new(variables: Vec<Variable>, cells: Vec<Option<Term>>, query: Option<String>) -> Self {
+        let choices = cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| {
+                let options = Choice::options(cell.as_ref());
+                match cell {
+                    _ if index == 0 => Choice::Any,
+                    Some(Term::Literal(_)) => Choice::Datatype,
+                    _ => options[0],
+                }
+            })
+            .collect();
+        Self {
+            variables,
+            cells,
+            choices,
+            selected: 0,
+            query,
+        }
+    }
+
+    /// 所選之列換其取法。
+    pub fn cycle(&mut self, delta: isize) {
+        let Some(choice) = self.choices.get_mut(self.selected) else {
+            return;
+        };
+        let options = Choice::options(self.cells[self.selected].as_ref());
+        let index = options.iter().position(|c| c == choice).unwrap_or(0) as isize;
+        *choice = options[(index + delta).rem_euclid(options.len() as isize) as usize];
+    }
+
+    pub fn select(&mut self, delta: isize) {
+        let last = self.variables.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+    }
+
+    /// 結果為三元組之形，可直接寫為三元組模式。
+    fn is_triple(&self) -> bool {
+        let names: Vec<&str> = self.variables.iter().map(Variable::as_str).collect();
+        names == ["s", "p", "o"]
+    }
+
+    /// 所生之查詢。非三元組形者包原查詢為子查詢而加過濾；原查詢非 SELECT 則無從生。
+    pub fn query(&self) -> Option<String> {
+        let mut filters = vec![];
+        let mut positions = vec![];
+        for ((variable, cell), choice) in self.variables.iter().zip(&self.cells).zip(&self.choices)
+        {
+            let position = match (choice, cell) {
+                (Choice::Exact, Some(term)) if self.is_triple() => term.to_string(),
+                (Choice::Exact, Some(Term::Literal(literal))) => {
+                    filters.push(format!("FILTER(sameTerm({variable}, {literal}))"));
+                    variable.to_string()
+                }
+                (Choice::Exact, Some(term)) => {
+                    filters.push(format!("FILTER({variable} = {term})"));
+                    variable.to_string()
+                }
+                (Choice::Datatype, Some(Term::Literal(literal))) => {
+                    filters.push(format!(
+                        "FILTER(DATATYPE({variable}) = {})",
+                        literal.datatype()
+                    ));
+                    variable.to_string()
+                }
+                _ => variable.to_string(),
+            };
+            positions.push(position);
+        }
+        let mut lines = vec![];
+        let prologue = if self.is_triple() {
+            lines.push(format!("  {} .", positions.join(" ")));
+            ""
+        } else {
+            let query = self.query.as_deref()?;
+            let (prologue, body) = split_prologue(query);
+            if !select().is_match(body) {
+                return None;
+            }
+            lines.push("  {".to_string());
+            lines.extend(body.trim().lines().map(|line| format!("    {line}")));
+            lines.push("  }".to_string());
+            prologue
+        };
+        lines.extend(filters.into_iter().map(|filter| format!("  {filter}")));
+        Some(format!(
+            "{prologue}SELECT * WHERE {{\n{}\n}}\n",
+            lines.join("\n")
+        ))
+    }
+}
+
+/// 分查詢之 PREFIX 與 BASE 聲明及其餘。子查詢不能帶聲明，故須提至外層。
+fn split_prologue(query: &str) -> (&str, &str) {
+    static PROLOGUE: OnceLock<Regex> = OnceLock::new();
+    let prologue = PROLOGUE.get_or_init(|| {
+        Regex::new(r"(?i)^(\s*(#[^\n]*|PREFIX\s+[^\s:]*:\s*<[^>]*>|BASE\s*<[^>]*>))*\s*").unwrap()
+    });
+    let end = prologue.find(query).map_or(0, |m| m.end());
+    query.split_at(end)
+}
+
+fn select() -> &'static Regex {
+    static SELECT: OnceLock<Regex> = OnceLock::new();
+    SELECT.get_or_init(|| Regex::new(r"(?i)^SELECT\b").unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, NamedNode};
+
+    fn example(names: &[&str], query: Option<&str>) -> Example {
+        let cells = vec![
+            Some(Term::from(NamedNode::new_unchecked("http://ex/a"))),
+            Some(Term::from(NamedNode::new_unchecked("http://ex/p"))),
+            Some(Term::from(Literal::new_typed_literal(
+                "1",
+                NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#integer"),
+            ))),
+        ];
+        let variables = names
+            .iter()
+            .map(|name| Variable::new_unchecked(*name))
+            .collect();
+        Example::new(variables, cells, query.map(str::to_string))
+    }
+
+    #[test]
+    fn triples_become_patterns_and_other_rows_filter_their_query() {
+        let mut triple = example(&["s", "p", "o"], None);
+        assert_eq!(
+            triple.query().unwrap(),
+            "SELECT * WHERE {\n  ?s <http://ex/p> ?o .\n  \
+             FILTER(DATATYPE(?o) = <http://www.w3.org/2001/XMLSchema#integer>)\n}\n"
+        );
+        triple.selected = 2;
+        triple.cycle(-1);
+        assert_eq!(triple.choices[2], Choice::Exact);
+        triple.select(-1);
+        triple.cycle(1);
+        assert_eq!(
+            triple.query().unwrap(),
+            "SELECT * WHERE {\n  \
+             ?s ?p \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n}\n"
+        );
+
+        let query = "PREFIX ex: <http://ex/>\nSELECT ?x ?y ?z { ?x ?y ?z }";
+        let mut row = example(&["x", "y", "z"], Some(query));
+        row.selected = 2;
+        row.cycle(1);
+        assert_eq!(
+            row.query().unwrap(),
+            "PREFIX ex: <http://ex/>\nSELECT * WHERE {\n  {\n    \
+             SELECT ?x ?y ?z { ?x ?y ?z }\n  }\n  FILTER(?y = <http://ex/p>)\n}\n"
+        );
+        assert!(example(&["x", "y", "z"], Some("ASK {}")).query().is_none());
+        assert!(example(&["x", "y", "z"], None).query().is_none());
+    }
+}
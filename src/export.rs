@@ -0,0 +1,905 @@
+use crate::{
+    action::Values,
+    display::{format_bytes, group_digits},
+    dupes::Dupes,
+    functional::Functional,
+    pattern::Pattern,
+    service::query_options,
+    sink::Sink,
+    worker::TermRow,
+};
+use anyhow::bail;
+use oxigraph::{
+    io::{RdfFormat, RdfSerializer},
+    model::{vocab::xsd, GraphName, NamedNodeRef, Quad, Subject, Term},
+    sparql::{
+        results::{QueryResultsFormat, QueryResultsSerializer},
+        Query, QueryResults, QuerySolution, Variable,
+    },
+    store::Store,
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, BufWriter, Write},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// CSV 無引用三元組之寫法，其主謂賓將混作一串。
+pub const CSV_QUOTED_TRIPLES: &str =
+    "CSV cannot represent quoted triples; export as tsv, json or xml instead";
+
+/// 平值所失者之數：非字串之字面量失其數據類型，帶語言之字串失其語言，空節點與同名之
+/// 字串無別。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Flattened {
+    pub typed: usize,
+    pub tagged: usize,
+    pub blank: usize,
+}
+
+impl Flattened {
+    /// 諸行中將失者。
+    pub fn of<'a>(rows: impl IntoIterator<Item = &'a TermRow>) -> Self {
+        let mut flattened = Self::default();
+        for term in rows.into_iter().flatten().flatten() {
+            flattened.add(term);
+        }
+        flattened
+    }
+
+    pub fn add(&mut self, term: &Term) {
+        match term {
+            Term::Literal(literal) if literal.language().is_some() => self.tagged += 1,
+            Term::Literal(literal) if literal.datatype() != xsd::STRING => self.typed += 1,
+            Term::BlankNode(_) => self.blank += 1,
+            _ => {}
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 如 `3 typed literals, 1 language-tagged string and 2 blank nodes`。
+impl fmt::Display for Flattened {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = [
+            (self.typed, "typed literal"),
+            (self.tagged, "language-tagged string"),
+            (self.blank, "blank node"),
+        ];
+        let parts: Vec<String> = counts
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, noun)| match count {
+                1 => format!("1 {noun}"),
+                count => format!("{} {noun}s", group_digits(count)),
+            })
+            .collect();
+        match parts.split_last() {
+            None => f.write_str("nothing"),
+            Some((last, [])) => f.write_str(last),
+            Some((last, rest)) => write!(f, "{} and {last}", rest.join(", ")),
+        }
+    }
+}
+
+/// 此格式與值之導出是否失數據類型、語言與空節點之別：平值皆然，原樣之 CSV 亦然，
+/// 類型列與原樣之 TSV 存之。
+pub fn flattens(format: QueryResultsFormat, values: Values) -> bool {
+    match values {
+        Values::Plain => matches!(format, QueryResultsFormat::Csv | QueryResultsFormat::Tsv),
+        Values::Raw => format == QueryResultsFormat::Csv,
+        Values::Typed => false,
+    }
+}
+
+/// 所導出之行。
+pub enum Rows {
+    /// 已載入之行。
+    Loaded {
+        variables: Vec<Variable>,
+        rows: Vec<TermRow>,
+    },
+    /// 重新執行之查詢。
+    Query {
+        query: Box<Query>,
+        federated: bool,
+        timeout: Duration,
+    },
+    /// 重新列出之三元組模式。
+    Pattern(Pattern),
+    /// 重新查找之重複值。
+    Dupes(Dupes),
+    /// 重新檢查之函數性屬性。
+    Functional(Functional),
+}
+
+/// 所導出之 RDF。
+pub enum Graph {
+    /// 存儲之諸圖。
+    Store,
+    /// 重新執行之 CONSTRUCT 或 DESCRIBE 查詢。
+    Query {
+        query: Box<Query>,
+        federated: bool,
+        timeout: Duration,
+    },
+}
+
+/// 輸入文件所聲明之前綴，按名存之。同名而異 IRI 者取其小者，故與文件載入之序無涉。
+#[derive(Debug, Clone, Default)]
+pub struct Declared(BTreeMap<String, String>);
+
+impl Declared {
+    pub fn add(&mut self, prefixes: &[(String, String)]) {
+        for (name, iri) in prefixes {
+            self.0
+                .entry(name.clone())
+                .and_modify(|known| {
+                    if iri < known {
+                        known.clone_from(iri);
+                    }
+                })
+                .or_insert_with(|| iri.clone());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// 導出所聲明之前綴：先此，後 `configured`；名或 IRI 已見者略去。
+    pub fn with(&self, configured: &[(String, String)]) -> Vec<(String, String)> {
+        let mut prefixes: Vec<(String, String)> = vec![];
+        for (name, iri) in self.0.iter().chain(configured.iter().map(|(n, i)| (n, i))) {
+            if !prefixes.iter().any(|(n, i)| n == name || i == iri) {
+                prefixes.push((name.clone(), iri.clone()));
+            }
+        }
+        prefixes
+    }
+}
+
+/// 後台執行中之導出。寫文件者先寫入 `<path>.partial`，成則易名為 `path`；
+/// 取消或失敗則刪之，或按設置留之。
+pub struct ExportJob {
+    pub sink: Sink,
+    /// 總行數。重新查詢者未知。
+    pub total: Option<usize>,
+    started: Instant,
+    written: Arc<AtomicUsize>,
+    bytes: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+    receiver: Receiver<Result<usize, String>>,
+    /// 去處之附註，如命令之輸出或 HTTP 之狀態。
+    note: Arc<Mutex<Option<String>>>,
+    /// 所數者之名，如 `rows`。
+    pub unit: &'static str,
+}
+
+impl ExportJob {
+    /// 於後台導出至多 `limit` 行。`values` 唯 CSV、TSV 用之。`keep_partial` 則取消或失敗時
+    /// 留下已寫之部分。
+    pub fn spawn(
+        store: &Store,
+        rows: Rows,
+        format: QueryResultsFormat,
+        values: Values,
+        sink: Sink,
+        limit: Option<usize>,
+        keep_partial: bool,
+    ) -> Self {
+        let limit = limit.unwrap_or(usize::MAX);
+        let total = match &rows {
+            Rows::Loaded { rows, .. } => Some(rows.len().min(limit)),
+            _ => None,
+        };
+        let store = store.clone();
+        Self::start(
+            sink,
+            format.media_type(),
+            total,
+            keep_partial,
+            "rows",
+            move |out, progress| write_rows(&store, rows, format, values, out, limit, progress),
+        )
+    }
+
+    /// 於後台以 RDF 導出至多 `limit` 個三元組，聲明並用 `prefixes`。
+    pub fn spawn_graph(
+        store: &Store,
+        graph: Graph,
+        format: RdfFormat,
+        prefixes: Vec<(String, String)>,
+        sink: Sink,
+        limit: Option<usize>,
+        keep_partial: bool,
+    ) -> Self {
+        let limit = limit.unwrap_or(usize::MAX);
+        let total = match graph {
+            Graph::Store => store.len().ok().map(|len| len.min(limit)),
+            Graph::Query { .. } => None,
+        };
+        let store = store.clone();
+        Self::start(
+            sink,
+            format.media_type(),
+            total,
+            keep_partial,
+            "triples",
+            move |out, progress| {
+                write_graph(&store, graph, format, &prefixes, out, limit, progress)
+            },
+        )
+    }
+
+    /// 於後台以 `write` 寫至 `sink`，計其字節。`unit` 為所數者之名。
+    fn start(
+        sink: Sink,
+        content_type: &'static str,
+        total: Option<usize>,
+        keep_partial: bool,
+        unit: &'static str,
+        write: impl FnOnce(&mut dyn Write, &Progress) -> anyhow::Result<usize> + Send + 'static,
+    ) -> Self {
+        let written = Arc::new(AtomicUsize::new(0));
+        let bytes = Arc::new(AtomicUsize::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let progress = Progress {
+            written: Arc::clone(&written),
+            bytes: Arc::clone(&bytes),
+            cancel: Arc::clone(&cancel),
+        };
+        let note: Arc<Mutex<Option<String>>> = Arc::default();
+        let (target, noted) = (sink.clone(), Arc::clone(&note));
+        thread::spawn(move || {
+            let result = target.deliver(content_type, keep_partial, false, |out| {
+                let mut out = Counting {
+                    inner: out,
+                    bytes: Arc::clone(&progress.bytes),
+                };
+                write(&mut out, &progress)
+            });
+            let result = result.map(|(count, note)| {
+                *noted.lock().unwrap() = note;
+                count
+            });
+            let _ = sender.send(result.map_err(|error| format!("{error:#}")));
+        });
+        Self {
+            sink,
+            total,
+            started: Instant::now(),
+            written,
+            bytes,
+            cancel,
+            receiver,
+            note,
+            unit,
+        }
+    }
+
+    /// 已完成者去處之附註。
+    pub fn note(&self) -> Option<String> {
+        self.note.lock().unwrap().clone()
+    }
+
+    /// 已完成則返回所寫之行數或錯誤。
+    pub fn poll(&self) -> Option<Result<usize, String>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// 等待完成。
+    pub fn wait(&self) -> Result<usize, String> {
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err("The export stopped".to_string()))
+    }
+
+    /// 請求取消。已寫之部分按設置刪去或留下。
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// 已寫之比例。總數未知則為零。
+    pub fn ratio(&self) -> f64 {
+        match self.total {
+            Some(0) | None => 0.0,
+            Some(total) => (self.written.load(Ordering::Relaxed) as f64 / total as f64).min(1.0),
+        }
+    }
+
+    /// 狀態欄之標籤，如 `Exporting 20,000 of 1,234,567 rows to out.csv`，
+    /// 總數未知則示所寫之字節數。
+    pub fn label(&self) -> String {
+        let (written, unit) = (
+            group_digits(self.written.load(Ordering::Relaxed)),
+            self.unit,
+        );
+        let progress = match self.total {
+            Some(total) => format!("{written} of {} {unit}", group_digits(total)),
+            None => format!(
+                "{written} {unit} ({})",
+                format_bytes(self.bytes.load(Ordering::Relaxed))
+            ),
+        };
+        format!("Exporting {progress} to {}", self.sink)
+    }
+}
+
+/// 與界面共享之進度。
+#[derive(Default)]
+struct Progress {
+    written: Arc<AtomicUsize>,
+    bytes: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// 計所寫之字節。
+struct Counting<W> {
+    inner: W,
+    bytes: Arc<AtomicUsize>,
+}
+
+impl<W: Write> Write for Counting<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes.fetch_add(written, Ordering::Relaxed);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+type Solutions<'a> = Box<dyn Iterator<Item = anyhow::Result<QuerySolution>> + 'a>;
+
+/// 即寫至多 `limit` 行於 `out`，返回行數。不可取消，供退出時寫於標準輸出。
+pub fn write_to(
+    store: &Store,
+    rows: Rows,
+    format: QueryResultsFormat,
+    values: Values,
+    out: impl Write,
+    limit: Option<usize>,
+) -> anyhow::Result<usize> {
+    let limit = limit.unwrap_or(usize::MAX);
+    write_rows(
+        store,
+        rows,
+        format,
+        values,
+        out,
+        limit,
+        &Progress::default(),
+    )
+}
+
+fn write_rows(
+    store: &Store,
+    rows: Rows,
+    format: QueryResultsFormat,
+    values: Values,
+    out: impl Write,
+    limit: usize,
+    progress: &Progress,
+) -> anyhow::Result<usize> {
+    let triple = |variables: &Arc<[Variable]>, row: [Term; 3]| {
+        QuerySolution::from((variables.clone(), row.map(Some).to_vec()))
+    };
+    let (variables, solutions): (Vec<Variable>, Solutions) = match rows {
+        Rows::Loaded { variables, rows } => {
+            let shared: Arc<[Variable]> = variables.clone().into();
+            let rows = rows.into_iter().map(move |row| {
+                let values = row.into_iter().map(|term| term.as_deref().cloned());
+                Ok(QuerySolution::from((
+                    shared.clone(),
+                    values.collect::<Vec<_>>(),
+                )))
+            });
+            (variables, Box::new(rows))
+        }
+        Rows::Query {
+            query,
+            federated,
+            timeout,
+        } => {
+            let options = query_options(federated, timeout, Arc::default());
+            let QueryResults::Solutions(solutions) = store.query_opt(*query, options)? else {
+                bail!("Not a SELECT query");
+            };
+            let variables = solutions.variables().to_vec();
+            (variables, Box::new(solutions.map(|solution| Ok(solution?))))
+        }
+        Rows::Pattern(pattern) => {
+            let variables: Arc<[Variable]> = Pattern::variables().into();
+            let rows = pattern
+                .rows(store)
+                .map(move |row| Ok(triple(&variables, row?)));
+            (Pattern::variables(), Box::new(rows))
+        }
+        Rows::Dupes(dupes) => {
+            let variables: Arc<[Variable]> = Dupes::variables().into();
+            let rows = dupes.clusters(store)?.rows.into_iter();
+            let rows = rows.map(move |row| Ok(triple(&variables, row)));
+            (Dupes::variables(), Box::new(rows))
+        }
+        Rows::Functional(functional) => {
+            let conflicts = functional.conflicts(store)?;
+            let shared: Arc<[Variable]> = conflicts.variables.clone().into();
+            let rows = conflicts
+                .rows
+                .into_iter()
+                .map(move |row| Ok(QuerySolution::from((shared.clone(), row))));
+            (conflicts.variables, Box::new(rows))
+        }
+    };
+    let separator = match format {
+        QueryResultsFormat::Csv => ',',
+        QueryResultsFormat::Tsv => '\t',
+        _ => return write_raw(out, variables, solutions, format, limit, progress),
+    };
+    if values == Values::Raw {
+        return write_raw(out, variables, solutions, format, limit, progress);
+    }
+    let mut file = BufWriter::new(out);
+    write_record(&mut file, &plain_header(&variables, values), separator)?;
+    let mut rows = 0;
+    for solution in solutions.take(limit) {
+        if progress.cancel.load(Ordering::Relaxed) {
+            bail!("Export cancelled after {} rows", group_digits(rows));
+        }
+        write_record(&mut file, &plain_row(solution?.values(), values), separator)?;
+        rows += 1;
+        progress.written.store(rows, Ordering::Relaxed);
+    }
+    file.flush()?;
+    Ok(rows)
+}
+
+/// 以 SPARQL 結果格式寫出之，CSV 與 TSV 之值原樣。
+fn write_raw(
+    file: impl Write,
+    variables: Vec<Variable>,
+    solutions: Solutions,
+    format: QueryResultsFormat,
+    limit: usize,
+    progress: &Progress,
+) -> anyhow::Result<usize> {
+    let mut writer = QueryResultsSerializer::from_format(format)
+        .serialize_solutions_to_write(BufWriter::new(file), variables)?;
+    let mut rows = 0;
+    for solution in solutions.take(limit) {
+        if progress.cancel.load(Ordering::Relaxed) {
+            bail!("Export cancelled after {} rows", group_digits(rows));
+        }
+        let solution = solution?;
+        if format == QueryResultsFormat::Csv
+            && solution
+                .values()
+                .iter()
+                .any(|term| matches!(term, Some(Term::Triple(_))))
+        {
+            bail!(CSV_QUOTED_TRIPLES);
+        }
+        writer.write(&solution)?;
+        rows += 1;
+        progress.written.store(rows, Ordering::Relaxed);
+    }
+    writer.finish()?.flush()?;
+    Ok(rows)
+}
+
+/// 即以 RDF 寫至多 `limit` 個三元組於 `out`，返回其數。不可取消，供退出時寫於標準輸出。
+pub fn write_graph_to(
+    store: &Store,
+    graph: Graph,
+    format: RdfFormat,
+    prefixes: &[(String, String)],
+    out: impl Write,
+    limit: Option<usize>,
+) -> anyhow::Result<usize> {
+    let limit = limit.unwrap_or(usize::MAX);
+    write_graph(
+        store,
+        graph,
+        format,
+        prefixes,
+        out,
+        limit,
+        &Progress::default(),
+    )
+}
+
+/// 以 RDF 寫出，依圖、主、謂、賓之文排序，前綴唯聲明其所用者，故同數據所寫之字節相同。
+fn write_graph(
+    store: &Store,
+    graph: Graph,
+    format: RdfFormat,
+    prefixes: &[(String, String)],
+    out: impl Write,
+    limit: usize,
+    progress: &Progress,
+) -> anyhow::Result<usize> {
+    let quads: Vec<Quad> = match graph {
+        Graph::Store => store.iter().take(limit).collect::<Result<_, _>>()?,
+        Graph::Query {
+            query,
+            federated,
+            timeout,
+        } => {
+            let options = query_options(federated, timeout, Arc::default());
+            let QueryResults::Graph(triples) = store.query_opt(*query, options)? else {
+                bail!("Only CONSTRUCT and DESCRIBE results export as RDF");
+            };
+            triples
+                .take(limit)
+                .map(|triple| Ok(triple?.in_graph(GraphName::DefaultGraph)))
+                .collect::<anyhow::Result<_>>()?
+        }
+    };
+    if !format.supports_datasets() && quads.iter().any(|quad| !quad.graph_name.is_default_graph()) {
+        bail!(
+            "{} holds only the default graph; export as trig or nq instead",
+            format.name()
+        );
+    }
+    let mut keyed: Vec<([String; 4], Quad)> = quads
+        .into_iter()
+        .map(|quad| {
+            let key = [
+                quad.graph_name.to_string(),
+                quad.subject.to_string(),
+                quad.predicate.to_string(),
+                quad.object.to_string(),
+            ];
+            (key, quad)
+        })
+        .collect();
+    keyed.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    let mut serializer = RdfSerializer::from_format(format);
+    for (name, iri) in prefixes {
+        if keyed.iter().any(|(_, quad)| uses(quad, iri)) {
+            serializer = serializer.with_prefix(name, iri)?;
+        }
+    }
+    let mut writer = serializer.serialize_to_write(BufWriter::new(out));
+    let mut count = 0;
+    for (_, quad) in &keyed {
+        if progress.cancel.load(Ordering::Relaxed) {
+            bail!("Export cancelled after {} triples", group_digits(count));
+        }
+        writer.write_quad(quad)?;
+        count += 1;
+        progress.written.store(count, Ordering::Relaxed);
+    }
+    writer.finish()?.flush()?;
+    Ok(count)
+}
+
+/// 此四元組之 IRI 或數據類型有始於 `namespace` 者。引用三元組之內不計。
+fn uses(quad: &Quad, namespace: &str) -> bool {
+    let starts = |iri: NamedNodeRef<'_>| iri.as_str().starts_with(namespace);
+    matches!(&quad.subject, Subject::NamedNode(iri) if starts(iri.as_ref()))
+        || starts(quad.predicate.as_ref())
+        || match &quad.object {
+            Term::NamedNode(iri) => starts(iri.as_ref()),
+            Term::Literal(literal) => starts(literal.datatype()),
+            _ => false,
+        }
+        || matches!(&quad.graph_name, GraphName::NamedNode(iri) if starts(iri.as_ref()))
+}
+
+/// 平值之表頭：變量名不加 `?`。`Typed` 則每變量後附 `<name>_type`。
+pub fn plain_header(variables: &[Variable], values: Values) -> Vec<String> {
+    let mut header = vec![];
+    for variable in variables {
+        header.push(variable.as_str().to_string());
+        if values == Values::Typed {
+            header.push(format!("{}_type", variable.as_str()));
+        }
+    }
+    header
+}
+
+/// 一行之平值。`Typed` 則每值後附其類型。
+pub fn plain_row(row: &[Option<Term>], values: Values) -> Vec<String> {
+    let mut cells = vec![];
+    for term in row {
+        cells.push(term.as_ref().map(plain_value).unwrap_or_default());
+        if values == Values::Typed {
+            cells.push(term.as_ref().map(value_type).unwrap_or_default());
+        }
+    }
+    cells
+}
+
+/// 字面量之詞法形式、IRI 之串、空白節點之標籤，皆不加引號與括號。引用三元組仍如 N-Triples。
+pub fn plain_value(term: &Term) -> String {
+    match term {
+        Term::Literal(literal) => literal.value().to_string(),
+        Term::NamedNode(node) => node.as_str().to_string(),
+        Term::BlankNode(node) => node.as_str().to_string(),
+        Term::Triple(triple) => triple.to_string(),
+    }
+}
+
+/// 字面量之語言，如 `@en`，或其數據類型之 IRI；`xsd:string` 及非字面量者為空。
+pub fn value_type(term: &Term) -> String {
+    let Term::Literal(literal) = term else {
+        return String::new();
+    };
+    match literal.language() {
+        Some(language) => format!("@{language}"),
+        None if literal.datatype() == xsd::STRING => String::new(),
+        None => literal.datatype().as_str().to_string(),
+    }
+}
+
+/// 寫一行。CSV 之值含逗號、引號或換行則加引號；TSV 之製表符、換行與反斜杠轉義之。
+pub fn write_record(writer: &mut impl Write, cells: &[String], separator: char) -> io::Result<()> {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| match separator {
+            ',' if cell.contains([',', '"', '\n', '\r']) => {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            }
+            '\t' => cell
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r"),
+            _ => cell.clone(),
+        })
+        .collect();
+    write!(writer, "{}", cells.join(&separator.to_string()))?;
+    // RFC 4180 以 CRLF 斷行，與 SPARQL CSV 同
+    writer.write_all(if separator == ',' { b"\r\n" } else { b"\n" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overwrite::partial_path;
+    use oxigraph::{io::RdfFormat, sparql::Query};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn flattening_counts_types_languages_and_blank_nodes() {
+        use oxigraph::model::{BlankNode, Literal, NamedNode};
+        let row = |terms: Vec<Term>| -> TermRow {
+            terms.into_iter().map(|t| Some(Arc::new(t))).collect()
+        };
+        let iri = Term::from(NamedNode::new_unchecked("http://ex/a"));
+        let string = Term::from(Literal::from("a"));
+        let plain = [row(vec![iri.clone(), string.clone()]), vec![None]];
+        assert!(Flattened::of(&plain).is_empty());
+
+        let kinds = [
+            (
+                Term::from(Literal::from(42)),
+                Flattened {
+                    typed: 1,
+                    ..Flattened::default()
+                },
+            ),
+            (
+                Term::from(Literal::new_language_tagged_literal_unchecked("a", "en")),
+                Flattened {
+                    tagged: 1,
+                    ..Flattened::default()
+                },
+            ),
+            (
+                Term::from(BlankNode::new_unchecked("b0")),
+                Flattened {
+                    blank: 1,
+                    ..Flattened::default()
+                },
+            ),
+        ];
+        for (term, expected) in &kinds {
+            let rows = [row(vec![iri.clone(), term.clone()])];
+            assert_eq!(Flattened::of(&rows), *expected);
+        }
+        let all: Vec<TermRow> = kinds
+            .iter()
+            .map(|(term, _)| row(vec![term.clone(), term.clone()]))
+            .collect();
+        assert_eq!(
+            Flattened::of(&all).to_string(),
+            "2 typed literals, 2 language-tagged strings and 2 blank nodes"
+        );
+        assert_eq!(kinds[2].1.to_string(), "1 blank node");
+
+        assert!(flattens(QueryResultsFormat::Csv, Values::Raw));
+        assert!(flattens(QueryResultsFormat::Tsv, Values::Plain));
+        assert!(!flattens(QueryResultsFormat::Tsv, Values::Raw));
+        assert!(!flattens(QueryResultsFormat::Csv, Values::Typed));
+        assert!(!flattens(QueryResultsFormat::Json, Values::Plain));
+    }
+
+    #[test]
+    fn plain_values_are_bare_and_escaped() {
+        let terms = [
+            Term::from(oxigraph::model::Literal::new_typed_literal(
+                "42",
+                xsd::INTEGER,
+            )),
+            Term::from(
+                oxigraph::model::Literal::new_language_tagged_literal_unchecked(
+                    "a, \"b\"\tc",
+                    "en",
+                ),
+            ),
+            Term::from(oxigraph::model::BlankNode::new_unchecked("b0")),
+        ];
+        let row: Vec<Option<Term>> = terms.into_iter().map(Some).chain([None]).collect();
+        assert_eq!(
+            plain_row(&row, Values::Typed),
+            [
+                "42",
+                xsd::INTEGER.as_str(),
+                "a, \"b\"\tc",
+                "@en",
+                "b0",
+                "",
+                "",
+                ""
+            ]
+        );
+        let cells = plain_row(&row, Values::Plain);
+        let mut csv = vec![];
+        write_record(&mut csv, &cells, ',').unwrap();
+        assert_eq!(csv, b"42,\"a, \"\"b\"\"\tc\",b0,\r\n");
+        let mut tsv = vec![];
+        write_record(&mut tsv, &cells, '\t').unwrap();
+        assert_eq!(tsv, b"42\ta, \"b\"\\tc\tb0\t\n");
+    }
+
+    #[test]
+    fn cancelled_exports_remove_or_keep_what_they_wrote() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfFormat::Turtle,
+                "<http://ex/a> <http://ex/p> 1, 2, 3 .".as_bytes(),
+            )
+            .unwrap();
+        for keep in [false, true] {
+            let path = std::env::temp_dir()
+                .join(format!("rdf-tui-export-{keep}-{}.tsv", std::process::id()));
+            // 三元組之十二重自連接，逾五十萬行
+            let query = Query::parse(
+                &format!(
+                    "SELECT * WHERE {{ {} }}",
+                    (0..12)
+                        .map(|n| format!("?s{n} ?p{n} ?o{n} ."))
+                        .collect::<String>()
+                ),
+                None,
+            )
+            .unwrap();
+            let rows = Rows::Query {
+                query: Box::new(query),
+                federated: false,
+                timeout: Duration::from_secs(30),
+            };
+            let job = ExportJob::spawn(
+                &store,
+                rows,
+                QueryResultsFormat::Tsv,
+                Values::Raw,
+                Sink::File(path.clone()),
+                None,
+                keep,
+            );
+            job.cancel();
+            assert!(job.wait().unwrap_err().contains("cancelled"));
+            assert!(!path.exists());
+            assert_eq!(partial_path(&path).exists(), keep);
+            let _ = fs::remove_file(partial_path(&path));
+        }
+    }
+
+    #[test]
+    fn rdf_exports_use_declared_prefixes_and_round_trip_byte_for_byte() {
+        use crate::loader::load_file;
+        use oxigraph::{
+            io::RdfParser,
+            model::{dataset::CanonicalizationAlgorithm, Dataset},
+        };
+        use std::sync::atomic::AtomicU64;
+
+        let store = Store::new().unwrap();
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/animals.ttl");
+        let load = load_file(&store, &fixture, &AtomicU64::new(0), false).unwrap();
+        store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::TriG).rename_blank_nodes(),
+                "<http://example.org/g> { [] <http://example.org/says> \"meow\" }".as_bytes(),
+            )
+            .unwrap();
+        // 同名異 IRI 者，與載入之序無涉
+        let other = [("ex".to_string(), "http://other.example/".to_string())];
+        let mut declared = Declared::default();
+        declared.add(&load.prefixes);
+        declared.add(&other);
+        let mut reversed = Declared::default();
+        reversed.add(&other);
+        reversed.add(&load.prefixes);
+        let configured = [
+            ("schema".to_string(), "http://example.org/".to_string()),
+            ("unused".to_string(), "http://unused.example/".to_string()),
+        ];
+        let prefixes = declared.with(&configured);
+        assert_eq!(prefixes, reversed.with(&configured));
+
+        let export = |graph: Graph, format: RdfFormat, prefixes: &[(String, String)]| {
+            let mut out = vec![];
+            write_graph_to(&store, graph, format, prefixes, &mut out, None).map(|_| out)
+        };
+        let trig = export(Graph::Store, RdfFormat::TriG, &prefixes).unwrap();
+        assert_eq!(
+            trig,
+            export(Graph::Store, RdfFormat::TriG, &prefixes).unwrap()
+        );
+        let text = String::from_utf8(trig.clone()).unwrap();
+        assert!(text.starts_with(
+            "@prefix ex: <http://example.org/> .\n\
+             @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n"
+        ));
+        assert!(text.contains("ex:Cat rdfs:label \"Cat\"@en"));
+        assert!(text.contains("ex:g {"));
+        assert!(!text.contains("schema:") && !text.contains("unused"));
+        let dataset = |bytes: &[u8], format: RdfFormat| {
+            let mut dataset: Dataset = RdfParser::from_format(format)
+                .parse_read(bytes)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            dataset.canonicalize(CanonicalizationAlgorithm::Unstable);
+            dataset
+        };
+        let mut dumped = vec![];
+        store.dump_to_write(RdfFormat::NQuads, &mut dumped).unwrap();
+        assert_eq!(
+            dataset(&trig, RdfFormat::TriG),
+            dataset(&dumped, RdfFormat::NQuads)
+        );
+
+        let plain = export(Graph::Store, RdfFormat::TriG, &[]).unwrap();
+        let plain = String::from_utf8(plain).unwrap();
+        assert!(!plain.contains("@prefix"));
+        assert!(plain.contains("<http://example.org/Cat>"));
+        assert_eq!(
+            export(Graph::Store, RdfFormat::Turtle, &prefixes)
+                .unwrap_err()
+                .to_string(),
+            "Turtle holds only the default graph; export as trig or nq instead"
+        );
+        let construct = Graph::Query {
+            query: Box::new(Query::parse("CONSTRUCT WHERE { ?s ?p ?o }", None).unwrap()),
+            federated: false,
+            timeout: Duration::from_secs(30),
+        };
+        let turtle = export(construct, RdfFormat::Turtle, &prefixes).unwrap();
+        assert!(String::from_utf8(turtle)
+            .unwrap()
+            .contains("ex:Dog rdfs:label \"Dog\"@en ;\n\trdfs:subClassOf ex:Animal ."));
+    }
+}
@@ -0,0 +1,360 @@
+use oxigraph::sparql::Variable;
+use regex::{Regex, RegexBuilder};
+use std::{iter::Peekable, ops::Range, str::Chars};
+
+/// 過濾結果行之表達式，對各格之顯示文本而行。以空白分隔之子句皆須相符。
+///
+/// 子句如 `smith`、`?name: smith`、`2: ^http://example`、`!"John Smith"`：
+/// `?變量:` 或 `列號:`（自 1 起）限於一列，`!` 取不相符者，`/…/` 為正則，
+/// 引號內之空白不分隔。餘者依輸入行之模式為正則或不分大小寫之字面子串。
+/// 各子句編譯一次，逐行重用。
+#[derive(Debug, Clone)]
+pub struct Filter {
+    /// 所寫之表達式。
+    pub text: String,
+    /// 未以 `/…/` 標明之子句是否為正則。
+    pub regex: bool,
+    clauses: Vec<Clause>,
+}
+
+/// 子句所查之列。
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scope {
+    /// 任一列。
+    Any,
+    /// 某變量之列，不含 `?`。
+    Variable(String),
+    /// 第幾列，自 0 起。
+    Column(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Clause {
+    scope: Scope,
+    invert: bool,
+    regex: bool,
+    /// 所寫之值，不含引號與 `/`。
+    value: String,
+    compiled: Regex,
+}
+
+impl Scope {
+    /// 此範圍於表中所對之列，任一列為 `Some(None)`。所指之列不在表中則無。
+    fn resolve(&self, variables: &[Variable]) -> Option<Option<usize>> {
+        match self {
+            Self::Any => Some(None),
+            Self::Variable(name) => variables
+                .iter()
+                .position(|variable| variable.as_str() == name)
+                .map(Some),
+            Self::Column(column) => (*column < variables.len()).then_some(Some(*column)),
+        }
+    }
+}
+
+impl Filter {
+    /// 解析並編譯表達式。語法或正則有誤則返回一行之錯誤。
+    pub fn new(text: &str, regex: bool) -> Result<Self, String> {
+        let mut clauses = vec![];
+        let mut tokens = tokenize(text)?.into_iter();
+        while let Some((token, quoted)) = tokens.next() {
+            let (invert, token) = match token.strip_prefix('!') {
+                Some(token) if !quoted => (true, token.to_string()),
+                _ => (false, token),
+            };
+            let (scope, rest) = if quoted {
+                (Scope::Any, token.clone())
+            } else {
+                scope(&token)?
+            };
+            // `?name: smith` 之值在下一詞
+            let (value, quoted) = if scope != Scope::Any && rest.is_empty() {
+                tokens
+                    .next()
+                    .ok_or_else(|| format!("missing value after `{token}`"))?
+            } else {
+                (rest, quoted)
+            };
+            if value.is_empty() {
+                return Err("empty clause".to_string());
+            }
+            let delimited = (!quoted)
+                .then(|| value.strip_prefix('/')?.strip_suffix('/'))
+                .flatten()
+                .filter(|inner| !inner.is_empty());
+            let (value, clause_regex) = match delimited {
+                Some(inner) => (inner.to_string(), true),
+                None => (value, regex),
+            };
+            let compiled = if clause_regex {
+                Regex::new(&value)
+            } else {
+                RegexBuilder::new(&regex::escape(&value))
+                    .case_insensitive(true)
+                    .build()
+            }
+            .map_err(|error| {
+                let message = error.to_string();
+                message
+                    .lines()
+                    .last()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string()
+            })?;
+            clauses.push(Clause {
+                scope,
+                invert,
+                regex: clause_regex,
+                value,
+                compiled,
+            });
+        }
+        if clauses.is_empty() {
+            return Err("empty filter".to_string());
+        }
+        Ok(Self {
+            text: text.trim().to_string(),
+            regex,
+            clauses,
+        })
+    }
+
+    /// 規範之寫法，如 `?name:"smith" !2:/^http/`。顯示於標題，亦為過濾結果之緩存所認。
+    pub fn describe(&self) -> String {
+        let clauses: Vec<String> = self
+            .clauses
+            .iter()
+            .map(|clause| {
+                let bang = if clause.invert { "!" } else { "" };
+                let scope = match &clause.scope {
+                    Scope::Any => String::new(),
+                    Scope::Variable(name) => format!("?{name}:"),
+                    Scope::Column(column) => format!("{}:", column + 1),
+                };
+                match clause.regex {
+                    true => format!("{bang}{scope}/{}/", clause.value),
+                    false => format!("{bang}{scope}\"{}\"", clause.value),
+                }
+            })
+            .collect();
+        clauses.join(" ")
+    }
+
+    /// 所指之列皆在表中否；否則返回首個不在者。
+    pub fn check(&self, variables: &[Variable]) -> Result<(), String> {
+        let missing = self
+            .clauses
+            .iter()
+            .map(|clause| &clause.scope)
+            .find(|scope| scope.resolve(variables).is_none());
+        match missing {
+            Some(Scope::Variable(name)) => Err(format!("no variable ?{name}")),
+            Some(Scope::Column(column)) => Err(format!("no column {}", column + 1)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 一行是否留下：各子句皆相符。子句於所指之列有格相符，反轉者則無格相符；
+    /// 所指之列不在表中者不相符。
+    pub fn keeps(&self, variables: &[Variable], cells: &[impl AsRef<str>]) -> bool {
+        self.clauses.iter().all(|clause| {
+            let found = match clause.scope.resolve(variables) {
+                None => return false,
+                Some(None) => cells
+                    .iter()
+                    .any(|cell| clause.compiled.is_match(cell.as_ref())),
+                Some(Some(column)) => cells
+                    .get(column)
+                    .is_some_and(|cell| clause.compiled.is_match(cell.as_ref())),
+            };
+            found != clause.invert
+        })
+    }
+
+    /// 某列之格中相符之字節區間，以供標示，依起點排序而可重疊。反轉之子句無可標。
+    pub fn spans(&self, variables: &[Variable], column: usize, text: &str) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = self
+            .clauses
+            .iter()
+            .filter(|clause| !clause.invert)
+            .filter(|clause| match clause.scope.resolve(variables) {
+                Some(None) => true,
+                Some(Some(scoped)) => scoped == column,
+                None => false,
+            })
+            .flat_map(|clause| clause.compiled.find_iter(text).map(|found| found.range()))
+            .filter(|range| !range.is_empty())
+            .collect();
+        ranges.sort_by_key(|range| range.start);
+        ranges
+    }
+}
+
+/// 以空白分詞，返回各詞及其是否出自引號。`"…"` 內之空白不分，`\"` 與 `\\` 為其字面。
+/// 前綴後緊接之引號，如 `?name:"john smith"`，分為前綴與引號二詞。
+fn tokenize(text: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else {
+            return Ok(tokens);
+        };
+        if first == '"' {
+            chars.next();
+            tokens.push((quoted(&mut chars)?, true));
+            continue;
+        }
+        let mut token = String::new();
+        while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace()) {
+            if ch == '"' && token.ends_with(':') {
+                tokens.push((std::mem::take(&mut token), false));
+                tokens.push((quoted(&mut chars)?, true));
+                break;
+            }
+            token.push(ch);
+        }
+        if !token.is_empty() {
+            tokens.push((token, false));
+        }
+    }
+}
+
+/// 讀至閉引號，開引號已讀。
+fn quoted(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    let mut text = String::new();
+    loop {
+        match chars.next() {
+            None => return Err("unclosed quote".to_string()),
+            Some('"') => return Ok(text),
+            Some('\\') if matches!(chars.peek(), Some('"' | '\\')) => text.extend(chars.next()),
+            Some(ch) => text.push(ch),
+        }
+    }
+}
+
+/// 拆出詞首之 `?變量:` 或 `列號:`；無則為任一列。
+fn scope(token: &str) -> Result<(Scope, String), String> {
+    let Some((head, rest)) = token.split_once(':') else {
+        return Ok((Scope::Any, token.to_string()));
+    };
+    if let Some(name) = head.strip_prefix('?') {
+        if name.is_empty() {
+            return Err(format!("missing variable name in `{token}`"));
+        }
+        return match Variable::new(name) {
+            Ok(_) => Ok((Scope::Variable(name.to_string()), rest.to_string())),
+            Err(_) => Err(format!("invalid variable name ?{name}")),
+        };
+    }
+    if !head.is_empty() && head.chars().all(|ch| ch.is_ascii_digit()) {
+        return match head.parse::<usize>() {
+            Ok(column) if column > 0 => Ok((Scope::Column(column - 1), rest.to_string())),
+            _ => Err(format!("columns are numbered from 1, not `{head}`")),
+        };
+    }
+    Ok((Scope::Any, token.to_string()))
+}
+
+/// 某過濾下相符之行，依表之序。表仍在增長，故記已查之行數，新到者續查。
+#[derive(Debug, Default)]
+pub struct Matches {
+    /// 所依之過濾，即 [`Filter::describe`]。
+    pub key: String,
+    pub checked: usize,
+    pub rows: Vec<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(names: &[&str]) -> Vec<Variable> {
+        names
+            .iter()
+            .map(|name| Variable::new_unchecked(*name))
+            .collect()
+    }
+
+    #[test]
+    fn substrings_ignore_case_and_regexes_do_not() {
+        let any = variables(&["s", "o"]);
+        let filter = Filter::new("dOg", false).unwrap();
+        assert!(filter.keeps(&any, &["<http://ex/Dog>", "\"cat\""]));
+        assert_eq!(filter.spans(&any, 0, "a dog, a DOG"), [2..5, 9..12]);
+        let filter = Filter::new("a.c", false).unwrap();
+        assert!(!filter.keeps(&any, &["abc"]));
+
+        let filter = Filter::new("^\"[0-9]+\"$", true).unwrap();
+        assert!(filter.keeps(&any, &["x", "\"42\""]));
+        assert!(!filter.keeps(&any, &["\"4a\""]));
+        assert_eq!(filter.describe(), "/^\"[0-9]+\"$/");
+    }
+
+    #[test]
+    fn bang_inverts_and_errors_are_one_line() {
+        let any = variables(&["s", "o"]);
+        let filter = Filter::new("!cat", false).unwrap();
+        assert!(filter.keeps(&any, &["dog", "bird"]));
+        assert!(!filter.keeps(&any, &["dog", "Cat"]));
+        assert!(filter.spans(&any, 0, "dog").is_empty());
+        assert_eq!(filter.describe(), "!\"cat\"");
+
+        let error = Filter::new("(a", true).unwrap_err();
+        assert_eq!(error, "error: unclosed group");
+    }
+
+    #[test]
+    fn clauses_are_scoped_and_combined() {
+        let table = variables(&["s", "name"]);
+        let filter = Filter::new("?name: smith 1:/^<http/", false).unwrap();
+        assert_eq!(filter.describe(), "?name:\"smith\" 1:/^<http/");
+        assert!(filter.keeps(&table, &["<http://ex/a>", "\"Smith\""]));
+        assert!(!filter.keeps(&table, &["<http://ex/smith>", "\"Jones\""]));
+        assert!(!filter.keeps(&table, &["_:b", "\"Smith\""]));
+        assert_eq!(filter.spans(&table, 1, "\"Smith\" smith"), [1..6, 8..13]);
+        assert!(filter.spans(&table, 1, "<http").is_empty());
+
+        // 輸入行為正則時，未標明之子句亦為正則
+        let filter = Filter::new("2:^\"S !?s:b>", true).unwrap();
+        assert_eq!(filter.describe(), "2:/^\"S/ !?s:/b>/");
+        assert!(filter.keeps(&table, &["<http://ex/a>", "\"Smith\""]));
+        assert!(!filter.keeps(&table, &["<http://ex/b>", "\"Smith\""]));
+
+        let other = variables(&["x"]);
+        assert_eq!(filter.check(&table), Ok(()));
+        assert_eq!(filter.check(&other), Err("no column 2".to_string()));
+        assert!(!filter.keeps(&other, &["\"Smith\""]));
+    }
+
+    #[test]
+    fn quotes_keep_spaces_and_mistakes_are_reported() {
+        let table = variables(&["name"]);
+        for text in [r#"?name: "john smith""#, r#"?name:"john smith""#] {
+            let filter = Filter::new(text, false).unwrap();
+            assert_eq!(filter.describe(), "?name:\"john smith\"");
+            assert!(filter.keeps(&table, &["\"John Smith\""]));
+            assert!(!filter.keeps(&table, &["\"john\" \"smith\""]));
+        }
+        // 引號內不認前綴、`!` 與 `/…/`
+        let filter = Filter::new(r#""!2:/a \"b\"/""#, false).unwrap();
+        assert_eq!(filter.describe(), r#""!2:/a "b"/""#);
+        assert!(filter.keeps(&table, &[r#"x !2:/A "B"/"#]));
+
+        let error = |text| Filter::new(text, false).unwrap_err();
+        assert_eq!(error("?: x"), "missing variable name in `?:`");
+        assert_eq!(error("?name:"), "missing value after `?name:`");
+        assert_eq!(error("0: x"), "columns are numbered from 1, not `0`");
+        assert_eq!(error("\"open"), "unclosed quote");
+        assert_eq!(error("   "), "empty filter");
+        assert_eq!(
+            Filter::new("?nope: x", false).unwrap().check(&table),
+            Err("no variable ?nope".to_string())
+        );
+        // 網址中之冒號非前綴
+        assert!(Filter::new("http://ex", false)
+            .unwrap()
+            .keeps(&table, &["<http://ex/a>"]));
+    }
+}
@@ -0,0 +1,371 @@
+//! 測試與基準所用之合成數據。依種子與 [`Spec`] 確定而生，同者每次、每平台皆同，故基準之
+//! 輸入可重現；規格僅數個數目，失敗時可以 [`minimize`] 縮之，得最小之形狀。
+//!
+//! 唯 `testutil` 特性或本庫之測試中有之。
+
+use oxigraph::{
+    io::{RdfFormat, RdfSerializer},
+    model::{BlankNode, GraphName, Literal, NamedNode, Quad, Subject, Term},
+    store::Store,
+};
+
+/// 所生之 IRI 皆在此下。
+pub const NAMESPACE: &str = "http://example.org/fixture/";
+
+/// 標籤之語言及其詞，依次取前 `languages` 種。
+const LABELS: &[(&str, &str)] = &[
+    ("en", "item"),
+    ("de", "Ding"),
+    ("fr", "objet"),
+    ("zh", "物件"),
+    ("ja", "もの"),
+    ("ar", "عنصر"),
+];
+
+/// 長字面量所用之詞。
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel",
+];
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_CLASS: &str = "http://www.w3.org/2000/01/rdf-schema#Class";
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+const RDFS_SUBCLASS: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+
+/// 數據之形狀。類成一二叉樹，`Class0` 為根；各類有 `instances` 個實例。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spec {
+    pub classes: usize,
+    /// 每類之實例數。
+    pub instances: usize,
+    /// 每實例之字面量屬性數，值雜以整數、小數與字串。
+    pub properties: usize,
+    /// 每實例指向他實例之 `link` 數。
+    pub links: usize,
+    /// 每實例之標籤之語言數，至多 `LABELS` 之數。
+    pub languages: usize,
+    /// 每實例之 `description` 之字符數；零則無。
+    pub literal_length: usize,
+    /// 每實例有一 `address` 空白節點，其下二屬性。
+    pub blank_nodes: bool,
+    /// 實例輪流置於此數之命名圖；零則皆在默認圖。類之三元組恆在默認圖。
+    pub graphs: usize,
+}
+
+impl Default for Spec {
+    fn default() -> Self {
+        Self {
+            classes: 3,
+            instances: 10,
+            properties: 2,
+            links: 1,
+            languages: 1,
+            literal_length: 0,
+            blank_nodes: false,
+            graphs: 0,
+        }
+    }
+}
+
+impl Spec {
+    /// 所生之四元組，逐實例生之，故大數據不先佔內存。
+    pub fn quads(&self, seed: u64) -> impl Iterator<Item = Quad> + '_ {
+        let classes = (0..self.classes).flat_map(|class| self.class(class));
+        let instances =
+            (0..self.classes * self.instances).flat_map(move |index| self.instance(seed, index));
+        classes.chain(instances)
+    }
+
+    /// 載入存儲。
+    pub fn load(&self, seed: u64, store: &Store) -> anyhow::Result<()> {
+        store.bulk_loader().load_quads(self.quads(seed))?;
+        Ok(())
+    }
+
+    /// 寫為 Turtle；有命名圖者寫為 TriG。
+    pub fn turtle(&self, seed: u64) -> String {
+        let format = match self.graphs {
+            0 => RdfFormat::Turtle,
+            _ => RdfFormat::TriG,
+        };
+        let mut writer = RdfSerializer::from_format(format).serialize_to_write(Vec::new());
+        for quad in self.quads(seed) {
+            writer
+                .write_quad(&quad)
+                .expect("writing to memory cannot fail");
+        }
+        let bytes = writer.finish().expect("writing to memory cannot fail");
+        String::from_utf8(bytes).expect("serializers write UTF-8")
+    }
+
+    /// 較小之規格，先大刀後小步：各數減半或減一，至其最小值止；開關關之。已最小者無。
+    pub fn shrink(&self) -> Vec<Spec> {
+        let mut smaller = vec![];
+        let counts: [(Field, usize); 7] = [
+            (|spec| &mut spec.classes, 1),
+            (|spec| &mut spec.instances, 1),
+            (|spec| &mut spec.properties, 0),
+            (|spec| &mut spec.links, 0),
+            (|spec| &mut spec.languages, 0),
+            (|spec| &mut spec.literal_length, 0),
+            (|spec| &mut spec.graphs, 0),
+        ];
+        for (field, least) in counts {
+            let value = *field(&mut self.clone());
+            let mut candidates = vec![least.max(value / 2), value.saturating_sub(1).max(least)];
+            candidates.dedup();
+            for candidate in candidates.into_iter().filter(|&c| c < value) {
+                let mut spec = self.clone();
+                *field(&mut spec) = candidate;
+                smaller.push(spec);
+            }
+        }
+        if self.blank_nodes {
+            smaller.push(Spec {
+                blank_nodes: false,
+                ..self.clone()
+            });
+        }
+        smaller
+    }
+
+    /// 類之三元組：類型、標籤，及其上級。
+    fn class(&self, class: usize) -> Vec<Quad> {
+        let iri = class_iri(class);
+        let mut quads = vec![
+            quad(
+                iri.clone(),
+                RDF_TYPE,
+                named(RDFS_CLASS),
+                GraphName::DefaultGraph,
+            ),
+            quad(
+                iri.clone(),
+                RDFS_LABEL,
+                Literal::new_simple_literal(format!("Class {class}")).into(),
+                GraphName::DefaultGraph,
+            ),
+        ];
+        if class > 0 {
+            quads.push(quad(
+                iri,
+                RDFS_SUBCLASS,
+                class_iri((class - 1) / 2).into(),
+                GraphName::DefaultGraph,
+            ));
+        }
+        quads
+    }
+
+    /// 第 `index` 個實例之三元組。各實例之隨機數自其序與種子起，故可單獨生之。
+    fn instance(&self, seed: u64, index: usize) -> Vec<Quad> {
+        let mut rng = SplitMix(seed ^ (index as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let class = index / self.instances.max(1);
+        let subject = instance_iri(index);
+        let graph = match self.graphs {
+            0 => GraphName::DefaultGraph,
+            graphs => {
+                NamedNode::new_unchecked(format!("{NAMESPACE}graph{}", index % graphs)).into()
+            }
+        };
+        let triple =
+            |predicate: &str, object: Term| quad(subject.clone(), predicate, object, graph.clone());
+        let mut quads = vec![triple(RDF_TYPE, class_iri(class).into())];
+        for (language, word) in LABELS.iter().take(self.languages) {
+            let label = format!("{word} {class}-{}", index % self.instances.max(1));
+            quads.push(triple(
+                RDFS_LABEL,
+                Literal::new_language_tagged_literal_unchecked(label, *language).into(),
+            ));
+        }
+        for property in 0..self.properties {
+            let value = match rng.below(3) {
+                0 => Literal::from(rng.next() as i64 % 1_000_000),
+                1 => Literal::new_typed_literal(
+                    format!("{}.{:02}", rng.below(1000), rng.below(100)),
+                    NamedNode::new_unchecked(XSD_DECIMAL),
+                ),
+                _ => Literal::new_simple_literal(format!("value {}", rng.below(10_000))),
+            };
+            quads.push(triple(&format!("{NAMESPACE}p{property}"), value.into()));
+        }
+        let total = self.classes * self.instances;
+        for _ in 0..self.links {
+            let target = instance_iri(rng.below(total as u64) as usize);
+            quads.push(triple(&format!("{NAMESPACE}link"), target.into()));
+        }
+        if self.literal_length > 0 {
+            quads.push(triple(
+                &format!("{NAMESPACE}description"),
+                Literal::new_simple_literal(sentence(&mut rng, self.literal_length)).into(),
+            ));
+        }
+        if self.blank_nodes {
+            let address = BlankNode::new_unchecked(format!("address{index}"));
+            quads.push(triple(
+                &format!("{NAMESPACE}address"),
+                address.clone().into(),
+            ));
+            quads.push(quad(
+                address.clone(),
+                &format!("{NAMESPACE}street"),
+                Literal::new_simple_literal(format!("{} street", WORDS[rng.below(8) as usize]))
+                    .into(),
+                graph.clone(),
+            ));
+            quads.push(quad(
+                address,
+                &format!("{NAMESPACE}number"),
+                Literal::from(rng.below(200) as i64 + 1).into(),
+                graph.clone(),
+            ));
+        }
+        quads
+    }
+}
+
+/// 規格之一數目。
+type Field = fn(&mut Spec) -> &mut usize;
+
+/// 縮 `spec` 至仍使 `fails` 為真之最小者：逐次取首個仍失敗之較小規格，至無可縮。
+pub fn minimize(spec: Spec, mut fails: impl FnMut(&Spec) -> bool) -> Spec {
+    let mut spec = spec;
+    while let Some(smaller) = spec.shrink().into_iter().find(|smaller| fails(smaller)) {
+        spec = smaller;
+    }
+    spec
+}
+
+fn class_iri(class: usize) -> NamedNode {
+    NamedNode::new_unchecked(format!("{NAMESPACE}Class{class}"))
+}
+
+fn instance_iri(index: usize) -> NamedNode {
+    NamedNode::new_unchecked(format!("{NAMESPACE}i{index}"))
+}
+
+fn named(iri: &str) -> Term {
+    NamedNode::new_unchecked(iri).into()
+}
+
+fn quad(subject: impl Into<Subject>, predicate: &str, object: Term, graph: GraphName) -> Quad {
+    Quad::new(subject, NamedNode::new_unchecked(predicate), object, graph)
+}
+
+/// 由 `WORDS` 隨機成句，恰 `length` 字符。
+fn sentence(rng: &mut SplitMix, length: usize) -> String {
+    let mut text = String::with_capacity(length + 8);
+    while text.len() < length {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(WORDS[rng.below(WORDS.len() as u64) as usize]);
+    }
+    text.truncate(length);
+    text
+}
+
+/// SplitMix64：小而確定，不依他庫之版本，同種子處處所得皆同。
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// `0..bound` 中之一數。`bound` 為零則為零。
+    fn below(&mut self, bound: u64) -> u64 {
+        match bound {
+            0 => 0,
+            bound => self.next() % bound,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::RdfParser;
+
+    #[test]
+    fn the_same_seed_and_spec_give_the_same_data() {
+        let spec = Spec {
+            literal_length: 40,
+            blank_nodes: true,
+            graphs: 2,
+            languages: 3,
+            ..Spec::default()
+        };
+        let quads: Vec<Quad> = spec.quads(7).collect();
+        assert_eq!(quads, spec.quads(7).collect::<Vec<_>>());
+        assert_ne!(quads, spec.quads(8).collect::<Vec<_>>());
+        // 類 3 × 實例 10：類型、三標籤、二屬性、一鏈、描述、地址及其二屬性
+        assert_eq!(quads.len(), 2 + 3 * 2 + 30 * 11);
+
+        let store = Store::new().unwrap();
+        spec.load(7, &store).unwrap();
+        assert_eq!(store.len().unwrap(), quads.len());
+        assert_eq!(store.named_graphs().count(), 2);
+        let reread = Store::new().unwrap();
+        reread
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::TriG),
+                spec.turtle(7).as_bytes(),
+            )
+            .unwrap();
+        assert_eq!(reread.len().unwrap(), quads.len());
+        let description = quads
+            .iter()
+            .find(|quad| quad.predicate.as_str().ends_with("description"))
+            .unwrap();
+        let Term::Literal(description) = &description.object else {
+            panic!("not a literal");
+        };
+        assert_eq!(description.value().len(), 40);
+    }
+
+    #[test]
+    fn failing_shapes_shrink_to_the_smallest_that_still_fails() {
+        // 實例逾五且有空白節點者「失敗」
+        let fails = |spec: &Spec| spec.classes * spec.instances > 5 && spec.blank_nodes;
+        let spec = Spec {
+            classes: 8,
+            instances: 40,
+            literal_length: 500,
+            blank_nodes: true,
+            graphs: 3,
+            ..Spec::default()
+        };
+        let smallest = minimize(spec, fails);
+        assert_eq!(
+            smallest,
+            Spec {
+                classes: 1,
+                instances: 6,
+                properties: 0,
+                links: 0,
+                languages: 0,
+                literal_length: 0,
+                blank_nodes: true,
+                graphs: 0,
+            }
+        );
+        assert!(Spec {
+            classes: 1,
+            instances: 1,
+            properties: 0,
+            links: 0,
+            languages: 0,
+            literal_length: 0,
+            blank_nodes: false,
+            graphs: 0,
+        }
+        .shrink()
+        .is_empty());
+    }
+}
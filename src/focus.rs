@@ -0,0 +1,96 @@
+use crate::app::Mode;
+
+/// 按鍵所向之部分。自上而下為查詢、結果表、其下之預覽；側面板代結果表之位，提示行在底。
+///
+/// 日後或增部分，故嵌入者匹配時須留通配分支。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Focus {
+    /// 查詢編輯器，或三元組模式之表單。
+    QueryEditor,
+    /// 結果表。
+    ResultsPrimary,
+    /// 結果表下之預覽窗格。
+    ResultsSecondary,
+    SidePanel(Panel),
+    /// 底部之命令行、過濾或改值之輸入行。
+    Prompt,
+}
+
+/// 代結果表而示之面板。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Panel {
+    Hierarchy,
+    Neighborhood,
+    Bookmarks,
+    Scratchpad,
+    Recent,
+    History,
+}
+
+impl Focus {
+    /// 模式所在之部分。`secondary` 為瀏覽時預覽取焦點。
+    pub fn of(mode: Mode, secondary: bool) -> Self {
+        match mode {
+            Mode::Query | Mode::Pattern => Self::QueryEditor,
+            Mode::Browse if secondary => Self::ResultsSecondary,
+            Mode::Browse => Self::ResultsPrimary,
+            Mode::Hierarchy => Self::SidePanel(Panel::Hierarchy),
+            Mode::Neighborhood => Self::SidePanel(Panel::Neighborhood),
+            Mode::Bookmarks => Self::SidePanel(Panel::Bookmarks),
+            Mode::Scratchpad => Self::SidePanel(Panel::Scratchpad),
+            Mode::Recent => Self::SidePanel(Panel::Recent),
+            Mode::History => Self::SidePanel(Panel::History),
+            Mode::Command | Mode::Filter | Mode::Edit => Self::Prompt,
+        }
+    }
+}
+
+/// 自 `current` 移 `step` 格。`wrap` 則首末相接，否則至首末而止。不在其中則取首。
+pub fn step(panes: &[Focus], current: Focus, step: isize, wrap: bool) -> Focus {
+    let Some(index) = panes.iter().position(|&pane| pane == current) else {
+        return panes.first().copied().unwrap_or(current);
+    };
+    let last = panes.len() as isize - 1;
+    let target = index as isize + step;
+    let target = if wrap {
+        target.rem_euclid(last + 1)
+    } else {
+        target.clamp(0, last)
+    };
+    panes[target as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_stop_at_the_ends_and_cycles_wrap() {
+        let panes = [
+            Focus::QueryEditor,
+            Focus::ResultsPrimary,
+            Focus::ResultsSecondary,
+        ];
+        assert_eq!(
+            step(&panes, Focus::QueryEditor, -1, false),
+            Focus::QueryEditor
+        );
+        assert_eq!(
+            step(&panes, Focus::QueryEditor, 1, false),
+            Focus::ResultsPrimary
+        );
+        assert_eq!(
+            step(&panes, Focus::ResultsSecondary, 1, true),
+            Focus::QueryEditor
+        );
+        assert_eq!(
+            step(&panes, Focus::QueryEditor, -1, true),
+            Focus::ResultsSecondary
+        );
+        assert_eq!(step(&panes, Focus::Prompt, 1, true), Focus::QueryEditor);
+        assert_eq!(Focus::of(Mode::Browse, true), Focus::ResultsSecondary);
+        assert_eq!(Focus::of(Mode::Filter, false), Focus::Prompt);
+    }
+}
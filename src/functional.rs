@@ -0,0 +1,162 @@
+use oxigraph::{
+    model::{Literal, NamedNode, Term},
+    sparql::{EvaluationError, QueryResults, Variable},
+    store::Store,
+};
+
+/// 一主語並列之值數上限，餘者僅計於值數。
+pub const MAX_VALUES: usize = 8;
+
+const FUNCTIONAL_PROPERTY: &str = "<http://www.w3.org/2002/07/owl#FunctionalProperty>";
+
+/// 函數性屬性之檢查：一主語於此謂語有多個相異之值者。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Functional {
+    pub predicate: NamedNode,
+}
+
+/// 違例之主語，每行為主語、值數與並列之各值。
+#[derive(Debug, Default)]
+pub struct Conflicts {
+    pub variables: Vec<Variable>,
+    pub rows: Vec<Vec<Option<Term>>>,
+}
+
+impl Functional {
+    /// 有多值之主語及其各值，依主語排序，以便逐組併之。
+    pub fn query(&self) -> String {
+        let predicate = &self.predicate;
+        format!(
+            "SELECT DISTINCT ?subject ?value WHERE {{ \
+               {{ SELECT ?subject WHERE {{ ?subject {predicate} ?value }} \
+                  GROUP BY ?subject HAVING (COUNT(DISTINCT ?value) > 1) }} \
+               ?subject {predicate} ?value }} ORDER BY ?subject ?value"
+        )
+    }
+
+    /// 查找違例，每主語併為一行。
+    pub fn conflicts(&self, store: &Store) -> Result<Conflicts, EvaluationError> {
+        let QueryResults::Solutions(solutions) = store.query(&self.query())? else {
+            unreachable!("a SELECT query")
+        };
+        let mut groups: Vec<(Term, Vec<Term>)> = vec![];
+        for solution in solutions {
+            let solution = solution?;
+            let (Some(subject), Some(value)) = (solution.get("subject"), solution.get("value"))
+            else {
+                continue;
+            };
+            match groups.last_mut() {
+                Some((last, values)) if last == subject => values.push(value.clone()),
+                _ => groups.push((subject.clone(), vec![value.clone()])),
+            }
+        }
+        let width = groups
+            .iter()
+            .map(|(_, values)| values.len())
+            .max()
+            .unwrap_or(2)
+            .min(MAX_VALUES);
+        let mut variables = vec![
+            Variable::new_unchecked("subject"),
+            Variable::new_unchecked("values"),
+        ];
+        variables.extend((1..=width).map(|n| Variable::new_unchecked(format!("value{n}"))));
+        let rows = groups
+            .into_iter()
+            .map(|(subject, values)| {
+                let count = Term::from(Literal::from(values.len() as i64));
+                let mut row = vec![Some(subject), Some(count)];
+                row.extend(values.into_iter().take(width).map(Some));
+                row.resize(width + 2, None);
+                row
+            })
+            .collect();
+        Ok(Conflicts { variables, rows })
+    }
+}
+
+/// 存儲中是否聲明有函數性屬性。
+pub fn declared(store: &Store) -> Result<bool, EvaluationError> {
+    let query = format!("ASK {{ ?p a {FUNCTIONAL_PROPERTY} }}");
+    match store.query(&query)? {
+        QueryResults::Boolean(found) => Ok(found),
+        _ => unreachable!("an ASK query"),
+    }
+}
+
+/// 各聲明為函數性之屬性一行：有多值之主語數與其值數之和，無違例者亦列。
+pub fn summary_query() -> String {
+    format!(
+        "SELECT ?predicate (SUM(IF(?n > 1, 1, 0)) AS ?subjects) \
+           (SUM(IF(?n > 1, ?n, 0)) AS ?values) WHERE {{\n  \
+           {{ SELECT ?predicate ?subject (COUNT(DISTINCT ?value) AS ?n) WHERE {{ \
+             ?predicate a {FUNCTIONAL_PROPERTY} OPTIONAL {{ ?subject ?predicate ?value }} }} \
+             GROUP BY ?predicate ?subject }}\n\
+         }} GROUP BY ?predicate ORDER BY DESC(?subjects) ?predicate"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::{RdfFormat, RdfParser};
+
+    #[test]
+    fn subjects_with_several_values_line_up_their_values() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::TriG),
+                r#"@prefix ex: <http://ex/> .
+                   ex:born a <http://www.w3.org/2002/07/owl#FunctionalProperty> .
+                   ex:a ex:born 1815, 1816, 1817 . ex:b ex:born 1906 . ex:c ex:born 1900, 1901 .
+                   GRAPH ex:g { ex:b ex:born 1906 }"#
+                    .as_bytes(),
+            )
+            .unwrap();
+        let conflicts = Functional {
+            predicate: NamedNode::new_unchecked("http://ex/born"),
+        }
+        .conflicts(&store)
+        .unwrap();
+        let names: Vec<&str> = conflicts.variables.iter().map(Variable::as_str).collect();
+        assert_eq!(names, ["subject", "values", "value1", "value2", "value3"]);
+        let rows: Vec<Vec<String>> = conflicts
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|term| {
+                        term.as_ref().map_or(String::new(), |t| match t {
+                            Term::Literal(literal) => literal.value().to_string(),
+                            term => term.to_string(),
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ["<http://ex/a>", "3", "1815", "1816", "1817"],
+                ["<http://ex/c>", "2", "1900", "1901", ""],
+            ]
+        );
+
+        assert!(declared(&store).unwrap());
+        let QueryResults::Solutions(summary) = store.query(&summary_query()).unwrap() else {
+            panic!()
+        };
+        let summary: Vec<_> = summary.map(Result::unwrap).collect();
+        assert_eq!(summary.len(), 1);
+        let value = |name: &str| match summary[0].get(name) {
+            Some(Term::Literal(literal)) => literal.value().to_string(),
+            _ => panic!(),
+        };
+        assert_eq!(
+            (value("subjects"), value("values")),
+            ("2".into(), "5".into())
+        );
+    }
+}
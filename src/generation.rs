@@ -0,0 +1,56 @@
+use std::{
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+
+/// 存儲之代數。每次修改存儲皆遞增；緩存記其所算時之代數，不符者即已過時。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Generation(u64);
+
+impl Generation {
+    pub fn bump(&mut self) {
+        self.0 += 1;
+    }
+}
+
+/// 依存儲某代所算之值。
+#[derive(Debug)]
+pub struct Cached<T> {
+    generation: Generation,
+    value: T,
+    /// 算之所耗，定其過時後可否自動重算。
+    took: Duration,
+}
+
+impl<T> Cached<T> {
+    pub fn new(generation: Generation, value: T, took: Duration) -> Self {
+        Self {
+            generation,
+            value,
+            took,
+        }
+    }
+
+    pub fn took(&self) -> Duration {
+        self.took
+    }
+
+    /// 存儲已改於其後。
+    pub fn is_stale(&self, current: Generation) -> bool {
+        self.generation != current
+    }
+}
+
+impl<T> Deref for Cached<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Cached<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{action::GraphOp, display::group_digits};
+use oxigraph::{
+    model::{GraphName, Quad},
+    store::Store,
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+};
+
+/// 每次提交之四元組數。
+const BATCH: usize = 10_000;
+
+impl GraphOp {
+    /// 所改之圖，寫檢查點用。
+    pub fn affected(&self) -> Vec<GraphName> {
+        match self {
+            Self::Copy(_, target) => vec![target.clone()],
+            Self::Move(source, target) => vec![source.clone(), target.clone()],
+            Self::Drop(graph) => vec![graph.clone()],
+        }
+    }
+
+    /// 四元組所出之圖。
+    pub fn source(&self) -> &GraphName {
+        match self {
+            Self::Copy(source, _) | Self::Move(source, _) => source,
+            Self::Drop(graph) => graph,
+        }
+    }
+}
+
+/// 圖名之寫法：`the default graph` 或 `<http://ex/g>`。
+pub fn describe_graph(graph: &GraphName) -> String {
+    match graph {
+        GraphName::DefaultGraph => "the default graph".to_string(),
+        graph => graph.to_string(),
+    }
+}
+
+/// 圖中之四元組數。
+pub fn graph_len(store: &Store, graph: &GraphName) -> usize {
+    store
+        .quads_for_pattern(None, None, None, Some(graph.as_ref()))
+        .count()
+}
+
+/// 後台執行中之圖操作。逐批提交，故大圖亦可示進度。
+pub struct GraphJob {
+    pub op: GraphOp,
+    /// 所出之圖之四元組數。
+    pub total: usize,
+    done: Arc<AtomicUsize>,
+    receiver: Receiver<Result<(), String>>,
+}
+
+impl GraphJob {
+    pub fn spawn(store: &Store, op: GraphOp) -> Self {
+        let total = graph_len(store, op.source());
+        let done = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::channel();
+        let (store, job, progress) = (store.clone(), op.clone(), Arc::clone(&done));
+        thread::spawn(move || {
+            let result = run(&store, &job, &progress).map_err(|error| error.to_string());
+            let _ = sender.send(result);
+        });
+        Self {
+            op,
+            total,
+            done,
+            receiver,
+        }
+    }
+
+    /// 已完成則返回其結果。
+    pub fn poll(&self) -> Option<Result<(), String>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// 已處理之比例。
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.done.load(Ordering::Relaxed) as f64 / self.total as f64).min(1.0)
+    }
+
+    /// 狀態欄之標籤，如 `Copying 20,000 of 1,234,567 quads to <http://ex/g>`。
+    pub fn label(&self) -> String {
+        let progress = format!(
+            "{} of {} quads",
+            group_digits(self.done.load(Ordering::Relaxed)),
+            group_digits(self.total)
+        );
+        match &self.op {
+            GraphOp::Copy(_, target) => format!("Copying {progress} to {}", describe_graph(target)),
+            GraphOp::Move(_, target) => format!("Moving {progress} to {}", describe_graph(target)),
+            GraphOp::Drop(graph) => format!("Dropping {}", describe_graph(graph)),
+        }
+    }
+}
+
+fn run(store: &Store, op: &GraphOp, done: &AtomicUsize) -> anyhow::Result<()> {
+    let (source, target) = match op {
+        GraphOp::Copy(source, target) | GraphOp::Move(source, target) => (source, target),
+        GraphOp::Drop(graph) => return drop_graph(store, graph),
+    };
+    // 同 SPARQL：自複製於己則無所為
+    if source == target {
+        return Ok(());
+    }
+    store.clear_graph(target)?;
+    if let GraphName::NamedNode(target) = target {
+        store.insert_named_graph(target)?;
+    }
+    let mut batch = Vec::with_capacity(BATCH);
+    for quad in store.quads_for_pattern(None, None, None, Some(source.as_ref())) {
+        let quad = quad?;
+        batch.push(Quad::new(
+            quad.subject,
+            quad.predicate,
+            quad.object,
+            target.clone(),
+        ));
+        if batch.len() == BATCH {
+            store.extend(batch.drain(..))?;
+            done.fetch_add(BATCH, Ordering::Relaxed);
+        }
+    }
+    done.fetch_add(batch.len(), Ordering::Relaxed);
+    store.extend(batch)?;
+    if matches!(op, GraphOp::Move(..)) {
+        drop_graph(store, source)?;
+    }
+    Ok(())
+}
+
+fn drop_graph(store: &Store, graph: &GraphName) -> anyhow::Result<()> {
+    match graph {
+        GraphName::NamedNode(graph) => {
+            store.remove_named_graph(graph)?;
+        }
+        graph => store.clear_graph(graph)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::{RdfFormat, RdfParser};
+    use oxigraph::model::NamedNode;
+
+    fn finish(store: &Store, op: GraphOp) {
+        let job = GraphJob::spawn(store, op);
+        loop {
+            if let Some(result) = job.poll() {
+                return result.unwrap();
+            }
+            thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn graphs_copy_move_and_drop_like_sparql() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::TriG),
+                "<http://ex/a> <http://ex/p> 1, 2 . <http://ex/g> { <http://ex/b> <http://ex/p> 3 }"
+                    .as_bytes(),
+            )
+            .unwrap();
+        let g = GraphName::from(NamedNode::new_unchecked("http://ex/g"));
+        let h = GraphName::from(NamedNode::new_unchecked("http://ex/h"));
+        finish(&store, GraphOp::Copy(GraphName::DefaultGraph, g.clone()));
+        assert_eq!(graph_len(&store, &g), 2);
+        assert_eq!(graph_len(&store, &GraphName::DefaultGraph), 2);
+
+        finish(&store, GraphOp::Move(g.clone(), h.clone()));
+        assert_eq!(graph_len(&store, &h), 2);
+        assert_eq!(graph_len(&store, &g), 0);
+        let named = |graph: &str| {
+            store
+                .contains_named_graph(NamedNode::new_unchecked(graph).as_ref())
+                .unwrap()
+        };
+        assert!(!named("http://ex/g"));
+
+        finish(&store, GraphOp::Drop(h));
+        assert!(!named("http://ex/h"));
+        assert_eq!(store.len().unwrap(), 2);
+    }
+}
@@ -0,0 +1,153 @@
+use spargebra::{
+    algebra::GraphPattern,
+    term::{NamedNodePattern, TermPattern, TriplePattern},
+    Query,
+};
+
+/// 無顯示上限時所加之 LIMIT。
+pub const DEFAULT_LIMIT: usize = 5000;
+
+/// 無 LIMIT 而或返回巨量之行之 SELECT，加 `LIMIT limit` 而返回其文本；否則無。
+/// 巨量者：獨一全為變量之三元組模式，或無共有變量之模式之交叉積。聚合者不計。
+pub fn guard(text: &str, limit: usize) -> Option<String> {
+    let Ok(Query::Select {
+        dataset,
+        pattern,
+        base_iri,
+    }) = Query::parse(text, None)
+    else {
+        return None;
+    };
+    if matches!(pattern, GraphPattern::Slice { .. }) || !explosive(&pattern) {
+        return None;
+    }
+    let pattern = GraphPattern::Slice {
+        inner: Box::new(pattern),
+        start: 0,
+        length: Some(limit),
+    };
+    Some(
+        Query::Select {
+            dataset,
+            pattern,
+            base_iri,
+        }
+        .to_string(),
+    )
+}
+
+fn explosive(pattern: &GraphPattern) -> bool {
+    match pattern {
+        GraphPattern::Bgp { patterns } => {
+            matches!(&patterns[..], [triple] if open(triple)) || components(patterns) > 1
+        }
+        GraphPattern::Join { left, right } => {
+            explosive(left) || explosive(right) || disjoint(left, right)
+        }
+        GraphPattern::Union { left, right } => explosive(left) || explosive(right),
+        GraphPattern::LeftJoin { left, .. } | GraphPattern::Minus { left, .. } => explosive(left),
+        GraphPattern::Filter { inner, .. }
+        | GraphPattern::Graph { inner, .. }
+        | GraphPattern::Extend { inner, .. }
+        | GraphPattern::OrderBy { inner, .. }
+        | GraphPattern::Project { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner } => explosive(inner),
+        _ => false,
+    }
+}
+
+/// 三元組模式之三位皆為變量。
+fn open(triple: &TriplePattern) -> bool {
+    variable(&triple.subject)
+        && matches!(triple.predicate, NamedNodePattern::Variable(_))
+        && variable(&triple.object)
+}
+
+fn variable(term: &TermPattern) -> bool {
+    matches!(term, TermPattern::Variable(_) | TermPattern::BlankNode(_))
+}
+
+/// 三元組模式之變量，空白節點亦算。
+fn names(triple: &TriplePattern) -> Vec<String> {
+    let mut names = vec![];
+    for term in [&triple.subject, &triple.object] {
+        match term {
+            TermPattern::Variable(variable) => names.push(variable.to_string()),
+            TermPattern::BlankNode(node) => names.push(node.to_string()),
+            _ => {}
+        }
+    }
+    if let NamedNodePattern::Variable(variable) = &triple.predicate {
+        names.push(variable.to_string());
+    }
+    names
+}
+
+/// 有變量之三元組模式，以共有變量相連，所成之組數。
+fn components(patterns: &[TriplePattern]) -> usize {
+    let mut groups: Vec<Vec<String>> = vec![];
+    for triple in patterns {
+        let names = names(triple);
+        if names.is_empty() {
+            continue;
+        }
+        let (joined, mut rest): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|group| group.iter().any(|name| names.contains(name)));
+        let mut merged: Vec<String> = joined.into_iter().flatten().collect();
+        merged.extend(names);
+        rest.push(merged);
+        groups = rest;
+    }
+    groups.len()
+}
+
+/// 兩側皆有變量而無共有者。
+fn disjoint(left: &GraphPattern, right: &GraphPattern) -> bool {
+    let mut names = vec![];
+    left.on_in_scope_variable(|variable| names.push(variable.clone()));
+    let (mut any, mut shared) = (false, false);
+    right.on_in_scope_variable(|variable| {
+        any = true;
+        shared |= names.contains(variable);
+    });
+    !names.is_empty() && any && !shared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unbounded_explosive_selects_are_limited() {
+        let guarded = guard("SELECT * WHERE { ?s ?p ?o }", 10).unwrap();
+        assert!(guarded.ends_with("LIMIT 10"), "{guarded}");
+        assert!(Query::parse(&guarded, None).is_ok());
+        // 無共有變量之交叉積
+        assert!(guard(
+            "SELECT * WHERE { ?a <http://ex/p> ?b . ?c <http://ex/q> ?d }",
+            10
+        )
+        .is_some());
+        assert!(guard(
+            "SELECT * WHERE { { ?a <http://ex/p> ?b } { SELECT ?c WHERE { ?c a ?t } } }",
+            10
+        )
+        .is_some());
+        assert!(guard("SELECT ?s WHERE { ?s ?p ?o } ORDER BY ?s", 10).is_some());
+
+        for text in [
+            "SELECT * WHERE { ?s ?p ?o } LIMIT 5",
+            "SELECT * WHERE { ?s ?p ?o } OFFSET 5",
+            "SELECT * WHERE { ?s <http://ex/p> ?o }",
+            "SELECT * WHERE { ?s ?p ?o . ?o a <http://ex/C> }",
+            "SELECT (COUNT(*) AS ?n) WHERE { ?s ?p ?o }",
+            "ASK { ?s ?p ?o }",
+            "CONSTRUCT WHERE { ?s ?p ?o }",
+            "SELECT",
+        ] {
+            assert_eq!(guard(text, 10), None, "{text}");
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use crate::aggregate::numeric;
+use oxigraph::model::Term;
+use spargebra::{algebra::GraphPattern, Query};
+use std::{cmp::Ordering, ops::Deref};
+
+/// 排序之向。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+impl Order {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Ascending => Self::Descending,
+            Self::Descending => Self::Ascending,
+        }
+    }
+
+    /// 表頭之標記。
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Self::Ascending => "▲",
+            Self::Descending => "▼",
+        }
+    }
+}
+
+/// 諸排序之鍵之字段，如 `2+,0-`：列之序，`+` 為升，`-` 為降。
+pub fn sort_to_field(keys: &[(usize, Order)]) -> String {
+    keys.iter()
+        .map(|(column, order)| match order {
+            Order::Ascending => format!("{column}+"),
+            Order::Descending => format!("{column}-"),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 解析 [`sort_to_field`] 所寫者。空則無鍵。
+pub fn sort_from_field(field: &str) -> Option<Vec<(usize, Order)>> {
+    field
+        .split(',')
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            let order = match key.chars().last()? {
+                '+' => Order::Ascending,
+                '-' => Order::Descending,
+                _ => return None,
+            };
+            Some((key[..key.len() - 1].parse().ok()?, order))
+        })
+        .collect()
+}
+
+/// 兩格之序，如 SPARQL 之 `ORDER BY`：未綁定、空白節點、IRI、字面量、引用三元組依次。
+/// 數值字面量依其值，先於他字面量；餘者依其文。值等者依其全文，如 `1` 與 `1.0`，
+/// 故唯同一之項相等，所排之序不隨原序。
+pub fn compare(a: Option<&Term>, b: Option<&Term>) -> Ordering {
+    let rank = |term: Option<&Term>| match term {
+        None => 0,
+        Some(Term::BlankNode(_)) => 1,
+        Some(Term::NamedNode(_)) => 2,
+        Some(Term::Literal(_)) => 3,
+        Some(Term::Triple(_)) => 4,
+    };
+    match (a, b) {
+        (Some(a), Some(b)) if rank(Some(a)) == rank(Some(b)) => match (numeric(a), numeric(b)) {
+            (Some(x), Some(y)) => x
+                .partial_cmp(&y)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.to_string().cmp(&b.to_string())),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => match (a, b) {
+                (Term::Literal(x), Term::Literal(y)) => x
+                    .value()
+                    .cmp(y.value())
+                    .then_with(|| a.to_string().cmp(&b.to_string())),
+                _ => a.to_string().cmp(&b.to_string()),
+            },
+        },
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// 兩行之序：逐列依 [`compare`]。
+pub fn compare_rows<T: Deref<Target = Term>>(a: &[Option<T>], b: &[Option<T>]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| compare(x.as_deref(), y.as_deref()))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// 查詢自有 `ORDER BY` 否。不能解析者，如用未聲明之前綴，按文字查之。
+pub fn ordered(query: &str) -> bool {
+    fn ordered(pattern: &GraphPattern) -> bool {
+        match pattern {
+            GraphPattern::OrderBy { .. } => true,
+            GraphPattern::Slice { inner, .. }
+            | GraphPattern::Project { inner, .. }
+            | GraphPattern::Distinct { inner }
+            | GraphPattern::Reduced { inner } => ordered(inner),
+            _ => false,
+        }
+    }
+    match Query::parse(query, None) {
+        Ok(Query::Select { pattern, .. }) => ordered(&pattern),
+        Ok(_) => false,
+        Err(_) => {
+            let words: Vec<String> = query
+                .split_whitespace()
+                .map(str::to_ascii_uppercase)
+                .collect();
+            words.windows(2).any(|pair| pair == ["ORDER", "BY"])
+        }
+    }
+}
+
+/// SELECT 中 `(表達式 AS ?name)` 之表達式，按文字查之，註釋與字符串中者不計。子查詢或
+/// `BIND` 所算者亦得之；僅投影之變量、`SELECT *` 所出者無。
+pub fn projection(query: &str, name: &str) -> Option<String> {
+    let masked = mask(query);
+    for sigil in ['?', '$'] {
+        let variable = format!("{sigil}{name}");
+        for (at, _) in masked.match_indices(&variable) {
+            let after = &masked[at + variable.len()..];
+            if after.starts_with(|ch: char| ch.is_alphanumeric() || ch == '_')
+                || !after.trim_start().starts_with(')')
+            {
+                continue;
+            }
+            let before = masked[..at].trim_end();
+            let Some(head) = before
+                .len()
+                .checked_sub(2)
+                .filter(|&cut| before.is_char_boundary(cut))
+                .filter(|&cut| before[cut..].eq_ignore_ascii_case("as"))
+                .map(|cut| &before[..cut])
+                .filter(|head| head.ends_with(|ch: char| ch.is_whitespace() || ch == ')'))
+            else {
+                continue;
+            };
+            // 自 AS 前向回找其開括號
+            let mut depth = 0;
+            for (open, ch) in head.char_indices().rev() {
+                match ch {
+                    ')' => depth += 1,
+                    '(' if depth == 0 => {
+                        return Some(query[open + 1..head.len()].trim().to_string())
+                    }
+                    '(' => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 註釋、字符串與 IRI 之字符代為等長之空格，餘者不變，故位置仍與原文相應。
+pub fn mask(query: &str) -> String {
+    let mut masked = String::with_capacity(query.len());
+    let mut chars = query.char_indices().peekable();
+    let blank = |masked: &mut String, text: &str| {
+        masked.extend(std::iter::repeat_n(' ', text.len()));
+    };
+    while let Some((start, ch)) = chars.next() {
+        let rest = &query[start..];
+        let end = match ch {
+            '#' => rest.find('\n').unwrap_or(rest.len()),
+            '"' | '\'' => {
+                let quote = if rest[1..].starts_with(&format!("{ch}{ch}")) {
+                    &rest[..3]
+                } else {
+                    &rest[..1]
+                };
+                let mut end = rest.len();
+                let mut escaped = false;
+                for (offset, ch) in rest[quote.len()..].char_indices() {
+                    match ch {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        _ if rest[quote.len() + offset..].starts_with(quote) => {
+                            end = quote.len() + offset + quote.len();
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                end
+            }
+            // 其中無空白者為 IRI，餘者為小於號
+            '<' => match rest.find('>') {
+                Some(close) if !rest[..close].contains(char::is_whitespace) => close + 1,
+                _ => {
+                    masked.push(ch);
+                    continue;
+                }
+            },
+            _ => {
+                masked.push(ch);
+                continue;
+            }
+        };
+        blank(&mut masked, &rest[..end]);
+        while chars.peek().is_some_and(|(next, _)| *next < start + end) {
+            chars.next();
+        }
+    }
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{vocab::xsd, BlankNode, Literal, NamedNode};
+
+    #[test]
+    fn cells_order_like_sparql_and_expressions_are_found() {
+        let number =
+            |value: &str| Some(Term::from(Literal::new_typed_literal(value, xsd::INTEGER)));
+        let mut cells = [
+            Some(Term::from(Literal::new_simple_literal("b"))),
+            number("10"),
+            Some(Term::from(NamedNode::new_unchecked("http://ex/a"))),
+            None,
+            number("9"),
+            Some(Term::from(BlankNode::new_unchecked("x"))),
+        ];
+        cells.sort_by(|a, b| compare(a.as_ref(), b.as_ref()));
+        let texts: Vec<String> = cells
+            .iter()
+            .map(|cell| cell.as_ref().map_or("-".to_string(), Term::to_string))
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                "-",
+                "_:x",
+                "<http://ex/a>",
+                "\"9\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+                "\"10\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+                "\"b\"",
+            ]
+        );
+
+        let query =
+            "SELECT ?s (COUNT(DISTINCT ?o) as ?n) (STR(?s) AS $name) { ?s ?p ?o } GROUP BY ?s";
+        assert_eq!(
+            projection(query, "n").as_deref(),
+            Some("COUNT(DISTINCT ?o)")
+        );
+        assert_eq!(projection(query, "name").as_deref(), Some("STR(?s)"));
+        assert_eq!(projection(query, "s"), None);
+        assert_eq!(projection(query, "o"), None);
+        // 註釋與字符串中之括號不計；子查詢所算者亦得之
+        let query = "SELECT ?label ?total # (1 AS ?label)\n\
+                     { { SELECT (SUM(?n) AS ?total) { ?s <http://ex/#n> ?n } } \
+                     BIND(CONCAT(\"(\", STR(?total), \" AS ?label)\") AS ?label) }";
+        assert_eq!(
+            projection(query, "label").as_deref(),
+            Some("CONCAT(\"(\", STR(?total), \" AS ?label)\")")
+        );
+        assert_eq!(projection(query, "total").as_deref(), Some("SUM(?n)"));
+        assert_eq!(projection("SELECT * { ?s ?p ?o }", "s"), None);
+        assert_eq!(
+            mask("a < b && <http://x#y> \"é\""),
+            "a < b &&                  "
+        );
+    }
+
+    #[test]
+    fn rows_order_canonically_and_queries_with_order_by_are_found() {
+        let iri = |iri: &str| Some(Term::from(NamedNode::new_unchecked(iri)));
+        let typed =
+            |value: &str, datatype| Some(Term::from(Literal::new_typed_literal(value, datatype)));
+        let tagged = |value: &str, language: &str| {
+            Some(Term::from(
+                Literal::new_language_tagged_literal(value, language).unwrap(),
+            ))
+        };
+        let mut rows = vec![
+            vec![tagged("chat", "fr"), None],
+            vec![typed("1.0", xsd::DECIMAL), iri("http://ex/b")],
+            vec![tagged("chat", "en"), None],
+            vec![iri("http://ex/a"), typed("2024-01-01", xsd::DATE)],
+            vec![typed("1", xsd::INTEGER), iri("http://ex/b")],
+            vec![Some(Term::from(BlankNode::new_unchecked("b"))), None],
+            vec![iri("http://ex/a"), iri("http://ex/c")],
+            vec![Some(Term::from(Literal::new_simple_literal("chat"))), None],
+        ];
+        let texts = |rows: &[Vec<Option<Term>>]| -> Vec<String> {
+            rows.iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| cell.as_ref().map_or("-".to_string(), Term::to_string))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect()
+        };
+        let mut shuffled = rows.clone();
+        shuffled.reverse();
+        let sort = |rows: &mut Vec<Vec<Option<Term>>>| {
+            rows.sort_by(|a, b| {
+                let (a, b): (Vec<_>, Vec<_>) = (
+                    a.iter().map(Option::as_ref).collect(),
+                    b.iter().map(Option::as_ref).collect(),
+                );
+                compare_rows(&a, &b)
+            })
+        };
+        sort(&mut rows);
+        sort(&mut shuffled);
+        // 原序不同，所排者同
+        assert_eq!(texts(&rows), texts(&shuffled));
+        assert_eq!(
+            texts(&rows),
+            [
+                "_:b -",
+                "<http://ex/a> <http://ex/c>",
+                "<http://ex/a> \"2024-01-01\"^^<http://www.w3.org/2001/XMLSchema#date>",
+                "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer> <http://ex/b>",
+                "\"1.0\"^^<http://www.w3.org/2001/XMLSchema#decimal> <http://ex/b>",
+                "\"chat\" -",
+                "\"chat\"@en -",
+                "\"chat\"@fr -",
+            ]
+        );
+
+        assert!(ordered("SELECT * { ?s ?p ?o } ORDER BY ?s LIMIT 10"));
+        assert!(ordered("SELECT DISTINCT ?s { ?s ?p ?o } ORDER BY DESC(?s)"));
+        assert!(!ordered("SELECT * { ?s ?p ?o }"));
+        // 唯一之子查詢之序，外層照傳
+        assert!(ordered(
+            "SELECT * { { SELECT ?s { ?s ?p ?o } ORDER BY ?s } }"
+        ));
+        assert!(!ordered(
+            "SELECT * { ?s ?p ?o { SELECT ?s { ?s ?p ?o } ORDER BY ?s } }"
+        ));
+        assert!(ordered("SELECT * { ?s a ex:C } order  by ?s"));
+        assert!(!ordered("SELECT * { ?s a ex:C }"));
+    }
+}
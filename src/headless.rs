@@ -0,0 +1,397 @@
+use crate::{
+    action::{ExportFormat, Values},
+    display::group_digits,
+    download::{self, fetch, Progress},
+    export::{plain_header, plain_row, write_record, Flattened},
+    loader::{load_data, load_file},
+    markup,
+    memory::abbreviate,
+    saved::results_format,
+    service::query_options,
+    sink::Sink,
+};
+use anyhow::Context;
+use oxigraph::{
+    io::{RdfFormat, RdfSerializer},
+    model::{Term, Variable},
+    sparql::{
+        results::{QueryResultsFormat, QueryResultsSerializer},
+        Query, QueryResults, QuerySolutionIter,
+    },
+    store::Store,
+};
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::PathBuf,
+    sync::{atomic::AtomicU64, mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+pub use crate::memory::parse_size;
+
+/// 無界面之執行：載入數據，執行一查詢，結果寫出後即退出。
+///
+/// 默認行為 TSV；ASK 為 TSV 之布爾值；CONSTRUCT 與 DESCRIBE 為 N-Triples。
+pub struct Headless {
+    pub paths: Vec<PathBuf>,
+    /// 命令行所給之數據。
+    pub data: Vec<String>,
+    pub format: RdfFormat,
+    pub query: String,
+    pub strict_n3: bool,
+    pub federated: bool,
+    /// 遠端請求之時限。
+    pub timeout: Duration,
+    /// 查詢之時限。逾時則棄之，已寫者不收回。
+    pub deadline: Option<Duration>,
+    /// 寫出之行數上限。
+    pub limit: Option<usize>,
+    /// TSV 之值原樣或為平值。
+    pub values: Values,
+    /// 平值失數據類型、語言或空節點之別者，不於標準錯誤報之。
+    pub quiet: bool,
+    /// 文件逾此字節數則不載入於內存。本版無磁盤上之存儲，故即止，免耗盡內存。
+    pub prefer_disk_above: Option<u64>,
+    /// 路徑為 URL 者之下載。
+    pub download: download::Options,
+    /// 結果之格式：SPARQL 結果格式，或 CONSTRUCT 之 RDF 格式。`None` 則如上。
+    pub output: Option<ExportFormat>,
+    /// 結果之去處。`None` 則寫於所給之輸出。
+    pub sink: Option<Sink>,
+}
+
+/// 查詢之結局。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Rows(usize),
+    Boolean(bool),
+    Triples(usize),
+}
+
+impl Outcome {
+    /// 退出碼：ASK 為假者為 1，`fail_if_empty` 則無行者亦然；餘為 0。
+    pub fn code(self, fail_if_empty: bool) -> u8 {
+        match self {
+            Self::Boolean(false) => 1,
+            Self::Rows(0) | Self::Triples(0) if fail_if_empty => 1,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |count: usize, noun: &str| {
+            let s = if count == 1 { "" } else { "s" };
+            format!("{} {noun}{s}", group_digits(count))
+        };
+        match self {
+            Self::Rows(count) => f.write_str(&plural(*count, "row")),
+            Self::Boolean(value) => write!(f, "{value}"),
+            Self::Triples(count) => f.write_str(&plural(*count, "triple")),
+        }
+    }
+}
+
+/// 無界面執行之失敗，按其因分退出碼。
+#[derive(Debug)]
+pub enum Failure {
+    /// 文件或數據載入失敗。
+    Load(anyhow::Error),
+    /// 查詢語法錯誤。
+    Syntax(anyhow::Error),
+    /// 查詢逾時。
+    Timeout(Duration),
+    /// 求值或寫出時出錯。
+    Other(anyhow::Error),
+}
+
+impl Failure {
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Syntax(_) => 2,
+            Self::Load(_) => 3,
+            Self::Timeout(_) => 4,
+            Self::Other(_) => 5,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load(error) | Self::Other(error) => write!(f, "{error:#}"),
+            Self::Syntax(error) => write!(f, "Invalid query: {error:#}"),
+            Self::Timeout(deadline) => {
+                write!(f, "The query took longer than {}s", deadline.as_secs())
+            }
+        }
+    }
+}
+
+impl Headless {
+    /// 載入錯誤即止，無界面可示。N3 之略去者記為警告。讀者先關管道（如 `| head`）不為錯。
+    pub fn run(&self, output: impl Write + Send + 'static) -> Result<Outcome, Failure> {
+        let store = Store::new().map_err(|error| Failure::Other(error.into()))?;
+        self.load(&store).map_err(Failure::Load)?;
+        let query =
+            Query::parse(&self.query, None).map_err(|error| Failure::Syntax(error.into()))?;
+        let options = query_options(self.federated, self.timeout, Arc::default());
+        let (limit, values) = (self.limit.unwrap_or(usize::MAX), self.values);
+        let (format, sink) = (self.output, self.sink.clone());
+        // 於他線程求值，以便逾時可棄之
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut flattened = Flattened::default();
+            let outcome = store
+                .query_opt(query, options)
+                .map_err(anyhow::Error::from)
+                .and_then(|results| {
+                    let content_type = match (format, &results) {
+                        (Some(format), _) => format.media_type(),
+                        (None, QueryResults::Graph(_)) => RdfFormat::NTriples.media_type(),
+                        (None, _) => QueryResultsFormat::Tsv.media_type(),
+                    };
+                    let emit = |out: &mut dyn Write| {
+                        write(results, out, format, limit, values, &mut flattened)
+                    };
+                    match sink {
+                        Some(sink) => sink.deliver(content_type, false, true, emit),
+                        None => emit(&mut { output }).map(|outcome| (outcome, None)),
+                    }
+                });
+            let _ = sender.send((outcome, flattened));
+        });
+        let (outcome, flattened) = match self.deadline {
+            Some(deadline) => receiver
+                .recv_timeout(deadline)
+                .map_err(|_| Failure::Timeout(deadline))?,
+            None => receiver
+                .recv()
+                .map_err(|_| Failure::Other(anyhow::anyhow!("query stopped unexpectedly")))?,
+        };
+        if !flattened.is_empty() && !self.quiet {
+            log::info!("headless output flattened {flattened}");
+            eprintln!("Plain values flattened {flattened} (--typed-columns keeps them)");
+        }
+        let (outcome, note) = outcome.map_err(Failure::Other)?;
+        if let Some(note) = note.filter(|_| !self.quiet) {
+            eprintln!("{note}");
+        }
+        Ok(outcome)
+    }
+
+    fn load(&self, store: &Store) -> anyhow::Result<()> {
+        let mut paths = Vec::with_capacity(self.paths.len());
+        for path in &self.paths {
+            if !download::is_url(path) {
+                paths.push(path.clone());
+                continue;
+            }
+            let url = path.to_string_lossy();
+            let fetched = fetch(&url, &self.download, &Progress::default())
+                .with_context(|| format!("Fail to download {url}"))?;
+            paths.push(fetched.path);
+        }
+        // 先查諸文件之大小，免載入大半而後止
+        if let Some(limit) = self.prefer_disk_above {
+            for path in &paths {
+                let size = fs::metadata(path).map_or(0, |metadata| metadata.len());
+                if size > limit {
+                    anyhow::bail!(
+                        "{} is {}, over --prefer-disk-above {}, and this build has no on-disk \
+                         store to load it into",
+                        path.display(),
+                        abbreviate(size as usize),
+                        abbreviate(limit as usize)
+                    );
+                }
+            }
+        }
+        for (index, data) in self.data.iter().enumerate() {
+            load_data(store, data, self.format, index + 1)
+                .with_context(|| format!("Fail to load inline data #{}", index + 1))?;
+        }
+        for path in &paths {
+            if results_format(path).is_some() {
+                anyhow::bail!(
+                    "{} holds query results, which cannot be queried",
+                    path.display()
+                );
+            }
+            let note = load_file(store, path, &AtomicU64::new(0), self.strict_n3)
+                .with_context(|| format!("Fail to load {}", path.display()))?
+                .note;
+            if let Some(note) = note {
+                log::warn!("load path={} {note}", path.display());
+                eprintln!("{}: {note}", path.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 以 `format` 寫出結果，至多 `limit` 行。CSV 與 TSV 之平值所失者計於 `flattened`。
+fn write(
+    results: QueryResults,
+    mut output: impl Write,
+    format: Option<ExportFormat>,
+    limit: usize,
+    values: Values,
+    flattened: &mut Flattened,
+) -> anyhow::Result<Outcome> {
+    let mismatch =
+        |kind: &str, formats: &str| anyhow::anyhow!("--output cannot write {kind}; use {formats}");
+    let results = match (format, results) {
+        (
+            Some(format @ (ExportFormat::Markdown | ExportFormat::Org)),
+            QueryResults::Solutions(solutions),
+        ) => return write_markup(solutions, output, format, limit, values, flattened),
+        (_, results) => results,
+    };
+    let results_format = match format {
+        None => QueryResultsFormat::Tsv,
+        Some(ExportFormat::Results(format)) => format,
+        Some(_) if matches!(results, QueryResults::Graph(_)) => QueryResultsFormat::Tsv,
+        Some(_) => {
+            return Err(mismatch(
+                "query results",
+                "csv, tsv, json, xml, markdown or org",
+            ))
+        }
+    };
+    let separator = match results_format {
+        QueryResultsFormat::Csv => Some(','),
+        QueryResultsFormat::Tsv => Some('\t'),
+        _ => None,
+    };
+    match results {
+        QueryResults::Solutions(solutions) if values != Values::Raw && separator.is_some() => {
+            let separator = separator.unwrap();
+            let header = plain_header(solutions.variables(), values);
+            if let Err(error) = write_record(&mut output, &header, separator) {
+                return closed(error, Outcome::Rows(0));
+            }
+            let mut rows = 0;
+            for solution in solutions.take(limit) {
+                let solution = solution?;
+                if values == Values::Plain {
+                    solution
+                        .values()
+                        .iter()
+                        .flatten()
+                        .for_each(|term| flattened.add(term));
+                }
+                let row = plain_row(solution.values(), values);
+                if let Err(error) = write_record(&mut output, &row, separator) {
+                    return closed(error, Outcome::Rows(rows));
+                }
+                rows += 1;
+            }
+            let outcome = Outcome::Rows(rows);
+            output
+                .flush()
+                .map_or_else(|error| closed(error, outcome), |()| Ok(outcome))
+        }
+        QueryResults::Solutions(solutions) => {
+            let mut writer = QueryResultsSerializer::from_format(results_format)
+                .serialize_solutions_to_write(output, solutions.variables().to_vec())?;
+            let mut rows = 0;
+            for solution in solutions.take(limit) {
+                if let Err(error) = writer.write(&solution?) {
+                    return closed(error, Outcome::Rows(rows));
+                }
+                rows += 1;
+            }
+            let outcome = Outcome::Rows(rows);
+            let written = writer.finish().and_then(|mut output| output.flush());
+            written.map_or_else(|error| closed(error, outcome), |()| Ok(outcome))
+        }
+        QueryResults::Boolean(value) => {
+            let written = QueryResultsSerializer::from_format(results_format)
+                .serialize_boolean_to_write(output, value)
+                .and_then(|mut output| {
+                    writeln!(output)?;
+                    output.flush()
+                });
+            let outcome = Outcome::Boolean(value);
+            written.map_or_else(|error| closed(error, outcome), |()| Ok(outcome))
+        }
+        QueryResults::Graph(triples) => {
+            let format = match format {
+                None => RdfFormat::NTriples,
+                Some(ExportFormat::Rdf(format)) => format,
+                Some(_) => return Err(mismatch("triples", "ttl, trig, nt or nq")),
+            };
+            let mut writer = RdfSerializer::from_format(format).serialize_to_write(output);
+            let mut count = 0;
+            for triple in triples.take(limit) {
+                if let Err(error) = writer.write_triple(&triple?) {
+                    return closed(error, Outcome::Triples(count));
+                }
+                count += 1;
+            }
+            let outcome = Outcome::Triples(count);
+            let written = writer.finish().and_then(|mut output| output.flush());
+            written.map_or_else(|error| closed(error, outcome), |()| Ok(outcome))
+        }
+    }
+}
+
+/// 以 Markdown 或 Org 表寫出，如 `:export`。表須見全部之行以定列寬，故先收之。
+fn write_markup(
+    solutions: QuerySolutionIter,
+    mut output: impl Write,
+    format: ExportFormat,
+    limit: usize,
+    values: Values,
+    flattened: &mut Flattened,
+) -> anyhow::Result<Outcome> {
+    let variables = solutions.variables().to_vec();
+    let rows = solutions.take(limit).collect::<Result<Vec<_>, _>>()?;
+    let mut numeric = markup::numeric_columns(variables.len(), &rows, |row, column| {
+        row.values()[column].as_ref()
+    });
+    let lines: Vec<Vec<String>> = if values == Values::Raw {
+        let header = variables.iter().map(Variable::to_string).collect();
+        let cells = rows.iter().map(|row| {
+            row.values()
+                .iter()
+                .map(|term| term.as_ref().map_or_else(String::new, Term::to_string))
+                .collect()
+        });
+        std::iter::once(header).chain(cells).collect()
+    } else {
+        if values == Values::Plain {
+            rows.iter()
+                .flat_map(|row| row.values().iter().flatten())
+                .for_each(|term| flattened.add(term));
+        }
+        if values == Values::Typed {
+            numeric = numeric.into_iter().flat_map(|n| [n, false]).collect();
+        }
+        let header = plain_header(&variables, values);
+        let cells = rows.iter().map(|row| plain_row(row.values(), values));
+        std::iter::once(header).chain(cells).collect()
+    };
+    let text = match format {
+        ExportFormat::Markdown => markup::markdown(&lines, &numeric),
+        _ => markup::org(&lines, &numeric),
+    };
+    let outcome = Outcome::Rows(rows.len());
+    let written = output
+        .write_all(text.as_bytes())
+        .and_then(|()| output.flush());
+    written.map_or_else(|error| closed(error, outcome), |()| Ok(outcome))
+}
+
+/// 讀者已關管道則止於所寫者。
+fn closed(error: io::Error, outcome: Outcome) -> anyhow::Result<Outcome> {
+    if error.kind() == io::ErrorKind::BrokenPipe {
+        Ok(outcome)
+    } else {
+        Err(error.into())
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{
+    label::{label_rank, LABEL_PATH},
+    memory,
+    util::offset_index,
+};
+use oxigraph::{
+    model::{NamedNode, Term},
+    sparql::QueryResults,
+    store::Store,
+};
+use std::{collections::HashMap, mem::size_of};
+
+const RDFS_SUBCLASS_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+pub const SKOS_BROADER: &str = "http://www.w3.org/2004/02/skos/core#broader";
+pub const SKOS_NARROWER: &str = "http://www.w3.org/2004/02/skos/core#narrower";
+
+/// 層級所依之關係。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    /// `?child rdfs:subClassOf ?parent`
+    SubClassOf,
+    /// `?child skos:broader ?parent`
+    Broader,
+    /// `?parent skos:narrower ?child`
+    Narrower,
+}
+
+impl Relation {
+    /// 輪換至下一關係。
+    pub fn next(self) -> Self {
+        match self {
+            Self::SubClassOf => Self::Broader,
+            Self::Broader => Self::Narrower,
+            Self::Narrower => Self::SubClassOf,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SubClassOf => "rdfs:subClassOf",
+            Self::Broader => "skos:broader",
+            Self::Narrower => "skos:narrower",
+        }
+    }
+
+    /// 以 `child`、`parent` 兩變量表此關係之三元組模式。
+    fn pattern(self, child: &str, parent: &str) -> String {
+        match self {
+            Self::SubClassOf => format!("{child} <{RDFS_SUBCLASS_OF}> {parent}"),
+            Self::Broader => format!("{child} <{SKOS_BROADER}> {parent}"),
+            Self::Narrower => format!("{parent} <{SKOS_NARROWER}> {child}"),
+        }
+    }
+}
+
+/// 樹之節點。子節點於展開時方查詢。
+struct Node {
+    iri: NamedNode,
+    label: Option<String>,
+    parent: Option<usize>,
+    /// `None` 表未載入。
+    children: Option<Vec<usize>>,
+    expanded: bool,
+    /// 祖先中已有此節點，不再展開。
+    cycle: bool,
+}
+
+/// 可見之一行。
+pub struct VisibleNode<'a> {
+    pub depth: usize,
+    pub iri: &'a NamedNode,
+    pub label: Option<&'a str>,
+    pub expanded: bool,
+    pub leaf: bool,
+    pub cycle: bool,
+}
+
+/// 類或概念之層級樹。
+pub struct Hierarchy {
+    relation: Relation,
+    lang: Option<String>,
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    /// 所選之可見行。
+    selected: usize,
+}
+
+impl Hierarchy {
+    /// 新建並查詢根節點。
+    pub fn new(store: &Store, relation: Relation, lang: Option<String>) -> anyhow::Result<Self> {
+        let mut hierarchy = Self {
+            relation,
+            lang,
+            nodes: vec![],
+            roots: vec![],
+            selected: 0,
+        };
+        let pattern = relation.pattern("?x", "?node");
+        let parent_pattern = relation.pattern("?node", "?p");
+        let query = format!(
+            "SELECT ?node ?label WHERE {{ \
+               {{ SELECT DISTINCT ?node WHERE {{ {pattern} \
+                 FILTER(isIRI(?node)) \
+                 FILTER NOT EXISTS {{ {parent_pattern} FILTER(isIRI(?p) && ?p != ?node) }} }} }} \
+               OPTIONAL {{ ?node {LABEL_PATH} ?label }} }}"
+        );
+        hierarchy.roots = hierarchy.fetch(store, &query, None)?;
+        Ok(hierarchy)
+    }
+
+    pub fn relation(&self) -> Relation {
+        self.relation
+    }
+
+    /// 估計之字節數：各節點及其 IRI、標籤與子節點之表。
+    pub fn bytes(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| {
+                size_of::<Node>()
+                    + node.iri.as_str().len()
+                    + node.label.as_deref().map_or(0, memory::text)
+                    + node
+                        .children
+                        .as_ref()
+                        .map_or(0, |c| c.len() * size_of::<usize>())
+            })
+            .sum::<usize>()
+            + self.roots.len() * size_of::<usize>()
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// 所選節點之 IRI。
+    pub fn selected_iri(&self) -> Option<&NamedNode> {
+        let index = *self.visible_indices().get(self.selected)?;
+        Some(&self.nodes[index].iri)
+    }
+
+    /// 所選上下移動若干節點。
+    pub fn select_by(&mut self, delta: isize) {
+        let len = self.visible_indices().len();
+        self.selected = offset_index(self.selected, delta, len);
+    }
+
+    /// 展開或收起所選節點。首次展開時查詢其子。
+    pub fn toggle(&mut self, store: &Store) -> anyhow::Result<()> {
+        let Some(&index) = self.visible_indices().get(self.selected) else {
+            return Ok(());
+        };
+        if self.nodes[index].cycle {
+            return Ok(());
+        }
+        if self.nodes[index].children.is_none() {
+            let pattern = self
+                .relation
+                .pattern("?node", &self.nodes[index].iri.to_string());
+            let query = format!(
+                "SELECT ?node ?label WHERE {{ {pattern} FILTER(isIRI(?node)) \
+                   OPTIONAL {{ ?node {LABEL_PATH} ?label }} }}"
+            );
+            let children = self.fetch(store, &query, Some(index))?;
+            self.nodes[index].children = Some(children);
+        }
+        let node = &mut self.nodes[index];
+        node.expanded = !node.expanded;
+        Ok(())
+    }
+
+    /// 依深度優先列出可見行。
+    pub fn visible(&self) -> Vec<VisibleNode<'_>> {
+        self.visible_indices()
+            .into_iter()
+            .map(|index| {
+                let node = &self.nodes[index];
+                VisibleNode {
+                    depth: self.depth(index),
+                    iri: &node.iri,
+                    label: node.label.as_deref(),
+                    expanded: node.expanded,
+                    leaf: node.children.as_ref().is_some_and(Vec::is_empty),
+                    cycle: node.cycle,
+                }
+            })
+            .collect()
+    }
+
+    fn visible_indices(&self) -> Vec<usize> {
+        let mut visible = vec![];
+        let mut stack: Vec<usize> = self.roots.iter().rev().copied().collect();
+        while let Some(index) = stack.pop() {
+            visible.push(index);
+            let node = &self.nodes[index];
+            if node.expanded {
+                if let Some(children) = &node.children {
+                    stack.extend(children.iter().rev());
+                }
+            }
+        }
+        visible
+    }
+
+    fn depth(&self, mut index: usize) -> usize {
+        let mut depth = 0;
+        while let Some(parent) = self.nodes[index].parent {
+            depth += 1;
+            index = parent;
+        }
+        depth
+    }
+
+    /// 祖先（含自身）中是否已有 `iri`。
+    fn on_path(&self, mut index: usize, iri: &NamedNode) -> bool {
+        loop {
+            if &self.nodes[index].iri == iri {
+                return true;
+            }
+            match self.nodes[index].parent {
+                Some(parent) => index = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// 執行返回 `?node ?label` 之查詢，建節點並返回其索引。
+    fn fetch(
+        &mut self,
+        store: &Store,
+        query: &str,
+        parent: Option<usize>,
+    ) -> anyhow::Result<Vec<usize>> {
+        let mut labels: Vec<(NamedNode, Option<(u8, String)>)> = vec![];
+        let mut positions = HashMap::new();
+        if let QueryResults::Solutions(solutions) = store.query(query)? {
+            for solution in solutions {
+                let solution = solution?;
+                let Some(Term::NamedNode(iri)) = solution.get("node") else {
+                    continue;
+                };
+                let position = *positions.entry(iri.clone()).or_insert_with(|| {
+                    labels.push((iri.clone(), None));
+                    labels.len() - 1
+                });
+                if let Some(Term::Literal(label)) = solution.get("label") {
+                    let rank = label_rank(label.language(), self.lang.as_deref());
+                    let current = &mut labels[position].1;
+                    if current.as_ref().is_none_or(|(r, _)| rank < *r) {
+                        *current = Some((rank, label.value().to_string()));
+                    }
+                }
+            }
+        }
+        labels.sort_by_cached_key(|(iri, label)| {
+            label
+                .as_ref()
+                .map_or(iri.as_str(), |(_, l)| l.as_str())
+                .to_lowercase()
+        });
+
+        let mut indices = vec![];
+        for (iri, label) in labels {
+            let label = label.map(|(_, label)| label);
+            let cycle = parent.is_some_and(|p| self.on_path(p, &iri));
+            self.nodes.push(Node {
+                iri,
+                label,
+                parent,
+                children: None,
+                expanded: false,
+                cycle,
+            });
+            indices.push(self.nodes.len() - 1);
+        }
+        Ok(indices)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{generation::Generation, memory};
+use oxigraph::model::{Term, Variable};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+/// 所記之查詢數上限。
+const CAPACITY: usize = 50;
+
+/// 一查詢結果之快照：表頭與前若干行。
+#[derive(Debug)]
+pub struct Snapshot {
+    pub variables: Vec<Variable>,
+    pub rows: Vec<Vec<Option<Arc<Term>>>>,
+}
+
+impl Snapshot {
+    /// 估計之字節數。與結果表共用之項亦計之，因結果換去後惟快照持之。
+    fn bytes(&self) -> usize {
+        let terms: usize = self
+            .rows
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|term| memory::shared_term(term))
+            .sum();
+        terms + memory::rows(self.rows.len(), self.rows.len(), self.variables.len())
+    }
+}
+
+/// 本會話所執行之一查詢。
+#[derive(Debug)]
+pub struct Entry {
+    pub query: String,
+    /// 執行之時，自紀元起之秒數。
+    pub ran: u64,
+    pub duration: Duration,
+    pub rows: usize,
+    /// 執行時存儲之代數，以辨其後存儲是否已改。
+    pub generation: Generation,
+    /// 逾內存預算則棄之，舊者先棄。
+    pub snapshot: Option<Snapshot>,
+}
+
+impl Entry {
+    /// 列表中所示者：查詢併為一行。
+    pub fn title(&self) -> String {
+        self.query.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// 本會話之查詢歷史，新者在前。失敗之查詢不記。
+#[derive(Debug, Default)]
+pub struct History {
+    entries: VecDeque<Entry>,
+}
+
+impl History {
+    pub fn entries(&self) -> &VecDeque<Entry> {
+        &self.entries
+    }
+
+    /// 記一次執行。同一查詢再執行則移至最前，換其快照。快照之總大小逾 `budget` 字節則自舊者棄之。
+    pub fn record(&mut self, entry: Entry, budget: usize) {
+        self.entries.retain(|old| old.query != entry.query);
+        self.entries.push_front(entry);
+        self.entries.truncate(CAPACITY);
+        self.trim(budget);
+    }
+
+    /// 棄最舊之快照，至其總大小不逾 `budget` 字節。返回所棄之數。
+    pub fn trim(&mut self, budget: usize) -> usize {
+        let mut bytes = self.snapshot_bytes();
+        let mut dropped = 0;
+        for entry in self.entries.iter_mut().rev() {
+            if bytes <= budget {
+                break;
+            }
+            if let Some(snapshot) = entry.snapshot.take() {
+                bytes -= snapshot.bytes();
+                dropped += 1;
+            }
+        }
+        dropped
+    }
+
+    /// 各快照之估計字節數之和。
+    pub fn snapshot_bytes(&self) -> usize {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.snapshot.as_ref())
+            .map(Snapshot::bytes)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::Literal;
+
+    fn entry(query: &str, rows: usize) -> Entry {
+        let term = Arc::new(Term::from(Literal::new_simple_literal("x".repeat(100))));
+        Entry {
+            query: query.to_string(),
+            ran: 0,
+            duration: Duration::ZERO,
+            rows,
+            generation: Generation::default(),
+            snapshot: Some(Snapshot {
+                variables: vec![Variable::new_unchecked("o")],
+                rows: vec![vec![Some(term)]; rows],
+            }),
+        }
+    }
+
+    #[test]
+    fn oldest_snapshots_go_first_when_over_budget() {
+        let one = entry("a", 10).snapshot.unwrap().bytes();
+        let mut history = History::default();
+        for query in ["a", "b", "c"] {
+            history.record(entry(query, 10), 2 * one);
+        }
+        let kept: Vec<bool> = history
+            .entries()
+            .iter()
+            .map(|entry| entry.snapshot.is_some())
+            .collect();
+        assert_eq!(kept, [true, true, false]);
+        assert_eq!(history.snapshot_bytes(), 2 * one);
+
+        history.record(entry("a", 1), 2 * one);
+        let queries: Vec<&str> = history.entries().iter().map(|e| e.query.as_str()).collect();
+        assert_eq!(queries, ["a", "c", "b"]);
+        assert_eq!(history.entries()[0].rows, 1);
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::{bail, Context};
+use std::{
+    io::{self, Read, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// 極簡 HTTP 客戶端。借系統之 `curl` 發請求，參數皆以 argv 傳遞，不經 shell。
+#[derive(Debug, Clone)]
+pub struct Client {
+    timeout: Option<Duration>,
+    redirection_limit: usize,
+}
+
+/// 應答。
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl Client {
+    pub fn new(timeout: Option<Duration>, redirection_limit: usize) -> Self {
+        Self {
+            timeout,
+            redirection_limit,
+        }
+    }
+
+    /// 發 GET 請求。
+    pub fn get(&self, url: &str, accept: &str) -> anyhow::Result<Response> {
+        self.send(url, accept, None)
+    }
+
+    /// 發 POST 請求，正文由 stdin 傳入。
+    pub fn post(
+        &self,
+        url: &str,
+        body: &[u8],
+        content_type: &str,
+        accept: &str,
+    ) -> anyhow::Result<Response> {
+        self.send(url, accept, Some((body, content_type)))
+    }
+
+    /// 發 POST 請求，正文寫於所返回者，邊寫邊以分塊傳輸送出，不先積於內存。
+    pub fn upload(&self, url: &str, content_type: &str, accept: &str) -> anyhow::Result<Upload> {
+        let mut command = self.command(url, accept)?;
+        command
+            .args(["--request", "POST", "--upload-file", "-"])
+            .args(["--header", &format!("Content-Type: {content_type}")])
+            // 不待 `100 Continue`
+            .args(["--header", "Expect:"])
+            .arg("--")
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = command.spawn().context("Fail to run curl")?;
+        Ok(Upload {
+            stdin: child.stdin.take(),
+            stdout: drain(child.stdout.take()),
+            stderr: drain(child.stderr.take()),
+            child,
+        })
+    }
+
+    /// curl 之共同參數。
+    fn command(&self, url: &str, accept: &str) -> anyhow::Result<Command> {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            bail!("unsupported URL scheme: {url}");
+        }
+        let mut command = Command::new("curl");
+        command
+            .args(["--silent", "--show-error", "--location"])
+            .args(["--max-redirs", &self.redirection_limit.to_string()])
+            .args(["--header", &format!("Accept: {accept}")])
+            // 狀態碼與內容類型附於正文之後
+            .args(["--write-out", "\n%{http_code}\n%{content_type}"]);
+        if let Some(timeout) = self.timeout {
+            command.args(["--max-time", &timeout.as_secs_f64().to_string()]);
+        }
+        Ok(command)
+    }
+
+    fn send(
+        &self,
+        url: &str,
+        accept: &str,
+        body: Option<(&[u8], &str)>,
+    ) -> anyhow::Result<Response> {
+        let mut command = self.command(url, accept)?;
+        if let Some((_, content_type)) = body {
+            command
+                .args(["--header", &format!("Content-Type: {content_type}")])
+                .args(["--data-binary", "@-"]);
+        }
+        // `--` 之後之 URL 不會被當作選項
+        command.arg("--").arg(url);
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = command.spawn().context("Fail to run curl")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Some((bytes, _)) = body {
+                stdin.write_all(bytes)?;
+            }
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+                    .trim()
+                    .trim_start_matches("curl: ")
+            );
+        }
+        parse_output(output.stdout)
+    }
+}
+
+/// 送出中之 POST，見 [`Client::upload`]。
+pub struct Upload {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: JoinHandle<Vec<u8>>,
+    stderr: JoinHandle<Vec<u8>>,
+}
+
+impl Upload {
+    /// 正文已畢，待應答。
+    pub fn finish(mut self) -> anyhow::Result<Response> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+        let stdout = self.stdout.join().unwrap_or_default();
+        let stderr = self.stderr.join().unwrap_or_default();
+        if !status.success() {
+            bail!(
+                "{}",
+                String::from_utf8_lossy(&stderr)
+                    .trim()
+                    .trim_start_matches("curl: ")
+            );
+        }
+        parse_output(stdout)
+    }
+
+    /// 棄之：殺 curl，使對方見連接中斷而非完整之正文。
+    pub fn abort(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Write for Upload {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.stdin {
+            Some(stdin) => stdin.write(buf),
+            None => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdin.as_mut().map_or(Ok(()), Write::flush)
+    }
+}
+
+/// 於他線程讀盡子進程之輸出，免其寫滿管道而與所寫者互等。
+pub fn drain(reader: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+/// 拆開 curl 輸出之正文、狀態碼與內容類型。
+fn parse_output(mut stdout: Vec<u8>) -> anyhow::Result<Response> {
+    let mut tail = stdout.rsplitn(3, |b| *b == b'\n');
+    let content_type = tail.next().map(|s| String::from_utf8_lossy(s).to_string());
+    let status = tail
+        .next()
+        .and_then(|s| std::str::from_utf8(s).ok())
+        .and_then(|s| s.parse().ok())
+        .context("Malformed response from curl")?;
+    let body_len = tail.next().map_or(0, |b| b.len());
+    stdout.truncate(body_len);
+    Ok(Response {
+        status,
+        content_type: content_type
+            .filter(|s| !s.is_empty())
+            .map(|s| s.split(';').next().unwrap_or_default().trim().to_string()),
+        body: stdout,
+    })
+}
@@ -0,0 +1,146 @@
+use crate::http::Client;
+use anyhow::{bail, Context};
+use oxigraph::{
+    io::{RdfFormat, RdfParser},
+    model::{GraphNameRef, NamedNode, Term},
+    sparql::QueryResults,
+    store::Store,
+};
+use std::{collections::HashSet, io::Cursor, time::Duration};
+
+const OWL_IMPORTS: &str = "http://www.w3.org/2002/07/owl#imports";
+
+/// 內容協商時所求之 RDF 格式。
+const ACCEPT: &str = "text/turtle, application/rdf+xml;q=0.9, application/n-triples;q=0.8, \
+                      application/trig;q=0.7, application/n-quads;q=0.7, */*;q=0.1";
+
+/// 追蹤 `owl:imports` 之結果。
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// 已導入之本體 IRI 及其三元組數。
+    pub imported: Vec<(String, usize)>,
+    /// 導入失敗之 IRI 及其因。
+    pub failures: Vec<(String, String)>,
+}
+
+impl ImportReport {
+    /// 一行摘要，供狀態欄顯示。
+    pub fn summary(&self) -> Option<String> {
+        if self.imported.is_empty() && self.failures.is_empty() {
+            return None;
+        }
+        let triples: usize = self.imported.iter().map(|(_, n)| n).sum();
+        let mut summary = format!(
+            "imported {} ontologies ({triples} triples)",
+            self.imported.len()
+        );
+        if !self.failures.is_empty() {
+            let failures = self
+                .failures
+                .iter()
+                .map(|(iri, error)| format!("<{iri}>: {error}"))
+                .collect::<Vec<_>>()
+                .join("; ");
+            summary.push_str(&format!(", {} failed: {failures}", self.failures.len()));
+        }
+        Some(summary)
+    }
+}
+
+/// 自 `graph` 起廣度優先追蹤 `owl:imports`，至多 `depth` 層。
+/// 每個本體載入以其 IRI 為名之具名圖。`visited` 記已嘗試之 IRI，以免成環。
+pub fn follow_imports(
+    store: &Store,
+    graph: GraphNameRef<'_>,
+    depth: usize,
+    visited: &mut HashSet<String>,
+    timeout: Duration,
+) -> ImportReport {
+    let client = Client::new(Some(timeout), 10);
+    let mut report = ImportReport::default();
+    let mut frontier = match imports_in(store, graph) {
+        Ok(imports) => imports,
+        Err(error) => {
+            report.failures.push((graph.to_string(), error.to_string()));
+            return report;
+        }
+    };
+
+    for _ in 0..depth {
+        let mut next = vec![];
+        for iri in frontier {
+            if !visited.insert(iri.as_str().to_string()) {
+                continue;
+            }
+            match fetch_into_graph(store, &client, &iri) {
+                Ok(count) => {
+                    report.imported.push((iri.as_str().to_string(), count));
+                    match imports_in(store, GraphNameRef::NamedNode(iri.as_ref())) {
+                        Ok(imports) => next.extend(imports),
+                        Err(error) => report
+                            .failures
+                            .push((iri.as_str().to_string(), error.to_string())),
+                    }
+                }
+                Err(error) => report
+                    .failures
+                    .push((iri.as_str().to_string(), error.to_string())),
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    report
+}
+
+/// 查 `graph` 中所有 `owl:imports` 之賓語。
+fn imports_in(store: &Store, graph: GraphNameRef<'_>) -> anyhow::Result<Vec<NamedNode>> {
+    let pattern = format!("?ontology <{OWL_IMPORTS}> ?import");
+    let query = match graph {
+        GraphNameRef::NamedNode(name) => {
+            format!("SELECT DISTINCT ?import WHERE {{ GRAPH {name} {{ {pattern} }} }}")
+        }
+        _ => format!("SELECT DISTINCT ?import WHERE {{ {pattern} }}"),
+    };
+    let mut imports = vec![];
+    if let QueryResults::Solutions(solutions) = store.query(query.as_str())? {
+        for solution in solutions {
+            if let Some(Term::NamedNode(iri)) = solution?.get("import") {
+                imports.push(iri.clone());
+            }
+        }
+    }
+    Ok(imports)
+}
+
+/// 經 HTTP 取 `iri`，載入同名之具名圖，返回新增三元組數。
+fn fetch_into_graph(store: &Store, client: &Client, iri: &NamedNode) -> anyhow::Result<usize> {
+    let response = client.get(iri.as_str(), ACCEPT)?;
+    if !(200..300).contains(&response.status) {
+        bail!("HTTP {}", response.status);
+    }
+    let format = response
+        .content_type
+        .as_deref()
+        .and_then(RdfFormat::from_media_type)
+        .or_else(|| {
+            let path = iri.as_str().split(['#', '?']).next().unwrap_or_default();
+            RdfFormat::from_extension(path.rsplit('.').next()?)
+        })
+        .with_context(|| {
+            format!(
+                "unsupported content type `{}`",
+                response.content_type.as_deref().unwrap_or_default()
+            )
+        })?;
+    let before = store.len()?;
+    store.load_from_read(
+        RdfParser::from_format(format)
+            .with_base_iri(iri.as_str())?
+            .with_default_graph(iri.clone()),
+        Cursor::new(response.body),
+    )?;
+    Ok(store.len()? - before)
+}
@@ -0,0 +1,94 @@
+use crate::count::Tally;
+use oxigraph::model::{NamedNode, Term};
+
+/// 樞紐資源所先載入之入邊數。
+pub const PREVIEW: usize = 1000;
+
+/// 入邊之列法。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum View {
+    /// 各入邊之主語與謂語，至多若干條。
+    All(Option<usize>),
+    /// 各謂語及其入邊數，多者居先。
+    Grouped,
+    /// 一謂語之入邊之主語，至多 `PREVIEW` 條。
+    Predicate(NamedNode),
+}
+
+/// 指向一資源之三元組，即以之為賓語者。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incoming {
+    pub term: Term,
+    pub view: View,
+}
+
+impl Incoming {
+    pub fn query(&self) -> String {
+        let term = &self.term;
+        match &self.view {
+            View::All(limit) => {
+                let limit = limit.map_or_else(String::new, |limit| format!(" LIMIT {limit}"));
+                format!("SELECT ?s ?p WHERE {{ ?s ?p {term} }}{limit}")
+            }
+            View::Grouped => format!(
+                "SELECT ?p (COUNT(*) AS ?n) WHERE {{ ?s ?p {term} }} GROUP BY ?p ORDER BY DESC(?n)"
+            ),
+            View::Predicate(predicate) => {
+                format!("SELECT ?s WHERE {{ ?s {predicate} {term} }} LIMIT {PREVIEW}")
+            }
+        }
+    }
+}
+
+/// 查詢可見之入邊數：僅默認圖，或連命名圖。
+pub fn visible(tally: &Tally, named_graphs: bool) -> usize {
+    match named_graphs {
+        true => tally.default + tally.named,
+        false => tally.default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::{io::RdfFormat, sparql::QueryResults, store::Store};
+
+    #[test]
+    fn incoming_views_list_group_and_expand() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfFormat::Turtle,
+                "@prefix ex: <http://ex/> . ex:a ex:in ex:hub . ex:b ex:in ex:hub . \
+                 ex:c ex:near ex:hub . ex:hub ex:out ex:a ."
+                    .as_bytes(),
+            )
+            .unwrap();
+        let rows = |view: View| {
+            let incoming = Incoming {
+                term: NamedNode::new_unchecked("http://ex/hub").into(),
+                view,
+            };
+            let QueryResults::Solutions(solutions) = store.query(&incoming.query()).unwrap() else {
+                unreachable!()
+            };
+            solutions
+                .map(|solution| {
+                    let solution = solution.unwrap();
+                    solution.iter().map(|(_, term)| term.to_string()).collect()
+                })
+                .collect::<Vec<Vec<String>>>()
+        };
+        assert_eq!(rows(View::All(None)).len(), 3);
+        assert_eq!(rows(View::All(Some(2))).len(), 2);
+        assert_eq!(
+            rows(View::Grouped)[0],
+            [
+                "<http://ex/in>",
+                "\"2\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+            ]
+        );
+        let predicate = View::Predicate(NamedNode::new_unchecked("http://ex/near"));
+        assert_eq!(rows(predicate), [["<http://ex/c>"]]);
+    }
+}
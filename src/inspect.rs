@@ -0,0 +1,310 @@
+use crate::typography::{self, Kind};
+use oxigraph::model::Term;
+use std::fmt::Write;
+
+/// 浮窗中一行所示之字符數，過長之值分行示之。
+const WIDTH: usize = 60;
+
+/// 原文之可見之形：控制字符、非 ASCII 之空白與不可見之字符，及首尾之空格，皆代以其碼位如
+/// `⟨U+00A0⟩`。他字符照錄。
+pub fn visible(text: &str) -> String {
+    let trimmed = text.trim_matches(' ');
+    let leading = text.len() - text.trim_start_matches(' ').len();
+    let trailing = if trimmed.is_empty() {
+        0
+    } else {
+        text.len() - text.trim_end_matches(' ').len()
+    };
+    let mut shown = "⟨U+0020⟩".repeat(leading);
+    for ch in text[leading..text.len() - trailing].chars() {
+        let escape = ch.is_control()
+            || matches!(
+                typography::kind(ch),
+                Some((Kind::Space | Kind::Invisible, _))
+            )
+            || (ch.is_whitespace() && ch != ' ');
+        match escape {
+            true => {
+                let _ = write!(shown, "⟨U+{:04X}⟩", ch as u32);
+            }
+            false => shown.push(ch),
+        }
+    }
+    shown.push_str(&"⟨U+0020⟩".repeat(trailing));
+    shown
+}
+
+/// 所比較之文本：IRI 與空節點之名，或字面量之詞法形式。
+pub fn value(term: &Term) -> String {
+    match term {
+        Term::NamedNode(iri) => iri.as_str().to_string(),
+        Term::BlankNode(node) => node.as_str().to_string(),
+        Term::Literal(literal) => literal.value().to_string(),
+        Term::Triple(triple) => triple.to_string(),
+    }
+}
+
+/// 檢視浮窗之各行：類別、原文及其長度，字面量之類型與語言，IRI 之各部分與解碼之形，
+/// 及諸非 ASCII 之字符。
+pub fn lines(term: &Term) -> Vec<(String, String)> {
+    let text = value(term);
+    let kind = match term {
+        Term::NamedNode(_) => "IRI",
+        Term::BlankNode(_) => "blank node",
+        Term::Literal(_) => "literal",
+        Term::Triple(_) => "quoted triple",
+    };
+    let mut lines = vec![("kind".to_string(), kind.to_string())];
+    push_wrapped(&mut lines, "value", &visible(&text));
+    lines.push((
+        "length".to_string(),
+        format!(
+            "{}, {} UTF-8",
+            plural(text.chars().count(), "character"),
+            plural(text.len(), "byte")
+        ),
+    ));
+    match term {
+        Term::Literal(literal) => {
+            lines.push((
+                "datatype".to_string(),
+                format!("<{}>", literal.datatype().as_str()),
+            ));
+            if let Some(language) = literal.language() {
+                lines.push(("language".to_string(), language.to_string()));
+            }
+        }
+        Term::NamedNode(iri) => {
+            let parts = Iri::split(iri.as_str());
+            lines.push(("scheme".to_string(), parts.scheme.to_string()));
+            for (name, part) in [
+                ("authority", parts.authority),
+                ("path", Some(parts.path)),
+                ("query", parts.query),
+                ("fragment", parts.fragment),
+            ] {
+                if let Some(part) = part.filter(|part| !part.is_empty()) {
+                    push_wrapped(&mut lines, name, &visible(part));
+                }
+            }
+            if iri.as_str().contains('%') {
+                push_wrapped(
+                    &mut lines,
+                    "decoded",
+                    &visible(&percent_decode(iri.as_str())),
+                );
+            }
+        }
+        _ => {}
+    }
+    let unusual: Vec<String> = text
+        .chars()
+        .filter(|ch| !ch.is_ascii())
+        .fold(vec![], |mut seen: Vec<char>, ch| {
+            if !seen.contains(&ch) {
+                seen.push(ch);
+            }
+            seen
+        })
+        .into_iter()
+        .map(|ch| format!("{} U+{:04X}", visible(&ch.to_string()), ch as u32))
+        .collect();
+    if !unusual.is_empty() {
+        push_wrapped(&mut lines, "non-ASCII", &unusual.join(", "));
+    }
+    lines
+}
+
+/// 逐字比較格之值與所粘貼者：相同，或異處之所在及兩者於此之文本，共同之首尾不示。
+pub fn compare(value: &str, other: &str) -> Vec<(String, String)> {
+    let mut lines = vec![];
+    push_wrapped(&mut lines, "pasted", &visible(other));
+    if value == other {
+        lines.push(("result".to_string(), "identical".to_string()));
+        return lines;
+    }
+    let (a, b): (Vec<char>, Vec<char>) = (value.chars().collect(), other.chars().collect());
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let middle = |chars: &[char]| -> String {
+        match chars[prefix..chars.len() - suffix]
+            .iter()
+            .collect::<String>()
+        {
+            text if text.is_empty() => "nothing".to_string(),
+            text => visible(&text),
+        }
+    };
+    lines.push((
+        "result".to_string(),
+        format!("differs from character {}", prefix + 1),
+    ));
+    push_wrapped(&mut lines, "value has", &middle(&a));
+    push_wrapped(&mut lines, "pasted has", &middle(&b));
+    lines
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        count => format!("{count} {noun}s"),
+    }
+}
+
+/// 加一項，其值逾 `WIDTH` 字符者續於後行，名為空。
+fn push_wrapped(lines: &mut Vec<(String, String)>, name: &str, text: &str) {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        lines.push((name.to_string(), String::new()));
+    }
+    for (index, chunk) in chars.chunks(WIDTH).enumerate() {
+        let name = if index == 0 { name } else { "" };
+        lines.push((name.to_string(), chunk.iter().collect()));
+    }
+}
+
+/// IRI 依 RFC 3986 之各部分。
+#[derive(Debug, PartialEq, Eq)]
+struct Iri<'a> {
+    scheme: &'a str,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> Iri<'a> {
+    fn split(iri: &'a str) -> Self {
+        let (rest, fragment) = match iri.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (iri, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (scheme, rest) = rest.split_once(':').unwrap_or(("", rest));
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => {
+                let end = rest.find('/').unwrap_or(rest.len());
+                (Some(&rest[..end]), &rest[end..])
+            }
+            None => (None, rest),
+        };
+        Self {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }
+    }
+}
+
+/// 解 `%XX` 之編碼，所得非 UTF-8 者代以 `�`。
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, NamedNode};
+
+    #[test]
+    fn values_show_their_exact_characters() {
+        assert_eq!(
+            visible(" a\u{00A0}b\t\u{200B} "),
+            "⟨U+0020⟩a⟨U+00A0⟩b⟨U+0009⟩⟨U+200B⟩⟨U+0020⟩"
+        );
+        assert_eq!(visible("a b"), "a b");
+        assert_eq!(visible("  "), "⟨U+0020⟩⟨U+0020⟩");
+
+        let literal = Term::from(Literal::new_language_tagged_literal_unchecked(
+            "Café ", "fr",
+        ));
+        let lines = lines(&literal);
+        let get = |name: &str| {
+            lines
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("value"), Some("Café⟨U+0020⟩"));
+        assert_eq!(get("length"), Some("5 characters, 6 bytes UTF-8"));
+        assert_eq!(get("language"), Some("fr"));
+        assert_eq!(get("non-ASCII"), Some("é U+00E9"));
+
+        let iri = Term::from(NamedNode::new_unchecked(
+            "http://ex.org:8080/a%20b/c?x=1#caf%C3%A9",
+        ));
+        let lines = super::lines(&iri);
+        let get = |name: &str| {
+            lines
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("scheme"), Some("http"));
+        assert_eq!(get("authority"), Some("ex.org:8080"));
+        assert_eq!(get("path"), Some("/a%20b/c"));
+        assert_eq!(get("query"), Some("x=1"));
+        assert_eq!(get("fragment"), Some("caf%C3%A9"));
+        assert_eq!(get("decoded"), Some("http://ex.org:8080/a b/c?x=1#café"));
+        assert_eq!(
+            Iri::split("urn:isbn:123").authority,
+            None,
+            "no authority without //"
+        );
+        assert_eq!(percent_decode("%zz%+1%4"), "%zz%+1%4");
+    }
+
+    #[test]
+    fn comparisons_show_where_values_differ() {
+        let result = |value: &str, other: &str| compare(value, other)[1..].to_vec();
+        let pair = |name: &str, value: &str| (name.to_string(), value.to_string());
+        assert_eq!(result("abc", "abc"), [pair("result", "identical")]);
+        assert_eq!(
+            result("Paris ", "Paris"),
+            [
+                pair("result", "differs from character 6"),
+                pair("value has", "⟨U+0020⟩"),
+                pair("pasted has", "nothing"),
+            ]
+        );
+        // 西里爾之 а 與拉丁之 a 形同
+        assert_eq!(
+            result("b\u{0430}r", "bar"),
+            [
+                pair("result", "differs from character 2"),
+                pair("value has", "\u{0430}"),
+                pair("pasted has", "a"),
+            ]
+        );
+    }
+}
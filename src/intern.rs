@@ -0,0 +1,30 @@
+use crate::memory::shared_term;
+use oxigraph::model::Term;
+use std::{collections::HashSet, sync::Arc};
+
+/// 物化結果時合併重複之項。結果中同一謂語、類型常見於百萬行，共用一份則省內存。
+///
+/// 僅於構造一表時存在；表中各項自持其 [`Arc`]，故表釋放則項亦釋放，不隨查詢累積。
+#[derive(Default)]
+pub struct Interner {
+    terms: HashSet<Arc<Term>>,
+    /// 已存各項之估計字節數。
+    bytes: usize,
+}
+
+impl Interner {
+    pub fn intern(&mut self, term: &Term) -> Arc<Term> {
+        if let Some(interned) = self.terms.get(term) {
+            return Arc::clone(interned);
+        }
+        let interned = Arc::new(term.clone());
+        self.bytes += shared_term(term);
+        self.terms.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// 已存各項之估計字節數。
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
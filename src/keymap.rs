@@ -0,0 +1,592 @@
+use crate::{action::Action, app::Mode, config::Keyscheme};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+
+/// 一次按鍵：鍵與修飾鍵。字符鍵之 Shift 已體現於大小寫，故略去。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+
+    /// 解析如 `q`、`ctrl-enter`、`shift-tab` 之按鍵。
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        // 末段為鍵，其前為修飾鍵；`-` 本身亦可為鍵
+        while let Some((modifier, tail)) = rest.split_once('-').filter(|(_, tail)| !tail.is_empty())
+        {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            rest = tail;
+        }
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            lower => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => KeyCode::Char(ch),
+                    _ => match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                        Some(n @ 1..=24) => KeyCode::F(n),
+                        _ => return Err(format!("unknown key `{text}`")),
+                    },
+                }
+            }
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(ch) => write!(f, "{ch}"),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::BackTab => f.write_str("shift-tab"),
+            KeyCode::Backspace => f.write_str("backspace"),
+            KeyCode::Delete => f.write_str("delete"),
+            KeyCode::Insert => f.write_str("insert"),
+            KeyCode::Up => f.write_str("up"),
+            KeyCode::Down => f.write_str("down"),
+            KeyCode::Left => f.write_str("left"),
+            KeyCode::Right => f.write_str("right"),
+            KeyCode::Home => f.write_str("home"),
+            KeyCode::End => f.write_str("end"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// 按鍵序列，如 `g g`。
+pub type KeySequence = Vec<KeyChord>;
+
+/// 解析以空白分隔之按鍵序列。
+pub fn parse_sequence(text: &str) -> Result<KeySequence, String> {
+    let sequence = text
+        .split_whitespace()
+        .map(KeyChord::parse)
+        .collect::<Result<KeySequence, _>>()?;
+    if sequence.is_empty() {
+        return Err("empty key sequence".to_string());
+    }
+    Ok(sequence)
+}
+
+/// 按鍵序列查找之結果。
+#[derive(Debug, PartialEq)]
+pub enum Lookup {
+    Action(Action),
+    /// 為某綁定之前綴，待後續按鍵。
+    Prefix,
+    None,
+}
+
+/// 提示欄所列之操作及其簡稱，各模式依重要性排序。窄時自末尾捨去。
+const HINTS: &[(Mode, Action, &str)] = &[
+    (Mode::Browse, Action::SwitchMode, "query"),
+    (Mode::Browse, Action::ShowHelp, "help"),
+    (Mode::Browse, Action::Quit, "quit"),
+    (Mode::Browse, Action::OpenCommandLine, "command"),
+    (Mode::Browse, Action::ShowDetails, "details"),
+    (Mode::Browse, Action::OpenHierarchy, "hierarchy"),
+    (Mode::Browse, Action::OpenPattern, "pattern"),
+    (Mode::Browse, Action::OpenNeighborhood, "neighborhood"),
+    (Mode::Browse, Action::BookmarkRow, "mark"),
+    (Mode::Browse, Action::OpenBookmarks, "bookmarks"),
+    (Mode::Browse, Action::Reload, "reload"),
+    (Mode::Browse, Action::Yank, "yank"),
+    (Mode::Query, Action::SwitchMode, "browse"),
+    (Mode::Query, Action::RunQuery, "run"),
+    (Mode::Hierarchy, Action::Back, "back"),
+    (Mode::Hierarchy, Action::Activate, "expand"),
+    (Mode::Hierarchy, Action::ShowHelp, "help"),
+    (Mode::Hierarchy, Action::ListInstances, "instances"),
+    (Mode::Hierarchy, Action::ShowDetails, "details"),
+    (Mode::Hierarchy, Action::CycleRelation, "relation"),
+    (Mode::Hierarchy, Action::BookmarkRow, "mark"),
+    (Mode::Bookmarks, Action::Back, "back"),
+    (Mode::Bookmarks, Action::Activate, "jump"),
+    (Mode::Bookmarks, Action::Delete, "delete"),
+    (Mode::Bookmarks, Action::ShowHelp, "help"),
+    (Mode::Recent, Action::Activate, "open"),
+    (Mode::Recent, Action::Delete, "remove"),
+    (Mode::Recent, Action::PromptOpen, "browse"),
+    (Mode::Recent, Action::Back, "back"),
+    (Mode::History, Action::Activate, "run"),
+    (Mode::History, Action::Back, "back"),
+    (Mode::Scratchpad, Action::Back, "back"),
+    (Mode::Scratchpad, Action::Activate, "insert"),
+    (Mode::Scratchpad, Action::Yank, "yank"),
+    (Mode::Scratchpad, Action::Delete, "delete"),
+    (Mode::Scratchpad, Action::MovePin(-1), "move up"),
+    (Mode::Scratchpad, Action::ShowHelp, "help"),
+    (Mode::Neighborhood, Action::Back, "back"),
+    (Mode::Neighborhood, Action::Activate, "center"),
+    (Mode::Neighborhood, Action::ExpandNode, "expand"),
+    (Mode::Neighborhood, Action::CollapseNode, "collapse"),
+    (Mode::Neighborhood, Action::ShowDetails, "details"),
+    (Mode::Neighborhood, Action::ShowHelp, "help"),
+    (Mode::Pattern, Action::Activate, "run"),
+    (Mode::Pattern, Action::CycleField(1), "next field"),
+    (Mode::Pattern, Action::Back, "back"),
+    (Mode::Pattern, Action::Complete, "complete"),
+    (Mode::Command, Action::Activate, "run"),
+    (Mode::Command, Action::Back, "cancel"),
+    (Mode::Command, Action::Complete, "complete"),
+    (Mode::Command, Action::ListCompletions, "list"),
+    (Mode::Filter, Action::Activate, "apply"),
+    (Mode::Filter, Action::Back, "cancel"),
+    (Mode::Filter, Action::ToggleRegex, "regex"),
+    (Mode::Edit, Action::Activate, "save"),
+    (Mode::Edit, Action::Back, "cancel"),
+];
+
+/// 一條綁定。
+#[derive(Debug, Clone)]
+struct Binding {
+    mode: Mode,
+    keys: KeySequence,
+    action: Action,
+}
+
+/// 舊式終端不能與他鍵區分之按鍵，及其代替者。舊式終端下 `ctrl-/` 報為 `ctrl-7`。
+const FALLBACKS: &[(&str, &str)] = &[("ctrl-enter", "f5"), ("ctrl-/", "ctrl-7")];
+
+/// 各模式下按鍵序列至操作之映射。
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    /// 默認鍵位。
+    pub fn new(keyscheme: Keyscheme) -> Self {
+        use Action::*;
+        let mut keymap = Self { bindings: vec![] };
+        let defaults: &[(Mode, &[&str], Action)] = &[
+            (Mode::Browse, &["tab"], SwitchMode),
+            (Mode::Browse, &["q"], Quit),
+            (Mode::Browse, &["Q"], ForceQuit),
+            (Mode::Browse, &["h"], OpenHierarchy),
+            (Mode::Browse, &["H"], OpenHistory),
+            (Mode::Browse, &["r"], Reload),
+            (Mode::Browse, &["up", "k"], ScrollRows(-1)),
+            (Mode::Browse, &["down", "j"], ScrollRows(1)),
+            (Mode::Browse, &["g g", "home"], SelectFirst),
+            (Mode::Browse, &["G", "end"], SelectLast),
+            (Mode::Browse, &["enter", "d"], ShowDetails),
+            (Mode::Browse, &["m"], BookmarkRow),
+            (Mode::Browse, &["M"], BookmarkQuery),
+            (Mode::Browse, &["'"], OpenBookmarks),
+            (Mode::Browse, &["P"], PinCell),
+            (Mode::Browse, &["y"], Yank),
+            (Mode::Browse, &["\""], OpenScratchpad),
+            (Mode::Browse, &["p"], OpenPattern),
+            (Mode::Browse, &["n"], OpenNeighborhood),
+            (Mode::Browse, &["o"], ToggleOrigin),
+            (Mode::Browse, &["O"], ToggleStableOrder),
+            (Mode::Browse, &["L"], ToggleLabels),
+            (Mode::Browse, &["U"], ToggleUnion),
+            (Mode::Browse, &["D"], ShowDiff),
+            (Mode::Browse, &["Σ"], ShowColumnStats),
+            (Mode::Browse, &["T"], ToggleTypes),
+            (Mode::Browse, &["I"], InspectTerm),
+            (Mode::Browse, &["R"], ShowRow),
+            (Mode::Browse, &["g x"], OpenIri),
+            (Mode::Browse, &["g t", "ctrl-pagedown"], CycleTab(1)),
+            (Mode::Browse, &["g T", "ctrl-pageup"], CycleTab(-1)),
+            (Mode::Browse, &["1"], ChooseDiffValue(0)),
+            (Mode::Browse, &["2"], ChooseDiffValue(1)),
+            (Mode::Browse, &["="], DiffValues),
+            (Mode::Browse, &["<"], ShowIncoming),
+            (Mode::Browse, &["b"], ToggleTile),
+            (Mode::Browse, &["t"], ToggleTransposed),
+            (Mode::Browse, &["c"], CycleTint),
+            (Mode::Browse, &["K"], SelectHeader),
+            (Mode::Browse, &["s"], AddSortKey),
+            (Mode::Browse, &["S"], ClearSort),
+            (Mode::Browse, &["#"], CountTerm),
+            (Mode::Browse, &["E"], EditValue),
+            (Mode::Browse, &["space"], ToggleMark),
+            (Mode::Browse, &["ctrl-d", "d d"], DeleteMarked),
+            (Mode::Browse, &["v"], TogglePreview),
+            (Mode::Browse, &["w"], CycleLayout),
+            (Mode::Browse, &["f12"], ToggleDebug),
+            (Mode::Browse, &["shift-down"], ScrollPreview(1)),
+            (Mode::Browse, &["shift-up"], ScrollPreview(-1)),
+            (Mode::Browse, &["left"], ScrollColumns(-1)),
+            (Mode::Browse, &["right"], ScrollColumns(1)),
+            (Mode::Browse, &[":"], OpenCommandLine),
+            (Mode::Browse, &["space e"], PromptExport),
+            (Mode::Browse, &["/"], OpenFilter),
+            (Mode::Browse, &["?"], ShowHelp),
+            (Mode::Browse, &["esc"], CancelQuery),
+            (Mode::Browse, &["ctrl-g"], QueryByExample),
+            (Mode::Browse, &["@"], PromptPlayMacro),
+            (Mode::Query, &["tab"], SwitchMode),
+            (Mode::Query, &["enter"], Newline),
+            (Mode::Query, &["backspace"], DeleteBackward),
+            (Mode::Query, &["ctrl-enter", "f5"], RunQuery),
+            (Mode::Query, &["ctrl-g"], CancelQuery),
+            (Mode::Query, &["ctrl-p"], OpenScratchpad),
+            (Mode::Query, &["ctrl-r"], PromptRename),
+            (Mode::Query, &["ctrl-u"], UndoRename),
+            (Mode::Query, &["ctrl-n"], NormalizeQuery),
+            (Mode::Query, &["f12"], ToggleDebug),
+            (Mode::Hierarchy, &["up", "k"], ScrollRows(-1)),
+            (Mode::Hierarchy, &["down", "j"], ScrollRows(1)),
+            (Mode::Hierarchy, &["enter"], Activate),
+            (Mode::Hierarchy, &["r"], CycleRelation),
+            (Mode::Hierarchy, &["R"], RefreshPanel),
+            (Mode::Hierarchy, &["i"], ListInstances),
+            (Mode::Hierarchy, &["d"], ShowDetails),
+            (Mode::Hierarchy, &["m"], BookmarkRow),
+            (Mode::Hierarchy, &["'"], OpenBookmarks),
+            (Mode::Hierarchy, &[":"], OpenCommandLine),
+            (Mode::Hierarchy, &["?"], ShowHelp),
+            (Mode::Hierarchy, &["esc", "tab"], Back),
+            (Mode::Hierarchy, &["q"], Quit),
+            (Mode::Hierarchy, &["@"], PromptPlayMacro),
+            (Mode::Bookmarks, &["up", "k"], ScrollRows(-1)),
+            (Mode::Bookmarks, &["down", "j"], ScrollRows(1)),
+            (Mode::Bookmarks, &["enter"], Activate),
+            (Mode::Bookmarks, &["d", "delete"], Delete),
+            (Mode::Bookmarks, &["?"], ShowHelp),
+            (Mode::Bookmarks, &["esc", "'"], Back),
+            (Mode::Bookmarks, &["q"], Quit),
+            (Mode::Recent, &["up", "k"], ScrollRows(-1)),
+            (Mode::Recent, &["down", "j"], ScrollRows(1)),
+            (Mode::Recent, &["enter"], Activate),
+            (Mode::Recent, &["d", "delete"], Delete),
+            (Mode::Recent, &["o"], PromptOpen),
+            (Mode::Recent, &[":"], OpenCommandLine),
+            (Mode::Recent, &["?"], ShowHelp),
+            (Mode::Recent, &["esc"], Back),
+            (Mode::Recent, &["q"], Quit),
+            (Mode::History, &["up", "k"], ScrollRows(-1)),
+            (Mode::History, &["down", "j"], ScrollRows(1)),
+            (Mode::History, &["enter"], Activate),
+            (Mode::History, &[":"], OpenCommandLine),
+            (Mode::History, &["?"], ShowHelp),
+            (Mode::History, &["esc"], Back),
+            (Mode::History, &["q"], Quit),
+            (Mode::Scratchpad, &["up", "k"], ScrollRows(-1)),
+            (Mode::Scratchpad, &["down", "j"], ScrollRows(1)),
+            (Mode::Scratchpad, &["shift-up", "K"], MovePin(-1)),
+            (Mode::Scratchpad, &["shift-down", "J"], MovePin(1)),
+            (Mode::Scratchpad, &["enter", "i"], Activate),
+            (Mode::Scratchpad, &["y"], Yank),
+            (Mode::Scratchpad, &["d", "delete"], Delete),
+            (Mode::Scratchpad, &["?"], ShowHelp),
+            (Mode::Scratchpad, &["esc", "\"", "ctrl-p"], Back),
+            (Mode::Neighborhood, &["up", "k"], ScrollRows(-1)),
+            (Mode::Neighborhood, &["down", "j"], ScrollRows(1)),
+            (Mode::Neighborhood, &["right", "l"], ExpandNode),
+            (Mode::Neighborhood, &["left", "h"], CollapseNode),
+            (Mode::Neighborhood, &["R"], RefreshPanel),
+            (Mode::Neighborhood, &["enter"], Activate),
+            (Mode::Neighborhood, &["d"], ShowDetails),
+            (Mode::Neighborhood, &["m"], BookmarkRow),
+            (Mode::Neighborhood, &["'"], OpenBookmarks),
+            (Mode::Neighborhood, &[":"], OpenCommandLine),
+            (Mode::Neighborhood, &["?"], ShowHelp),
+            (Mode::Neighborhood, &["esc", "tab"], Back),
+            (Mode::Neighborhood, &["q"], Quit),
+            (Mode::Neighborhood, &["@"], PromptPlayMacro),
+            (Mode::Pattern, &["tab"], CycleField(1)),
+            (Mode::Pattern, &["shift-tab"], CycleField(-1)),
+            (Mode::Pattern, &["enter"], Activate),
+            (Mode::Pattern, &["esc"], Back),
+            (Mode::Pattern, &["backspace"], DeleteBackward),
+            (Mode::Pattern, &["ctrl-space"], Complete),
+            (Mode::Command, &["tab"], Complete),
+            (Mode::Command, &["ctrl-l"], ListCompletions),
+            (Mode::Command, &["enter"], Activate),
+            (Mode::Command, &["esc"], Back),
+            (Mode::Command, &["backspace"], DeleteBackward),
+            (Mode::Filter, &["enter"], Activate),
+            (Mode::Filter, &["esc"], Back),
+            (Mode::Filter, &["backspace"], DeleteBackward),
+            (Mode::Filter, &["ctrl-r"], ToggleRegex),
+            (Mode::Edit, &["enter"], Activate),
+            (Mode::Edit, &["esc"], Back),
+            (Mode::Edit, &["backspace"], DeleteBackward),
+        ];
+        // 輸入行以外，各部分皆可移焦點、錄宏
+        let panes = [
+            Mode::Browse,
+            Mode::Query,
+            Mode::Pattern,
+            Mode::Hierarchy,
+            Mode::Neighborhood,
+            Mode::Bookmarks,
+            Mode::Scratchpad,
+            Mode::Recent,
+            Mode::History,
+        ];
+        let focus: &[(&[&str], Action)] = &[
+            (&["ctrl-up"], MoveFocus(-1)),
+            (&["ctrl-down"], MoveFocus(1)),
+            (&["f6"], CycleFocus(1)),
+            (&["shift-f6"], CycleFocus(-1)),
+            (&["ctrl-q"], ToggleMacroRecording),
+        ];
+        let focus = panes.iter().flat_map(|mode| {
+            focus
+                .iter()
+                .map(|(keys, action)| (*mode, *keys, action.clone()))
+        });
+        for (mode, keys, action) in defaults.iter().cloned().chain(focus) {
+            for keys in keys {
+                let keys = parse_sequence(keys).expect("default key binding");
+                keymap.bindings.push(Binding {
+                    mode,
+                    keys,
+                    action: action.clone(),
+                });
+            }
+        }
+        if keyscheme == Keyscheme::Vim {
+            keymap.bindings.push(Binding {
+                mode: Mode::Query,
+                keys: vec![KeyChord::new(KeyCode::Esc, KeyModifiers::NONE)],
+                action: SwitchMode,
+            });
+        }
+        keymap
+    }
+
+    /// 以 `sequences` 替換某模式下某操作之綁定。此等按鍵原有之綁定一併移除。
+    /// `sequences` 為空即解除綁定。
+    pub fn bind(&mut self, mode: Mode, action: Action, sequences: Vec<KeySequence>) {
+        self.bindings.retain(|binding| {
+            binding.mode != mode || (binding.action != action && !sequences.contains(&binding.keys))
+        });
+        self.bindings
+            .extend(sequences.into_iter().map(|keys| Binding {
+                mode,
+                keys,
+                action: action.clone(),
+            }));
+    }
+
+    /// 終端不支持 kitty 鍵盤協議時，為含 `FALLBACKS` 中按鍵之綁定加其代替者，
+    /// 並移此等綁定於後，使提示欄示可按之鍵。代替者已另有綁定則不加。
+    pub fn add_fallbacks(&mut self) {
+        let fallbacks: Vec<(KeyChord, KeyChord)> = FALLBACKS
+            .iter()
+            .map(|(key, fallback)| {
+                let parse = |key| KeyChord::parse(key).expect("fallback key");
+                (parse(key), parse(fallback))
+            })
+            .collect();
+        let replace = |chord: &KeyChord| {
+            fallbacks
+                .iter()
+                .find(|(key, _)| key == chord)
+                .map(|(_, fallback)| *fallback)
+        };
+        let needs_enhancement =
+            |binding: &Binding| binding.keys.iter().any(|k| replace(k).is_some());
+        let mut added = vec![];
+        for binding in self.bindings.iter().filter(|b| needs_enhancement(b)) {
+            let keys: KeySequence = binding
+                .keys
+                .iter()
+                .map(|chord| replace(chord).unwrap_or(*chord))
+                .collect();
+            let bound = self
+                .bindings
+                .iter()
+                .chain(&added)
+                .any(|other| other.mode == binding.mode && other.keys == keys);
+            if !bound {
+                added.push(Binding {
+                    mode: binding.mode,
+                    keys,
+                    action: binding.action.clone(),
+                });
+            }
+        }
+        self.bindings.extend(added);
+        self.bindings
+            .sort_by_key(|binding| needs_enhancement(binding));
+    }
+
+    /// 將按鍵譯為操作。`pending` 為已按而未成綁定之序列，隨之更新；`Esc` 棄之。
+    /// 序列中斷時，其已成綁定之前段先執行，末鍵重新查找，故或得二操作。
+    /// 未綁定之字符鍵於查詢、模式表單與命令行中為輸入。
+    pub fn map_key(&self, mode: Mode, pending: &mut KeySequence, key: KeyEvent) -> Vec<Action> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            // 原始模式下 Ctrl+C 不產生 SIGINT，於此視同退出
+            KeyCode::Char('c') if ctrl => {
+                pending.clear();
+                return vec![Action::Quit];
+            }
+            // 同理 Ctrl+Z 不產生 SIGTSTP
+            KeyCode::Char('z') if ctrl => {
+                pending.clear();
+                return vec![Action::Suspend];
+            }
+            KeyCode::Esc if !pending.is_empty() => {
+                pending.clear();
+                return vec![];
+            }
+            _ => {}
+        }
+        pending.push(KeyChord::from(key));
+        match self.lookup(mode, pending) {
+            Lookup::Action(action) => {
+                pending.clear();
+                vec![action]
+            }
+            Lookup::Prefix => vec![],
+            Lookup::None => {
+                let broken = std::mem::take(pending);
+                if broken.len() > 1 {
+                    let mut actions: Vec<Action> = self
+                        .resolve(mode, &broken[..broken.len() - 1])
+                        .into_iter()
+                        .collect();
+                    actions.extend(self.map_key(mode, pending, key));
+                    return actions;
+                }
+                match key.code {
+                    KeyCode::Char(ch)
+                        if matches!(
+                            mode,
+                            Mode::Query | Mode::Pattern | Mode::Command | Mode::Filter | Mode::Edit
+                        ) && !key
+                            .modifiers
+                            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                    {
+                        vec![Action::InsertChar(ch)]
+                    }
+                    _ => vec![],
+                }
+            }
+        }
+    }
+
+    /// 查找已按之序列。既成綁定又為更長者之前綴，如 `d` 與 `d d`，則為前綴，
+    /// 待後續按鍵或逾時後以 [`Keymap::resolve`] 取之。
+    pub fn lookup(&self, mode: Mode, pending: &[KeyChord]) -> Lookup {
+        let mut prefix = false;
+        for binding in self.bindings.iter().filter(|b| b.mode == mode) {
+            prefix |= binding.keys.len() > pending.len() && binding.keys.starts_with(pending);
+        }
+        match self.resolve(mode, pending) {
+            _ if prefix => Lookup::Prefix,
+            Some(action) => Lookup::Action(action),
+            None => Lookup::None,
+        }
+    }
+
+    /// 恰綁定此序列之操作。
+    pub fn resolve(&self, mode: Mode, pending: &[KeyChord]) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|b| b.mode == mode && b.keys == pending)
+            .map(|binding| binding.action.clone())
+    }
+
+    /// 某模式下提示欄之按鍵與簡稱。各操作取其首個按鍵，未綁定者略去。
+    pub fn hints(&self, mode: Mode) -> Vec<(String, &'static str)> {
+        HINTS
+            .iter()
+            .filter(|(m, _, _)| *m == mode)
+            .filter_map(|(_, action, label)| Some((self.key(mode, action)?, *label)))
+            .collect()
+    }
+
+    /// 某模式下操作之首個綁定，如 `ctrl-r`。未綁定則無。
+    pub fn key(&self, mode: Mode, action: &Action) -> Option<String> {
+        let binding = self
+            .bindings
+            .iter()
+            .find(|b| b.mode == mode && b.action == *action)?;
+        Some(
+            binding
+                .keys
+                .iter()
+                .map(KeyChord::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    }
+
+    /// 某模式下各操作及其按鍵，依首次綁定之序。
+    pub fn bindings(&self, mode: Mode) -> Vec<(Action, Vec<String>)> {
+        let mut actions: Vec<(Action, Vec<String>)> = vec![];
+        for binding in self.bindings.iter().filter(|b| b.mode == mode) {
+            let keys = binding
+                .keys
+                .iter()
+                .map(KeyChord::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            match actions.iter_mut().find(|(a, _)| *a == binding.action) {
+                Some((_, all)) => all.push(keys),
+                None => actions.push((binding.action.clone(), vec![keys])),
+            }
+        }
+        actions
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(Keyscheme::Default)
+    }
+}
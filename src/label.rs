@@ -0,0 +1,199 @@
+use oxigraph::{
+    model::{NamedNode, Term},
+    sparql::QueryResults,
+    store::Store,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// 一批所問之 IRI 數之上限。
+pub const BATCH: usize = 256;
+
+/// 可見之行前後另解其標籤之行數，滾動時標籤已在。
+pub const LOOKAHEAD: usize = 50;
+
+/// 可作標籤之謂詞路徑。
+pub const LABEL_PATH: &str =
+    "<http://www.w3.org/2000/01/rdf-schema#label>|<http://www.w3.org/2004/02/skos/core#prefLabel>";
+
+/// 標籤語言之優先序，愈小愈優：合所好之語言、無語言標記、其他。
+pub fn label_rank(lang: Option<&str>, preferred: Option<&str>) -> u8 {
+    match (lang, preferred) {
+        (Some(lang), Some(preferred)) if lang.eq_ignore_ascii_case(preferred) => 0,
+        (None, _) => 1,
+        _ => 2,
+    }
+}
+
+/// 查詢 `iri` 之首選標籤。
+pub fn resolve_label(store: &Store, iri: &NamedNode, lang: Option<&str>) -> Option<String> {
+    let query = format!("SELECT ?label WHERE {{ {iri} {LABEL_PATH} ?label }}");
+    let QueryResults::Solutions(solutions) = store.query(query.as_str()).ok()? else {
+        return None;
+    };
+    solutions
+        .filter_map(|s| match s.ok()?.get("label") {
+            Some(Term::Literal(label)) => Some((
+                label_rank(label.language(), lang),
+                label.value().to_string(),
+            )),
+            _ => None,
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, label)| label)
+}
+
+/// 已解之標籤，結果表與導出共用。無標籤者記為 `None`，不再問之。存儲有變則清之。
+#[derive(Debug, Clone, Default)]
+pub struct LabelCache(Arc<Mutex<HashMap<NamedNode, Option<String>>>>);
+
+impl LabelCache {
+    /// `iri` 之標籤。未解者為 `None`。
+    pub fn get(&self, iri: &NamedNode) -> Option<Option<String>> {
+        self.lock().get(iri).cloned()
+    }
+
+    /// 諸項中未解之 IRI，去重，依其先後，至多 `limit` 個。
+    pub fn missing<'a>(
+        &self,
+        terms: impl IntoIterator<Item = &'a Term>,
+        limit: usize,
+    ) -> Vec<NamedNode> {
+        let cache = self.lock();
+        let mut seen = HashSet::new();
+        terms
+            .into_iter()
+            .filter_map(|term| match term {
+                Term::NamedNode(iri) if !cache.contains_key(iri) && seen.insert(iri) => {
+                    Some(iri.clone())
+                }
+                _ => None,
+            })
+            .take(limit)
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// 以一 `VALUES` 查詢解一批 IRI 之標籤，無標籤者亦記之。返回得標籤者之數。
+    pub fn resolve(
+        &self,
+        store: &Store,
+        iris: &[NamedNode],
+        lang: Option<&str>,
+    ) -> Result<usize, String> {
+        let values: String = iris.iter().map(|iri| format!("{iri} ")).collect();
+        let query = format!(
+            "SELECT ?iri ?label WHERE {{ VALUES ?iri {{ {values}}} ?iri {LABEL_PATH} ?label }}"
+        );
+        let QueryResults::Solutions(solutions) =
+            store.query(query.as_str()).map_err(|e| e.to_string())?
+        else {
+            return Ok(0);
+        };
+        let mut best: HashMap<NamedNode, (u8, String)> = HashMap::new();
+        for solution in solutions {
+            let solution = solution.map_err(|e| e.to_string())?;
+            let (Some(Term::NamedNode(iri)), Some(Term::Literal(label))) =
+                (solution.get("iri"), solution.get("label"))
+            else {
+                continue;
+            };
+            let rank = label_rank(label.language(), lang);
+            if best.get(iri).is_none_or(|(best, _)| rank < *best) {
+                best.insert(iri.clone(), (rank, label.value().to_string()));
+            }
+        }
+        let found = best.len();
+        let mut cache = self.lock();
+        for iri in iris {
+            cache.insert(iri.clone(), best.remove(iri).map(|(_, label)| label));
+        }
+        Ok(found)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<NamedNode, Option<String>>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 後台所解之一批標籤。一次僅一批，滾動中所經之行不各發一查詢。
+pub struct Prefetch {
+    receiver: Receiver<Result<usize, String>>,
+}
+
+impl Prefetch {
+    pub fn spawn(
+        store: &Store,
+        cache: &LabelCache,
+        iris: Vec<NamedNode>,
+        lang: Option<String>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (store, cache) = (store.clone(), cache.clone());
+        thread::spawn(move || {
+            let _ = sender.send(cache.resolve(&store, &iris, lang.as_deref()));
+        });
+        Self { receiver }
+    }
+
+    /// 已解畢則返回其結果，不等待。
+    pub fn poll(&self) -> Option<Result<usize, String>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::RdfFormat;
+
+    #[test]
+    fn labels_are_resolved_in_batches_and_cached() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfFormat::Turtle,
+                r#"@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+                <http://ex/a> rdfs:label "Alpha", "Alfa"@it .
+                <http://ex/b> <http://www.w3.org/2004/02/skos/core#prefLabel> "Beta"@en ."#
+                    .as_bytes(),
+            )
+            .unwrap();
+        let iri = |name: &str| NamedNode::new_unchecked(format!("http://ex/{name}"));
+        let terms: Vec<Term> = ["a", "b", "a", "c"].map(|name| iri(name).into()).to_vec();
+        let cache = LabelCache::default();
+        let missing = cache.missing(&terms, BATCH);
+        assert_eq!(missing, [iri("a"), iri("b"), iri("c")]);
+        assert_eq!(cache.resolve(&store, &missing, Some("it")), Ok(2));
+        assert_eq!(cache.get(&iri("a")), Some(Some("Alfa".to_string())));
+        assert_eq!(cache.get(&iri("b")), Some(Some("Beta".to_string())));
+        // 無標籤者亦記之，不再問
+        assert_eq!(cache.get(&iri("c")), Some(None));
+        assert!(cache.missing(&terms, BATCH).is_empty());
+        assert_eq!(LabelCache::default().missing(&terms, 1), [iri("a")]);
+        assert_eq!(cache.get(&iri("d")), None);
+
+        let prefetch = Prefetch::spawn(&store, &cache.clone(), vec![iri("d")], None);
+        let result = loop {
+            if let Some(result) = prefetch.poll() {
+                break result;
+            }
+            thread::yield_now();
+        };
+        assert_eq!(result, Ok(0));
+        assert_eq!(cache.get(&iri("d")), Some(None));
+        cache.clear();
+        assert_eq!(cache.get(&iri("a")), None);
+    }
+}
@@ -0,0 +1,370 @@
+//! 窗格之佈局預設：何窗格可見，及其約束。`App::render_app` 依 [`arrange`] 所排者繪之，
+//! 不另調 `Layout`，故各終端尺寸下之排版可不經渲染而測之。
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use std::fmt;
+
+/// 並排之旁窗格所需之最小總寬。窄於此則上下排列。
+pub const BESIDE_MIN_WIDTH: u16 = 60;
+
+/// 窗格。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Tabs,
+    Query,
+    /// 結果表，或代之之面板，如層級樹。
+    Main,
+    /// 所選格之預覽。
+    Preview,
+    /// 結果與存儲之統計。
+    Stats,
+    Status,
+    Hints,
+}
+
+/// 窗格之約束。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    /// 依內容，唯查詢用之。
+    Auto,
+    /// 行數，並排者則為列數。
+    Cells(u16),
+    /// 佔主部分之百分比。
+    Percent(u16),
+}
+
+impl Size {
+    fn parse(text: &str) -> Result<Self, String> {
+        let number = |digits: &str| {
+            digits
+                .parse()
+                .map_err(|_| format!("invalid size `{text}` (auto, a number or a percentage)"))
+        };
+        match text {
+            "auto" => Ok(Self::Auto),
+            text => match text.strip_suffix('%') {
+                Some(percent) => match number(percent)? {
+                    percent @ 1..=90 => Ok(Self::Percent(percent)),
+                    _ => Err(format!("percentage `{text}` out of 1%-90%")),
+                },
+                None => number(text).map(Self::Cells),
+            },
+        }
+    }
+
+    fn constraint(self, auto: u16) -> Constraint {
+        match self {
+            Self::Auto => Constraint::Length(auto),
+            Self::Cells(cells) => Constraint::Length(cells),
+            Self::Percent(percent) => Constraint::Percentage(percent),
+        }
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => f.write_str("auto"),
+            Self::Cells(cells) => write!(f, "{cells}"),
+            Self::Percent(percent) => write!(f, "{percent}%"),
+        }
+    }
+}
+
+/// 一預設：查詢之高，及結果旁之窗格。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    /// 查詢窗格之高。`None` 則不示，唯編輯查詢或三元組模式時示之。
+    pub query: Option<Size>,
+    /// 結果旁之窗格及其約束，依序排列。
+    pub panes: Vec<(Pane, Size)>,
+    /// 旁窗格在結果之右；否則在其下。
+    pub beside: bool,
+}
+
+impl Preset {
+    /// 解析如 `query:30%, preview:40%, beside` 之描述。
+    pub fn parse(name: &str, spec: &str) -> Result<Self, String> {
+        let mut preset = Self {
+            name: name.to_string(),
+            query: Some(Size::Auto),
+            panes: vec![],
+            beside: false,
+        };
+        for item in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            match item
+                .split_once(':')
+                .map(|(key, value)| (key.trim(), value.trim()))
+            {
+                Some(("query", "hidden")) => preset.query = None,
+                Some(("query", size)) => preset.query = Some(Size::parse(size)?),
+                Some((pane @ ("preview" | "stats"), size)) => {
+                    let pane = match pane {
+                        "preview" => Pane::Preview,
+                        _ => Pane::Stats,
+                    };
+                    match Size::parse(size)? {
+                        Size::Auto => return Err(format!("{pane:?} needs a fixed size")),
+                        size => {
+                            preset.panes.retain(|(known, _)| *known != pane);
+                            preset.panes.push((pane, size));
+                        }
+                    }
+                }
+                None if item == "beside" => preset.beside = true,
+                None if item == "below" => preset.beside = false,
+                _ => {
+                    return Err(format!(
+                        "unknown layout item `{item}` (query:<size>|hidden, preview:<size>, \
+                         stats:<size>, beside, below)"
+                    ))
+                }
+            }
+        }
+        Ok(preset)
+    }
+
+    /// 反 [`Self::parse`]。
+    pub fn spec(&self) -> String {
+        let mut items = vec![match self.query {
+            Some(size) => format!("query:{size}"),
+            None => "query:hidden".to_string(),
+        }];
+        for (pane, size) in &self.panes {
+            let name = match pane {
+                Pane::Preview => "preview",
+                _ => "stats",
+            };
+            items.push(format!("{name}:{size}"));
+        }
+        if self.beside {
+            items.push("beside".to_string());
+        }
+        items.join(", ")
+    }
+}
+
+/// 內置之預設，首者為默認。
+pub const BUILTIN: &[(&str, &str)] = &[
+    ("default", "query:auto"),
+    ("editing", "query:60%"),
+    ("triage", "query:hidden"),
+    ("explore", "query:auto, preview:40%, beside"),
+    ("monitor", "query:auto, stats:30%, beside"),
+];
+
+/// 內置之預設，繼以配置之 `[layouts]`；同名者代內置者。
+pub fn presets(configured: &[Preset]) -> Vec<Preset> {
+    let mut presets: Vec<Preset> = BUILTIN
+        .iter()
+        .map(|(name, spec)| Preset::parse(name, spec).expect("builtin layout"))
+        .collect();
+    for preset in configured {
+        match presets.iter_mut().find(|known| known.name == preset.name) {
+            Some(known) => *known = preset.clone(),
+            None => presets.push(preset.clone()),
+        }
+    }
+    presets
+}
+
+/// 排版所需之當前狀態。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chrome {
+    /// 多於一頁則示標籤頁條。
+    pub tabs: bool,
+    /// 查詢依內容之高。
+    pub query_height: u16,
+    /// 編輯查詢或三元組模式中，查詢隱者亦示之。
+    pub editing: bool,
+    /// 主部分示結果表，非代之之面板。旁窗格唯此時示之。
+    pub table: bool,
+    /// 以 `v` 開預覽。預設無之則加於結果之下。
+    pub preview: bool,
+    pub hints: bool,
+}
+
+/// 依預設分 `area` 為各窗格，自上而下、自左而右。不示者不列。
+pub fn arrange(preset: &Preset, area: Rect, chrome: Chrome) -> Vec<(Pane, Rect)> {
+    use Constraint::{Fill, Length};
+    let query = match preset.query {
+        Some(size) => Some(size),
+        None if chrome.editing => Some(Size::Auto),
+        None => None,
+    };
+    let outer = Layout::vertical([
+        Length(chrome.tabs as u16),
+        query.map_or(Length(0), |size| size.constraint(chrome.query_height)),
+        Fill(1),
+        Length(1),
+        Length(chrome.hints as u16),
+    ])
+    .split(area);
+    let mut areas = vec![];
+    if chrome.tabs {
+        areas.push((Pane::Tabs, outer[0]));
+    }
+    if query.is_some() {
+        areas.push((Pane::Query, outer[1]));
+    }
+    let main = outer[2];
+    let mut panes = match chrome.table {
+        true => preset.panes.clone(),
+        false => vec![],
+    };
+    if chrome.table && chrome.preview && !panes.iter().any(|(pane, _)| *pane == Pane::Preview) {
+        panes.push((Pane::Preview, Size::Cells(PREVIEW_HEIGHT)));
+    }
+    let beside = preset.beside && main.width >= BESIDE_MIN_WIDTH;
+    // 上下排列者之列數不可為行數
+    let constraints = std::iter::once(Fill(1)).chain(panes.iter().map(|(_, size)| match size {
+        Size::Cells(_) if preset.beside && !beside => Length(PREVIEW_HEIGHT),
+        size => size.constraint(0),
+    }));
+    let parts = match beside {
+        true => Layout::horizontal(constraints).split(main),
+        false => Layout::vertical(constraints).split(main),
+    };
+    areas.push((Pane::Main, parts[0]));
+    areas.extend(
+        panes
+            .iter()
+            .zip(&parts[1..])
+            .map(|((pane, _), rect)| (*pane, *rect)),
+    );
+    areas.push((Pane::Status, outer[3]));
+    if chrome.hints {
+        areas.push((Pane::Hints, outer[4]));
+    }
+    areas
+}
+
+/// 預覽窗格之高度，含邊框。
+pub const PREVIEW_HEIGHT: u16 = 8;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(areas: &[(Pane, Rect)], pane: Pane) -> Option<Rect> {
+        areas
+            .iter()
+            .find(|(known, _)| *known == pane)
+            .map(|(_, rect)| *rect)
+    }
+
+    #[test]
+    fn presets_arrange_their_panes_at_every_size() {
+        let presets = presets(&[]);
+        let preset = |name: &str| presets.iter().find(|p| p.name == name).unwrap();
+        let chrome = Chrome {
+            query_height: 5,
+            table: true,
+            hints: true,
+            ..Chrome::default()
+        };
+        let area = Rect::new(0, 0, 100, 30);
+
+        let default = arrange(preset("default"), area, chrome);
+        let panes: Vec<Pane> = default.iter().map(|(pane, _)| *pane).collect();
+        assert_eq!(panes, [Pane::Query, Pane::Main, Pane::Status, Pane::Hints]);
+        assert_eq!(find(&default, Pane::Query).unwrap().height, 5);
+        assert_eq!(find(&default, Pane::Main).unwrap().height, 23);
+
+        let editing = arrange(preset("editing"), area, chrome);
+        assert_eq!(find(&editing, Pane::Query).unwrap().height, 18);
+
+        // 查詢隱者編輯時仍示之
+        let triage = arrange(preset("triage"), area, chrome);
+        assert_eq!(find(&triage, Pane::Query), None);
+        assert_eq!(find(&triage, Pane::Main).unwrap().height, 28);
+        let editing_triage = arrange(
+            preset("triage"),
+            area,
+            Chrome {
+                editing: true,
+                ..chrome
+            },
+        );
+        assert_eq!(find(&editing_triage, Pane::Query).unwrap().height, 5);
+
+        let explore = arrange(preset("explore"), area, chrome);
+        let (main, preview) = (
+            find(&explore, Pane::Main).unwrap(),
+            find(&explore, Pane::Preview).unwrap(),
+        );
+        assert_eq!((main.width, preview.width, preview.x), (60, 40, 60));
+        assert_eq!(main.height, preview.height);
+        // 窄則上下排列；旁窗格唯示結果表時有之
+        let narrow = arrange(preset("explore"), Rect::new(0, 0, 50, 30), chrome);
+        let preview = find(&narrow, Pane::Preview).unwrap();
+        assert_eq!((preview.width, preview.y), (50, 19));
+        let panel = arrange(
+            preset("monitor"),
+            area,
+            Chrome {
+                table: false,
+                ..chrome
+            },
+        );
+        assert_eq!(find(&panel, Pane::Stats), None);
+
+        // `v` 之預覽加於預設之下，而不重複
+        let toggled = arrange(
+            preset("monitor"),
+            area,
+            Chrome {
+                preview: true,
+                ..chrome
+            },
+        );
+        assert!(find(&toggled, Pane::Stats).is_some() && find(&toggled, Pane::Preview).is_some());
+        let toggled = arrange(
+            preset("explore"),
+            area,
+            Chrome {
+                preview: true,
+                ..chrome
+            },
+        );
+        assert_eq!(
+            toggled
+                .iter()
+                .filter(|(pane, _)| *pane == Pane::Preview)
+                .count(),
+            1
+        );
+
+        // 極矮亦不出錯
+        for height in 0..8 {
+            arrange(preset("editing"), Rect::new(0, 0, 20, height), chrome);
+        }
+    }
+
+    #[test]
+    fn presets_parse_and_print_their_spec() {
+        let preset = Preset::parse("wide", "query:hidden, stats:12, preview:25%, beside").unwrap();
+        assert_eq!(preset.query, None);
+        assert_eq!(
+            preset.panes,
+            [
+                (Pane::Stats, Size::Cells(12)),
+                (Pane::Preview, Size::Percent(25))
+            ]
+        );
+        assert_eq!(Preset::parse("wide", &preset.spec()).unwrap(), preset);
+        assert!(Preset::parse("x", "query:auto, table:3").is_err());
+        assert!(Preset::parse("x", "preview:auto").is_err());
+        assert!(Preset::parse("x", "preview:95%").is_err());
+
+        let custom = Preset::parse("default", "query:10").unwrap();
+        let presets = presets(&[custom.clone(), Preset::parse("mine", "").unwrap()]);
+        assert_eq!(presets[0], custom);
+        assert_eq!(presets.last().unwrap().name, "mine");
+    }
+}
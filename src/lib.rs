@@ -0,0 +1,107 @@
+//! rdf-tui 之庫：以 [`App`] 於終端中瀏覽 RDF 數據、編輯 SPARQL 查詢。
+//!
+//! 可嵌入他 ratatui 應用：宿主各幀以 [`App::render`] 繪於一區域，以
+//! [`App::handle_event`] 轉交輸入。見 `examples/embedded.rs`。
+//!
+//! 不經界面者，可以 [`App::query_iter`] 逐個取查詢之解，或以 [`backend::StoreWorker`]
+//! 之命令與事件操作存儲。
+
+pub mod action;
+mod aggregate;
+pub mod app;
+pub mod backend;
+mod background;
+mod bookmarks;
+mod chardiff;
+mod check;
+mod checkpoint;
+mod command;
+pub mod config;
+mod contain;
+mod count;
+mod dataset;
+mod definition;
+mod diff;
+mod display;
+pub mod doctor;
+pub mod download;
+mod dupes;
+mod edit;
+mod example;
+mod export;
+mod filter;
+#[cfg(any(test, feature = "testutil"))]
+pub mod fixture;
+pub mod focus;
+mod functional;
+mod generation;
+mod graphs;
+mod guard;
+mod header;
+#[doc(hidden)]
+pub mod headless;
+mod hierarchy;
+mod history;
+mod http;
+mod imports;
+mod incoming;
+mod inspect;
+mod intern;
+mod keymap;
+mod label;
+mod layout;
+mod library;
+mod linear;
+mod link;
+mod loader;
+pub mod locale;
+#[doc(hidden)]
+pub mod logging;
+mod macros;
+mod markup;
+mod memory;
+mod metrics;
+mod neighborhood;
+mod onboarding;
+mod opener;
+pub mod overwrite;
+mod path_input;
+pub mod paths;
+mod pattern;
+mod prepared;
+pub mod profile;
+mod provenance;
+mod recent;
+pub mod record;
+mod refresh;
+mod rename;
+pub mod renderer;
+mod saved;
+mod scratchpad;
+pub mod script;
+mod selection;
+mod server;
+mod service;
+pub mod session;
+#[doc(hidden)]
+pub mod signal;
+pub mod sink;
+pub mod solutions;
+mod startup;
+mod tabdo;
+mod theme;
+mod tile;
+mod types;
+mod typography;
+#[doc(hidden)]
+pub mod util;
+mod watch;
+mod widths;
+mod worker;
+mod yank;
+
+pub use action::Action;
+pub use app::{App, Mode, Query, ResultTable};
+pub use config::Config;
+pub use renderer::CellRenderer;
+pub use solutions::{QueryOptions, SolutionStream};
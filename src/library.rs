@@ -0,0 +1,261 @@
+use crate::{
+    bookmarks::View,
+    config::{parse_toml, quote, Value},
+    paths::create_dir,
+};
+use anyhow::{bail, Context};
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// 庫之索引。其各節以 `.rq` 文件名（去擴展名）為名，記其名、說明與標籤。
+pub const INDEX: &str = "index.toml";
+
+/// 查詢庫中之一查詢。庫為一目錄：每查詢一 `.rq` 文件，另有索引，可隨數據存於版本庫中。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    /// 文件之原文，註釋與換行皆不改。
+    pub query: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl Entry {
+    pub fn from_view(view: &View) -> Self {
+        Self {
+            name: view.name.clone(),
+            query: view.query.clone(),
+            description: view.description.clone(),
+            tags: view.tags.clone(),
+        }
+    }
+
+    /// 書籤之查詢無名，以其首行之註釋為名，無則為 `query`。
+    pub fn from_query(query: &str) -> Self {
+        let name = query
+            .lines()
+            .next()
+            .and_then(|line| line.trim().strip_prefix('#'))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or("query");
+        Self {
+            name: name.to_string(),
+            query: query.to_string(),
+            description: None,
+            tags: vec![],
+        }
+    }
+
+    /// 導入為視圖，顯示之設置皆默認。
+    pub fn into_view(self) -> View {
+        View {
+            name: self.name,
+            query: self.query,
+            filter: None,
+            column: None,
+            origin: false,
+            preview: false,
+            limit: None,
+            lang: None,
+            description: self.description,
+            tags: self.tags,
+            sort: vec![],
+        }
+    }
+}
+
+/// 讀一庫：先依索引之序，再以名排未列入索引之 `.rq` 文件，其名即文件名。
+pub fn read(dir: &Path) -> anyhow::Result<Vec<Entry>> {
+    let index = dir.join(INDEX);
+    let mut entries = vec![];
+    let mut listed = HashSet::new();
+    match fs::read_to_string(&index) {
+        Ok(text) => {
+            for (stem, mut entry) in
+                parse_index(&text).with_context(|| format!("{}", index.display()))?
+            {
+                let file = dir.join(format!("{stem}.rq"));
+                entry.query = fs::read_to_string(&file).with_context(|| {
+                    format!("{} is listed in {INDEX} but unreadable", file.display())
+                })?;
+                listed.insert(file);
+                entries.push(entry);
+            }
+        }
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error).with_context(|| format!("{}", index.display())),
+    }
+    let mut unlisted: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("{}", dir.display()))?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rq") && !listed.contains(path))
+        .collect();
+    unlisted.sort();
+    for file in unlisted {
+        let name = file
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        entries.push(Entry {
+            name,
+            query: fs::read_to_string(&file).with_context(|| format!("{}", file.display()))?,
+            description: None,
+            tags: vec![],
+        });
+    }
+    Ok(entries)
+}
+
+/// 索引之各節：文件名及其所記，查詢待讀。
+fn parse_index(text: &str) -> anyhow::Result<Vec<(String, Entry)>> {
+    let mut sections: Vec<(String, Entry)> = vec![];
+    for entry in parse_toml(text)? {
+        let line = entry.line;
+        let stem = entry.section.trim_matches('"').to_string();
+        if stem.is_empty() {
+            bail!("line {line}: `{}` must be in a [file] section", entry.key);
+        }
+        if sections.last().is_none_or(|(last, _)| *last != stem) {
+            sections.push((
+                stem.clone(),
+                Entry {
+                    name: stem.clone(),
+                    query: String::new(),
+                    description: None,
+                    tags: vec![],
+                },
+            ));
+        }
+        let (_, section) = sections.last_mut().unwrap_or_else(|| unreachable!());
+        let key = entry.key;
+        let string = |value: Value| {
+            value
+                .string(&key)
+                .map_err(|error| anyhow::anyhow!("line {line}: {error}"))
+        };
+        match key.as_str() {
+            "name" => section.name = string(entry.value)?,
+            "description" => section.description = Some(string(entry.value)?),
+            "tags" => {
+                let Value::Array(tags) = entry.value else {
+                    bail!("line {line}: `tags` must be an array of strings");
+                };
+                section.tags = tags.into_iter().map(string).collect::<Result<_, _>>()?;
+            }
+            _ => bail!("line {line}: unknown key `{key}` (name, description, tags)"),
+        }
+    }
+    Ok(sections)
+}
+
+/// 寫一庫：各查詢之原文為一 `.rq` 文件，並寫索引。目錄中已有之同名文件代之，他文件不動。
+pub fn write(dir: &Path, entries: &[Entry]) -> anyhow::Result<()> {
+    create_dir(dir)?;
+    let mut index =
+        "# Queries of this library: each section names a .rq file beside this index.\n".to_string();
+    let mut stems = HashSet::new();
+    for entry in entries {
+        let base = file_stem(&entry.name);
+        let stem = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{base}-{n}"),
+            })
+            .find(|stem| !stems.contains(stem))
+            .unwrap_or_else(|| unreachable!());
+        let file = dir.join(format!("{stem}.rq"));
+        fs::write(&file, &entry.query).with_context(|| format!("{}", file.display()))?;
+        let _ = write!(index, "\n[{stem}]\nname = {}\n", quote(&entry.name));
+        if let Some(description) = &entry.description {
+            let _ = writeln!(index, "description = {}", quote(description));
+        }
+        if !entry.tags.is_empty() {
+            let tags: Vec<String> = entry.tags.iter().map(|tag| quote(tag)).collect();
+            let _ = writeln!(index, "tags = [{}]", tags.join(", "));
+        }
+        stems.insert(stem);
+    }
+    let path = dir.join(INDEX);
+    fs::write(&path, index).with_context(|| format!("{}", path.display()))
+}
+
+/// 名之文件名：字母、數字、`-` 與 `_` 留之，他字符為 `-`。
+fn file_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for ch in name.chars() {
+        match ch {
+            ch if ch.is_ascii_alphanumeric() || ch == '_' => stem.push(ch),
+            _ if stem.ends_with('-') || stem.is_empty() => {}
+            _ => stem.push('-'),
+        }
+    }
+    match stem.trim_end_matches('-') {
+        "" => "query".to_string(),
+        stem => stem.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn libraries_round_trip_queries_byte_for_byte() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-library-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let entries = vec![
+            Entry {
+                name: "People by age".to_string(),
+                query: "# oldest first\r\nSELECT ?p ?age WHERE {\r\n  ?p ex:age ?age # \"years\"\r\n}\r\nORDER BY DESC(?age)\n\n".to_string(),
+                description: Some("Everyone with an \"age\",\tsorted".to_string()),
+                tags: vec!["people".to_string(), "démo".to_string()],
+            },
+            Entry::from_query("ASK { ?s ?p ?o }"),
+            Entry::from_query("  # Count\nSELECT (COUNT(*) AS ?n) {}"),
+            Entry::from_query("# Count\nSELECT (COUNT(?s) AS ?n) {}"),
+        ];
+        assert_eq!(entries[1].name, "query");
+        assert_eq!(entries[2].name, "Count");
+        write(&dir, &entries).unwrap();
+        assert!(dir.join("People-by-age.rq").exists());
+        assert!(dir.join("Count-2.rq").exists());
+        // 未列入索引之文件亦讀之，排於其後
+        fs::write(dir.join("extra.rq"), "SELECT * {}").unwrap();
+        let read_back = read(&dir).unwrap();
+        assert_eq!(read_back[..4], entries[..]);
+        assert_eq!(read_back[4].name, "extra");
+
+        let copy = dir.join("copy");
+        write(&copy, &read_back[..4]).unwrap();
+        for file in [
+            "People-by-age.rq",
+            "query.rq",
+            "Count.rq",
+            "Count-2.rq",
+            INDEX,
+        ] {
+            assert_eq!(
+                fs::read(dir.join(file)).unwrap(),
+                fs::read(copy.join(file)).unwrap(),
+                "{file}"
+            );
+        }
+
+        fs::write(copy.join(INDEX), "[query]\nnames = \"x\"\n").unwrap();
+        let error = read(&copy).unwrap_err();
+        assert!(format!("{error:#}").contains("line 2: unknown key `names`"));
+        fs::write(copy.join(INDEX), "[missing]\nname = \"x\"\n").unwrap();
+        let error = read(&copy).unwrap_err();
+        assert!(
+            format!("{error:#}").contains("missing.rq is listed"),
+            "{error:#}"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
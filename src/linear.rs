@@ -0,0 +1,74 @@
+use crate::app::Mode;
+
+/// 模式之名，線性輸出之首行宣之。
+pub fn mode_name(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Query => "Query",
+        Mode::Browse => "Browse",
+        Mode::Hierarchy => "Hierarchy",
+        Mode::Bookmarks => "Bookmarks",
+        Mode::Scratchpad => "Scratchpad",
+        Mode::Neighborhood => "Neighborhood",
+        Mode::Recent => "Recent files",
+        Mode::History => "Query history",
+        Mode::Pattern => "Pattern",
+        Mode::Command => "Command line",
+        Mode::Filter => "Filter",
+        Mode::Edit => "Edit value",
+    }
+}
+
+/// 列表之一項，如 `row 3 of 120: s = ex:a, p = rdfs:label`。`position` 自 0 起。
+pub fn describe_item(kind: &str, position: usize, total: usize, text: &str) -> String {
+    format!("{kind} {} of {total}: {text}", position + 1)
+}
+
+/// 一行之各格，如 `s = ex:a, p = rdfs:label`。未綁定者略去，皆未綁定則為 `nothing bound`。
+pub fn describe_cells<'a>(cells: impl IntoIterator<Item = (&'a str, Option<String>)>) -> String {
+    let cells: Vec<String> = cells
+        .into_iter()
+        .filter_map(|(name, value)| Some(format!("{name} = {}", value?)))
+        .collect();
+    if cells.is_empty() {
+        "nothing bound".to_string()
+    } else {
+        cells.join(", ")
+    }
+}
+
+/// 線性模式中無對應者之功能之說明。
+pub fn unavailable(feature: &str) -> String {
+    format!("{feature} is not available in linear mode")
+}
+
+/// 提示之一行，如 `Keys: tab query, ? help`。
+pub fn describe_hints(hints: &[(String, &str)]) -> String {
+    let hints: Vec<String> = hints
+        .iter()
+        .map(|(key, label)| format!("{key} {label}"))
+        .collect();
+    format!("Keys: {}", hints.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_read_as_one_sentence() {
+        let cells = describe_cells([
+            ("s", Some("ex:a".to_string())),
+            ("p", None),
+            ("o", Some("\"c\"@en".to_string())),
+        ]);
+        assert_eq!(
+            describe_item("row", 2, 120, &cells),
+            "row 3 of 120: s = ex:a, o = \"c\"@en"
+        );
+        assert_eq!(describe_cells([("s", None)]), "nothing bound");
+        assert_eq!(
+            describe_hints(&[("tab".to_string(), "query"), ("?".to_string(), "help")]),
+            "Keys: tab query, ? help"
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! 可分享之鏈接：一行文字，記所載入之文件、查詢、數據集及其顯示，他人以 `:goto` 或
+//! `--open-link` 至同處。其內為如會話文件之諸行，經 Base64 編碼，首行記其格式之版本；
+//! 新版所增之項舊版略之，新版之格式舊版拒之而不致出錯。
+
+use crate::{
+    bookmarks::{escape, unescape},
+    dataset::Dataset,
+    header::{sort_from_field, sort_to_field, Order},
+    util::{base64, decode_base64},
+};
+
+/// 鏈接之首，以辨之。
+pub const PREFIX: &str = "rdf-tui:";
+
+/// 內容之首行。
+const HEADER: &str = "rdf-tui-link";
+
+/// 所寫之格式之版本，亦為所能讀之最新者。
+const VERSION: u32 = 1;
+
+/// URL 之查詢參數名含此者，視為憑據而去之。
+const SECRET_PARAMETERS: &[&str] = &[
+    "token",
+    "key",
+    "secret",
+    "password",
+    "passwd",
+    "auth",
+    "signature",
+    "sig",
+];
+
+/// 鏈接所記者。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Link {
+    /// 所載入之文件之絕對路徑，或下載之 URL，已去其憑據。
+    pub sources: Vec<String>,
+    pub query: String,
+    pub dataset: Dataset,
+    /// 過濾之表達式，及未標明之子句是否為正則。
+    pub filter: Option<(String, bool)>,
+    /// 所依排序之諸列及其向，前者為主。
+    pub sort: Vec<(usize, Order)>,
+    pub column: Option<usize>,
+    pub row: usize,
+    pub limit: Option<usize>,
+    pub lang: Option<String>,
+}
+
+impl Link {
+    /// 如 `rdf-tui:cmRmLXR1aS1saW5r…`。
+    pub fn encode(&self) -> String {
+        let mut lines = vec![format!("{HEADER}\t{VERSION}")];
+        for source in &self.sources {
+            lines.push(format!("source\t{}", escape(&public(source))));
+        }
+        lines.push(format!("query\t{}", escape(&self.query)));
+        if self.dataset != Dataset::Default {
+            lines.push(format!("dataset\t{}", self.dataset.to_field()));
+        }
+        if let Some((filter, regex)) = &self.filter {
+            let kind = if *regex { "regex" } else { "text" };
+            lines.push(format!("filter\t{kind}\t{}", escape(filter)));
+        }
+        if !self.sort.is_empty() {
+            lines.push(format!("sort\t{}", sort_to_field(&self.sort)));
+        }
+        if let Some(column) = self.column {
+            lines.push(format!("column\t{column}"));
+        }
+        lines.push(format!("row\t{}", self.row));
+        if let Some(limit) = self.limit {
+            lines.push(format!("limit\t{limit}"));
+        }
+        if let Some(lang) = &self.lang {
+            lines.push(format!("lang\t{}", escape(lang)));
+        }
+        format!("{PREFIX}{}", base64(lines.join("\n").as_bytes()))
+    }
+
+    /// 解 [`Self::encode`] 所寫者。前後之空白與引號略之，便於自聊天中貼入。
+    pub fn decode(text: &str) -> Result<Self, String> {
+        let text = text.trim().trim_matches(['`', '"', '\'']);
+        let encoded = text
+            .strip_prefix(PREFIX)
+            .ok_or_else(|| format!("Not an rdf-tui link (links start with {PREFIX})"))?;
+        let content = decode_base64(encoded)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or("The link is damaged: it is not valid base64 text")?;
+        let mut lines = content.lines();
+        let version: u32 = lines
+            .next()
+            .and_then(|line| line.strip_prefix(HEADER)?.strip_prefix('\t')?.parse().ok())
+            .ok_or("The link is damaged: it has no version")?;
+        if version > VERSION {
+            return Err(format!(
+                "The link is from a newer rdf-tui (link format {version}); this one reads format \
+                 {VERSION}"
+            ));
+        }
+        let mut link = Self::default();
+        let mut has_query = false;
+        for (index, line) in lines.enumerate() {
+            let invalid = || format!("The link is damaged: line {} is invalid", index + 2);
+            let (key, value) = line.split_once('\t').ok_or_else(invalid)?;
+            let number = |value: &str| value.parse().map_err(|_| invalid());
+            match key {
+                "source" => link.sources.push(unescape(value)),
+                "query" => {
+                    link.query = unescape(value);
+                    has_query = true;
+                }
+                "dataset" => link.dataset = Dataset::from_field(value).ok_or_else(invalid)?,
+                "filter" => {
+                    link.filter = Some(match value.split_once('\t') {
+                        Some(("regex", filter)) => (unescape(filter), true),
+                        Some(("text", filter)) => (unescape(filter), false),
+                        _ => return Err(invalid()),
+                    })
+                }
+                "sort" => link.sort = sort_from_field(value).ok_or_else(invalid)?,
+                "column" => link.column = Some(number(value)?),
+                "row" => link.row = number(value)?,
+                "limit" => link.limit = Some(number(value)?),
+                "lang" => link.lang = Some(unescape(value)),
+                // 同版之後所增之項，略之
+                _ => {}
+            }
+        }
+        if !has_query {
+            return Err("The link is damaged: it has no query".to_string());
+        }
+        Ok(link)
+    }
+}
+
+/// 去 URL 之憑據：`user:password@`，及名如 `token`、`api_key` 之查詢參數。路徑不變。
+pub fn public(source: &str) -> String {
+    let Some((scheme, rest)) = source.split_once("://") else {
+        return source.to_string();
+    };
+    let (rest, fragment) = match rest.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (rest, None),
+    };
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let mut url = format!("{scheme}://{host}{path}");
+    let kept: Vec<&str> = query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .filter(|parameter| {
+            let name = parameter.split('=').next().unwrap_or_default();
+            let name = name.to_ascii_lowercase();
+            !SECRET_PARAMETERS.iter().any(|secret| name.contains(secret))
+        })
+        .collect();
+    if !kept.is_empty() {
+        url.push('?');
+        url.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{GraphName, NamedNode};
+
+    #[test]
+    fn links_round_trip_without_credentials_and_refuse_newer_formats() {
+        let link = Link {
+            sources: vec![
+                "/data/a b.ttl".to_string(),
+                "https://user:pw@example.org/d.ttl?api_key=1&v=2#x".to_string(),
+            ],
+            query: "SELECT * WHERE {\n\t?s ?p ?o\n}".to_string(),
+            dataset: Dataset::Graphs(vec![
+                GraphName::DefaultGraph,
+                NamedNode::new_unchecked("http://ex/g").into(),
+            ]),
+            filter: Some(("?s: ex\\b".to_string(), true)),
+            sort: vec![(2, Order::Descending), (0, Order::Ascending)],
+            column: Some(1),
+            row: 41,
+            limit: Some(500),
+            lang: Some("en".to_string()),
+        };
+        let text = link.encode();
+        assert!(text.starts_with(PREFIX) && !text.contains(char::is_whitespace));
+        let decoded = Link::decode(&format!("  `{text}`\n")).unwrap();
+        assert_eq!(
+            decoded.sources[1], "https://example.org/d.ttl?v=2#x",
+            "credentials are never shared"
+        );
+        assert_eq!(
+            decoded,
+            Link {
+                sources: decoded.sources.clone(),
+                ..link.clone()
+            }
+        );
+        assert_eq!(public("file.ttl"), "file.ttl");
+        assert_eq!(
+            public("http://h/p?Token=x"),
+            "http://h/p",
+            "{}",
+            public("http://h/p?Token=x")
+        );
+
+        // 新版之格式與損壞者報錯，不致出錯；未知之項略之
+        let encode = |content: &str| format!("{PREFIX}{}", base64(content.as_bytes()));
+        let newer = Link::decode(&encode("rdf-tui-link\t2\nquery\tx")).unwrap_err();
+        assert!(newer.contains("newer rdf-tui (link format 2)"), "{newer}");
+        let extra = Link::decode(&encode("rdf-tui-link\t1\nquery\tx\nfuture\t1")).unwrap();
+        assert_eq!(extra.query, "x");
+        assert!(Link::decode(&encode("rdf-tui-link\t1\nrow\tx\nquery\tx")).is_err());
+        assert!(Link::decode(&encode("rdf-tui-link\t1")).is_err());
+        assert!(Link::decode("rdf-tui:%%%").unwrap_err().contains("base64"));
+        assert!(Link::decode("https://example.org")
+            .unwrap_err()
+            .starts_with("Not"));
+    }
+}
@@ -0,0 +1,340 @@
+use anyhow::Context;
+use oxigraph::{
+    io::{RdfFormat, RdfParser},
+    model::{BlankNode, GraphName, Quad, Subject, Term},
+    store::Store,
+};
+use oxttl::n3::{N3Parser, N3Term};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{absolute, Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// 依擴展名讀取 RDF 文件，不識者作 Turtle。`read` 隨讀入之字節數遞增，以示進度。
+///
+/// N3 中無法存入之構造略去，其說明附於所得；`strict_n3` 則視之為錯誤。
+pub fn load_file(
+    store: &Store,
+    path: &Path,
+    read: &AtomicU64,
+    strict_n3: bool,
+) -> anyhow::Result<FileLoad> {
+    let path = absolute(path)?;
+    let iri = format!(
+        "file://{}",
+        path.to_str().context("Fail to convert path to string")?
+    );
+    let format = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(RdfFormat::from_extension)
+        .unwrap_or(RdfFormat::Turtle);
+    let reader = Counting {
+        inner: BufReader::new(File::open(&path)?),
+        read,
+    };
+    if format == RdfFormat::N3 {
+        return load_n3(store, &iri, reader, strict_n3);
+    }
+    load_quads(
+        store,
+        RdfParser::from_format(format).with_base_iri(&iri)?,
+        reader,
+    )
+}
+
+/// 一文件載入所得。
+#[derive(Debug, Default)]
+pub struct FileLoad {
+    /// N3 略去之說明。
+    pub note: Option<String>,
+    /// 各圖所載入之三元組數，依圖初見之序。
+    pub graphs: Vec<(GraphName, usize)>,
+    /// 文件所聲明之前綴，按名排序。唯 Turtle、TriG 與 N3 有之。
+    pub prefixes: Vec<(String, String)>,
+}
+
+/// 解析後一次提交，如 [`Store::load_from_read`]，並按圖計數。
+fn load_quads(store: &Store, parser: RdfParser, reader: impl Read) -> anyhow::Result<FileLoad> {
+    let mut reader = parser.rename_blank_nodes().parse_read(reader);
+    let quads = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+    let prefixes = sorted_prefixes(reader.prefixes());
+    let graphs = count_graphs(&quads);
+    store.extend(quads)?;
+    Ok(FileLoad {
+        note: None,
+        graphs,
+        prefixes,
+    })
+}
+
+/// 解析所見之前綴，按名排序，以免隨散列之序。
+fn sorted_prefixes<'a>(
+    prefixes: impl Iterator<Item = (&'a str, &'a str)>,
+) -> Vec<(String, String)> {
+    let mut prefixes: Vec<(String, String)> = prefixes
+        .map(|(name, iri)| (name.to_string(), iri.to_string()))
+        .collect();
+    prefixes.sort();
+    prefixes
+}
+
+/// 按圖計數。
+fn count_graphs(quads: &[Quad]) -> Vec<(GraphName, usize)> {
+    let mut graphs: Vec<(GraphName, usize)> = vec![];
+    for quad in quads {
+        match graphs
+            .iter_mut()
+            .find(|(graph, _)| *graph == quad.graph_name)
+        {
+            Some((_, count)) => *count += 1,
+            None => graphs.push((quad.graph_name.clone(), 1)),
+        }
+    }
+    graphs
+}
+
+/// N3 中略去之陳述，按其因計數。
+#[derive(Debug, Default)]
+struct Skipped {
+    /// 公式中之陳述，及以公式為主語或賓語者。
+    formulae: usize,
+    /// `=>` 與 `<=` 所寫之規則。
+    rules: usize,
+    /// 含變量或他種 RDF 不容之項者。
+    other: usize,
+}
+
+impl Skipped {
+    fn is_empty(&self) -> bool {
+        self.formulae + self.rules + self.other == 0
+    }
+
+    /// 如 `3 statements in formulae, 1 rule`。
+    fn describe(&self) -> String {
+        let plural = |count: usize, one: &str, many: &str| {
+            format!("{count} {}", if count == 1 { one } else { many })
+        };
+        let parts: Vec<String> = [
+            (
+                self.formulae,
+                "statement in formulae",
+                "statements in formulae",
+            ),
+            (self.rules, "rule", "rules"),
+            (self.other, "other statement", "other statements"),
+        ]
+        .into_iter()
+        .filter(|(count, _, _)| *count > 0)
+        .map(|(count, one, many)| plural(count, one, many))
+        .collect();
+        parts.join(", ")
+    }
+}
+
+const LOG_IMPLIES: &str = "http://www.w3.org/2000/10/swap/log#implies";
+
+/// 讀取 N3，存入其中可表為 RDF 之三元組。空白節點重新命名，以免與他文件相混。
+fn load_n3(store: &Store, base: &str, reader: impl Read, strict: bool) -> anyhow::Result<FileLoad> {
+    let mut reader = N3Parser::new().with_base_iri(base)?.parse_read(reader);
+    let statements = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+    let prefixes = sorted_prefixes(reader.prefixes());
+    // 公式以空白節點為名，其中之陳述在該名之圖中
+    let formulae: HashSet<BlankNode> = statements
+        .iter()
+        .filter_map(|statement| match &statement.graph_name {
+            GraphName::BlankNode(node) => Some(node.clone()),
+            _ => None,
+        })
+        .collect();
+    let is_formula =
+        |term: &N3Term| matches!(term, N3Term::BlankNode(node) if formulae.contains(node));
+    let mut blank_nodes = HashMap::new();
+    let mut rename = |node: BlankNode| {
+        blank_nodes
+            .entry(node)
+            .or_insert_with(BlankNode::default)
+            .clone()
+    };
+    let mut skipped = Skipped::default();
+    let mut quads = vec![];
+    for statement in statements {
+        if matches!(&statement.predicate, N3Term::NamedNode(p) if p.as_str() == LOG_IMPLIES) {
+            skipped.rules += 1;
+        } else if !statement.graph_name.is_default_graph()
+            || is_formula(&statement.subject)
+            || is_formula(&statement.object)
+        {
+            skipped.formulae += 1;
+        } else {
+            let subject = match statement.subject {
+                N3Term::NamedNode(node) => Some(Subject::from(node)),
+                N3Term::BlankNode(node) => Some(rename(node).into()),
+                N3Term::Triple(triple) => Some((*triple).into()),
+                N3Term::Literal(_) | N3Term::Variable(_) => None,
+            };
+            let predicate = match statement.predicate {
+                N3Term::NamedNode(node) => Some(node),
+                _ => None,
+            };
+            let object = match statement.object {
+                N3Term::NamedNode(node) => Some(Term::from(node)),
+                N3Term::BlankNode(node) => Some(rename(node).into()),
+                N3Term::Literal(literal) => Some(literal.into()),
+                N3Term::Triple(triple) => Some((*triple).into()),
+                N3Term::Variable(_) => None,
+            };
+            match (subject, predicate, object) {
+                (Some(subject), Some(predicate), Some(object)) => quads.push(Quad::new(
+                    subject,
+                    predicate,
+                    object,
+                    GraphName::DefaultGraph,
+                )),
+                _ => skipped.other += 1,
+            }
+        }
+        if strict && !skipped.is_empty() {
+            anyhow::bail!(
+                "{} cannot be represented in RDF (--strict-n3)",
+                skipped.describe()
+            );
+        }
+    }
+    let graphs = count_graphs(&quads);
+    store.extend(quads)?;
+    Ok(FileLoad {
+        note: (!skipped.is_empty()).then(|| format!("skipped {} (N3 only)", skipped.describe())),
+        graphs,
+        prefixes,
+    })
+}
+
+/// 讀取命令行所給之數據。以序號為基 IRI，如 `http://inline.invalid/2/`。返回各圖之三元組數。
+pub fn load_data(
+    store: &Store,
+    data: &str,
+    format: RdfFormat,
+    number: usize,
+) -> anyhow::Result<Vec<(GraphName, usize)>> {
+    let iri = format!("http://inline.invalid/{number}/");
+    let load = load_quads(
+        store,
+        RdfParser::from_format(format).with_base_iri(&iri)?,
+        data.as_bytes(),
+    )?;
+    Ok(load.graphs)
+}
+
+/// 計數讀入之字節。
+struct Counting<'a, R> {
+    inner: R,
+    read: &'a AtomicU64,
+}
+
+impl<R: Read> Read for Counting<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// 一文件載入之結果。
+pub struct Loaded {
+    pub path: PathBuf,
+    pub result: anyhow::Result<FileLoad>,
+    pub duration: Duration,
+}
+
+/// 以 `jobs` 個線程並行載入多個文件，各文件自行解析，寫入由存儲依次提交。
+/// 每文件畢即送其結果，皆畢則通道關閉。`read` 隨讀入之字節數遞增，以示進度。
+pub fn load_all(
+    store: &Store,
+    paths: Vec<PathBuf>,
+    jobs: usize,
+    strict_n3: bool,
+    read: &Arc<AtomicU64>,
+) -> Receiver<Loaded> {
+    let count = paths.len();
+    let queue = Arc::new(Mutex::new(VecDeque::from(paths)));
+    let (sender, receiver) = mpsc::channel();
+    for _ in 0..jobs.max(1).min(count) {
+        let (store, queue, read, sender) = (
+            store.clone(),
+            Arc::clone(&queue),
+            Arc::clone(read),
+            sender.clone(),
+        );
+        thread::spawn(move || loop {
+            let Some(path) = queue.lock().unwrap().pop_front() else {
+                return;
+            };
+            let start = Instant::now();
+            let result = load_file(&store, &path, &read, strict_n3);
+            let loaded = Loaded {
+                path,
+                result,
+                duration: start.elapsed(),
+            };
+            if sender.send(loaded).is_err() {
+                return;
+            }
+        });
+    }
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load(source: &str, strict: bool) -> (Store, anyhow::Result<FileLoad>) {
+        let path =
+            std::env::temp_dir().join(format!("rdf-tui-n3-{}-{strict}.n3", std::process::id()));
+        fs::write(&path, source).unwrap();
+        let store = Store::new().unwrap();
+        let result = load_file(&store, &path, &AtomicU64::new(0), strict);
+        fs::remove_file(path).unwrap();
+        (store, result)
+    }
+
+    const N3: &str = "@prefix : <http://ex/> .\n\
+        :a :knows :b ; :friend [ :name \"c\" ] .\n\
+        { ?x :knows ?y } => { ?y :knows ?x } .\n\
+        :d :says { :sky :is :green } .\n";
+
+    #[test]
+    fn n3_keeps_plain_triples_and_counts_the_rest() {
+        let (store, result) = load(N3, false);
+        assert_eq!(store.len().unwrap(), 3);
+        let load = result.unwrap();
+        assert_eq!(
+            load.note.as_deref(),
+            Some("skipped 4 statements in formulae, 1 rule (N3 only)")
+        );
+        assert_eq!(load.graphs, [(GraphName::DefaultGraph, 3)]);
+        assert_eq!(load.prefixes, [(String::new(), "http://ex/".to_string())]);
+    }
+
+    #[test]
+    fn strict_n3_fails_and_loads_nothing() {
+        let (store, result) = load(N3, true);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .ends_with("cannot be represented in RDF (--strict-n3)"));
+        assert!(store.is_empty().unwrap());
+        assert_eq!(load("<a> <b> <c> .", true).1.unwrap().note, None);
+    }
+}
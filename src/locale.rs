@@ -0,0 +1,590 @@
+use ratatui::text::Span;
+use std::{env, str::FromStr};
+
+/// 界面之語言。另於 `lang`，彼乃標籤之語言。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    En,
+    /// 中文，正體字。
+    Zh,
+}
+
+impl Language {
+    /// 依語言標籤，如 `zh`、`zh-TW`、`zh_CN.UTF-8`。唯取其首段。
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag
+            .split(['-', '_', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Language::En),
+            "zh" => Some(Language::Zh),
+            _ => None,
+        }
+    }
+
+    /// 依 `LC_ALL`、`LC_MESSAGES`、`LANG`，取首個非空者。無譯文之語言，及 `C`，為英文。
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_tag(&value))
+            .unwrap_or_default()
+    }
+
+    /// `text` 之譯文。以英文為鍵，無譯文者返回原文。
+    pub fn tr(self, text: &'static str) -> &'static str {
+        match self {
+            Language::En => text,
+            Language::Zh => ZH
+                .iter()
+                .find(|(english, _)| *english == text)
+                .map_or(text, |(_, chinese)| *chinese),
+        }
+    }
+
+    /// 譯 `template`，再依次以 `args` 代其 `{}`。
+    pub fn fill(self, template: &'static str, args: &[&str]) -> String {
+        let mut parts = self.tr(template).split("{}");
+        let mut text = parts.next().unwrap_or_default().to_string();
+        for (part, arg) in parts.zip(args.iter().chain(std::iter::repeat(&""))) {
+            text.push_str(arg);
+            text.push_str(part);
+        }
+        text
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        Self::from_tag(tag).ok_or_else(|| format!("unknown interface language `{tag}` (en, zh)"))
+    }
+}
+
+/// 補空格至 `width` 格。以顯示寬度計，故中文等寬字亦對齊。
+pub fn pad(text: &str, width: usize) -> String {
+    let used = Span::raw(text).width();
+    format!("{text}{}", " ".repeat(width.saturating_sub(used)))
+}
+
+/// 中文之譯文。
+const ZH: &[(&str, &str)] = &[
+    // 標題
+    ("Explore", "瀏覽"),
+    ("Explore (LIMIT {} added)", "瀏覽（已加 LIMIT {}）"),
+    ("Query", "查詢"),
+    ("Query *", "查詢 *"),
+    ("Pattern", "三元組模式"),
+    ("Hierarchy ({})", "層級（{}）"),
+    ("Neighborhood of {}", "{} 之鄰域"),
+    ("Bookmarks", "書籤"),
+    ("Recent files", "最近之文件"),
+    ("Query history", "查詢歷史"),
+    ("Scratchpad", "便箋"),
+    ("Preview", "預覽"),
+    ("Statistics", "統計"),
+    ("Help", "幫助"),
+    ("Keys", "按鍵"),
+    ("Commands", "命令"),
+    ("Matches", "候選"),
+    ("Updates", "更新"),
+    ("Checkpoints", "檢查點"),
+    ("Imported from {}", "匯入自 {}"),
+    (
+        " fix the file and press r to reload ",
+        " 修正文件後按 r 重新載入 ",
+    ),
+    ("{} triples, {}", "{} 個三元組，{}"),
+    ("(missing)", "（已不存在）"),
+    ("any", "任意"),
+    ("subject", "主語"),
+    ("predicate", "謂語"),
+    ("object", "賓語"),
+    // 提示欄
+    ("any key", "任意鍵"),
+    ("close", "關閉"),
+    ("confirm", "確認"),
+    ("cancel", "取消"),
+    ("column", "列"),
+    ("choose", "選擇"),
+    ("insert", "插入"),
+    ("Query by example", "以例查詢"),
+    ("exact value", "定值"),
+    ("same datatype", "同一類型"),
+    ("query", "查詢"),
+    ("help", "幫助"),
+    ("quit", "退出"),
+    ("command", "命令"),
+    ("details", "詳情"),
+    ("hierarchy", "層級"),
+    ("pattern", "模式"),
+    ("neighborhood", "鄰域"),
+    ("mark", "書籤"),
+    ("bookmarks", "書籤列表"),
+    ("reload", "重新載入"),
+    ("browse", "瀏覽"),
+    ("run", "執行"),
+    ("back", "返回"),
+    ("expand", "展開"),
+    ("instances", "實例"),
+    ("relation", "關係"),
+    ("jump", "跳轉"),
+    ("delete", "刪除"),
+    ("open", "打開"),
+    ("remove", "移除"),
+    ("insert", "插入"),
+    ("yank", "複製"),
+    ("move up", "上移"),
+    ("center", "置中"),
+    ("collapse", "收起"),
+    ("next field", "下一欄"),
+    ("complete", "補全"),
+    ("list", "列出"),
+    ("apply", "套用"),
+    ("regex", "正則"),
+    ("save", "保存"),
+    // 路徑之狀態
+    ("exists", "已存在"),
+    ("not found", "不存在"),
+    ("a directory", "為目錄"),
+    ("new file", "新文件"),
+    ("overwrites", "將覆蓋"),
+    ("no such directory", "目錄不存在"),
+    // 操作之說明
+    ("quit without confirmation", "不經確認即退出"),
+    ("switch between Query and Browse", "於查詢與瀏覽間切換"),
+    ("move the selection down", "選擇下移"),
+    ("move the selection up", "選擇上移"),
+    ("select the next column", "選擇下一列"),
+    ("select the previous column", "選擇上一列"),
+    ("select the first row", "選擇首行"),
+    ("select the last row", "選擇末行"),
+    ("expand / jump / run", "展開 / 跳轉 / 執行"),
+    ("back to Browse", "返回瀏覽"),
+    ("delete the bookmark", "刪除書籤"),
+    ("next hierarchy relation", "下一種層級關係"),
+    ("list instances", "列出實例"),
+    ("show details of the resource", "顯示資源之詳情"),
+    ("complete the command or prefix", "補全命令或前綴"),
+    ("list the completions", "列出補全之候選"),
+    ("next pattern field", "模式之下一欄"),
+    ("previous pattern field", "模式之上一欄"),
+    ("delete a character", "刪除一字"),
+    ("insert a newline", "插入換行"),
+    ("run the query now", "立即執行查詢"),
+    ("stop the running query", "停止執行中之查詢"),
+    ("class hierarchy", "類之層級"),
+    ("browse by triple pattern", "以三元組模式瀏覽"),
+    ("neighborhood of the selected resource", "所選資源之鄰域"),
+    ("expand the selected node", "展開所選節點"),
+    ("collapse the selected node", "收起所選節點"),
+    ("bookmark the resource", "將資源加入書籤"),
+    ("bookmark the query", "將查詢加入書籤"),
+    ("recently opened files", "最近打開之文件"),
+    ("command line", "命令行"),
+    ("export the results", "導出結果"),
+    ("open a file", "打開文件"),
+    ("rename the variable at the cursor", "改光標處變量之名"),
+    (
+        "undo the last rename or normalization",
+        "撤銷末次改名或規範化",
+    ),
+    (
+        "replace curly quotes, odd spaces and invisible characters",
+        "代彎引號、異空白與不可見之字符",
+    ),
+    ("start or stop recording a macro", "始或止錄宏"),
+    ("replay a macro", "重放宏"),
+    ("recorded macros", "所錄之宏"),
+    ("List recorded macros", "列所錄之宏"),
+    ("Macros", "宏"),
+    ("Paths", "路徑"),
+    ("where settings and state live", "設置與狀態之所在"),
+    (
+        "Show where the config, sessions, bookmarks and other state are kept",
+        "示配置、會話、書籤等狀態存於何處",
+    ),
+    ("Memory", "內存"),
+    ("estimated memory use", "所估之內存"),
+    (
+        "Show the estimated memory of the store, results, snapshots and caches",
+        "示存儲、結果、快照與諸緩存之估計內存",
+    ),
+    (
+        "Rename a query variable everywhere (asks first)",
+        "改查詢中一變量之各處（先確認）",
+    ),
+    (
+        "Replace curly quotes, odd spaces and invisible characters in the query",
+        "代查詢中之彎引號、異空白與不可見之字符",
+    ),
+    ("filter the rows", "過濾行"),
+    ("switch between regex and substring", "於正則與子串間切換"),
+    ("reload all files", "重新載入所有文件"),
+    ("edit the literal value", "編輯字面量之值"),
+    ("mark the row", "標記此行"),
+    ("delete the marked triples", "刪除所標記之三元組"),
+    ("store statistics", "存儲之統計"),
+    ("statistics of the selected column", "所選列之統計"),
+    ("column types under the table", "表下示各列之類型"),
+    ("single values as big text or a table", "單值以大字或表示之"),
+    (
+        "the selected row as one variable and value per line",
+        "所選之行各變量及其值各佔一行",
+    ),
+    (
+        "tint cells by term kind or column",
+        "依項之類別或依列為格著色",
+    ),
+    ("types of the selected column", "所選列之類型"),
+    (
+        "triples pointing at the selected resource",
+        "指向所選資源之三元組",
+    ),
+    (
+        "exact characters and parts of the selected cell",
+        "所選之格之原字符與各部分",
+    ),
+    (
+        "choose the selected cell as the first value to compare",
+        "以所選之格為所比較之第一值",
+    ),
+    (
+        "choose the selected cell as the second value to compare",
+        "以所選之格為所比較之第二值",
+    ),
+    (
+        "character differences between the chosen values",
+        "所選兩值之逐字差異",
+    ),
+    ("Inspect", "檢視"),
+    ("Row", "行"),
+    ("Compare", "比較"),
+    ("1/2 copy a value · j/k scroll", "1/2 複製其值 · j/k 滾動"),
+    (
+        "Paste text to compare it with the value",
+        "粘貼文本以與此值比較",
+    ),
+    ("select the column headers", "選表頭"),
+    ("sort by the selected column", "依所選列排序"),
+    ("add the selected column as the next sort key", "以所選列為次一排序之鍵"),
+    ("clear all sorting", "清除排序"),
+    ("query rows like the selected one", "查與所選行相似之行"),
+    ("queries run in this session", "本會話所執行之查詢"),
+    (
+        "count the triples using the selected term",
+        "計用所選項之三元組",
+    ),
+    (
+        "pin the selected cell to the scratchpad",
+        "將所選單元格釘於便箋",
+    ),
+    ("scratchpad", "便箋"),
+    ("copy to the clipboard", "複製至剪貼板"),
+    ("move the pin up", "上移"),
+    ("rebuild the tree from the current data", "依當前數據重建樹"),
+    ("next layout of the panes", "換窗格之下一佈局"),
+    (
+        "latency, queue and cache numbers for debugging redraws",
+        "調試重繪之延遲、隊列與緩存之數",
+    ),
+    (" refreshing… ", " 重算中… "),
+    (" stale — press R to refresh ", " 已過時——按 R 重算 "),
+    ("move the pin down", "下移"),
+    ("where each graph came from", "各圖之來源"),
+    ("preview the selected cell", "預覽所選單元格"),
+    ("scroll the preview down", "預覽向下捲動"),
+    ("scroll the preview up", "預覽向上捲動"),
+    ("focus the pane above", "焦點移至上方之窗格"),
+    ("focus the pane below", "焦點移至下方之窗格"),
+    ("focus the next pane", "焦點移至後一窗格"),
+    ("focus the previous pane", "焦點移至前一窗格"),
+    (
+        "show the source file of each row's graph",
+        "顯示各行之圖之來源文件",
+    ),
+    ("show IRIs as their labels", "以標籤示 IRI"),
+    (
+        "every value of the selected row and how it was computed",
+        "所選行之各值及其所算之法",
+    ),
+    (
+        "open the selected IRI in the browser (open_command)",
+        "以瀏覽器打開所選之 IRI（open_command）",
+    ),
+    (
+        "sort results without ORDER BY canonically",
+        "無 ORDER BY 之結果依規範之序排列",
+    ),
+    (
+        "query the union of all graphs or the default graph",
+        "於諸圖之並與默認圖間切換所查",
+    ),
+    ("compare with the previous run", "與上次結果比較"),
+    ("data-quality report", "數據質量報告"),
+    ("this help", "此幫助"),
+    ("switch to the next tab", "換至後一標籤頁"),
+    ("switch to the previous tab", "換至前一標籤頁"),
+    ("close this tab and its store", "關此標籤頁及其存儲"),
+    // 命令之說明
+    (
+        "Load an RDF file, or download one from an http(s) URL",
+        "載入 RDF 文件，或自 http(s) 之 URL 下載之",
+    ),
+    (
+        "Load the bundled sample: the chemical elements of periods 1 to 5",
+        "載入所附之例：第一至第五週期之化學元素",
+    ),
+    ("Reload all files", "重新載入所有文件"),
+    (
+        "Open a file in a new tab with its own store, query and history",
+        "於新標籤頁打開文件，其存儲、查詢與歷史各自獨立",
+    ),
+    (
+        "Close this tab and free its store (:tabclose! skips the confirmation)",
+        "關此標籤頁並釋其存儲（:tabclose! 不經確認）",
+    ),
+    ("Switch to the next tab", "換至後一標籤頁"),
+    ("Switch to the previous tab", "換至前一標籤頁"),
+    (
+        "Run the query in every tab and compare the row counts, show the last comparison, or write it as Markdown",
+        "於各標籤頁執行查詢而比較其行數，再示上次之比較，或寫之為 Markdown",
+    ),
+    ("Pick a recently opened file", "選擇最近打開之文件"),
+    (
+        "Browse the queries run in this session",
+        "瀏覽本會話所執行之查詢",
+    ),
+    (
+        "Write the result, or a CONSTRUCT's triples as RDF, to a file, a command's stdin or a URL (:export! skips waiting for a running query)",
+        "寫出結果，或以 RDF 寫出 CONSTRUCT 之三元組，至文件、命令之標準輸入或 URL（:export! 不等待執行中之查詢）",
+    ),
+    (
+        "Write every graph in the store as RDF, in the format of the path's extension",
+        "以 RDF 寫出存儲之諸圖，格式依路徑之擴展名",
+    ),
+    (
+        "Export CSV, TSV and tables as terms, plain values, or plain values with type columns",
+        "CSV、TSV 及表格導出為項、純值，或純值附類型列",
+    ),
+    (
+        "Cap displayed rows (0 for unlimited)",
+        "限制顯示之行數（0 為不限）",
+    ),
+    (
+        "Query the default graph, the union of all graphs, or the graphs listed",
+        "查默認圖、諸圖之並或所列之圖",
+    ),
+    (
+        "Apply a profile of display, theme and key settings for this session, or write the current ones",
+        "以側寫之顯示、主題與鍵位之設置用於此會話，或寫出當前之設置",
+    ),
+    (
+        "Sort results without ORDER BY canonically, or keep the engine's order",
+        "無 ORDER BY 之結果依規範之序排列，或留引擎之序",
+    ),
+    (
+        "The query returned no rows from the default graph; named graphs are left out (U or :dataset union)",
+        "默認圖中無一行；命名圖未查（U 或 :dataset union）",
+    ),
+    (
+        "Browse a named graph, or copy, move or drop one (default names the default graph)",
+        "瀏覽命名圖，或複製、移動、刪除之（default 指默認圖）",
+    ),
+    (
+        "Show the rows matching every clause (! inverts; no argument clears)",
+        "顯示符合各條件之行（! 為反；無參數則清除）",
+    ),
+    (
+        "Save the query with its display settings, or open a saved view",
+        "保存查詢及其顯示設置，或打開已存之視圖",
+    ),
+    (
+        "Merge a directory of .rq files and index.toml into the views, or write them out",
+        "併 .rq 文件與 index.toml 之目錄入視圖，或寫出之",
+    ),
+    (
+        "Re-run the query every interval, such as 30s or 5m, and show what changed",
+        "每隔一段時間（如 30s、5m）重新執行查詢並顯示變化",
+    ),
+    (
+        "Pin a snippet, or the selected cell, to the scratchpad",
+        "將片段或所選單元格釘於便箋",
+    ),
+    (
+        "Arrange the panes by a layout preset, or list the presets",
+        "依佈局預設排列窗格，或列出諸預設",
+    ),
+    (
+        "Copy the selected cell in that form, or pick the form from a menu",
+        "以此形式複製所選單元格，或於菜單中選其形式",
+    ),
+    (
+        "Copy a link to the loaded files, query, dataset, filter, sort and selected row",
+        "複製鏈接，記所載入之文件、查詢、數據集、過濾、排序及所選行",
+    ),
+    (
+        "Open a link made by :share, loading its files and running its query",
+        "打開 :share 所作之鏈接，載入其文件並執行其查詢",
+    ),
+    (
+        "Show or hide the latency, queue and cache numbers, or write them to the log",
+        "示或隱延遲、隊列與緩存之數，或寫之於日誌",
+    ),
+    (
+        "List the updates run in this session",
+        "列出本會話所執行之更新",
+    ),
+    (
+        "Undo the last edit or delete, the last n, or all of this session's (asks first)",
+        "撤銷最近一次、最近 n 次或本會話所有之編輯與刪除（先詢問）",
+    ),
+    (
+        "List the checkpoints, or replace what the nth saved with it (asks first)",
+        "列出檢查點，或以第 n 個所存者取代之（先詢問）",
+    ),
+    (
+        "Show store statistics, or those of the selected column",
+        "顯示存儲或所選列之統計",
+    ),
+    (
+        "List each graph's source file, load time and triples",
+        "列出各圖之來源文件、載入時間與三元組數",
+    ),
+    (
+        "Find subjects sharing a literal (default rdfs:label; -i ignores case and spacing)",
+        "找出共用字面量之主語（默認 rdfs:label；-i 忽略大小寫與空白）",
+    ),
+    (
+        "Count untyped subjects, dangling references and undefined predicates; \
+         functional lists subjects with several values for a functional property",
+        "計無類型之主語、懸空之引用與未定義之謂語；functional 列於函數性屬性有多值之主語",
+    ),
+    (
+        "Remove all data, or one named graph",
+        "移除所有數據或一命名圖",
+    ),
+    ("Show keys and commands", "顯示按鍵與命令"),
+    ("Quit (:q! skips the confirmation)", "退出（:q! 不經確認）"),
+    // 確認與狀態
+    (
+        "Quit? The running export will be cancelled (y/n)",
+        "退出？執行中之導出將取消 (y/n)",
+    ),
+    (
+        "Quit? Unsaved query will be lost (y/n)",
+        "退出？未保存之查詢將丟失 (y/n)",
+    ),
+    (
+        "Close the tab? Unsaved query will be lost (y/n)",
+        "關標籤頁？未保存之查詢將丟失 (y/n)",
+    ),
+    (
+        "Only one tab is open (:tabnew opens another)",
+        "僅開一標籤頁（:tabnew 開他頁）",
+    ),
+    (
+        "The last tab stays open (:quit to leave)",
+        "末一標籤頁不關（:quit 以退出）",
+    ),
+    ("Nothing compared yet (:tabdo run)", "尚無比較（:tabdo run）"),
+    ("Tabs", "標籤頁"),
+    (
+        "Enter shows a tab's rows · c compares two tabs · y copies Markdown",
+        "Enter 示一頁之行 · c 比較兩頁 · y 複製為 Markdown",
+    ),
+    (
+        "Show all rows? Large results can exhaust memory (y/n)",
+        "顯示所有行？大結果或耗盡內存 (y/n)",
+    ),
+    (
+        "Restore the previous session? (y/n)",
+        "恢復上次之會話？(y/n)",
+    ),
+    ("Welcome to rdf-tui", "歡迎使用 rdf-tui"),
+    ("(unbound)", "（未綁定）"),
+    (
+        "Query mode edits SPARQL and Browse mode explores the results; {} switches.",
+        "查詢模式編輯 SPARQL，瀏覽模式檢視其結果；{} 切換之。",
+    ),
+    ("In Query mode {} runs the query.", "查詢模式中 {} 執行查詢。"),
+    (
+        "{} lists every key, and : opens the command line.",
+        "{} 列出所有按鍵，: 打開命令行。",
+    ),
+    (
+        "loads a sample: the chemical elements of periods 1 to 5",
+        "載入一例：第一至第五週期之化學元素",
+    ),
+    (
+        "Any other key starts empty; this shows once (--no-onboarding skips it)",
+        "他鍵以空存儲開始；此引導僅示一次（--no-onboarding 略之）",
+    ),
+    (
+        "Pick a recent file, or o to open another",
+        "選擇最近之文件，或按 o 打開他者",
+    ),
+    ("Copied {}", "已複製 {}"),
+    (
+        "note: the store is empty — load data with :open or pass a file on the command line",
+        "注：存儲為空——以 :open 載入數據，或於命令行給出文件",
+    ),
+    (
+        "note: the default graph is empty; the data is in named graphs (U or :dataset union)",
+        "注：默認圖為空，數據皆在命名圖中（U 或 :dataset union）",
+    ),
+    ("The query returned no rows", "查詢無一行"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{action::BINDABLE, app::Mode, command::COMMANDS, keymap::Keymap};
+
+    #[test]
+    fn languages_come_from_tags_and_every_hint_and_help_line_is_translated() {
+        assert_eq!(Language::from_tag("zh_TW.UTF-8"), Some(Language::Zh));
+        assert_eq!(Language::from_tag("zh-Hant"), Some(Language::Zh));
+        assert_eq!(Language::from_tag("en_GB"), Some(Language::En));
+        assert_eq!(Language::from_tag("C.UTF-8"), None);
+        assert!("fr".parse::<Language>().is_err());
+
+        let zh = Language::Zh;
+        assert_eq!(zh.fill("Neighborhood of {}", &["ex:a"]), "ex:a 之鄰域");
+        assert_eq!(Language::En.fill("Copied {}", &["x"]), "Copied x");
+        assert_eq!(zh.tr("untranslated"), "untranslated");
+        assert_eq!(pad("主語", 6), "主語  ");
+
+        let untranslated = |text: &&'static str| zh.tr(text) == *text;
+        let keymap = Keymap::default();
+        let modes = [
+            Mode::Query,
+            Mode::Browse,
+            Mode::Hierarchy,
+            Mode::Bookmarks,
+            Mode::Scratchpad,
+            Mode::Neighborhood,
+            Mode::Recent,
+            Mode::History,
+            Mode::Pattern,
+            Mode::Command,
+            Mode::Filter,
+            Mode::Edit,
+        ];
+        let mut missing: Vec<&str> = modes
+            .iter()
+            .flat_map(|mode| keymap.hints(*mode))
+            .map(|(_, label)| label)
+            .chain(BINDABLE.iter().map(|(_, _, description)| *description))
+            .chain(COMMANDS.iter().map(|(_, _, help)| *help))
+            .filter(untranslated)
+            .collect();
+        missing.dedup();
+        assert!(missing.is_empty(), "untranslated: {missing:?}");
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Context;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// 寫入文件之日誌。一事一行：`時間 級別 模塊: 訊息`，訊息中之字段以 `key=value` 記之。
+/// 每行即寫即落盤，以免 panic 或信號退出時遺失。
+struct FileLogger {
+    file: Mutex<File>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let line = format!(
+            "{}.{:03} {:<5} {}: {}\n",
+            time.as_secs(),
+            time.subsec_millis(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// 初始化日誌。未給文件則不記錄，日誌宏皆為空操作。
+pub fn init(path: Option<&Path>, level: LevelFilter) -> anyhow::Result<()> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Fail to open log file {}", path.display()))?;
+    log::set_boxed_logger(Box::new(FileLogger {
+        file: Mutex::new(file),
+    }))?;
+    log::set_max_level(level);
+    log::info!("started version={}", env!("CARGO_PKG_VERSION"));
+    Ok(())
+}
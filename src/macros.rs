@@ -0,0 +1,209 @@
+use crate::action::Action;
+use std::collections::VecDeque;
+
+/// 非可綁定而可記之操作之名，如確認提示之答。
+const UNBOUND: &[(&str, Action)] = &[
+    ("confirm", Action::Confirm),
+    ("cancel", Action::Cancel),
+    ("confirm_alternative", Action::ConfirmAlternative),
+    ("close_help", Action::CloseHelp),
+];
+
+/// 所記之一操作之名：可綁定者依配置之名，輸入之字符為 `insert:` 加其碼位。
+fn name(action: &Action) -> Option<String> {
+    if let Action::InsertChar(ch) = action {
+        return Some(format!("insert:{:x}", u32::from(*ch)));
+    }
+    let unbound = UNBOUND.iter().find(|(_, a)| a == action).map(|(n, _)| *n);
+    Some(
+        crate::action::BINDABLE
+            .iter()
+            .find(|(_, a, _)| a == action)
+            .map(|(n, _, _)| *n)
+            .or(unbound)?
+            .to_string(),
+    )
+}
+
+fn parse_name(name: &str) -> Option<Action> {
+    if let Some(code) = name.strip_prefix("insert:") {
+        return char::from_u32(u32::from_str_radix(code, 16).ok()?).map(Action::InsertChar);
+    }
+    Action::from_name(name).or_else(|| {
+        UNBOUND
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, action)| action.clone())
+    })
+}
+
+/// 錄製與重放本身之操作，不錄入宏。
+pub fn is_control(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::ToggleMacroRecording
+            | Action::RecordMacro(_)
+            | Action::PromptPlayMacro
+            | Action::PlayMacro(..)
+    )
+}
+
+/// 一寄存器所記之操作。記操作而非按鍵，故改鍵位後仍可重放。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Macro {
+    pub register: char,
+    pub actions: Vec<Action>,
+}
+
+impl Macro {
+    /// 會話文件中之一欄：寄存器、Tab 及以空格分隔之操作名。無名之操作不存。
+    pub fn to_field(&self) -> String {
+        let names: Vec<String> = self.actions.iter().filter_map(name).collect();
+        format!("{}\t{}", self.register, names.join(" "))
+    }
+
+    pub fn from_field(field: &str) -> Option<Self> {
+        let (register, names) = field.split_once('\t')?;
+        let mut chars = register.chars();
+        let (Some(register), None) = (chars.next(), chars.next()) else {
+            return None;
+        };
+        let actions = names
+            .split_whitespace()
+            .map(parse_name)
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { register, actions })
+    }
+
+    /// 面板中所示：各操作之名，連續輸入之字符併為一串，如 `open_command_line "pin" activate`。
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = vec![];
+        let mut text = String::new();
+        for action in &self.actions {
+            match action {
+                Action::InsertChar(ch) => text.push(*ch),
+                action => {
+                    if !text.is_empty() {
+                        parts.push(format!("{:?}", std::mem::take(&mut text)));
+                    }
+                    parts.push(name(action).unwrap_or_else(|| format!("{action:?}")));
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(format!("{text:?}"));
+        }
+        parts.join(" ")
+    }
+}
+
+/// 各寄存器之宏，依寄存器排序，隨會話保存。
+#[derive(Debug, Default)]
+pub struct Macros {
+    macros: Vec<Macro>,
+}
+
+impl Macros {
+    pub fn all(&self) -> &[Macro] {
+        &self.macros
+    }
+
+    pub fn get(&self, register: char) -> Option<&Macro> {
+        self.macros.iter().find(|m| m.register == register)
+    }
+
+    /// 存入，代同一寄存器之舊者。無操作則清之。
+    pub fn set(&mut self, recorded: Macro) {
+        self.macros.retain(|m| m.register != recorded.register);
+        if !recorded.actions.is_empty() {
+            let index = self
+                .macros
+                .partition_point(|m| m.register < recorded.register);
+            self.macros.insert(index, recorded);
+        }
+    }
+
+    /// 換為會話所存者。
+    pub fn replace(&mut self, macros: Vec<Macro>) {
+        self.macros.clear();
+        for recorded in macros {
+            self.set(recorded);
+        }
+    }
+}
+
+/// 重放中之宏：餘下之操作，及其前一操作是否起了查詢，待其完而驗之。
+#[derive(Debug)]
+pub struct Replay {
+    pub register: char,
+    pub actions: VecDeque<Action>,
+    pub awaiting_query: bool,
+}
+
+impl Replay {
+    /// 重放 `times` 次。
+    pub fn new(recorded: &Macro, times: usize) -> Self {
+        Self {
+            register: recorded.register,
+            actions: (0..times)
+                .flat_map(|_| recorded.actions.iter().cloned())
+                .collect(),
+            awaiting_query: false,
+        }
+    }
+
+    /// 確認提示出現時，記錄中其後即答之者方可續行。
+    pub fn answers_prompt(&self) -> bool {
+        matches!(
+            self.actions.front(),
+            Some(Action::Confirm | Action::Cancel | Action::ConfirmAlternative)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macros_round_trip_by_action_name() {
+        let recorded = Macro {
+            register: 'a',
+            actions: vec![
+                Action::OpenCommandLine,
+                Action::InsertChar('p'),
+                Action::InsertChar(' '),
+                Action::Activate,
+                Action::ScrollRows(1),
+                Action::Confirm,
+            ],
+        };
+        let field = recorded.to_field();
+        assert_eq!(
+            field,
+            "a\topen_command_line insert:70 insert:20 activate select_next confirm"
+        );
+        assert_eq!(Macro::from_field(&field), Some(recorded.clone()));
+        assert_eq!(
+            recorded.describe(),
+            "open_command_line \"p \" activate select_next confirm"
+        );
+        assert_eq!(Macro::from_field("ab\tquit"), None);
+        assert_eq!(Macro::from_field("a\tno_such_action"), None);
+
+        let mut macros = Macros::default();
+        macros.set(Macro {
+            register: 'b',
+            actions: vec![Action::Quit],
+        });
+        macros.set(recorded.clone());
+        assert_eq!(macros.all()[0].register, 'a');
+        let playback = Replay::new(&recorded, 3);
+        assert_eq!(playback.actions.len(), 18);
+        macros.set(Macro {
+            register: 'a',
+            actions: vec![],
+        });
+        assert!(macros.get('a').is_none());
+    }
+}
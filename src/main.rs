@@ -1,28 +1,546 @@
-mod app;
-mod util;
-
-use crate::app::App;
+use anyhow::Context;
 use clap::Parser;
-use util::{restore_terminal, setup_terminal};
+use log::LevelFilter;
+use oxigraph::io::RdfFormat;
+use rdf_tui::{
+    action::{ExportFormat, Values},
+    app::{App, Exit, Mode},
+    config::{self, Config},
+    doctor, download,
+    headless::{self, Headless},
+    locale::Language,
+    logging,
+    overwrite::Overwrite,
+    paths::Paths,
+    profile,
+    record::{self, Recorder},
+    script,
+    session::Restore,
+    signal,
+    sink::Sink,
+    util::{setup_terminal, Capabilities},
+};
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    num::NonZeroUsize,
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
+    if args.print_default_config {
+        print!("{}", config::DEFAULT_CONFIG);
+        return Ok(());
+    }
+    let capabilities = if args.assume_dumb {
+        Capabilities::dumb()
+    } else {
+        Capabilities::detect()
+    };
+    let paths = Paths::new(
+        args.config_dir.clone(),
+        args.state_dir.clone(),
+        args.config.clone(),
+    );
+    if args.doctor {
+        print!("{}", doctor::report(&paths, capabilities));
+        return Ok(());
+    }
+    // 輸出或輸入非終端則不起界面，徑寫結果；腳本不讀輸入
+    let scripted_input = args.script.is_some() || args.replay.is_some();
+    // 退出時寫結果於標準輸出者，標準輸出非終端則繪界面於標準錯誤
+    let on_stderr =
+        args.emit_on_exit.is_some() && !io::stdout().is_terminal() && io::stderr().is_terminal();
+    let interactive =
+        (io::stdout().is_terminal() || on_stderr) && (scripted_input || io::stdin().is_terminal());
+    // 結果有所去或有所定之格式者，亦不起界面
+    let piped = args.sink.is_some() || args.output.is_some();
+    let headless = (!interactive || piped) && !args.force_tui;
+    // 先於終端初始化，以便 panic 鈎子可寫日誌
+    logging::init(args.log_file.as_deref(), args.log_level)?;
+    let (mut config, mut warnings) =
+        Config::load(&paths).or_else(|error| fail(headless, error, 5))?;
+    // 側寫蓋於配置之上，命令行之選項仍先之
+    if let Some(path) = &args.profile {
+        let applied = fs::read_to_string(path)
+            .with_context(|| format!("Fail to read profile {}", path.display()))
+            .and_then(|text| profile::import(&mut config, &text))
+            .with_context(|| format!("Invalid profile {}", path.display()))
+            .or_else(|error| fail(headless, error, 5))?;
+        log::info!("profile {} {}", path.display(), applied.describe());
+        let overrides =
+            (!applied.overridden.is_empty()).then(|| format!("profile {}", applied.describe()));
+        warnings.extend(applied.warnings);
+        warnings.extend(overrides);
+    }
+    let script = args
+        .script
+        .as_deref()
+        .map(script::load)
+        .transpose()
+        .or_else(|error| fail(headless, error, 5))?;
+    let replay = args
+        .replay
+        .as_deref()
+        .map(record::load)
+        .transpose()
+        .or_else(|error| fail(headless, error, 5))?;
+    let script = script.or_else(|| replay.as_ref().map(|replay| replay.steps.clone()));
+    if let Some(max_rows) = args.max_rows {
+        config.max_rows = (max_rows > 0).then_some(max_rows);
+    }
+    if let Some(theme) = args.theme {
+        config.set_theme(theme).map_err(anyhow::Error::msg)?;
+    }
+    // 見 https://no-color.org：非空即生效
+    if args.no_color || env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        config.no_color = true;
+    }
+    if !capabilities.colors {
+        config.no_color = true;
+    }
+
+    let query = match (args.query, &args.query_file) {
+        (Some(query), _) => Some(query),
+        (None, Some(path)) => Some(
+            fs::read_to_string(path)
+                .with_context(|| format!("Fail to read {}", path.display()))
+                .or_else(|error| fail(headless, error, 2))?,
+        ),
+        (None, None) => None,
+    };
+    let values = if args.typed_columns {
+        Values::Typed
+    } else if args.plain_values {
+        Values::Plain
+    } else {
+        Values::Raw
+    };
+    if headless {
+        let Some(query) = query else {
+            eprintln!(
+                "Error: stdout is not a terminal: give --query or --query-file to print the \
+                 results as TSV, or --force-tui to start the interface anyway"
+            );
+            process::exit(2);
+        };
+        let headless = Headless {
+            paths: args.paths.iter().map(PathBuf::from).collect(),
+            data: args.data,
+            format: args.format.unwrap_or(RdfFormat::Turtle),
+            query,
+            strict_n3: args.strict_n3,
+            federated: args.federated,
+            timeout: config.timeout,
+            deadline: args.timeout.map(Duration::from_secs),
+            limit: args.export_limit.filter(|limit| *limit > 0),
+            values,
+            quiet: args.quiet,
+            prefer_disk_above: args.prefer_disk_above,
+            download: download::Options {
+                cache: paths.cache_dir(),
+                max: args.max_download,
+                retries: config.download_retries,
+                stall: config.timeout,
+                ..download::Options::default()
+            },
+            output: args.output,
+            sink: args.sink,
+        };
+        let start = Instant::now();
+        let code = match headless.run(io::stdout()) {
+            Ok(outcome) => {
+                if !args.quiet {
+                    eprintln!("{outcome} in {} ms", start.elapsed().as_millis());
+                }
+                outcome.code(args.fail_if_empty)
+            }
+            Err(failure) => {
+                eprintln!("Error: {failure}");
+                failure.code()
+            }
+        };
+        process::exit(code.into());
+    }
 
-    let mut terminal = setup_terminal()?;
+    // 內聯視口經標準輸出問光標之位置，故繪於標準錯誤者不內聯
+    let inline = args
+        .inline
+        .filter(|_| !on_stderr)
+        .map(|height| height.unwrap_or(16));
+    let mut terminal = setup_terminal(inline, capabilities, on_stderr)?;
+    // 未設主題者依終端之背景
+    if config.theme.is_none() {
+        config.theme = terminal
+            .background()
+            .map(|background| background.preset().to_string());
+    }
+    if !terminal.keyboard_enhanced() {
+        config.keymap.add_fallbacks();
+    }
+    signal::install_handlers()?;
     let mut app = App::new()?;
+    app.set_paths(paths);
+    let lang = args.lang.or_else(|| config.lang.clone());
+    app.set_language(
+        args.lang_ui
+            .or(config.ui_language)
+            .unwrap_or_else(Language::from_env),
+    );
+    app.configure(config);
+    if !warnings.is_empty() {
+        for warning in &warnings {
+            log::warn!("config {warning}");
+        }
+        app.warn(format!("config: {}", warnings.join("; ")));
+    }
+    app.set_federated(args.federated);
+    app.set_linear(args.linear);
+    app.set_contain_panics(!args.debug_render);
+    app.set_debug_overlay(args.debug_overlay);
+    if let Some(address) = &args.serve {
+        app.serve(address)?;
+    }
+    app.set_lang(lang);
+    app.set_follow_imports(args.follow_imports.map(|depth| depth.unwrap_or(usize::MAX)));
+
+    // 指定之查詢不為會話所蓋
+    let pinned = query.is_some() || args.open_link.is_some();
+    if let Some(query) = query {
+        app.set_query(query);
+    }
+    match args.start_in.as_deref() {
+        Some("query") => app.set_mode(Mode::Query),
+        Some("browse") => app.set_mode(Mode::Browse),
+        _ => {}
+    }
+    if args.run {
+        app.run_on_start();
+    }
+
+    // 腳本為可重現之演示與測試，默認不恢復亦不保存會話
+    let scripted = script.is_some();
+    app.set_restore(if args.restore {
+        Restore::Always
+    } else if args.no_restore || scripted || pinned {
+        Restore::Never
+    } else {
+        Restore::Ask
+    });
+    app.set_startup_query(!args.no_startup_query && !pinned);
+    app.set_guard(!args.no_guard);
+    // 無人可問之腳本不覆蓋已有之文件，除非 --force
+    app.set_overwrite(if args.force {
+        Overwrite::Always
+    } else if scripted && !args.script_then_interactive {
+        Overwrite::Never
+    } else {
+        Overwrite::Ask
+    });
+    if !scripted {
+        app.attach_recent();
+        if !args.no_recent && !pinned && args.paths.is_empty() && args.data.is_empty() {
+            app.offer_recent();
+            if !args.no_onboarding {
+                app.offer_onboarding();
+            }
+        }
+    }
+    if let Some(steps) = script {
+        app.play(steps, args.script_then_interactive);
+    }
 
-    if let Some(path) = args.path {
-        app.load(path)?;
+    app.set_strict_n3(args.strict_n3);
+    app.set_prefixed_exports(!args.no_prefixes);
+    app.set_read_only(args.read_only);
+    if let Some(dir) = args.checkpoint_dir {
+        app.set_checkpoints(dir, args.checkpoint_limit);
+    }
+    app.set_export_limit(args.export_limit.filter(|limit| *limit > 0));
+    app.set_max_download(args.max_download);
+    app.set_values(values);
+    if let Some(jobs) = args.jobs {
+        app.set_jobs(jobs.get());
+    }
+    let format = args.format.unwrap_or(RdfFormat::Turtle);
+    for data in args.data {
+        app.load_data(data, format);
+    }
+    // 重放而未給文件者，載入記錄中之文件
+    let sources = match replay {
+        Some(replay) if args.paths.is_empty() => replay.sources,
+        _ => args.paths.iter().map(PathBuf::from).collect(),
+    };
+    for path in sources {
+        app.queue_load(path);
+    }
+    if let Some(link) = &args.open_link {
+        app.open_link(link).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(path) = &args.record {
+        app.record(Recorder::create(path, args.record_full)?);
     }
 
-    app.run(&mut terminal)?;
+    while let Exit::Suspend = app.run(terminal.terminal())? {
+        terminal.suspend()?;
+    }
+    if !scripted {
+        if let Err(error) = app.save_session() {
+            log::warn!("session save failed error={error}");
+        }
+    }
 
-    restore_terminal();
+    // 內聯模式下抹去視口；無備用屏幕者亦不留末幀於屏上
+    if inline.is_some() || !capabilities.alternate_screen {
+        terminal.terminal().clear()?;
+    }
+    drop(terminal);
+    // 恢復終端後寫之，故留於回滾中。強退或末次查詢出錯則不寫
+    if let Some(format) = args.emit_on_exit {
+        if !app.quit_normally() {
+            process::exit(1);
+        }
+        if let Err(error) = app.emit(format, io::stdout().lock()) {
+            eprintln!("Error: {error}");
+            process::exit(1);
+        }
+    } else if inline.is_some() {
+        // 留下查詢與結果於回滾中
+        print!("{}", app.transcript());
+    }
 
     Ok(())
 }
 
 #[derive(Debug, Parser)]
+#[command(after_help = "\
+When stdout or stdin is not a terminal, the query runs without the interface.
+Exit codes then: 0 success, 1 a false ASK (or no results with --fail-if-empty),
+2 an invalid or missing query, 3 a load error, 4 a timeout, 5 other errors.")]
 pub struct Args {
-    path: Option<String>,
+    /// RDF files or http(s) URLs to load, by extension (.ttl, .nt, .nq, .trig, .n3, .rdf;
+    /// Turtle otherwise)
+    paths: Vec<String>,
+    /// Fail to load N3 files with formulae or rules instead of skipping them
+    #[arg(long)]
+    strict_n3: bool,
+    /// Load this text into the default graph, as if from a file (repeatable)
+    #[arg(long, value_name = "TEXT")]
+    data: Vec<String>,
+    /// Syntax of --data: turtle, ntriples, nquads, trig, n3 or rdfxml
+    #[arg(long, value_name = "FORMAT", value_parser = parse_format)]
+    format: Option<RdfFormat>,
+    /// Load up to N files at once (default: the number of cores; 1 loads them one by one)
+    #[arg(long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+    /// Start with this query instead of listing all triples (skips the session offer)
+    #[arg(long, value_name = "SPARQL", conflicts_with = "query_file")]
+    query: Option<String>,
+    /// Start with the query in this file
+    #[arg(long, value_name = "PATH")]
+    query_file: Option<PathBuf>,
+    /// Open a link copied by :share: load its files, run its query and restore its view
+    #[arg(long, value_name = "LINK", conflicts_with_all = ["query", "query_file"])]
+    open_link: Option<String>,
+    /// Start in the query editor or the result table
+    #[arg(long, value_name = "MODE", value_parser = ["query", "browse"])]
+    start_in: Option<String>,
+    /// Run the query once the files are loaded, even with `autorun = false`
+    #[arg(long)]
+    run: bool,
+    /// Refuse to change the loaded data: no editing or deleting of triples, no :clear
+    #[arg(long)]
+    read_only: bool,
+    /// Before an edit, a delete or :clear, save the store as N-Quads in DIR (only the
+    /// affected graphs when the store is over --checkpoint-limit)
+    #[arg(long, value_name = "DIR")]
+    checkpoint_dir: Option<PathBuf>,
+    /// Save no checkpoint of more than N quads; ask before going on without one
+    /// (default 100000)
+    #[arg(long, value_name = "N", requires = "checkpoint_dir")]
+    checkpoint_limit: Option<usize>,
+    /// Answer read-only SPARQL queries over HTTP at http://ADDR/query while running
+    #[arg(long, value_name = "ADDR")]
+    serve: Option<String>,
+    /// Resolve SERVICE clauses against remote SPARQL endpoints
+    #[arg(long)]
+    federated: bool,
+    /// After loading, fetch `owl:imports` over HTTP into named graphs, up to DEPTH levels
+    /// (all levels when DEPTH is omitted)
+    #[arg(long, value_name = "DEPTH", require_equals = true)]
+    follow_imports: Option<Option<usize>>,
+    /// Show at most N rows of a result (0 for all; overrides `max_rows` in the config)
+    #[arg(long, value_name = "N")]
+    max_rows: Option<usize>,
+    /// Write at most N rows with :export (default: all, regardless of --max-rows)
+    #[arg(long, value_name = "N")]
+    export_limit: Option<usize>,
+    /// Export CSV, TSV and tables with plain values: lexical forms, bare IRIs and blank node labels
+    #[arg(long)]
+    plain_values: bool,
+    /// Like --plain-values, with a <name>_type column after each variable for datatypes and languages
+    #[arg(long)]
+    typed_columns: bool,
+    /// Preferred language tag for labels
+    #[arg(long)]
+    lang: Option<String>,
+    /// Language of the interface: en or zh (default: from LC_ALL, LC_MESSAGES or LANG)
+    #[arg(long, value_name = "LANG")]
+    lang_ui: Option<Language>,
+    /// Start the interface even when stdout or stdin is not a terminal
+    #[arg(long)]
+    force_tui: bool,
+    /// Without the interface, give up on the query after SECS seconds (exit code 4)
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+    /// Download at most SIZE (such as 500M) of each URL given in place of a file
+    #[arg(long, value_name = "SIZE", value_parser = headless::parse_size)]
+    max_download: Option<u64>,
+    /// Without the interface, refuse to load a file larger than SIZE (such as 2G) into
+    /// memory (exit code 3); this build has no on-disk store
+    #[arg(long, value_name = "SIZE", value_parser = headless::parse_size)]
+    prefer_disk_above: Option<u64>,
+    /// Print the results without the interface as csv, tsv, json, xml, markdown or org, or a
+    /// CONSTRUCT's triples as ttl, trig, nt or nq (default: tsv and nt)
+    #[arg(long, value_name = "FORMAT", value_parser = parse_export_format)]
+    output: Option<ExportFormat>,
+    /// Send the results without the interface to file:PATH, append:PATH, cmd:COMMAND (its
+    /// stdin, without a shell) or post:URL instead of stdout, streaming them
+    #[arg(long, value_name = "SINK", value_parser = Sink::parse)]
+    sink: Option<Sink>,
+    /// Without the interface, exit with 1 when the query returns nothing
+    #[arg(long)]
+    fail_if_empty: bool,
+    /// Without the interface, print no summary line on stderr
+    #[arg(long, short)]
+    quiet: bool,
+    /// On a normal quit, print the last result to stdout as csv, tsv, json, xml,
+    /// markdown or org, or a CONSTRUCT's triples as ttl, trig, nt or nq; a force-quit or a failed query prints nothing and exits with 1.
+    /// The interface draws on stderr when stdout is not a terminal
+    #[arg(long, value_name = "FORMAT", value_parser = parse_export_format)]
+    emit_on_exit: Option<ExportFormat>,
+    /// Write full IRIs in Turtle, TriG and RDF/XML exports instead of the prefixes
+    /// the loaded files and the config declare
+    #[arg(long)]
+    no_prefixes: bool,
+    /// Draw in LINES rows below the prompt instead of the alternate screen, and
+    /// print the final query and result on exit
+    #[arg(long, value_name = "LINES", require_equals = true)]
+    inline: Option<Option<u16>>,
+    /// Color theme: dark, light or high-contrast; by default, light or dark to
+    /// match the terminal's background
+    #[arg(long, value_name = "NAME")]
+    theme: Option<String>,
+    /// Let a panic while drawing a pane end the session instead of replacing the
+    /// pane with an error box, for development
+    #[arg(long)]
+    debug_render: bool,
+    /// Collect key-to-draw latency, draw time, queue depths, cache hit rates and frame
+    /// rate from the start and show them (F12 or :debug toggles the overlay)
+    #[arg(long)]
+    debug_overlay: bool,
+    /// Use no colors (also enabled by the NO_COLOR environment variable)
+    #[arg(long)]
+    no_color: bool,
+    /// Treat the terminal as TERM=dumb: no colors, no alternate screen and no
+    /// title, clipboard or mouse escape sequences (detected for dumb and serial terminals)
+    #[arg(long)]
+    assume_dumb: bool,
+    /// Replay key presses from FILE (`key <keys>`, `type "<text>"`, `wait <duration>`
+    /// per line) instead of reading the keyboard, then quit
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+    /// With --script, hand control back to the keyboard when the script ends
+    #[arg(long, requires = "script")]
+    script_then_interactive: bool,
+    /// Append a transcript of every key, the actions it led to, queries with their
+    /// durations, commands and errors to FILE, for bug reports
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+    /// With --record, write the selected values and the text typed into filters and
+    /// edited values instead of hashing them
+    #[arg(long, requires = "record")]
+    record_full: bool,
+    /// Drive the interface with the keys of the last session in a --record transcript,
+    /// loading its files unless others are given, then quit
+    #[arg(long, value_name = "FILE", conflicts_with = "script")]
+    replay: Option<PathBuf>,
+    /// Restore the previous session of these files without asking
+    #[arg(long, conflicts_with = "no_restore")]
+    restore: bool,
+    /// Do not offer to restore the previous session
+    #[arg(long)]
+    no_restore: bool,
+    /// Ignore .rdf-tui.rq files and configured startup queries, e.g. for untrusted data
+    #[arg(long)]
+    no_startup_query: bool,
+    /// Run SELECT queries without LIMIT as written, even when they may return millions of rows
+    #[arg(long)]
+    no_guard: bool,
+    /// Start with an empty store instead of offering the recently opened files
+    #[arg(long)]
+    no_recent: bool,
+    /// Never show the first-run overlay that offers the bundled sample dataset
+    #[arg(long)]
+    no_onboarding: bool,
+    /// For screen readers: show the selected row as a sentence instead of the table, without box drawing
+    #[arg(long)]
+    linear: bool,
+    /// Overwrite existing files when exporting, without asking; scripts otherwise refuse
+    #[arg(long)]
+    force: bool,
+    /// Read settings from this file instead of config.toml in the config directory
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Apply this profile (see :profile export) over the config for this session
+    #[arg(long, value_name = "PATH")]
+    profile: Option<PathBuf>,
+    /// Keep the config in DIR instead of $XDG_CONFIG_HOME/rdf-tui (or the platform's equivalent)
+    #[arg(long, value_name = "DIR")]
+    config_dir: Option<PathBuf>,
+    /// Keep sessions, bookmarks and recent files in DIR instead of $XDG_STATE_HOME/rdf-tui
+    /// (or the platform's equivalent), e.g. next to a dataset
+    #[arg(long, value_name = "DIR")]
+    state_dir: Option<PathBuf>,
+    /// Print a commented configuration template and exit
+    #[arg(long)]
+    print_default_config: bool,
+    /// Print what the terminal, locale and config look like to rdf-tui, for bug reports, and exit
+    #[arg(long)]
+    doctor: bool,
+    /// Write a log of loads, queries, errors and actions to this file
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+    /// Minimum level written to the log file (error, warn, info, debug, trace)
+    #[arg(long, value_name = "LEVEL", default_value = "info")]
+    log_level: LevelFilter,
+}
+
+/// 無界面時，啟動之錯誤亦依說明中之退出碼退出，不與假之 ASK 同為 1；有界面則照常返回。
+fn fail<T>(headless: bool, error: anyhow::Error, code: i32) -> anyhow::Result<T> {
+    if headless {
+        eprintln!("Error: {error:#}");
+        process::exit(code);
+    }
+    Err(error)
+}
+
+/// 導出格式之名。
+fn parse_export_format(name: &str) -> Result<ExportFormat, String> {
+    ExportFormat::from_name(name).ok_or_else(|| format!("unknown export format: {name}"))
+}
+
+/// 格式名或其擴展名。
+fn parse_format(name: &str) -> Result<RdfFormat, String> {
+    match name {
+        "turtle" => Some(RdfFormat::Turtle),
+        "ntriples" => Some(RdfFormat::NTriples),
+        "nquads" => Some(RdfFormat::NQuads),
+        "rdfxml" => Some(RdfFormat::RdfXml),
+        _ => RdfFormat::from_extension(name),
+    }
+    .ok_or_else(|| format!("unknown RDF format: {name}"))
 }
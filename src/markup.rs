@@ -0,0 +1,149 @@
+use oxigraph::model::{vocab::xsd, NamedNodeRef, Term};
+use ratatui::text::Span;
+
+/// XSD 之數值類型。
+const NUMERIC: [NamedNodeRef<'_>; 16] = [
+    xsd::INTEGER,
+    xsd::DECIMAL,
+    xsd::DOUBLE,
+    xsd::FLOAT,
+    xsd::INT,
+    xsd::LONG,
+    xsd::SHORT,
+    xsd::BYTE,
+    xsd::NEGATIVE_INTEGER,
+    xsd::NON_NEGATIVE_INTEGER,
+    xsd::NON_POSITIVE_INTEGER,
+    xsd::POSITIVE_INTEGER,
+    xsd::UNSIGNED_BYTE,
+    xsd::UNSIGNED_INT,
+    xsd::UNSIGNED_LONG,
+    xsd::UNSIGNED_SHORT,
+];
+
+/// 各列是否右對齊：已綁定者皆為數值字面量，且至少有一。
+pub fn numeric_columns<'a, R>(
+    columns: usize,
+    rows: &'a [R],
+    cell: impl Fn(&'a R, usize) -> Option<&'a Term>,
+) -> Vec<bool> {
+    (0..columns)
+        .map(|column| {
+            let mut terms = rows.iter().filter_map(|row| cell(row, column)).peekable();
+            terms.peek().is_some() && terms.all(is_numeric)
+        })
+        .collect()
+}
+
+fn is_numeric(term: &Term) -> bool {
+    matches!(term, Term::Literal(literal) if NUMERIC.contains(&literal.datatype()))
+}
+
+/// 以 Markdown 管道表排版。首行為表頭；`numeric` 之列右對齊。`|` 轉義為 `\|`。
+pub fn markdown(lines: &[Vec<String>], numeric: &[bool]) -> String {
+    let lines = escape(lines, |cell| cell.replace('|', "\\|"));
+    let widths = widths(&lines, 3);
+    let separator: Vec<String> = widths
+        .iter()
+        .zip(numeric)
+        .map(|(width, numeric)| match numeric {
+            true => format!("{}:", "-".repeat(width - 1)),
+            false => "-".repeat(*width),
+        })
+        .collect();
+    table(&lines, &widths, numeric, row(&separator))
+}
+
+/// 以 Org 表排版，分隔行作 `|---+---|`。`|` 轉義為 `\vert{}`。
+pub fn org(lines: &[Vec<String>], numeric: &[bool]) -> String {
+    let lines = escape(lines, |cell| cell.replace('|', "\\vert{}"));
+    let widths = widths(&lines, 1);
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+    let separator = format!("|{}|", separator.join("+"));
+    table(&lines, &widths, numeric, separator)
+}
+
+fn escape(lines: &[Vec<String>], escape: impl Fn(&str) -> String) -> Vec<Vec<String>> {
+    lines
+        .iter()
+        .map(|line| line.iter().map(|cell| escape(cell)).collect())
+        .collect()
+}
+
+/// 各列之顯示寬度，至少 `min`。
+fn widths(lines: &[Vec<String>], min: usize) -> Vec<usize> {
+    let columns = lines.first().map_or(0, Vec::len);
+    (0..columns)
+        .map(|column| {
+            lines
+                .iter()
+                .map(|line| Span::raw(line[column].as_str()).width())
+                .max()
+                .unwrap_or(0)
+                .max(min)
+        })
+        .collect()
+}
+
+fn table(lines: &[Vec<String>], widths: &[usize], numeric: &[bool], separator: String) -> String {
+    let mut output = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(widths)
+            .zip(numeric)
+            .map(|((cell, width), numeric)| {
+                let padding = " ".repeat(width - Span::raw(cell.as_str()).width());
+                // 表頭恆左對齊
+                if *numeric && index > 0 {
+                    padding + cell
+                } else {
+                    cell.clone() + &padding
+                }
+            })
+            .collect();
+        output.push(row(&cells));
+        if index == 0 {
+            output.push(separator.clone());
+        }
+    }
+    output.iter().map(|line| format!("{line}\n")).collect()
+}
+
+fn row(cells: &[String]) -> String {
+    format!("| {} |", cells.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines() -> Vec<Vec<String>> {
+        [["?name", "?n"], ["a|b", "7"], ["ünï", "1024"]]
+            .iter()
+            .map(|line| line.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn markdown_escapes_pipes_and_aligns_numbers() {
+        assert_eq!(
+            markdown(&lines(), &[false, true]),
+            "| ?name | ?n   |\n\
+             | ----- | ---: |\n\
+             | a\\|b  |    7 |\n\
+             | ünï   | 1024 |\n"
+        );
+    }
+
+    #[test]
+    fn org_uses_plus_separators() {
+        assert_eq!(
+            org(&lines(), &[false, false]),
+            "| ?name     | ?n   |\n\
+             |-----------+------|\n\
+             | a\\vert{}b | 7    |\n\
+             | ünï       | 1024 |\n"
+        );
+    }
+}
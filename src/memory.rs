@@ -0,0 +1,259 @@
+use oxigraph::model::{Subject, Term};
+use ratatui::text::{Line, Span};
+use std::{mem::size_of, sync::Arc};
+
+/// 內存存儲中每四元組之大約字節數：諸索引中之項，及所編碼之字符串之一份。
+pub const QUAD_BYTES: usize = 400;
+
+/// N-Triples 每行之大約字節數，以文件之大小估其三元組之數。
+pub const LINE_BYTES: u64 = 100;
+
+/// 載入 `bytes` 字節之文件後，內存存儲之大約字節數。
+pub fn load_estimate(bytes: u64) -> usize {
+    (bytes / LINE_BYTES).saturating_mul(QUAD_BYTES as u64) as usize
+}
+
+/// 系統可用之內存，即 `/proc/meminfo` 之 `MemAvailable`。他系統不知，則無。
+pub fn available() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kilobytes: usize = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kilobytes.saturating_mul(1024))
+}
+
+/// 讀 `abbreviate` 之寫法，如 `500M`、`1.5G`；無單位者為字節。
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let shift = match unit.trim().to_ascii_uppercase().trim_end_matches('B') {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        "T" => 40,
+        _ => return Err(format!("{text:?} is not a size such as 500M or 2G")),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("{text:?} is not a size such as 500M or 2G"))?;
+    Ok((number * (1u64 << shift) as f64) as u64)
+}
+
+/// 項於堆上之文本之大約字節數。
+pub fn heap_size(term: &Term) -> usize {
+    match term {
+        Term::NamedNode(iri) => iri.as_str().len(),
+        Term::BlankNode(node) => node.as_str().len(),
+        Term::Literal(literal) => {
+            literal.value().len()
+                + literal.language().map_or(0, str::len)
+                + literal.datatype().as_str().len()
+        }
+        Term::Triple(triple) => {
+            size_of::<Term>()
+                + subject_size(&triple.subject)
+                + triple.predicate.as_str().len()
+                + heap_size(&triple.object)
+        }
+    }
+}
+
+fn subject_size(subject: &Subject) -> usize {
+    match subject {
+        Subject::NamedNode(iri) => iri.as_str().len(),
+        Subject::BlankNode(node) => node.as_str().len(),
+        Subject::Triple(_) => size_of::<Term>(),
+    }
+}
+
+/// 共用之一項：`Arc` 之二計數、項本身與其文本。
+pub fn shared_term(term: &Term) -> usize {
+    2 * size_of::<usize>() + size_of::<Term>() + heap_size(term)
+}
+
+/// 結果之行：`capacity` 行之向量，已有之 `len` 行各 `width` 格。所指之項另計。
+pub fn rows(capacity: usize, len: usize, width: usize) -> usize {
+    capacity * size_of::<Vec<Option<Arc<Term>>>>() + len * width * size_of::<Option<Arc<Term>>>()
+}
+
+/// 一字符串。
+pub fn text(text: &str) -> usize {
+    size_of::<String>() + text.len()
+}
+
+/// 已顯示之一格。
+pub fn line(line: &Line) -> usize {
+    size_of::<Line>()
+        + line
+            .spans
+            .iter()
+            .map(|span| size_of::<Span>() + span.content.len())
+            .sum::<usize>()
+}
+
+/// 各大結構之估計字節數。皆約略之數，以見其量級。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub store: usize,
+    /// 所示之結果，監視中未換入之結果，及比較中之新結果。
+    pub results: usize,
+    /// 已顯示之格之緩存。
+    pub cells: usize,
+    /// 查詢歷史之快照。
+    pub snapshots: usize,
+    /// 前次之結果，留以比較。
+    pub previous: usize,
+    /// 層級與鄰域之樹及其標籤。
+    pub trees: usize,
+    /// 可撤銷與已執行之更新。
+    pub updates: usize,
+}
+
+/// 逾預算時所棄者。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Relief {
+    /// 快照所留之字節數，自舊者棄之。無須棄則無。
+    pub snapshots: Option<usize>,
+    pub cells: bool,
+    pub previous: bool,
+}
+
+impl Relief {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Usage {
+    pub fn total(&self) -> usize {
+        self.store
+            + self.results
+            + self.cells
+            + self.snapshots
+            + self.previous
+            + self.trees
+            + self.updates
+    }
+
+    /// 復歸 `budget` 之內所須棄者：先舊之快照，次格之緩存，再次前次之結果。存儲、
+    /// 所示之結果、樹與更新不棄；僅此數者已逾之，則棄盡可棄者亦不足。
+    pub fn relieve(&self, budget: usize) -> Relief {
+        let mut excess = self.total().saturating_sub(budget);
+        let mut relief = Relief::default();
+        if excess > 0 && self.snapshots > 0 {
+            let dropped = excess.min(self.snapshots);
+            relief.snapshots = Some(self.snapshots - dropped);
+            excess -= dropped;
+        }
+        if excess > 0 && self.cells > 0 {
+            relief.cells = true;
+            excess = excess.saturating_sub(self.cells);
+        }
+        if excess > 0 && self.previous > 0 {
+            relief.previous = true;
+        }
+        relief
+    }
+
+    /// `:memory` 所列：各項及其大小，末為總數與預算。
+    pub fn lines(&self, budget: Option<usize>) -> Vec<(String, String)> {
+        let mut lines: Vec<(String, String)> = [
+            ("store", self.store),
+            ("results", self.results),
+            ("cells", self.cells),
+            ("snapshots", self.snapshots),
+            ("previous", self.previous),
+            ("trees", self.trees),
+            ("updates", self.updates),
+            ("total", self.total()),
+        ]
+        .into_iter()
+        .map(|(name, bytes)| (name.to_string(), format!("~{}", abbreviate(bytes))))
+        .collect();
+        lines.push((
+            "budget".to_string(),
+            budget.map_or_else(|| "none (memory_budget)".to_string(), abbreviate),
+        ));
+        lines
+    }
+}
+
+/// 狀態欄所示之簡寫，如 `1.2G`、`340M`。
+pub fn abbreviate(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1023.95 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match value < 9.95 {
+        true => format!("{value:.1}{}", UNITS[unit]),
+        false => format!("{value:.0}{}", UNITS[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, NamedNode};
+
+    #[test]
+    fn estimates_relieve_the_cheapest_to_rebuild_first() {
+        let term = Term::from(Literal::new_language_tagged_literal_unchecked("chat", "fr"));
+        assert_eq!(heap_size(&term), 4 + 2 + 53);
+        let iri = Term::from(NamedNode::new_unchecked("http://ex/a"));
+        assert_eq!(
+            shared_term(&iri),
+            2 * size_of::<usize>() + size_of::<Term>() + 11
+        );
+        assert_eq!(
+            rows(4, 2, 3),
+            4 * size_of::<Vec<Option<Arc<Term>>>>() + 6 * size_of::<usize>()
+        );
+
+        let usage = Usage {
+            store: 600,
+            results: 100,
+            cells: 50,
+            snapshots: 80,
+            previous: 200,
+            ..Usage::default()
+        };
+        assert_eq!(usage.total(), 1030);
+        assert!(usage.relieve(1030).is_empty());
+        let partial = Relief {
+            snapshots: Some(50),
+            ..Relief::default()
+        };
+        assert_eq!(usage.relieve(1000), partial);
+        let relief = usage.relieve(900);
+        assert_eq!(relief.snapshots, Some(0));
+        assert!(relief.cells && !relief.previous);
+        // 存儲與所示之結果已逾之，可棄者盡棄
+        let all = Relief {
+            snapshots: Some(0),
+            cells: true,
+            previous: true,
+        };
+        assert_eq!(usage.relieve(500), all);
+
+        assert_eq!(abbreviate(512), "512B");
+        assert_eq!(abbreviate(12 * 1024), "12K");
+        assert_eq!(abbreviate(1_288_490_189), "1.2G");
+        assert_eq!(abbreviate(1024 * 1024 - 1), "1.0M");
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("1.5G"), Ok(3 << 29));
+        assert_eq!(parse_size("300mb"), Ok(300 << 20));
+        assert!(parse_size("2 parsecs").is_err() && parse_size("G").is_err());
+        assert_eq!(load_estimate(1000), 10 * QUAD_BYTES);
+    }
+}
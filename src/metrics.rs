@@ -0,0 +1,278 @@
+//! 調試浮層之度量：按鍵至繪畢之延遲、繪製之耗時、事件與後台之隊列深度、緩存之命中率
+//! 及每秒之幀數，各取樣於小環形緩衝。未以 `:debug` 或 `--debug-overlay` 開之者，`App`
+//! 無此物，諸鈎皆不作。
+
+use crate::display::group_digits;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// 每環形緩衝所留之樣本數。
+pub const SAMPLES: usize = 60;
+
+/// 迷你圖之八級。
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// 最近 [`SAMPLES`] 個樣本，舊者先出。
+#[derive(Debug, Clone, Default)]
+pub struct Ring(pub(crate) VecDeque<u64>);
+
+impl Ring {
+    pub fn push(&mut self, value: u64) {
+        if self.0.len() == SAMPLES {
+            self.0.pop_front();
+        }
+        self.0.push_back(value);
+    }
+
+    pub fn last(&self) -> Option<u64> {
+        self.0.back().copied()
+    }
+
+    pub fn max(&self) -> u64 {
+        self.0.iter().copied().max().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> u64 {
+        match self.0.len() {
+            0 => 0,
+            len => self.0.iter().sum::<u64>() / len as u64,
+        }
+    }
+
+    /// 最近至多 `width` 個樣本，每樣本一字，依其中最大者縮放。
+    pub fn sparkline(&self, width: usize) -> String {
+        let recent = self.0.iter().skip(self.0.len().saturating_sub(width));
+        let max = recent.clone().copied().max().unwrap_or_default().max(1);
+        recent
+            .map(|&value| BARS[(value * (BARS.len() as u64 - 1)).div_ceil(max) as usize])
+            .collect()
+    }
+}
+
+/// 一緩存之命中與未中之數。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Hits {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Hits {
+    /// 命中之百分比。未查者為 `None`。
+    pub fn rate(self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 * 100.0 / total as f64)
+    }
+}
+
+/// 所收之度量。延遲與繪製之耗時以微秒計。
+#[derive(Debug)]
+pub struct Metrics {
+    /// 讀得按鍵至其後首次繪畢。
+    pub latency: Ring,
+    /// 每幀之繪製，含寫至終端。
+    pub draw: Ring,
+    /// 每幀時，接連讀得而其後仍有待讀者之事件數。
+    pub events: Ring,
+    /// 每幀時，待載入、待下載及執行中之後台工作之數。
+    pub workers: Ring,
+    /// 每秒所繪之幀數。
+    pub fps: Ring,
+    /// 諸緩存之累計，依首次查詢之序。繪製時以 `&self` 計之。
+    caches: RefCell<Vec<(&'static str, Hits)>>,
+    /// 未繪之首個按鍵之時。
+    key: Option<Instant>,
+    backlog: u64,
+    /// 本秒之始及其幀數。
+    second: (Instant, u64),
+}
+
+impl Metrics {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            latency: Ring::default(),
+            draw: Ring::default(),
+            events: Ring::default(),
+            workers: Ring::default(),
+            fps: Ring::default(),
+            caches: RefCell::default(),
+            key: None,
+            backlog: 0,
+            second: (now, 0),
+        }
+    }
+
+    /// 於 `at` 讀得一按鍵；`waiting` 為其後已有他事件待讀。延遲自未繪之首個按鍵計。
+    pub fn input(&mut self, at: Instant, waiting: bool) {
+        self.key.get_or_insert(at);
+        self.backlog = match waiting {
+            true => self.backlog + 1,
+            false => 0,
+        };
+    }
+
+    /// 一幀始於 `started`，繪畢於 `now`。
+    pub fn frame(&mut self, started: Instant, now: Instant, workers: usize) {
+        self.draw.push(micros(now - started));
+        if let Some(key) = self.key.take() {
+            self.latency.push(micros(now - key));
+        }
+        self.events.push(self.backlog);
+        self.workers.push(workers as u64);
+        let (start, frames) = &mut self.second;
+        *frames += 1;
+        let elapsed = now - *start;
+        if elapsed >= Duration::from_secs(1) {
+            self.fps
+                .push((*frames as f64 / elapsed.as_secs_f64()).round() as u64);
+            self.second = (now, 0);
+        }
+    }
+
+    /// 計名為 `name` 之緩存之命中與未中。
+    pub fn cache(&self, name: &'static str, hits: u64, misses: u64) {
+        let mut caches = self.caches.borrow_mut();
+        let index = match caches.iter().position(|(known, _)| *known == name) {
+            Some(index) => index,
+            None => {
+                caches.push((name, Hits::default()));
+                caches.len() - 1
+            }
+        };
+        caches[index].1.hits += hits;
+        caches[index].1.misses += misses;
+    }
+
+    pub fn caches(&self) -> Vec<(&'static str, Hits)> {
+        self.caches.borrow().clone()
+    }
+
+    /// 一行之摘要，寫於日誌以附於性能之報告。
+    pub fn summary(&self) -> String {
+        let ring = |name: &str, ring: &Ring| {
+            format!(
+                "{name} last={} mean={} max={}",
+                ring.last().unwrap_or_default(),
+                ring.mean(),
+                ring.max()
+            )
+        };
+        let mut parts = vec![
+            ring("latency_us", &self.latency),
+            ring("draw_us", &self.draw),
+            ring("events", &self.events),
+            ring("workers", &self.workers),
+            ring("fps", &self.fps),
+        ];
+        for (name, hits) in self.caches() {
+            parts.push(format!(
+                "cache_{name} hits={} misses={}",
+                hits.hits, hits.misses
+            ));
+        }
+        parts.join(" ")
+    }
+
+    /// 浮層之諸行：名、迷你圖及其最近之值。
+    pub fn lines(&self, width: usize) -> Vec<(String, String, String)> {
+        // 延遲與繪製之耗時示為毫秒
+        let rings = [
+            ("key→draw", &self.latency, true),
+            ("draw", &self.draw, true),
+            ("events", &self.events, false),
+            ("workers", &self.workers, false),
+            ("fps", &self.fps, false),
+        ];
+        let mut lines: Vec<_> = rings
+            .into_iter()
+            .map(|(name, ring, timed)| {
+                let show = |value: u64| match timed {
+                    true => format!("{:.1} ms", value as f64 / 1000.0),
+                    false => value.to_string(),
+                };
+                let value = match ring.last() {
+                    Some(last) => format!("{} (max {})", show(last), show(ring.max())),
+                    None => "-".to_string(),
+                };
+                (name.to_string(), ring.sparkline(width), value)
+            })
+            .collect();
+        for (name, hits) in self.caches() {
+            let value = match hits.rate() {
+                Some(rate) => format!(
+                    "{rate:.1}% of {}",
+                    group_digits((hits.hits + hits.misses) as usize)
+                ),
+                None => "-".to_string(),
+            };
+            lines.push((format!("{name} cache"), String::new(), value));
+        }
+        lines
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros().try_into().unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_sample_latency_frames_and_caches() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut metrics = Metrics::new(start);
+        // 二鍵接連而至，延遲自首鍵計
+        metrics.input(at(0), true);
+        metrics.input(at(5), false);
+        metrics.frame(at(10), at(12), 2);
+        assert_eq!(metrics.latency.last(), Some(12_000));
+        assert_eq!(metrics.draw.last(), Some(2_000));
+        assert_eq!(metrics.events.last(), Some(0));
+        assert_eq!(metrics.workers.last(), Some(2));
+        // 無按鍵之幀不計延遲
+        metrics.frame(at(20), at(21), 0);
+        assert_eq!(metrics.latency.mean(), 12_000);
+        metrics.input(at(30), true);
+        metrics.frame(at(1000), at(1001), 0);
+        assert_eq!(metrics.events.last(), Some(1));
+        assert_eq!(metrics.fps.last(), Some(3));
+
+        metrics.cache("cells", 3, 1);
+        metrics.cache("labels", 0, 2);
+        metrics.cache("cells", 1, 0);
+        assert_eq!(metrics.caches()[0].1.rate(), Some(80.0));
+        let summary = metrics.summary();
+        assert!(
+            summary.starts_with("latency_us last=971000 mean=491500 max=971000 draw_us"),
+            "{summary}"
+        );
+        assert!(summary.ends_with("cache_cells hits=4 misses=1 cache_labels hits=0 misses=2"));
+        let lines = metrics.lines(10);
+        assert_eq!(lines[0].2, "971.0 ms (max 971.0 ms)");
+        assert_eq!(
+            lines[6],
+            ("labels cache".into(), String::new(), "0.0% of 2".into())
+        );
+    }
+
+    #[test]
+    fn rings_keep_the_latest_samples_and_scale_their_sparkline() {
+        let mut ring = Ring::default();
+        assert_eq!(ring.sparkline(8), "");
+        for value in 0..100 {
+            ring.push(value);
+        }
+        assert_eq!((ring.0.len(), ring.0[0], ring.max()), (SAMPLES, 40, 99));
+        let mut ring = Ring::default();
+        for value in [0, 1, 4, 8] {
+            ring.push(value);
+        }
+        assert_eq!(ring.sparkline(8), "▁▂▅█");
+        assert_eq!(ring.sparkline(2), "▅█");
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{label::resolve_label, memory, util::offset_index};
+use oxigraph::{
+    model::{GraphNameRef, NamedNode, SubjectRef, Term, TermRef},
+    store::Store,
+};
+use std::{collections::BTreeMap, mem::size_of};
+
+/// 每謂語顯示之賓語上限。餘者合為「… n more」一行，以免樞紐節點撐爆界面。
+const FAN_OUT: usize = 12;
+
+/// 自中心起可展開之層數。
+const MAX_HOPS: usize = 2;
+
+/// 節點之種類。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    /// 資源或字面量。
+    Term(Term),
+    /// 一組同謂語之出邊。
+    Predicate(NamedNode),
+    /// 因上限未列出之賓語數。
+    More(usize),
+}
+
+/// 樹之節點。資源之出邊於展開時方查詢。
+struct Node {
+    kind: Kind,
+    label: Option<String>,
+    parent: Option<usize>,
+    /// `None` 表未載入。
+    children: Option<Vec<usize>>,
+    expanded: bool,
+    /// 祖先中已有此資源，不再展開。
+    cycle: bool,
+    /// 距中心之跳數。
+    hops: usize,
+}
+
+/// 可見之一行。
+pub struct VisibleNode<'a> {
+    pub depth: usize,
+    pub kind: &'a Kind,
+    pub label: Option<&'a str>,
+    pub expanded: bool,
+    /// 無可展開者，如字面量、無出邊之資源或已達層數上限者。
+    pub leaf: bool,
+    pub cycle: bool,
+}
+
+/// 一資源之鄰域：以之為中心，出邊按謂語分組，可再展開一層。
+pub struct Neighborhood {
+    lang: Option<String>,
+    nodes: Vec<Node>,
+    /// 所選之可見行。
+    selected: usize,
+}
+
+impl Neighborhood {
+    /// 以某資源為中心新建，並展開之。
+    pub fn new(store: &Store, center: Term, lang: Option<String>) -> anyhow::Result<Self> {
+        let mut neighborhood = Self {
+            lang,
+            nodes: vec![],
+            selected: 0,
+        };
+        let label = neighborhood.label(store, &center);
+        neighborhood.nodes.push(Node {
+            kind: Kind::Term(center),
+            label,
+            parent: None,
+            children: None,
+            expanded: false,
+            cycle: false,
+            hops: 0,
+        });
+        neighborhood.expand(store)?;
+        Ok(neighborhood)
+    }
+
+    /// 中心之資源。
+    pub fn center(&self) -> &Term {
+        match &self.nodes[0].kind {
+            Kind::Term(term) => term,
+            _ => unreachable!("the center is a term"),
+        }
+    }
+
+    pub fn center_label(&self) -> Option<&str> {
+        self.nodes[0].label.as_deref()
+    }
+
+    /// 估計之字節數：各節點及其項、標籤與子節點之表。
+    pub fn bytes(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| {
+                let kind = match &node.kind {
+                    Kind::Term(term) => memory::heap_size(term),
+                    Kind::Predicate(iri) => iri.as_str().len(),
+                    Kind::More(_) => 0,
+                };
+                size_of::<Node>()
+                    + kind
+                    + node.label.as_deref().map_or(0, memory::text)
+                    + node
+                        .children
+                        .as_ref()
+                        .map_or(0, |c| c.len() * size_of::<usize>())
+            })
+            .sum()
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// 所選節點之項。謂語與「更多」行則無。
+    pub fn selected_term(&self) -> Option<&Term> {
+        let index = *self.visible_indices().get(self.selected)?;
+        match &self.nodes[index].kind {
+            Kind::Term(term) => Some(term),
+            _ => None,
+        }
+    }
+
+    /// 所選上下移動若干節點。
+    pub fn select_by(&mut self, delta: isize) {
+        let len = self.visible_indices().len();
+        self.selected = offset_index(self.selected, delta, len);
+    }
+
+    /// 展開所選節點。資源首次展開時查詢其出邊。
+    pub fn expand(&mut self, store: &Store) -> anyhow::Result<()> {
+        let Some(&index) = self.visible_indices().get(self.selected) else {
+            return Ok(());
+        };
+        if !self.expandable(index) {
+            return Ok(());
+        }
+        if self.nodes[index].children.is_none() {
+            let children = self.fetch(store, index)?;
+            self.nodes[index].children = Some(children);
+        }
+        self.nodes[index].expanded = true;
+        Ok(())
+    }
+
+    /// 收起所選節點。已收起者則移至其父。
+    pub fn collapse(&mut self) {
+        let visible = self.visible_indices();
+        let Some(&index) = visible.get(self.selected) else {
+            return;
+        };
+        let node = &mut self.nodes[index];
+        if node.expanded {
+            node.expanded = false;
+        } else if let Some(parent) = node.parent {
+            self.selected = visible.iter().position(|&i| i == parent).unwrap_or(0);
+        }
+    }
+
+    /// 依深度優先列出可見行。
+    pub fn visible(&self) -> Vec<VisibleNode<'_>> {
+        self.visible_indices()
+            .into_iter()
+            .map(|index| {
+                let node = &self.nodes[index];
+                VisibleNode {
+                    depth: self.depth(index),
+                    kind: &node.kind,
+                    label: node.label.as_deref(),
+                    expanded: node.expanded,
+                    leaf: !self.expandable(index)
+                        || node.children.as_ref().is_some_and(Vec::is_empty),
+                    cycle: node.cycle,
+                }
+            })
+            .collect()
+    }
+
+    fn visible_indices(&self) -> Vec<usize> {
+        let mut visible = vec![];
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            visible.push(index);
+            let node = &self.nodes[index];
+            if node.expanded {
+                if let Some(children) = &node.children {
+                    stack.extend(children.iter().rev());
+                }
+            }
+        }
+        visible
+    }
+
+    fn depth(&self, mut index: usize) -> usize {
+        let mut depth = 0;
+        while let Some(parent) = self.nodes[index].parent {
+            depth += 1;
+            index = parent;
+        }
+        depth
+    }
+
+    /// 謂語組恆可展開；資源須可為主語，未成環，且未達層數上限。
+    fn expandable(&self, index: usize) -> bool {
+        let node = &self.nodes[index];
+        match &node.kind {
+            Kind::Predicate(_) => true,
+            Kind::Term(Term::Literal(_)) | Kind::More(_) => false,
+            Kind::Term(_) => !node.cycle && node.hops < MAX_HOPS,
+        }
+    }
+
+    /// 祖先（含自身）中是否已有 `term`。
+    fn on_path(&self, mut index: usize, term: &Term) -> bool {
+        loop {
+            if self.nodes[index].kind == Kind::Term(term.clone()) {
+                return true;
+            }
+            match self.nodes[index].parent {
+                Some(parent) => index = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// 查詢默認圖中一資源之出邊，建謂語組及賓語節點，返回謂語組之索引。
+    fn fetch(&mut self, store: &Store, index: usize) -> anyhow::Result<Vec<usize>> {
+        let Kind::Term(term) = &self.nodes[index].kind else {
+            return Ok(vec![]);
+        };
+        let subject = match term.as_ref() {
+            TermRef::NamedNode(iri) => SubjectRef::NamedNode(iri),
+            TermRef::BlankNode(node) => SubjectRef::BlankNode(node),
+            TermRef::Triple(triple) => SubjectRef::Triple(triple),
+            TermRef::Literal(_) => return Ok(vec![]),
+        };
+        // 謂語依 IRI 排序；賓語依存儲之序，僅留上限內者而計其餘
+        let mut edges: BTreeMap<String, (NamedNode, Vec<Term>, usize)> = BTreeMap::new();
+        for quad in
+            store.quads_for_pattern(Some(subject), None, None, Some(GraphNameRef::DefaultGraph))
+        {
+            let quad = quad?;
+            let (_, objects, hidden) = edges
+                .entry(quad.predicate.as_str().to_string())
+                .or_insert_with(|| (quad.predicate.clone(), vec![], 0));
+            if objects.len() < FAN_OUT {
+                objects.push(quad.object);
+            } else {
+                *hidden += 1;
+            }
+        }
+
+        let hops = self.nodes[index].hops + 1;
+        let mut groups = vec![];
+        for (_, (predicate, objects, hidden)) in edges {
+            let label = resolve_label(store, &predicate, self.lang.as_deref());
+            let group = self.push(Kind::Predicate(predicate), label, index, hops);
+            let mut children = vec![];
+            for object in objects {
+                let cycle = self.on_path(index, &object);
+                let label = self.label(store, &object);
+                let child = self.push(Kind::Term(object), label, group, hops);
+                self.nodes[child].cycle = cycle;
+                children.push(child);
+            }
+            if hidden > 0 {
+                children.push(self.push(Kind::More(hidden), None, group, hops));
+            }
+            let node = &mut self.nodes[group];
+            node.children = Some(children);
+            node.expanded = true;
+            groups.push(group);
+        }
+        Ok(groups)
+    }
+
+    fn push(&mut self, kind: Kind, label: Option<String>, parent: usize, hops: usize) -> usize {
+        self.nodes.push(Node {
+            kind,
+            label,
+            parent: Some(parent),
+            children: None,
+            expanded: false,
+            cycle: false,
+            hops,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn label(&self, store: &Store, term: &Term) -> Option<String> {
+        match term {
+            Term::NamedNode(iri) => resolve_label(store, iri, self.lang.as_deref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::{RdfFormat, RdfParser};
+
+    fn store(turtle: &str) -> Store {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(RdfParser::from_format(RdfFormat::Turtle), turtle.as_bytes())
+            .unwrap();
+        store
+    }
+
+    fn iri(iri: &str) -> Term {
+        NamedNode::new_unchecked(iri).into()
+    }
+
+    #[test]
+    fn edges_are_grouped_capped_and_cycles_marked() {
+        let objects: String = (0..FAN_OUT + 5)
+            .map(|n| format!("<http://ex/o{n}>, "))
+            .collect();
+        let store = store(&format!(
+            "<http://ex/hub> <http://ex/has> {objects}<http://ex/last> ; \
+               <http://ex/next> <http://ex/a> . \
+             <http://ex/a> <http://ex/next> <http://ex/hub>, <http://ex/b> . \
+             <http://ex/b> <http://ex/next> <http://ex/c> ."
+        ));
+        let mut neighborhood = Neighborhood::new(&store, iri("http://ex/hub"), None).unwrap();
+        let visible = neighborhood.visible();
+        // 中心、謂語 has、上限內之賓語、更多、謂語 next、a
+        assert_eq!(visible.len(), FAN_OUT + 5);
+        assert_eq!(
+            visible[1].kind,
+            &Kind::Predicate(NamedNode::new_unchecked("http://ex/has"))
+        );
+        assert_eq!(visible[FAN_OUT + 2].kind, &Kind::More(6));
+
+        // 展開 a：回指中心者為環，b 可見而不可再展開
+        neighborhood.select_by(FAN_OUT as isize + 4);
+        assert_eq!(neighborhood.selected_term(), Some(&iri("http://ex/a")));
+        neighborhood.expand(&store).unwrap();
+        let visible = neighborhood.visible();
+        let hub = visible
+            .iter()
+            .find(|node| node.depth == 4 && node.kind == &Kind::Term(iri("http://ex/hub")))
+            .unwrap();
+        assert!(hub.cycle && hub.leaf);
+        let b = visible
+            .iter()
+            .find(|node| node.kind == &Kind::Term(iri("http://ex/b")))
+            .unwrap();
+        assert!(b.leaf && !b.cycle);
+
+        // 收起後再收起則回到父節點
+        neighborhood.collapse();
+        assert_eq!(neighborhood.visible().len(), FAN_OUT + 5);
+        neighborhood.collapse();
+        assert_eq!(neighborhood.selected(), FAN_OUT + 3);
+    }
+}
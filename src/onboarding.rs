@@ -0,0 +1,49 @@
+use crate::{paths::create_dir, startup};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// 隨程序所附之例：週期表之前五週期，元素之符號、原子量、週期與類別。
+pub const SAMPLE: &str = include_str!("sample.ttl");
+
+/// 例之啓動查詢，載入後即行之。
+pub const SAMPLE_QUERY: &str = include_str!("sample.rq");
+
+/// 寫例於 `dir` 下之 `sample` 目錄，旁置其啓動查詢，返回數據之路徑，以照常載入之。
+pub fn write_sample(dir: &Path) -> io::Result<PathBuf> {
+    let dir = dir.join("sample");
+    create_dir(&dir)?;
+    let path = dir.join("elements.ttl");
+    fs::write(&path, SAMPLE)?;
+    fs::write(dir.join(startup::FILE_NAME), SAMPLE_QUERY)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::{
+        io::{RdfFormat, RdfParser},
+        sparql::QueryResults,
+        store::Store,
+    };
+
+    #[test]
+    fn the_sample_parses_and_its_query_lists_every_element() {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(RdfParser::from_format(RdfFormat::Turtle), SAMPLE.as_bytes())
+            .unwrap();
+        assert!((300..1000).contains(&store.len().unwrap()));
+        let QueryResults::Solutions(solutions) = store.query(SAMPLE_QUERY).unwrap() else {
+            panic!("not a SELECT");
+        };
+        assert_eq!(solutions.count(), 54);
+
+        let dir = std::env::temp_dir().join(format!("rdf-tui-sample-{}", std::process::id()));
+        let path = write_sample(&dir).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), SAMPLE);
+        assert!(dir.join("sample").join(startup::FILE_NAME).is_file());
+    }
+}
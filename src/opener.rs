@@ -0,0 +1,139 @@
+use std::{
+    process::{Command, Stdio},
+    thread,
+};
+
+/// 默認所許之方案，他者須列於 `open_schemes`。
+pub const SCHEMES: &[&str] = &["http", "https", "file"];
+
+/// 命令模板中代以 IRI 之參數。
+pub const PLACEHOLDER: &str = "{iri}";
+
+/// 系統打開 IRI 之命令。
+pub fn default_command() -> Vec<String> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    vec![program.to_string(), PLACEHOLDER.to_string()]
+}
+
+/// 可交予外部程序之 IRI：方案須為 `SCHEMES` 或 `allowed` 中者，不區分大小寫；URI 所不許之
+/// 字符，如空白、引號、換行與非 ASCII 者，以其 UTF-8 之字節百分號編碼。
+pub fn prepare(iri: &str, allowed: &[String]) -> Result<String, String> {
+    let scheme = iri
+        .split_once(':')
+        .map(|(scheme, _)| scheme)
+        .filter(|scheme| {
+            let mut chars = scheme.chars();
+            chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+                && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        })
+        .ok_or_else(|| format!("{iri} has no scheme"))?;
+    let permitted = SCHEMES
+        .iter()
+        .copied()
+        .chain(allowed.iter().map(String::as_str))
+        .any(|known| known.eq_ignore_ascii_case(scheme));
+    if !permitted {
+        return Err(format!(
+            "Not opening {scheme}: IRIs (allowed: {}; open_schemes adds others)",
+            SCHEMES.join(", ")
+        ));
+    }
+    let mut encoded = String::with_capacity(iri.len());
+    for byte in iri.bytes() {
+        let excluded = matches!(
+            byte,
+            b'"' | b'<' | b'>' | b'\\' | b'^' | b'`' | b'{' | b'|' | b'}'
+        );
+        match (0x21..=0x7e).contains(&byte) && !excluded {
+            true => encoded.push(byte as char),
+            false => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    Ok(encoded)
+}
+
+/// 依模板構造命令，不經 shell。模板首項為程序，各項為一整參數；項中之 `{iri}` 代以 IRI，
+/// 其中之空格、引號與 `;` 皆不分割亦不解釋。無 `{iri}` 者附 IRI 於末。
+pub fn command(template: &[String], iri: &str) -> Result<Command, String> {
+    let (program, args) = template.split_first().ok_or("open_command is empty")?;
+    let mut command = Command::new(program);
+    command.args(args.iter().map(|arg| arg.replace(PLACEHOLDER, iri)));
+    if !template.iter().any(|arg| arg.contains(PLACEHOLDER)) {
+        command.arg(iri);
+    }
+    Ok(command)
+}
+
+/// 於背景打開 IRI，不待其結束。輸出棄之，以免亂界面。
+pub fn open(template: &[String], iri: &str) -> Result<(), String> {
+    let mut child = command(template, iri)?
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|error| format!("Fail to run {}: {error}", template[0]))?;
+    // 收其退出之狀態，免留殭屍進程
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostile_iris_reach_the_child_as_one_inert_argument() {
+        assert_eq!(
+            prepare("http://x/$(rm -rf)", &[]).as_deref(),
+            Ok("http://x/$(rm%20-rf)")
+        );
+        assert_eq!(
+            prepare("https://x/\"; echo pwned; \"", &[]).as_deref(),
+            Ok("https://x/%22;%20echo%20pwned;%20%22")
+        );
+        assert_eq!(
+            prepare("HTTP://x/a\nb/東", &[]).as_deref(),
+            Ok("HTTP://x/a%0Ab/%E6%9D%B1")
+        );
+        assert!(prepare("javascript:alert(1)", &[]).is_err());
+        assert!(prepare("mailto:a@example.org", &[]).is_err());
+        assert!(prepare("mailto:a@example.org", &["mailto".to_string()]).is_ok());
+        assert!(prepare("no scheme", &[]).is_err());
+        assert!(command(&[], "http://x/").is_err());
+
+        #[cfg(unix)]
+        for iri in [
+            "http://x/$(rm -rf)",
+            "http://x/'; touch /tmp/pwned; '",
+            "http://x/a\nb\"c` -v",
+        ] {
+            let template = ["printf", "[%s]", "{iri}"].map(String::from);
+            let output = command(&template, iri).unwrap().output().unwrap();
+            assert_eq!(
+                String::from_utf8(output.stdout).unwrap(),
+                format!("[{iri}]")
+            );
+            // 無 `{iri}` 者附於末，項中之 `{iri}` 亦不分割
+            let output = command(&["printf".to_string(), "<%s>".to_string()], iri)
+                .unwrap()
+                .output()
+                .unwrap();
+            assert_eq!(
+                String::from_utf8(output.stdout).unwrap(),
+                format!("<{iri}>")
+            );
+            let template = ["printf", "%s|%s", "--url={iri}", "x"].map(String::from);
+            let output = command(&template, iri).unwrap().output().unwrap();
+            assert_eq!(
+                String::from_utf8(output.stdout).unwrap(),
+                format!("--url={iri}|x")
+            );
+        }
+    }
+}
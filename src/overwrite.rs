@@ -0,0 +1,85 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// 所寫之文件已存在時如何。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overwrite {
+    /// 問用戶：覆蓋、改名或取消。
+    Ask,
+    /// 不問而覆蓋，如 `--force`。
+    Always,
+    /// 不寫而報錯，如無人可問之腳本。
+    Never,
+}
+
+/// 寫入中之文件名。
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+/// 先寫至 `<path>.partial`，成則易名為 `path`，故中斷者不以 `path` 之名留半截之文件。
+/// 失敗則刪之，`keep_partial` 則留之。
+pub fn write_then_rename<T>(
+    path: &Path,
+    keep_partial: bool,
+    write: impl FnOnce(&Path) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let partial = partial_path(path);
+    let result = write(&partial).and_then(|value| {
+        fs::rename(&partial, path)?;
+        Ok(value)
+    });
+    if result.is_err() && !keep_partial {
+        let _ = fs::remove_file(&partial);
+    }
+    result
+}
+
+/// 不存在之名：`path` 本身，或於擴展名前加 `-1`、`-2`……
+pub fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{stem}-{n}{extension}")))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_count_up_and_failed_writes_leave_nothing() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-overwrite-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.csv");
+        assert_eq!(unique_path(&path), path);
+        fs::write(&path, "old").unwrap();
+        fs::write(dir.join("out-1.csv"), "").unwrap();
+        assert_eq!(unique_path(&path), dir.join("out-2.csv"));
+        fs::write(dir.join("README"), "").unwrap();
+        assert_eq!(unique_path(&dir.join("README")), dir.join("README-1"));
+
+        let failed = write_then_rename(&path, false, |partial| -> anyhow::Result<()> {
+            fs::write(partial, "half")?;
+            anyhow::bail!("interrupted")
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        assert!(!partial_path(&path).exists());
+        write_then_rename(&path, false, |partial| Ok(fs::write(partial, "new")?)).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,280 @@
+use crate::util::common_prefix;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// 可載入之文件之擴展名：RDF 及他處所存之結果。
+pub const OPEN_EXTENSIONS: &[&str] = &[
+    "ttl", "nt", "nq", "trig", "n3", "rdf", "owl", "xml", "srj", "json", "srx", "tsv", "csv",
+];
+
+/// 路徑之狀態，示於輸入行之末。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// 所讀之文件存在。
+    Exists,
+    /// 所讀之文件不存在。
+    Missing,
+    /// 為目錄，不可讀寫。
+    Directory,
+    /// 所寫之文件尚無，將新建之。
+    New,
+    /// 所寫之文件已有，將覆蓋之。
+    Overwrites,
+    /// 所寫之文件之目錄不存在。
+    NoDirectory,
+}
+
+impl State {
+    pub fn label(self) -> &'static str {
+        match self {
+            State::Exists => "exists",
+            State::Missing => "not found",
+            State::Directory => "a directory",
+            State::New => "new file",
+            State::Overwrites => "overwrites",
+            State::NoDirectory => "no such directory",
+        }
+    }
+
+    /// 是否須留意：讀之不得，或將覆蓋。
+    pub fn is_alarming(self) -> bool {
+        !matches!(self, State::Exists | State::New)
+    }
+}
+
+/// 輸入行中之路徑：自 `start` 起至行末，所期之擴展名，及是否將寫之。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathArg<'a> {
+    pub start: usize,
+    pub text: &'a str,
+    /// 空則不篩。
+    pub extensions: &'a [&'a str],
+    pub writing: bool,
+}
+
+impl PathArg<'_> {
+    /// 路徑之狀態。未輸入則無。
+    pub fn state(&self) -> Option<State> {
+        if self.text.trim().is_empty() {
+            return None;
+        }
+        let path = expand(self.text.trim());
+        Some(match fs::metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => State::Directory,
+            Ok(_) if self.writing => State::Overwrites,
+            Ok(_) => State::Exists,
+            Err(_) if !self.writing => State::Missing,
+            Err(_) => match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() && !dir.is_dir() => State::NoDirectory,
+                _ => State::New,
+            },
+        })
+    }
+}
+
+/// 展開路徑首之 `~` 為家目錄。無 `HOME` 則不展開。
+pub fn expand(text: &str) -> PathBuf {
+    let home = || env::var_os("HOME").filter(|home| !home.is_empty());
+    match text.strip_prefix('~') {
+        Some("") => home().map_or_else(|| PathBuf::from(text), PathBuf::from),
+        Some(rest) if rest.starts_with('/') => match home() {
+            Some(home) => Path::new(&home).join(rest.trim_start_matches('/')),
+            None => PathBuf::from(text),
+        },
+        _ => PathBuf::from(text),
+    }
+}
+
+/// 與 `partial` 相符之目錄項，目錄附 `/`，依名排序。文件依擴展名篩，無一相符則不篩；
+/// 目錄不篩。以 `.` 起之項，唯 `partial` 之末段亦以 `.` 起方列之。目錄不可讀則無。
+pub fn matches(partial: &str, extensions: &[&str]) -> Vec<String> {
+    let (dir, prefix) = split(partial);
+    let read_dir = if dir.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand(dir)
+    };
+    let Ok(entries) = fs::read_dir(read_dir) else {
+        return vec![];
+    };
+    let mut names: Vec<(String, bool)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let mut name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            // 循符號連結；斷者作文件
+            let is_dir = fs::metadata(entry.path()).is_ok_and(|metadata| metadata.is_dir());
+            if is_dir {
+                name.push('/');
+            }
+            Some((name, is_dir))
+        })
+        .collect();
+    let wanted = |name: &str| {
+        Path::new(name)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                extensions
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(extension))
+            })
+    };
+    if !extensions.is_empty() && names.iter().any(|(name, is_dir)| !is_dir && wanted(name)) {
+        names.retain(|(name, is_dir)| *is_dir || wanted(name));
+    }
+    let mut names: Vec<String> = names.into_iter().map(|(name, _)| name).collect();
+    names.sort();
+    names
+}
+
+/// 補全路徑至諸候選之公共前綴，唯一者則補全之，返回補全者及其餘候選。
+pub fn complete(partial: &str, extensions: &[&str]) -> (String, Vec<String>) {
+    // `~` 補為家目錄
+    if partial == "~" {
+        return ("~/".to_string(), vec![]);
+    }
+    let (dir, prefix) = split(partial);
+    let names = matches(partial, extensions);
+    match names.as_slice() {
+        [] => (partial.to_string(), vec![]),
+        [name] => (format!("{dir}{name}"), vec![]),
+        _ => (format!("{dir}{}", common_prefix(prefix, &names)), names),
+    }
+}
+
+/// 分路徑為目錄部分（含末之 `/`）與末段。
+fn split(partial: &str) -> (&str, &str) {
+    match partial.rfind('/') {
+        Some(i) => (&partial[..=i], &partial[i + 1..]),
+        None => ("", partial),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rdf-tui-path-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for file in [
+            "data.ttl",
+            "data.nt",
+            "notes.txt",
+            ".hidden.ttl",
+            "sub/inner.ttl",
+        ] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn completion_filters_by_extension_and_follows_trailing_slashes() {
+        let dir = fixture("complete");
+        let base = format!("{}/", dir.display());
+        let ttl = &["ttl"][..];
+
+        assert_eq!(
+            complete(&format!("{base}d"), ttl),
+            (format!("{base}data.ttl"), vec![])
+        );
+        // 無一相符則不篩
+        assert_eq!(
+            complete(&format!("{base}n"), ttl).0,
+            format!("{base}notes.txt")
+        );
+        let (common, candidates) = complete(&format!("{base}d"), &[]);
+        assert_eq!(common, format!("{base}data."));
+        assert_eq!(candidates, ["data.nt", "data.ttl"]);
+        // 目錄補 `/`，末有 `/` 則列其內
+        assert_eq!(complete(&format!("{base}su"), ttl).0, format!("{base}sub/"));
+        assert_eq!(
+            complete(&format!("{base}sub/"), ttl).0,
+            format!("{base}sub/inner.ttl")
+        );
+        assert_eq!(matches(&base, ttl), ["data.ttl", "sub/"]);
+        assert_eq!(matches(&format!("{base}."), ttl), [".hidden.ttl"]);
+        // 不存在之目錄
+        assert_eq!(
+            complete(&format!("{base}nowhere/x"), ttl),
+            (format!("{base}nowhere/x"), vec![])
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_followed_and_unreadable_directories_yield_nothing() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = fixture("links");
+        symlink(dir.join("sub"), dir.join("linked")).unwrap();
+        symlink(dir.join("gone.ttl"), dir.join("broken.ttl")).unwrap();
+        let base = format!("{}/", dir.display());
+        assert_eq!(
+            complete(&format!("{base}l"), &[]).0,
+            format!("{base}linked/")
+        );
+        assert_eq!(
+            complete(&format!("{base}linked/"), &[]).0,
+            format!("{base}linked/inner.ttl")
+        );
+        assert_eq!(
+            complete(&format!("{base}b"), &[]).0,
+            format!("{base}broken.ttl")
+        );
+
+        let locked = dir.join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("secret.ttl"), "").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        let partial = format!("{}/s", locked.display());
+        // 特權用戶仍可讀之
+        if fs::read_dir(&locked).is_err() {
+            assert_eq!(complete(&partial, &[]), (partial.clone(), vec![]));
+        }
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn states_tell_reads_from_overwrites() {
+        let dir = fixture("state");
+        fn arg(text: &str, writing: bool) -> PathArg<'_> {
+            PathArg {
+                start: 0,
+                text,
+                extensions: &[],
+                writing,
+            }
+        }
+        let existing = format!("{}/data.ttl", dir.display());
+        let fresh = format!("{}/out.csv", dir.display());
+        let nowhere = format!("{}/nowhere/out.csv", dir.display());
+        assert_eq!(arg(&existing, false).state(), Some(State::Exists));
+        assert_eq!(arg(&fresh, false).state(), Some(State::Missing));
+        assert_eq!(arg(&existing, true).state(), Some(State::Overwrites));
+        assert_eq!(arg(&fresh, true).state(), Some(State::New));
+        assert_eq!(arg(&nowhere, true).state(), Some(State::NoDirectory));
+        assert_eq!(
+            arg(&dir.display().to_string(), true).state(),
+            Some(State::Directory)
+        );
+        assert_eq!(arg("out.csv", true).state(), Some(State::New));
+        assert_eq!(arg("", true).state(), None);
+        fs::remove_dir_all(dir).unwrap();
+
+        if let Some(home) = env::var_os("HOME").filter(|home| !home.is_empty()) {
+            assert_eq!(expand("~/a.ttl"), Path::new(&home).join("a.ttl"));
+            assert_eq!(expand("~"), PathBuf::from(&home));
+        }
+        assert_eq!(expand("~user/a.ttl"), PathBuf::from("~user/a.ttl"));
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+const APP: &str = "rdf-tui";
+
+/// 配置與狀態之所在。凡存於磁盤者皆經此取其路徑，目錄待寫時方建。
+///
+/// 默認依平台：Linux 等依 XDG 之 `$XDG_CONFIG_HOME` 與 `$XDG_STATE_HOME`；macOS 為
+/// `~/Library/Application Support/rdf-tui`，設 XDG 之變量則從之；Windows 為 `%APPDATA%` 與
+/// `%LOCALAPPDATA%`。`--config-dir`、`--state-dir` 與 `--config` 代之。
+#[derive(Debug, Clone, Default)]
+pub struct Paths {
+    config_dir: Option<PathBuf>,
+    state_dir: Option<PathBuf>,
+    /// `--config` 所指之文件。
+    config_file: Option<PathBuf>,
+}
+
+impl Paths {
+    pub fn new(
+        config_dir: Option<PathBuf>,
+        state_dir: Option<PathBuf>,
+        config_file: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            config_dir,
+            state_dir,
+            config_file,
+        }
+    }
+
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        self.config_dir
+            .clone()
+            .or_else(|| platform_dir("XDG_CONFIG_HOME", ".config", "APPDATA"))
+    }
+
+    pub fn state_dir(&self) -> Option<PathBuf> {
+        self.state_dir
+            .clone()
+            .or_else(|| platform_dir("XDG_STATE_HOME", ".local/state", "LOCALAPPDATA"))
+    }
+
+    /// 下載之緩存。給 `--state-dir` 者在其下，否則依 XDG 之 `$XDG_CACHE_HOME`。
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        let base = match &self.state_dir {
+            Some(dir) => dir.clone(),
+            None => platform_dir("XDG_CACHE_HOME", ".cache", "LOCALAPPDATA")?,
+        };
+        Some(base.join("downloads"))
+    }
+
+    /// `--config` 所指之文件。給定則必須存在。
+    pub fn given_config(&self) -> Option<&Path> {
+        self.config_file.as_deref()
+    }
+
+    /// 所讀之配置文件：`--config` 所指者，或配置目錄中之 `config.toml`。
+    pub fn config_file(&self) -> Option<PathBuf> {
+        self.config_file
+            .clone()
+            .or_else(|| Some(self.config_dir()?.join("config.toml")))
+    }
+
+    /// 最近打開之文件之記錄。
+    pub fn recent_file(&self) -> Option<PathBuf> {
+        Some(self.state_dir()?.join("recent.tsv"))
+    }
+
+    /// 有此文件則已示過首次啓動之引導。
+    pub fn onboarding_marker(&self) -> Option<PathBuf> {
+        Some(self.state_dir()?.join("onboarded"))
+    }
+
+    /// 此數據集之會話。
+    pub fn session_file(&self, sources: &[PathBuf]) -> Option<PathBuf> {
+        self.dataset_file("sessions", sources)
+    }
+
+    /// 此數據集之書籤。
+    pub fn bookmarks_file(&self, sources: &[PathBuf]) -> Option<PathBuf> {
+        self.dataset_file("bookmarks", sources)
+    }
+
+    fn dataset_file(&self, kind: &str, sources: &[PathBuf]) -> Option<PathBuf> {
+        if sources.is_empty() {
+            return None;
+        }
+        Some(
+            self.state_dir()?
+                .join(kind)
+                .join(format!("{}.tsv", dataset_key(sources))),
+        )
+    }
+
+    /// `:paths` 所列：各項及其路徑。未載入文件則無會話與書籤。
+    pub fn lines(&self, sources: &[PathBuf]) -> Vec<(String, String)> {
+        let describe = |path: Option<PathBuf>, missing: &str| match path {
+            Some(path) if path.exists() => path.display().to_string(),
+            Some(path) => format!("{} (not yet created)", path.display()),
+            None => missing.to_string(),
+        };
+        let no_home = "unknown: no home directory";
+        let no_files = "none: no files loaded";
+        vec![
+            ("config".to_string(), describe(self.config_file(), no_home)),
+            ("state".to_string(), describe(self.state_dir(), no_home)),
+            ("downloads".to_string(), describe(self.cache_dir(), no_home)),
+            (
+                "recent files".to_string(),
+                describe(self.recent_file(), no_home),
+            ),
+            (
+                "session".to_string(),
+                describe(self.session_file(sources), no_files),
+            ),
+            (
+                "bookmarks".to_string(),
+                describe(self.bookmarks_file(sources), no_files),
+            ),
+        ]
+    }
+}
+
+/// 依平台之基目錄加 `rdf-tui`。`xdg` 之變量為空則以 `home_relative` 代之。
+fn platform_dir(xdg: &str, home_relative: &str, windows: &str) -> Option<PathBuf> {
+    let var = |name: &str| {
+        env::var_os(name)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    };
+    let base = if cfg!(windows) {
+        var(windows)?
+    } else if cfg!(target_os = "macos") {
+        var(xdg).or_else(|| Some(var("HOME")?.join("Library/Application Support")))?
+    } else {
+        var(xdg).or_else(|| Some(var("HOME")?.join(home_relative)))?
+    };
+    Some(base.join(APP))
+}
+
+/// 建一目錄及其上級。失敗者如權限不足，錯誤中明其目錄。
+pub fn create_dir(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir).map_err(|error| {
+        io::Error::new(
+            error.kind(),
+            format!("Cannot create {}: {error}", dir.display()),
+        )
+    })
+}
+
+/// 建文件所在之目錄。
+pub fn create_parent(file: &Path) -> io::Result<()> {
+    match file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => create_dir(dir),
+        None => Ok(()),
+    }
+}
+
+/// 以所載入之路徑集合為數據集之鍵。與順序無關，跨版本穩定（FNV-1a）。
+pub fn dataset_key(paths: &[PathBuf]) -> String {
+    let mut paths: Vec<_> = paths.iter().map(|p| p.to_string_lossy()).collect();
+    paths.sort();
+    paths.dedup();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in paths.join("\n").bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_place_every_file() {
+        let state = env::temp_dir().join(format!("rdf-tui-paths-{}", std::process::id()));
+        let paths = Paths::new(Some(PathBuf::from("/etc/rdf")), Some(state.clone()), None);
+        let sources = [PathBuf::from("/data/a.ttl")];
+        assert_eq!(
+            paths.config_file(),
+            Some(PathBuf::from("/etc/rdf/config.toml"))
+        );
+        assert_eq!(paths.recent_file(), Some(state.join("recent.tsv")));
+        assert_eq!(paths.cache_dir(), Some(state.join("downloads")));
+        let session = paths.session_file(&sources).unwrap();
+        assert!(session.starts_with(state.join("sessions")));
+        assert_eq!(paths.session_file(&[]), None);
+
+        create_parent(&session).unwrap();
+        assert!(state.join("sessions").is_dir());
+        // 父為文件，則無從建之
+        fs::write(state.join("file"), "").unwrap();
+        let error = create_parent(&state.join("file/sub/x.tsv")).unwrap_err();
+        assert!(error.to_string().starts_with("Cannot create "), "{error}");
+        assert!(error.to_string().contains("file/sub"), "{error}");
+    }
+}
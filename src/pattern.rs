@@ -0,0 +1,243 @@
+use crate::display::compact_iri;
+use oxigraph::{
+    io::{RdfFormat, RdfParser},
+    model::{GraphNameRef, NamedNode, Subject, Term, Triple, Variable},
+    store::{StorageError, Store},
+};
+
+/// 表單各欄之名，依主語、謂語、賓語之序。
+pub const FIELDS: [&str; 3] = ["subject", "predicate", "object"];
+
+/// 三元組模式之表單。各欄為一項之 Turtle 寫法，空者為通配。
+#[derive(Debug, Default)]
+pub struct PatternForm {
+    pub fields: [String; 3],
+    /// 輸入所在之欄。
+    pub focus: usize,
+}
+
+impl PatternForm {
+    pub fn push(&mut self, ch: char) {
+        self.fields[self.focus].push(ch);
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        self.fields[self.focus].pop()
+    }
+
+    /// 移至後或前若干欄，首尾相接。
+    pub fn cycle(&mut self, delta: isize) {
+        self.focus = (self.focus as isize + delta).rem_euclid(FIELDS.len() as isize) as usize;
+    }
+
+    /// 以某項填某欄，並移至該欄。
+    pub fn fill(&mut self, field: usize, term: &Term, prefixes: &[(String, String)]) {
+        self.fields[field] = term_text(term, prefixes);
+        self.focus = field;
+    }
+
+    /// 補全所在欄之前綴名。唯一相符者即補全，返回相符之前綴。
+    pub fn complete(&mut self, prefixes: &[(String, String)]) -> Vec<String> {
+        let field = &mut self.fields[self.focus];
+        if field.contains(':') || field.starts_with(['<', '"', '_']) {
+            return vec![];
+        }
+        let candidates: Vec<String> = prefixes
+            .iter()
+            .filter(|(prefix, _)| prefix.starts_with(field.as_str()))
+            .map(|(prefix, _)| format!("{prefix}:"))
+            .collect();
+        if let [candidate] = candidates.as_slice() {
+            field.clone_from(candidate);
+        }
+        candidates
+    }
+
+    /// 解析各欄。錯誤指明其欄。
+    pub fn parse(&self, prefixes: &[(String, String)]) -> Result<Pattern, String> {
+        let term = |field: usize| {
+            parse_term(&self.fields[field], field, prefixes)
+                .map_err(|error| format!("Invalid {}: {error}", FIELDS[field]))
+        };
+        let (subject, predicate, object) = (term(0)?, term(1)?, term(2)?);
+        Ok(Pattern {
+            subject: subject.map(|triple| triple.subject),
+            predicate: predicate.map(|triple| triple.predicate),
+            object: object.map(|triple| triple.object),
+        })
+    }
+}
+
+/// 已解析之三元組模式。`None` 為通配。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub subject: Option<Subject>,
+    pub predicate: Option<NamedNode>,
+    pub object: Option<Term>,
+}
+
+impl Pattern {
+    /// 結果表之列。
+    pub fn variables() -> Vec<Variable> {
+        ["s", "p", "o"].map(Variable::new_unchecked).to_vec()
+    }
+
+    /// 默認圖中相符之三元組，與查詢所見者同。
+    pub fn rows<'a>(
+        &self,
+        store: &'a Store,
+    ) -> impl Iterator<Item = Result<[Term; 3], StorageError>> + 'a {
+        store
+            .quads_for_pattern(
+                self.subject.as_ref().map(Into::into),
+                self.predicate.as_ref().map(Into::into),
+                self.object.as_ref().map(Into::into),
+                Some(GraphNameRef::DefaultGraph),
+            )
+            .map(|quad| {
+                let quad = quad?;
+                Ok([quad.subject.into(), quad.predicate.into(), quad.object])
+            })
+    }
+
+    /// 如 `?s <http://ex/p> ?o`。
+    pub fn describe(&self) -> String {
+        let part = |term: Option<String>, name: &str| term.unwrap_or_else(|| format!("?{name}"));
+        [
+            part(self.subject.as_ref().map(Subject::to_string), "s"),
+            part(self.predicate.as_ref().map(NamedNode::to_string), "p"),
+            part(self.object.as_ref().map(Term::to_string), "o"),
+        ]
+        .join(" ")
+    }
+}
+
+/// 項之寫法，可填回表單：IRI 盡量以前綴縮寫，餘者如 N-Triples。
+pub fn term_text(term: &Term, prefixes: &[(String, String)]) -> String {
+    if let Term::NamedNode(iri) = term {
+        if let Some(compact) = compact_iri(iri.as_str(), prefixes) {
+            let (_, local) = compact.split_once(':').unwrap_or_default();
+            // 僅取無需轉義之本地名
+            let simple = local
+                .chars()
+                .all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.'))
+                && !local.starts_with(['-', '.'])
+                && !local.ends_with('.');
+            if simple {
+                return compact;
+            }
+        }
+    }
+    term.to_string()
+}
+
+/// 以 Turtle 解析一謂語，如 `rdfs:label`、`<http://…>` 或 `a`。
+pub fn parse_predicate(text: &str, prefixes: &[(String, String)]) -> Result<NamedNode, String> {
+    match parse_term(text, 1, prefixes)? {
+        Some(triple) => Ok(triple.predicate),
+        None => Err("missing predicate".to_string()),
+    }
+}
+
+/// 以 Turtle 解析一欄，置於三元組之相應位置，以便前綴、`a` 與字面量之簡寫皆可用。
+/// 空者為通配。
+fn parse_term(
+    text: &str,
+    field: usize,
+    prefixes: &[(String, String)],
+) -> Result<Option<Triple>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let mut document: String = prefixes
+        .iter()
+        .map(|(prefix, namespace)| format!("@prefix {prefix}: <{namespace}> .\n"))
+        .collect();
+    let mut parts = ["<urn:x-s>", "<urn:x-p>", "<urn:x-o>"];
+    parts[field] = text;
+    document.push_str(&format!("{} .\n", parts.join(" ")));
+    let triples = RdfParser::from_format(RdfFormat::Turtle)
+        .parse_read(document.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| {
+            // 位置出自所拼之文檔，於用戶無益，略之
+            let message = error.to_string();
+            match message.split_once(": ") {
+                Some((_, reason)) => reason.to_string(),
+                None => message,
+            }
+        })?;
+    match <[_; 1]>::try_from(triples) {
+        Ok([quad]) => Ok(Some(quad.into())),
+        Err(_) => Err("not a single term".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{BlankNode, Literal};
+
+    fn prefixes() -> Vec<(String, String)> {
+        vec![
+            ("ex".to_string(), "http://ex/".to_string()),
+            ("exa".to_string(), "http://exa/".to_string()),
+        ]
+    }
+
+    #[test]
+    fn fields_are_terms_in_turtle() {
+        let mut form = PatternForm {
+            fields: ["_:b1".into(), "a".into(), "ex:Dog".into()],
+            focus: 0,
+        };
+        let pattern = form.parse(&prefixes()).unwrap();
+        assert_eq!(pattern.subject, Some(BlankNode::new_unchecked("b1").into()));
+        assert_eq!(
+            pattern.describe(),
+            "_:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://ex/Dog>"
+        );
+        form.fields = ["".into(), "".into(), "42".into()];
+        assert_eq!(
+            form.parse(&prefixes()).unwrap().object,
+            Some(Literal::from(42).into())
+        );
+        form.fields[0] = "\"x\"".into();
+        assert!(form
+            .parse(&prefixes())
+            .unwrap_err()
+            .starts_with("Invalid subject: "));
+        form.fields[0] = "rdfs:Class".into();
+        assert_eq!(
+            form.parse(&prefixes()).unwrap_err(),
+            "Invalid subject: The prefix rdfs: has not been declared"
+        );
+        form.fields[0] = "ex:a ex:b ex:c . ex:d".into();
+        assert_eq!(
+            form.parse(&prefixes()).unwrap_err(),
+            "Invalid subject: not a single term"
+        );
+    }
+
+    #[test]
+    fn prefixes_complete_and_terms_round_trip() {
+        let mut form = PatternForm::default();
+        form.fields[0] = "e".into();
+        assert_eq!(form.complete(&prefixes()), ["ex:", "exa:"]);
+        form.fields[0] = "exa".into();
+        form.complete(&prefixes());
+        assert_eq!(form.fields[0], "exa:");
+
+        for term in [
+            Term::from(NamedNode::new_unchecked("http://ex/Dog")),
+            NamedNode::new_unchecked("http://ex/a/b").into(),
+            Literal::new_language_tagged_literal_unchecked("chien", "fr").into(),
+        ] {
+            form.fill(2, &term, &prefixes());
+            assert_eq!(form.parse(&prefixes()).unwrap().object, Some(term));
+        }
+        assert_eq!(form.fields[2], "\"chien\"@fr");
+        assert_eq!(form.focus, 2);
+    }
+}
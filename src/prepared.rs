@@ -0,0 +1,84 @@
+use oxigraph::sparql::{Query, SparqlSyntaxError};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+/// 緩存之查詢數。
+const CAPACITY: usize = 64;
+
+/// 已解析之查詢，按文字與基礎 IRI 緩存，供監視、自動執行等反復執行同一查詢者共用。
+/// 數據集（FROM 等）為解析所得，不隨存儲之內容而變，故載入數據後無須作廢。
+/// 逾 `CAPACITY` 條則去最久未用者。可跨線程共用，複製者共用同一緩存。
+#[derive(Debug, Clone, Default)]
+pub struct Prepared {
+    entries: Arc<Mutex<VecDeque<Entry>>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    text: String,
+    base: Option<String>,
+    query: Query,
+}
+
+impl Prepared {
+    /// 取已解析者，無則解析而存之。語法有誤者不存。
+    pub fn get(&self, text: &str, base: Option<&str>) -> Result<Query, SparqlSyntaxError> {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let found = entries
+            .iter()
+            .position(|entry| entry.text == text && entry.base.as_deref() == base);
+        if let Some(index) = found {
+            let entry = entries.remove(index).expect("found above");
+            let query = entry.query.clone();
+            entries.push_back(entry);
+            return Ok(query);
+        }
+        drop(entries);
+        let query = Query::parse(text, base)?;
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        if entries.len() >= CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(Entry {
+            text: text.to_string(),
+            base: base.map(str::to_string),
+            query: query.clone(),
+        });
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_queries_are_evicted() {
+        let prepared = Prepared::default();
+        let text = |n: usize| format!("SELECT * WHERE {{ ?s ?p {n} }}");
+        for n in 0..CAPACITY {
+            prepared.get(&text(n), None).unwrap();
+        }
+        // 用第一條，則先去者為第二條
+        prepared.get(&text(0), None).unwrap();
+        prepared.get(&text(CAPACITY), None).unwrap();
+        let entries = prepared.entries.lock().unwrap();
+        assert_eq!(entries.len(), CAPACITY);
+        assert!(entries.iter().any(|entry| entry.text == text(0)));
+        assert!(!entries.iter().any(|entry| entry.text == text(1)));
+        drop(entries);
+
+        assert!(prepared.get("SELECT", None).is_err());
+        assert_eq!(prepared.entries.lock().unwrap().len(), CAPACITY);
+        let based = prepared.get("SELECT * WHERE { ?s ?p <a> }", Some("http://ex/"));
+        assert!(based.unwrap().to_string().contains("<http://ex/a>"));
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    action::BINDABLE,
+    config::{quote, section_mode, Config, Keyscheme, KEYS, SECTIONS},
+    keymap::Keymap,
+    locale::Language,
+    theme::Tint,
+};
+use std::fmt::Write;
+
+/// 導出之文件之首。
+const HEADER: &str = "\
+# rdf-tui profile: the settings in effect when it was exported, in the format
+# of config.toml (rdf-tui --print-default-config documents every key). Apply it
+# over the config for one session with --profile PATH or :profile import PATH.
+";
+
+/// 所報之改動至多列此數，餘者計其數。
+const LISTED: usize = 8;
+
+/// 一項：節、鍵及已寫為 TOML 之值。頂層之節為空。
+type Field = (String, String, String);
+
+/// 導入所改者及配置之警告。
+#[derive(Debug, Default)]
+pub struct Applied {
+    /// 值有變之項，如 `theme`、`prefixes.ex`、`keys.quit`。
+    pub overridden: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Applied {
+    /// 如 `overrides theme, tint and 3 more`。
+    pub fn describe(&self) -> String {
+        let listed = self.overridden.len().min(LISTED);
+        let mut text = match listed {
+            0 => return "overrides nothing".to_string(),
+            _ => format!("overrides {}", self.overridden[..listed].join(", ")),
+        };
+        if self.overridden.len() > listed {
+            let _ = write!(text, " and {} more", self.overridden.len() - listed);
+        }
+        text
+    }
+}
+
+/// 寫配置之諸項為一文件，節與鍵同於配置文件，故亦可為配置文件。鍵位全列之，默認綁定而
+/// 已解除者寫為 `[]`。
+pub fn export(config: &Config) -> String {
+    let mut text = HEADER.to_string();
+    let mut section = "";
+    for (name, key, value) in &fields(config) {
+        if name != section {
+            let _ = write!(text, "\n[{name}]\n");
+            section = name;
+        }
+        let key = match key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            true => key.clone(),
+            false => quote(key),
+        };
+        let _ = writeln!(text, "{key} = {value}");
+    }
+    text
+}
+
+/// 以文件之諸項蓋 `config`，不改配置文件。出錯則 `config` 不變。
+pub fn import(config: &mut Config, text: &str) -> anyhow::Result<Applied> {
+    let before = fields(config);
+    let mut merged = config.clone();
+    let warnings = merged.merge(text)?;
+    let mut overridden: Vec<String> = vec![];
+    for (section, key, value) in fields(&merged) {
+        let name = match section.as_str() {
+            "" => key.clone(),
+            section => format!("{section}.{key}"),
+        };
+        if !before.contains(&(section, key, value)) && !overridden.contains(&name) {
+            overridden.push(name);
+        }
+    }
+    *config = merged;
+    Ok(Applied {
+        overridden,
+        warnings,
+    })
+}
+
+/// 配置之諸項，依 `KEYS` 與 `SECTIONS` 之序。未設而依環境或終端者略去；啓動之查詢以
+/// 數據之路徑為鍵，隨機器而異，亦略去。
+fn fields(config: &Config) -> Vec<Field> {
+    let mut fields = vec![];
+    let mut push = |section: &str, key: &str, value: String| {
+        fields.push((section.to_string(), key.to_string(), value));
+    };
+    for key in KEYS {
+        if let Some(value) = top_level(config, key) {
+            push("", key, value);
+        }
+    }
+    for (name, namespace) in &config.prefixes {
+        push("prefixes", name, quote(namespace));
+    }
+    for preset in &config.layouts {
+        push("layouts", &preset.name, quote(&preset.spec()));
+    }
+    for (name, color) in &config.colors {
+        push("theme", name, quote(&color.to_string()));
+    }
+    let defaults = Keymap::new(config.keyscheme);
+    for section in SECTIONS {
+        let Some(mode) = section_mode(section) else {
+            continue;
+        };
+        let (bound, default) = (config.keymap.bindings(mode), defaults.bindings(mode));
+        for (name, action, _) in BINDABLE {
+            let keys = bound.iter().find(|(bound, _)| bound == action);
+            let value = match keys {
+                Some((_, keys)) => array(keys),
+                None if default.iter().any(|(bound, _)| bound == action) => "[]".to_string(),
+                None => continue,
+            };
+            push(section, name, value);
+        }
+    }
+    fields
+}
+
+/// 字串之數組，如 `["a", "b"]`。
+fn array(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|item| quote(item)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// 頂層之一鍵之值，寫法同於配置文件。
+fn top_level(config: &Config, key: &str) -> Option<String> {
+    let megabytes = |bytes: usize| (bytes >> 20).to_string();
+    Some(match key {
+        "max_rows" => config.max_rows.unwrap_or(0).to_string(),
+        "max_cells" => config.max_cells.unwrap_or(0).to_string(),
+        "max_result_memory" => megabytes(config.max_result_memory.unwrap_or(0)),
+        "lang" => quote(config.lang.as_deref().unwrap_or_default()),
+        "lang_ui" => quote(match config.ui_language? {
+            Language::En => "en",
+            Language::Zh => "zh",
+        }),
+        "theme" => quote(config.theme.as_deref()?),
+        "no_color" => config.no_color.to_string(),
+        "timeout" => config.timeout.as_secs().to_string(),
+        "autorun" => config.autorun.to_string(),
+        "tick_rate" => config.tick_rate.as_millis().to_string(),
+        "null" => quote(&config.null),
+        "layout" => quote(&config.layout),
+        "keyscheme" => quote(match config.keyscheme {
+            Keyscheme::Default => "default",
+            Keyscheme::Vim => "vim",
+        }),
+        "terminal_title" => config.terminal_title.to_string(),
+        "key_hints" => config.key_hints.to_string(),
+        "startup_query_files" => config.startup_query_files.to_string(),
+        "width_sample" => config.width_sample.to_string(),
+        "max_table_width" => config.max_table_width.to_string(),
+        "tint" => quote(match config.tint {
+            Tint::Off => "off",
+            Tint::Kind => "kind",
+            Tint::Column => "column",
+        }),
+        "bidi_cells" => quote(config.bidi_cells.name()),
+        "wrap_columns" => config.wrap_columns.to_string(),
+        "yank" => quote(config.yank.name()),
+        "check_typography" => config.check_typography.to_string(),
+        "stable_order" => config.stable_order.to_string(),
+        "default_query" => quote(config.default_query.as_deref()?),
+        "large_store" => config.large_store.to_string(),
+        "auto_refresh_quads" => config.auto_refresh_quads.to_string(),
+        "auto_refresh_ms" => config.auto_refresh_time.as_millis().to_string(),
+        "keep_partial_exports" => config.keep_partial_exports.to_string(),
+        "open_command" => array(config.open_command.as_deref()?),
+        "open_schemes" => array(&config.open_schemes),
+        "hub_threshold" => config.hub_threshold.to_string(),
+        "snapshot_rows" => config.snapshot_rows.to_string(),
+        "snapshot_memory" => megabytes(config.snapshot_memory),
+        "memory_budget" => megabytes(config.memory_budget.unwrap_or(0)),
+        "load_memory_share" => config.load_memory_share.to_string(),
+        "download_retries" => config.download_retries.to_string(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{action::Action, app::Mode, config::DEFAULT_CONFIG, keymap::parse_sequence};
+
+    #[test]
+    fn profiles_round_trip_and_report_what_they_override() {
+        // 導出者即配置文件之格式，每項皆見於模板
+        let default = Config::default();
+        let text = export(&default);
+        for (section, key, _) in fields(&default) {
+            match section.as_str() {
+                "" => assert!(DEFAULT_CONFIG.contains(&format!("# {key} = ")), "{key}"),
+                section => assert!(
+                    DEFAULT_CONFIG.contains(&format!("[{section}]")),
+                    "{section}"
+                ),
+            }
+        }
+        let (parsed, warnings) = Config::parse(&text).unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(fields(&parsed), fields(&default));
+
+        let (mut config, _) = Config::parse(
+            "theme = \"light\"\ntint = \"kind\"\n[prefixes]\nex = \"http://ex/\"\n\
+             [keys]\nselect_first = []",
+        )
+        .unwrap();
+        let exported = export(&config);
+        assert!(exported.contains("\nselect_first = []\n"), "{exported}");
+        let applied = import(
+            &mut config,
+            "theme = \"dark\"\ntint = \"kind\"\nnull = \"-\"\n[prefixes]\nex = \"http://example.org/\"\n\
+             [keys]\nquit = \"x\"\nmystery = \"y\"",
+        )
+        .unwrap();
+        assert_eq!(
+            applied.overridden,
+            ["theme", "null", "prefixes.ex", "keys.quit"]
+        );
+        assert!(applied.warnings[0].contains("unknown action `mystery`"));
+        assert_eq!(
+            applied.describe(),
+            "overrides theme, null, prefixes.ex, keys.quit"
+        );
+        assert_eq!(
+            config.prefixes,
+            [("ex".to_string(), "http://example.org/".to_string())]
+        );
+        let keys = parse_sequence("x").unwrap();
+        assert_eq!(
+            config.keymap.resolve(Mode::Browse, &keys),
+            Some(Action::Quit)
+        );
+        // 原配置所解除者仍解除
+        assert!(export(&config).contains("\nselect_first = []\n"));
+
+        // 導入自身之導出者無改動；有誤者不改配置
+        let again = export(&config);
+        assert!(import(&mut config, &again).unwrap().overridden.is_empty());
+        assert!(import(&mut config, "tint = \"loud\"").is_err());
+        assert_eq!(config.tint, Tint::Kind);
+        assert_eq!(Applied::default().describe(), "overrides nothing");
+    }
+}
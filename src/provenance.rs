@@ -0,0 +1,211 @@
+use oxigraph::model::{GraphName, Literal};
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// 一圖之來源：自何處、何時載入、含幾個三元組。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphLoad {
+    /// 圖名，如 `<http://ex/g>`，與結果中之項同寫法；默認圖為 `None`。
+    pub graph: Option<String>,
+    /// 文件之路徑，或導入之 URL。
+    pub source: String,
+    /// 載入之時，自 Unix 紀元起之秒數。
+    pub loaded_at: u64,
+    pub triples: usize,
+}
+
+impl GraphLoad {
+    /// 來源之短名：文件名，或 URL 之末段。
+    pub fn short_source(&self) -> &str {
+        if self.source.contains("://") {
+            let path = self.source.split(['#', '?']).next().unwrap_or_default();
+            return match path.trim_end_matches('/').rsplit_once('/') {
+                Some((_, last)) if !last.is_empty() => last,
+                _ => &self.source,
+            };
+        }
+        Path::new(&self.source)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.source)
+    }
+}
+
+/// 各圖之載入記錄，依載入之序。
+#[derive(Debug, Default)]
+pub struct Provenance {
+    entries: Vec<GraphLoad>,
+}
+
+impl Provenance {
+    pub fn entries(&self) -> &[GraphLoad] {
+        &self.entries
+    }
+
+    /// 記一來源所載入之各圖。先棄此來源之舊記錄，故重新載入後不重複。
+    pub fn record(&mut self, source: &str, graphs: &[(GraphName, usize)]) {
+        let loaded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.entries.retain(|entry| entry.source != source);
+        self.entries
+            .extend(graphs.iter().map(|(graph, triples)| GraphLoad {
+                graph: match graph {
+                    GraphName::DefaultGraph => None,
+                    graph => Some(graph.to_string()),
+                },
+                source: source.to_string(),
+                loaded_at,
+                triples: *triples,
+            }));
+    }
+
+    /// 併入會話所存、此次未記之圖之記錄。
+    pub fn merge(&mut self, saved: Vec<GraphLoad>) {
+        for entry in saved {
+            if !self.entries.iter().any(|known| known.graph == entry.graph) {
+                self.entries.push(entry);
+            }
+        }
+    }
+
+    /// 棄某圖之記錄。`graph` 如 `<http://ex/g>`，默認圖為 `None`。
+    pub fn remove_graph(&mut self, graph: Option<&str>) {
+        self.entries.retain(|entry| entry.graph.as_deref() != graph);
+    }
+
+    /// 一圖複製於另一圖：後者之記錄換為前者之記錄，來源與時刻如舊。
+    pub fn copy_graph(&mut self, from: Option<&str>, to: Option<&str>) {
+        if from == to {
+            return;
+        }
+        self.remove_graph(to);
+        let copies: Vec<GraphLoad> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.graph.as_deref() == from)
+            .map(|entry| GraphLoad {
+                graph: to.map(str::to_string),
+                ..entry.clone()
+            })
+            .collect();
+        self.entries.extend(copies);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 具名圖之來源之短名。數源共一圖者以逗號相連；非所記之圖則無。
+    pub fn origin(&self, graph: &str) -> Option<String> {
+        let names: Vec<&str> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.graph.as_deref() == Some(graph))
+            .map(GraphLoad::short_source)
+            .collect();
+        (!names.is_empty()).then(|| names.join(", "))
+    }
+
+    /// 列出各記錄之查詢，以 `VALUES` 內聯，故可如常瀏覽、導出。
+    /// 默認圖之圖名為空；空白節點不可入 `VALUES`，以其名之字符串代之。
+    pub fn report_query(&self) -> String {
+        let rows: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let graph = match entry.graph.as_deref() {
+                    None => "UNDEF".to_string(),
+                    Some(name) if name.starts_with('<') => name.to_string(),
+                    Some(name) => Literal::new_simple_literal(name).to_string(),
+                };
+                format!(
+                    "({graph} {} \"{}\"^^<http://www.w3.org/2001/XMLSchema#dateTime> {})",
+                    Literal::new_simple_literal(&entry.source),
+                    timestamp(entry.loaded_at),
+                    entry.triples
+                )
+            })
+            .collect();
+        format!(
+            "SELECT ?graph ?source ?loaded ?triples WHERE {{\n  \
+               VALUES (?graph ?source ?loaded ?triples) {{\n    {}\n  }}\n}}",
+            rows.join("\n    ")
+        )
+    }
+}
+
+/// 自 Unix 紀元起之秒數為 UTC 之 `xsd:dateTime`，如 `2024-07-01T12:00:00Z`。
+pub fn timestamp(seconds: u64) -> String {
+    let (days, time) = (seconds / 86400, seconds % 86400);
+    // 公曆之換算，見 Howard Hinnant 之 `civil_from_days`
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::NamedNode;
+
+    #[test]
+    fn reloading_a_source_replaces_its_graphs() {
+        let graph = GraphName::from(NamedNode::new_unchecked("http://ex/g"));
+        let mut provenance = Provenance::default();
+        provenance.record(
+            "/data/a.trig",
+            &[(GraphName::DefaultGraph, 2), (graph.clone(), 3)],
+        );
+        provenance.record("https://ex.org/onto.ttl#", &[(graph.clone(), 1)]);
+        assert_eq!(
+            provenance.origin("<http://ex/g>").as_deref(),
+            Some("a.trig, onto.ttl")
+        );
+        provenance.record("/data/a.trig", &[(graph, 4)]);
+        let triples: Vec<usize> = provenance.entries().iter().map(|e| e.triples).collect();
+        assert_eq!(triples, [1, 4]);
+        assert_eq!(provenance.origin("<http://ex/other>"), None);
+    }
+
+    #[test]
+    fn copied_graphs_keep_their_sources() {
+        let mut provenance = Provenance::default();
+        provenance.record("/data/a.ttl", &[(GraphName::DefaultGraph, 2)]);
+        provenance.record(
+            "/data/b.trig",
+            &[(NamedNode::new_unchecked("http://ex/g").into(), 5)],
+        );
+        provenance.copy_graph(None, Some("<http://ex/g>"));
+        assert_eq!(provenance.origin("<http://ex/g>").as_deref(), Some("a.ttl"));
+        provenance.remove_graph(None);
+        assert_eq!(provenance.entries().len(), 1);
+    }
+
+    #[test]
+    fn timestamps_are_utc_date_times() {
+        assert_eq!(timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(timestamp(951_782_400 + 3_661), "2000-02-29T01:01:01Z");
+        assert_eq!(timestamp(1_719_835_200), "2024-07-01T12:00:00Z");
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    bookmarks::{escape, unescape},
+    paths::create_parent,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// 所記之文件數上限。
+const CAPACITY: usize = 20;
+
+/// 最近打開之一文件。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// 打開之時，自紀元起之秒數。
+    pub opened: u64,
+    /// 打開時所載入之三元組數，結果文件則為行數。
+    pub triples: usize,
+}
+
+impl Entry {
+    /// 文件中之一行：時間、三元組數與轉義之路徑，以製表符分隔。
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}",
+            self.opened,
+            self.triples,
+            escape(&self.path.to_string_lossy())
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(3, '\t');
+        Some(Self {
+            opened: fields.next()?.parse().ok()?,
+            triples: fields.next()?.parse().ok()?,
+            path: PathBuf::from(unescape(fields.next()?)),
+        })
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+}
+
+/// 最近打開之文件，新者在前。若有文件則每次修改皆寫回。
+#[derive(Debug, Default)]
+pub struct Recent {
+    entries: Vec<Entry>,
+    file: Option<PathBuf>,
+}
+
+impl Recent {
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// 改用 `file` 存儲，讀入其中所記者。損壞之行略去。
+    pub fn attach(&mut self, file: PathBuf) -> io::Result<()> {
+        self.entries = match fs::read_to_string(&file) {
+            Ok(content) => content.lines().filter_map(Entry::from_line).collect(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error),
+        };
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// 記打開一文件，移至最前。逾上限則去最舊者。
+    pub fn record(&mut self, path: &Path, triples: usize, now: u64) -> io::Result<()> {
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.insert(
+            0,
+            Entry {
+                path: path.to_path_buf(),
+                opened: now,
+                triples,
+            },
+        );
+        self.entries.truncate(CAPACITY);
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> io::Result<()> {
+        if index < self.entries.len() {
+            self.entries.remove(index);
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        create_parent(file)?;
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(&entry.to_line());
+            content.push('\n');
+        }
+        fs::write(file, content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_move_to_the_front_and_persist() {
+        let file = std::env::temp_dir().join(format!("rdf-tui-recent-{}.tsv", std::process::id()));
+        let mut recent = Recent::default();
+        recent.attach(file.clone()).unwrap();
+        for n in 0..CAPACITY + 1 {
+            recent
+                .record(Path::new(&format!("/data/{n}.ttl")), n, n as u64)
+                .unwrap();
+        }
+        recent.record(Path::new("/data/5.ttl"), 7, 99).unwrap();
+        assert_eq!(recent.entries().len(), CAPACITY);
+        assert_eq!(recent.entries()[0].triples, 7);
+        assert!(!recent
+            .entries()
+            .iter()
+            .any(|entry| entry.path == Path::new("/data/0.ttl")));
+
+        let mut reloaded = Recent::default();
+        reloaded.attach(file.clone()).unwrap();
+        assert_eq!(reloaded.entries(), recent.entries());
+        reloaded.remove(0).unwrap();
+        assert_eq!(reloaded.entries().len(), CAPACITY - 1);
+        assert!(!reloaded.entries()[0].exists());
+        fs::remove_file(file).unwrap();
+    }
+}
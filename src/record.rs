@@ -0,0 +1,219 @@
+use crate::{
+    action::Action,
+    app::Mode,
+    bookmarks::{escape, unescape},
+    keymap::KeyChord,
+    script::Step,
+};
+use anyhow::{bail, Context};
+use oxigraph::model::Term;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// 記錄文件之首行。格式有變則增其版本。
+const HEADER: &str = "rdf-tui-transcript\t1";
+
+/// 值之記法：原文，或其散列。散列於同一記錄中一致，故仍可辨值之同異。
+fn value(text: &str, full: bool) -> String {
+    if full {
+        return escape(text);
+    }
+    // FNV-1a
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("#{hash:016x}")
+}
+
+/// 此按鍵所輸入者為數據之值，如改值與過濾之文字；不全記則略去之。
+fn is_data(mode: Mode, key: &KeyEvent) -> bool {
+    matches!(mode, Mode::Edit | Mode::Filter) && matches!(key.code, KeyCode::Char(_))
+}
+
+/// 將所行之操作逐行追加於文件，以供報告問題時重現。每行以 Tab 分隔：自開始之毫秒數、
+/// 類別及其字段。查詢原文照錄；所選格之值與改值、過濾所輸入之文字僅記散列，除非 `full`。
+/// 每行即寫，程序中途崩潰亦留其前之記錄。
+#[derive(Debug)]
+pub struct Recorder {
+    file: File,
+    start: Instant,
+    full: bool,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, full: bool) -> anyhow::Result<Self> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Fail to open transcript {}", path.display()))?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{HEADER}")?;
+        }
+        let mut recorder = Self {
+            file,
+            start: Instant::now(),
+            full,
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let values = if full { "full" } else { "hashed" };
+        recorder.write("start", &[&now.to_string(), values]);
+        Ok(recorder)
+    }
+
+    /// 所載之文件，重放時依之。
+    pub fn source(&mut self, path: &Path) {
+        self.write("source", &[&escape(&path.to_string_lossy())]);
+    }
+
+    /// 一次按鍵、其時之模式、所得之操作及所選格之值。
+    pub fn key(&mut self, key: KeyEvent, mode: Mode, actions: &[Action], cell: Option<&Term>) {
+        let chord = KeyChord::new(key.code, key.modifiers).to_string();
+        let (chord, actions) = if is_data(mode, &key) && !self.full {
+            ("data".to_string(), String::new())
+        } else {
+            let actions: Vec<String> = actions.iter().map(|a| format!("{a:?}")).collect();
+            (escape(&chord), escape(&actions.join(" ")))
+        };
+        let cell = cell.map_or(String::new(), |term| value(&term.to_string(), self.full));
+        self.write("key", &[&chord, &format!("{mode:?}"), &actions, &cell]);
+    }
+
+    /// 所執行之命令行。
+    pub fn command(&mut self, line: &str) {
+        self.write("command", &[&escape(line)]);
+    }
+
+    /// 查詢之結果：行數或錯誤，及耗時。
+    pub fn query(&mut self, query: &str, duration_ms: u128, outcome: Result<usize, &str>) {
+        let outcome = match outcome {
+            Ok(rows) => format!("{rows} rows"),
+            Err(error) => format!("error: {}", escape(error)),
+        };
+        self.write(
+            "query",
+            &[&duration_ms.to_string(), &outcome, &escape(query)],
+        );
+    }
+
+    /// 他處之錯誤，如載入失敗。
+    pub fn error(&mut self, message: &str) {
+        self.write("error", &[&escape(message)]);
+    }
+
+    /// 寫一行。寫不成者僅記於日誌，不擾界面。
+    fn write(&mut self, kind: &str, fields: &[&str]) {
+        let mut line = format!("{}\t{kind}", self.start.elapsed().as_millis());
+        for field in fields {
+            line.push('\t');
+            line.push_str(field);
+        }
+        line.push('\n');
+        if let Err(error) = self.file.write_all(line.as_bytes()) {
+            log::warn!("transcript write failed error={error}");
+        }
+    }
+}
+
+/// 重放所需者：末次記錄所載之文件與其按鍵。
+#[derive(Debug, PartialEq)]
+pub struct Replay {
+    pub sources: Vec<PathBuf>,
+    pub steps: Vec<Step>,
+}
+
+/// 讀記錄之末次。略去之輸入以 `x` 代之，故需原文者當以全記錄之。
+pub fn load(path: &Path) -> anyhow::Result<Replay> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Fail to read transcript {}", path.display()))?;
+    parse(&text).with_context(|| format!("Fail to parse transcript {}", path.display()))
+}
+
+pub fn parse(text: &str) -> anyhow::Result<Replay> {
+    let mut lines = text.lines().enumerate();
+    if lines.next().map(|(_, line)| line) != Some(HEADER) {
+        bail!("not an rdf-tui transcript");
+    }
+    let mut replay = Replay {
+        sources: vec![],
+        steps: vec![],
+    };
+    for (index, line) in lines {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields[..] {
+            [_, "start", ..] => {
+                replay.sources.clear();
+                replay.steps.clear();
+            }
+            [_, "source", path] => replay.sources.push(PathBuf::from(unescape(path))),
+            [_, "key", "data", ..] => replay
+                .steps
+                .push(Step::Key(KeyEvent::from(KeyCode::Char('x')))),
+            [_, "key", chord, ..] => {
+                let chord = KeyChord::parse(&unescape(chord))
+                    .map_err(|error| anyhow::anyhow!("line {}: {error}", index + 1))?;
+                replay
+                    .steps
+                    .push(Step::Key(KeyEvent::new(chord.code, chord.modifiers)));
+            }
+            // 查詢、命令與錯誤僅供閱讀
+            _ => {}
+        }
+    }
+    Ok(replay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::Literal;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    #[test]
+    fn values_are_hashed_and_the_last_session_replays() {
+        let path = std::env::temp_dir().join(format!("rdf-tui-record-{}.tsv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let secret = Term::from(Literal::new_simple_literal("secret"));
+        for full in [false, true] {
+            let mut recorder = Recorder::create(&path, full).unwrap();
+            recorder.source(Path::new("/data/a.ttl"));
+            let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL);
+            recorder.key(enter, Mode::Query, &[Action::RunQuery], None);
+            recorder.query("SELECT * {}", 12, Ok(3));
+            let typed = KeyEvent::from(KeyCode::Char('s'));
+            recorder.key(typed, Mode::Edit, &[Action::InsertChar('s')], Some(&secret));
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        let hashed = text.split("\tstart\t").nth(1).unwrap();
+        assert!(!hashed.contains("secret") && hashed.contains("\tkey\tdata\tEdit\t\t#"));
+        assert!(hashed.contains("\tquery\t12\t3 rows\tSELECT * {}"));
+        assert!(text.contains("InsertChar('s')\t\"secret\""));
+
+        let replay = parse(&text).unwrap();
+        assert_eq!(replay.sources, [PathBuf::from("/data/a.ttl")]);
+        assert_eq!(
+            replay.steps,
+            [
+                Step::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL)),
+                Step::Key(KeyEvent::from(KeyCode::Char('s'))),
+            ]
+        );
+        assert_eq!(
+            parse(&text.replace("\tkey\ts\t", "\tkey\tdata\t"))
+                .unwrap()
+                .steps[1],
+            Step::Key(KeyEvent::from(KeyCode::Char('x')))
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
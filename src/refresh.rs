@@ -0,0 +1,91 @@
+use crate::generation::{Cached, Generation};
+use oxigraph::store::Store;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// 存儲有變後，過時之面板可否自動重算：存儲至多 `max_quads` 條，或上次所算短於 `budget`。
+/// 否則面板標為過時，舊值照示，待用戶按 `R`。
+pub fn automatic(quads: usize, took: Duration, max_quads: usize, budget: Duration) -> bool {
+    quads <= max_quads || took < budget
+}
+
+/// 後台重算之一面板，如層級或鄰域之樹。丟棄即取消：未始者不始，已算者棄之。
+pub struct Refresh<T> {
+    receiver: Receiver<anyhow::Result<Cached<T>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> Refresh<T> {
+    /// 於新線程以存儲之第 `generation` 代算之，記其所耗之時。
+    pub fn spawn(
+        store: &Store,
+        generation: Generation,
+        compute: impl FnOnce(&Store) -> anyhow::Result<T> + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (store, stop) = (store.clone(), Arc::clone(&cancelled));
+        thread::spawn(move || {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let started = Instant::now();
+            let value = compute(&store);
+            if !stop.load(Ordering::Relaxed) {
+                let _ = sender
+                    .send(value.map(|value| Cached::new(generation, value, started.elapsed())));
+            }
+        });
+        Self {
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// 已算畢則返回之。
+    pub fn poll(&self) -> Option<anyhow::Result<Cached<T>>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// 待其算畢，供測試。
+    #[cfg(test)]
+    pub fn wait(&self) -> anyhow::Result<Cached<T>> {
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("cancelled")))
+    }
+}
+
+impl<T> Drop for Refresh<T> {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_stores_and_quick_panels_refresh_themselves() {
+        let budget = Duration::from_millis(200);
+        assert!(automatic(10, Duration::from_secs(5), 1000, budget));
+        assert!(automatic(10_000, Duration::from_millis(20), 1000, budget));
+        assert!(!automatic(10_000, Duration::from_millis(300), 1000, budget));
+
+        let store = Store::new().unwrap();
+        let mut generation = Generation::default();
+        generation.bump();
+        let refresh = Refresh::spawn(&store, generation, |store| Ok(store.len()?));
+        let cached = refresh.wait().unwrap();
+        assert_eq!(*cached, 0);
+        assert!(!cached.is_stale(generation));
+    }
+}
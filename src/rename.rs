@@ -0,0 +1,119 @@
+use std::ops::Range;
+
+/// 變量名之字符。`?x` 與 `$x` 為同一變量。
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '\u{b7}'
+}
+
+/// 去變量之 `?` 或 `$`，驗其名。
+pub fn name(text: &str) -> Option<&str> {
+    let name = text.strip_prefix(['?', '$']).unwrap_or(text);
+    (!name.is_empty() && name.chars().all(is_name_char)).then_some(name)
+}
+
+/// 查詢中各變量名之範圍，不含其標記。字符串、註釋與 IRI 中者非變量，略之。
+fn variables(query: &str) -> Vec<Range<usize>> {
+    let mut found = vec![];
+    let mut i = 0;
+    while let Some(ch) = query[i..].chars().next() {
+        let rest = &query[i..];
+        i += match ch {
+            '#' => rest.find('\n').unwrap_or(rest.len()),
+            '"' | '\'' => string_len(rest, ch),
+            '<' => iri_len(rest).unwrap_or(1),
+            '?' | '$' => {
+                let len = rest[1..]
+                    .find(|c| !is_name_char(c))
+                    .unwrap_or(rest.len() - 1);
+                if len > 0 {
+                    found.push(i + 1..i + 1 + len);
+                }
+                1 + len
+            }
+            ch => ch.len_utf8(),
+        };
+    }
+    found
+}
+
+/// 字符串之長，含其引號；未閉者至行末，長字符串至文末。
+fn string_len(text: &str, quote: char) -> usize {
+    let long: String = [quote; 3].iter().collect();
+    let (open, close) = match text.starts_with(&long) {
+        true => (3, long.as_str()),
+        false => (1, &text[..1]),
+    };
+    let mut chars = text[open..].char_indices();
+    while let Some((j, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '\n' if open == 1 => return open + j,
+            _ if text[open + j..].starts_with(close) => return open + j + close.len(),
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+/// 以 `<` 起之 IRI 之長。其中不得有空白，故 `?a < ?b` 之 `<` 為比較。
+fn iri_len(text: &str) -> Option<usize> {
+    let end = text[1..].find(|c: char| c <= ' ' || "<>\"{}|^`\\".contains(c))?;
+    text[1 + end..].starts_with('>').then_some(end + 2)
+}
+
+/// 以光標結束之變量名，即查詢末尾之變量。
+pub fn at_end(query: &str) -> Option<&str> {
+    variables(query)
+        .pop()
+        .filter(|range| range.end == query.len())
+        .map(|range| &query[range])
+}
+
+/// 此變量出現之次數。
+pub fn occurrences(query: &str, name: &str) -> usize {
+    variables(query)
+        .into_iter()
+        .filter(|range| &query[range.clone()] == name)
+        .count()
+}
+
+/// 改 `from` 之各處為 `to`，其標記不變。
+pub fn rename(query: &str, from: &str, to: &str) -> String {
+    let mut renamed = String::with_capacity(query.len());
+    let mut last = 0;
+    for range in variables(query) {
+        if query[range.clone()] == *from {
+            renamed.push_str(&query[last..range.start]);
+            renamed.push_str(to);
+            last = range.end;
+        }
+    }
+    renamed.push_str(&query[last..]);
+    renamed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_skip_longer_names_strings_comments_and_iris() {
+        let query = "SELECT ?x ?xLabel WHERE {\n  \
+                     { SELECT $x WHERE { $x <http://ex/?x> \"?x\" } }\n  \
+                     ?x ex:p '''a ''?x''' # ?x\n  FILTER(?x<?xLabel && ?x > 1)\n} ORDER BY ?x";
+        assert_eq!(occurrences(query, "x"), 7);
+        assert_eq!(
+            rename(query, "x", "person"),
+            "SELECT ?person ?xLabel WHERE {\n  \
+             { SELECT $person WHERE { $person <http://ex/?x> \"?x\" } }\n  \
+             ?person ex:p '''a ''?x''' # ?x\n  FILTER(?person<?xLabel && ?person > 1)\n} \
+             ORDER BY ?person"
+        );
+        assert_eq!(at_end(query), Some("x"));
+        assert_eq!(at_end("SELECT ?x {}"), None);
+        assert_eq!(name("?person"), Some("person"));
+        assert_eq!(name("?a-b"), None);
+    }
+}
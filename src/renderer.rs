@@ -0,0 +1,229 @@
+//! 單元格之自定顯示。嵌入者以 [`App::register_renderer`](crate::App::register_renderer)
+//! 依數據類型註冊之；結果表與詳情先問之，皆不應者照常顯示。導出與複製仍用原值。
+
+use oxigraph::model::Term;
+use ratatui::text::Span;
+use std::collections::HashMap;
+
+/// 一類字面量之顯示。不欲顯示此值者返回 `None`，交後註冊者或默認之顯示。
+pub trait CellRenderer {
+    fn render(&self, term: &Term) -> Option<Vec<Span<'static>>>;
+}
+
+/// GeoSPARQL 之 WKT 字面量之數據類型。
+pub const WKT_LITERAL: &str = "http://www.opengis.net/ont/geosparql#wktLiteral";
+
+/// UCUM 單位之量之數據類型，如 `"9.81 m.s-2"`。
+pub const UCUM: &str = "http://w3id.org/lindt/custom_datatypes#ucum";
+
+/// 依數據類型 IRI 所註冊之諸顯示，後註冊者先問。
+#[derive(Default)]
+pub struct Renderers {
+    by_datatype: HashMap<String, Vec<Box<dyn CellRenderer>>>,
+}
+
+impl Renderers {
+    /// 內置之顯示，依所開之特性。
+    pub fn builtin() -> Self {
+        #[allow(unused_mut)]
+        let mut renderers = Self::default();
+        #[cfg(feature = "wkt")]
+        renderers.register(WKT_LITERAL, Box::new(Wkt));
+        #[cfg(feature = "ucum")]
+        renderers.register(UCUM, Box::new(Ucum));
+        renderers
+    }
+
+    pub fn register(&mut self, datatype: &str, renderer: Box<dyn CellRenderer>) {
+        self.by_datatype
+            .entry(datatype.to_string())
+            .or_default()
+            .push(renderer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_datatype.is_empty()
+    }
+
+    /// 首個應之者所顯示。非字面量，或其數據類型無所註冊者，為 `None`。
+    pub fn render(&self, term: &Term) -> Option<Vec<Span<'static>>> {
+        let Term::Literal(literal) = term else {
+            return None;
+        };
+        self.by_datatype
+            .get(literal.datatype().as_str())?
+            .iter()
+            .rev()
+            .find_map(|renderer| renderer.render(term))
+    }
+}
+
+/// 長之 WKT 縮為其類型與點數，如 `POLYGON(…197 pts)`。短者照常顯示。
+#[cfg(feature = "wkt")]
+struct Wkt;
+
+#[cfg(feature = "wkt")]
+impl CellRenderer for Wkt {
+    fn render(&self, term: &Term) -> Option<Vec<Span<'static>>> {
+        let Term::Literal(literal) = term else {
+            return None;
+        };
+        let text = literal.value().trim();
+        // 或以坐標系之 IRI 起，如 `<http://…/4326> POINT(1 2)`
+        let geometry = match text.strip_prefix('<') {
+            Some(rest) => rest.split_once('>')?.1.trim_start(),
+            None => text,
+        };
+        let (kind, coordinates) = geometry.split_once('(')?;
+        if geometry.len() <= 32 {
+            return None;
+        }
+        let bytes = coordinates.as_bytes();
+        let number = |byte: &u8| byte.is_ascii_digit() || matches!(byte, b'-' | b'+' | b'.');
+        let next = |index: usize| bytes[index + 1..].iter().find(|b| !b.is_ascii_whitespace());
+        let previous = |index: usize| {
+            bytes[..index]
+                .iter()
+                .rev()
+                .find(|b| !b.is_ascii_whitespace())
+        };
+        // 最內之括號各起一點，其中之逗號各隔一點；類型後之括號已分去
+        let first = coordinates
+            .trim_start()
+            .bytes()
+            .next()
+            .is_some_and(|b| number(&b));
+        let groups = first as usize
+            + (0..bytes.len())
+                .filter(|&index| bytes[index] == b'(' && next(index).is_some_and(number))
+                .count();
+        let separators = (0..bytes.len())
+            .filter(|&index| {
+                bytes[index] == b','
+                    && previous(index).is_some_and(number)
+                    && next(index).is_some_and(number)
+            })
+            .count();
+        let points = match groups + separators {
+            1 => "1 pt".to_string(),
+            points => format!("{points} pts"),
+        };
+        Some(vec![Span::raw(format!("{}(…{points})", kind.trim_end()))])
+    }
+}
+
+/// UCUM 之單位以上標與點號示之，如 `9.81 m·s⁻²`。
+#[cfg(feature = "ucum")]
+struct Ucum;
+
+#[cfg(feature = "ucum")]
+impl CellRenderer for Ucum {
+    fn render(&self, term: &Term) -> Option<Vec<Span<'static>>> {
+        let Term::Literal(literal) = term else {
+            return None;
+        };
+        let (value, unit) = literal.value().trim().split_once(' ')?;
+        let mut shown = String::new();
+        let mut after_atom = false;
+        for ch in unit.trim().chars() {
+            let superscript = match ch {
+                '0'..='9' if after_atom => {
+                    Some("⁰¹²³⁴⁵⁶⁷⁸⁹".chars().nth(ch as usize - '0' as usize)?)
+                }
+                '-' if after_atom => Some('⁻'),
+                _ => None,
+            };
+            match superscript {
+                Some(superscript) => shown.push(superscript),
+                None if ch == '.' => shown.push('·'),
+                None => shown.push(ch),
+            }
+            after_atom = ch.is_alphabetic() || ch == ']' || superscript.is_some();
+        }
+        Some(vec![
+            Span::raw(value.to_string()),
+            Span::raw(format!(" {shown}")),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, NamedNode};
+
+    fn literal(value: &str, datatype: &str) -> Term {
+        Literal::new_typed_literal(value, NamedNode::new_unchecked(datatype)).into()
+    }
+
+    fn text(spans: Option<Vec<Span>>) -> Option<String> {
+        spans.map(|spans| spans.iter().map(|span| span.content.as_ref()).collect())
+    }
+
+    struct Upper;
+
+    impl CellRenderer for Upper {
+        fn render(&self, term: &Term) -> Option<Vec<Span<'static>>> {
+            match term {
+                Term::Literal(literal) if literal.value() != "skip" => {
+                    Some(vec![Span::raw(literal.value().to_uppercase())])
+                }
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn renderers_are_chosen_by_datatype() {
+        let mut renderers = Renderers::default();
+        assert!(renderers.is_empty());
+        renderers.register("http://ex/shout", Box::new(Upper));
+        assert_eq!(
+            text(renderers.render(&literal("hi", "http://ex/shout"))),
+            Some("HI".to_string())
+        );
+        // 不應者及他類型照常顯示
+        assert_eq!(renderers.render(&literal("skip", "http://ex/shout")), None);
+        assert_eq!(renderers.render(&literal("hi", "http://ex/other")), None);
+        assert_eq!(
+            renderers.render(&NamedNode::new_unchecked("http://ex/shout").into()),
+            None
+        );
+    }
+
+    #[cfg(feature = "wkt")]
+    #[test]
+    fn long_wkt_is_shortened_to_its_points() {
+        let renderers = Renderers::builtin();
+        let wkt = |value: &str| text(renderers.render(&literal(value, WKT_LITERAL)));
+        assert_eq!(wkt("POINT(1 2)"), None);
+        assert_eq!(
+            wkt("POLYGON((30 10, 40 40, 20 40, 10 20, 30 10))").as_deref(),
+            Some("POLYGON(…5 pts)")
+        );
+        assert_eq!(
+            wkt("<http://www.opengis.net/def/crs/EPSG/0/4326> MULTIPOLYGON (((1 1, 2 2, 3 1, 1 1)), ((5 5, 6 6, 7 5, 5 5)))")
+                .as_deref(),
+            Some("MULTIPOLYGON(…8 pts)")
+        );
+        assert_eq!(
+            wkt("MULTIPOINT ((10 40), (40 30), (20 20), (30 10))").as_deref(),
+            Some("MULTIPOINT(…4 pts)")
+        );
+        assert_eq!(
+            wkt("LINESTRING Z (30 10 1, 10 30 2, 40 40 3)").as_deref(),
+            Some("LINESTRING Z(…3 pts)")
+        );
+    }
+
+    #[cfg(feature = "ucum")]
+    #[test]
+    fn ucum_units_use_superscripts() {
+        let renderers = Renderers::builtin();
+        let ucum = |value: &str| text(renderers.render(&literal(value, UCUM)));
+        assert_eq!(ucum("9.81 m.s-2").as_deref(), Some("9.81 m·s⁻²"));
+        assert_eq!(ucum("3 cm2").as_deref(), Some("3 cm²"));
+        assert_eq!(ucum("1.5 10*3.kg").as_deref(), Some("1.5 10*3·kg"));
+        assert_eq!(ucum("12"), None);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::app::ResultTable;
+use oxigraph::{
+    model::{BlankNode, Literal, NamedNode, Term},
+    sparql::{
+        results::{FromReadQueryResultsReader, QueryResultsFormat, QueryResultsParser},
+        QuerySolution, Variable,
+    },
+};
+use std::{
+    convert::Infallible,
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+    sync::Arc,
+};
+
+/// 依擴展名判斷是否為他處所存之 SPARQL 結果。
+pub fn results_format(path: &Path) -> Option<QueryResultsFormat> {
+    match path.extension()?.to_str()? {
+        "srj" | "json" => Some(QueryResultsFormat::Json),
+        "srx" => Some(QueryResultsFormat::Xml),
+        "tsv" => Some(QueryResultsFormat::Tsv),
+        "csv" => Some(QueryResultsFormat::Csv),
+        _ => None,
+    }
+}
+
+/// 讀取結果文件為表，至多 `limit` 行。
+pub fn read_results(
+    path: &Path,
+    format: QueryResultsFormat,
+    limit: Option<usize>,
+) -> anyhow::Result<ResultTable> {
+    if format == QueryResultsFormat::Csv {
+        let (variables, rows) = read_csv(&fs::read_to_string(path)?)?;
+        let shared: Arc<[Variable]> = variables.clone().into();
+        let solutions = rows
+            .into_iter()
+            .map(|row| Ok::<_, Infallible>(QuerySolution::from((Arc::clone(&shared), row))));
+        return ResultTable::from_solutions(variables, solutions, limit)
+            .map_err(anyhow::Error::msg);
+    }
+    let file = BufReader::new(File::open(path)?);
+    match QueryResultsParser::from_format(format).parse_read(file)? {
+        FromReadQueryResultsReader::Solutions(solutions) => {
+            let variables = solutions.variables().to_vec();
+            ResultTable::from_solutions(variables, solutions, limit).map_err(anyhow::Error::msg)
+        }
+        FromReadQueryResultsReader::Boolean(value) => {
+            anyhow::bail!("An ASK result ({value}) has no table")
+        }
+    }
+}
+
+/// 一行之項。未綁定者為 `None`。
+type Row = Vec<Option<Term>>;
+
+/// 讀取 SPARQL CSV。CSV 不分 IRI 與字面量，故形如絕對 IRI 者作 IRI，`_:` 起者作空白節點，
+/// 餘皆作字符串；空字段為未綁定。
+fn read_csv(text: &str) -> anyhow::Result<(Vec<Variable>, Vec<Row>)> {
+    let mut records = csv_records(text)?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Empty CSV file"))?;
+    let variables = header
+        .iter()
+        .map(|name| Variable::new(name.trim_start_matches('?')))
+        .collect::<Result<Vec<_>, _>>()?;
+    let rows = records
+        .enumerate()
+        .map(|(index, record)| {
+            if record.len() != variables.len() {
+                anyhow::bail!(
+                    "Row {} has {} fields, expected {}",
+                    index + 1,
+                    record.len(),
+                    variables.len()
+                );
+            }
+            Ok(record.iter().map(|value| csv_term(value)).collect())
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok((variables, rows))
+}
+
+fn csv_term(value: &str) -> Option<Term> {
+    if value.is_empty() {
+        return None;
+    }
+    if let Some(id) = value.strip_prefix("_:") {
+        if let Ok(node) = BlankNode::new(id) {
+            return Some(node.into());
+        }
+    }
+    if value.contains(':') {
+        if let Ok(iri) = NamedNode::new(value) {
+            return Some(iri.into());
+        }
+    }
+    Some(Literal::new_simple_literal(value).into())
+}
+
+/// 按 RFC 4180 分記錄與字段：`"` 內可含逗號、換行，`""` 為 `"`。略去末尾空行。
+fn csv_records(text: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            ch => field.push(ch),
+        }
+    }
+    if quoted {
+        anyhow::bail!("Unterminated quoted field in CSV");
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_may_be_quoted() {
+        let records = csv_records("a,b\r\n\"x, \"\"y\"\"\",\"two\nlines\"\n,\n").unwrap();
+        assert_eq!(
+            records,
+            [vec!["a", "b"], vec!["x, \"y\"", "two\nlines"], vec!["", ""]]
+        );
+        assert!(csv_records("a\n\"open").is_err());
+    }
+
+    #[test]
+    fn csv_values_become_terms() {
+        let (variables, rows) = read_csv("?s,o\nhttp://ex/a,_:b\nnote: hi,\n").unwrap();
+        assert_eq!(
+            variables,
+            [Variable::new_unchecked("s"), Variable::new_unchecked("o")]
+        );
+        assert_eq!(
+            rows,
+            [
+                vec![
+                    Some(NamedNode::new_unchecked("http://ex/a").into()),
+                    Some(BlankNode::new_unchecked("b").into())
+                ],
+                vec![Some(Literal::new_simple_literal("note: hi").into()), None],
+            ]
+        );
+        assert!(read_csv("s,o\nonly\n").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    bookmarks::{escape, unescape},
+    util::offset_index,
+};
+use oxigraph::model::Term;
+use std::str::FromStr;
+
+/// 面板中一項顯示之字數上限。全文示於所選項之下。
+pub const MAX_TITLE: usize = 60;
+
+/// 便箋之一項：所釘之項，或一段文字。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pin {
+    Term(Term),
+    Text(String),
+}
+
+impl Pin {
+    /// 插入查詢或複製之文字。項以 N-Triples 之寫法，亦即 SPARQL 之寫法。
+    pub fn text(&self) -> String {
+        match self {
+            Self::Term(term) => term.to_string(),
+            Self::Text(text) => text.clone(),
+        }
+    }
+
+    /// 面板中之一行：空白併為一空格，逾 `MAX_TITLE` 字則截斷。
+    pub fn title(&self) -> String {
+        let text = self.text().split_whitespace().collect::<Vec<_>>().join(" ");
+        match text.char_indices().nth(MAX_TITLE) {
+            Some((end, _)) => format!("{}…", &text[..end]),
+            None => text,
+        }
+    }
+
+    /// 會話文件中之值：種類與轉義之文字，以製表符分隔。
+    pub fn to_field(&self) -> String {
+        match self {
+            Self::Term(term) => format!("term\t{}", escape(&term.to_string())),
+            Self::Text(text) => format!("text\t{}", escape(text)),
+        }
+    }
+
+    pub fn from_field(field: &str) -> Option<Self> {
+        match field.split_once('\t')? {
+            ("term", term) => Term::from_str(&unescape(term)).ok().map(Self::Term),
+            ("text", text) => Some(Self::Text(unescape(text))),
+            _ => None,
+        }
+    }
+}
+
+/// 便箋：所釘之項與文字，依會話保存。
+#[derive(Debug, Default)]
+pub struct Scratchpad {
+    pins: Vec<Pin>,
+}
+
+impl Scratchpad {
+    pub fn pins(&self) -> &[Pin] {
+        &self.pins
+    }
+
+    /// 換為會話所存者。
+    pub fn replace(&mut self, pins: Vec<Pin>) {
+        self.pins = pins;
+    }
+
+    /// 釘於末。已有則不重複，返回 `false`。
+    pub fn add(&mut self, pin: Pin) -> bool {
+        if self.pins.contains(&pin) {
+            return false;
+        }
+        self.pins.push(pin);
+        true
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Pin> {
+        (index < self.pins.len()).then(|| self.pins.remove(index))
+    }
+
+    /// 將第 `index` 項上下移若干位，至首末而止。返回其新位置。
+    pub fn shift(&mut self, index: usize, delta: isize) -> usize {
+        if index >= self.pins.len() {
+            return index;
+        }
+        let target = offset_index(index, delta, self.pins.len());
+        let pin = self.pins.remove(index);
+        self.pins.insert(target, pin);
+        target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, NamedNode};
+
+    #[test]
+    fn pins_round_trip_and_reorder() {
+        let iri = Pin::Term(NamedNode::new_unchecked("http://ex/a").into());
+        let literal =
+            Pin::Term(Literal::new_language_tagged_literal_unchecked("a\tb", "en").into());
+        let text = Pin::Text("FILTER(?o > 1)\n".to_string());
+        for pin in [&iri, &literal, &text] {
+            assert_eq!(Pin::from_field(&pin.to_field()).as_ref(), Some(pin));
+        }
+        assert_eq!(literal.text(), "\"a\\tb\"@en");
+        assert_eq!(text.title(), "FILTER(?o > 1)");
+        assert_eq!(
+            Pin::Text("x".repeat(70)).title().chars().count(),
+            MAX_TITLE + 1
+        );
+
+        let mut scratchpad = Scratchpad::default();
+        for pin in [iri.clone(), literal.clone(), text.clone()] {
+            assert!(scratchpad.add(pin));
+        }
+        assert!(!scratchpad.add(iri.clone()));
+        assert_eq!(scratchpad.shift(0, 5), 2);
+        assert_eq!(
+            scratchpad.pins(),
+            [literal.clone(), text.clone(), iri.clone()]
+        );
+        assert_eq!(scratchpad.shift(1, -1), 0);
+        assert_eq!(scratchpad.remove(0), Some(text));
+        assert_eq!(scratchpad.pins(), [literal, iri]);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{config::parse_quoted, keymap::parse_sequence};
+use anyhow::{bail, Context};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// 腳本之一步。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// 按一鍵。
+    Key(KeyEvent),
+    /// 逐字輸入，如同按鍵。
+    Type(String),
+    /// 暫停。
+    Wait(Duration),
+}
+
+/// 讀取腳本文件。
+pub fn load(path: &Path) -> anyhow::Result<Vec<Step>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Fail to read script {}", path.display()))?;
+    parse(&text).with_context(|| format!("Fail to parse script {}", path.display()))
+}
+
+/// 解析腳本。每行一命令：`key <keys>`、`type "<text>"`、`wait <duration>`。
+/// `#` 起為注釋。錯誤附行號。
+pub fn parse(text: &str) -> anyhow::Result<Vec<Step>> {
+    let mut steps = vec![];
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (command, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = argument.trim();
+        let parsed = match command {
+            "key" => parse_sequence(argument).map(|keys| {
+                keys.into_iter()
+                    .map(|chord| Step::Key(KeyEvent::new(chord.code, chord.modifiers)))
+                    .collect()
+            }),
+            "type" => parse_text(argument).map(|text| vec![Step::Type(text)]),
+            "wait" => parse_duration(argument).map(|duration| vec![Step::Wait(duration)]),
+            _ => Err(format!("unknown command `{command}` (key, type, wait)")),
+        };
+        match parsed {
+            Ok(parsed) => steps.extend(parsed),
+            Err(error) => bail!("line {}: {error}", index + 1),
+        }
+    }
+    Ok(steps)
+}
+
+fn parse_text(argument: &str) -> Result<String, String> {
+    let rest = argument
+        .strip_prefix('"')
+        .ok_or_else(|| "expected a quoted string".to_string())?;
+    let (text, tail) = parse_quoted(rest)?;
+    let tail = tail.trim();
+    if !tail.is_empty() && !tail.starts_with('#') {
+        return Err(format!("unexpected text `{tail}`"));
+    }
+    Ok(text)
+}
+
+/// 解析如 `500ms`、`2s`、`1.5s` 之時長。
+fn parse_duration(argument: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration `{argument}` (e.g. 500ms, 2s)");
+    if let Some(millis) = argument.strip_suffix("ms") {
+        return millis
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| invalid());
+    }
+    let seconds = argument.strip_suffix('s').ok_or_else(invalid)?;
+    seconds
+        .parse()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(invalid)
+}
+
+/// 播放中之腳本。
+#[derive(Debug)]
+pub struct Playback {
+    steps: VecDeque<Step>,
+    /// 暫停至此時。
+    resume_at: Option<Instant>,
+    /// 播完後是否交還鍵盤；否則退出。
+    pub then_interactive: bool,
+}
+
+impl Playback {
+    pub fn new(steps: Vec<Step>, then_interactive: bool) -> Self {
+        Self {
+            steps: steps.into(),
+            resume_at: None,
+            then_interactive,
+        }
+    }
+
+    /// 取下一按鍵。暫停中返回 `None`。`type` 之文本逐字展開。
+    pub fn next_key(&mut self) -> Option<KeyEvent> {
+        if let Some(resume_at) = self.resume_at {
+            if Instant::now() < resume_at {
+                return None;
+            }
+            self.resume_at = None;
+        }
+        match self.steps.pop_front()? {
+            Step::Key(key) => Some(key),
+            Step::Type(text) => {
+                let mut chars = text.chars();
+                let ch = chars.next()?;
+                let rest = chars.as_str();
+                if !rest.is_empty() {
+                    self.steps.push_front(Step::Type(rest.to_string()));
+                }
+                Some(KeyEvent::from(KeyCode::Char(ch)))
+            }
+            Step::Wait(duration) => {
+                self.resume_at = Some(Instant::now() + duration);
+                None
+            }
+        }
+    }
+
+    /// 是否已播完。
+    pub fn is_finished(&self) -> bool {
+        self.steps.is_empty() && self.resume_at.is_none_or(|at| Instant::now() >= at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    #[test]
+    fn parses_commands_and_skips_comments() {
+        let steps = parse(
+            "# demo\n\
+             key tab\n\
+             type \"SELECT \\\"x\\\"\"\n\
+             \n\
+             key ctrl-enter g g\n\
+             wait 500ms\n\
+             wait 1.5s\n",
+        )
+        .unwrap();
+        assert_eq!(
+            steps,
+            [
+                Step::Key(KeyEvent::from(KeyCode::Tab)),
+                Step::Type("SELECT \"x\"".to_string()),
+                Step::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL)),
+                Step::Key(KeyEvent::from(KeyCode::Char('g'))),
+                Step::Key(KeyEvent::from(KeyCode::Char('g'))),
+                Step::Wait(Duration::from_millis(500)),
+                Step::Wait(Duration::from_millis(1500)),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |text: &str| parse(text).unwrap_err().to_string();
+        assert_eq!(
+            error("key q\npress q"),
+            "line 2: unknown command `press` (key, type, wait)"
+        );
+        assert_eq!(error("\n\nkey hyper-q"), "line 3: unknown key `hyper-q`");
+        assert_eq!(error("type SELECT"), "line 1: expected a quoted string");
+        assert_eq!(error("type \"SELECT"), "line 1: unterminated string");
+        assert_eq!(
+            error("wait soon"),
+            "line 1: invalid duration `soon` (e.g. 500ms, 2s)"
+        );
+    }
+
+    #[test]
+    fn playback_types_character_by_character() {
+        let mut playback = Playback::new(
+            vec![
+                Step::Type("ab".to_string()),
+                Step::Key(KeyEvent::from(KeyCode::Tab)),
+            ],
+            false,
+        );
+        assert_eq!(
+            playback.next_key(),
+            Some(KeyEvent::from(KeyCode::Char('a')))
+        );
+        assert_eq!(
+            playback.next_key(),
+            Some(KeyEvent::from(KeyCode::Char('b')))
+        );
+        assert!(!playback.is_finished());
+        assert_eq!(playback.next_key(), Some(KeyEvent::from(KeyCode::Tab)));
+        assert!(playback.is_finished());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{util::offset_index, worker::TermRow};
+use oxigraph::sparql::Variable;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// 重新執行後，於新結果之前此數行中尋原所選之行；其後者不尋，免大結果之重新執行變慢。
+pub const MAX_IDENTITY_ROWS: usize = 100_000;
+
+/// 所選之格之列移 `delta` 步。未選者，右移選首列，左移選末列。`wrap` 則越兩端而繞回，否則止於兩端。
+pub fn step_column(column: Option<usize>, delta: isize, len: usize, wrap: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    Some(match column {
+        Some(column) if wrap => (column as isize + delta).rem_euclid(len as isize) as usize,
+        Some(column) => offset_index(column, delta, len),
+        None if delta < 0 => len - 1,
+        None => 0,
+    })
+}
+
+/// 表之列變後所選之列：依變量名隨之，如重新執行或改投影之序；已無此變量則不選。
+pub fn follow_column(variable: Option<&Variable>, variables: &[Variable]) -> Option<usize> {
+    variables.iter().position(|v| Some(v) == variable)
+}
+
+/// 過濾後所選之行：不相符則移至其後首個相符者，無則末個；皆不相符則不動。
+pub fn snap_row(row: usize, matching: &[usize]) -> usize {
+    let position = matching.partition_point(|&matching| matching < row);
+    matching
+        .get(position)
+        .or(matching.last())
+        .copied()
+        .unwrap_or(row)
+}
+
+/// 表縮後所選之行止於末行。新到之行追加於表末，所選不動。
+pub fn clamp_row(row: usize, len: usize) -> usize {
+    row.min(len.saturating_sub(1))
+}
+
+/// 重新執行前所選之行，待新結果之行到而復之。
+#[derive(Debug, Clone)]
+pub struct Anchor {
+    /// 變量同者方復之。
+    pub variables: Vec<Variable>,
+    pub row: usize,
+    /// 所選行之上可見之行數，復之後仍居屏上原處。
+    pub above: usize,
+    /// 所選行之諸項之哈希。
+    hash: u64,
+}
+
+/// 所復之行。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restored {
+    /// 新結果中之同一行。
+    Same(usize),
+    /// 同一行已無，或未尋之，取原行號，止於末行。
+    Index(usize),
+}
+
+impl Anchor {
+    /// 表之第 `row` 行，`offset` 為首個可見行。
+    pub fn new(
+        variables: &[Variable],
+        rows: &[TermRow],
+        row: usize,
+        offset: usize,
+    ) -> Option<Self> {
+        Some(Self {
+            variables: variables.to_vec(),
+            row,
+            above: row.saturating_sub(offset),
+            hash: row_hash(rows.get(row)?),
+        })
+    }
+
+    /// 新結果之行已到者為 `rows`，其前 `searched` 行已尋。尋不得而行未齊則 `None`，待後至之行。
+    pub fn restore(&self, rows: &[TermRow], searched: usize, finished: bool) -> Option<Restored> {
+        let end = rows.len().min(MAX_IDENTITY_ROWS);
+        if let Some(row) = (searched.min(end)..end).find(|&row| row_hash(&rows[row]) == self.hash) {
+            return Some(Restored::Same(row));
+        }
+        match finished {
+            true => Some(Restored::Index(clamp_row(self.row, rows.len()))),
+            // 逾尋之限，行號已到則取之
+            false if rows.len() > MAX_IDENTITY_ROWS && rows.len() > self.row => {
+                Some(Restored::Index(self.row))
+            }
+            false => None,
+        }
+    }
+}
+
+fn row_hash(row: &TermRow) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    row.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_step_with_or_without_wrapping() {
+        assert_eq!(step_column(None, 1, 3, false), Some(0));
+        assert_eq!(step_column(None, -1, 3, false), Some(2));
+        assert_eq!(step_column(Some(2), 1, 3, false), Some(2));
+        assert_eq!(step_column(Some(0), -1, 3, false), Some(0));
+        assert_eq!(step_column(Some(2), 1, 3, true), Some(0));
+        assert_eq!(step_column(Some(0), -1, 3, true), Some(2));
+        assert_eq!(step_column(Some(1), 1, 3, true), Some(2));
+        assert_eq!(step_column(Some(1), 1, 0, true), None);
+        // 前表之列於今表已越界者，止於末列
+        assert_eq!(step_column(Some(5), 0, 3, false), Some(2));
+    }
+
+    #[test]
+    fn columns_follow_their_variable() {
+        let variables = |names: &[&str]| -> Vec<Variable> {
+            names.iter().map(|n| Variable::new_unchecked(*n)).collect()
+        };
+        let old = variables(&["s", "p", "o"]);
+        let selected = old.get(2);
+        assert_eq!(follow_column(selected, &old), Some(2));
+        assert_eq!(follow_column(selected, &variables(&["o", "s"])), Some(0));
+        assert_eq!(follow_column(selected, &variables(&["s", "p"])), None);
+        assert_eq!(follow_column(None, &old), None);
+    }
+
+    #[test]
+    fn rows_snap_to_matches_and_clamp_to_the_table() {
+        assert_eq!(snap_row(3, &[1, 3, 5]), 3);
+        assert_eq!(snap_row(2, &[1, 3, 5]), 3);
+        assert_eq!(snap_row(9, &[1, 3, 5]), 5);
+        assert_eq!(snap_row(4, &[]), 4);
+        assert_eq!(clamp_row(4, 10), 4);
+        assert_eq!(clamp_row(4, 3), 2);
+        assert_eq!(clamp_row(4, 0), 0);
+    }
+
+    #[test]
+    fn re_run_rows_are_found_by_their_terms_then_by_their_index() {
+        use oxigraph::model::{Literal, Term};
+        use std::sync::Arc;
+        let rows = |values: &[i32]| -> Vec<TermRow> {
+            values
+                .iter()
+                .map(|value| vec![Some(Arc::new(Term::from(Literal::from(*value))))])
+                .collect()
+        };
+        let variables = [Variable::new_unchecked("n")];
+        let anchor = Anchor::new(&variables, &rows(&[1, 2, 3, 4]), 2, 1).unwrap();
+        assert_eq!(anchor.above, 1);
+        assert!(Anchor::new(&variables, &rows(&[1]), 2, 0).is_none());
+
+        // 前移之行仍尋得；已尋者不再尋
+        let moved = rows(&[0, 3, 1]);
+        assert_eq!(anchor.restore(&moved, 0, false), Some(Restored::Same(1)));
+        assert_eq!(anchor.restore(&moved, 2, false), None);
+        // 已無者，齊則取原行號，止於末行
+        assert_eq!(
+            anchor.restore(&rows(&[7, 8, 9, 10]), 0, true),
+            Some(Restored::Index(2))
+        );
+        assert_eq!(
+            anchor.restore(&rows(&[7]), 0, true),
+            Some(Restored::Index(0))
+        );
+        assert_eq!(anchor.restore(&[], 0, true), Some(Restored::Index(0)));
+
+        // 逾尋之限者不尋
+        let many = rows(&vec![0; MAX_IDENTITY_ROWS + 1]);
+        assert_eq!(
+            anchor.restore(&many, MAX_IDENTITY_ROWS, false),
+            Some(Restored::Index(2))
+        );
+    }
+}
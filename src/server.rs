@@ -0,0 +1,797 @@
+use crate::service::DisabledServiceHandler;
+use anyhow::{bail, Context};
+use oxigraph::{
+    io::{RdfFormat, RdfSerializer},
+    model::{GraphName, NamedNode, NamedOrBlankNode},
+    sparql::{
+        results::{QueryResultsFormat, QueryResultsSerializer},
+        Query, QueryOptions, QueryResults, QuerySolutionIter, QueryTripleIter,
+    },
+    store::Store,
+};
+use std::{
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// 無連接時檢查是否已停之間隔。
+const POLL: Duration = Duration::from_millis(50);
+/// 讀取請求之時限。
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// 請求頭之總長上限。
+const MAX_HEAD: u64 = 64 * 1024;
+/// 請求體之長度上限。
+const MAX_BODY: usize = 1024 * 1024;
+/// 同時應答之連接數上限。逾之則即答 503，不開線程。
+const MAX_CONNECTIONS: usize = 8;
+/// 每查詢之時限。逾之則斷其應答，已寫者不收回。
+const QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+/// 停時等待各連接之線程之時限。逾之則不再等，求值中者自行終了。
+const STOP_WAIT: Duration = Duration::from_secs(1);
+/// 結果積至此字節數即作一塊送出。
+const CHUNK: usize = 64 * 1024;
+
+/// 極簡 SPARQL 端點，於後台線程應答 SPARQL 協議之查詢：`GET` 或 `POST /query`。
+/// 與界面共用存儲，故所見即所得；更新一概拒之。結果邊求值邊送出，不先積於內存。
+/// 丟棄即停：斷進行中之連接，其查詢亦止。
+///
+/// 存儲之求值不可中途取消，故未出首行之查詢（排序、聚合、大連接）逾時則斷其連接，
+/// 其線程待次行時方止；停時亦不久等之。
+pub struct Server {
+    address: SocketAddr,
+    /// 所應答之存儲。重新載入後換之。
+    store: Arc<Mutex<Store>>,
+    requests: Arc<AtomicUsize>,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    /// 綁定地址，如 `127.0.0.1:7878`。端口為 0 則由系統擇之。
+    pub fn spawn(store: &Store, address: &str) -> anyhow::Result<Self> {
+        Self::start(store, address, QUERY_TIMEOUT)
+    }
+
+    fn start(store: &Store, address: &str, timeout: Duration) -> anyhow::Result<Self> {
+        let listener =
+            TcpListener::bind(address).with_context(|| format!("Fail to listen on {address}"))?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+        let requests = Arc::new(AtomicUsize::new(0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let store = Arc::new(Mutex::new(store.clone()));
+        let context = (
+            Arc::clone(&requests),
+            Arc::clone(&stopped),
+            Arc::clone(&store),
+        );
+        let thread = thread::spawn(move || {
+            let (requests, stopped, store) = context;
+            let mut connections: Vec<Connection> = vec![];
+            while !stopped.load(Ordering::Relaxed) {
+                connections.retain(|connection| !connection.thread.is_finished());
+                // 逾時者斷之，讀者即知其不完；其線程仍佔一位，至求值終了
+                for connection in &connections {
+                    if connection.accepted.elapsed() > timeout {
+                        let _ = connection.stream.shutdown(Shutdown::Both);
+                    }
+                }
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        requests.fetch_add(1, Ordering::Relaxed);
+                        if connections.len() >= MAX_CONNECTIONS {
+                            refuse_busy(stream);
+                            continue;
+                        }
+                        let Ok(copy) = stream.try_clone() else {
+                            continue;
+                        };
+                        let store = store.lock().unwrap().clone();
+                        let stopped = Arc::clone(&stopped);
+                        let thread = thread::spawn(move || {
+                            if let Err(error) = serve_connection(&store, stream, &stopped, timeout)
+                            {
+                                log::debug!("endpoint connection failed error={error:#}");
+                            }
+                        });
+                        connections.push(Connection {
+                            thread,
+                            stream: copy,
+                            accepted: Instant::now(),
+                        });
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL),
+                    Err(error) => {
+                        log::warn!("endpoint accept failed error={error}");
+                        thread::sleep(POLL);
+                    }
+                }
+            }
+            // 斷之，讀者即醒；出行中之查詢見 `stopped` 即止，求值中者不等
+            for connection in &connections {
+                let _ = connection.stream.shutdown(Shutdown::Both);
+            }
+            let started = Instant::now();
+            while started.elapsed() < STOP_WAIT {
+                connections.retain(|connection| !connection.thread.is_finished());
+                if connections.is_empty() {
+                    return;
+                }
+                thread::sleep(POLL);
+            }
+            log::debug!(
+                "endpoint left queries to finish on their own count={}",
+                connections.len()
+            );
+        });
+        log::info!("endpoint listening address={address}");
+        Ok(Self {
+            address,
+            store,
+            requests,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    /// 查詢之 URL，如 `http://127.0.0.1:7878/query`。
+    pub fn url(&self) -> String {
+        format!("http://{}/query", self.address)
+    }
+
+    /// 此後之請求查詢 `store`。進行中者不受影響。
+    pub fn swap(&self, store: &Store) {
+        *self.store.lock().unwrap() = store.clone();
+    }
+
+    /// 迄今所收之請求數。
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        log::info!("endpoint stopped address={}", self.address);
+    }
+}
+
+/// 應答中之連接：其線程，及連接之副本以便逾時或停時斷之。
+struct Connection {
+    thread: JoinHandle<()>,
+    stream: TcpStream,
+    accepted: Instant,
+}
+
+/// 所收之請求。
+#[derive(Debug)]
+struct Request {
+    method: String,
+    /// 路徑，不含查詢串。
+    path: String,
+    /// URL 之查詢串，未解碼。
+    query_string: String,
+    content_type: Option<String>,
+    accept: Option<String>,
+    body: Vec<u8>,
+}
+
+/// 所發之應答。
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+/// 查詢之應答：錯誤，或待寫出之結果。
+enum Reply {
+    Error(Response),
+    Solutions(QuerySolutionIter, QueryResultsFormat),
+    Boolean(bool, QueryResultsFormat),
+    Graph(QueryTripleIter, RdfFormat),
+}
+
+impl Reply {
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::Error(Response::error(status, message))
+    }
+
+    fn status(&self) -> u16 {
+        match self {
+            Self::Error(response) => response.status,
+            _ => 200,
+        }
+    }
+}
+
+impl Response {
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        let mut body = message.into().into_bytes();
+        body.push(b'\n');
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body,
+        }
+    }
+}
+
+fn serve_connection(
+    store: &Store,
+    stream: TcpStream,
+    stopped: &AtomicBool,
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    answer(
+        store,
+        &mut BufReader::new(&stream),
+        &stream,
+        stopped,
+        timeout,
+    )
+}
+
+/// 讀一請求而答之。求值逾 `timeout` 或 `stopped` 則止，其應答不完。
+fn answer(
+    store: &Store,
+    reader: &mut impl BufRead,
+    output: impl Write,
+    stopped: &AtomicBool,
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let reply = match read_request(reader) {
+        Ok(request) => {
+            let reply = respond(store, &request);
+            log::info!(
+                "endpoint {} {} status={}",
+                request.method,
+                request.path,
+                reply.status()
+            );
+            reply
+        }
+        Err(error) => Reply::Error(Response::error(400, error.to_string())),
+    };
+    let check = || {
+        if stopped.load(Ordering::Relaxed) {
+            bail!("the endpoint stopped");
+        }
+        if started.elapsed() > timeout {
+            bail!("the query took longer than {}s", timeout.as_secs());
+        }
+        Ok(())
+    };
+    let mut body = match &reply {
+        Reply::Error(response) => return write_response(output, response),
+        Reply::Solutions(_, format) | Reply::Boolean(_, format) => {
+            chunked(output, format.media_type())?
+        }
+        Reply::Graph(_, format) => chunked(output, format.media_type())?,
+    };
+    match reply {
+        // 已答於上
+        Reply::Error(_) => {}
+        Reply::Solutions(solutions, format) => {
+            let mut writer = QueryResultsSerializer::from_format(format)
+                .serialize_solutions_to_write(&mut body, solutions.variables().to_vec())?;
+            for solution in solutions {
+                check()?;
+                writer.write(&solution?)?;
+            }
+            writer.finish()?;
+        }
+        Reply::Boolean(value, format) => {
+            QueryResultsSerializer::from_format(format)
+                .serialize_boolean_to_write(&mut body, value)?;
+        }
+        Reply::Graph(triples, format) => {
+            let mut writer = RdfSerializer::from_format(format).serialize_to_write(&mut body);
+            for triple in triples {
+                check()?;
+                writer.write_triple(&triple?)?;
+            }
+            writer.finish()?;
+        }
+    }
+    body.into_inner()
+        .map_err(|error| error.into_error())?
+        .finish()?;
+    Ok(())
+}
+
+/// 連接已滿：即答 503 而關之。不等待，應答短，必入發送之緩衝；不能即寫者棄之，
+/// 故慢讀者不阻他人之連接。
+fn refuse_busy(stream: TcpStream) {
+    let response = Response::error(
+        503,
+        format!("Already answering {MAX_CONNECTIONS} requests; try again later"),
+    );
+    let written = stream
+        .set_nonblocking(true)
+        .map_err(anyhow::Error::from)
+        .and_then(|()| write_response(&stream, &response));
+    if let Err(error) = written {
+        log::debug!("endpoint busy reply failed error={error:#}");
+    }
+}
+
+/// 讀取一請求。僅識 `Content-Length` 所定長之請求體。
+fn read_request(reader: &mut impl BufRead) -> anyhow::Result<Request> {
+    let mut head = reader.by_ref().take(MAX_HEAD);
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        bail!("Malformed request line");
+    };
+    let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query_string: query_string.to_string(),
+        content_type: None,
+        accept: None,
+        body: vec![],
+    };
+    let mut length = 0;
+    loop {
+        line.clear();
+        if head.read_line(&mut line)? == 0 {
+            bail!("Request headers too long or cut off");
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            bail!("Malformed header: {line}");
+        };
+        let value = value.trim().to_string();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-type" => request.content_type = Some(value),
+            "accept" => request.accept = Some(value),
+            "content-length" => length = value.parse().context("Malformed Content-Length")?,
+            _ => {}
+        }
+    }
+    if length > MAX_BODY {
+        bail!("Request body over {MAX_BODY} bytes");
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+    Ok(request)
+}
+
+fn write_response(mut stream: impl Write, response: &Response) -> anyhow::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        415 => "Unsupported Media Type",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// 寫成功之應答頭，返回分塊寫出其體者。
+fn chunked<W: Write>(mut stream: W, content_type: &str) -> io::Result<BufWriter<Chunked<W>>> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nTransfer-Encoding: chunked\r\n\
+         Connection: close\r\n\r\n"
+    )?;
+    Ok(BufWriter::with_capacity(CHUNK, Chunked(stream)))
+}
+
+/// 以分塊傳輸寫出，結果之長無須先知。
+struct Chunked<W: Write>(W);
+
+impl<W: Write> Chunked<W> {
+    /// 末塊。無之則讀者知應答不完。
+    fn finish(mut self) -> io::Result<()> {
+        self.0.write_all(b"0\r\n\r\n")?;
+        self.0.flush()
+    }
+}
+
+impl<W: Write> Write for Chunked<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        write!(self.0, "{:X}\r\n", buf.len())?;
+        self.0.write_all(buf)?;
+        self.0.write_all(b"\r\n")?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// 按 SPARQL 協議應答。
+fn respond(store: &Store, request: &Request) -> Reply {
+    if request.path != "/query" {
+        return Reply::error(404, "Only /query is served");
+    }
+    let mut params = match decode_form(&request.query_string) {
+        Ok(params) => params,
+        Err(error) => return Reply::error(400, error.to_string()),
+    };
+    match request.method.as_str() {
+        "GET" => {}
+        "POST" => {
+            let media_type = request
+                .content_type
+                .as_deref()
+                .and_then(|content_type| content_type.split(';').next())
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+            match media_type.as_str() {
+                "application/sparql-query" => match String::from_utf8(request.body.clone()) {
+                    Ok(query) => params.push(("query".to_string(), query)),
+                    Err(_) => return Reply::error(400, "The query is not UTF-8"),
+                },
+                "application/x-www-form-urlencoded" => {
+                    match std::str::from_utf8(&request.body)
+                        .map_err(anyhow::Error::from)
+                        .and_then(decode_form)
+                    {
+                        Ok(form) => params.extend(form),
+                        Err(error) => return Reply::error(400, error.to_string()),
+                    }
+                }
+                "application/sparql-update" => return refuse_update(),
+                _ => {
+                    return Reply::error(
+                        415,
+                        "Send application/sparql-query or application/x-www-form-urlencoded",
+                    )
+                }
+            }
+        }
+        _ => return Reply::error(405, "Use GET or POST"),
+    }
+    if params.iter().any(|(name, _)| name == "update") {
+        return refuse_update();
+    }
+    let Some((_, query)) = params.iter().find(|(name, _)| name == "query") else {
+        return Reply::error(400, "Missing the query parameter");
+    };
+    let mut query = match Query::parse(query, None) {
+        Ok(query) => query,
+        Err(error) => return Reply::error(400, error.to_string()),
+    };
+    if let Err(error) = set_dataset(&mut query, &params) {
+        return Reply::error(400, error.to_string());
+    }
+    let options = QueryOptions::default().with_service_handler(DisabledServiceHandler);
+    let results = match store.query_opt(query, options) {
+        Ok(results) => results,
+        Err(error) => return Reply::error(500, error.to_string()),
+    };
+    let accept = request.accept.as_deref().unwrap_or("*/*");
+    if let QueryResults::Graph(triples) = results {
+        return match negotiate(accept, RdfFormat::Turtle, RdfFormat::from_media_type) {
+            Some(format) => Reply::Graph(triples, format),
+            None => Reply::error(406, "Graph results are available as RDF"),
+        };
+    }
+    let Some(format) = negotiate(
+        accept,
+        QueryResultsFormat::Json,
+        QueryResultsFormat::from_media_type,
+    ) else {
+        return Reply::error(406, "Results are available as JSON, XML, CSV or TSV");
+    };
+    match results {
+        QueryResults::Solutions(solutions) => Reply::Solutions(solutions, format),
+        QueryResults::Boolean(value) => Reply::Boolean(value, format),
+        QueryResults::Graph(_) => unreachable!("graphs are answered above"),
+    }
+}
+
+fn refuse_update() -> Reply {
+    Reply::error(403, "This endpoint is read-only")
+}
+
+/// 以 `default-graph-uri` 與 `named-graph-uri` 代查詢之數據集。
+fn set_dataset(query: &mut Query, params: &[(String, String)]) -> anyhow::Result<()> {
+    let graphs = |key: &str| {
+        params
+            .iter()
+            .filter(|(name, _)| name == key)
+            .map(|(_, iri)| NamedNode::new(iri.as_str()))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let (default, named) = (graphs("default-graph-uri")?, graphs("named-graph-uri")?);
+    if !default.is_empty() || !named.is_empty() {
+        let dataset = query.dataset_mut();
+        dataset.set_default_graph(default.into_iter().map(GraphName::from).collect());
+        dataset.set_available_named_graphs(named.into_iter().map(NamedOrBlankNode::from).collect());
+    }
+    Ok(())
+}
+
+/// 按 `Accept` 之權重擇格式。`*/*`、`application/*` 與 `text/*` 取默認者。
+fn negotiate<F>(accept: &str, default: F, parse: impl Fn(&str) -> Option<F>) -> Option<F> {
+    let mut ranges: Vec<(&str, f32)> = accept
+        .split(',')
+        .map(|range| {
+            let mut parts = range.split(';');
+            let media_type = parts.next().unwrap_or_default().trim();
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse().ok())
+                .unwrap_or(1.0);
+            (media_type, weight)
+        })
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect();
+    // 穩定排序，同權者依原序
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut default = Some(default);
+    for (media_type, _) in ranges {
+        if matches!(media_type, "*/*" | "application/*" | "text/*") {
+            return default.take();
+        }
+        if let Some(format) = parse(media_type) {
+            return Some(format);
+        }
+    }
+    None
+}
+
+/// 解 `application/x-www-form-urlencoded`：`+` 為空格，`%XX` 為字節。
+fn decode_form(text: &str) -> anyhow::Result<Vec<(String, String)>> {
+    text.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((decode_component(name)?, decode_component(value)?))
+        })
+        .collect()
+}
+
+fn decode_component(text: &str) -> anyhow::Result<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = rest
+                    .get(..2)
+                    .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .context("Malformed percent-encoding")?;
+                bytes.push(hex);
+                rest = &rest[2..];
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).context("Parameter is not UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture;
+    use oxigraph::io::RdfParser;
+
+    fn store() -> Store {
+        let store = Store::new().unwrap();
+        store
+            .load_from_read(
+                RdfParser::from_format(RdfFormat::Turtle),
+                "<http://ex/a> <http://ex/p> 1 .".as_bytes(),
+            )
+            .unwrap();
+        store
+    }
+
+    /// 應答之狀態、內容類型與體。體分塊者合之；末塊缺則體為 `None`。
+    struct Answer {
+        status: u16,
+        content_type: String,
+        body: Option<String>,
+    }
+
+    fn answer_within(store: &Store, raw: &str, timeout: Duration) -> Answer {
+        let mut output = vec![];
+        let stopped = AtomicBool::new(false);
+        let _ = answer(store, &mut raw.as_bytes(), &mut output, &stopped, timeout);
+        let output = String::from_utf8(output).unwrap();
+        let (head, mut rest) = output.split_once("\r\n\r\n").unwrap();
+        let header = |name: &str| {
+            head.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+                .map(str::to_string)
+        };
+        let body = if header("Transfer-Encoding").as_deref() == Some("chunked") {
+            let mut body = String::new();
+            loop {
+                let Some((size, tail)) = rest.split_once("\r\n") else {
+                    break None;
+                };
+                let size = usize::from_str_radix(size, 16).unwrap();
+                if size == 0 {
+                    break Some(body);
+                }
+                body.push_str(&tail[..size]);
+                rest = &tail[size + 2..];
+            }
+        } else {
+            Some(rest.to_string())
+        };
+        Answer {
+            status: head.split(' ').nth(1).unwrap().parse().unwrap(),
+            content_type: header("Content-Type").unwrap(),
+            body,
+        }
+    }
+
+    fn request(raw: &str) -> Answer {
+        answer_within(&store(), raw, QUERY_TIMEOUT)
+    }
+
+    #[test]
+    fn queries_are_decoded_and_negotiated() {
+        let response = request(
+            "GET /query?query=SELECT+%3Fs+WHERE+%7B%3Fs+%3Fp+%3Fo%7D HTTP/1.1\r\n\
+             Accept: text/csv;q=0.9, application/sparql-results+xml\r\n\r\n",
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "application/sparql-results+xml");
+
+        let body = "query=SELECT+%3Fs+WHERE+%7B%3Fs+%3Fp+%3Fo%7D";
+        let response = request(&format!(
+            "POST /query HTTP/1.1\r\nContent-Type: application/x-www-form-urlencoded\r\n\
+             Accept: text/csv\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        ));
+        assert_eq!(response.body.unwrap(), "s\r\nhttp://ex/a\r\n");
+
+        let body = "ASK { ?s ?p 1 }";
+        let response = request(&format!(
+            "POST /query HTTP/1.1\r\nContent-Type: application/sparql-query\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        ));
+        assert_eq!(response.content_type, "application/sparql-results+json");
+        assert_eq!(response.body.unwrap(), "{\"head\":{},\"boolean\":true}");
+    }
+
+    #[test]
+    fn updates_and_other_paths_are_refused() {
+        assert_eq!(
+            request("GET /query?update=CLEAR+ALL HTTP/1.1\r\n\r\n").status,
+            403
+        );
+        let body = "CLEAR ALL";
+        let update = format!(
+            "POST /query HTTP/1.1\r\nContent-Type: application/sparql-update\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        assert_eq!(request(&update).status, 403);
+        assert_eq!(request("GET /update HTTP/1.1\r\n\r\n").status, 404);
+        assert_eq!(request("DELETE /query HTTP/1.1\r\n\r\n").status, 405);
+        assert_eq!(
+            request("GET /query?query=ASK{} HTTP/1.1\r\nAccept: image/png\r\n\r\n").status,
+            406
+        );
+    }
+
+    #[test]
+    fn long_results_stream_until_the_timeout_cuts_them() {
+        let store = Store::new().unwrap();
+        let spec = fixture::Spec {
+            instances: 200,
+            ..fixture::Spec::default()
+        };
+        spec.load(0, &store).unwrap();
+        let raw = "GET /query?query=SELECT+*+WHERE+%7B%3Fs+%3Fp+%3Fo%7D HTTP/1.1\r\n\
+                   Accept: text/tab-separated-values\r\n\r\n";
+        let whole = answer_within(&store, raw, QUERY_TIMEOUT);
+        assert_eq!(
+            whole.body.unwrap().lines().count(),
+            store.len().unwrap() + 1
+        );
+        // 頭已發，逾時則無末塊，讀者知其不完
+        let cut = answer_within(&store, raw, Duration::ZERO);
+        assert_eq!(cut.status, 200);
+        assert!(cut.body.is_none());
+    }
+
+    #[test]
+    fn slow_queries_are_cut_off_and_do_not_hold_up_stopping() {
+        let store = Store::new().unwrap();
+        let spec = fixture::Spec {
+            instances: 200,
+            ..fixture::Spec::default()
+        };
+        spec.load(0, &store).unwrap();
+        let server = Server::start(&store, "127.0.0.1:0", Duration::from_millis(100)).unwrap();
+        // 計數須先求盡其積，首行之前無可查之處
+        let mut stream = TcpStream::connect(server.address).unwrap();
+        stream
+            .write_all(
+                b"GET /query?query=SELECT+(COUNT(*)+AS+%3Fn)+WHERE+\
+                  %7B%3Fa+%3Fp+%3Fb+.+%3Fc+%3Fq+%3Fd%7D HTTP/1.1\r\n\r\n",
+            )
+            .unwrap();
+        let started = Instant::now();
+        let mut reply = vec![];
+        let _ = stream.read_to_end(&mut reply);
+        assert!(started.elapsed() < READ_TIMEOUT / 2);
+        assert!(!reply.ends_with(b"0\r\n\r\n"));
+
+        let started = Instant::now();
+        drop(server);
+        assert!(started.elapsed() < STOP_WAIT * 2);
+    }
+
+    #[test]
+    fn malformed_escapes_are_rejected() {
+        assert_eq!(decode_component("a%2Fb+c").unwrap(), "a/b c");
+        for text in ["%+1", "%-1", "%4", "%zz"] {
+            assert!(decode_component(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn busy_servers_refuse_more_connections_and_stop_promptly() {
+        let server = Server::spawn(&store(), "127.0.0.1:0").unwrap();
+        let address = server.address;
+        // 連而不發請求者各佔一線程
+        let idle: Vec<TcpStream> = (0..MAX_CONNECTIONS)
+            .map(|_| TcpStream::connect(address).unwrap())
+            .collect();
+        let started = Instant::now();
+        while server.requests() < MAX_CONNECTIONS && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(POLL);
+        }
+        let mut extra = TcpStream::connect(address).unwrap();
+        let mut reply = String::new();
+        extra.read_to_string(&mut reply).unwrap();
+        assert!(reply.starts_with("HTTP/1.1 503"), "{reply}");
+
+        let started = Instant::now();
+        drop(server);
+        assert!(started.elapsed() < READ_TIMEOUT / 2);
+        drop(idle);
+    }
+}
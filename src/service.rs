@@ -0,0 +1,102 @@
+use crate::http::Client;
+use oxigraph::{
+    model::NamedNode,
+    sparql::{results::QueryResultsFormat, Query, QueryOptions, QueryResults, ServiceHandler},
+};
+use std::{
+    fmt,
+    io::Cursor,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// 聯邦查詢之 SERVICE 處理者。經 HTTP 將子查詢送至遠端端點，並記請求數。
+pub struct FederatedServiceHandler {
+    client: Client,
+    requests: Arc<AtomicUsize>,
+}
+
+impl FederatedServiceHandler {
+    pub fn new(requests: Arc<AtomicUsize>, timeout: Duration) -> Self {
+        Self {
+            client: Client::new(Some(timeout), 5),
+            requests,
+        }
+    }
+}
+
+impl ServiceHandler for FederatedServiceHandler {
+    type Error = ServiceError;
+
+    fn handle(&self, service_name: NamedNode, query: Query) -> Result<QueryResults, Self::Error> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let endpoint = service_name.as_str();
+        let error = |message: String| ServiceError {
+            endpoint: endpoint.to_string(),
+            message,
+        };
+
+        let response = self
+            .client
+            .post(
+                endpoint,
+                query.to_string().as_bytes(),
+                "application/sparql-query",
+                "application/sparql-results+json, application/sparql-results+xml",
+            )
+            .map_err(|e| error(e.to_string()))?;
+        if !(200..300).contains(&response.status) {
+            return Err(error(format!("HTTP {}", response.status)));
+        }
+        let content_type = response.content_type.unwrap_or_default();
+        let format = QueryResultsFormat::from_media_type(&content_type)
+            .ok_or_else(|| error(format!("unsupported content type `{content_type}`")))?;
+        QueryResults::read(Cursor::new(response.body), format).map_err(|e| error(e.to_string()))
+    }
+}
+
+/// 查詢之選項：聯邦則經 HTTP 處理 SERVICE，並計請求數於 `requests`。
+pub fn query_options(
+    federated: bool,
+    timeout: Duration,
+    requests: Arc<AtomicUsize>,
+) -> QueryOptions {
+    if federated {
+        QueryOptions::default()
+            .with_service_handler(FederatedServiceHandler::new(requests, timeout))
+    } else {
+        QueryOptions::default().with_service_handler(DisabledServiceHandler)
+    }
+}
+
+/// 未開聯邦查詢時之處理者，明示 SERVICE 被略過之因。
+pub struct DisabledServiceHandler;
+
+impl ServiceHandler for DisabledServiceHandler {
+    type Error = ServiceError;
+
+    fn handle(&self, service_name: NamedNode, _: Query) -> Result<QueryResults, Self::Error> {
+        Err(ServiceError {
+            endpoint: service_name.into_string(),
+            message: "federation is disabled (start with --federated)".to_string(),
+        })
+    }
+}
+
+/// SERVICE 子句之錯誤，附端點 URL。
+#[derive(Debug)]
+pub struct ServiceError {
+    endpoint: String,
+    message: String,
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SERVICE <{}>: {}", self.endpoint, self.message)
+    }
+}
+
+impl std::error::Error for ServiceError {}
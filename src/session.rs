@@ -0,0 +1,241 @@
+use crate::{
+    app::Mode,
+    bookmarks::{escape, unescape},
+    dataset::Dataset,
+    header::{sort_from_field, sort_to_field, Order},
+    macros::Macro,
+    paths::create_parent,
+    provenance::GraphLoad,
+    scratchpad::Pin,
+};
+use std::{fs, io, path::Path};
+
+/// 會話文件之首行。格式有變則增其版本，舊文件遂被忽略。
+const HEADER: &str = "rdf-tui-session\t1";
+
+/// 啓動時是否恢復上次之會話。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restore {
+    /// 詢問用戶。
+    Ask,
+    Always,
+    Never,
+}
+
+/// 退出時之狀態，按數據集保存，下次載入同一數據集時恢復。書籤另存，不在其中。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    /// 編輯器中之查詢。
+    pub query: String,
+    /// 上次執行之查詢。不自動查詢時可與編輯器中者相異。
+    pub last_query: Option<String>,
+    /// 瀏覽或編輯查詢。
+    pub mode: Mode,
+    pub selected_row: usize,
+    /// 結果所依排序之諸列及其向，前者為主。
+    pub sort: Vec<(usize, Order)>,
+    /// 顯示之行數上限。
+    pub limit: Option<usize>,
+    /// 查詢之默認圖。
+    pub dataset: Dataset,
+    /// 各圖之來源。
+    pub graphs: Vec<GraphLoad>,
+    /// 便箋所釘者。
+    pub pins: Vec<Pin>,
+    /// 各寄存器所記之宏。
+    pub macros: Vec<Macro>,
+}
+
+impl Session {
+    /// 讀取會話。無文件則為 `None`；損壞或版本不符則為錯誤，由調用者警告後忽略。
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.to_string()),
+        };
+        let mut lines = content.lines();
+        if lines.next() != Some(HEADER) {
+            return Err("unsupported session file version".to_string());
+        }
+        let mut session = Self {
+            query: String::new(),
+            last_query: None,
+            mode: Mode::Browse,
+            selected_row: 0,
+            sort: vec![],
+            limit: None,
+            dataset: Dataset::Default,
+            graphs: vec![],
+            pins: vec![],
+            macros: vec![],
+        };
+        let mut has_query = false;
+        for (index, line) in lines.enumerate() {
+            let invalid = || format!("line {}: invalid entry", index + 2);
+            let (key, value) = line.split_once('\t').ok_or_else(invalid)?;
+            match key {
+                "query" => {
+                    session.query = unescape(value);
+                    has_query = true;
+                }
+                "last_query" => session.last_query = Some(unescape(value)),
+                "mode" => {
+                    session.mode = match value {
+                        "query" => Mode::Query,
+                        "browse" => Mode::Browse,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "selected_row" => session.selected_row = value.parse().map_err(|_| invalid())?,
+                "sort" => session.sort = sort_from_field(value).ok_or_else(invalid)?,
+                "limit" => {
+                    session.limit = match value {
+                        "none" => None,
+                        _ => Some(value.parse().map_err(|_| invalid())?),
+                    }
+                }
+                "dataset" => session.dataset = Dataset::from_field(value).ok_or_else(invalid)?,
+                // 圖名、來源、載入之時、三元組數；默認圖之名為空
+                "graph" => {
+                    let fields: Vec<&str> = value.split('\t').collect();
+                    let [graph, source, loaded_at, triples] = fields[..] else {
+                        return Err(invalid());
+                    };
+                    session.graphs.push(GraphLoad {
+                        graph: (!graph.is_empty()).then(|| unescape(graph)),
+                        source: unescape(source),
+                        loaded_at: loaded_at.parse().map_err(|_| invalid())?,
+                        triples: triples.parse().map_err(|_| invalid())?,
+                    });
+                }
+                "pin" => session
+                    .pins
+                    .push(Pin::from_field(value).ok_or_else(invalid)?),
+                "macro" => session
+                    .macros
+                    .push(Macro::from_field(value).ok_or_else(invalid)?),
+                // 新版或增之項，忽略之
+                _ => {}
+            }
+        }
+        if !has_query {
+            return Err("no query in session file".to_string());
+        }
+        Ok(Some(session))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        create_parent(path)?;
+        let mut content = format!("{HEADER}\nquery\t{}\n", escape(&self.query));
+        if let Some(query) = &self.last_query {
+            content.push_str(&format!("last_query\t{}\n", escape(query)));
+        }
+        let mode = match self.mode {
+            Mode::Query => "query",
+            _ => "browse",
+        };
+        content.push_str(&format!("mode\t{mode}\n"));
+        content.push_str(&format!("selected_row\t{}\n", self.selected_row));
+        if !self.sort.is_empty() {
+            content.push_str(&format!("sort\t{}\n", sort_to_field(&self.sort)));
+        }
+        match self.limit {
+            Some(limit) => content.push_str(&format!("limit\t{limit}\n")),
+            None => content.push_str("limit\tnone\n"),
+        }
+        content.push_str(&format!("dataset\t{}\n", self.dataset.to_field()));
+        for graph in &self.graphs {
+            content.push_str(&format!(
+                "graph\t{}\t{}\t{}\t{}\n",
+                escape(graph.graph.as_deref().unwrap_or_default()),
+                escape(&graph.source),
+                graph.loaded_at,
+                graph.triples
+            ));
+        }
+        for pin in &self.pins {
+            content.push_str(&format!("pin\t{}\n", pin.to_field()));
+        }
+        for recorded in &self.macros {
+            content.push_str(&format!("macro\t{}\n", recorded.to_field()));
+        }
+        fs::write(path, content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-session-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn round_trips() {
+        let path = temp_file("round-trip.tsv");
+        let session = Session {
+            query: "SELECT *\nWHERE {\t?s ?p ?o }".to_string(),
+            last_query: Some("ASK {}".to_string()),
+            mode: Mode::Query,
+            selected_row: 3,
+            sort: vec![(2, Order::Descending), (0, Order::Ascending)],
+            limit: Some(10),
+            dataset: Dataset::Graphs(vec![oxigraph::model::NamedNode::new_unchecked(
+                "http://ex/g",
+            )
+            .into()]),
+            graphs: vec![
+                GraphLoad {
+                    graph: None,
+                    source: "/data/a\tb.trig".to_string(),
+                    loaded_at: 1_719_835_200,
+                    triples: 2,
+                },
+                GraphLoad {
+                    graph: Some("<http://ex/g>".to_string()),
+                    source: "/data/a\tb.trig".to_string(),
+                    loaded_at: 1_719_835_200,
+                    triples: 5,
+                },
+            ],
+            pins: vec![
+                Pin::Text("?s a ?type".to_string()),
+                Pin::Term(oxigraph::model::NamedNode::new_unchecked("http://ex/a").into()),
+            ],
+            macros: vec![Macro {
+                register: 'q',
+                actions: vec![
+                    crate::action::Action::ScrollRows(1),
+                    crate::action::Action::InsertChar('\t'),
+                ],
+            }],
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path), Ok(Some(session)));
+    }
+
+    #[test]
+    fn ignores_missing_and_rejects_corrupt_files() {
+        assert_eq!(Session::load(&temp_file("missing.tsv")), Ok(None));
+
+        let path = temp_file("corrupt.tsv");
+        fs::write(&path, "rdf-tui-session\t0\nquery\tx\n").unwrap();
+        assert!(Session::load(&path).is_err());
+        fs::write(&path, "rdf-tui-session\t1\nquery\tx\nselected_row\tmany\n").unwrap();
+        assert_eq!(
+            Session::load(&path),
+            Err("line 3: invalid entry".to_string())
+        );
+        fs::write(&path, "rdf-tui-session\t1\nquery\tx\nsort\t1^\n").unwrap();
+        assert_eq!(
+            Session::load(&path),
+            Err("line 3: invalid entry".to_string())
+        );
+        fs::write(&path, [0xff, 0xfe]).unwrap();
+        assert!(Session::load(&path).is_err());
+    }
+}
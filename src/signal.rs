@@ -0,0 +1,79 @@
+use crate::util::restore_terminal;
+use std::{
+    io,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+/// 是否收到終止信號。
+static TERMINATE: AtomicBool = AtomicBool::new(false);
+
+/// 是否收到外來之 SIGTSTP，待主循環掛起。
+static SUSPEND: AtomicBool = AtomicBool::new(false);
+
+/// 自身掛起時發予進程組之 SIGTSTP 數。信號線程收到時抵消之，不再請求掛起。
+static OWN_STOPS: AtomicUsize = AtomicUsize::new(0);
+
+/// 安裝信號處理。
+///
+/// SIGTERM、SIGHUP、SIGINT 首次收到時請求退出，由主循環照常恢復終端；
+/// 若主循環忙於查詢而再次收到，則於此直接恢復終端並退出。
+/// SIGTSTP 不依默認停止進程，以免終端停於原始模式，而由主循環經守衛掛起。
+#[cfg(unix)]
+pub fn install_handlers() -> io::Result<()> {
+    use signal_hook::{
+        consts::{SIGHUP, SIGINT, SIGTERM, SIGTSTP},
+        iterator::Signals,
+    };
+    let mut signals = Signals::new([SIGTERM, SIGHUP, SIGINT, SIGTSTP])?;
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            log::info!("signal={signal}");
+            if signal == SIGTSTP {
+                let own = OWN_STOPS
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok();
+                if !own {
+                    SUSPEND.store(true, Ordering::SeqCst);
+                }
+                continue;
+            }
+            if TERMINATE.swap(true, Ordering::SeqCst) {
+                restore_terminal();
+                std::process::exit(128 + signal);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn install_handlers() -> io::Result<()> {
+    Ok(())
+}
+
+/// 是否已收到終止信號。
+pub fn terminate_requested() -> bool {
+    TERMINATE.load(Ordering::SeqCst)
+}
+
+/// 取走外來之掛起請求。
+pub fn take_suspend_request() -> bool {
+    SUSPEND.swap(false, Ordering::SeqCst)
+}
+
+/// 停止進程組，待 SIGCONT 後返回。
+///
+/// SIGTSTP 已由 [`install_handlers`] 接管，不能停止自身，故另向自身發 SIGSTOP。
+#[cfg(unix)]
+pub fn stop() {
+    use signal_hook::{consts::SIGSTOP, low_level::raise};
+    OWN_STOPS.fetch_add(1, Ordering::SeqCst);
+    // SAFETY: `kill` 僅發信號，無內存之虞
+    if unsafe { libc::kill(0, libc::SIGTSTP) } != 0 {
+        OWN_STOPS.fetch_sub(1, Ordering::SeqCst);
+    }
+    let _ = raise(SIGSTOP);
+}
+
+#[cfg(not(unix))]
+pub fn stop() {}
@@ -0,0 +1,437 @@
+//! 結果之去處：寫文件、附於文件之末、交予命令之標準輸入，或 POST 至 URL。皆邊寫邊送，
+//! 不先積全部結果於內存。`:export` 與無界面之 `--sink` 共用之。
+
+use crate::{
+    display::format_bytes,
+    http::{drain, Client},
+    link::public,
+    overwrite::write_then_rename,
+};
+use anyhow::{anyhow, bail, Context};
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+};
+
+/// POST 之重定向上限。
+const REDIRECTS: usize = 5;
+
+/// 結果之去處。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sink {
+    /// 先寫 `<path>.partial`，成則易名。
+    File(PathBuf),
+    /// 附於文件之末，無則建之。
+    Append(PathBuf),
+    /// 程序及其參數，不經 shell。
+    Command(Vec<String>),
+    /// POST 至此 URL，內容類型隨格式。
+    Post(String),
+}
+
+impl Sink {
+    /// `file:PATH`、`append:PATH`、`cmd:PROGRAM ARGS…`、`post:URL` 或徑以 `http(s)://` 始者；
+    /// 餘者為文件之路徑。命令之參數以空白分之，單雙引號內之空白不分。
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let required = |rest: &str, what: &str| match rest.trim() {
+            "" => Err(format!("The sink {spec} needs {what}")),
+            rest => Ok(rest.to_string()),
+        };
+        if let Some(path) = spec.strip_prefix("file:") {
+            Ok(Self::File(required(path, "a path")?.into()))
+        } else if let Some(path) = spec.strip_prefix("append:") {
+            Ok(Self::Append(required(path, "a path")?.into()))
+        } else if let Some(command) = spec.strip_prefix("cmd:") {
+            Ok(Self::Command(split(&required(command, "a command")?)?))
+        } else if let Some(url) = spec.strip_prefix("post:") {
+            let url = required(url, "a URL")?;
+            match url.starts_with("http://") || url.starts_with("https://") {
+                true => Ok(Self::Post(url)),
+                false => Err(format!("Not an http(s) URL: {url}")),
+            }
+        } else if spec.starts_with("http://") || spec.starts_with("https://") {
+            Ok(Self::Post(spec.to_string()))
+        } else if spec.is_empty() {
+            Err("The sink is empty".to_string())
+        } else {
+            Ok(Self::File(spec.into()))
+        }
+    }
+
+    /// 所寫之文件，覆蓋前須問者。
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::File(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// 以 `write` 寫入，畢則收之，返回其所返回者及附註，如命令之首行輸出或 HTTP 之狀態。
+    /// `attached` 者命令之輸出徑示於終端，否則收之，免亂界面。`write` 出錯者，命令與上傳
+    /// 即止，不使其得半截之結果而以為完整。
+    pub fn deliver<T>(
+        &self,
+        content_type: &str,
+        keep_partial: bool,
+        attached: bool,
+        write: impl FnOnce(&mut dyn Write) -> anyhow::Result<T>,
+    ) -> anyhow::Result<(T, Option<String>)> {
+        match self {
+            Self::File(path) => {
+                let value = write_then_rename(path, keep_partial, |partial| {
+                    write(&mut File::create(partial)?)
+                })?;
+                Ok((value, None))
+            }
+            Self::Append(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("Fail to open {}", path.display()))?;
+                let mut file = Counted::new(file);
+                match write(&mut file) {
+                    Ok(value) => Ok((value, None)),
+                    Err(error) if file.bytes > 0 => Err(anyhow!(
+                        "{error:#} ({} already appended to {})",
+                        format_bytes(file.bytes),
+                        path.display()
+                    )),
+                    Err(error) => Err(error),
+                }
+            }
+            Self::Command(argv) => self.run(argv, attached, write),
+            Self::Post(url) => {
+                let upload = Client::new(None, REDIRECTS).upload(url, content_type, "*/*")?;
+                let mut upload = Counted::new(upload);
+                let written = write(&mut upload);
+                let (bytes, broken) = (upload.bytes, upload.broken);
+                if written.is_err() && !broken {
+                    upload.inner.abort();
+                    return written.map(|value| (value, None));
+                }
+                let response = upload.inner.finish().map_err(|error| match broken {
+                    true => anyhow!(
+                        "{error:#} (the upload stopped after {})",
+                        format_bytes(bytes)
+                    ),
+                    false => error,
+                })?;
+                if !(200..300).contains(&response.status) {
+                    let body = String::from_utf8_lossy(&response.body);
+                    match first_line(&body) {
+                        Some(line) => bail!("{self} answered HTTP {}: {line}", response.status),
+                        None => bail!("{self} answered HTTP {}", response.status),
+                    }
+                }
+                let value = written?;
+                Ok((
+                    value,
+                    Some(format!("{self} answered HTTP {}", response.status)),
+                ))
+            }
+        }
+    }
+
+    /// 啓命令，寫其標準輸入，待其退出。
+    fn run<T>(
+        &self,
+        argv: &[String],
+        attached: bool,
+        write: impl FnOnce(&mut dyn Write) -> anyhow::Result<T>,
+    ) -> anyhow::Result<(T, Option<String>)> {
+        let (program, args) = argv.split_first().context("The command is empty")?;
+        let output = || match attached {
+            true => Stdio::inherit(),
+            false => Stdio::piped(),
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(output())
+            .stderr(output())
+            .spawn()
+            .with_context(|| format!("Fail to run {program}"))?;
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+        let mut stdin = Counted::new(child.stdin.take().context("No stdin")?);
+        let written = write(&mut stdin);
+        let (bytes, broken) = (stdin.bytes, stdin.broken);
+        drop(stdin);
+        if written.is_err() && !broken {
+            let _ = child.kill();
+        }
+        let status = child.wait()?;
+        let stdout = String::from_utf8_lossy(&stdout.join().unwrap_or_default()).into_owned();
+        let stderr = String::from_utf8_lossy(&stderr.join().unwrap_or_default()).into_owned();
+        let value = match written {
+            Err(error) if !broken => {
+                bail!(
+                    "{error:#} ({self} was stopped after {})",
+                    format_bytes(bytes)
+                )
+            }
+            written => written,
+        };
+        if !status.success() {
+            let reason = first_line(&stderr).map_or_else(String::new, |line| format!(": {line}"));
+            bail!(
+                "{self} {} after reading {}{reason}",
+                exited(status),
+                format_bytes(bytes)
+            );
+        }
+        // 讀者先止，如 `head`：退出無誤者不為錯，然所得不全
+        let stopped = || format!("{self} stopped reading after {}", format_bytes(bytes));
+        match value {
+            Ok(value) if broken => Ok((value, Some(stopped()))),
+            Ok(value) => Ok((
+                value,
+                first_line(&stdout).map(|line| format!("{self}: {line}")),
+            )),
+            Err(_) => Err(anyhow!(stopped())),
+        }
+    }
+}
+
+impl fmt::Display for Sink {
+    /// 文件之路徑、命令如 `` `jq .` ``，或去其憑據之 URL。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) | Self::Append(path) => write!(f, "{}", path.display()),
+            Self::Command(argv) => write!(f, "`{}`", argv.join(" ")),
+            Self::Post(url) => f.write_str(&public(url)),
+        }
+    }
+}
+
+/// 計所寫之字節，記讀者是否已關管道。
+struct Counted<W> {
+    inner: W,
+    bytes: usize,
+    broken: bool,
+}
+
+impl<W> Counted<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            bytes: 0,
+            broken: false,
+        }
+    }
+}
+
+impl<W: Write> Write for Counted<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf).inspect_err(|error| {
+            self.broken |= error.kind() == io::ErrorKind::BrokenPipe;
+        })?;
+        self.bytes += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush().inspect_err(|error| {
+            self.broken |= error.kind() == io::ErrorKind::BrokenPipe;
+        })
+    }
+}
+
+/// 以空白分參數；單雙引號內者為一參數之部分。
+fn split(text: &str) -> Result<Vec<String>, String> {
+    let mut args = vec![];
+    let mut current: Option<String> = None;
+    let mut quote = None;
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(open) = quote {
+        return Err(format!("Unclosed {open} in the command: {text}"));
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+/// 首個非空之行。
+fn first_line(text: &str) -> Option<&str> {
+    text.lines().map(str::trim).find(|line| !line.is_empty())
+}
+
+/// 如 `exited with status 3`。
+fn exited(status: ExitStatus) -> String {
+    match status.code() {
+        Some(code) => format!("exited with status {code}"),
+        None => "was killed by a signal".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env, fs,
+        io::{BufRead, BufReader, Read},
+        net::TcpListener,
+        process, thread,
+    };
+
+    #[test]
+    fn sinks_parse_their_prefixes_and_split_commands_without_a_shell() {
+        assert_eq!(
+            Sink::parse("cmd:jq '.results.bindings | length'"),
+            Ok(Sink::Command(vec![
+                "jq".to_string(),
+                ".results.bindings | length".to_string()
+            ]))
+        );
+        assert_eq!(
+            Sink::parse("cmd:jq .results.bindings|length").unwrap(),
+            Sink::Command(vec!["jq".into(), ".results.bindings|length".into()])
+        );
+        assert_eq!(
+            Sink::parse("append:out.csv"),
+            Ok(Sink::Append("out.csv".into()))
+        );
+        assert_eq!(Sink::parse("out.csv"), Ok(Sink::File("out.csv".into())));
+        assert_eq!(
+            Sink::parse("https://u:p@example.org/in?token=1").map(|sink| sink.to_string()),
+            Ok("https://example.org/in".to_string())
+        );
+        assert!(Sink::parse("post:ftp://example.org").is_err());
+        assert!(Sink::parse("cmd:jq '.").unwrap_err().contains("Unclosed"));
+        assert!(Sink::parse("cmd: ").is_err());
+    }
+
+    #[test]
+    fn files_are_replaced_or_appended_to() {
+        let path = env::temp_dir().join(format!("rdf-tui-sink-{}.csv", process::id()));
+        let write = |text: &'static str| {
+            move |out: &mut dyn Write| -> anyhow::Result<usize> {
+                out.write_all(text.as_bytes())?;
+                Ok(1)
+            }
+        };
+        Sink::File(path.clone())
+            .deliver("text/csv", false, false, write("a\n"))
+            .unwrap();
+        let append = Sink::Append(path.clone());
+        append
+            .deliver("text/csv", false, false, write("b\n"))
+            .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\n");
+        // 附後出錯者，報其已附之字節
+        let error = append
+            .deliver("text/csv", false, false, |out| -> anyhow::Result<()> {
+                out.write_all(b"c\n")?;
+                bail!("query failed")
+            })
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("query failed (2 B already appended"),
+            "{error}"
+        );
+        let _ = fs::remove_file(&path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_read_the_stream_and_report_their_exit_status() {
+        let lines = |out: &mut dyn Write| -> anyhow::Result<usize> {
+            out.write_all(b"a\nb\nc\n")?;
+            Ok(3)
+        };
+        let count = Sink::Command(vec!["wc".into(), "-l".into()]);
+        let (rows, note) = count.deliver("text/csv", false, false, lines).unwrap();
+        assert_eq!((rows, note.as_deref()), (3, Some("`wc -l`: 3")));
+
+        let failing =
+            Sink::parse("cmd:sh -c 'cat >/dev/null; echo bad input >&2; exit 3'").unwrap();
+        let error = failing
+            .deliver("text/csv", false, false, lines)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.ends_with("exited with status 3 after reading 6 B: bad input"),
+            "{error}"
+        );
+
+        // 不讀而退出者，所寫不全
+        let closed = Sink::Command(vec!["true".into()]);
+        let error = closed
+            .deliver("text/csv", false, false, |out| -> anyhow::Result<()> {
+                loop {
+                    out.write_all(&[b'x'; 1 << 16])?;
+                }
+            })
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("`true` stopped reading after"), "{error}");
+    }
+
+    /// 收一請求之正文，至分塊之末，答以 `status`。返回 URL 及所收之請求。
+    fn serve(status: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/in", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            while !request.ends_with("\r\n0\r\n\r\n") {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                request.push_str(&line);
+            }
+            let body = "stored\n";
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            let _ = reader.get_mut().read(&mut [0; 16]);
+            request
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn posts_stream_with_the_format_content_type_and_report_the_status() {
+        let json = |out: &mut dyn Write| -> anyhow::Result<usize> {
+            out.write_all(b"{\"head\":{}}")?;
+            Ok(0)
+        };
+        let (url, request) = serve("201 Created");
+        let (_, note) = Sink::Post(url.clone())
+            .deliver("application/sparql-results+json", false, false, json)
+            .unwrap();
+        assert_eq!(note, Some(format!("{url} answered HTTP 201")));
+        let request = request.join().unwrap();
+        assert!(request.starts_with("POST /in"), "{request}");
+        assert!(request.contains("Content-Type: application/sparql-results+json"));
+        assert!(request.contains("Transfer-Encoding: chunked"));
+        assert!(request.contains("{\"head\":{}}"));
+
+        let (url, _) = serve("500 Internal Server Error");
+        let error = Sink::Post(url.clone())
+            .deliver("text/csv", false, false, json)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, format!("{url} answered HTTP 500: stored"));
+    }
+}
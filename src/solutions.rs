@@ -0,0 +1,234 @@
+//! 不經界面而執行查詢，逐個取其解，如 [`App::query_iter`](crate::App::query_iter)。
+//! 界面之後台查詢亦經此，故兩者之行數上限、取消與錯誤不相異。
+
+use crate::service::query_options;
+use oxigraph::{
+    sparql::{Query, QueryResults, QuerySolution, QuerySolutionIter, Variable},
+    store::Store,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// 取消之憑據。複製者共用之，任一處取消，諸處皆見。
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 查詢之選項。
+#[derive(Debug, Clone)]
+pub struct QueryOptions {
+    /// 整個查詢之時限，自開始計。逾之則以一錯誤終。
+    pub timeout: Option<Duration>,
+    /// 遠端 SERVICE 每請求之時限。
+    pub remote_timeout: Duration,
+    /// 已取消則不再取解。
+    pub cancel: CancelToken,
+    /// 至多取此數之解，餘者不取，[`SolutionStream::is_truncated`] 為真。
+    pub limit: Option<usize>,
+    /// SERVICE 經 HTTP 查詢遠端；否則報其被略過。
+    pub federated: bool,
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            remote_timeout: Duration::from_secs(30),
+            cancel: CancelToken::default(),
+            limit: None,
+            federated: false,
+        }
+    }
+}
+
+/// 查詢之解，逐個取之。SELECT 之外之查詢無變量亦無解。
+pub struct SolutionStream {
+    variables: Option<Arc<[Variable]>>,
+    solutions: Option<QuerySolutionIter>,
+    options: QueryOptions,
+    requests: Arc<AtomicUsize>,
+    started: Instant,
+    count: usize,
+    truncated: bool,
+}
+
+impl SolutionStream {
+    /// SELECT 之變量；他種查詢則無。
+    pub fn variables(&self) -> Option<&[Variable]> {
+        self.variables.as_deref()
+    }
+
+    /// 是否因行數上限而未取盡。
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// 迄今所發之遠端請求數。
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+}
+
+impl Iterator for SolutionStream {
+    type Item = anyhow::Result<QuerySolution>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let solutions = self.solutions.as_mut()?;
+        if self.options.cancel.is_cancelled() {
+            self.solutions = None;
+            return None;
+        }
+        if let Some(timeout) = self
+            .options
+            .timeout
+            .filter(|timeout| self.started.elapsed() > *timeout)
+        {
+            self.solutions = None;
+            return Some(Err(anyhow::anyhow!(
+                "timed out after {}s",
+                timeout.as_secs_f64()
+            )));
+        }
+        let solution = solutions.next()?;
+        // 多取一解方知其後尚有否
+        if self.options.limit.is_some_and(|limit| self.count >= limit) {
+            self.truncated = solution.is_ok();
+            self.solutions = None;
+            return None;
+        }
+        self.count += 1;
+        if solution.is_err() {
+            self.solutions = None;
+        }
+        Some(solution.map_err(anyhow::Error::from))
+    }
+}
+
+/// 於存儲執行查詢。默認圖為存儲之默認圖，查詢自有 FROM 者從之。
+pub fn query(store: &Store, sparql: &str, options: QueryOptions) -> anyhow::Result<SolutionStream> {
+    run(store, Query::parse(sparql, None)?, options, Arc::default())
+}
+
+/// 執行已解析之查詢，遠端請求計於 `requests`。
+pub(crate) fn run(
+    store: &Store,
+    query: Query,
+    options: QueryOptions,
+    requests: Arc<AtomicUsize>,
+) -> anyhow::Result<SolutionStream> {
+    let started = Instant::now();
+    let results = store.query_opt(
+        query,
+        query_options(
+            options.federated,
+            options.remote_timeout,
+            Arc::clone(&requests),
+        ),
+    )?;
+    let (variables, solutions) = match results {
+        QueryResults::Solutions(solutions) => (Some(solutions.variables().into()), Some(solutions)),
+        _ => (None, None),
+    };
+    Ok(SolutionStream {
+        variables,
+        solutions,
+        options,
+        requests,
+        started,
+        count: 0,
+        truncated: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::io::{RdfFormat, RdfParser};
+
+    fn store() -> Store {
+        let store = Store::new().unwrap();
+        let data: String = (0..5)
+            .map(|n| format!("<http://ex/s{n}> <http://ex/p> {n} .\n"))
+            .collect();
+        store
+            .load_from_read(RdfParser::from_format(RdfFormat::Turtle), data.as_bytes())
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn solutions_stop_at_the_limit_or_when_cancelled() {
+        let store = store();
+        let sparql = "SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?o";
+        let all = query(&store, sparql, QueryOptions::default()).unwrap();
+        assert_eq!(
+            all.variables().unwrap(),
+            [Variable::new_unchecked("s"), Variable::new_unchecked("o")]
+        );
+        assert_eq!(all.count(), 5);
+
+        let options = QueryOptions {
+            limit: Some(3),
+            ..QueryOptions::default()
+        };
+        let mut limited = query(&store, sparql, options.clone()).unwrap();
+        let values: Vec<String> = limited
+            .by_ref()
+            .map(|solution| solution.unwrap().get("o").unwrap().to_string())
+            .collect();
+        assert_eq!(
+            values,
+            ["0", "1", "2"].map(|n| format!("\"{n}\"^^<http://www.w3.org/2001/XMLSchema#integer>"))
+        );
+        assert!(limited.is_truncated());
+        let exact = QueryOptions {
+            limit: Some(5),
+            ..options
+        };
+        let mut exact = query(&store, sparql, exact).unwrap();
+        assert_eq!(exact.by_ref().count(), 5);
+        assert!(!exact.is_truncated());
+
+        let options = QueryOptions::default();
+        let mut cancelled = query(&store, sparql, options.clone()).unwrap();
+        assert!(cancelled.next().is_some());
+        options.cancel.cancel();
+        assert!(cancelled.next().is_none());
+
+        // 時限已過則以一錯誤終；他種查詢無解；語法有誤者不成流
+        let options = QueryOptions {
+            timeout: Some(Duration::ZERO),
+            ..QueryOptions::default()
+        };
+        let mut late = query(&store, sparql, options).unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(late
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .to_string()
+            .starts_with("timed out"));
+        assert!(late.next().is_none());
+        let mut ask = query(&store, "ASK {}", QueryOptions::default()).unwrap();
+        assert!(ask.variables().is_none() && ask.next().is_none());
+        assert!(query(&store, "SELECT", QueryOptions::default()).is_err());
+    }
+}
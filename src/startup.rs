@@ -0,0 +1,72 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// 數據文件旁之啓動查詢，數據集之「首頁」。
+pub const FILE_NAME: &str = ".rdf-tui.rq";
+
+/// 啓動時代替默認查詢者，及其出處。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupQuery {
+    pub query: String,
+    /// 狀態欄所示之出處，如文件之路徑。
+    pub origin: String,
+}
+
+/// 依所載入之文件之序查找：先配置中為其所設者，再其旁之文件（`files` 為假則不讀）。
+pub fn find(
+    sources: &[PathBuf],
+    configured: &[(PathBuf, String)],
+    files: bool,
+) -> Option<StartupQuery> {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    for source in sources {
+        let source = canonical(source);
+        if let Some((path, query)) = configured
+            .iter()
+            .find(|(path, _)| canonical(path) == source)
+        {
+            return Some(StartupQuery {
+                query: query.clone(),
+                origin: format!("the config for {}", path.display()),
+            });
+        }
+    }
+    if !files {
+        return None;
+    }
+    sources.iter().find_map(|source| {
+        let path = source.parent()?.join(FILE_NAME);
+        let query = fs::read_to_string(&path).ok()?;
+        (!query.trim().is_empty()).then(|| StartupQuery {
+            query: query.trim_end().to_string(),
+            origin: path.display().to_string(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_queries_come_before_files() {
+        let dir = std::env::temp_dir().join(format!("rdf-tui-startup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let data = dir.join("data.ttl");
+        fs::write(&data, "").unwrap();
+        let sources = [data.clone()];
+        assert_eq!(find(&sources, &[], true), None);
+
+        fs::write(dir.join(FILE_NAME), "SELECT ?class WHERE { [] a ?class }\n").unwrap();
+        let found = find(&sources, &[], true).unwrap();
+        assert_eq!(found.query, "SELECT ?class WHERE { [] a ?class }");
+        assert!(found.origin.ends_with(FILE_NAME));
+        assert_eq!(find(&sources, &[], false), None);
+
+        let configured = [(data.clone(), "ASK {}".to_string())];
+        assert_eq!(find(&sources, &configured, false).unwrap().query, "ASK {}");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
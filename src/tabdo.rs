@@ -0,0 +1,289 @@
+use crate::{
+    display::group_digits,
+    markup,
+    worker::{Message, TermRow, Truncation, Worker},
+};
+use oxigraph::sparql::Variable;
+use std::time::Duration;
+
+/// 一標籤頁中所行之查詢及其所得。
+pub struct TabRun {
+    /// 標籤頁之序。
+    pub tab: usize,
+    pub name: String,
+    worker: Option<Worker>,
+    /// SELECT 之變量；他種查詢則無。
+    pub variables: Option<Vec<Variable>>,
+    pub rows: Vec<TermRow>,
+    /// 各項之估計字節數。
+    pub term_bytes: usize,
+    pub truncated: Option<Truncation>,
+    pub error: Option<String>,
+    pub duration: Duration,
+}
+
+impl TabRun {
+    /// 併入已到之消息，不等待。
+    fn poll(&mut self) {
+        while let Some(message) = self.worker.as_ref().and_then(Worker::try_recv) {
+            self.receive(message);
+        }
+    }
+
+    fn receive(&mut self, message: Message) {
+        let elapsed = self.worker.as_ref().map(Worker::elapsed);
+        match message {
+            Message::Note(_) => {}
+            Message::Started(variables) => self.variables = variables,
+            Message::Rows(rows, term_bytes) => {
+                self.rows.extend(rows);
+                self.term_bytes = term_bytes;
+            }
+            Message::Finished { truncated } => {
+                self.truncated = truncated;
+                self.duration = elapsed.unwrap_or_default();
+                self.worker = None;
+            }
+            Message::Failed(error) => {
+                self.error = Some(error);
+                self.duration = elapsed.unwrap_or_default();
+                self.worker = None;
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    /// 如 `1,204 rows`，截斷者附 `+`。
+    fn rows_text(&self) -> String {
+        let rows = group_digits(self.rows.len());
+        let more = if self.truncated.is_some() { "+" } else { "" };
+        match self.rows.len() {
+            1 => format!("1{more} row"),
+            _ => format!("{rows}{more} rows"),
+        }
+    }
+}
+
+/// `:tabdo run` 所行：同一查詢於各標籤頁之存儲，並行之。一頁出錯不礙他頁。
+pub struct TabRuns {
+    pub query: String,
+    pub runs: Vec<TabRun>,
+    /// 浮窗中所選之項。
+    pub selected: usize,
+    /// 選為比較之舊者之項。
+    pub base: Option<usize>,
+}
+
+impl TabRuns {
+    pub fn new(query: String) -> Self {
+        Self {
+            query,
+            runs: vec![],
+            selected: 0,
+            base: None,
+        }
+    }
+
+    pub fn push(&mut self, tab: usize, name: String, worker: Worker) {
+        self.runs.push(TabRun {
+            tab,
+            name,
+            worker: Some(worker),
+            variables: None,
+            rows: vec![],
+            term_bytes: 0,
+            truncated: None,
+            error: None,
+            duration: Duration::ZERO,
+        });
+    }
+
+    pub fn poll(&mut self) {
+        for run in &mut self.runs {
+            run.poll();
+        }
+    }
+
+    /// 等各頁皆畢。供腳本與測試。
+    pub fn wait(&mut self) {
+        for run in &mut self.runs {
+            while let Some(worker) = &run.worker {
+                let message = worker.recv();
+                run.receive(message);
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.runs.iter().any(TabRun::is_running)
+    }
+
+    /// 兩項之結果可逐行比較：皆成而變量同。不可者返回其因。
+    pub fn comparable(&self, old: usize, new: usize) -> Result<(), String> {
+        let (old, new) = (&self.runs[old], &self.runs[new]);
+        for run in [old, new] {
+            if run.is_running() {
+                return Err(format!("Tab {} is still running", run.tab + 1));
+            }
+            if run.error.is_some() || run.variables.is_none() {
+                return Err(format!("Tab {} has no rows to compare", run.tab + 1));
+            }
+        }
+        match old.variables == new.variables {
+            true => Ok(()),
+            false => Err(format!(
+                "Tabs {} and {} return different columns",
+                old.tab + 1,
+                new.tab + 1
+            )),
+        }
+    }
+
+    /// 浮窗之諸行：頁及其名，與其行數、耗時、相較首頁之差，或其錯誤。
+    pub fn lines(&self) -> Vec<(String, String)> {
+        let first = self.first_count();
+        self.runs
+            .iter()
+            .map(|run| {
+                let summary = match (&run.error, run.is_running()) {
+                    (_, true) => format!("running… {}", run.rows_text()),
+                    (Some(error), _) => format!("error: {error}"),
+                    (None, false) => {
+                        let mut summary =
+                            format!("{} · {} ms", run.rows_text(), run.duration.as_millis());
+                        if let Some(change) = first.and_then(|first| change(first, run)) {
+                            summary.push_str(&format!(" · {change}"));
+                        }
+                        summary
+                    }
+                };
+                (format!("{} {}", run.tab + 1, run.name), summary)
+            })
+            .collect()
+    }
+
+    /// 以 Markdown 寫之：查詢，及各頁之行數、耗時與相較首頁之差。
+    pub fn markdown(&self) -> String {
+        let first = self.first_count();
+        let mut lines = vec![["tab", "dataset", "rows", "ms", "change"]
+            .map(String::from)
+            .to_vec()];
+        for run in &self.runs {
+            let (rows, ms, change) = match (&run.error, run.is_running()) {
+                (_, true) => (String::new(), String::new(), "running".to_string()),
+                (Some(error), _) => (String::new(), String::new(), format!("error: {error}")),
+                (None, false) => (
+                    group_digits(run.rows.len()) + if run.truncated.is_some() { "+" } else { "" },
+                    run.duration.as_millis().to_string(),
+                    first
+                        .and_then(|first| change(first, run))
+                        .unwrap_or_default(),
+                ),
+            };
+            lines.push(vec![
+                (run.tab + 1).to_string(),
+                run.name.clone(),
+                rows,
+                ms,
+                change,
+            ]);
+        }
+        let query = self.query.trim();
+        format!(
+            "```sparql\n{query}\n```\n\n{}",
+            markup::markdown(&lines, &[true, false, true, true, false])
+        )
+    }
+
+    /// 首個已成之項：其頁與行數。
+    fn first_count(&self) -> Option<(usize, usize)> {
+        self.runs
+            .iter()
+            .find(|run| !run.is_running() && run.error.is_none())
+            .map(|run| (run.tab, run.rows.len()))
+    }
+}
+
+/// 相較首個已成之頁之行數差，如 `-24 vs tab 1`。即其頁者無。
+fn change((tab, rows): (usize, usize), run: &TabRun) -> Option<String> {
+    if run.tab == tab {
+        return None;
+    }
+    let difference = run.rows.len() as i64 - rows as i64;
+    Some(match difference {
+        0 => format!("same as tab {}", tab + 1),
+        difference => format!(
+            "{}{} vs tab {}",
+            if difference > 0 { "+" } else { "-" },
+            group_digits(difference.unsigned_abs() as usize),
+            tab + 1
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, Term};
+    use std::sync::Arc;
+
+    fn run(tab: usize, rows: usize, error: Option<&str>) -> TabRun {
+        let row = vec![Some(Arc::new(Term::from(Literal::from(1))))];
+        TabRun {
+            tab,
+            name: format!("v{tab}.ttl"),
+            worker: None,
+            variables: error.is_none().then(|| vec![Variable::new_unchecked("s")]),
+            rows: vec![row; rows],
+            term_bytes: 0,
+            truncated: None,
+            error: error.map(String::from),
+            duration: Duration::from_millis(5),
+        }
+    }
+
+    #[test]
+    fn counts_are_compared_with_the_first_tab_that_succeeded() {
+        let mut runs = TabRuns::new("SELECT ?s { ?s ?p ?o }\n".to_string());
+        runs.runs = vec![
+            run(0, 0, Some("Parse error | here")),
+            run(1, 204, None),
+            run(2, 180, None),
+            run(3, 204, None),
+        ];
+        let lines = runs.lines();
+        assert_eq!(
+            lines[0],
+            (
+                "1 v0.ttl".to_string(),
+                "error: Parse error | here".to_string()
+            )
+        );
+        assert_eq!(lines[1].1, "204 rows · 5 ms");
+        assert_eq!(lines[2].1, "180 rows · 5 ms · -24 vs tab 2");
+        assert_eq!(lines[3].1, "204 rows · 5 ms · same as tab 2");
+        assert_eq!(
+            runs.markdown(),
+            "```sparql\nSELECT ?s { ?s ?p ?o }\n```\n\n\
+             | tab | dataset | rows | ms  | change                     |\n\
+             | --: | ------- | ---: | --: | -------------------------- |\n\
+             |   1 | v0.ttl  |      |     | error: Parse error \\| here |\n\
+             |   2 | v1.ttl  |  204 |   5 |                            |\n\
+             |   3 | v2.ttl  |  180 |   5 | -24 vs tab 2               |\n\
+             |   4 | v3.ttl  |  204 |   5 | same as tab 2              |\n"
+        );
+        assert!(runs.comparable(1, 2).is_ok());
+        assert_eq!(
+            runs.comparable(0, 1),
+            Err("Tab 1 has no rows to compare".to_string())
+        );
+        runs.runs[3].variables = Some(vec![Variable::new_unchecked("o")]);
+        assert_eq!(
+            runs.comparable(1, 3),
+            Err("Tabs 2 and 4 return different columns".to_string())
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::env;
+
+/// 可選之預設主題。
+pub const PRESETS: &[&str] = &["dark", "light", "high-contrast"];
+
+/// 結果格之色調。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tint {
+    #[default]
+    Off,
+    /// 依項之類別：IRI、字面量、空節點各一色。
+    Kind,
+    /// 每列一淡底色，相鄰者異。
+    Column,
+}
+
+impl Tint {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "kind" => Some(Self::Kind),
+            "column" => Some(Self::Column),
+            _ => None,
+        }
+    }
+
+    /// 依次換之。
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Kind,
+            Self::Kind => Self::Column,
+            Self::Column => Self::Off,
+        }
+    }
+}
+
+/// 列之底色之數。
+pub const COLUMN_TINTS: usize = 4;
+
+/// 界面各處之樣式。渲染皆取於此，不另構造。
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// 部分之標題。
+    pub title: Style,
+    /// 焦點所在部分之邊框。
+    pub focused_border: Style,
+    /// 他部分之邊框。
+    pub border: Style,
+    /// 表頭。
+    pub header: Style,
+    /// 所選之行。
+    pub selected: Style,
+    /// 錯誤文字與出錯部分之邊框。
+    pub error: Style,
+    /// 警告、確認提示與聯邦查詢之提示。
+    pub warning: Style,
+    /// 狀態欄。
+    pub status: Style,
+    /// 次要文字，如截斷提示、標籤旁之 IRI。
+    pub dim: Style,
+    /// 幫助中之按鍵。
+    pub key: Style,
+    /// 字面量之語言標籤與數據類型。
+    pub literal_suffix: Style,
+    /// 格中與過濾相符之處。
+    pub matched: Style,
+    /// 比較前次結果時新增之行。
+    pub added: Style,
+    /// 比較前次結果時刪去之行。
+    pub removed: Style,
+    /// 所標記之行。
+    pub marked: Style,
+    /// 查詢中之彎引號、非 ASCII 之空白與不可見之字符。
+    pub suspicious: Style,
+    /// 依類別著色時之 IRI 與引用三元組。
+    pub iri: Style,
+    /// 依類別著色時之字面量。
+    pub literal: Style,
+    /// 依類別著色時之空節點。
+    pub blank: Style,
+    /// 依列著色時各列之底色，輪用之。
+    pub column_tints: [Style; COLUMN_TINTS],
+    /// 無色。焦點、錯誤與所選行另以文字標明。
+    pub monochrome: bool,
+}
+
+/// 可覆蓋之樣式名。
+pub const STYLES: &[&str] = &[
+    "title",
+    "focused_border",
+    "border",
+    "header",
+    "selected",
+    "error",
+    "warning",
+    "status",
+    "dim",
+    "key",
+    "literal_suffix",
+    "matched",
+    "added",
+    "removed",
+    "marked",
+    "suspicious",
+    "iri",
+    "literal",
+    "blank",
+    "column_1",
+    "column_2",
+    "column_3",
+    "column_4",
+];
+
+impl Theme {
+    /// 取預設主題。
+    pub fn preset(name: &str) -> Option<Self> {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let dark = Self {
+            title: bold,
+            focused_border: Style::default().fg(Color::Green),
+            border: Style::default(),
+            header: bold.add_modifier(Modifier::UNDERLINED),
+            selected: Style::default().add_modifier(Modifier::REVERSED),
+            error: Style::default().fg(Color::Red),
+            warning: Style::default().fg(Color::Yellow),
+            status: Style::default(),
+            dim,
+            key: Style::default().fg(Color::Green),
+            literal_suffix: dim,
+            matched: Style::default().fg(Color::Black).bg(Color::Yellow),
+            added: Style::default().fg(Color::Green),
+            removed: Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::CROSSED_OUT),
+            marked: bold.fg(Color::Magenta),
+            suspicious: Style::default().fg(Color::Black).bg(Color::Red),
+            iri: Style::default().fg(Color::Cyan),
+            literal: Style::default().fg(Color::Green),
+            blank: Style::default().fg(Color::Magenta),
+            column_tints: [
+                Style::default().bg(Color::Rgb(28, 32, 42)),
+                Style::default().bg(Color::Rgb(38, 28, 36)),
+                Style::default().bg(Color::Rgb(26, 38, 30)),
+                Style::default().bg(Color::Rgb(40, 36, 24)),
+            ],
+            monochrome: false,
+        };
+        match name {
+            "dark" => Some(dark),
+            // 淺色背景上綠、黃難辨
+            "light" => Some(Self {
+                focused_border: Style::default().fg(Color::Blue),
+                warning: Style::default().fg(Color::Magenta),
+                key: Style::default().fg(Color::Blue),
+                added: Style::default().fg(Color::Blue),
+                iri: Style::default().fg(Color::Blue),
+                literal: Style::default().fg(Color::Rgb(0, 112, 48)),
+                blank: Style::default().fg(Color::Rgb(150, 70, 0)),
+                column_tints: [
+                    Style::default().bg(Color::Rgb(234, 240, 252)),
+                    Style::default().bg(Color::Rgb(250, 236, 244)),
+                    Style::default().bg(Color::Rgb(234, 248, 236)),
+                    Style::default().bg(Color::Rgb(250, 246, 226)),
+                ],
+                ..dark
+            }),
+            // 不用 DIM，以免低對比
+            "high-contrast" => Some(Self {
+                title: bold.fg(Color::White),
+                focused_border: bold.fg(Color::LightYellow),
+                border: Style::default().fg(Color::White),
+                selected: bold.fg(Color::Black).bg(Color::LightYellow),
+                error: bold.fg(Color::LightRed),
+                warning: bold.fg(Color::LightYellow),
+                status: Style::default().fg(Color::White),
+                dim: Style::default().fg(Color::Gray),
+                key: bold.fg(Color::LightCyan),
+                literal_suffix: Style::default().fg(Color::LightCyan),
+                matched: bold.fg(Color::Black).bg(Color::LightCyan),
+                added: bold.fg(Color::LightGreen),
+                marked: bold.fg(Color::LightMagenta),
+                suspicious: bold.fg(Color::Black).bg(Color::LightRed),
+                removed: Style::default()
+                    .fg(Color::LightRed)
+                    .add_modifier(Modifier::CROSSED_OUT),
+                iri: Style::default().fg(Color::LightCyan),
+                literal: Style::default().fg(Color::LightGreen),
+                blank: Style::default().fg(Color::LightMagenta),
+                // 淡底色減對比，不用
+                column_tints: [Style::default(); COLUMN_TINTS],
+                ..dark
+            }),
+            _ => None,
+        }
+    }
+
+    /// 無色之主題，僅用粗體、下劃線等修飾。
+    pub fn monochrome() -> Self {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        Self {
+            title: bold,
+            focused_border: bold,
+            border: Style::default(),
+            header: bold.add_modifier(Modifier::UNDERLINED),
+            selected: Style::default().add_modifier(Modifier::REVERSED),
+            error: bold,
+            warning: bold,
+            status: Style::default(),
+            dim: Style::default(),
+            key: bold,
+            literal_suffix: Style::default(),
+            matched: Style::default().add_modifier(Modifier::UNDERLINED),
+            added: bold,
+            removed: Style::default().add_modifier(Modifier::CROSSED_OUT),
+            marked: bold.add_modifier(Modifier::ITALIC),
+            suspicious: Style::default().add_modifier(Modifier::REVERSED),
+            iri: Style::default(),
+            literal: Style::default(),
+            blank: Style::default(),
+            column_tints: [Style::default(); COLUMN_TINTS],
+            monochrome: true,
+        }
+    }
+
+    /// 覆蓋一樣式之前景色；名以 `_bg` 結尾則為背景色。
+    pub fn set_color(&mut self, name: &str, color: Color) -> Result<(), String> {
+        let (name, background) = match name.strip_suffix("_bg") {
+            Some(name) => (name, true),
+            None => (name, false),
+        };
+        let style = match name {
+            "title" => &mut self.title,
+            "focused_border" => &mut self.focused_border,
+            "border" => &mut self.border,
+            "header" => &mut self.header,
+            "selected" => &mut self.selected,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "status" => &mut self.status,
+            "dim" => &mut self.dim,
+            "key" => &mut self.key,
+            "literal_suffix" => &mut self.literal_suffix,
+            "matched" => &mut self.matched,
+            "added" => &mut self.added,
+            "removed" => &mut self.removed,
+            "marked" => &mut self.marked,
+            "suspicious" => &mut self.suspicious,
+            "iri" => &mut self.iri,
+            "literal" => &mut self.literal,
+            "blank" => &mut self.blank,
+            "column_1" => &mut self.column_tints[0],
+            "column_2" => &mut self.column_tints[1],
+            "column_3" => &mut self.column_tints[2],
+            "column_4" => &mut self.column_tints[3],
+            _ => {
+                return Err(format!(
+                    "unknown style `{name}` (valid styles: {})",
+                    STYLES.join(", ")
+                ))
+            }
+        };
+        *style = if background {
+            // 有背景色則反色無謂
+            style.bg(color).remove_modifier(Modifier::REVERSED)
+        } else {
+            style.fg(color)
+        };
+        Ok(())
+    }
+
+    /// 依終端之色彩能力降級所有顏色。
+    pub fn degrade(mut self, support: ColorSupport) -> Self {
+        for style in [
+            &mut self.title,
+            &mut self.focused_border,
+            &mut self.border,
+            &mut self.header,
+            &mut self.selected,
+            &mut self.error,
+            &mut self.warning,
+            &mut self.status,
+            &mut self.dim,
+            &mut self.key,
+            &mut self.literal_suffix,
+            &mut self.matched,
+            &mut self.added,
+            &mut self.removed,
+            &mut self.marked,
+            &mut self.suspicious,
+            &mut self.iri,
+            &mut self.literal,
+            &mut self.blank,
+        ] {
+            style.fg = style.fg.map(|color| support.degrade(color));
+            style.bg = style.bg.map(|color| support.degrade(color));
+        }
+        // 十六色中無淡色，近者過濃，故去之
+        for style in &mut self.column_tints {
+            style.bg = match support {
+                ColorSupport::Ansi16 => None,
+                support => style.bg.map(|color| support.degrade(color)),
+            };
+        }
+        self
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset("dark").expect("dark theme")
+    }
+}
+
+/// 解析顏色：名稱（如 `red`、`lightblue`）、`#rrggbb` 或 0–255 之索引。
+pub fn parse_color(text: &str) -> Option<Color> {
+    if let Some(hex) = text.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        return Some(Color::Rgb(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ));
+    }
+    if let Ok(index) = text.parse() {
+        return Some(Color::Indexed(index));
+    }
+    Some(
+        match text
+            .to_ascii_lowercase()
+            .replace(['-', '_', ' '], "")
+            .as_str()
+        {
+            "reset" | "default" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "darkgray" | "darkgrey" => Color::DarkGray,
+            "lightred" => Color::LightRed,
+            "lightgreen" => Color::LightGreen,
+            "lightyellow" => Color::LightYellow,
+            "lightblue" => Color::LightBlue,
+            "lightmagenta" => Color::LightMagenta,
+            "lightcyan" => Color::LightCyan,
+            "white" => Color::White,
+            _ => return None,
+        },
+    )
+}
+
+/// 終端之色彩能力。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+/// 十六色之近似 RGB，用以求最近色。
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+impl ColorSupport {
+    /// 由 `COLORTERM` 與 `TERM` 推斷。
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            Self::TrueColor
+        } else if term.contains("256") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// 將顏色降至此能力所能顯示者。
+    pub fn degrade(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::TrueColor, color) => color,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_256(r, g, b)),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => nearest_16(r, g, b),
+            (Self::Ansi16, Color::Indexed(index)) => match index {
+                0..=15 => ANSI16[index as usize].0,
+                _ => {
+                    let (r, g, b) = indexed_to_rgb(index);
+                    nearest_16(r, g, b)
+                }
+            },
+            (_, color) => color,
+        }
+    }
+}
+
+/// 取 256 色中 6×6×6 色塊或灰階之最近者。
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| {
+        if v < 48 {
+            0
+        } else {
+            ((v as u16 - 35) / 40) as u8
+        }
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let gray = (r as u16 + g as u16 + b as u16) / 3;
+    let gray_index = if gray < 8 {
+        16
+    } else if gray > 238 {
+        231
+    } else {
+        232 + ((gray - 8) / 10) as u8
+    };
+    let distance = |index: u8| {
+        let (cr, cg, cb) = indexed_to_rgb(index);
+        squared_distance((r, g, b), (cr, cg, cb))
+    };
+    if distance(gray_index) < distance(cube) {
+        gray_index
+    } else {
+        cube
+    }
+}
+
+/// 256 色索引之 RGB。
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn nearest_16(r: u8, g: u8, b: u8) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, rgb)| squared_distance((r, g, b), *rgb))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+fn squared_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
@@ -0,0 +1,64 @@
+/// 大字之行數。
+pub const HEIGHT: usize = 5;
+
+/// 一字之形，每行三格。
+fn glyph(ch: char) -> Option<[&'static str; HEIGHT]> {
+    Some(match ch {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => [" █ ", "██ ", " █ ", " █ ", "███"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", " ██", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        '-' => ["   ", "   ", "███", "   ", "   "],
+        '.' => ["   ", "   ", "   ", "   ", " █ "],
+        ',' => ["   ", "   ", "   ", " █ ", "█  "],
+        '%' => ["█ █", "  █", " █ ", "█  ", "█ █"],
+        ' ' => ["   ", "   ", "   ", "   ", "   "],
+        _ => return None,
+    })
+}
+
+/// 以大字書之，字間一空。有無形之字者，或寬逾 `width`，則無。
+pub fn big(text: &str, width: usize) -> Option<Vec<String>> {
+    let glyphs = text.chars().map(glyph).collect::<Option<Vec<_>>>()?;
+    if glyphs.is_empty() || glyphs.len() * 4 - 1 > width {
+        return None;
+    }
+    Some(
+        (0..HEIGHT)
+            .map(|row| {
+                glyphs
+                    .iter()
+                    .map(|glyph| glyph[row])
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_drawn_in_block_digits() {
+        assert_eq!(
+            big("1,0", 20).unwrap(),
+            [
+                " █      ███",
+                "██      █ █",
+                " █      █ █",
+                " █   █  █ █",
+                "███ █   ███"
+            ]
+        );
+        assert!(big("1,0", 10).is_none());
+        assert!(big("abc", 80).is_none());
+    }
+}
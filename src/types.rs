@@ -0,0 +1,228 @@
+use crate::display::{compact_iri, group_digits};
+use oxigraph::model::{vocab::xsd, Term};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// 一格之類別。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    Iri,
+    Blank,
+    Triple,
+    /// 帶語言標籤之字面量。
+    Lang(String),
+    /// 他種字面量，以其數據類型。
+    Typed(String),
+}
+
+impl Kind {
+    fn of(term: &Term) -> Self {
+        match term {
+            Term::NamedNode(_) => Self::Iri,
+            Term::BlankNode(_) => Self::Blank,
+            Term::Triple(_) => Self::Triple,
+            Term::Literal(literal) => match literal.language() {
+                Some(language) => Self::Lang(language.to_string()),
+                None => Self::Typed(literal.datatype().as_str().to_string()),
+            },
+        }
+    }
+
+    /// 不另分配而比較。逐格計數時用之。
+    fn matches(&self, term: &Term) -> bool {
+        match (self, term) {
+            (Self::Iri, Term::NamedNode(_))
+            | (Self::Blank, Term::BlankNode(_))
+            | (Self::Triple, Term::Triple(_)) => true,
+            (Self::Lang(language), Term::Literal(literal)) => {
+                literal.language() == Some(language.as_str())
+            }
+            (Self::Typed(datatype), Term::Literal(literal)) => {
+                literal.language().is_none() && literal.datatype().as_str() == datatype
+            }
+            _ => false,
+        }
+    }
+
+    fn is_resource(&self) -> bool {
+        matches!(self, Self::Iri | Self::Blank | Self::Triple)
+    }
+
+    /// 如 `IRI`、`xsd:integer`、`string`、`@en`。
+    pub fn label(&self, prefixes: &[(String, String)]) -> String {
+        match self {
+            Self::Iri => "IRI".to_string(),
+            Self::Blank => "blank node".to_string(),
+            Self::Triple => "quoted triple".to_string(),
+            Self::Lang(language) => format!("@{language}"),
+            Self::Typed(datatype) if datatype == xsd::STRING.as_str() => "string".to_string(),
+            Self::Typed(datatype) => compact_iri(datatype, prefixes)
+                .or_else(|| Some(format!("xsd:{}", datatype.strip_prefix(XSD)?)))
+                .unwrap_or_else(|| format!("<{datatype}>")),
+        }
+    }
+}
+
+/// 一列中各類別之格數，隨行之到達而增。
+#[derive(Debug, Clone, Default)]
+pub struct ColumnTypes {
+    /// 各類別及其格數，先見者居前。
+    pub kinds: Vec<(Kind, usize)>,
+    pub unbound: usize,
+}
+
+impl ColumnTypes {
+    pub fn add(&mut self, term: Option<&Term>) {
+        let Some(term) = term else {
+            self.unbound += 1;
+            return;
+        };
+        match self.kinds.iter_mut().find(|(kind, _)| kind.matches(term)) {
+            Some((_, count)) => *count += 1,
+            None => self.kinds.push((Kind::of(term), 1)),
+        }
+    }
+
+    fn bound(&self) -> usize {
+        self.kinds.iter().map(|(_, count)| count).sum()
+    }
+
+    /// 兼有資源與字面量，多為查詢之誤。
+    pub fn mixes_resources_and_literals(&self) -> bool {
+        let resources = self
+            .kinds
+            .iter()
+            .filter(|(kind, _)| kind.is_resource())
+            .count();
+        resources > 0 && resources < self.kinds.len()
+    }
+
+    /// 各類別，多者居前。
+    pub fn breakdown(&self) -> Vec<(Kind, usize)> {
+        let mut kinds = self.kinds.clone();
+        kinds.sort_by(|(_, a), (_, b)| b.cmp(a));
+        kinds
+    }
+
+    /// 表尾所示之概要：`IRI (100%)`、`xsd:integer (94%), string (6%)`、`literal@en/@de`，
+    /// 逾兩類則 `mixed`。各語言之字面量合為一類。
+    pub fn summary(&self, prefixes: &[(String, String)]) -> String {
+        let bound = self.bound();
+        if bound == 0 {
+            return match self.unbound {
+                0 => String::new(),
+                _ => "unbound".to_string(),
+            };
+        }
+        let breakdown = self.breakdown();
+        let languages: Vec<String> = breakdown
+            .iter()
+            .filter(|(kind, _)| matches!(kind, Kind::Lang(_)))
+            .map(|(kind, _)| kind.label(prefixes))
+            .collect();
+        let lang_count: usize = breakdown
+            .iter()
+            .filter(|(kind, _)| matches!(kind, Kind::Lang(_)))
+            .map(|(_, count)| count)
+            .sum();
+        let mut groups: Vec<(String, usize)> = breakdown
+            .iter()
+            .filter(|(kind, _)| !matches!(kind, Kind::Lang(_)))
+            .map(|(kind, count)| (kind.label(prefixes), *count))
+            .collect();
+        if !languages.is_empty() {
+            groups.push((format!("literal{}", languages.join("/")), lang_count));
+            groups.sort_by(|(_, a), (_, b)| b.cmp(a));
+        }
+        match groups.as_slice() {
+            [(label, _)] if !languages.is_empty() => label.clone(),
+            [_] | [_, _] => groups
+                .iter()
+                .map(|(label, count)| format!("{label} ({})", percent(*count, bound)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => "mixed".to_string(),
+        }
+    }
+
+    /// 浮窗之各行：類別、格數與比例，及未綁定之數。
+    pub fn lines(&self, prefixes: &[(String, String)]) -> Vec<(String, String)> {
+        let bound = self.bound();
+        let mut lines: Vec<(String, String)> = self
+            .breakdown()
+            .into_iter()
+            .map(|(kind, count)| {
+                let share = percent(count, bound);
+                (
+                    kind.label(prefixes),
+                    format!("{} ({share})", group_digits(count)),
+                )
+            })
+            .collect();
+        lines.push(("unbound".to_string(), group_digits(self.unbound)));
+        lines
+    }
+}
+
+/// 四捨五入之百分比；不足一者示 `<1%`，不全者不作 `100%`。
+fn percent(count: usize, total: usize) -> String {
+    let share = (count * 100 + total / 2) / total.max(1);
+    match share {
+        0 => "<1%".to_string(),
+        100 if count < total => ">99%".to_string(),
+        share => format!("{share}%"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, NamedNode};
+
+    fn types(terms: &[Term]) -> ColumnTypes {
+        let mut types = ColumnTypes::default();
+        for term in terms {
+            types.add(Some(term));
+        }
+        types
+    }
+
+    #[test]
+    fn columns_summarize_their_kinds() {
+        let iri = Term::from(NamedNode::new_unchecked("http://ex/a"));
+        let integer = Term::from(Literal::from(1));
+        let string = Term::from(Literal::new_simple_literal("a"));
+        let english = Term::from(Literal::new_language_tagged_literal_unchecked("a", "en"));
+        let german = Term::from(Literal::new_language_tagged_literal_unchecked("a", "de"));
+
+        let resources = types(&[iri.clone(), iri.clone()]);
+        assert_eq!(resources.summary(&[]), "IRI (100%)");
+        assert!(!resources.mixes_resources_and_literals());
+
+        let mut numbers = vec![integer.clone(); 15];
+        numbers.push(string.clone());
+        assert_eq!(
+            types(&numbers).summary(&[]),
+            "xsd:integer (94%), string (6%)"
+        );
+
+        let labels = types(&[english.clone(), english, german]);
+        assert_eq!(labels.summary(&[]), "literal@en/@de");
+
+        let mixed = types(&[iri.clone(), integer, string]);
+        assert_eq!(mixed.summary(&[]), "mixed");
+        assert!(mixed.mixes_resources_and_literals());
+
+        let mut sparse = types(&[iri]);
+        sparse.add(None);
+        assert_eq!(
+            sparse.lines(&[]),
+            [
+                ("IRI".to_string(), "1 (100%)".to_string()),
+                ("unbound".to_string(), "1".to_string())
+            ]
+        );
+        assert_eq!(percent(1, 1000), "<1%");
+        assert_eq!(percent(999, 1000), ">99%");
+    }
+}
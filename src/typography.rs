@@ -0,0 +1,175 @@
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+/// 粘貼自聊天、文檔與網頁之可疑字符之類。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// 彎引號與撇號，代以 ASCII 之引號。
+    Quote,
+    /// 不斷行空格等非 ASCII 之空白，代以空格。
+    Space,
+    /// 零寬與軟連字符，刪之。
+    Invisible,
+}
+
+/// 字符之類及其 ASCII 之代替。SPARQL 之語法僅識 ASCII 之引號與空白，查詢中有此等字符多為誤粘。
+pub fn kind(ch: char) -> Option<(Kind, &'static str)> {
+    Some(match ch {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => (Kind::Quote, "'"),
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => (Kind::Quote, "\""),
+        '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => {
+            (Kind::Space, " ")
+        }
+        '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}' => {
+            (Kind::Invisible, "")
+        }
+        _ => return None,
+    })
+}
+
+/// 各類可疑字符之數。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub quotes: usize,
+    pub spaces: usize,
+    pub invisible: usize,
+}
+
+impl Counts {
+    pub fn of(text: &str) -> Self {
+        let mut counts = Self::default();
+        for ch in text.chars() {
+            counts.add(ch);
+        }
+        counts
+    }
+
+    fn add(&mut self, ch: char) {
+        match kind(ch) {
+            Some((Kind::Quote, _)) => self.quotes += 1,
+            Some((Kind::Space, _)) => self.spaces += 1,
+            Some((Kind::Invisible, _)) => self.invisible += 1,
+            None => {}
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.quotes + self.spaces + self.invisible
+    }
+
+    /// 如 `2 curly quotes, 1 non-ASCII space and 1 invisible character`。
+    pub fn describe(&self) -> String {
+        let parts: Vec<String> = [
+            (self.quotes, "curly quote"),
+            (self.spaces, "non-ASCII space"),
+            (self.invisible, "invisible character"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, noun)| match count {
+            1 => format!("1 {noun}"),
+            count => format!("{count} {noun}s"),
+        })
+        .collect();
+        match parts.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} and {last}", rest.join(", ")),
+            None => "no unusual characters".to_string(),
+        }
+    }
+}
+
+/// 代以 ASCII，返回所得及所代之數。字串字面量中者亦代之，故僅於用戶要求時行之。
+pub fn normalize(text: &str) -> (String, Counts) {
+    let mut normalized = String::with_capacity(text.len());
+    let mut counts = Counts::default();
+    for ch in text.chars() {
+        match kind(ch) {
+            Some((_, replacement)) => {
+                counts.add(ch);
+                normalized.push_str(replacement);
+            }
+            None => normalized.push(ch),
+        }
+    }
+    (normalized, counts)
+}
+
+/// 編輯器中一行之顯示：可疑之字符以 `style` 標之，不可見者示其碼位如 `⟨U+200B⟩`。
+pub fn mark(line: &str, style: Style) -> Line<'static> {
+    let mut spans = vec![];
+    let mut plain = String::new();
+    for ch in line.chars() {
+        let Some((kind, _)) = kind(ch) else {
+            plain.push(ch);
+            continue;
+        };
+        if !plain.is_empty() {
+            spans.push(Span::raw(std::mem::take(&mut plain)));
+        }
+        let shown = match kind {
+            Kind::Invisible => format!("⟨U+{:04X}⟩", ch as u32),
+            // 空白標為 `·`，否則不見其所在
+            Kind::Space => "·".to_string(),
+            Kind::Quote => ch.to_string(),
+        };
+        spans.push(Span::styled(shown, style));
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Modifier;
+
+    #[test]
+    fn pasted_typography_is_counted_marked_and_normalized() {
+        let pasted = "SELECT * { ?s rdfs:label \u{201C}Caf\u{00E9}\u{201D}@fr\u{00A0}}\u{200B}";
+        let counts = Counts::of(pasted);
+        assert_eq!(
+            counts,
+            Counts {
+                quotes: 2,
+                spaces: 1,
+                invisible: 1
+            }
+        );
+        assert_eq!(
+            counts.describe(),
+            "2 curly quotes, 1 non-ASCII space and 1 invisible character"
+        );
+        assert_eq!(Counts::of("ASK {}").describe(), "no unusual characters");
+
+        let (normalized, replaced) = normalize(pasted);
+        assert_eq!(normalized, "SELECT * { ?s rdfs:label \"Caf\u{00E9}\"@fr }");
+        assert_eq!(replaced, counts);
+        // 已規範者不變
+        assert_eq!(
+            normalize(&normalized),
+            (normalized.clone(), Counts::default())
+        );
+
+        let style = Style::new().add_modifier(Modifier::REVERSED);
+        let line = mark("a\u{2019}b\u{FEFF}", style);
+        let shown: Vec<(&str, Style)> = line
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                ("a", Style::new()),
+                ("\u{2019}", style),
+                ("b", Style::new()),
+                ("⟨U+FEFF⟩", style)
+            ]
+        );
+    }
+}
@@ -1,27 +1,355 @@
+use crate::{
+    background::{self, Rgb},
+    contain, signal,
+};
 use ratatui::{
     backend::CrosstermBackend,
     crossterm::{
-        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-        ExecutableCommand,
+        cursor::Show,
+        event::{
+            DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+            KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+        },
+        execute,
+        style::Print,
+        terminal::{
+            disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+            LeaveAlternateScreen, SetTitle,
+        },
     },
-    Terminal,
+    Terminal, TerminalOptions, Viewport,
+};
+use std::{
+    env,
+    io::{self, stderr, stdout, BufWriter, Stderr, Stdout, Write},
+    sync::atomic::{AtomicBool, Ordering},
 };
-use std::io::{self, stdout, Stdout};
 
-/// 初始化終端。
-pub fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
-    stdout().execute(EnterAlternateScreen)?;
-    enable_raw_mode()?;
-    // 於 panic 時恢復終端
+/// 是否以內聯模式運行，即不用備用屏幕。恢復終端時據此決定是否離開備用屏幕。
+static INLINE: AtomicBool = AtomicBool::new(false);
+
+/// 是否已設置窗口標題。恢復終端時據此還原原標題。
+static TITLE: AtomicBool = AtomicBool::new(false);
+
+/// 是否進入了備用屏幕。
+static ALTERNATE: AtomicBool = AtomicBool::new(false);
+
+/// 終端是否識標題、剪貼板、鼠標等轉義序列。
+static ESCAPES: AtomicBool = AtomicBool::new(true);
+
+/// 是否已壓入 kitty 鍵盤協議之標誌。留之則用戶之 shell 失常，故恢復終端時必彈出。
+static KEYBOARD: AtomicBool = AtomicBool::new(false);
+
+/// 界面是否繪於標準錯誤，標準輸出留予退出時所寫之結果。
+static STDERR: AtomicBool = AtomicBool::new(false);
+
+/// 界面所繪之處：標準輸出，或 [`setup_terminal`] 所選之標準錯誤。
+pub enum Screen {
+    Stdout(Stdout),
+    /// 標準錯誤無緩衝，一幀之諸轉義須合寫之。
+    Stderr(BufWriter<Stderr>),
+}
+
+impl Screen {
+    fn get() -> Self {
+        match STDERR.load(Ordering::SeqCst) {
+            true => Self::Stderr(BufWriter::new(stderr())),
+            false => Self::Stdout(stdout()),
+        }
+    }
+}
+
+impl Write for Screen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(out) => out.write(buf),
+            Self::Stderr(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(out) => out.flush(),
+            Self::Stderr(out) => out.flush(),
+        }
+    }
+}
+
+/// 終端之能力，由 `TERM` 粗略推斷。不識之轉義或直寫入串口之日誌，故寧缺毋濫。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub colors: bool,
+    pub alternate_screen: bool,
+    /// 標題（OSC 0/2）、剪貼板（OSC 52）、鼠標與括號粘貼之開關。
+    pub escapes: bool,
+}
+
+impl Capabilities {
+    pub fn detect() -> Self {
+        match env::var("TERM").unwrap_or_default().as_str() {
+            "" | "dumb" | "unknown" => Self::dumb(),
+            // 串口與硬件終端：無色，無備用屏幕
+            term if term.starts_with("vt") && !term.contains("color") => Self {
+                colors: false,
+                alternate_screen: false,
+                escapes: false,
+            },
+            _ => Self {
+                colors: true,
+                alternate_screen: true,
+                escapes: true,
+            },
+        }
+    }
+
+    /// 終端是否答 kitty 鍵盤協議之查詢。須於原始模式中問之；不識者不答，查詢自會逾時。
+    pub fn keyboard_enhancement(&self) -> bool {
+        self.escapes && supports_keyboard_enhancement().unwrap_or(false)
+    }
+
+    /// 僅設原始模式與光標移動，不用顏色與他種轉義。
+    pub fn dumb() -> Self {
+        Self {
+            colors: false,
+            alternate_screen: false,
+            escapes: false,
+        }
+    }
+}
+
+/// 終端之守衛。持有期間終端處於原始模式與備用屏幕，析構時恢復。
+/// 故 `main` 中任何提前返回皆不會令終端失常。
+pub struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Screen>>,
+    background: Option<Rgb>,
+}
+
+impl TerminalGuard {
+    pub fn terminal(&mut self) -> &mut Terminal<CrosstermBackend<Screen>> {
+        &mut self.terminal
+    }
+
+    /// 掛起至 shell。先恢復終端再停止進程；繼續後重入原始模式與備用屏幕，並全屏重繪。
+    pub fn suspend(&mut self) -> io::Result<()> {
+        let enhanced = KEYBOARD.load(Ordering::SeqCst);
+        restore_terminal();
+        signal::stop();
+        enable_raw_mode()?;
+        if ALTERNATE.load(Ordering::SeqCst) {
+            execute!(self.terminal.backend_mut(), EnterAlternateScreen)?;
+        }
+        if ESCAPES.load(Ordering::SeqCst) {
+            execute!(self.terminal.backend_mut(), EnableBracketedPaste)?;
+        }
+        if enhanced {
+            enhance_keyboard(self.terminal.backend_mut())?;
+        }
+        self.terminal.clear()
+    }
+
+    /// 啓動時終端所報之背景色。不答或不識轉義者為 `None`。
+    pub fn background(&self) -> Option<Rgb> {
+        self.background
+    }
+
+    /// 終端是否以 kitty 協議報告按鍵，即 `ctrl-enter` 等可與單鍵區分。
+    pub fn keyboard_enhanced(&self) -> bool {
+        KEYBOARD.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// 初始化終端。`inline` 給定則於光標下方佔此行數繪製，不進入備用屏幕；
+/// 終端無備用屏幕則於原屏繪製。`on_stderr` 則繪於標準錯誤。
+pub fn setup_terminal(
+    inline: Option<u16>,
+    capabilities: Capabilities,
+    on_stderr: bool,
+) -> io::Result<TerminalGuard> {
+    STDERR.store(on_stderr, Ordering::SeqCst);
+    INLINE.store(inline.is_some(), Ordering::SeqCst);
+    ALTERNATE.store(
+        inline.is_none() && capabilities.alternate_screen,
+        Ordering::SeqCst,
+    );
+    ESCAPES.store(capabilities.escapes, Ordering::SeqCst);
+    // 於 panic 時先記日誌，再恢復終端。繪製一部件時者止於該部件，終端照舊
     std::panic::set_hook(Box::new(|info| {
+        if contain::handle(info) {
+            return;
+        }
+        if log::log_enabled!(log::Level::Error) {
+            let backtrace = std::backtrace::Backtrace::force_capture();
+            log::error!("panic: {info}\n{backtrace}");
+            log::logger().flush();
+        }
         restore_terminal();
         eprintln!("{}", info);
     }));
-    ratatui::Terminal::new(CrosstermBackend::new(stdout()))
+    enable_raw_mode()?;
+    // 背景色之答須於他處讀輸入之前讀盡，否則混入按鍵
+    let background = match capabilities.escapes {
+        true => background::query(background::TIMEOUT),
+        false => None,
+    };
+    let viewport = match inline {
+        Some(height) => Viewport::Inline(height),
+        None => Viewport::Fullscreen,
+    };
+    // 內聯視口須查光標位置，失敗亦恢復終端
+    let terminal = Terminal::with_options(
+        CrosstermBackend::new(Screen::get()),
+        TerminalOptions { viewport },
+    )
+    .inspect_err(|_| restore_terminal())?;
+    // 自此之後若出錯，守衛析構時恢復終端
+    let mut guard = TerminalGuard {
+        terminal,
+        background,
+    };
+    if ALTERNATE.load(Ordering::SeqCst) {
+        execute!(guard.terminal.backend_mut(), EnterAlternateScreen)?;
+    }
+    // 粘貼之文本整塊送來，不逐字譯為按鍵，換行亦不觸發鍵位
+    if capabilities.escapes {
+        execute!(guard.terminal.backend_mut(), EnableBracketedPaste)?;
+    }
+    if capabilities.keyboard_enhancement() {
+        enhance_keyboard(guard.terminal.backend_mut())?;
+    }
+    Ok(guard)
+}
+
+/// 請終端區分 `ctrl-enter`、`ctrl-/` 等與單鍵。
+fn enhance_keyboard(out: &mut impl Write) -> io::Result<()> {
+    execute!(
+        out,
+        PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+    )?;
+    KEYBOARD.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// 設置終端窗口標題。首次設置前將原標題壓棧，恢復終端時彈出。
+/// 內聯模式下終端非本程序獨佔，終端不識轉義者亦不設。
+pub fn set_title(out: &mut impl Write, title: &str) -> io::Result<()> {
+    if INLINE.load(Ordering::SeqCst) || !ESCAPES.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    if !TITLE.swap(true, Ordering::SeqCst) {
+        // XTWINOPS：壓入窗口與圖標標題
+        execute!(out, Print("\x1b[22;0t"))?;
+    }
+    execute!(out, SetTitle(title))
+}
+
+/// 以 OSC 52 請終端將文字置於剪貼板。終端不支持則無效，亦無從得知。
+pub fn copy_to_clipboard(out: &mut impl Write, text: &str) -> io::Result<()> {
+    if !ESCAPES.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    execute!(
+        out,
+        Print(format!("\x1b]52;c;{}\x07", base64(text.as_bytes())))
+    )
+}
+
+/// Base64 之字母表。
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// 標準之 Base64，有填充。
+pub fn base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
 }
 
-/// 恢復終端。
+/// 解 [`base64`] 所寫者。填充可略，空白略之；URL 之 `-` 與 `_` 亦可。有他字則 `None`。
+pub fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut n, mut bits) = (0u32, 0);
+    for ch in text.bytes().filter(|ch| !ch.is_ascii_whitespace()) {
+        let value = match ch {
+            b'-' => 62,
+            b'_' => 63,
+            b'=' => continue,
+            ch => ALPHABET.iter().position(|&known| known == ch)? as u32,
+        };
+        n = n << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((n >> bits) as u8);
+            n &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// 盡力恢復終端。逐步執行，忽略各步之錯誤，以免一步失敗而餘步不行。
+/// 守衛、panic 鈎子與信號處理皆用之。
 pub fn restore_terminal() {
-    stdout().execute(LeaveAlternateScreen).unwrap();
-    disable_raw_mode().unwrap();
+    let mut screen = Screen::get();
+    if KEYBOARD.swap(false, Ordering::SeqCst) {
+        let _ = execute!(screen, PopKeyboardEnhancementFlags);
+    }
+    if ESCAPES.load(Ordering::SeqCst) {
+        let _ = execute!(screen, DisableMouseCapture);
+        let _ = execute!(screen, DisableBracketedPaste);
+    }
+    if ALTERNATE.load(Ordering::SeqCst) {
+        let _ = execute!(screen, LeaveAlternateScreen);
+    }
+    let _ = execute!(screen, Show);
+    // 先清空標題，終端不支持標題棧者亦不留殘跡
+    if TITLE.swap(false, Ordering::SeqCst) {
+        let _ = execute!(screen, SetTitle(""), Print("\x1b[23;0t"));
+    }
+    let _ = disable_raw_mode();
+}
+
+/// 長為 `len` 之列表中，自 `index` 移動 `delta` 所至之處，夾於首末。
+pub fn offset_index(index: usize, delta: isize, len: usize) -> usize {
+    index
+        .saturating_add_signed(delta)
+        .min(len.saturating_sub(1))
+}
+
+/// 諸候選之公共前綴，至少為 `current`。
+pub fn common_prefix(current: &str, candidates: &[String]) -> String {
+    let Some(first) = candidates.first() else {
+        return current.to_string();
+    };
+    let mut prefix = first.as_str();
+    for candidate in &candidates[1..] {
+        let len = prefix
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8());
+        prefix = &prefix[..len];
+    }
+    if prefix.len() > current.len() {
+        prefix.to_string()
+    } else {
+        current.to_string()
+    }
 }
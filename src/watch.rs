@@ -0,0 +1,300 @@
+use crate::{diff::Partition, display::group_digits, generation::Generation, worker::TermRow};
+use regex::Regex;
+use spargebra::{algebra::GraphPattern, Query};
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+/// 間隔之下限，以免查詢不停。
+pub const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 指紋所取之行數。
+const SAMPLE: usize = 64;
+
+/// 定時重新執行某查詢。前一次未完則不再發，故不致堆積。
+#[derive(Debug)]
+pub struct Watch {
+    pub interval: Duration,
+    /// 所監視之查詢。查詢被改則暫停。
+    pub query: String,
+    pub paused: bool,
+    /// 暫停後是否已問過。
+    pub asked: bool,
+    /// 上次刷新完成之時，自 Unix 紀元起之秒數。
+    pub refreshed_at: Option<u64>,
+    /// 與前一結果相較之增減。
+    pub diff: Option<Diff>,
+    /// 下次刷新之時，自上次完成起算。
+    pub due: Instant,
+    /// 上次之指紋，見 [`fingerprint_query`]。
+    pub fingerprint: Option<String>,
+    /// 所執行者為指紋查詢，非原查詢。
+    pub probing: bool,
+    /// 指紋已變，下次即重新執行原查詢。
+    pub changed: bool,
+    /// 上次到期時結果未變，未重新執行。
+    pub skipped: bool,
+    /// 指紋查詢曾失敗，如端點不支持 MD5，此後逕重新執行。
+    pub unprobed: bool,
+    /// 此次刷新發出時存儲之代數。
+    pub started: Generation,
+}
+
+impl Watch {
+    pub fn new(query: String, interval: Duration) -> Self {
+        Self {
+            interval,
+            query,
+            paused: false,
+            asked: false,
+            refreshed_at: None,
+            diff: None,
+            due: Instant::now() + interval,
+            fingerprint: None,
+            probing: false,
+            changed: false,
+            skipped: false,
+            unprobed: false,
+            started: Generation::default(),
+        }
+    }
+
+    /// 是否到期。
+    pub fn is_due(&self) -> bool {
+        !self.paused && Instant::now() >= self.due
+    }
+
+    /// 一次刷新已完成，自此再計間隔。
+    pub fn refreshed(&mut self, at: u64, diff: Option<Diff>) {
+        self.refreshed_at = Some(at);
+        self.diff = diff;
+        self.skipped = false;
+        self.due = Instant::now() + self.interval;
+    }
+
+    /// 結果未變，此次不重新執行。
+    pub fn skip(&mut self, at: u64) {
+        self.refreshed_at = Some(at);
+        self.diff = None;
+        self.skipped = true;
+        self.due = Instant::now() + self.interval;
+    }
+}
+
+/// 同一存儲上重新執行必得同一結果否。含 `NOW()`、`RAND()` 或新造之項者每次皆異。
+pub fn deterministic(query: &str) -> bool {
+    static VOLATILE: OnceLock<Regex> = OnceLock::new();
+    !VOLATILE
+        .get_or_init(|| {
+            Regex::new(r"(?i)\b(NOW|RAND|UUID|STRUUID|BNODE)\s*\(")
+                .unwrap_or_else(|_| unreachable!())
+        })
+        .is_match(query)
+}
+
+/// 查詢含 SERVICE 否。其結果隨遠端而變，存儲之代數不足以知之。
+pub fn remote(query: &str) -> bool {
+    static SERVICE: OnceLock<Regex> = OnceLock::new();
+    SERVICE
+        .get_or_init(|| Regex::new(r"(?i)(^|[^?$\w])SERVICE\b").unwrap_or_else(|_| unreachable!()))
+        .is_match(query)
+}
+
+/// 廉價之指紋查詢：SELECT 查詢之總行數，及其前 `SAMPLE` 行之 MD5。所返之一行與上次同者，
+/// 視結果未變。前若干行之序若每次不同，則僅多一次重新執行。非 SELECT 者為 `None`。
+pub fn fingerprint_query(query: &str) -> Option<String> {
+    let Query::Select {
+        dataset, pattern, ..
+    } = Query::parse(query, None).ok()?
+    else {
+        return None;
+    };
+    let mut inner = &pattern;
+    let variables = loop {
+        match inner {
+            GraphPattern::Project { variables, .. } => break variables,
+            GraphPattern::Slice { inner: next, .. }
+            | GraphPattern::Distinct { inner: next }
+            | GraphPattern::Reduced { inner: next }
+            | GraphPattern::OrderBy { inner: next, .. } => inner = next,
+            _ => return None,
+        }
+    };
+    // 未綁定與空串有別，各值之間以單元分隔符相隔
+    let row = variables
+        .iter()
+        .map(|variable| format!("COALESCE(CONCAT(\"=\", STR({variable})), \"\")"))
+        .collect::<Vec<_>>()
+        .join(", \"\\u001F\", ");
+    let dataset = dataset.map_or_else(String::new, |dataset| format!("{dataset} "));
+    Some(format!(
+        "SELECT ?count ?sample {dataset}WHERE {{ \
+         {{ SELECT (COUNT(*) AS ?count) WHERE {{ {pattern} }} }} \
+         {{ SELECT (MD5(GROUP_CONCAT(?row; SEPARATOR=\"\\n\")) AS ?sample) WHERE {{ \
+         {{ SELECT * WHERE {{ {pattern} }} LIMIT {SAMPLE} }} BIND(CONCAT(\"\", {row}) AS ?row) }} }} }}"
+    ))
+}
+
+/// 指紋查詢所返之一行。
+pub fn fingerprint(rows: &[TermRow]) -> Option<String> {
+    let row = rows.first()?;
+    Some(
+        row.iter()
+            .map(|term| {
+                term.as_ref()
+                    .map_or_else(String::new, |term| term.to_string())
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// 前後兩結果之行數增減。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Diff {
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl Diff {
+    pub fn between(old: &[TermRow], new: &[TermRow]) -> Self {
+        let partition = Partition::between(old, new);
+        Self {
+            added: partition.added.len(),
+            removed: partition.removed.len(),
+        }
+    }
+
+    /// 如 `+12 rows, −3 rows`；無變則為 `no changes`。
+    pub fn badge(&self) -> String {
+        if self.added == 0 && self.removed == 0 {
+            return "no changes".to_string();
+        }
+        let rows = |n: usize| if n == 1 { "row" } else { "rows" };
+        format!(
+            "+{} {}, −{} {}",
+            group_digits(self.added),
+            rows(self.added),
+            group_digits(self.removed),
+            rows(self.removed)
+        )
+    }
+}
+
+/// 解析間隔，如 `30s`、`5m`、`1h`；無單位者為秒。
+pub fn parse_interval(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Not an interval: {text}"))?;
+    let seconds = match unit {
+        "" | "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        _ => return Err(format!("Unknown unit `{unit}` (use s, m or h)")),
+    };
+    let interval = Duration::from_secs(seconds);
+    if interval < MIN_INTERVAL {
+        return Err("The interval must be at least 1s".to_string());
+    }
+    Ok(interval)
+}
+
+/// 間隔之寫法，如 `30s`、`5m`。
+pub fn describe_interval(interval: Duration) -> String {
+    match interval.as_secs() {
+        seconds if seconds % 3600 == 0 => format!("{}h", seconds / 3600),
+        seconds if seconds % 60 == 0 => format!("{}m", seconds / 60),
+        seconds => format!("{seconds}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, Term};
+    use std::sync::Arc;
+
+    #[test]
+    fn intervals_take_units() {
+        assert_eq!(parse_interval("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_interval("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_interval(" 2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_interval("10"), Ok(Duration::from_secs(10)));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("5d").is_err());
+        assert!(parse_interval("s").is_err());
+        assert_eq!(describe_interval(Duration::from_secs(90)), "90s");
+        assert_eq!(describe_interval(Duration::from_secs(120)), "2m");
+    }
+
+    #[test]
+    fn fingerprints_change_with_the_results() {
+        use oxigraph::{io::RdfFormat, sparql::QueryResults, store::Store};
+
+        let store = Store::new().unwrap();
+        let load = |turtle: &str| {
+            store
+                .load_from_read(RdfFormat::Turtle, turtle.as_bytes())
+                .unwrap()
+        };
+        load("<http://ex/a> <http://ex/p> 1 . <http://ex/b> <http://ex/p> \"\" .");
+        let query = "PREFIX ex: <http://ex/> SELECT ?s ?o ?missing WHERE { ?s ex:p ?o } LIMIT 10";
+        let fingerprint = || {
+            let QueryResults::Solutions(solutions) =
+                store.query(&fingerprint_query(query).unwrap()).unwrap()
+            else {
+                unreachable!()
+            };
+            let rows: Vec<TermRow> = solutions
+                .map(|solution| {
+                    let solution = solution.unwrap();
+                    solution
+                        .values()
+                        .iter()
+                        .map(|term| term.clone().map(Arc::new))
+                        .collect()
+                })
+                .collect();
+            fingerprint(&rows).unwrap()
+        };
+        let before = fingerprint();
+        assert!(before.starts_with("\"2\"^^"));
+        assert_eq!(fingerprint(), before);
+        load("<http://ex/a> <http://ex/q> 2 .");
+        assert_eq!(fingerprint(), before);
+        load("<http://ex/c> <http://ex/p> 3 .");
+        assert_ne!(fingerprint(), before);
+
+        assert_eq!(fingerprint_query("ASK { ?s ?p ?o }"), None);
+        assert_eq!(fingerprint_query("SELECT"), None);
+        assert!(deterministic("SELECT * WHERE { ?s ?p ?o }"));
+        assert!(!deterministic("SELECT (now() AS ?t) {}"));
+        assert!(remote(
+            "SELECT * { service <http://ex/sparql> { ?s ?p ?o } }"
+        ));
+        assert!(!remote("SELECT ?service { ?service ?p ?o }"));
+    }
+
+    #[test]
+    fn diffs_count_rows_as_multisets() {
+        let row = |n: i64| vec![Some(Arc::new(Term::from(Literal::from(n))))];
+        let old = [row(1), row(2), row(2), row(3)];
+        let new = [row(2), row(3), row(4), row(5)];
+        let diff = Diff::between(&old, &new);
+        assert_eq!(
+            diff,
+            Diff {
+                added: 2,
+                removed: 2
+            }
+        );
+        assert_eq!(diff.badge(), "+2 rows, −2 rows");
+        assert_eq!(Diff::between(&new, &new).badge(), "no changes");
+    }
+}
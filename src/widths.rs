@@ -0,0 +1,260 @@
+use std::ops::Range;
+
+/// 取樣量寬之缺省行數。
+pub const DEFAULT_SAMPLE: usize = 1000;
+
+/// 各列依內容之寬度，隨結果緩存。量表頭、前若干行之取樣與曾可見之行；只增不減，
+/// 故行陸續送回時列不跳動。
+#[derive(Debug, Clone, Default)]
+pub struct ColumnWidths {
+    widths: Vec<usize>,
+    /// 已量之取樣行數。
+    sampled: usize,
+}
+
+impl ColumnWidths {
+    pub fn widths(&self) -> &[usize] {
+        &self.widths
+    }
+
+    /// 尚未量之取樣行：前 `sample` 行中 `sampled` 之後、已送回者。
+    pub fn unsampled(&self, rows: usize, sample: usize) -> Range<usize> {
+        self.sampled..rows.min(sample).max(self.sampled)
+    }
+
+    /// 記取樣已量至第 `end` 行。
+    pub fn sampled_to(&mut self, end: usize) {
+        self.sampled = self.sampled.max(end);
+    }
+
+    /// 以一行各格之寬放寬各列。
+    pub fn widen(&mut self, row: impl IntoIterator<Item = usize>) {
+        for (column, width) in row.into_iter().enumerate() {
+            match self.widths.get_mut(column) {
+                Some(current) => *current = (*current).max(width),
+                None => self.widths.push(width),
+            }
+        }
+    }
+}
+
+/// 分 `available` 格予各列，列間各隔 `spacing`。容得下則各得其寬，餘者均分；
+/// 否則窄於均分者得其寬，餘者按寬之比分之。終端縮放時僅重算此步。
+pub fn allocate(widths: &[usize], available: u16, spacing: u16) -> Vec<u16> {
+    let columns = widths.len();
+    if columns == 0 {
+        return vec![];
+    }
+    let gaps = spacing as usize * (columns - 1);
+    let available = (available as usize).saturating_sub(gaps);
+    let widths: Vec<usize> = widths.iter().map(|width| (*width).max(1)).collect();
+    let total: usize = widths.iter().sum();
+    if total <= available {
+        let extra = available - total;
+        return widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| {
+                let share = extra / columns + usize::from(column < extra % columns);
+                (width + share) as u16
+            })
+            .collect();
+    }
+    let mut allocated = vec![0; columns];
+    let mut open: Vec<usize> = (0..columns).collect();
+    let mut remaining = available;
+    loop {
+        let share = remaining / open.len();
+        let (narrow, wide): (Vec<usize>, Vec<usize>) =
+            open.iter().partition(|&&column| widths[column] <= share);
+        if narrow.is_empty() {
+            let total: usize = wide.iter().map(|&column| widths[column]).sum();
+            for &column in &wide {
+                allocated[column] = remaining * widths[column] / total;
+            }
+            break;
+        }
+        for column in narrow {
+            allocated[column] = widths[column];
+            remaining -= widths[column];
+        }
+        if wide.is_empty() {
+            break;
+        }
+        open = wide;
+    }
+    allocated.into_iter().map(|width| width as u16).collect()
+}
+
+/// 可讀之列至少之寬。列多而不能各得此寬者，僅示其一段，隨所選之列左右移。
+pub const MIN_COLUMN: usize = 8;
+
+/// 寬 `available` 之表一次所示之列：自 `offset` 起，各列需其寬而至多 `MIN_COLUMN`，能容者
+/// 皆示之。所選之列在其左則自之起，在其右則移至其為末列。至少示一列。
+pub fn window(
+    widths: &[usize],
+    available: u16,
+    spacing: u16,
+    offset: usize,
+    selected: Option<usize>,
+) -> Range<usize> {
+    let need = |column: usize| widths[column].clamp(1, MIN_COLUMN);
+    let fits = |columns: Range<usize>| {
+        let gaps = spacing as usize * columns.len().saturating_sub(1);
+        columns.map(need).sum::<usize>() + gaps <= available as usize
+    };
+    let columns = widths.len();
+    let mut start = offset.min(columns.saturating_sub(1));
+    if let Some(selected) = selected.filter(|&selected| selected < columns) {
+        start = start.min(selected);
+        while start < selected && !fits(start..selected + 1) {
+            start += 1;
+        }
+    }
+    let mut end = (start + 1).min(columns);
+    while end < columns && fits(start..end + 1) {
+        end += 1;
+    }
+    // 右端已盡而左有餘處，則左移以填之
+    while start > 0 && fits(start - 1..end) {
+        start -= 1;
+    }
+    start..end
+}
+
+/// 右側預覽至少之寬，不足則不示。
+const MIN_SIDE_PREVIEW: u16 = 30;
+
+/// 表與右側預覽之間隔。
+const SIDE_GAP: u16 = 2;
+
+/// 表於結果窗格中之位置：左空、表寬與右側預覽之寬（零則無）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub indent: u16,
+    pub table: u16,
+    pub preview: u16,
+}
+
+/// 排佈寬 `available` 之窗格，表之內容需寬 `content`。窗格不逾 `max` 則表佔滿之，如常；
+/// 否則表寬取內容之寬而不逾 `max`，餘處足以容預覽且 `preview` 則右示預覽，寬亦不逾 `max`，
+/// 表與預覽合而居中。`max` 為零則不限。
+pub fn place(content: u16, available: u16, max: u16, preview: bool) -> Placement {
+    if max == 0 || available <= max {
+        return Placement {
+            indent: 0,
+            table: available,
+            preview: 0,
+        };
+    }
+    let table = content.clamp(1, max);
+    let spare = available - table;
+    let side = match preview && spare >= MIN_SIDE_PREVIEW + SIDE_GAP {
+        true => (spare - SIDE_GAP).min(max),
+        false => 0,
+    };
+    let used = table + if side > 0 { SIDE_GAP + side } else { 0 };
+    Placement {
+        indent: (available - used) / 2,
+        table,
+        preview: side,
+    }
+}
+
+impl Placement {
+    /// 右側預覽之起處，自窗格左緣計。
+    pub fn preview_offset(&self) -> u16 {
+        self.indent + self.table + SIDE_GAP
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_panes_center_a_capped_table_beside_a_preview() {
+        let placement = |available| place(60, available, 160, true);
+        // 常寬之終端如舊
+        for available in [76, 160] {
+            assert_eq!(
+                placement(available),
+                Placement {
+                    indent: 0,
+                    table: available,
+                    preview: 0
+                }
+            );
+        }
+        // 餘處不足容預覽，則表居中
+        assert_eq!(
+            place(150, 176, 160, true),
+            Placement {
+                indent: 13,
+                table: 150,
+                preview: 0
+            }
+        );
+        assert_eq!(
+            placement(200),
+            Placement {
+                indent: 0,
+                table: 60,
+                preview: 138
+            }
+        );
+        // 預覽亦不逾上限，合而居中
+        let wide = placement(396);
+        assert_eq!(
+            wide,
+            Placement {
+                indent: 87,
+                table: 60,
+                preview: 160
+            }
+        );
+        assert_eq!(wide.preview_offset(), 149);
+        assert_eq!(place(400, 396, 160, false).table, 160);
+        assert_eq!(place(60, 396, 160, false).indent, 168);
+        assert_eq!(place(60, 396, 0, true).table, 396);
+    }
+
+    #[test]
+    fn many_columns_show_a_window_that_follows_the_selection() {
+        // 一列、五列及五十列，於窄與寬之終端
+        assert_eq!(window(&[30], 40, 1, 0, None), 0..1);
+        assert_eq!(window(&[30], 200, 1, 0, Some(0)), 0..1);
+        assert_eq!(window(&[30; 5], 40, 1, 0, None), 0..4);
+        assert_eq!(window(&[30; 5], 200, 1, 0, None), 0..5);
+        assert_eq!(window(&[2; 5], 40, 1, 0, None), 0..5);
+        assert_eq!(window(&[20; 50], 40, 1, 0, None), 0..4);
+        assert_eq!(window(&[20; 50], 200, 1, 0, None), 0..22);
+        assert_eq!(window(&[2; 50], 200, 1, 0, None), 0..50);
+        // 所選之列恆可見，另一端盡處不留空
+        assert_eq!(window(&[20; 50], 40, 1, 0, Some(10)), 7..11);
+        assert_eq!(window(&[20; 50], 40, 1, 7, Some(8)), 7..11);
+        assert_eq!(window(&[20; 50], 40, 1, 7, Some(3)), 3..7);
+        assert_eq!(window(&[20; 50], 40, 1, 40, Some(49)), 46..50);
+        assert_eq!(window(&[20; 50], 200, 1, 0, Some(49)), 28..50);
+        assert_eq!(window(&[20; 5], 40, 1, 3, None), 1..5);
+        // 過窄者仍示一列
+        assert_eq!(window(&[20; 3], 4, 1, 0, Some(2)), 2..3);
+        assert_eq!(window(&[], 40, 1, 0, None), 0..0);
+    }
+
+    #[test]
+    fn narrow_columns_keep_their_width() {
+        assert_eq!(allocate(&[2, 4], 10, 1), [4, 5]);
+        assert_eq!(allocate(&[3, 40, 20], 32, 1), [3, 18, 9]);
+        assert_eq!(allocate(&[50, 50], 21, 1), [10, 10]);
+        assert_eq!(allocate(&[], 80, 1), Vec::<u16>::new());
+
+        let mut widths = ColumnWidths::default();
+        widths.widen([2, 2]);
+        assert_eq!(widths.unsampled(5, 3), 0..3);
+        widths.widen([1, 7]);
+        widths.sampled_to(3);
+        assert_eq!(widths.widths(), [2, 7]);
+        assert_eq!(widths.unsampled(10, 3), 3..3);
+    }
+}
@@ -0,0 +1,503 @@
+use crate::{
+    dataset::Dataset,
+    display::group_digits,
+    dupes::{Dupes, MAX_CLUSTERS},
+    functional::Functional,
+    intern::Interner,
+    memory::{self, abbreviate},
+    pattern::Pattern,
+    prepared::Prepared,
+    solutions::{self, CancelToken, QueryOptions},
+};
+use oxigraph::{
+    model::{Term, Variable},
+    sparql::{EvaluationError, QuerySolution},
+    store::Store,
+};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, SyncSender, TryRecvError, TrySendError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// 每批送回之行數，界面跟不上時漸增至 `MAX_BATCH`。
+const BATCH: usize = 256;
+const MAX_BATCH: usize = 16 * 1024;
+/// 行來得慢時，不足一批亦每隔此時送回。
+const FLUSH: Duration = Duration::from_millis(100);
+/// 兩批之最短間隔，約每秒三十批。
+const THROTTLE: Duration = Duration::from_millis(33);
+/// 未取之消息數上限。滿則後台線程等待，故界面忙時記憶體不隨結果膨脹。
+const CAPACITY: usize = 8;
+/// 等待時查看取消之間隔。
+const WAIT: Duration = Duration::from_millis(1);
+
+/// 一行之項。未綁定者為 `None`。
+pub type TermRow = Vec<Option<Arc<Term>>>;
+
+/// 後台查詢送回之消息。首條為 `Started`（或先有 `Note`），末條為 `Finished` 或 `Failed`。
+pub enum Message {
+    /// 須告知用戶者，如結果有所略去。
+    Note(String),
+    /// SELECT 之變量；他種查詢則為 `None`，其後無行。
+    Started(Option<Vec<Variable>>),
+    /// 一批行，及迄今各項之估計字節數。
+    Rows(Vec<TermRow>, usize),
+    /// 已取盡，或因上限而截斷。
+    Finished {
+        truncated: Option<Truncation>,
+    },
+    Failed(String),
+}
+
+/// 結果截斷之因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truncation {
+    /// 行數上限。
+    Rows(usize),
+    /// 格數上限。
+    Cells(usize),
+    /// 諸項與行之估計字節數之上限。
+    Bytes(usize),
+}
+
+impl fmt::Display for Truncation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rows(rows) => write!(f, "at {} rows", group_digits(*rows)),
+            Self::Cells(cells) => write!(f, "at {} cells", group_digits(*cells)),
+            Self::Bytes(bytes) => write!(f, "at {} of cell data", abbreviate(*bytes)),
+        }
+    }
+}
+
+/// 行數之外之物化上限，先至者截之。導出另行查詢，不受之。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Caps {
+    /// 行數乘列數。
+    pub cells: Option<usize>,
+    /// 所共用之項及諸行之估計字節數，同於內存之統計。
+    pub bytes: Option<usize>,
+}
+
+impl Caps {
+    /// 已有 `rows` 行 `width` 列，其項共 `term_bytes` 字節，再收一行則逾上限者其因。
+    fn reached(self, rows: usize, width: usize, term_bytes: usize) -> Option<Truncation> {
+        if let Some(cells) = self.cells.filter(|&cells| (rows + 1) * width > cells) {
+            return Some(Truncation::Cells(cells));
+        }
+        let bytes = term_bytes + memory::rows(rows, rows, width);
+        self.bytes
+            .filter(|&cap| bytes >= cap)
+            .map(Truncation::Bytes)
+    }
+}
+
+/// 所需之查詢設置。
+pub struct Job {
+    pub query: String,
+    pub federated: bool,
+    pub timeout: Duration,
+    pub limit: Option<usize>,
+    pub caps: Caps,
+    /// 解析所經之緩存。
+    pub prepared: Prepared,
+    /// 默認圖之所指。
+    pub dataset: Dataset,
+}
+
+/// 後台執行中之查詢。丟棄即取消。
+pub struct Worker {
+    receiver: Receiver<Message>,
+    cancelled: CancelToken,
+    /// 此次查詢發出之遠端請求數。
+    requests: Arc<AtomicUsize>,
+    started: Instant,
+}
+
+impl Worker {
+    /// 於新線程執行查詢，經 [`solutions::run`]，如庫之所為。存儲為快照，其後之載入不影響之。
+    pub fn spawn(store: &Store, job: Job) -> Self {
+        let store = store.clone();
+        Self::start(None, job.caps, move |stream, requests| {
+            let options = QueryOptions {
+                timeout: None,
+                remote_timeout: job.timeout,
+                cancel: stream.cancelled.clone(),
+                limit: job.limit,
+                federated: job.federated,
+            };
+            let results = job
+                .prepared
+                .get(&job.query, None)
+                .map_err(|error| EvaluationError::from(error).into())
+                .and_then(|mut query| {
+                    job.dataset.apply(&mut query);
+                    solutions::run(&store, query, options, requests)
+                });
+            let mut solutions = match results {
+                Ok(solutions) => solutions,
+                Err(error) => {
+                    stream.send(Message::Failed(error.to_string()));
+                    return;
+                }
+            };
+            let Some(variables) = solutions.variables().map(<[Variable]>::to_vec) else {
+                stream.send(Message::Started(None));
+                stream.send(Message::Finished { truncated: None });
+                return;
+            };
+            let rows = solutions
+                .by_ref()
+                .map(|solution| solution.map_err(|error| error.to_string()));
+            if stream.batches(variables, rows, QuerySolution::values) {
+                stream.send(Message::Finished {
+                    truncated: solutions
+                        .is_truncated()
+                        .then(|| Truncation::Rows(job.limit.unwrap_or_default())),
+                });
+            }
+        })
+    }
+
+    /// 於新線程列出默認圖中合於三元組模式者，不經 SPARQL。
+    pub fn spawn_pattern(
+        store: &Store,
+        pattern: Pattern,
+        limit: Option<usize>,
+        caps: Caps,
+    ) -> Self {
+        let store = store.clone();
+        Self::start(limit, caps, move |stream, _| {
+            let rows = pattern.rows(&store).map(|row| {
+                row.map(|terms| terms.map(Some))
+                    .map_err(|error| error.to_string())
+            });
+            stream.rows(Pattern::variables(), rows, |row| row.as_slice());
+        })
+    }
+
+    /// 於新線程查找共用同一字面量值之主語，每簇相連。
+    pub fn spawn_dupes(store: &Store, dupes: Dupes, limit: Option<usize>, caps: Caps) -> Self {
+        let store = store.clone();
+        Self::start(limit, caps, move |stream, _| match dupes.clusters(&store) {
+            Ok(clusters) => {
+                if clusters.truncated {
+                    stream.send(Message::Note(format!(
+                        "Showing the first {MAX_CLUSTERS} clusters only"
+                    )));
+                }
+                let rows = clusters.rows.into_iter().map(|row| Ok(row.map(Some)));
+                stream.rows(Dupes::variables(), rows, |row| row.as_slice());
+            }
+            Err(error) => {
+                stream.send(Message::Failed(error.to_string()));
+            }
+        })
+    }
+
+    /// 於新線程檢查一函數性屬性，有多值之主語各一行。
+    pub fn spawn_functional(
+        store: &Store,
+        functional: Functional,
+        limit: Option<usize>,
+        caps: Caps,
+    ) -> Self {
+        let store = store.clone();
+        Self::start(limit, caps, move |stream, _| {
+            match functional.conflicts(&store) {
+                Ok(conflicts) => {
+                    let rows = conflicts.rows.into_iter().map(Ok);
+                    stream.rows(conflicts.variables, rows, Vec::as_slice);
+                }
+                Err(error) => {
+                    stream.send(Message::Failed(error.to_string()));
+                }
+            }
+        })
+    }
+
+    fn start(
+        limit: Option<usize>,
+        caps: Caps,
+        produce: impl FnOnce(&Stream, Arc<AtomicUsize>) + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(CAPACITY);
+        let cancelled = CancelToken::new();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let stream = Stream {
+            sender,
+            cancelled: cancelled.clone(),
+            limit,
+            caps,
+        };
+        let context = Arc::clone(&requests);
+        thread::spawn(move || produce(&stream, context));
+        Self {
+            receiver,
+            cancelled,
+            requests,
+            started: Instant::now(),
+        }
+    }
+
+    /// 取已到之消息，不等待。線程意外終止則為 `Failed`；取消後則無。
+    pub fn try_recv(&self) -> Option<Message> {
+        match self.receiver.try_recv() {
+            Ok(message) => Some(message),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) if self.cancelled.is_cancelled() => None,
+            Err(TryRecvError::Disconnected) => Some(self.stopped()),
+        }
+    }
+
+    /// 等待下一消息。
+    pub fn recv(&self) -> Message {
+        self.receiver.recv().unwrap_or_else(|_| self.stopped())
+    }
+
+    fn stopped(&self) -> Message {
+        if self.cancelled.is_cancelled() {
+            Message::Finished { truncated: None }
+        } else {
+            Message::Failed("query stopped unexpectedly".to_string())
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.cancel();
+    }
+
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// 後台線程送回消息之端。
+struct Stream {
+    sender: SyncSender<Message>,
+    cancelled: CancelToken,
+    limit: Option<usize>,
+    caps: Caps,
+}
+
+/// 一消息如何送出。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sent {
+    At(Pressure),
+    /// 接收端已去，或已取消。
+    Gone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pressure {
+    /// 即時送出。
+    Low,
+    /// 等界面取走方送出。
+    High,
+}
+
+impl Stream {
+    /// 接收端已去或已取消則為假。
+    fn send(&self, message: Message) -> bool {
+        self.offer(message) != Sent::Gone
+    }
+
+    /// 送出，槽滿則等待，至取消而止。
+    fn offer(&self, mut message: Message) -> Sent {
+        let mut pressure = Pressure::Low;
+        loop {
+            match self.sender.try_send(message) {
+                Ok(()) => return Sent::At(pressure),
+                Err(TrySendError::Disconnected(_)) => return Sent::Gone,
+                Err(TrySendError::Full(returned)) => {
+                    if self.cancelled.is_cancelled() {
+                        return Sent::Gone;
+                    }
+                    message = returned;
+                    pressure = Pressure::High;
+                    thread::sleep(WAIT);
+                }
+            }
+        }
+    }
+
+    /// 逐批送回各行而終之，見 [`Stream::batches`]。
+    fn rows<R>(
+        &self,
+        variables: Vec<Variable>,
+        rows: impl Iterator<Item = Result<R, String>>,
+        values: impl Fn(&R) -> &[Option<Term>],
+    ) {
+        if self.batches(variables, rows, values) {
+            self.send(Message::Finished { truncated: None });
+        }
+    }
+
+    /// 逐批送回各行，至多行數上限及 [`Caps`]，至取消而止。`values` 取一行按變量之序之項。
+    /// 每批至少隔 `THROTTLE`；須等界面者，下批加倍，即時送出者減半。取盡而未終者為真，
+    /// 待呼者送 `Finished`。
+    fn batches<R>(
+        &self,
+        variables: Vec<Variable>,
+        rows: impl Iterator<Item = Result<R, String>>,
+        values: impl Fn(&R) -> &[Option<Term>],
+    ) -> bool {
+        let width = variables.len();
+        if !self.send(Message::Started(Some(variables))) {
+            return false;
+        }
+        let mut interner = Interner::default();
+        let mut size = BATCH;
+        let mut batch = Vec::with_capacity(size);
+        let mut sent = Instant::now();
+        for (count, row) in rows.enumerate() {
+            if self.cancelled.is_cancelled() {
+                return false;
+            }
+            let truncated = match self.limit {
+                Some(limit) if count >= limit => Some(Truncation::Rows(limit)),
+                _ => self.caps.reached(count, width, interner.bytes()),
+            };
+            if truncated.is_some() {
+                self.send(Message::Rows(batch, interner.bytes()));
+                self.send(Message::Finished { truncated });
+                return false;
+            }
+            let row = match row {
+                Ok(row) => row,
+                Err(error) => {
+                    self.send(Message::Rows(batch, interner.bytes()));
+                    self.send(Message::Failed(error));
+                    return false;
+                }
+            };
+            let values = values(&row);
+            batch.push(
+                (0..width)
+                    .map(|i| values.get(i)?.as_ref().map(|term| interner.intern(term)))
+                    .collect(),
+            );
+            let elapsed = sent.elapsed();
+            if (batch.len() >= size && elapsed >= THROTTLE)
+                || elapsed >= FLUSH
+                || batch.len() >= MAX_BATCH
+            {
+                let full = std::mem::replace(&mut batch, Vec::with_capacity(size));
+                size = match self.offer(Message::Rows(full, interner.bytes())) {
+                    Sent::Gone => return false,
+                    Sent::At(Pressure::High) => (size * 2).min(MAX_BATCH),
+                    Sent::At(Pressure::Low) => (size / 2).max(BATCH),
+                };
+                sent = Instant::now();
+            }
+        }
+        !self.cancelled.is_cancelled() && self.send(Message::Rows(batch, interner.bytes()))
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::Literal;
+
+    #[test]
+    fn a_slow_consumer_bounds_the_rows_in_flight() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&produced);
+        let worker = Worker::start(None, Caps::default(), move |stream, _| {
+            let rows = (0..).map(|n: i64| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                Ok(vec![Some(Term::from(Literal::from(n)))])
+            });
+            stream.rows(vec![Variable::new_unchecked("n")], rows, |row| {
+                row.as_slice()
+            });
+        });
+        let (mut received, mut batches) = (0, 0);
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(500) {
+            if let Some(Message::Rows(rows, _)) = worker.try_recv() {
+                received += rows.len();
+                batches += 1;
+            }
+            thread::sleep(Duration::from_millis(20));
+            let in_flight = produced.load(Ordering::Relaxed) - received;
+            assert!(
+                in_flight <= (CAPACITY + 1) * MAX_BATCH,
+                "{in_flight} rows in flight"
+            );
+        }
+        assert!(batches > 0);
+        // 界面跟不上，故批漸大
+        assert!(received > batches * BATCH);
+    }
+
+    #[test]
+    fn rows_stop_at_the_first_cap_reached_with_its_reason() {
+        let run = |limit: Option<usize>, caps: Caps| {
+            let worker = Worker::start(limit, caps, move |stream, _| {
+                // 每行二格，其一為千字節之字面量
+                let rows = (0..1000).map(|n: i64| {
+                    Ok(vec![
+                        Some(Term::from(Literal::from(n))),
+                        Some(Term::from(Literal::new_simple_literal(format!(
+                            "{n:01000}"
+                        )))),
+                    ])
+                });
+                let variables = vec![Variable::new_unchecked("n"), Variable::new_unchecked("s")];
+                stream.rows(variables, rows, |row| row.as_slice());
+            });
+            let mut received = 0;
+            loop {
+                match worker.recv() {
+                    Message::Rows(rows, _) => received += rows.len(),
+                    Message::Finished { truncated } => return (received, truncated),
+                    Message::Failed(error) => panic!("{error}"),
+                    _ => {}
+                }
+            }
+        };
+        assert_eq!(run(None, Caps::default()), (1000, None));
+        assert_eq!(
+            run(Some(10), Caps::default()),
+            (10, Some(Truncation::Rows(10)))
+        );
+        let cells = Caps {
+            cells: Some(50),
+            bytes: None,
+        };
+        assert_eq!(run(Some(100), cells), (25, Some(Truncation::Cells(50))));
+        let bytes = Caps {
+            cells: Some(1 << 20),
+            bytes: Some(100 << 10),
+        };
+        let (rows, truncated) = run(None, bytes);
+        assert_eq!(truncated, Some(Truncation::Bytes(100 << 10)));
+        assert!((80..100).contains(&rows), "{rows} rows");
+        assert_eq!(
+            Truncation::Bytes(120 << 20).to_string(),
+            "at 120M of cell data"
+        );
+        assert_eq!(
+            Truncation::Cells(1_000_000).to_string(),
+            "at 1 000 000 cells"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! 複製所選之格之諸形式：IRI、CURIE、標籤、詞法值、N-Triples，或整行。
+
+use crate::{
+    action::Values,
+    display::compact_iri,
+    export::{plain_row, plain_value, write_record},
+};
+use oxigraph::model::{vocab::xsd, Literal, Term};
+
+/// 所複製之形式。`y` 後一鍵選之，`y y` 為配置之默認者。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Yank {
+    /// IRI 本身，不加尖括號。空節點與字面量無之。
+    Iri,
+    /// 以前綴縮寫之 IRI，無相符者加尖括號；字面量如 Turtle 之 `"42"^^xsd:integer`；
+    /// 空節點如 `_:b0`。皆可貼入查詢。
+    Curie,
+    /// IRI 之標籤；字面量即其值。空節點無之。
+    Label,
+    /// 字面量之詞法形式，不含語言與數據類型；IRI 之串；空節點之標籤。同於平值之導出。
+    Value,
+    /// 整行，以製表符分隔諸平值，未綁定者為空。
+    Row,
+    /// 項之 N-Triples 形式。
+    #[default]
+    NTriples,
+}
+
+impl Yank {
+    /// 諸形式，依菜單之序。
+    pub const ALL: [Self; 6] = [
+        Self::Iri,
+        Self::Curie,
+        Self::Label,
+        Self::Value,
+        Self::Row,
+        Self::NTriples,
+    ];
+
+    /// 菜單中選之之鍵。
+    pub fn key(self) -> char {
+        match self {
+            Self::Iri => 'i',
+            Self::Curie => 'c',
+            Self::Label => 'l',
+            Self::Value => 'v',
+            Self::Row => 'r',
+            Self::NTriples => 'n',
+        }
+    }
+
+    /// 配置與命令中之名。
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Iri => "iri",
+            Self::Curie => "curie",
+            Self::Label => "label",
+            Self::Value => "value",
+            Self::Row => "row",
+            Self::NTriples => "ntriples",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "nt" => Some(Self::NTriples),
+            name => Self::ALL.into_iter().find(|yank| yank.name() == name),
+        }
+    }
+
+    pub fn from_key(key: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|yank| yank.key() == key)
+    }
+
+    /// 狀態欄所示之菜單，如 `Yank: i iri · c curie … · y ntriples`。
+    pub fn menu(default: Self) -> String {
+        let items: Vec<String> = Self::ALL
+            .iter()
+            .map(|yank| format!("{} {}", yank.key(), yank.name()))
+            .collect();
+        format!("Yank: {} · y {}", items.join(" · "), default.name())
+    }
+}
+
+/// 項之此形式。`label` 為 IRI 之標籤，僅 `Label` 用之。不可得則返回所報者。`Row` 見 [`row`]。
+pub fn term(
+    yank: Yank,
+    term: &Term,
+    prefixes: &[(String, String)],
+    label: Option<String>,
+) -> Result<String, &'static str> {
+    match (yank, term) {
+        (Yank::Iri, Term::NamedNode(iri)) => Ok(iri.as_str().to_string()),
+        (Yank::Iri, Term::Literal(_)) => Err("A literal has no IRI; v copies its value"),
+        (Yank::Iri, _) => Err("Only IRIs have an IRI; n copies the term"),
+        (Yank::Curie, Term::NamedNode(iri)) => Ok(curie(iri.as_str(), prefixes)),
+        (Yank::Curie, Term::Literal(literal)) => {
+            let quoted = Literal::new_simple_literal(literal.value()).to_string();
+            Ok(match literal.language() {
+                Some(language) => format!("{quoted}@{language}"),
+                None if literal.datatype() == xsd::STRING => quoted,
+                None => format!("{quoted}^^{}", curie(literal.datatype().as_str(), prefixes)),
+            })
+        }
+        (Yank::Curie, term) => Ok(term.to_string()),
+        (Yank::Label, Term::NamedNode(_)) => label.ok_or("The IRI has no label"),
+        (Yank::Label, Term::Literal(literal)) => Ok(literal.value().to_string()),
+        (Yank::Label, _) => Err("Only IRIs and literals have labels"),
+        (Yank::Value, term) => Ok(plain_value(term)),
+        (Yank::Row | Yank::NTriples, term) => Ok(term.to_string()),
+    }
+}
+
+/// 整行之平值，以製表符分隔，轉義同 TSV 之導出，無行末之換行。
+pub fn row(row: &[Option<Term>]) -> String {
+    let mut line = vec![];
+    write_record(&mut line, &plain_row(row, Values::Plain), '\t').expect("write to a vector");
+    line.pop();
+    String::from_utf8(line).expect("cells are UTF-8")
+}
+
+fn curie(iri: &str, prefixes: &[(String, String)]) -> String {
+    compact_iri(iri, prefixes).unwrap_or_else(|| format!("<{iri}>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{BlankNode, NamedNode};
+
+    #[test]
+    fn each_form_fits_each_kind_of_term() {
+        let prefixes = [("ex".to_string(), "http://ex/".to_string())];
+        let iri = Term::from(NamedNode::new_unchecked("http://ex/a"));
+        let other = Term::from(NamedNode::new_unchecked("http://other/b"));
+        let typed = Term::from(Literal::new_typed_literal("4\"2", xsd::INTEGER));
+        let tagged = Term::from(Literal::new_language_tagged_literal_unchecked("chat", "fr"));
+        let blank = Term::from(BlankNode::new_unchecked("b0"));
+        let yank = |yank, term, label: Option<&str>| {
+            super::term(yank, term, &prefixes, label.map(str::to_string))
+        };
+
+        assert_eq!(yank(Yank::Iri, &iri, None), Ok("http://ex/a".to_string()));
+        assert!(yank(Yank::Iri, &typed, None).is_err());
+        assert!(yank(Yank::Iri, &blank, None).is_err());
+
+        assert_eq!(yank(Yank::Curie, &iri, None), Ok("ex:a".to_string()));
+        assert_eq!(
+            yank(Yank::Curie, &other, None),
+            Ok("<http://other/b>".to_string())
+        );
+        assert_eq!(
+            yank(Yank::Curie, &typed, None),
+            Ok("\"4\\\"2\"^^<http://www.w3.org/2001/XMLSchema#integer>".to_string())
+        );
+        assert_eq!(
+            yank(Yank::Curie, &tagged, None),
+            Ok("\"chat\"@fr".to_string())
+        );
+        assert_eq!(yank(Yank::Curie, &blank, None), Ok("_:b0".to_string()));
+
+        assert_eq!(yank(Yank::Label, &iri, Some("A")), Ok("A".to_string()));
+        assert!(yank(Yank::Label, &iri, None).is_err());
+        assert_eq!(yank(Yank::Label, &tagged, None), Ok("chat".to_string()));
+        assert!(yank(Yank::Label, &blank, None).is_err());
+
+        assert_eq!(yank(Yank::Value, &typed, None), Ok("4\"2".to_string()));
+        assert_eq!(yank(Yank::Value, &iri, None), Ok("http://ex/a".to_string()));
+        assert_eq!(yank(Yank::Value, &blank, None), Ok("b0".to_string()));
+
+        assert_eq!(
+            yank(Yank::NTriples, &tagged, None),
+            Ok("\"chat\"@fr".to_string())
+        );
+        assert_eq!(yank(Yank::NTriples, &blank, None), Ok("_:b0".to_string()));
+
+        assert_eq!(
+            row(&[Some(iri), None, Some(Term::from(Literal::from("a\tb")))]),
+            "http://ex/a\t\ta\\tb"
+        );
+        assert_eq!(Yank::parse("nt"), Some(Yank::NTriples));
+        assert_eq!(Yank::from_key('c'), Some(Yank::Curie));
+        assert!(Yank::menu(Yank::Curie).ends_with("n ntriples · y curie"));
+    }
+}
@@ -0,0 +1,294 @@
+//! 不經界面，以命令驅動存儲線程，檢查其所送回之事件。
+
+use oxigraph::{
+    io::RdfFormat,
+    model::GraphName,
+    sparql::{results::QueryResultsFormat, QueryResults},
+    store::Store,
+};
+use rdf_tui::backend::{Command, Event, Id, Settings, StoreWorker};
+use std::{fs, path::PathBuf, time::Duration};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// 收 `id` 之事件至其終，他命令之事件略之。
+fn events(worker: &StoreWorker, id: Id) -> Vec<Event> {
+    let mut events = vec![];
+    loop {
+        let event = worker
+            .recv_timeout(Duration::from_secs(10))
+            .expect("no event within 10s");
+        if event.id() == id {
+            let last = event.is_last();
+            events.push(event);
+            if last {
+                return events;
+            }
+        }
+    }
+}
+
+fn rows(events: &[Event]) -> usize {
+    events
+        .iter()
+        .map(|event| match event {
+            Event::Rows { rows, .. } => rows.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[test]
+fn loads_advance_the_generation_seen_by_queries() {
+    let worker = StoreWorker::spawn(Store::new().unwrap(), Settings::default());
+    worker.send(Command::Load {
+        id: 1,
+        path: fixture("animals.ttl"),
+    });
+    let loaded = events(&worker, 1);
+    assert!(matches!(loaded[..], [Event::Loaded { generation: 1, .. }]));
+    assert_eq!(worker.generation(), 1);
+
+    worker.send(Command::RunQuery {
+        id: 2,
+        text: "SELECT ?s WHERE { ?s ?p ?o }".to_string(),
+    });
+    let query = events(&worker, 2);
+    assert!(matches!(
+        query[0],
+        Event::Started {
+            generation: 1,
+            variables: Some(_),
+            ..
+        }
+    ));
+    assert_eq!(rows(&query), 5);
+    assert!(matches!(
+        query.last(),
+        Some(Event::Finished {
+            truncated: false,
+            ..
+        })
+    ));
+
+    worker.send(Command::Load {
+        id: 3,
+        path: fixture("broken.ttl"),
+    });
+    assert!(matches!(events(&worker, 3)[..], [Event::Failed { .. }]));
+    assert_eq!(worker.generation(), 1);
+}
+
+#[test]
+fn read_only_stores_refuse_changes_but_not_loads() {
+    let settings = Settings {
+        read_only: true,
+        ..Settings::default()
+    };
+    let worker = StoreWorker::spawn(Store::new().unwrap(), settings);
+    worker.send(Command::Load {
+        id: 1,
+        path: fixture("animals.ttl"),
+    });
+    assert!(matches!(events(&worker, 1)[..], [Event::Loaded { .. }]));
+    worker.send(Command::Update {
+        id: 2,
+        text: "CLEAR ALL".to_string(),
+    });
+    worker.send(Command::Clear { id: 3, graph: None });
+    for id in [2, 3] {
+        match &events(&worker, id)[..] {
+            [Event::Failed { error, .. }] => assert!(error.contains("read-only")),
+            events => panic!("{events:?}"),
+        }
+    }
+    assert_eq!(worker.generation(), 1);
+    assert_eq!(worker.store().len().unwrap(), 5);
+}
+
+#[test]
+fn updates_and_clears_wait_for_the_loads_before_them() {
+    let worker = StoreWorker::spawn(Store::new().unwrap(), Settings::default());
+    let (cat, dog) = ("<http://example.org/Cat>", "<http://example.org/Dog>");
+    worker.send(Command::LoadData {
+        id: 1,
+        data: format!("{cat} a {dog} ."),
+        format: RdfFormat::Turtle,
+        number: 1,
+    });
+    worker.send(Command::Update {
+        id: 2,
+        text: format!("DELETE DATA {{ {cat} a {dog} }} ; INSERT DATA {{ {dog} a {cat} }}"),
+    });
+    match &events(&worker, 1)[..] {
+        [Event::Loaded {
+            generation: 1,
+            graphs,
+            ..
+        }] => assert_eq!(graphs[..], [(GraphName::DefaultGraph, 1)]),
+        events => panic!("{events:?}"),
+    }
+    assert!(matches!(
+        events(&worker, 2)[..],
+        [Event::Changed { generation: 2, .. }]
+    ));
+    let store = worker.store();
+    assert_eq!(store.len().unwrap(), 1);
+    assert!(store
+        .query(format!("ASK {{ {dog} a {cat} }}").as_str())
+        .is_ok_and(|results| matches!(results, QueryResults::Boolean(true))));
+
+    worker.send(Command::Update {
+        id: 3,
+        text: "DELETE WHERE".to_string(),
+    });
+    assert!(matches!(events(&worker, 3)[..], [Event::Failed { .. }]));
+    worker.send(Command::Clear { id: 4, graph: None });
+    assert!(matches!(
+        events(&worker, 4)[..],
+        [Event::Changed { generation: 3, .. }]
+    ));
+    assert!(store.is_empty().unwrap());
+}
+
+#[test]
+fn reloads_swap_in_a_new_store_only_when_everything_loads() {
+    let store = Store::new().unwrap();
+    let worker = StoreWorker::spawn(store.clone(), Settings::default());
+    let data = vec![(
+        "<http://ex/a> <http://ex/b> <http://ex/c> .".to_string(),
+        RdfFormat::Turtle,
+    )];
+    worker.send(Command::Reload {
+        id: 1,
+        data: data.clone(),
+        paths: vec![fixture("animals.ttl"), fixture("broken.ttl")],
+    });
+    match &events(&worker, 1)[..] {
+        [Event::Failed { error, .. }] => assert!(error.contains("broken.ttl")),
+        events => panic!("{events:?}"),
+    }
+    assert!(worker.store().is_empty().unwrap());
+
+    worker.send(Command::Reload {
+        id: 2,
+        data,
+        paths: vec![fixture("animals.ttl")],
+    });
+    match &events(&worker, 2)[..] {
+        [Event::Reloaded {
+            generation: 1,
+            loads,
+            ..
+        }] => {
+            let sources: Vec<&str> = loads.iter().map(|(source, _)| source.as_str()).collect();
+            assert_eq!(sources[0], "inline data #1");
+            assert!(sources[1].ends_with("animals.ttl"));
+        }
+        events => panic!("{events:?}"),
+    }
+    assert_eq!(worker.store().len().unwrap(), 6);
+    // 舊存儲不受其影響
+    assert!(store.is_empty().unwrap());
+}
+
+#[test]
+fn queries_are_cancelled_or_time_out() {
+    let settings = Settings {
+        deadline: Some(Duration::from_millis(200)),
+        ..Settings::default()
+    };
+    let worker = StoreWorker::spawn(Store::new().unwrap(), settings);
+    worker.send(Command::Load {
+        id: 1,
+        path: fixture("animals.ttl"),
+    });
+    events(&worker, 1);
+
+    // 五三元組之九重自連接，約二百萬行
+    let slow = format!(
+        "SELECT * WHERE {{ {} }}",
+        (0..9)
+            .map(|n| format!("?s{n} ?p{n} ?o{n} ."))
+            .collect::<String>()
+    );
+    worker.send(Command::RunQuery {
+        id: 2,
+        text: slow.clone(),
+    });
+    worker.send(Command::Cancel { id: 2 });
+    assert!(matches!(
+        events(&worker, 2).last(),
+        Some(Event::Cancelled { .. })
+    ));
+
+    worker.send(Command::RunQuery { id: 3, text: slow });
+    match events(&worker, 3).last() {
+        Some(Event::Failed { error, .. }) => assert!(error.contains("timed out")),
+        event => panic!("{event:?}"),
+    }
+
+    // 取消未知之號無事
+    worker.send(Command::Cancel { id: 9 });
+    worker.send(Command::RunQuery {
+        id: 4,
+        text: "SELECT".to_string(),
+    });
+    assert!(matches!(events(&worker, 4)[..], [Event::Failed { .. }]));
+}
+
+#[test]
+fn exports_write_every_row() {
+    let worker = StoreWorker::spawn(Store::new().unwrap(), Settings::default());
+    worker.send(Command::Load {
+        id: 1,
+        path: fixture("animals.ttl"),
+    });
+    events(&worker, 1);
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("backend-export.tsv");
+    worker.send(Command::Export {
+        id: 2,
+        text: "SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?s ?o".to_string(),
+        format: QueryResultsFormat::Tsv,
+        path: path.clone(),
+    });
+    assert!(matches!(
+        events(&worker, 2)[..],
+        [Event::Exported { rows: 5, .. }]
+    ));
+    let text = fs::read_to_string(&path).unwrap();
+    assert_eq!(text.lines().count(), 6);
+    assert!(text.starts_with("?s\t?o\n<http://example.org/Animal>\t\"Animal\"@en\n"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn exports_keep_existing_files_unless_told_to_overwrite() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("backend-clobber.tsv");
+    for overwrite in [false, true] {
+        fs::write(&path, "keep me").unwrap();
+        let settings = Settings {
+            overwrite,
+            ..Settings::default()
+        };
+        let worker = StoreWorker::spawn(Store::new().unwrap(), settings);
+        worker.send(Command::Export {
+            id: 1,
+            text: "SELECT * WHERE { ?s ?p ?o }".to_string(),
+            format: QueryResultsFormat::Tsv,
+            path: path.clone(),
+        });
+        match &events(&worker, 1)[..] {
+            [Event::Failed { error, .. }] if !overwrite => assert!(error.ends_with("exists")),
+            [Event::Exported { rows: 0, .. }] if overwrite => {}
+            events => panic!("{events:?}"),
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(text == "keep me", !overwrite);
+    }
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,315 @@
+//! 以管道運行可執行文件，檢查無界面時之輸出與退出碼。CI 上無終端亦可運行，兼為命令行之約定之文檔。
+
+use std::{
+    fs::{self, File},
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// 不載入文件之命令，配置與狀態皆在臨時目錄。
+fn command(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rdf-tui"));
+    command
+        .args(args)
+        .args(["--config-dir", env!("CARGO_TARGET_TMPDIR")])
+        .args(["--state-dir", env!("CARGO_TARGET_TMPDIR")])
+        .stdin(Stdio::null());
+    command
+}
+
+/// 以 `animals.ttl` 運行，stdout 與 stdin 皆非終端。
+fn run(args: &[&str]) -> Output {
+    command(&[&fixture("animals.ttl")])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// 臨時目錄中之文件。
+fn temp_file(name: &str, content: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, content).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+#[test]
+fn results_are_printed_as_tsv_with_a_summary() {
+    let output = run(&[
+        "--query",
+        "SELECT ?c WHERE { ?c <http://www.w3.org/2000/01/rdf-schema#subClassOf> ?p } ORDER BY ?c",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "?c\n<http://example.org/Cat>\n<http://example.org/Dog>\n"
+    );
+    assert!(stderr(&output).starts_with("2 rows in "));
+
+    let output = run(&[
+        "--quiet",
+        "--export-limit",
+        "1",
+        "--query",
+        "SELECT * {?s ?p ?o}",
+    ]);
+    assert_eq!(stdout(&output).lines().count(), 2);
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn plain_values_drop_the_term_syntax() {
+    let query = "SELECT ?c ?label WHERE { ?c <http://www.w3.org/2000/01/rdf-schema#label> ?label \
+                 FILTER(?c = <http://example.org/Cat>) }";
+    let output = run(&["--quiet", "--plain-values", "--query", query]);
+    assert_eq!(stdout(&output), "c\tlabel\nhttp://example.org/Cat\tCat\n");
+    let output = run(&["--quiet", "--typed-columns", "--query", query]);
+    assert_eq!(
+        stdout(&output),
+        "c\tc_type\tlabel\tlabel_type\nhttp://example.org/Cat\t\tCat\t@en\n"
+    );
+}
+
+#[test]
+fn plain_values_report_what_they_flatten_unless_quiet() {
+    let flattened = |object: &str, extra: &[&str]| {
+        let data = format!("<http://ex/a> <http://ex/b> {object} .");
+        let output = command(&["--data", &data, "--format", "ntriples", "--plain-values"])
+            .args(extra)
+            .args(["--query", "SELECT ?o { ?s ?p ?o }"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        stderr(&output)
+            .lines()
+            .find(|line| line.starts_with("Plain values flattened"))
+            .map(str::to_string)
+    };
+    assert_eq!(
+        flattened("\"42\"^^<http://www.w3.org/2001/XMLSchema#integer>", &[]).as_deref(),
+        Some("Plain values flattened 1 typed literal (--typed-columns keeps them)")
+    );
+    assert!(flattened("\"chat\"@fr", &[])
+        .unwrap()
+        .contains("1 language-tagged string"));
+    assert!(flattened("_:b0", &[]).unwrap().contains("1 blank node"));
+    assert_eq!(flattened("_:b0", &["--quiet"]), None);
+    assert_eq!(flattened("\"x\"", &[]), None);
+    assert_eq!(flattened("<http://ex/c>", &[]), None);
+}
+
+#[test]
+fn exit_codes_tell_the_outcome() {
+    let ask = |query: &str| run(&["--query", query]).status.code();
+    assert_eq!(ask("ASK { ?s ?p ?o }"), Some(0));
+    assert_eq!(ask("ASK { ?s ?p 42 }"), Some(1));
+    assert_eq!(ask("SELEKT * {}"), Some(2));
+
+    let empty = "SELECT ?s ?p { ?s ?p 42 }";
+    assert_eq!(run(&["--query", empty]).status.code(), Some(0));
+    let output = run(&["--fail-if-empty", "--query", empty]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "?s\t?p\n");
+
+    let output = run(&[&fixture("broken.ttl"), "--query", "ASK {}"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).starts_with("Error: Fail to load"));
+
+    let output = run(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--force-tui"));
+}
+
+#[test]
+fn ask_prints_its_boolean() {
+    let output = run(&["--query", "ASK { ?s ?p ?o }"]);
+    assert_eq!(stdout(&output), "true\n");
+    assert!(stderr(&output).starts_with("true in "));
+    let output = run(&["--quiet", "--query", "ASK { ?s ?p 42 }"]);
+    assert_eq!((stdout(&output), stderr(&output)), ("false\n", ""));
+}
+
+#[test]
+fn graphs_are_printed_as_n_triples() {
+    let output = run(&[
+        "--query",
+        "PREFIX ex: <http://example.org/> CONSTRUCT { ?c a ex:Class } \
+         WHERE { ?c <http://www.w3.org/2000/01/rdf-schema#subClassOf> ?p } ORDER BY ?c",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "<http://example.org/Cat> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/Class> .\n\
+         <http://example.org/Dog> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/Class> .\n"
+    );
+    assert!(stderr(&output).starts_with("2 triples in "));
+
+    // 上限亦及於三元組
+    let output = run(&[
+        "--export-limit",
+        "1",
+        "--query",
+        "DESCRIBE <http://example.org/Cat>",
+    ]);
+    assert_eq!(stdout(&output).lines().count(), 1);
+    assert!(stdout(&output).starts_with("<http://example.org/Cat> "));
+    assert!(stderr(&output).starts_with("1 triple in "));
+}
+
+#[test]
+fn inline_data_and_query_files_stand_in_for_fixtures() {
+    let query = temp_file(
+        "headless-query.rq",
+        "# the one literal\r\nSELECT ?o {\r\n  ?s ?p ?o\r\n}\r\n",
+    );
+    let output = command(&[
+        "--data",
+        "<http://ex/a> <http://ex/b> \"x\" .",
+        "--format",
+        "ntriples",
+        "--quiet",
+        "--query-file",
+        &query,
+    ])
+    .output()
+    .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "?o\n\"x\"\n");
+}
+
+#[test]
+fn stdout_redirected_to_a_file_runs_without_the_interface() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("headless-redirected.tsv");
+    let status = command(&[
+        &fixture("animals.ttl"),
+        "--quiet",
+        "--query",
+        "SELECT ?l { <http://example.org/Dog> <http://www.w3.org/2000/01/rdf-schema#label> ?l }",
+    ])
+    .stdout(File::create(&path).unwrap())
+    .status()
+    .unwrap();
+    assert_eq!(status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "?l\n\"Dog\"@en\n");
+}
+
+#[test]
+fn startup_errors_keep_their_exit_codes() {
+    let output = run(&["--query", "SELEKT * {}"]);
+    assert!(stderr(&output).starts_with("Error: Invalid query: error at 1:1"));
+    assert_eq!(stdout(&output), "");
+
+    let output = run(&["/nonexistent/data.ttl", "--query", "ASK {}"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).starts_with("Error: Fail to load /nonexistent/data.ttl: "));
+
+    let results = temp_file("headless-results.srj", "{}");
+    let output = run(&[&results, "--query", "ASK {}"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("holds query results, which cannot be queried"));
+
+    // 讀不到查詢為缺查詢，而非假之 ASK
+    let output = run(&["--query-file", "/nonexistent/query.rq"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("Error: Fail to read /nonexistent/query.rq: "));
+    assert_eq!(stderr(&output).lines().count(), 1);
+
+    let config = temp_file("headless-config.toml", "max_rows = [\n");
+    let output = run(&["--config", &config, "--query", "ASK {}"]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr(&output).starts_with("Error: "));
+}
+
+#[test]
+fn slow_queries_time_out() {
+    // 五個三元組之十二重笛卡兒積
+    let patterns: String = (0..12).map(|i| format!("?s{i} ?p{i} ?o{i} . ")).collect();
+    let query = format!("SELECT (COUNT(*) AS ?n) WHERE {{ {patterns} }}");
+    let output = run(&["--timeout", "1", "--query", &query]);
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(stderr(&output), "Error: The query took longer than 1s\n");
+}
+
+#[test]
+fn files_over_the_disk_threshold_are_not_loaded_into_memory() {
+    let output = run(&["--query", "ASK {}", "--prefer-disk-above", "100"]);
+    assert_eq!(output.status.code(), Some(3));
+    let error = stderr(&output);
+    assert!(
+        error.contains("animals.ttl is ") && error.contains("over --prefer-disk-above 100B"),
+        "{error}"
+    );
+    let output = run(&["--query", "ASK {}", "--prefer-disk-above", "1M"]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = run(&["--query", "ASK {}", "--prefer-disk-above", "lots"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn output_formats_and_sinks_take_the_results_elsewhere() {
+    let classes = "SELECT ?c WHERE { ?c <http://www.w3.org/2000/01/rdf-schema#subClassOf> ?p } \
+                   ORDER BY ?c";
+    let output = run(&["--quiet", "--output", "json", "--query", classes]);
+    assert!(stdout(&output).starts_with("{\"head\":{\"vars\":[\"c\"]}"));
+    let output = run(&[
+        "--output",
+        "json",
+        "--query",
+        "CONSTRUCT WHERE { ?s ?p ?o }",
+    ]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr(&output).contains("--output cannot write triples"));
+    let output = run(&["--output", "markdown", "--query", classes]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "| ?c                       |\n\
+         | ------------------------ |\n\
+         | <http://example.org/Cat> |\n\
+         | <http://example.org/Dog> |\n"
+    );
+
+    // 附於文件之末，二次則二表頭
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("headless-sink.csv");
+    let _ = fs::remove_file(&path);
+    let sink = format!("append:{}", path.display());
+    for _ in 0..2 {
+        let output = run(&["--output", "csv", "--sink", &sink, "--query", classes]);
+        assert_eq!((output.status.code(), stdout(&output)), (Some(0), ""));
+    }
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "c\r\nhttp://example.org/Cat\r\nhttp://example.org/Dog\r\n".repeat(2)
+    );
+
+    // 命令之輸出示於標準輸出，其失敗報其退出碼
+    let output = run(&["--sink", "cmd:wc -l", "--query", classes]);
+    assert_eq!(stdout(&output).trim(), "3");
+    let output = run(&[
+        "--sink",
+        "cmd:sh -c 'cat >/dev/null; echo no thanks >&2; exit 3'",
+        "--query",
+        classes,
+    ]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(
+        stderr(&output).contains("exited with status 3 after reading"),
+        "{}",
+        stderr(&output)
+    );
+}
@@ -0,0 +1,299 @@
+//! 以 `TestBackend` 繪製界面，檢查所示之內容。
+
+use oxigraph::{
+    model::{Literal, Term, Variable},
+    sparql::QuerySolution,
+};
+use ratatui::{
+    backend::TestBackend,
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+    Terminal,
+};
+use rdf_tui::{action::Action, app::App, ResultTable};
+use std::{convert::Infallible, path::PathBuf, sync::Arc};
+
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 24;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// 載入 `animals.ttl` 並執行查詢之應用。
+fn app_with(query: &str) -> App {
+    let mut app = App::new().unwrap();
+    app.open(fixture("animals.ttl"));
+    app.set_query(query);
+    app.handle_action(Action::RunQuery).unwrap();
+    app.wait_for_query();
+    app
+}
+
+fn draw(app: &mut App) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    app.draw(&mut terminal).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// 畫面各行，去除行尾空白。
+fn lines(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+fn find(lines: &[String], text: &str) -> Option<usize> {
+    lines.iter().position(|line| line.contains(text))
+}
+
+/// 第 `y` 行中 `text` 首字符之單元格。
+fn cell_at<'a>(buffer: &'a Buffer, lines: &[String], y: usize, text: &str) -> &'a Cell {
+    let x = lines[y][..lines[y].find(text).unwrap()].chars().count();
+    buffer.get(x as u16, y as u16)
+}
+
+#[test]
+fn table_shows_header_and_rows() {
+    let mut app = app_with(
+        "PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#> \
+         SELECT ?class ?label WHERE { ?class rdfs:label ?label } ORDER BY ?label",
+    );
+    let buffer = draw(&mut app);
+    let lines = lines(&buffer);
+
+    let header = find(&lines, "│ ?class").expect("header");
+    assert!(lines[header].contains("?label"));
+    let header_cell = cell_at(&buffer, &lines, header, "?class");
+    assert!(header_cell.modifier.contains(Modifier::BOLD));
+    assert!(header_cell.modifier.contains(Modifier::UNDERLINED));
+
+    let animal = find(&lines, "<http://example.org/Animal>").expect("first row");
+    let cat = find(&lines, "<http://example.org/Cat>").expect("second row");
+    let dog = find(&lines, "<http://example.org/Dog>").expect("third row");
+    assert!(header < animal && animal < cat && cat < dog);
+    assert!(lines[animal].contains("\"Animal\"@en"));
+    assert!(lines[cat].contains("\"Cat\"@en"));
+}
+
+#[test]
+fn focused_pane_has_the_focused_border() {
+    let mut app = app_with("SELECT * WHERE { ?s ?p ?o }");
+    let border = |buffer: &Buffer, title: &str| {
+        let lines = lines(buffer);
+        let y = find(&lines, title).unwrap();
+        buffer.get(0, y as u16).fg
+    };
+
+    let buffer = draw(&mut app);
+    assert_eq!(border(&buffer, "Explore"), Color::Green);
+    assert_eq!(border(&buffer, "Query"), Color::Reset);
+
+    app.handle_action(Action::SwitchMode).unwrap();
+    let buffer = draw(&mut app);
+    assert_eq!(border(&buffer, "Explore"), Color::Reset);
+    assert_eq!(border(&buffer, "Query"), Color::Green);
+}
+
+#[test]
+fn non_select_query_shows_no_result() {
+    let mut app = app_with("ASK { ?s ?p ?o }");
+    let lines = lines(&draw(&mut app));
+    assert!(find(&lines, "NO RESULT").is_some(), "{lines:#?}");
+}
+
+#[test]
+fn query_error_is_shown_in_the_table_pane() {
+    let mut app = app_with("SELECT WHERE");
+    let buffer = draw(&mut app);
+    let lines = lines(&buffer);
+    let y = find(&lines, "error").expect("error message");
+    assert_eq!(cell_at(&buffer, &lines, y, "error").fg, Color::Red);
+}
+
+#[test]
+fn load_error_names_the_file() {
+    let mut app = App::new().unwrap();
+    app.open(fixture("broken.ttl"));
+    let lines = lines(&draw(&mut app));
+    assert!(find(&lines, "Fail to load").is_some(), "{lines:#?}");
+    assert!(find(&lines, "broken.ttl").is_some());
+    assert!(find(&lines, "press r to reload").is_some());
+}
+
+#[test]
+fn scrolling_keeps_the_selected_row_visible() {
+    let variables: Arc<[Variable]> = [Variable::new_unchecked("n")].into();
+    let solutions = (0..10_000).map(|n| {
+        let term = Term::from(Literal::from(n));
+        Ok::<_, Infallible>(QuerySolution::from((
+            Arc::clone(&variables),
+            vec![Some(term)],
+        )))
+    });
+    let table = ResultTable::from_solutions(variables.to_vec(), solutions, None).unwrap();
+    let mut app = App::new().unwrap();
+    app.show_table("SELECT ?n {}", table);
+    let row = |lines: &[String], n: i32| find(lines, &format!("\"{n}\"^^"));
+
+    app.handle_action(Action::ScrollRows(9_000)).unwrap();
+    let screen = lines(&draw(&mut app));
+    let selected = row(&screen, 9_000).expect("selected row");
+    assert!(row(&screen, 8_999).is_some_and(|above| above < selected));
+    assert!(row(&screen, 0).is_none());
+
+    // 回滾時所選行留於頂部
+    app.handle_action(Action::ScrollRows(-100)).unwrap();
+    let screen = lines(&draw(&mut app));
+    let header = find(&screen, "│ ?n").unwrap();
+    assert_eq!(row(&screen, 8_900), Some(header + 1));
+}
+
+#[test]
+fn wide_terminals_center_the_table_beside_a_preview() {
+    let mut app = app_with(
+        "PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#> \
+         SELECT ?class ?label WHERE { ?class rdfs:label ?label } ORDER BY ?label",
+    );
+    let mut terminal = Terminal::new(TestBackend::new(320, HEIGHT)).unwrap();
+    app.draw(&mut terminal).unwrap();
+    let lines = lines(terminal.backend().buffer());
+
+    let header = find(&lines, "<http://example.org/Animal>").expect("first row") - 1;
+    let indent = lines[header].find("?class").unwrap();
+    assert!(indent > 20, "table is centered: {:?}", lines[header]);
+    // 預覽在表之右，示所選之格
+    assert!(lines[header].find("Preview").unwrap() > indent);
+    assert!(lines.iter().any(|line| line.contains("│ Animal")));
+
+    // 常寬之終端如舊
+    let lines = self::lines(&draw(&mut app));
+    assert!(find(&lines, "Preview").is_none());
+    assert!(find(&lines, "│ ?class").is_some());
+}
+
+/// `columns` 列、二行之結果，出自 `VALUES`。
+fn wide_result(columns: usize) -> App {
+    let variables: Vec<String> = (1..=columns).map(|n| format!("?v{n}")).collect();
+    let row = |base: usize| {
+        (1..=columns)
+            .map(|n| format!("\"value {}\"", base + n))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let variables = variables.join(" ");
+    app_with(&format!(
+        "SELECT {variables} {{ VALUES ({variables}) {{ ({}) ({}) }} }}",
+        row(0),
+        row(100)
+    ))
+}
+
+fn draw_at(app: &mut App, width: u16) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, HEIGHT)).unwrap();
+    app.draw(&mut terminal).unwrap();
+    lines(terminal.backend().buffer())
+}
+
+#[test]
+fn wide_results_show_as_many_readable_columns_as_fit() {
+    for (columns, width, indicator) in [
+        (1, 40, None),
+        (1, 200, None),
+        (5, 40, Some(" 4/5 columns")),
+        (5, 200, None),
+        (50, 40, Some(" 4/50 columns")),
+        (50, 200, Some(" 17/50 columns")),
+    ] {
+        let mut app = wide_result(columns);
+        let screen = draw_at(&mut app, width);
+        // 查詢折行亦含 `?v1`，故取結果表之標題下一行
+        let header = 1 + screen
+            .iter()
+            .position(|line| line.contains("Explore"))
+            .unwrap_or_else(|| panic!("{screen:#?}"));
+        assert!(screen[header].contains("?v1"), "{screen:#?}");
+        match indicator {
+            Some(indicator) => assert!(find(&screen, indicator).is_some(), "{screen:#?}"),
+            None => assert!(find(&screen, " columns (t").is_none(), "{screen:#?}"),
+        }
+        // 所示之列皆可讀其值
+        let shown = screen[header].matches("?v").count();
+        assert!(
+            screen[header + 1].matches("\"value").count() == shown,
+            "{columns} columns at {width}: {screen:#?}"
+        );
+    }
+
+    // 所示之段隨所選之列移
+    let mut app = wide_result(50);
+    for _ in 0..50 {
+        app.handle_action(Action::ScrollColumns(1)).unwrap();
+    }
+    let screen = draw_at(&mut app, 40);
+    let header = find(&screen, "?v50 ").expect("last column shown");
+    assert!(!screen[header].contains("?v1 "), "{screen:#?}");
+
+    // 轉置則逐變量一行
+    app.handle_action(Action::ToggleTransposed).unwrap();
+    let screen = draw_at(&mut app, 40);
+    assert!(find(&screen, "row 1 of 2").is_some(), "{screen:#?}");
+    let line = find(&screen, "?v50 ").expect("selected variable shown");
+    assert!(screen[line].contains("value 50"), "{screen:#?}");
+    app.handle_action(Action::ScrollRows(1)).unwrap();
+    let screen = draw_at(&mut app, 40);
+    assert!(find(&screen, "value 150").is_some(), "{screen:#?}");
+}
+
+#[test]
+fn bidirectional_text_keeps_the_columns_in_place() {
+    let mut app = app_with(
+        "SELECT ?o ?p ?s { VALUES (?o ?p ?s) { \
+         (\"invoice\u{202E}fdp.exe\u{202C}\" \"x\" <http://ex/s1>) \
+         (\"שלום עולם 42\" \"y\" <http://ex/s2>) \
+         (\"مرحبا \u{2067}بالعالم\u{2069}\" \"z\" <http://ex/s3>) \
+         (\"plain\" \"w\" <http://ex/s4>) } }",
+    );
+    let buffer = draw(&mut app);
+    let lines = lines(&buffer);
+    let column = |y: usize, text: &str| {
+        let line = &lines[y];
+        line[..line
+            .find(text)
+            .unwrap_or_else(|| panic!("{text}: {lines:#?}"))]
+            .chars()
+            .count()
+    };
+    let header = find(&lines, "│ ?o").expect("header");
+    for (value, s) in [
+        ("\"x\"", "s1"),
+        ("\"y\"", "s2"),
+        ("\"z\"", "s3"),
+        ("\"w\"", "s4"),
+    ] {
+        let row = header + find(&lines[header..], &format!("<http://ex/{s}>")).expect("row");
+        assert_eq!(column(row, value), column(header, "?p"), "{lines:#?}");
+        assert_eq!(column(row, "<http"), column(header, "?s"), "{lines:#?}");
+    }
+    // 控制符已去而註之，右至左之文字按視覺順序
+    assert!(
+        lines[header + 1].contains("\"invoicefdp.exe\"⇄"),
+        "{lines:#?}"
+    );
+    assert!(
+        lines[header + 2].contains("\"42 םלוע םולש\" "),
+        "{lines:#?}"
+    );
+    assert!(lines[header + 3].contains("\"ملاعلاب ابحرم\"⇄"), "{lines:#?}");
+    assert!(buffer.content.iter().all(|cell| !cell
+        .symbol()
+        .contains(['\u{202E}', '\u{202C}', '\u{2067}', '\u{2069}'])));
+}